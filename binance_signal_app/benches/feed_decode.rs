@@ -0,0 +1,128 @@
+use binance_signal_app::core::{
+    orderbook::{parse_decimal, OrderbookSnapshot, Side},
+    trade::{Trade, TradeSide},
+};
+use binance_signal_app::ws::{binance::DepthUpdate, binance_trades::parse_trade, parse_f64};
+use chrono::DateTime;
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::{hint::black_box, str::FromStr};
+
+// Cách decode cũ: mỗi level cấp phát hai String rồi mới parse
+#[derive(Deserialize)]
+struct OwnedDepthUpdate {
+    #[serde(rename = "lastUpdateId")]
+    last_update_id: Option<u64>,
+    bids: Vec<[String; 2]>,
+    asks: Vec<[String; 2]>,
+}
+
+#[derive(Deserialize)]
+struct OwnedTradeEvent {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "a")]
+    id: u64,
+    #[serde(rename = "p")]
+    price: String,
+    #[serde(rename = "q")]
+    qty: String,
+    #[serde(rename = "T")]
+    trade_time: i64,
+    #[serde(rename = "m")]
+    buyer_is_maker: bool,
+}
+
+// partial depth 20 như `@depth20@100ms`
+fn depth_message(levels: usize) -> String {
+    let side = |base: f64, step: f64| {
+        (0..levels)
+            .map(|i| format!(r#"["{:.2}","{:.5}"]"#, base + step * i as f64, 0.1 + i as f64 * 0.37))
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    format!(r#"{{"lastUpdateId":160000000,"bids":[{}],"asks":[{}]}}"#, side(30000.0, -0.01), side(30000.01, 0.01))
+}
+
+const AGG_TRADE: &str = r#"{"e":"aggTrade","E":1700000000123,"s":"BTCUSDT","a":26129,"p":"30000.01","q":"0.01200000","f":100,"l":105,"T":1700000000120,"m":true,"M":true}"#;
+
+fn bench_depth_decode(c: &mut Criterion) {
+    let raw = depth_message(20);
+    let mut group = c.benchmark_group("depth20_decode_apply");
+    group.throughput(Throughput::Bytes(raw.len() as u64));
+    let mut ob = OrderbookSnapshot::new();
+
+    group.bench_function("owned_strings", |b| {
+        b.iter(|| {
+            let data: OwnedDepthUpdate = serde_json::from_str(black_box(&raw)).unwrap();
+            ob.clear();
+            for [price, qty] in &data.bids {
+                ob.set_level_str(Side::Bid, price, qty);
+            }
+            for [price, qty] in &data.asks {
+                ob.set_level_str(Side::Ask, price, qty);
+            }
+            ob.last_update_id = data.last_update_id.unwrap_or(0);
+        })
+    });
+
+    group.bench_function("borrowed", |b| {
+        b.iter(|| {
+            let data: DepthUpdate = serde_json::from_str(black_box(&raw)).unwrap();
+            data.apply_to(&mut ob);
+        })
+    });
+
+    group.finish();
+}
+
+fn bench_trade_decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("agg_trade_decode");
+    group.throughput(Throughput::Bytes(AGG_TRADE.len() as u64));
+
+    group.bench_function("owned_strings", |b| {
+        b.iter(|| {
+            let ev: OwnedTradeEvent = serde_json::from_str(black_box(AGG_TRADE)).unwrap();
+            Trade {
+                symbol: ev.symbol.to_lowercase(),
+                trade_id: ev.id,
+                price: ev.price.parse().unwrap(),
+                qty: ev.qty.parse().unwrap(),
+                side: if ev.buyer_is_maker { TradeSide::Sell } else { TradeSide::Buy },
+                timestamp: DateTime::from_timestamp_millis(ev.trade_time).unwrap_or_default(),
+            }
+        })
+    });
+
+    group.bench_function("borrowed", |b| b.iter(|| parse_trade(black_box(AGG_TRADE)).unwrap()));
+
+    group.finish();
+}
+
+const NUMBERS: [&str; 6] = ["30000.01000000", "0.00123000", "1.50000000", "65432.1", "0.1", "12.34567891"];
+
+// parser chuẩn so với parser hot path (`parse_decimal` / `parse_f64`), đo trước / sau
+// feature `fast-float`: cargo bench --bench feed_decode [--no-default-features]
+fn bench_number_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("number_parse");
+    group.throughput(Throughput::Elements(NUMBERS.len() as u64));
+
+    group.bench_function("decimal_std", |b| {
+        b.iter(|| NUMBERS.map(|s| Decimal::from_str(black_box(s)).unwrap()))
+    });
+    group.bench_function("decimal", |b| {
+        b.iter(|| NUMBERS.map(|s| parse_decimal(black_box(s)).unwrap()))
+    });
+    group.bench_function("f64_std", |b| {
+        b.iter(|| NUMBERS.map(|s| black_box(s).parse::<f64>().unwrap()))
+    });
+    group.bench_function("f64", |b| {
+        b.iter(|| NUMBERS.map(|s| parse_f64("bench", black_box(s)).unwrap()))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_depth_decode, bench_trade_decode, bench_number_parse);
+criterion_main!(benches);
@@ -0,0 +1,134 @@
+use binance_signal_app::core::orderbook::{OrderbookSnapshot, SharedOrderbook, Side};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use rust_decimal::Decimal;
+use std::hint::black_box;
+
+// Các thao tác chính của book để bắt regression khi đổi cấu trúc book.
+// Đọc best price khi có writer chạy song song nằm ở `orderbook_read.rs`
+
+const DEPTHS: [usize; 3] = [20, 100, 1000];
+
+// giá theo tick 0.01 quanh 30000, qty khác nhau theo level
+fn levels(side: Side, depth: usize, qty_seed: usize) -> Vec<[String; 2]> {
+    (0..depth)
+        .map(|i| {
+            let ticks = match side {
+                Side::Bid => 3_000_000 - i as i64,
+                Side::Ask => 3_000_001 + i as i64,
+            };
+            let qty = Decimal::new(((i + qty_seed) % 97 + 1) as i64 * 1000, 5);
+            [Decimal::new(ticks, 2).to_string(), qty.to_string()]
+        })
+        .collect()
+}
+
+fn borrowed(levels: &[[String; 2]]) -> Vec<[&str; 2]> {
+    levels.iter().map(|[p, q]| [p.as_str(), q.as_str()]).collect()
+}
+
+fn book(depth: usize) -> OrderbookSnapshot {
+    let (bids, asks) = (levels(Side::Bid, depth, 0), levels(Side::Ask, depth, 0));
+    let mut ob = OrderbookSnapshot::new();
+    ob.replace_levels_str(Side::Bid, &borrowed(&bids));
+    ob.replace_levels_str(Side::Ask, &borrowed(&asks));
+    ob
+}
+
+// Diff kiểu `@depth@100ms`: đổi qty vài level gần top, xoá một level, thêm một level ngoài cùng
+fn diff(depth: usize, round: usize) -> Vec<[String; 2]> {
+    let mut out: Vec<[String; 2]> = levels(Side::Bid, 8, round + 1);
+    out.push([Decimal::new(3_000_000 - 3, 2).to_string(), "0".to_string()]);
+    out.push([Decimal::new(3_000_000 - depth as i64, 2).to_string(), "1.5".to_string()]);
+    out
+}
+
+fn bench_snapshot_apply(c: &mut Criterion) {
+    let mut group = c.benchmark_group("snapshot_apply");
+    for depth in DEPTHS {
+        let (bids, asks) = (levels(Side::Bid, depth, 0), levels(Side::Ask, depth, 3));
+        let (bids, asks) = (borrowed(&bids), borrowed(&asks));
+        group.throughput(Throughput::Elements(2 * depth as u64));
+
+        let mut ob = OrderbookSnapshot::new();
+        group.bench_with_input(BenchmarkId::new("snapshot", depth), &depth, |b, _| {
+            b.iter(|| {
+                ob.replace_levels_str(Side::Bid, black_box(&bids));
+                ob.replace_levels_str(Side::Ask, black_box(&asks));
+            })
+        });
+
+        // gồm cả clone + publish cho reader
+        let shared = SharedOrderbook::new();
+        let _rx = shared.subscribe();
+        group.bench_with_input(BenchmarkId::new("shared", depth), &depth, |b, _| {
+            b.iter(|| {
+                shared.update(|ob| {
+                    ob.replace_levels_str(Side::Bid, &bids);
+                    ob.replace_levels_str(Side::Ask, &asks);
+                    true
+                })
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_diff_apply(c: &mut Criterion) {
+    let mut group = c.benchmark_group("diff_apply");
+    for depth in DEPTHS {
+        let diffs: Vec<_> = (0..4).map(|round| diff(depth, round)).collect();
+        let diffs: Vec<_> = diffs.iter().map(|d| borrowed(d)).collect();
+        group.throughput(Throughput::Elements(diffs[0].len() as u64));
+
+        let base = book(depth);
+        group.bench_with_input(BenchmarkId::new("snapshot", depth), &depth, |b, _| {
+            let mut round = 0;
+            b.iter_batched_ref(
+                || base.clone(),
+                |ob| {
+                    for [price, qty] in &diffs[round % diffs.len()] {
+                        ob.set_level_str(Side::Bid, price, qty);
+                    }
+                    round += 1;
+                },
+                BatchSize::SmallInput,
+            )
+        });
+
+        // book trong SharedOrderbook có subscriber event: thêm clone + tính BookDiff mỗi lần
+        let shared = SharedOrderbook::new();
+        shared.update(|ob| {
+            *ob = base.clone();
+            true
+        });
+        let _rx = shared.subscribe_events();
+        group.bench_with_input(BenchmarkId::new("shared_with_events", depth), &depth, |b, _| {
+            let mut round = 0;
+            b.iter(|| {
+                shared.update(|ob| {
+                    for [price, qty] in &diffs[round % diffs.len()] {
+                        ob.set_level_str(Side::Bid, price, qty);
+                    }
+                    true
+                });
+                round += 1;
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_vwap(c: &mut Criterion) {
+    let mut group = c.benchmark_group("vwap_for_size");
+    let ob = book(1000);
+    // ăn 1 level, ~30 level, ~100 level
+    for size in [Decimal::new(1, 2), Decimal::new(5, 0), Decimal::new(50, 0)] {
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, size| {
+            b.iter(|| ob.vwap_for_size(Side::Ask, black_box(*size)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_snapshot_apply, bench_diff_apply, bench_vwap);
+criterion_main!(benches);
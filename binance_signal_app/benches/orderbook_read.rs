@@ -0,0 +1,76 @@
+use binance_signal_app::core::orderbook::{OrderbookSnapshot, SharedOrderbook, Side};
+use rust_decimal::Decimal;
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::thread;
+
+fn seed(ob: &mut OrderbookSnapshot, tick: u64) {
+    ob.clear();
+    for i in 0..20 {
+        let offset = Decimal::new(i, 2);
+        ob.set_level(Side::Bid, Decimal::new(10000, 2) - offset, Decimal::from(1 + tick % 7));
+        ob.set_level(Side::Ask, Decimal::new(10001, 2) + offset, Decimal::from(1 + tick % 5));
+    }
+}
+
+// Writer chạy liên tục ở thread khác để đo độ trễ đọc khi có contention
+fn bench_best_price_under_writes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("best_price_read_under_writes");
+
+    {
+        let book = Arc::new(tokio::sync::Mutex::new(OrderbookSnapshot::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let writer = {
+            let book = book.clone();
+            let stop = stop.clone();
+            thread::spawn(move || {
+                let mut tick = 0;
+                while !stop.load(Ordering::Relaxed) {
+                    seed(&mut book.blocking_lock(), tick);
+                    tick += 1;
+                }
+            })
+        };
+
+        group.bench_function("tokio_mutex", |b| {
+            b.iter(|| book.blocking_lock().best_bid_ask())
+        });
+
+        stop.store(true, Ordering::Relaxed);
+        writer.join().unwrap();
+    }
+
+    {
+        let book = Arc::new(SharedOrderbook::new());
+        let stop = Arc::new(AtomicBool::new(false));
+        let writer = {
+            let book = book.clone();
+            let stop = stop.clone();
+            thread::spawn(move || {
+                let mut tick = 0;
+                while !stop.load(Ordering::Relaxed) {
+                    book.update(|ob| {
+                        seed(ob, tick);
+                        true
+                    });
+                    tick += 1;
+                }
+            })
+        };
+
+        group.bench_function("arc_swap", |b| b.iter(|| book.best_bid_ask()));
+        // 5 level đầu đã tính sẵn lúc publish
+        group.bench_function("top_n_5", |b| b.iter(|| book.top_n::<5>()));
+
+        stop.store(true, Ordering::Relaxed);
+        writer.join().unwrap();
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_best_price_under_writes);
+criterion_main!(benches);
@@ -0,0 +1,11 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let fds = protox::compile(["proto/market_data.proto"], ["proto"])?;
+    tonic_build::configure().build_client(true).compile_fds(fds)?;
+    // message push của MEXC, chỉ cần khi bật feature `mexc`
+    if std::env::var_os("CARGO_FEATURE_MEXC").is_some() {
+        let fds = protox::compile(["proto/mexc.proto"], ["proto"])?;
+        tonic_build::configure().build_client(false).build_server(false).compile_fds(fds)?;
+    }
+    println!("cargo:rerun-if-changed=proto");
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+use crate::net;
+
+// Discord cắt content quá 2000 ký tự
+const MAX_CONTENT_CHARS: usize = 2000;
+
+// webhook_url nên để ở BSA_ALERTS__DISCORD__WEBHOOK_URL thay vì trong file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DiscordConfig {
+    pub enabled: bool,
+    pub webhook_url: String,
+    // tên hiển thị thay cho tên mặc định của webhook
+    pub username: Option<String>,
+    // Discord cho ~30 request / phút mỗi webhook
+    pub max_per_minute: u32,
+}
+
+impl Default for DiscordConfig {
+    fn default() -> Self {
+        Self { enabled: false, webhook_url: String::new(), username: None, max_per_minute: 30 }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct WebhookMessage<'a> {
+    content: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    username: Option<&'a str>,
+}
+
+// Gửi tin qua Discord webhook
+#[derive(Debug, Clone)]
+pub struct DiscordClient {
+    client: reqwest::Client,
+    config: DiscordConfig,
+}
+
+impl DiscordClient {
+    pub fn new(config: DiscordConfig) -> Self {
+        Self { client: net::http_client(), config }
+    }
+
+    pub async fn send(&self, text: &str) -> Result<(), reqwest::Error> {
+        let content = match text.char_indices().nth(MAX_CONTENT_CHARS) {
+            Some((idx, _)) => &text[..idx],
+            None => text,
+        };
+        let body = WebhookMessage { content, username: self.config.username.as_deref() };
+        self.client.post(&self.config.webhook_url).json(&body).send().await?.error_for_status()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,333 @@
+pub mod discord;
+pub mod notify;
+pub mod telegram;
+
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::info;
+
+use crate::core::{backpressure::LosslessReceiver, order::Fill, orderbook::OrderbookSnapshot};
+use crate::hub::MarketHub;
+use crate::supervisor::Shutdown;
+use discord::DiscordConfig;
+use notify::{AlertNotifier, Templates};
+use telegram::TelegramConfig;
+
+fn default_levels() -> usize {
+    5
+}
+
+// Điều kiện của rule, khai báo phẳng cùng các field khác:
+// kind = "spread_above", bps = 20
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Condition {
+    SpreadAbove { bps: Decimal },
+    // tổng qty `levels` level đầu phía bid / ask
+    BidDepthBelow {
+        qty: Decimal,
+        #[serde(default = "default_levels")]
+        levels: usize,
+    },
+    AskDepthBelow {
+        qty: Decimal,
+        #[serde(default = "default_levels")]
+        levels: usize,
+    },
+    // mid đi qua `level` theo bất kỳ chiều nào
+    PriceCross { level: Decimal },
+    // book không update quá `secs`
+    Stale { secs: u64 },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AlertRule {
+    pub name: String,
+    // None = mọi feed, không phân biệt hoa thường
+    pub symbol: Option<String>,
+    pub exchange: Option<String>,
+    #[serde(flatten)]
+    pub condition: Condition,
+    // không bắn lại rule trong khoảng này dù điều kiện bật / tắt liên tục
+    #[serde(default)]
+    pub cooldown_secs: u64,
+}
+
+impl AlertRule {
+    fn matches(&self, exchange: &str, symbol: &str) -> bool {
+        self.symbol.as_ref().is_none_or(|s| s.eq_ignore_ascii_case(symbol))
+            && self.exchange.as_ref().is_none_or(|e| e == exchange)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let ok = match &self.condition {
+            Condition::SpreadAbove { bps } => *bps > Decimal::ZERO,
+            Condition::BidDepthBelow { qty, levels } | Condition::AskDepthBelow { qty, levels } => {
+                *qty > Decimal::ZERO && *levels > 0
+            }
+            Condition::PriceCross { level } => *level > Decimal::ZERO,
+            Condition::Stale { secs } => *secs > 0,
+        };
+        if self.name.trim().is_empty() {
+            Err("alert rule name must not be empty".to_string())
+        } else if !ok {
+            Err(format!("alert rule {:?}: threshold must be > 0", self.name))
+        } else {
+            Ok(())
+        }
+    }
+
+    // (điều kiện đúng, giá trị quan sát); None = chưa đủ data để đánh giá
+    fn observe(&self, snap: &OrderbookSnapshot, now: DateTime<Utc>) -> Option<(bool, Decimal)> {
+        match &self.condition {
+            Condition::SpreadAbove { bps } => snap.spread_bps().map(|s| (s > *bps, s)),
+            Condition::BidDepthBelow { qty, levels } => {
+                let depth: Decimal = snap.bids.values().rev().take(*levels).sum();
+                Some((depth < *qty, depth))
+            }
+            Condition::AskDepthBelow { qty, levels } => {
+                let depth: Decimal = snap.asks.values().take(*levels).sum();
+                Some((depth < *qty, depth))
+            }
+            // `true` ở đây = mid đang ở trên level, engine so với lần trước
+            Condition::PriceCross { level } => snap.mid_price().map(|m| (m >= *level, m)),
+            Condition::Stale { secs } => {
+                let age = now - snap.timestamp;
+                Some((age > Duration::seconds(*secs as i64), Decimal::from(age.num_milliseconds()) / Decimal::ONE_THOUSAND))
+            }
+        }
+    }
+
+    fn describe(&self, value: Decimal) -> String {
+        let value = value.round_dp(6).normalize();
+        match &self.condition {
+            Condition::SpreadAbove { bps } => format!("spread {} bps > {} bps", value, bps),
+            Condition::BidDepthBelow { qty, levels } => format!("bid depth {} < {} (top {})", value, qty, levels),
+            Condition::AskDepthBelow { qty, levels } => format!("ask depth {} < {} (top {})", value, qty, levels),
+            Condition::PriceCross { level } => format!("mid {} crossed {}", value, level),
+            Condition::Stale { secs } => format!("no update for {}s (> {}s)", value, secs),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AlertEvent {
+    pub rule: String,
+    pub exchange: String,
+    pub symbol: String,
+    pub value: Decimal,
+    pub message: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct RuleState {
+    // điều kiện lần đánh giá trước
+    active: Option<bool>,
+    last_fired: Option<DateTime<Utc>>,
+}
+
+// Đánh giá rule trên snapshot mới nhất của từng feed. Rule chỉ bắn khi điều kiện
+// chuyển từ sai sang đúng (price_cross: khi mid đổi phía), tránh lặp mỗi tick
+#[derive(Debug, Clone)]
+pub struct AlertEngine {
+    rules: Vec<AlertRule>,
+    // (index rule, exchange, symbol)
+    state: HashMap<(usize, String, String), RuleState>,
+}
+
+impl AlertEngine {
+    pub fn new(rules: Vec<AlertRule>) -> Self {
+        Self { rules, state: HashMap::new() }
+    }
+
+    pub fn evaluate(&mut self, exchange: &str, symbol: &str, snap: &OrderbookSnapshot, now: DateTime<Utc>) -> Vec<AlertEvent> {
+        let mut events = Vec::new();
+        for (idx, rule) in self.rules.iter().enumerate() {
+            if !rule.matches(exchange, symbol) {
+                continue;
+            }
+            let Some((active, value)) = rule.observe(snap, now) else { continue };
+            let state = self.state.entry((idx, exchange.to_string(), symbol.to_string())).or_default();
+            let prev = state.active.replace(active);
+            let triggered = match rule.condition {
+                Condition::PriceCross { .. } => prev.is_some_and(|p| p != active),
+                _ => active && prev != Some(true),
+            };
+            let cooled = state.last_fired.is_none_or(|t| now - t >= Duration::seconds(rule.cooldown_secs as i64));
+            if triggered && cooled {
+                state.last_fired = Some(now);
+                events.push(AlertEvent {
+                    rule: rule.name.clone(),
+                    exchange: exchange.to_string(),
+                    symbol: symbol.to_uppercase(),
+                    value,
+                    message: rule.describe(value),
+                    timestamp: now,
+                });
+            }
+        }
+        events
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AlertSettings {
+    pub enabled: bool,
+    pub eval_interval_ms: u64,
+    pub rules: Vec<AlertRule>,
+    // ghi event ra log (tracing)
+    pub log: bool,
+    // POST JSON `AlertEvent` tới URL này
+    pub webhook: Option<String>,
+    pub telegram: TelegramConfig,
+    pub discord: DiscordConfig,
+    pub templates: Templates,
+    // gửi fill của `market-make` qua Telegram / Discord
+    pub fills: bool,
+}
+
+impl Default for AlertSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            eval_interval_ms: 250,
+            rules: Vec::new(),
+            log: true,
+            webhook: None,
+            telegram: TelegramConfig::default(),
+            discord: DiscordConfig::default(),
+            templates: Templates::default(),
+            fills: false,
+        }
+    }
+}
+
+impl AlertSettings {
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors: Vec<String> = self.rules.iter().filter_map(|r| r.validate().err()).collect();
+        if self.enabled && self.rules.is_empty() && !self.fills {
+            errors.push("`alerts.enabled` requires at least one `[[alerts.rules]]` or `fills = true`".to_string());
+        }
+        if self.eval_interval_ms == 0 {
+            errors.push("`alerts.eval_interval_ms` must be > 0".to_string());
+        }
+        let tg = &self.telegram;
+        if tg.enabled && (tg.bot_token.is_empty() || tg.chat_id.is_empty()) {
+            errors.push("`alerts.telegram` requires bot_token and chat_id".to_string());
+        }
+        if self.discord.enabled && self.discord.webhook_url.is_empty() {
+            errors.push("`alerts.discord` requires webhook_url".to_string());
+        }
+        errors
+    }
+}
+
+async fn next_fill(fills: &mut Option<LosslessReceiver<Fill>>) -> Option<Fill> {
+    let Some(rx) = fills else {
+        return std::future::pending().await;
+    };
+    rx.recv().await
+}
+
+// Đánh giá mọi feed theo chu kỳ (stale chỉ phát hiện được bằng timer, không theo update).
+// `fills` = Some khi chạy cùng venue và bật `alerts.fills`
+pub async fn run(settings: AlertSettings, hub: MarketHub, mut fills: Option<LosslessReceiver<Fill>>, mut shutdown: Shutdown) {
+    let mut engine = AlertEngine::new(settings.rules.clone());
+    let mut notifier = AlertNotifier::new(&settings);
+    let mut interval = tokio::time::interval(std::time::Duration::from_millis(settings.eval_interval_ms));
+    let mut fired = 0u64;
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            fill = next_fill(&mut fills) => {
+                match fill {
+                    Some(fill) => notifier.notify_fill(&fill).await,
+                    None => fills = None,
+                }
+                continue;
+            }
+            _ = shutdown.wait() => break,
+        }
+        let now = Utc::now();
+        for feed in hub.feeds() {
+            for event in engine.evaluate(feed.exchange(), feed.symbol(), &feed.snapshot(), now) {
+                fired += 1;
+                notifier.notify(&event).await;
+            }
+        }
+    }
+    info!(fired, "alerts stopped");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::orderbook::Side;
+    use figment::{
+        providers::{Format, Toml},
+        Figment,
+    };
+    use rust_decimal_macros::dec;
+
+    fn book(ts: DateTime<Utc>, bid: Decimal, bid_qty: Decimal, ask: Decimal) -> OrderbookSnapshot {
+        let mut snap = OrderbookSnapshot::new();
+        snap.set_level(Side::Bid, bid, bid_qty);
+        snap.set_level(Side::Ask, ask, dec!(1));
+        snap.timestamp = ts;
+        snap
+    }
+
+    #[test]
+    fn test_rules_fire_on_transition() {
+        let settings: AlertSettings = Figment::from(Toml::string(
+            r#"
+            enabled = true
+            [[rules]]
+            name = "wide"
+            symbol = "BTCUSDT"
+            kind = "spread_above"
+            bps = 20
+            [[rules]]
+            name = "thin bid"
+            kind = "bid_depth_below"
+            qty = 2
+            [[rules]]
+            name = "cross 100"
+            kind = "price_cross"
+            level = 100
+            [[rules]]
+            name = "stale"
+            kind = "stale"
+            secs = 5
+            cooldown_secs = 60
+            "#,
+        ))
+        .extract()
+        .unwrap();
+        assert!(settings.validate().is_empty());
+        assert_eq!(settings.rules[1].condition, Condition::BidDepthBelow { qty: dec!(2), levels: 5 });
+
+        let mut engine = AlertEngine::new(settings.rules);
+        let t0 = Utc::now();
+        let names = |events: Vec<AlertEvent>| events.into_iter().map(|e| e.rule).collect::<Vec<_>>();
+
+        // spread 10 bps, depth 3, mid 99.95 dưới 100: chưa có gì
+        assert!(engine.evaluate("binance", "btcusdt", &book(t0, dec!(99.9), dec!(3), dec!(100)), t0).is_empty());
+        // spread ~30 bps, depth 1, mid 100.05 -> wide + thin + cross
+        let wide = book(t0, dec!(99.9), dec!(1), dec!(100.2));
+        assert_eq!(names(engine.evaluate("binance", "btcusdt", &wide, t0)), ["wide", "thin bid", "cross 100"]);
+        // điều kiện vẫn đúng: không bắn lại
+        assert!(engine.evaluate("binance", "btcusdt", &wide, t0).is_empty());
+        // symbol khác không khớp rule "wide"
+        assert_eq!(names(engine.evaluate("binance", "ethusdt", &wide, t0)), ["thin bid"]);
+
+        let t1 = t0 + Duration::seconds(10);
+        let events = engine.evaluate("binance", "btcusdt", &wide, t1);
+        assert_eq!(names(events.clone()), ["stale"]);
+        assert_eq!(events[0].message, "no update for 10s (> 5s)");
+    }
+}
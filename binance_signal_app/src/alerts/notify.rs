@@ -0,0 +1,233 @@
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use super::{discord::DiscordClient, telegram::TelegramClient, AlertEvent, AlertSettings};
+use crate::core::order::{Fill, OrderSide};
+use crate::net;
+
+const MINUTE: Duration = Duration::from_secs(60);
+
+// Mẫu tin nhắn cho Telegram / Discord, `{field}` được thay bằng giá trị:
+// alert: rule, exchange, symbol, message, value, time
+// fill: symbol, side, qty, price, notional, fee, role (maker/taker), order_id, time
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Templates {
+    pub alert: String,
+    pub fill: String,
+}
+
+impl Default for Templates {
+    fn default() -> Self {
+        Self {
+            alert: "🚨 [{rule}] {exchange}:{symbol} {message}".to_string(),
+            fill: "✅ {side} {qty} {symbol} @ {price} ({role}, fee {fee})".to_string(),
+        }
+    }
+}
+
+// Thay `{key}`, key không có trong `vars` giữ nguyên
+pub fn render(template: &str, vars: &[(&str, String)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        let value = tail.find('}').and_then(|end| vars.iter().find(|(k, _)| *k == &tail[1..end]).map(|(_, v)| (end, v)));
+        match value {
+            Some((end, v)) => {
+                out.push_str(v);
+                rest = &tail[end + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = &tail[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+pub fn alert_vars(event: &AlertEvent) -> Vec<(&'static str, String)> {
+    vec![
+        ("rule", event.rule.clone()),
+        ("exchange", event.exchange.clone()),
+        ("symbol", event.symbol.clone()),
+        ("message", event.message.clone()),
+        ("value", event.value.to_string()),
+        ("time", event.timestamp.format("%H:%M:%S").to_string()),
+    ]
+}
+
+pub fn fill_vars(fill: &Fill) -> Vec<(&'static str, String)> {
+    vec![
+        ("symbol", fill.symbol.to_uppercase()),
+        ("side", if fill.side == OrderSide::Buy { "BUY" } else { "SELL" }.to_string()),
+        ("qty", fill.qty.normalize().to_string()),
+        ("price", fill.price.normalize().to_string()),
+        ("notional", fill.notional().round_dp(8).normalize().to_string()),
+        ("fee", fill.fee.normalize().to_string()),
+        ("role", if fill.is_maker { "maker" } else { "taker" }.to_string()),
+        ("order_id", fill.order_id.to_string()),
+        ("time", fill.timestamp.format("%H:%M:%S").to_string()),
+    ]
+}
+
+// Token bucket không chờ: hết token thì bỏ tin, đếm lại để báo ở tin kế tiếp
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last: Instant,
+}
+
+impl RateLimiter {
+    pub fn per_minute(limit: u32, now: Instant) -> Self {
+        let capacity = limit.max(1) as f64;
+        Self { capacity, tokens: capacity, refill_per_sec: capacity / MINUTE.as_secs_f64(), last: now }
+    }
+
+    pub fn try_acquire(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+enum ChatClient {
+    Telegram(TelegramClient),
+    Discord(DiscordClient),
+}
+
+struct ChatChannel {
+    name: &'static str,
+    client: ChatClient,
+    limiter: RateLimiter,
+    suppressed: u64,
+}
+
+impl ChatChannel {
+    async fn deliver(&mut self, text: &str) {
+        if !self.limiter.try_acquire(Instant::now()) {
+            self.suppressed += 1;
+            return;
+        }
+        let text = match std::mem::take(&mut self.suppressed) {
+            0 => text.to_string(),
+            n => format!("{}\n(+{} suppressed by rate limit)", text, n),
+        };
+        let result = match &self.client {
+            ChatClient::Telegram(c) => c.send(&text).await,
+            ChatClient::Discord(c) => c.send(&text).await,
+        };
+        if let Err(e) = result {
+            warn!(channel = self.name, error = %e, "notification failed");
+        }
+    }
+}
+
+// Đẩy alert / fill ra log, webhook (JSON), Telegram và Discord. Lỗi gửi chỉ log lại
+pub struct AlertNotifier {
+    log: bool,
+    webhook: Option<String>,
+    chats: Vec<ChatChannel>,
+    templates: Templates,
+    client: reqwest::Client,
+}
+
+impl AlertNotifier {
+    pub fn new(settings: &AlertSettings) -> Self {
+        let now = Instant::now();
+        let mut chats = Vec::new();
+        if settings.telegram.enabled {
+            chats.push(ChatChannel {
+                name: "telegram",
+                client: ChatClient::Telegram(TelegramClient::new(settings.telegram.clone())),
+                limiter: RateLimiter::per_minute(settings.telegram.max_per_minute, now),
+                suppressed: 0,
+            });
+        }
+        if settings.discord.enabled {
+            chats.push(ChatChannel {
+                name: "discord",
+                client: ChatClient::Discord(DiscordClient::new(settings.discord.clone())),
+                limiter: RateLimiter::per_minute(settings.discord.max_per_minute, now),
+                suppressed: 0,
+            });
+        }
+        Self {
+            log: settings.log,
+            webhook: settings.webhook.clone(),
+            chats,
+            templates: settings.templates.clone(),
+            client: net::http_client(),
+        }
+    }
+
+    pub async fn notify(&mut self, event: &AlertEvent) {
+        if self.log {
+            warn!(rule = %event.rule, exchange = %event.exchange, symbol = %event.symbol, "alert: {}", event.message);
+        }
+        if let Some(url) = &self.webhook
+            && let Err(e) = self.client.post(url).json(event).send().await.and_then(|r| r.error_for_status())
+        {
+            warn!(%url, error = %e, "alert webhook failed");
+        }
+        let text = render(&self.templates.alert, &alert_vars(event));
+        for chat in &mut self.chats {
+            chat.deliver(&text).await;
+        }
+    }
+
+    // fill chỉ gửi qua Telegram / Discord, log đã có ở venue
+    pub async fn notify_fill(&mut self, fill: &Fill) {
+        let text = render(&self.templates.fill, &fill_vars(fill));
+        for chat in &mut self.chats {
+            chat.deliver(&text).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_render_templates() {
+        let fill = Fill {
+            order_id: 7,
+            symbol: "btcusdt".into(),
+            side: OrderSide::Sell,
+            price: dec!(30000.50),
+            qty: dec!(0.010),
+            fee: dec!(0.3),
+            is_maker: true,
+            timestamp: Utc::now(),
+        };
+        assert_eq!(render(&Templates::default().fill, &fill_vars(&fill)), "✅ SELL 0.01 BTCUSDT @ 30000.5 (maker, fee 0.3)");
+        assert_eq!(render("{a}{b} {unknown} {", &[("a", "1".into()), ("b", "2".into())]), "12 {unknown} {");
+    }
+
+    #[test]
+    fn test_rate_limiter() {
+        let t0 = Instant::now();
+        let mut limiter = RateLimiter::per_minute(2, t0);
+        assert!(limiter.try_acquire(t0));
+        assert!(limiter.try_acquire(t0));
+        assert!(!limiter.try_acquire(t0));
+        // 2 / phút -> 1 token mỗi 30s
+        assert!(limiter.try_acquire(t0 + Duration::from_secs(30)));
+        assert!(!limiter.try_acquire(t0 + Duration::from_secs(31)));
+    }
+}
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+
+use crate::net;
+
+const API_BASE_URL: &str = "https://api.telegram.org";
+
+// bot_token nên để ở BSA_ALERTS__TELEGRAM__BOT_TOKEN thay vì trong file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TelegramConfig {
+    pub enabled: bool,
+    pub bot_token: String,
+    pub chat_id: String,
+    // Bot API giới hạn ~20 tin / phút vào cùng một group
+    pub max_per_minute: u32,
+}
+
+impl Default for TelegramConfig {
+    fn default() -> Self {
+        Self { enabled: false, bot_token: String::new(), chat_id: String::new(), max_per_minute: 20 }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct SendMessage<'a> {
+    chat_id: &'a str,
+    text: &'a str,
+    disable_web_page_preview: bool,
+}
+
+// Gửi tin qua Bot API `sendMessage`
+#[derive(Debug, Clone)]
+pub struct TelegramClient {
+    client: reqwest::Client,
+    config: TelegramConfig,
+    base_url: String,
+}
+
+impl TelegramClient {
+    pub fn new(config: TelegramConfig) -> Self {
+        Self { client: net::http_client(), config, base_url: API_BASE_URL.to_string() }
+    }
+
+    // trỏ sang server giả khi test
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    pub async fn send(&self, text: &str) -> Result<(), reqwest::Error> {
+        let url = format!("{}/bot{}/sendMessage", self.base_url, self.config.bot_token);
+        let body = SendMessage { chat_id: &self.config.chat_id, text, disable_web_page_preview: true };
+        self.client.post(url).json(&body).send().await?.error_for_status()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::Path, routing::post, Json, Router};
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_send_message() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let app = Router::new().route(
+            "/:bot/sendMessage",
+            post(move |Path(bot): Path<String>, Json(body): Json<serde_json::Value>| {
+                let tx = tx.clone();
+                async move {
+                    let _ = tx.send((bot, body));
+                    Json(serde_json::json!({"ok": true}))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let config = TelegramConfig { enabled: true, bot_token: "123:abc".into(), chat_id: "-42".into(), ..Default::default() };
+        let client = TelegramClient::new(config).with_base_url(format!("http://{}", addr));
+        client.send("spread wide").await.unwrap();
+
+        let (bot, body) = rx.recv().await.unwrap();
+        assert_eq!(bot, "bot123:abc");
+        assert_eq!((body["chat_id"].as_str(), body["text"].as_str()), (Some("-42"), Some("spread wide")));
+        server.abort();
+    }
+}
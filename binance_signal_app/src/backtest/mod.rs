@@ -0,0 +1,157 @@
+pub mod report;
+
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+
+use crate::core::signal::MarketData;
+use crate::sim::{PaperConfig, PaperExchange};
+use report::{BacktestReport, EquityPoint, SymbolReport};
+
+// Strategy chạy trong backtest: nhận từng event sau khi PaperExchange đã xử lý
+// (fill của order cũ đã có) và đặt / huỷ order trực tiếp trên exchange
+pub trait Strategy {
+    fn on_event(&mut self, data: &MarketData, exchange: &mut PaperExchange);
+}
+
+impl<F: FnMut(&MarketData, &mut PaperExchange)> Strategy for F {
+    fn on_event(&mut self, data: &MarketData, exchange: &mut PaperExchange) {
+        self(data, exchange)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BacktestConfig {
+    // phí + trượt giá
+    pub paper: PaperConfig,
+    // chu kỳ lấy mẫu equity để tính Sharpe
+    pub sample_interval: Duration,
+}
+
+impl Default for BacktestConfig {
+    fn default() -> Self {
+        Self { paper: PaperConfig::default(), sample_interval: Duration::minutes(1) }
+    }
+}
+
+// Chạy strategy trên data đã sắp theo thời gian, trả về report và exchange cuối cùng
+pub fn run<S: Strategy>(config: &BacktestConfig, events: &[MarketData], strategy: &mut S) -> (BacktestReport, PaperExchange) {
+    let mut exchange = PaperExchange::new(config.paper.clone());
+    let mut equity = Vec::new();
+    let mut next_sample: Option<DateTime<Utc>> = None;
+    let mut peak = Decimal::ZERO;
+    let mut max_drawdown = Decimal::ZERO;
+
+    for ev in events {
+        exchange.on_market_data(ev);
+        strategy.on_event(ev, &mut exchange);
+
+        // drawdown theo từng event, Sharpe theo mẫu đều nhau
+        let pnl = exchange.total_pnl();
+        peak = peak.max(pnl);
+        max_drawdown = max_drawdown.max(peak - pnl);
+
+        let ts = ev.timestamp();
+        if next_sample.is_none_or(|next| ts >= next) {
+            equity.push(EquityPoint { timestamp: ts, equity: pnl });
+            next_sample = Some(ts + config.sample_interval);
+        }
+    }
+    if let Some(last) = events.last()
+        && equity.last().is_none_or(|p| p.timestamp < last.timestamp())
+    {
+        equity.push(EquityPoint { timestamp: last.timestamp(), equity: exchange.total_pnl() });
+    }
+
+    let mut symbols: Vec<SymbolReport> = exchange
+        .positions()
+        .iter()
+        .map(|(symbol, pos)| SymbolReport {
+            symbol: symbol.clone(),
+            position: pos.qty,
+            realized_pnl: pos.realized_pnl,
+            fees: pos.fees,
+            unrealized_pnl: exchange.unrealized_pnl(symbol),
+        })
+        .collect();
+    symbols.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+
+    let orders = exchange.orders();
+    let filled = orders.iter().filter(|o| !o.filled_qty.is_zero()).count();
+    let report = BacktestReport {
+        start: events.first().map(MarketData::timestamp),
+        end: events.last().map(MarketData::timestamp),
+        events: events.len(),
+        orders: orders.len(),
+        fills: exchange.fills().len(),
+        total_pnl: exchange.total_pnl(),
+        realized_pnl: symbols.iter().map(|s| s.realized_pnl).sum(),
+        unrealized_pnl: symbols.iter().map(|s| s.unrealized_pnl).sum(),
+        fees: symbols.iter().map(|s| s.fees).sum(),
+        turnover: exchange.fills().iter().map(|f| f.price * f.qty).sum(),
+        fill_rate: if orders.is_empty() { 0.0 } else { filled as f64 / orders.len() as f64 },
+        sharpe: report::sharpe(&equity, config.sample_interval),
+        max_drawdown,
+        symbols,
+        equity,
+    };
+    (report, exchange)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        order::OrderSide,
+        orderbook::{OrderbookSnapshot, Side},
+    };
+    use rust_decimal_macros::dec;
+    use std::sync::Arc;
+
+    fn event(secs: i64, bid: Decimal, ask: Decimal) -> MarketData {
+        let mut ob = OrderbookSnapshot::new();
+        ob.set_level(Side::Bid, bid, dec!(10));
+        ob.set_level(Side::Ask, ask, dec!(10));
+        ob.timestamp = DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap();
+        MarketData::Orderbook { symbol: "BTCUSDT".into(), snap: Arc::new(ob) }
+    }
+
+    #[test]
+    fn test_run_reports_pnl_drawdown_and_fills() {
+        let events = vec![
+            event(0, dec!(100), dec!(101)),
+            event(60, dec!(95), dec!(96)),
+            event(120, dec!(110), dec!(111)),
+            event(180, dec!(110), dec!(111)),
+        ];
+        let config = BacktestConfig {
+            paper: PaperConfig { maker_fee_bps: Decimal::ZERO, taker_fee_bps: Decimal::ZERO, slippage_bps: dec!(100) },
+            sample_interval: Duration::minutes(1),
+        };
+        // mua 1 ở event đầu, bán ở event thứ ba, thêm một limit không bao giờ khớp
+        let mut step = 0;
+        let mut strategy = |_: &MarketData, ex: &mut PaperExchange| {
+            match step {
+                0 => {
+                    ex.place_market("BTCUSDT", OrderSide::Buy, dec!(1)).unwrap();
+                    ex.place_limit("BTCUSDT", OrderSide::Buy, dec!(50), dec!(1)).unwrap();
+                }
+                2 => {
+                    ex.place_market("BTCUSDT", OrderSide::Sell, dec!(1)).unwrap();
+                }
+                _ => {}
+            }
+            step += 1;
+        };
+        let (report, _) = run(&config, &events, &mut strategy);
+
+        // mua 101 * 1.01 = 102.01, bán 110 * 0.99 = 108.9
+        assert_eq!(report.total_pnl, dec!(6.89));
+        assert_eq!(report.turnover, dec!(210.91));
+        assert_eq!((report.orders, report.fills, report.fill_rate), (3, 2, 2.0 / 3.0));
+        // mark theo mid 95.5 khi giữ giá vốn 102.01
+        assert_eq!(report.max_drawdown, dec!(6.51));
+        assert_eq!(report.equity.len(), 4);
+        assert!(report.sharpe > 0.0);
+        assert_eq!(report.symbols[0].position, Decimal::ZERO);
+    }
+}
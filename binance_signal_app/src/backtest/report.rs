@@ -0,0 +1,162 @@
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use serde::Serialize;
+use std::{fs, io, path::Path};
+
+const SECONDS_PER_YEAR: f64 = 365.0 * 24.0 * 3600.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EquityPoint {
+    pub timestamp: DateTime<Utc>,
+    // total pnl (realized - fees + unrealized) tại thời điểm lấy mẫu
+    pub equity: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SymbolReport {
+    pub symbol: String,
+    pub position: Decimal,
+    pub realized_pnl: Decimal,
+    pub fees: Decimal,
+    pub unrealized_pnl: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BacktestReport {
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    pub events: usize,
+    pub orders: usize,
+    pub fills: usize,
+    pub total_pnl: Decimal,
+    pub realized_pnl: Decimal,
+    pub unrealized_pnl: Decimal,
+    pub fees: Decimal,
+    // tổng notional đã khớp
+    pub turnover: Decimal,
+    // tỉ lệ order có khớp (một phần hoặc toàn bộ)
+    pub fill_rate: f64,
+    // annualized, trên chênh lệch equity giữa các mẫu
+    pub sharpe: f64,
+    // sụt giảm lớn nhất từ đỉnh, theo quote currency
+    pub max_drawdown: Decimal,
+    pub symbols: Vec<SymbolReport>,
+    pub equity: Vec<EquityPoint>,
+}
+
+const CSV_HEADER: &str =
+    "start,end,events,orders,fills,total_pnl,realized_pnl,unrealized_pnl,fees,turnover,fill_rate,sharpe,max_drawdown";
+
+impl BacktestReport {
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    // một dòng tổng kết, không gồm equity / từng symbol
+    pub fn to_csv(&self) -> String {
+        let ts = |t: Option<DateTime<Utc>>| t.map(|t| t.to_rfc3339()).unwrap_or_default();
+        format!(
+            "{}\n{},{},{},{},{},{},{},{},{},{},{:.4},{:.4},{}\n",
+            CSV_HEADER,
+            ts(self.start),
+            ts(self.end),
+            self.events,
+            self.orders,
+            self.fills,
+            self.total_pnl,
+            self.realized_pnl,
+            self.unrealized_pnl,
+            self.fees,
+            self.turnover,
+            self.fill_rate,
+            self.sharpe,
+            self.max_drawdown,
+        )
+    }
+
+    // định dạng theo đuôi file: .json hoặc .csv
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let body = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => self.to_json().map_err(io::Error::other)?,
+            Some("csv") => self.to_csv(),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("unsupported report format {:?}, expected .json or .csv", path),
+                ));
+            }
+        };
+        fs::write(path, body)
+    }
+}
+
+// Sharpe annualized trên chênh lệch equity giữa các mẫu đều nhau, 0 nếu không đủ dữ liệu
+pub fn sharpe(equity: &[EquityPoint], interval: Duration) -> f64 {
+    let returns: Vec<f64> = equity
+        .windows(2)
+        .filter_map(|w| (w[1].equity - w[0].equity).to_f64())
+        .collect();
+    let secs = interval.num_milliseconds() as f64 / 1000.0;
+    if returns.len() < 2 || secs <= 0.0 {
+        return 0.0;
+    }
+    let n = returns.len() as f64;
+    let mean = returns.iter().sum::<f64>() / n;
+    let var = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    if var <= 0.0 {
+        return 0.0;
+    }
+    mean / var.sqrt() * (SECONDS_PER_YEAR / secs).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn point(secs: i64, equity: Decimal) -> EquityPoint {
+        EquityPoint { timestamp: DateTime::from_timestamp(secs, 0).unwrap(), equity }
+    }
+
+    #[test]
+    fn test_sharpe_and_serialization() {
+        let flat = [point(0, dec!(0)), point(60, dec!(1)), point(120, dec!(2))];
+        // lợi nhuận đều -> độ lệch chuẩn 0
+        assert_eq!(sharpe(&flat, Duration::minutes(1)), 0.0);
+
+        let equity = vec![point(0, dec!(0)), point(86400, dec!(2)), point(172800, dec!(1)), point(259200, dec!(4))];
+        // returns 2, -1, 3: mean 4/3, std sqrt(13/3)
+        let expected = (4.0 / 3.0) / (13.0f64 / 3.0).sqrt() * 365f64.sqrt();
+        assert!((sharpe(&equity, Duration::days(1)) - expected).abs() < 1e-9);
+
+        let report = BacktestReport {
+            start: Some(equity[0].timestamp),
+            end: Some(equity[3].timestamp),
+            events: 4,
+            orders: 2,
+            fills: 1,
+            total_pnl: dec!(4),
+            realized_pnl: dec!(4.5),
+            unrealized_pnl: dec!(0),
+            fees: dec!(0.5),
+            turnover: dec!(100),
+            fill_rate: 0.5,
+            sharpe: 1.0,
+            max_drawdown: dec!(1),
+            symbols: Vec::new(),
+            equity,
+        };
+        let csv = report.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], CSV_HEADER);
+        assert!(lines[1].ends_with(",4,2,1,4,4.5,0,0.5,100,0.5000,1.0000,1"));
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(json["total_pnl"], "4");
+        assert_eq!(json["equity"].as_array().unwrap().len(), 4);
+
+        let dir = tempfile::tempdir().unwrap();
+        report.save(&dir.path().join("report.csv")).unwrap();
+        assert!(report.save(&dir.path().join("report.txt")).is_err());
+    }
+}
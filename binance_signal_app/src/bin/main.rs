@@ -1,25 +1,13 @@
-use binance_signal_app::ws::binance::BinanceOrderbookWS;
-
-use std::sync::Arc;
-use tokio::time::{sleep, Duration};
-
-#[tokio::main]
-async fn main() {
-    let pair = "cakebnb";
-    let ob = Arc::new(BinanceOrderbookWS::new(pair, 20));
-    let ob_clone = ob.clone();
-
-    tokio::spawn(async move {
-        ob_clone.start().await;
-    });
-
-    loop {
-        if let Some(((bid_p, bid_q), (ask_p, ask_q))) = ob.get_best_price().await {
-            println!(
-                "🟢 Bid: {:.4} ({:.2}) | Ask: {:.4} ({:.2})",
-                bid_p, bid_q, ask_p, ask_q
-            );
-        }
-        sleep(Duration::from_secs(1)).await;
+use binance_signal_app::cli::{self, Cli};
+use clap::Parser;
+
+// runtime dựng tay thay cho #[tokio::main] để áp `[threads]` (số worker, pin core)
+fn main() {
+    let cli = Cli::parse();
+    cli::init_tracing(cli.log_json, cli.log_dir.as_deref());
+    let result = cli::build_runtime(&cli).and_then(|runtime| runtime.block_on(cli::run(cli)));
+    if let Err(e) = result {
+        eprintln!("❌ {}", e);
+        std::process::exit(1);
     }
 }
@@ -0,0 +1,32 @@
+use binance_signal_app::cli::FeedArgs;
+use binance_signal_app::config::{AppConfig, DEFAULT_CONFIG_PATH};
+use binance_signal_app::tui;
+use clap::Parser;
+
+// Dashboard terminal: depth ladder, spread, trade, signal và positions
+#[derive(Debug, Parser)]
+#[command(name = "tui", about = "Live orderbook dashboard in the terminal")]
+struct Args {
+    #[arg(short, long, default_value = DEFAULT_CONFIG_PATH)]
+    config: String,
+    #[command(flatten)]
+    feed: FeedArgs,
+    /// Base URL HTTP API của process serve / market-make để lấy positions
+    #[arg(long)]
+    api: Option<String>,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+    let result = async {
+        let mut config = AppConfig::load(&args.config)?;
+        args.feed.apply(&mut config);
+        config.validate()?;
+        tui::run(&config, args.api).await
+    };
+    if let Err(e) = result.await {
+        eprintln!("❌ {}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,380 @@
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use tokio::{
+    sync::broadcast::{self, error::RecvError},
+    task::JoinHandle,
+};
+use tracing::debug;
+
+use crate::core::{
+    book_events::BookDiff,
+    candle::Candle,
+    order::Fill,
+    perp::{Liquidation, PerpStats},
+    trade::Trade,
+};
+use crate::oms::OrderUpdate;
+use crate::ws::{BestBidAsk, OrderbookFeed};
+
+const EVENT_CHANNEL_CAPACITY: usize = 4096;
+
+// Mọi loại data thị trường / tài khoản dưới một kiểu, dùng chung cho producer và consumer
+#[derive(Debug, Clone, PartialEq)]
+pub enum MarketEvent {
+    // thay đổi theo level của một lần update book
+    DepthUpdate {
+        exchange: &'static str,
+        symbol: String,
+        diff: Arc<BookDiff>,
+    },
+    Trade(Trade),
+    // nến đã đóng
+    Kline {
+        symbol: String,
+        candle: Candle,
+    },
+    // best bid / ask, chỉ phát khi top of book đổi
+    BookTicker {
+        exchange: &'static str,
+        symbol: String,
+        best: BestBidAsk,
+        timestamp: DateTime<Utc>,
+    },
+    Funding(PerpStats),
+    Liquidation(Liquidation),
+    Fill(Fill),
+    OrderUpdate(OrderUpdate),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Topic {
+    Depth,
+    Trade,
+    Kline,
+    BookTicker,
+    Funding,
+    Liquidation,
+    Fill,
+    Order,
+}
+
+impl Topic {
+    pub const ALL: [Topic; 8] = [
+        Topic::Depth,
+        Topic::Trade,
+        Topic::Kline,
+        Topic::BookTicker,
+        Topic::Funding,
+        Topic::Liquidation,
+        Topic::Fill,
+        Topic::Order,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Topic::Depth => "depth",
+            Topic::Trade => "trade",
+            Topic::Kline => "kline",
+            Topic::BookTicker => "book_ticker",
+            Topic::Funding => "funding",
+            Topic::Liquidation => "liquidation",
+            Topic::Fill => "fill",
+            Topic::Order => "order",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Topic> {
+        Self::ALL.into_iter().find(|t| t.name() == name)
+    }
+}
+
+impl MarketEvent {
+    pub fn topic(&self) -> Topic {
+        match self {
+            MarketEvent::DepthUpdate { .. } => Topic::Depth,
+            MarketEvent::Trade(_) => Topic::Trade,
+            MarketEvent::Kline { .. } => Topic::Kline,
+            MarketEvent::BookTicker { .. } => Topic::BookTicker,
+            MarketEvent::Funding(_) => Topic::Funding,
+            MarketEvent::Liquidation(_) => Topic::Liquidation,
+            MarketEvent::Fill(_) => Topic::Fill,
+            MarketEvent::OrderUpdate(_) => Topic::Order,
+        }
+    }
+
+    pub fn symbol(&self) -> &str {
+        match self {
+            MarketEvent::DepthUpdate { symbol, .. }
+            | MarketEvent::Kline { symbol, .. }
+            | MarketEvent::BookTicker { symbol, .. } => symbol,
+            MarketEvent::Trade(t) => &t.symbol,
+            MarketEvent::Funding(s) => &s.symbol,
+            MarketEvent::Liquidation(l) => &l.symbol,
+            MarketEvent::Fill(f) => &f.symbol,
+            MarketEvent::OrderUpdate(u) => &u.symbol,
+        }
+    }
+
+    // None với event không gắn với feed của một sàn cụ thể
+    pub fn exchange(&self) -> Option<&'static str> {
+        match self {
+            MarketEvent::DepthUpdate { exchange, .. } | MarketEvent::BookTicker { exchange, .. } => Some(exchange),
+            _ => None,
+        }
+    }
+
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        match self {
+            MarketEvent::DepthUpdate { diff, .. } => diff.timestamp,
+            MarketEvent::Trade(t) => t.timestamp,
+            MarketEvent::Kline { candle, .. } => candle.close_time,
+            MarketEvent::BookTicker { timestamp, .. } => *timestamp,
+            MarketEvent::Funding(s) => s.timestamp,
+            MarketEvent::Liquidation(l) => l.timestamp,
+            MarketEvent::Fill(f) => f.timestamp,
+            MarketEvent::OrderUpdate(u) => u.event_time,
+        }
+    }
+}
+
+// Rỗng = nhận hết. Symbol so không phân biệt hoa thường, exchange chỉ lọc được
+// event có exchange (depth, book ticker), event khác luôn qua
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    pub topics: Vec<Topic>,
+    pub symbols: Vec<String>,
+    pub exchange: Option<String>,
+}
+
+impl EventFilter {
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    pub fn topics(topics: &[Topic]) -> Self {
+        Self { topics: topics.to_vec(), ..Self::default() }
+    }
+
+    pub fn with_symbol(mut self, symbol: &str) -> Self {
+        self.symbols.push(symbol.to_string());
+        self
+    }
+
+    pub fn with_exchange(mut self, exchange: &str) -> Self {
+        self.exchange = Some(exchange.to_string());
+        self
+    }
+
+    pub fn matches(&self, event: &MarketEvent) -> bool {
+        (self.topics.is_empty() || self.topics.contains(&event.topic()))
+            && (self.symbols.is_empty() || self.symbols.iter().any(|s| s.eq_ignore_ascii_case(event.symbol())))
+            && self.exchange.as_deref().is_none_or(|e| event.exchange().is_none_or(|ex| ex == e))
+    }
+}
+
+// Bus trung tâm: một broadcast channel, mỗi subscriber tự lọc theo topic / symbol.
+// Clone rẻ, mọi bản clone dùng chung channel
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    tx: broadcast::Sender<Arc<MarketEvent>>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(EVENT_CHANNEL_CAPACITY)
+    }
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        Self { tx: broadcast::channel(capacity).0 }
+    }
+
+    // không có subscriber thì bỏ event, trả về số subscriber đã nhận
+    pub fn publish(&self, event: MarketEvent) -> usize {
+        self.tx.send(Arc::new(event)).unwrap_or(0)
+    }
+
+    pub fn has_subscribers(&self) -> bool {
+        self.tx.receiver_count() > 0
+    }
+
+    pub fn subscribe(&self, filter: EventFilter) -> EventSubscription {
+        EventSubscription { rx: self.tx.subscribe(), filter, lagged: 0 }
+    }
+
+    // đẩy một stream có sẵn (trade, fill, nến, ...) vào bus cho tới khi stream đóng
+    pub fn forward<T, F>(&self, mut rx: broadcast::Receiver<T>, map: F) -> JoinHandle<()>
+    where
+        T: Clone + Send + 'static,
+        F: Fn(T) -> MarketEvent + Send + 'static,
+    {
+        let bus = self.clone();
+        tokio::spawn(async move {
+            loop {
+                match rx.recv().await {
+                    Ok(item) => {
+                        if bus.has_subscribers() {
+                            bus.publish(map(item));
+                        }
+                    }
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    // depth + book ticker của feed, thêm funding / thanh lý nếu là feed perp
+    pub fn attach_feed(&self, feed: Arc<dyn OrderbookFeed>) -> Vec<JoinHandle<()>> {
+        let mut handles = Vec::new();
+        if let Some(rx) = feed.subscribe_perp() {
+            handles.push(self.forward(rx, MarketEvent::Funding));
+        }
+        if let Some(rx) = feed.subscribe_liquidations() {
+            handles.push(self.forward(rx, MarketEvent::Liquidation));
+        }
+
+        let bus = self.clone();
+        let mut rx = feed.subscribe_events();
+        handles.push(tokio::spawn(async move {
+            let mut last_best = None;
+            loop {
+                let diff = match rx.recv().await {
+                    Ok(diff) => diff,
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                };
+                if !bus.has_subscribers() {
+                    continue;
+                }
+                let (exchange, symbol) = (feed.exchange(), feed.symbol().to_string());
+                let timestamp = diff.timestamp;
+                bus.publish(MarketEvent::DepthUpdate { exchange, symbol: symbol.clone(), diff });
+                let best = feed.best_bid_ask();
+                if let Some(best) = best
+                    && last_best != Some(best)
+                {
+                    bus.publish(MarketEvent::BookTicker { exchange, symbol, best, timestamp });
+                }
+                last_best = best;
+            }
+        }));
+        handles
+    }
+}
+
+pub struct EventSubscription {
+    rx: broadcast::Receiver<Arc<MarketEvent>>,
+    filter: EventFilter,
+    lagged: u64,
+}
+
+impl EventSubscription {
+    // event kế tiếp khớp filter, None khi bus đã đóng. Subscriber chậm bị bỏ event cũ
+    pub async fn recv(&mut self) -> Option<Arc<MarketEvent>> {
+        loop {
+            match self.rx.recv().await {
+                Ok(event) if self.filter.matches(&event) => return Some(event),
+                Ok(_) => continue,
+                Err(RecvError::Lagged(n)) => {
+                    debug!(skipped = n, "event subscriber lagged");
+                    self.lagged += n;
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    // tổng số event bị bỏ vì subscriber chậm
+    pub fn lagged(&self) -> u64 {
+        self.lagged
+    }
+
+    pub fn filter(&self) -> &EventFilter {
+        &self.filter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{orderbook::{SharedOrderbook, Side}, trade::TradeSide};
+    use async_trait::async_trait;
+    use rust_decimal_macros::dec;
+
+    struct TestFeed {
+        orderbook: SharedOrderbook,
+    }
+
+    #[async_trait]
+    impl OrderbookFeed for TestFeed {
+        fn exchange(&self) -> &'static str {
+            "binance"
+        }
+
+        fn symbol(&self) -> &str {
+            "btcusdt"
+        }
+
+        fn orderbook(&self) -> &SharedOrderbook {
+            &self.orderbook
+        }
+
+        async fn start(self: Arc<Self>) {}
+    }
+
+    fn trade(symbol: &str) -> MarketEvent {
+        MarketEvent::Trade(Trade {
+            symbol: symbol.to_string(),
+            trade_id: 1,
+            price: 100.0,
+            qty: 1.0,
+            side: TradeSide::Buy,
+            timestamp: Utc::now(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_topic_and_symbol_filtering() {
+        let bus = EventBus::default();
+        let mut all = bus.subscribe(EventFilter::all());
+        let mut btc_trades = bus.subscribe(EventFilter::topics(&[Topic::Trade]).with_symbol("BTCUSDT"));
+        let mut books = bus.subscribe(EventFilter::topics(&[Topic::Depth, Topic::BookTicker]).with_exchange("binance"));
+
+        let feed = Arc::new(TestFeed { orderbook: SharedOrderbook::new() });
+        let handles = bus.attach_feed(feed.clone());
+        assert_eq!(handles.len(), 1);
+
+        assert_eq!(bus.publish(trade("ethusdt")), 3);
+        bus.publish(trade("btcusdt"));
+        feed.orderbook.update(|ob| {
+            ob.set_level(Side::Bid, dec!(100), dec!(1));
+            ob.set_level(Side::Ask, dec!(101), dec!(2));
+            true
+        });
+
+        assert_eq!(btc_trades.recv().await.unwrap().symbol(), "btcusdt");
+        let depth = books.recv().await.unwrap();
+        let MarketEvent::DepthUpdate { diff, .. } = depth.as_ref() else {
+            panic!("expected depth update, got {:?}", depth);
+        };
+        assert_eq!(diff.events.len(), 2);
+        let ticker = books.recv().await.unwrap();
+        assert!(matches!(ticker.as_ref(), MarketEvent::BookTicker { best, .. } if *best == ((dec!(100), dec!(1)), (dec!(101), dec!(2)))));
+
+        // qty ngoài top đổi: chỉ có depth, không có book ticker
+        feed.orderbook.update(|ob| {
+            ob.set_level(Side::Bid, dec!(99), dec!(5));
+            true
+        });
+        assert_eq!(books.recv().await.unwrap().topic(), Topic::Depth);
+        let mut seen = Vec::new();
+        for _ in 0..5 {
+            seen.push(all.recv().await.unwrap().topic());
+        }
+        assert_eq!(seen, vec![Topic::Trade, Topic::Trade, Topic::Depth, Topic::BookTicker, Topic::Depth]);
+        assert_eq!(Topic::from_name("book_ticker"), Some(Topic::BookTicker));
+        handles.into_iter().for_each(|h| h.abort());
+    }
+}
@@ -0,0 +1,1192 @@
+use chrono::{Duration, NaiveDate, Utc};
+use clap::{Args, Parser, Subcommand};
+use rust_decimal::Decimal;
+use std::{error::Error, net::SocketAddr, path::{Path, PathBuf}, sync::Arc, time::Duration as StdDuration};
+use tokio::sync::{broadcast, mpsc};
+use tracing::{info, warn};
+use tracing_subscriber::{fmt::writer::BoxMakeWriter, EnvFilter};
+
+use crate::alerts;
+use crate::backtest::{self, BacktestConfig};
+use crate::db::Store;
+use crate::config::{AppConfig, Exchange, RecorderKind, VenueKind, DEFAULT_CONFIG_PATH};
+use crate::core::{
+    order::{Fill, OrderSide},
+    clock::ClockSync,
+    ladder::Ladder,
+    sampling::Sampler,
+    signal::{forward_liquidations, forward_orderbook, forward_perp, forward_sampled, ofi_name, volatility::VolSource, MarketData, SignalEngine},
+};
+use crate::grpc;
+use crate::server;
+use crate::hub::{forward_trades, MarketHub};
+use crate::portfolio::{AttributionTag, SharedPortfolio};
+use crate::rest::binance::BinanceRestClient;
+use crate::recorder::{parquet::ParquetRecorder, state::BookStateStore, tick::TickRecorder, RecorderError};
+use crate::risk::{self, OrderIntent, RiskManager};
+use crate::logfile;
+use crate::rollover::Rollover;
+use crate::schedule::{self, Activity, Scheduler};
+use crate::replay::{reader, ReplaySpeed, Replayer};
+use crate::runtime::{next_params, OfiParams, ParamsWatcher, StrategyParams};
+use crate::sim::PaperExchange;
+#[cfg(feature = "kafka")]
+use crate::sink::kafka::KafkaSink;
+use crate::sink::{clickhouse::ClickHouseWriter, redis::RedisSink};
+use crate::execution::algos::{AlgoConfig, AlgoExecutor, AlgoKind};
+use crate::strategy::{
+    fair_value::FairValuePublisher,
+    hedger::{self, Hedger},
+    market_maker::{MarketMaker, MarketMakerConfig},
+    triangular::{Triangle, TriangularConfig, TriangularScanner},
+};
+use crate::supervisor::{Supervisor, DEFAULT_SHUTDOWN_TIMEOUT};
+use crate::threads::FeedRuntime;
+use crate::venue::{self, accounts::Accounts};
+use crate::web::{self, api::ApiState};
+use crate::net;
+use crate::ws::{
+    binance_trades::{BinanceTradesWS, TradeStreamKind},
+    stale, transport,
+    OrderbookFeed,
+};
+
+const DATA_CHANNEL_CAPACITY: usize = 4096;
+// tên strategy trong `[venue.routes]`
+const MARKET_MAKER: &str = "market_maker";
+const EXECUTION: &str = "execution";
+
+#[derive(Debug, Parser)]
+#[command(name = "binance_signal_app", about = "Orderbook streaming, recording, replay and paper trading")]
+pub struct Cli {
+    // file không tồn tại thì dùng default + biến môi trường
+    #[arg(short, long, global = true, default_value = DEFAULT_CONFIG_PATH)]
+    pub config: String,
+    /// Log dạng JSON (một object mỗi dòng) để đẩy vào hệ thống log
+    #[arg(long, global = true)]
+    pub log_json: bool,
+    /// Ghi log vào thư mục này thay vì stdout, `[rollover]` mở file mới mỗi ngày
+    #[arg(long, global = true)]
+    pub log_dir: Option<PathBuf>,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// In ladder top-N realtime kèm spread
+    Stream {
+        symbol: Option<String>,
+        #[command(flatten)]
+        feed: FeedArgs,
+        /// Số level mỗi phía trong ladder
+        #[arg(long, default_value_t = 10)]
+        levels: usize,
+        /// Khoảng cách tối thiểu giữa hai lần in (ms), update ở giữa bị gộp
+        #[arg(long, default_value_t = 1000)]
+        interval_ms: u64,
+    },
+    /// Ghi orderbook ra đĩa theo `[recorder]` trong config, Ctrl-C để dừng
+    Record {
+        #[command(flatten)]
+        feed: FeedArgs,
+    },
+    /// Phát lại file/thư mục đã ghi qua SignalEngine
+    Replay {
+        path: PathBuf,
+        /// max, realtime hoặc hệ số (vd. 10)
+        #[arg(long, default_value = "max", value_parser = parse_speed)]
+        speed: ReplaySpeed,
+        #[command(flatten)]
+        feed: FeedArgs,
+    },
+    /// Chạy strategy trên data đã ghi với PaperExchange, phí / trượt giá theo `[venue]`
+    Backtest {
+        path: PathBuf,
+        #[command(flatten)]
+        feed: FeedArgs,
+        #[command(flatten)]
+        strategy: StrategyArgs,
+        /// Ghi report ra file .json hoặc .csv
+        #[arg(long)]
+        report: Option<PathBuf>,
+        /// Chu kỳ lấy mẫu equity cho Sharpe
+        #[arg(long, default_value_t = 60)]
+        sample_secs: i64,
+    },
+    /// Chạy strategy trên feed live, khớp lệnh giả lập
+    PaperTrade {
+        #[command(flatten)]
+        feed: FeedArgs,
+        #[command(flatten)]
+        strategy: StrategyArgs,
+    },
+    /// Quét arbitrage tam giác trên Binance từ stream bookTicker
+    Triangular {
+        /// Lặp lại để quét nhiều tam giác, asset bắt đầu là quote của market đầu
+        #[arg(short, long = "triangle", default_value = "BTC/USDT,ETH/BTC,ETH/USDT", value_parser = Triangle::parse)]
+        triangles: Vec<Triangle>,
+        /// Mặc định theo `[fees]` của binance (taker)
+        #[arg(long)]
+        fee_bps: Option<Decimal>,
+        #[arg(long, default_value = "0")]
+        min_profit_bps: Decimal,
+    },
+    /// Market making trên symbol đầu tiên, đặt lệnh qua `[venue]` (paper hoặc live)
+    MarketMake {
+        #[command(flatten)]
+        feed: FeedArgs,
+        #[command(flatten)]
+        quoting: QuotingArgs,
+    },
+    /// Vào / thoát vị thế dần bằng TWAP / VWAP trên symbol đầu tiên, đặt lệnh qua `[venue]`
+    Execute {
+        #[command(flatten)]
+        feed: FeedArgs,
+        #[command(flatten)]
+        algo: AlgoArgs,
+    },
+    /// Stream feed + trade + signal và mở server theo `[server]` cho process khác đọc
+    Serve {
+        #[command(flatten)]
+        feed: FeedArgs,
+        #[arg(long)]
+        grpc_addr: Option<SocketAddr>,
+        #[arg(long)]
+        ws_addr: Option<SocketAddr>,
+        #[arg(long)]
+        http_addr: Option<SocketAddr>,
+    },
+    /// Tổng hợp fill trong ngày (UTC) từ `[db]` để đối soát, in JSON ra stdout
+    Reconcile {
+        // mặc định hôm nay
+        #[arg(long)]
+        date: Option<NaiveDate>,
+    },
+}
+
+// Override phần feed của config
+#[derive(Debug, Clone, Default, Args)]
+pub struct FeedArgs {
+    /// Lặp lại để chọn nhiều symbol
+    #[arg(short, long = "symbol")]
+    pub symbols: Vec<String>,
+    #[arg(short, long = "exchange", value_enum)]
+    pub exchanges: Vec<Exchange>,
+    #[arg(short, long)]
+    pub depth: Option<usize>,
+}
+
+impl FeedArgs {
+    pub fn apply(&self, config: &mut AppConfig) {
+        if !self.symbols.is_empty() {
+            config.symbols = self.symbols.clone();
+        }
+        if !self.exchanges.is_empty() {
+            config.exchanges = self.exchanges.clone();
+        }
+        if let Some(depth) = self.depth {
+            config.depth = depth;
+        }
+    }
+}
+
+// Strategy mẫu: vào lệnh market theo dấu của OFI khi vượt ngưỡng
+#[derive(Debug, Clone, Args)]
+pub struct StrategyArgs {
+    #[arg(long, default_value_t = 1000)]
+    pub ofi_horizon_ms: i64,
+    #[arg(long, default_value_t = 10.0)]
+    pub threshold: f64,
+    #[arg(long, default_value = "1")]
+    pub qty: Decimal,
+    /// File TOML ghi đè threshold / qty (`[ofi]`), paper-trade đọc lại khi file đổi
+    #[arg(long)]
+    pub params: Option<PathBuf>,
+}
+
+impl StrategyArgs {
+    // key có trong `[ofi]` thay giá trị CLI
+    fn with_params(&self, params: &OfiParams) -> Self {
+        Self { threshold: params.threshold.unwrap_or(self.threshold), qty: params.qty.unwrap_or(self.qty), ..self.clone() }
+    }
+
+    fn signal_name(&self) -> String {
+        ofi_name(Duration::milliseconds(self.ofi_horizon_ms))
+    }
+
+    // đưa position về +qty / -qty khi |signal| > threshold, giữ nguyên nếu không
+    fn step(&self, exchange: &mut PaperExchange, risk: &RiskManager, symbol: &str, value: f64) -> Vec<Fill> {
+        let target = if value > self.threshold {
+            self.qty
+        } else if value < -self.threshold {
+            -self.qty
+        } else {
+            return Vec::new();
+        };
+        let delta = target - exchange.position(symbol).qty;
+        if delta.is_zero() {
+            return Vec::new();
+        }
+        let side = if delta > Decimal::ZERO { OrderSide::Buy } else { OrderSide::Sell };
+        let intent = OrderIntent::market(symbol, side, delta.abs());
+        let open = exchange.open_orders(symbol);
+        let pending = open.iter().filter(|o| o.side == side).map(|o| o.remaining()).sum();
+        let book = exchange.latest_book(symbol);
+        if let Err(e) = risk.check(&intent, &exchange.position(symbol), pending, open.len(), book.as_deref()) {
+            warn!(error = %e, symbol, "paper order blocked by risk");
+            return Vec::new();
+        }
+        match exchange.place_market(symbol, side, delta.abs()) {
+            Ok((_, fills)) => fills,
+            Err(e) => {
+                warn!(error = %e, symbol, "paper order rejected");
+                Vec::new()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct QuotingArgs {
+    #[arg(long, default_value = "5")]
+    pub half_spread_bps: Decimal,
+    #[arg(long, default_value = "0.001")]
+    pub order_qty: Decimal,
+    #[arg(long, default_value = "0.01")]
+    pub max_inventory: Decimal,
+    #[arg(long, default_value = "5")]
+    pub skew_bps: Decimal,
+    #[arg(long, default_value_t = 30)]
+    pub refresh_secs: u64,
+    #[arg(long, default_value = "1")]
+    pub requote_threshold_bps: Decimal,
+    #[arg(long)]
+    pub tick_size: Option<Decimal>,
+    /// Half spread tối thiểu = hệ số × realized vol (bps), 0 = tắt
+    #[arg(long, default_value = "0")]
+    pub vol_multiplier: Decimal,
+    #[arg(long, default_value_t = 60)]
+    pub vol_horizon_secs: u64,
+    /// VPIN từ trade stream vượt ngưỡng này thì nhân half spread với --toxic-spread-mult, 0 = tắt
+    #[arg(long, default_value = "0")]
+    pub toxic_vpin: Decimal,
+    #[arg(long, default_value = "2")]
+    pub toxic_spread_mult: Decimal,
+    /// Volume (base asset) mỗi bucket VPIN
+    #[arg(long, default_value = "1")]
+    pub vpin_bucket_qty: Decimal,
+    #[arg(long, default_value_t = 50)]
+    pub vpin_buckets: usize,
+    /// File TOML ghi đè spread / size / ngưỡng (`[market_maker]`), đọc lại khi file đổi
+    #[arg(long)]
+    pub params: Option<PathBuf>,
+}
+
+impl QuotingArgs {
+    fn to_config(&self, symbol: &str) -> MarketMakerConfig {
+        MarketMakerConfig {
+            symbol: symbol.to_uppercase(),
+            half_spread_bps: self.half_spread_bps,
+            order_qty: self.order_qty,
+            max_inventory: self.max_inventory,
+            skew_bps: self.skew_bps,
+            refresh_interval: StdDuration::from_secs(self.refresh_secs),
+            requote_threshold_bps: self.requote_threshold_bps,
+            tick_size: self.tick_size,
+            vol_multiplier: self.vol_multiplier,
+            vol_horizon: StdDuration::from_secs(self.vol_horizon_secs),
+            toxic_vpin: self.toxic_vpin,
+            toxic_spread_mult: self.toxic_spread_mult,
+            vpin_bucket_qty: self.vpin_bucket_qty,
+            vpin_buckets: self.vpin_buckets,
+        }
+    }
+}
+
+// Parent order cho `execute`, account chọn qua `[venue.routes] execution = ...`
+#[derive(Debug, Clone, Args)]
+pub struct AlgoArgs {
+    #[arg(long, value_parser = parse_side)]
+    pub side: OrderSide,
+    #[arg(long)]
+    pub qty: Decimal,
+    #[arg(long, value_enum, default_value = "twap")]
+    pub algo: AlgoKind,
+    #[arg(long, default_value_t = 600)]
+    pub duration_secs: u64,
+    #[arg(long, default_value_t = 10)]
+    pub slice_secs: u64,
+    /// Child lấy tối đa phần này của depth đang hiển thị phía đối diện trong dải giá cho phép
+    #[arg(long, default_value = "0.2")]
+    pub max_book_take: Decimal,
+    #[arg(long, default_value = "5")]
+    pub max_slippage_bps: Decimal,
+    #[arg(long)]
+    pub limit_price: Option<Decimal>,
+    /// Chậm so với lịch thì child tối đa bằng hệ số × phần theo lịch, 1 = không đuổi
+    #[arg(long, default_value = "2")]
+    pub catch_up_mult: Decimal,
+    #[arg(long)]
+    pub lot_size: Option<Decimal>,
+    #[arg(long)]
+    pub tick_size: Option<Decimal>,
+    /// Trọng số volume các khoảng đều nhau (VWAP), vd. 3,2,1,1,2,3
+    #[arg(long, value_delimiter = ',')]
+    pub profile: Vec<Decimal>,
+}
+
+impl AlgoArgs {
+    fn to_config(&self) -> AlgoConfig {
+        AlgoConfig {
+            kind: self.algo,
+            duration: StdDuration::from_secs(self.duration_secs),
+            slice_interval: StdDuration::from_secs(self.slice_secs),
+            max_book_take: self.max_book_take,
+            max_slippage_bps: self.max_slippage_bps,
+            limit_price: self.limit_price,
+            catch_up_mult: self.catch_up_mult,
+            lot_size: self.lot_size,
+            tick_size: self.tick_size,
+            profile: self.profile.clone(),
+        }
+    }
+}
+
+pub fn parse_side(s: &str) -> Result<OrderSide, String> {
+    match s.to_lowercase().as_str() {
+        "buy" => Ok(OrderSide::Buy),
+        "sell" => Ok(OrderSide::Sell),
+        _ => Err(format!("invalid side {:?}, expected buy or sell", s)),
+    }
+}
+
+// Runtime chính theo `[threads]`: số worker + pin core, feed runtime riêng được tạo trong `start_feeds`
+pub fn build_runtime(cli: &Cli) -> Result<tokio::runtime::Runtime, Box<dyn Error>> {
+    let config = AppConfig::load(&cli.config)?;
+    Ok(config.threads.build_main_runtime()?)
+}
+
+// Mức log theo RUST_LOG, mặc định info (vd. RUST_LOG=binance_signal_app::ws=debug)
+pub fn init_tracing(json: bool, log_dir: Option<&Path>) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let writer = match log_dir.map(logfile::init) {
+        Some(Ok(writer)) => BoxMakeWriter::new(writer),
+        Some(Err(e)) => {
+            eprintln!("⚠️ cannot open log dir, logging to stdout: {}", e);
+            BoxMakeWriter::new(std::io::stdout)
+        }
+        None => BoxMakeWriter::new(std::io::stdout),
+    };
+    let builder = tracing_subscriber::fmt().with_env_filter(filter).with_writer(writer).with_ansi(log_dir.is_none());
+    // đã có subscriber (vd. trong test) thì giữ nguyên
+    let _ = if json {
+        builder.json().with_current_span(true).try_init()
+    } else {
+        builder.try_init()
+    };
+}
+
+pub fn parse_speed(s: &str) -> Result<ReplaySpeed, String> {
+    match s.to_lowercase().as_str() {
+        "max" => Ok(ReplaySpeed::Max),
+        "realtime" | "1" => Ok(ReplaySpeed::RealTime),
+        x => match x.trim_end_matches('x').parse::<f64>() {
+            Ok(v) if v > 0.0 => Ok(ReplaySpeed::Multiplier(v)),
+            _ => Err(format!("invalid speed {:?}, expected max, realtime or a positive number", s)),
+        },
+    }
+}
+
+pub async fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    let mut config = AppConfig::load(&cli.config)?;
+    // trước khi tạo client REST / kết nối WS nào
+    net::configure(&config.network)?;
+    transport::configure(&config.ws);
+    // chọn endpoint Binance nhanh nhất trước khi feed kết nối
+    if let Some(pool) = config.binance.network.pool() {
+        pool.probe().await;
+    }
+    match cli.command {
+        Command::Stream { symbol, feed, levels, interval_ms } => {
+            feed.apply(&mut config);
+            if let Some(symbol) = symbol {
+                config.symbols = vec![symbol];
+            }
+            config.validate()?;
+            stream(&config, levels, StdDuration::from_millis(interval_ms)).await?;
+        }
+        Command::Record { feed } => {
+            feed.apply(&mut config);
+            config.validate()?;
+            record(&config).await?;
+        }
+        Command::Replay { path, speed, feed } => {
+            replay(&path, speed, &feed.symbols).await?;
+        }
+        Command::Backtest { path, feed, strategy, report, sample_secs } => {
+            let backtest_config = BacktestConfig {
+                paper: config.paper_config(),
+                sample_interval: Duration::seconds(sample_secs.max(1)),
+            };
+            // backtest chỉ đọc params một lần
+            let strategy = match &strategy.params {
+                Some(params) => strategy.with_params(&StrategyParams::load(&params.to_string_lossy())?.ofi),
+                None => strategy,
+            };
+            backtest(&path, &feed.symbols, &strategy, backtest_config, report.as_deref())?;
+        }
+        Command::PaperTrade { feed, strategy } => {
+            feed.apply(&mut config);
+            config.validate()?;
+            paper_trade(&config, &strategy).await?;
+        }
+        Command::Triangular { triangles, fee_bps, min_profit_bps } => {
+            let taker_fee_bps = fee_bps.unwrap_or(config.fees.schedule(Exchange::Binance).taker_bps);
+            let config = TriangularConfig { taker_fee_bps, min_profit_bps };
+            triangular(triangles, config).await?;
+        }
+        Command::MarketMake { feed, quoting } => {
+            feed.apply(&mut config);
+            config.validate()?;
+            market_make(&config, &quoting).await?;
+        }
+        Command::Execute { feed, algo } => {
+            feed.apply(&mut config);
+            config.validate()?;
+            execute(&config, &algo).await?;
+        }
+        Command::Serve { feed, grpc_addr, ws_addr, http_addr } => {
+            feed.apply(&mut config);
+            if grpc_addr.is_some() {
+                config.server.grpc_addr = grpc_addr;
+            }
+            if ws_addr.is_some() {
+                config.server.ws_addr = ws_addr;
+            }
+            if http_addr.is_some() {
+                config.server.http_addr = http_addr;
+            }
+            config.validate()?;
+            serve(&config).await?;
+        }
+        Command::Reconcile { date } => {
+            let store = Store::connect(&config.db.url).await?;
+            let summary = store.reconcile(date.unwrap_or_else(|| Utc::now().date_naive())).await?;
+            println!("{}", serde_json::to_string_pretty(&summary)?);
+        }
+    }
+    Ok(())
+}
+
+// Sync giờ với Binance theo `[clock]`, None nếu tắt hoặc không cần
+fn start_clock(config: &AppConfig, sup: &mut Supervisor) -> Option<ClockSync> {
+    if !config.needs_clock() {
+        return None;
+    }
+    let clock = ClockSync::new();
+    let client = BinanceRestClient::new(None).with_base_url(&config.binance.network.rest_url());
+    let every = std::time::Duration::from_secs(config.clock.sync_secs);
+    let task_clock = clock.clone();
+    sup.spawn_graceful("clock sync", move |shutdown| client.run_clock_sync(task_clock, every, shutdown));
+    Some(clock)
+}
+
+fn start_feeds(config: &AppConfig, sup: &mut Supervisor) -> Vec<Arc<dyn OrderbookFeed>> {
+    let clock = start_clock(config, sup);
+    start_feeds_with_clock(config, sup, clock.as_ref())
+}
+
+// feed Binance ghi latency theo giờ sàn khi có clock
+fn start_feeds_with_clock(config: &AppConfig, sup: &mut Supervisor, clock: Option<&ClockSync>) -> Vec<Arc<dyn OrderbookFeed>> {
+    let feeds = config.feeds();
+    if config.book_state.enabled {
+        let store = BookStateStore::new(&config.book_state);
+        for feed in &feeds {
+            store.restore(feed.as_ref());
+        }
+        let saved = feeds.clone();
+        sup.on_shutdown("save book state", async move { store.save_all(&saved) });
+    }
+    for feed in &feeds {
+        feed.orderbook().integrity().configure(config.book_checks);
+        feed.orderbook().set_drop_policy(config.backpressure.snapshots);
+        if let Some(clock) = clock
+            && feed.exchange().starts_with("binance")
+        {
+            feed.orderbook().set_clock(clock.clone());
+        }
+    }
+    let feed_runtime = config
+        .threads
+        .dedicated_feed_runtime
+        .then(|| FeedRuntime::start(&config.threads))
+        .transpose()
+        .unwrap_or_else(|e| {
+            warn!(error = %e, "cannot start feed runtime, feeds run on the main runtime");
+            None
+        });
+    for feed in &feeds {
+        let name = format!("feed {}:{}", feed.exchange(), feed.symbol());
+        match &feed_runtime {
+            Some(rt) => sup.adopt(name, rt.spawn(feed.clone().start())),
+            None => sup.spawn(name, feed.clone().start()),
+        }
+    }
+    if let Some(pool) = config.binance.network.pool()
+        && config.binance.probe_secs > 0
+    {
+        sup.spawn("endpoint probe", pool.clone().run_probe(std::time::Duration::from_secs(config.binance.probe_secs)));
+    }
+    if config.stale.enabled {
+        sup.spawn("stale watchdog", stale::watch_feeds(feeds.clone(), config.stale.clone()));
+    }
+    if let Some(rt) = feed_runtime {
+        // join thread feed, không block worker của runtime chính
+        sup.on_shutdown("stop feed runtime", async move {
+            let _ = tokio::task::spawn_blocking(move || rt.stop()).await;
+        });
+    }
+    feeds
+}
+
+// Lịch dừng quote / ghi theo `[schedule]`, None nếu tắt
+fn start_scheduler(config: &AppConfig, sup: &mut Supervisor) -> Option<Arc<Scheduler>> {
+    if !config.schedule.enabled {
+        return None;
+    }
+    let scheduler = Scheduler::new(config.schedule.clone());
+    info!(state = ?scheduler.state(), "session scheduler started");
+    let task = scheduler.clone();
+    sup.spawn_graceful("session scheduler", move |shutdown| task.run(shutdown));
+    Some(scheduler)
+}
+
+// `kill -USR1 <pid>` bật kill switch: chặn order mới, venue tự huỷ order đang mở
+fn start_kill_switch(risk: &RiskManager, sup: &mut Supervisor) -> Result<(), Box<dyn Error>> {
+    #[cfg(unix)]
+    sup.adopt("kill switch", risk::spawn_signal_trigger(risk.kill_switch())?);
+    #[cfg(not(unix))]
+    let _ = (risk, sup);
+    Ok(())
+}
+
+// Fill của mọi account cộng vào counter ngày dùng chung (`risk.max_daily_notional`),
+// rollover reset counter lúc sang ngày
+fn start_daily_counters(accounts: &Accounts, sup: &mut Supervisor) {
+    for (account, venue) in accounts.iter() {
+        let (daily, mut fills) = (accounts.risk().daily(), venue.fills());
+        sup.spawn(format!("daily risk {}", account), async move {
+            while let Some(fill) = fills.recv().await {
+                daily.on_fill(&fill);
+            }
+        });
+    }
+}
+
+// Ctrl-C / SIGTERM rồi join mọi task, task lỗi thì trả lỗi
+async fn run_until_signal(sup: Supervisor) -> Result<(), Box<dyn Error>> {
+    let report = sup.run_until_signal(DEFAULT_SHUTDOWN_TIMEOUT).await?;
+    if let Some((task, e)) = report.failed.first() {
+        return Err(format!("task {} failed: {}", task, e).into());
+    }
+    Ok(())
+}
+
+async fn stream(config: &AppConfig, levels: usize, interval: StdDuration) -> Result<(), Box<dyn Error>> {
+    let mut sup = Supervisor::new();
+    for feed in start_feeds(config, &mut sup) {
+        let name = format!("printer {}:{}", feed.exchange(), feed.symbol());
+        // chờ update thay vì poll, in xong nghỉ `interval`: watch chỉ giữ bản mới nhất
+        sup.spawn(name, async move {
+            let mut latest = feed.watch();
+            while latest.changed().await.is_ok() {
+                let snap = latest.borrow_and_update().clone();
+                if snap.best_bid().is_some() || snap.best_ask().is_some() {
+                    println!("{} {} #{}\n{}", feed.exchange(), feed.symbol(), snap.last_update_id, Ladder::new(&snap, levels));
+                }
+                tokio::time::sleep(interval).await;
+            }
+        });
+    }
+    run_until_signal(sup).await
+}
+
+async fn record(config: &AppConfig) -> Result<(), Box<dyn Error>> {
+    let mut sup = Supervisor::new();
+    let (tx, mut rx) = mpsc::channel(DATA_CHANNEL_CAPACITY);
+    // trong cửa sổ dừng ghi thì bỏ data trước khi tới recorder, file vẫn mở
+    if let Some(scheduler) = start_scheduler(config, &mut sup) {
+        let (gated, handle) = schedule::gate(rx, scheduler.watch(), Activity::Recording);
+        sup.adopt("recording gate", handle);
+        rx = gated;
+    }
+    let settings = config.recorder.clone();
+    // cuối ngày giao dịch cắt file dù chưa tới kỳ xoay
+    let rollover = config.rollover.enabled.then(|| Rollover::new(&config.rollover)).transpose()?;
+    let cut = rollover.as_ref().map(|r| r.cut_watch());
+    // recorder ghi footer và đóng file khi mọi sender (forwarder) đã drop
+    sup.spawn_blocking("recorder", move || {
+        let paths = match (settings.kind, cut) {
+            (RecorderKind::Tick, None) => TickRecorder::new(settings.tick).run_blocking(rx),
+            (RecorderKind::Tick, Some(cut)) => TickRecorder::new(settings.tick).with_cut(cut).run_blocking(rx),
+            (RecorderKind::Parquet, None) => ParquetRecorder::new(settings.parquet.to_config()).run_blocking(rx),
+            (RecorderKind::Parquet, Some(cut)) => ParquetRecorder::new(settings.parquet.to_config()).with_cut(cut).run_blocking(rx),
+        }?;
+        for path in paths {
+            info!(path = %path.display(), "recorded file");
+        }
+        Ok::<_, RecorderError>(())
+    });
+    if let Some(rollover) = rollover {
+        sup.spawn_graceful("rollover", move |shutdown| rollover.run(None, shutdown));
+    }
+
+    for feed in start_feeds(config, &mut sup) {
+        let name = format!("forwarder {}:{}", feed.exchange(), feed.symbol());
+        sup.adopt(name, forward_orderbook(feed, tx.clone()));
+    }
+    drop(tx);
+
+    run_until_signal(sup).await
+}
+
+fn load_events(path: &Path) -> Result<Vec<MarketData>, RecorderError> {
+    let files = if path.is_dir() { reader::list_files(path)? } else { vec![path.to_path_buf()] };
+    let mut events = Vec::new();
+    for file in files {
+        events.extend(reader::read_file(&file)?);
+    }
+    events.sort_by_key(MarketData::timestamp);
+    Ok(events)
+}
+
+// symbol trong data nếu không chỉ định
+fn symbols_of(events: &[MarketData], filter: &[String]) -> Vec<String> {
+    let mut symbols: Vec<String> = if filter.is_empty() {
+        events.iter().map(|e| e.symbol().to_uppercase()).collect()
+    } else {
+        filter.iter().map(|s| s.to_uppercase()).collect()
+    };
+    symbols.sort();
+    symbols.dedup();
+    symbols
+}
+
+async fn replay(path: &Path, speed: ReplaySpeed, filter: &[String]) -> Result<(), Box<dyn Error>> {
+    let events = load_events(path)?;
+    let mut engine = SignalEngine::new();
+    for symbol in symbols_of(&events, filter) {
+        engine.register_ofi(&symbol, &[Duration::milliseconds(100), Duration::seconds(1), Duration::seconds(5)]);
+    }
+    let mut outputs = engine.subscribe();
+    let printer = tokio::spawn(async move {
+        while let Ok(out) = outputs.recv().await {
+            let values: Vec<String> = out.values.iter().map(|(n, v)| format!("{}={:.4}", n, v)).collect();
+            info!(symbol = %out.symbol, timestamp = %out.timestamp, values = %values.join(" "), "signal");
+        }
+    });
+
+    let replayer = Replayer::new(events, speed);
+    let (tx, rx) = mpsc::channel(DATA_CHANNEL_CAPACITY);
+    let engine_task = tokio::spawn(engine.run(rx));
+    let sent = replayer.run(Some(tx)).await;
+    engine_task.await?;
+    printer.await?;
+    info!(events = sent, "replay finished");
+    Ok(())
+}
+
+fn backtest(path: &Path, filter: &[String], strategy: &StrategyArgs, config: BacktestConfig, out: Option<&Path>) -> Result<(), Box<dyn Error>> {
+    let events = load_events(path)?;
+    let symbols = symbols_of(&events, filter);
+    let signal = strategy.signal_name();
+    let mut engine = SignalEngine::new();
+    for symbol in &symbols {
+        engine.register_ofi(symbol, &[Duration::milliseconds(strategy.ofi_horizon_ms)]);
+    }
+
+    // backtest đo tín hiệu, không áp `[risk]`
+    let risk = RiskManager::default();
+    let mut step = |ev: &MarketData, exchange: &mut PaperExchange| {
+        let MarketData::Orderbook { symbol, snap } = ev else { return };
+        if !symbols.contains(&symbol.to_uppercase()) {
+            return;
+        }
+        if let Some(value) = engine.on_orderbook(symbol, snap).and_then(|out| out.get(&signal)) {
+            strategy.step(exchange, &risk, symbol, value);
+        }
+    };
+    let (report, _) = backtest::run(&config, &events, &mut step);
+
+    info!(
+        events = report.events,
+        fills = report.fills,
+        total_pnl = %report.total_pnl,
+        fees = %report.fees,
+        turnover = %report.turnover,
+        sharpe = report.sharpe,
+        max_drawdown = %report.max_drawdown,
+        "backtest finished"
+    );
+    for s in &report.symbols {
+        info!(
+            symbol = %s.symbol,
+            position = %s.position,
+            realized = %s.realized_pnl,
+            fees = %s.fees,
+            unrealized = %s.unrealized_pnl,
+            "backtest position"
+        );
+    }
+    if let Some(out) = out {
+        report.save(out)?;
+        info!(path = %out.display(), "backtest report written");
+    }
+    Ok(())
+}
+
+async fn paper_trade(config: &AppConfig, strategy: &StrategyArgs) -> Result<(), Box<dyn Error>> {
+    let mut sup = Supervisor::new();
+    let signal = strategy.signal_name();
+    let mut engine = SignalEngine::new();
+    engine.set_history_capacity(config.signal_history.capacity);
+    let (tx, mut rx) = mpsc::channel(DATA_CHANNEL_CAPACITY);
+    for feed in start_feeds(config, &mut sup) {
+        engine.register_ofi(feed.symbol(), &[Duration::milliseconds(strategy.ofi_horizon_ms)]);
+        let name = format!("forwarder {}:{}", feed.exchange(), feed.symbol());
+        sup.adopt(name, forward_sampled(feed, tx.clone(), Sampler::with_config(config.signal_sampling.clone())));
+    }
+    drop(tx);
+
+    let mut params = None;
+    if let Some(path) = &strategy.params {
+        let watcher = ParamsWatcher::load(path)?;
+        params = Some(watcher.subscribe());
+        sup.spawn_graceful("strategy params", move |shutdown| watcher.run(shutdown));
+    }
+
+    let risk = RiskManager::new(config.risk.clone());
+    start_kill_switch(&risk, &mut sup)?;
+
+    // fill do signal kích hoạt tag theo tên signal
+    let portfolio = SharedPortfolio::new();
+    let tag = AttributionTag::new("ofi", &signal);
+    let (settings, reports) = (config.attribution.clone(), portfolio.clone());
+    sup.spawn_graceful("attribution", move |shutdown| reports.run_attribution(settings, shutdown));
+
+    let (base, paper) = (strategy.clone(), config.paper_config());
+    sup.spawn_graceful("paper strategy", |mut shutdown| async move {
+        let mut exchange = PaperExchange::new(paper);
+        let mut strategy = match &params {
+            Some(params) => base.with_params(&params.borrow().ofi),
+            None => base.clone(),
+        };
+        loop {
+            let data = tokio::select! {
+                data = rx.recv() => match data {
+                    Some(data) => data,
+                    None => break,
+                },
+                params = next_params(&mut params) => {
+                    strategy = base.with_params(&params.ofi);
+                    info!(threshold = strategy.threshold, qty = %strategy.qty, "strategy params updated");
+                    continue;
+                }
+                _ = shutdown.wait() => break,
+            };
+            let mut fills = exchange.on_market_data(&data);
+            let mut signal_fills = Vec::new();
+            if let MarketData::Orderbook { symbol, snap } = &data
+                && let Some(value) = engine.on_orderbook(symbol, snap).and_then(|out| out.get(&signal))
+            {
+                signal_fills = strategy.step(&mut exchange, &risk, symbol, value);
+            }
+            for fill in fills.iter().chain(&signal_fills) {
+                risk.daily().on_fill(fill);
+            }
+            portfolio.update(|p| {
+                p.on_market_data(&data);
+                for fill in &fills {
+                    p.on_fill(fill);
+                }
+                for fill in &signal_fills {
+                    p.on_fill_tagged(fill, tag.clone());
+                }
+            });
+            fills.extend(signal_fills);
+            for fill in fills {
+                info!(
+                    symbol = %fill.symbol,
+                    side = ?fill.side,
+                    qty = %fill.qty,
+                    price = %fill.price,
+                    total_pnl = %exchange.total_pnl(),
+                    "paper fill"
+                );
+            }
+        }
+        info!(fills = exchange.fills().len(), total_pnl = %exchange.total_pnl(), "paper trading stopped");
+    });
+
+    run_until_signal(sup).await
+}
+
+async fn triangular(triangles: Vec<Triangle>, config: TriangularConfig) -> Result<(), Box<dyn Error>> {
+    let mut sup = Supervisor::new();
+    let scanner = TriangularScanner::new(triangles, config);
+    for feed in scanner.feeds() {
+        sup.spawn(format!("feed {}:{}", feed.exchange(), feed.symbol()), feed.start());
+    }
+    sup.spawn("triangular scanner", scanner.run());
+    run_until_signal(sup).await
+}
+
+async fn market_make(config: &AppConfig, quoting: &QuotingArgs) -> Result<(), Box<dyn Error>> {
+    let mut sup = Supervisor::new();
+    let clock = start_clock(config, &mut sup);
+    let accounts = venue::from_config(config, clock.clone()).await?;
+    let venue = accounts.for_strategy(MARKET_MAKER)?;
+    info!(account = accounts.account_for(MARKET_MAKER), "market maker account");
+    start_kill_switch(accounts.risk(), &mut sup)?;
+    start_daily_counters(&accounts, &mut sup);
+    // khôi phục order / position trước khi user stream chạy
+    let portfolio = match config.venue.kind {
+        VenueKind::Live => SharedPortfolio::with_fees(config.fees.schedule(Exchange::Binance)),
+        // fill paper đã tính phí theo `paper_config`
+        VenueKind::Paper => SharedPortfolio::new(),
+    };
+    let mut rollover_store = None;
+    if config.db.enabled {
+        let store = Store::connect(&config.db.url).await?;
+        rollover_store = Some(store.clone());
+        let oms = venue.oms_handle();
+        store.restore(oms.as_deref(), &portfolio).await?;
+        let (settings, fills, portfolio) = (config.db.clone(), venue.fills(), portfolio.clone());
+        sup.spawn_graceful("db writer", move |shutdown| store.run(settings, fills, portfolio, oms, shutdown));
+    }
+    // mọi account đều chạy user stream để /accounts có số dư
+    for (account, venue) in accounts.iter() {
+        sup.spawn(format!("venue {}:{}", venue.name(), account), venue.clone().start());
+    }
+    let feeds = start_feeds_with_clock(config, &mut sup, clock.as_ref());
+    let feed = feeds.first().cloned().ok_or("no feed configured")?;
+
+    // book symbol hedge trên sàn của account hedge (spot / perp) để paper khớp và mark vị thế hedge
+    let hedge_feed = config.hedge.enabled.then(|| {
+        let (exchange, symbol) = (config.hedge_exchange(), config.hedge.symbol.as_deref().unwrap_or(feed.symbol()));
+        feeds.iter().find(|f| f.exchange() == exchange.name() && f.symbol().eq_ignore_ascii_case(symbol)).cloned()
+    });
+    let mut marks = vec![feed.clone()];
+    if let Some(Some(hedge_feed)) = &hedge_feed
+        && !Arc::ptr_eq(hedge_feed, &feed)
+    {
+        marks.push(hedge_feed.clone());
+    }
+    sup.spawn("portfolio", portfolio.clone().with_oms(venue.oms_handle()).run(venue.fills(), marks));
+    let (settings, reports) = (config.attribution.clone(), portfolio.clone());
+    sup.spawn_graceful("attribution", move |shutdown| reports.run_attribution(settings, shutdown));
+
+    // market-make không chạy SignalEngine, hub chỉ phục vụ book
+    let hub = MarketHub::new(vec![feed.clone()], broadcast::channel(1).0);
+    if let Some(addr) = config.server.http_addr {
+        let state = ApiState::new(hub.clone()).with_portfolio(portfolio.clone()).with_accounts(accounts.clone());
+        spawn_router(&mut sup, "http", addr, web::api::router(state));
+    }
+    if config.alerts.enabled {
+        let fills = config.alerts.fills.then(|| venue.fills());
+        let (settings, hub) = (config.alerts.clone(), hub.clone());
+        sup.spawn_graceful("alerts", move |shutdown| alerts::run(settings, hub, fills, shutdown));
+    }
+    #[cfg(feature = "kafka")]
+    if config.sink.kafka.enabled {
+        let sink = KafkaSink::new(config.sink.kafka.clone())?;
+        let fills = venue.fills();
+        sup.spawn_graceful("kafka sink", move |shutdown| sink.run(hub, Some(fills), shutdown));
+    }
+
+    if config.rollover.enabled {
+        // counter ngày của risk, fill cộng vào qua `start_daily_counters`
+        let mut rollover = Rollover::new(&config.rollover)?.with_portfolio(portfolio.clone()).with_daily(accounts.risk().daily());
+        if let Some(store) = rollover_store {
+            rollover = rollover.with_store(store);
+        }
+        sup.spawn_graceful("rollover", move |shutdown| rollover.run(None, shutdown));
+    }
+    let mut mm = MarketMaker::new(quoting.to_config(feed.symbol()), venue).with_attribution(portfolio.clone());
+    if let Some(scheduler) = start_scheduler(config, &mut sup) {
+        mm = mm.with_session(scheduler.watch());
+    }
+    if let Some(path) = &quoting.params {
+        let watcher = ParamsWatcher::load(path)?;
+        mm = mm.with_params(watcher.subscribe());
+        sup.spawn_graceful("strategy params", move |shutdown| watcher.run(shutdown));
+    }
+    // trade stream (VPIN, fair value) hiện chỉ có cho Binance spot
+    let trades = (feed.exchange() == Exchange::Binance.name() && (quoting.toxic_vpin > Decimal::ZERO || config.fair_value.enabled))
+        .then(|| Arc::new(BinanceTradesWS::new(feed.symbol(), TradeStreamKind::AggTrade).with_network(config.binance.network.clone())));
+    if quoting.toxic_vpin > Decimal::ZERO
+        && let Some(trades) = &trades
+    {
+        mm = mm.with_trades(trades.subscribe());
+    }
+    if config.fair_value.enabled {
+        // sàn khác cùng cặp làm tham chiếu
+        let pair = feed.instrument();
+        let venues: Vec<_> = feeds[1..]
+            .iter()
+            .filter(|f| f.instrument().zip(pair.as_ref()).is_some_and(|(i, p)| i.same_pair(p)))
+            .cloned()
+            .collect();
+        let mut publisher = FairValuePublisher::new(config.fair_value.clone(), feed.clone(), venues);
+        if let Some(trades) = &trades {
+            publisher = publisher.with_trades(trades.subscribe());
+        }
+        mm = mm.with_fair_value(publisher.subscribe());
+        sup.spawn_graceful("fair value", move |shutdown| publisher.run(shutdown));
+    }
+    if let Some(trades) = trades {
+        sup.spawn(format!("trades binance:{}", feed.symbol()), trades.start());
+    }
+    if let Some(hedge_feed) = hedge_feed {
+        let account = accounts.account_for(hedger::STRATEGY);
+        let hedge_venue = accounts.for_strategy(hedger::STRATEGY)?;
+        let same_account = account == accounts.account_for(MARKET_MAKER);
+        let hedge_symbol = config.hedge.symbol.as_deref().unwrap_or(feed.symbol());
+        if same_account && hedge_symbol.eq_ignore_ascii_case(feed.symbol()) {
+            return Err("`hedge.symbol` must differ from the quoted symbol when the hedger shares the market maker account".into());
+        }
+        // fill của account hedge cũng vào portfolio, tag "inventory" theo account đó
+        let hedge_portfolio = if same_account {
+            portfolio.clone()
+        } else {
+            let scoped = portfolio.clone().with_oms(hedge_venue.oms_handle()).for_account(account);
+            sup.spawn(format!("portfolio {}", account), scoped.clone().run(hedge_venue.fills(), Vec::new()));
+            scoped
+        };
+        // order hedge qua cùng pre-trade check `[risk]` như mọi venue của `accounts`
+        let mut hedger = Hedger::new(config.hedge.clone(), feed.symbol(), hedge_portfolio, hedge_venue);
+        if let Some(hedge_feed) = hedge_feed {
+            hedger = hedger.with_feed(hedge_feed);
+        }
+        info!(account, symbol = hedger.hedge_symbol(), "hedger account");
+        sup.spawn_graceful("hedger", move |shutdown| hedger.run(shutdown));
+    }
+    sup.spawn_graceful("market maker", move |shutdown| mm.run(feed, shutdown));
+    let result = run_until_signal(sup).await;
+    let snap = portfolio.snapshot();
+    info!(total_pnl = %snap.total_pnl, fees = %snap.fees, exposure = %snap.gross_exposure, "market making stopped");
+    result
+}
+
+async fn execute(config: &AppConfig, args: &AlgoArgs) -> Result<(), Box<dyn Error>> {
+    let algo = args.to_config();
+    let errors = algo.validate();
+    if !errors.is_empty() {
+        return Err(errors.join("; ").into());
+    }
+    let mut sup = Supervisor::new();
+    let clock = start_clock(config, &mut sup);
+    let accounts = venue::from_config(config, clock.clone()).await?;
+    let venue = accounts.for_strategy(EXECUTION)?;
+    info!(account = accounts.account_for(EXECUTION), "execution account");
+    start_kill_switch(accounts.risk(), &mut sup)?;
+    start_daily_counters(&accounts, &mut sup);
+    sup.spawn(format!("venue {}:{}", venue.name(), accounts.account_for(EXECUTION)), venue.clone().start());
+    let feeds = start_feeds_with_clock(config, &mut sup, clock.as_ref());
+    let feed = feeds.first().cloned().ok_or("no feed configured")?;
+
+    let executor = AlgoExecutor::new(algo, OrderIntent::market(feed.symbol(), args.side, args.qty), venue);
+    // xong parent order thì dừng cả process
+    let done = sup.shutdown_signal();
+    sup.spawn_graceful("algo", move |shutdown| async move {
+        executor.run(feed, shutdown).await;
+        done.trigger();
+    });
+    run_until_signal(sup).await
+}
+
+// Feed + SignalEngine (OFI, vol) + trade stream, dùng chung cho `serve` và TUI
+pub fn start_hub(config: &AppConfig, sup: &mut Supervisor) -> MarketHub {
+    let feeds = start_feeds(config, sup);
+
+    let mut engine = SignalEngine::new();
+    engine.set_history_capacity(config.signal_history.capacity);
+    let (tx, rx) = mpsc::channel(DATA_CHANNEL_CAPACITY);
+    for feed in &feeds {
+        engine.register_ofi(feed.symbol(), &[Duration::seconds(1), Duration::seconds(5)]);
+        engine.register_mid_change(feed.symbol(), &[Duration::seconds(1), Duration::seconds(10)]);
+        engine.register_volatility(feed.symbol(), &[Duration::minutes(1), Duration::minutes(5)], VolSource::Mid);
+        // feed perp: funding, basis, thay đổi OI, thanh lý
+        if let Some(handle) = forward_perp(feed.clone(), tx.clone()) {
+            engine.register_carry(feed.symbol(), &[Duration::minutes(5)]);
+            sup.adopt(format!("perp forwarder {}", feed.symbol()), handle);
+        }
+        if let Some(handle) = forward_liquidations(feed.clone(), tx.clone()) {
+            engine.register_liquidations(feed.symbol(), &[Duration::minutes(1), Duration::minutes(5)]);
+            sup.adopt(format!("liquidation forwarder {}", feed.symbol()), handle);
+        }
+        let name = format!("forwarder {}:{}", feed.exchange(), feed.symbol());
+        sup.adopt(name, forward_sampled(feed.clone(), tx.clone(), Sampler::with_config(config.signal_sampling.clone())));
+    }
+    drop(tx);
+    let hub = MarketHub::new(feeds, engine.output_sender());
+    sup.spawn("signal engine", engine.run(rx));
+    for (name, handle) in hub.attach_feeds() {
+        sup.adopt(name, handle);
+    }
+
+    // trade stream hiện chỉ có cho Binance spot
+    if config.exchanges.contains(&Exchange::Binance) {
+        for symbol in &config.venue_symbols(Exchange::Binance) {
+            let trades = Arc::new(BinanceTradesWS::new(symbol, TradeStreamKind::AggTrade).with_network(config.binance.network.clone()));
+            sup.adopt(format!("trades forwarder {}", symbol), forward_trades(trades.subscribe(), hub.clone()));
+            sup.spawn(format!("trades binance:{}", symbol), trades.start());
+        }
+    }
+    hub
+}
+
+async fn serve(config: &AppConfig) -> Result<(), Box<dyn Error>> {
+    let servers = config.server.clone();
+    if !servers.any_enabled() && !config.sink.any_enabled() && !config.alerts.enabled {
+        return Err("no server, sink or alerts enabled, set `[server]` / `[sink]` / `[alerts]` or --grpc-addr / --ws-addr / --http-addr".into());
+    }
+    let mut sup = Supervisor::new();
+    let hub = start_hub(config, &mut sup);
+
+    if let Some(addr) = servers.grpc_addr {
+        let hub = hub.clone();
+        sup.spawn_graceful("grpc server", move |shutdown| async move {
+            if let Err(e) = grpc::serve(addr, hub, shutdown).await {
+                warn!(%addr, error = %e, "grpc server stopped");
+            }
+        });
+    }
+    if let Some(addr) = servers.ws_addr {
+        spawn_router(&mut sup, "ws", addr, server::ws::router(hub.clone()));
+    }
+    if let Some(addr) = servers.http_addr {
+        spawn_router(&mut sup, "http", addr, web::api::router(ApiState::new(hub.clone())));
+    }
+    if config.sink.redis.enabled {
+        let sink = RedisSink::connect(config.sink.redis.clone()).await?;
+        let hub = hub.clone();
+        sup.spawn_graceful("redis sink", move |shutdown| sink.run(hub, shutdown));
+    }
+    if config.sink.clickhouse.enabled {
+        let writer = ClickHouseWriter::new(config.sink.clickhouse.clone());
+        let hub = hub.clone();
+        sup.spawn_graceful("clickhouse writer", move |shutdown| writer.run(hub, shutdown));
+    }
+    if config.alerts.enabled {
+        let (settings, hub) = (config.alerts.clone(), hub.clone());
+        sup.spawn_graceful("alerts", move |shutdown| alerts::run(settings, hub, None, shutdown));
+    }
+    #[cfg(feature = "kafka")]
+    if config.sink.kafka.enabled {
+        let sink = KafkaSink::new(config.sink.kafka.clone())?;
+        sup.spawn_graceful("kafka sink", move |shutdown| sink.run(hub, None, shutdown));
+    }
+    // không có portfolio: chỉ xoay file log và báo cáo mốc ngày
+    if config.rollover.enabled {
+        let rollover = Rollover::new(&config.rollover)?;
+        sup.spawn_graceful("rollover", move |shutdown| rollover.run(None, shutdown));
+    }
+    run_until_signal(sup).await
+}
+
+fn spawn_router(sup: &mut Supervisor, name: &'static str, addr: SocketAddr, router: axum::Router) {
+    sup.spawn_graceful(format!("{} server", name), move |shutdown| async move {
+        if let Err(e) = server::serve(name, addr, router, shutdown).await {
+            warn!(%addr, error = %e, "{} server stopped", name);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::orderbook::{OrderbookSnapshot, Side};
+    use crate::sim::PaperConfig;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_parse_subcommands() {
+        let cli = Cli::parse_from(["app", "stream", "btcusdt", "-e", "binance", "-e", "kraken", "-d", "10"]);
+        let Command::Stream { symbol, feed, levels, .. } = cli.command else { panic!("expected stream") };
+        assert_eq!((symbol.as_deref(), levels), (Some("btcusdt"), 10));
+        assert_eq!(feed.exchanges, vec![Exchange::Binance, Exchange::Kraken]);
+
+        let mut config = AppConfig::default();
+        feed.apply(&mut config);
+        assert_eq!((config.depth, config.exchanges.len()), (10, 2));
+
+        let cli = Cli::parse_from(["app", "--config", "x.toml", "replay", "data/", "--speed", "10x"]);
+        assert_eq!(cli.config, "x.toml");
+        assert!(matches!(cli.command, Command::Replay { speed: ReplaySpeed::Multiplier(10.0), .. }));
+
+        let cli = Cli::parse_from(["app", "backtest", "data/", "--report", "out.json"]);
+        let Command::Backtest { report, sample_secs, .. } = cli.command else { panic!("expected backtest") };
+        assert_eq!((report, sample_secs), (Some(PathBuf::from("out.json")), 60));
+
+        let cli = Cli::parse_from(["app", "paper-trade", "-s", "ethusdt", "--qty", "0.5", "--threshold", "2"]);
+        let Command::PaperTrade { strategy, .. } = cli.command else { panic!("expected paper-trade") };
+        assert_eq!((strategy.qty, strategy.signal_name()), (dec!(0.5), "ofi_1s".to_string()));
+        let strategy = strategy.with_params(&OfiParams { threshold: Some(4.0), qty: None });
+        assert_eq!((strategy.threshold, strategy.qty), (4.0, dec!(0.5)));
+
+        let cli = Cli::parse_from(["app", "triangular", "-t", "BTC/USDT,BNB/BTC,BNB/USDT", "--min-profit-bps", "5"]);
+        let Command::Triangular { triangles, min_profit_bps, .. } = cli.command else { panic!("expected triangular") };
+        assert_eq!((triangles[0].markets[1].symbol.as_str(), min_profit_bps), ("bnbbtc", dec!(5)));
+
+        let cli = Cli::parse_from(["app", "market-make", "-s", "btcusdt", "--half-spread-bps", "3", "--tick-size", "0.01"]);
+        let Command::MarketMake { quoting, .. } = cli.command else { panic!("expected market-make") };
+        let mm = quoting.to_config("btcusdt");
+        assert_eq!((mm.symbol.as_str(), mm.half_spread_bps, mm.tick_size), ("BTCUSDT", dec!(3), Some(dec!(0.01))));
+
+        let cli = Cli::parse_from(["app", "execute", "-s", "btcusdt", "--side", "sell", "--qty", "2", "--algo", "vwap", "--profile", "3,1", "--tick-size", "0.01"]);
+        let Command::Execute { algo, .. } = cli.command else { panic!("expected execute") };
+        let algo_config = algo.to_config();
+        assert_eq!((algo.side, algo_config.kind, algo_config.profile), (OrderSide::Sell, AlgoKind::Vwap, vec![dec!(3), dec!(1)]));
+        assert_eq!(algo_config.tick_size, Some(dec!(0.01)));
+        assert!(Cli::try_parse_from(["app", "execute", "--side", "long", "--qty", "1"]).is_err());
+
+        let cli = Cli::parse_from(["app", "serve", "-s", "btcusdt", "--grpc-addr", "0.0.0.0:50051", "--ws-addr", "127.0.0.1:8765"]);
+        let Command::Serve { grpc_addr, ws_addr, http_addr, .. } = cli.command else { panic!("expected serve") };
+        assert_eq!(http_addr, None);
+
+        let cli = Cli::parse_from(["app", "reconcile", "--date", "2024-03-05"]);
+        assert!(matches!(cli.command, Command::Reconcile { date: Some(d) } if d.to_string() == "2024-03-05"));
+        assert_eq!((grpc_addr.map(|a| a.port()), ws_addr.map(|a| a.port())), (Some(50051), Some(8765)));
+
+        assert!(Cli::try_parse_from(["app", "replay", "f", "--speed", "fast"]).is_err());
+        assert!(Cli::try_parse_from(["app", "triangular", "-t", "BTC/USDT,ETH/BTC"]).is_err());
+        assert!(Cli::try_parse_from(["app", "stream", "-e", "ftx"]).is_err());
+    }
+
+    #[test]
+    fn test_strategy_step_targets_position() {
+        let mut ob = OrderbookSnapshot::new();
+        ob.set_level(Side::Bid, dec!(100), dec!(10));
+        ob.set_level(Side::Ask, dec!(101), dec!(10));
+        let mut exchange = PaperExchange::new(PaperConfig::default());
+        exchange.on_orderbook("BTCUSDT", Arc::new(ob));
+
+        let strategy = StrategyArgs { ofi_horizon_ms: 1000, threshold: 1.0, qty: dec!(2), params: None };
+        let risk = RiskManager::default();
+        assert!(strategy.step(&mut exchange, &risk, "BTCUSDT", 0.5).is_empty());
+        strategy.step(&mut exchange, &risk, "BTCUSDT", 3.0);
+        assert_eq!(exchange.position("BTCUSDT").qty, dec!(2));
+        // đã đúng target thì không đặt thêm
+        assert!(strategy.step(&mut exchange, &risk, "BTCUSDT", 3.0).is_empty());
+        // đảo chiều: bán 4
+        let fills = strategy.step(&mut exchange, &risk, "BTCUSDT", -3.0);
+        assert_eq!(fills[0].qty, dec!(4));
+        assert_eq!(exchange.position("BTCUSDT").qty, dec!(-2));
+        // kill switch: không đặt thêm
+        risk.kill_switch().trigger("test");
+        assert!(strategy.step(&mut exchange, &risk, "BTCUSDT", 3.0).is_empty());
+        assert_eq!(exchange.position("BTCUSDT").qty, dec!(-2));
+    }
+}
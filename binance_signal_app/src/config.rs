@@ -0,0 +1,888 @@
+use chrono::Duration;
+use figment::{
+    providers::{Env, Format, Toml},
+    Figment,
+};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    net::SocketAddr,
+    path::PathBuf,
+    sync::Arc,
+};
+
+use crate::alerts::AlertSettings;
+use crate::core::{backpressure::BackpressureConfig, history::HistoryConfig, integrity::IntegrityConfig};
+use crate::db::DbSettings;
+use crate::dex::DexSettings;
+use crate::net::NetworkSettings;
+use crate::portfolio::AttributionSettings;
+use crate::risk::RiskLimits;
+use crate::rollover::RolloverSettings;
+use crate::schedule::ScheduleSettings;
+use crate::threads::ThreadSettings;
+use crate::fees::FeeModel;
+use crate::recorder::{parquet::ParquetConfig, state::BookStateConfig, tick::TickRecorderConfig, SamplingConfig};
+use crate::rest::{
+    binance::{BinanceCredentials, BinanceMarket, BinanceNetwork},
+    endpoints::{EndpointPool, EndpointSpec},
+};
+use crate::sim::PaperConfig;
+use crate::strategy::{fair_value::FairValueConfig, hedger::{self, HedgeConfig}};
+use crate::symbols::Instrument;
+use crate::sink::{clickhouse::ClickHouseConfig, kafka::KafkaSinkConfig, redis::RedisSinkConfig};
+use crate::ws::{
+    binance::{BinanceOrderbookWS, DepthMode, UpdateSpeed},
+    binance_futures::BinanceFuturesWS,
+    stale::StaleSettings,
+    transport::WsSettings,
+    OrderbookFeed,
+};
+#[cfg(feature = "bitfinex")]
+use crate::ws::bitfinex::BitfinexOrderbookWS;
+#[cfg(feature = "bybit")]
+use crate::ws::bybit::{BybitCategory, BybitOrderbookWS};
+#[cfg(feature = "coinbase")]
+use crate::ws::coinbase::CoinbaseOrderbookWS;
+#[cfg(feature = "gateio")]
+use crate::ws::gateio::GateioOrderbookWS;
+#[cfg(feature = "htx")]
+use crate::ws::htx::HtxOrderbookWS;
+#[cfg(feature = "kraken")]
+use crate::ws::kraken::KrakenOrderbookWS;
+#[cfg(feature = "kucoin")]
+use crate::ws::kucoin::KucoinOrderbookWS;
+#[cfg(feature = "mexc")]
+use crate::ws::mexc::MexcOrderbookWS;
+#[cfg(feature = "okx")]
+use crate::ws::okx::{OkxChannel, OkxOrderbookWS};
+
+pub const DEFAULT_CONFIG_PATH: &str = "config.toml";
+// BSA_DEPTH=10, BSA_RECORDER__ENABLED=true, BSA_SYMBOLS='["btcusdt","ethusdt"]'
+pub const ENV_PREFIX: &str = "BSA_";
+// account dùng BINANCE_API_KEY / BINANCE_API_SECRET
+pub const DEFAULT_ACCOUNT: &str = "default";
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Load(Box<figment::Error>),
+    // liệt kê mọi lỗi một lần thay vì dừng ở lỗi đầu tiên
+    Invalid(Vec<String>),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Load(e) => write!(f, "failed to load config: {}", e),
+            ConfigError::Invalid(errors) => {
+                write!(f, "invalid config:")?;
+                for e in errors {
+                    write!(f, "\n  - {}", e)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<figment::Error> for ConfigError {
+    fn from(e: figment::Error) -> Self {
+        ConfigError::Load(Box::new(e))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+#[value(rename_all = "snake_case")]
+pub enum Exchange {
+    Binance,
+    BinanceFutures,
+    Coinbase,
+    Okx,
+    Bybit,
+    Kraken,
+    Kucoin,
+    Gateio,
+    Bitfinex,
+    Mexc,
+    Htx,
+    // pool on-chain, cấu hình trong `[dex]` chứ không qua `exchanges`
+    Dex,
+}
+
+impl Exchange {
+    pub const ALL: [Exchange; 12] = [
+        Exchange::Binance,
+        Exchange::BinanceFutures,
+        Exchange::Coinbase,
+        Exchange::Okx,
+        Exchange::Bybit,
+        Exchange::Kraken,
+        Exchange::Kucoin,
+        Exchange::Gateio,
+        Exchange::Bitfinex,
+        Exchange::Mexc,
+        Exchange::Htx,
+        Exchange::Dex,
+    ];
+
+    // ngược với `name`, dùng cho `OrderbookFeed::exchange()`
+    pub fn from_name(name: &str) -> Option<Exchange> {
+        Self::ALL.into_iter().find(|e| e.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Exchange::Binance => "binance",
+            Exchange::BinanceFutures => "binance_futures",
+            Exchange::Coinbase => "coinbase",
+            Exchange::Okx => "okx",
+            Exchange::Bybit => "bybit",
+            Exchange::Kraken => "kraken",
+            Exchange::Kucoin => "kucoin",
+            Exchange::Gateio => "gateio",
+            Exchange::Bitfinex => "bitfinex",
+            Exchange::Mexc => "mexc",
+            Exchange::Htx => "htx",
+            Exchange::Dex => "dex",
+        }
+    }
+
+    // Sàn bị tắt bằng Cargo feature vẫn parse được trong config, `AppConfig::validate` báo lỗi
+    pub fn is_enabled(&self) -> bool {
+        match self {
+            Exchange::Binance | Exchange::BinanceFutures | Exchange::Dex => true,
+            Exchange::Coinbase => cfg!(feature = "coinbase"),
+            Exchange::Okx => cfg!(feature = "okx"),
+            Exchange::Bybit => cfg!(feature = "bybit"),
+            Exchange::Kraken => cfg!(feature = "kraken"),
+            Exchange::Kucoin => cfg!(feature = "kucoin"),
+            Exchange::Gateio => cfg!(feature = "gateio"),
+            Exchange::Bitfinex => cfg!(feature = "bitfinex"),
+            Exchange::Mexc => cfg!(feature = "mexc"),
+            Exchange::Htx => cfg!(feature = "htx"),
+        }
+    }
+
+    // None = sàn không cho chọn depth (coinbase full L2, okx dùng channel books, kucoin level2),
+    // gateio dùng depth làm limit của REST snapshot (chặn ở 100)
+    pub fn allowed_depths(&self) -> Option<&'static [usize]> {
+        match self {
+            Exchange::Binance | Exchange::BinanceFutures | Exchange::Mexc | Exchange::Htx => Some(&[5, 10, 20]),
+            Exchange::Bybit => Some(&[1, 50, 200]),
+            Exchange::Kraken => Some(&[10, 25, 100, 500, 1000]),
+            Exchange::Bitfinex => Some(&[1, 25, 100, 250]),
+            Exchange::Coinbase | Exchange::Okx | Exchange::Kucoin | Exchange::Gateio | Exchange::Dex => None,
+        }
+    }
+
+    // symbol dạng của sàn (cakebnb, BTC-USD, XBT/USD, ...), xem `Instrument::venue_symbol`.
+    // Sàn chưa bật feature thì panic, config đã qua `validate` không gặp
+    pub fn feed(&self, symbol: &str, depth: usize, binance: &BinanceStreamSettings) -> Arc<dyn OrderbookFeed> {
+        match self {
+            Exchange::Binance => {
+                let ws = BinanceOrderbookWS::with_mode(symbol, depth, binance.mode).with_speed(binance.speed);
+                Arc::new(ws.with_network(binance.network.clone()))
+            }
+            Exchange::BinanceFutures => Arc::new(BinanceFuturesWS::new(symbol, depth).with_network(binance.network.clone())),
+            #[cfg(feature = "coinbase")]
+            Exchange::Coinbase => Arc::new(CoinbaseOrderbookWS::new(symbol)),
+            #[cfg(feature = "okx")]
+            Exchange::Okx => Arc::new(OkxOrderbookWS::new(symbol, OkxChannel::Books)),
+            #[cfg(feature = "bybit")]
+            Exchange::Bybit => Arc::new(BybitOrderbookWS::with_depth(symbol, BybitCategory::Spot, depth)),
+            #[cfg(feature = "kraken")]
+            Exchange::Kraken => Arc::new(KrakenOrderbookWS::new(symbol, depth)),
+            #[cfg(feature = "kucoin")]
+            Exchange::Kucoin => Arc::new(KucoinOrderbookWS::new(symbol)),
+            #[cfg(feature = "gateio")]
+            Exchange::Gateio => Arc::new(GateioOrderbookWS::new(symbol, depth)),
+            // book P0, R0 (L3) dùng `BitfinexOrderbookWS::new_raw`
+            #[cfg(feature = "bitfinex")]
+            Exchange::Bitfinex => Arc::new(BitfinexOrderbookWS::new(symbol, depth)),
+            #[cfg(feature = "mexc")]
+            Exchange::Mexc => Arc::new(MexcOrderbookWS::new(symbol, depth)),
+            #[cfg(feature = "htx")]
+            Exchange::Htx => Arc::new(HtxOrderbookWS::new(symbol, depth)),
+            Exchange::Dex => panic!("dex pools are configured under `[dex]`, not `exchanges`"),
+            #[allow(unreachable_patterns)]
+            _ => panic!("exchange {} is not compiled in, rebuild with `--features {}`", self.name(), self.name()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecorderKind {
+    // JSONL / CSV, xem `recorder.tick`
+    Tick,
+    Parquet,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ParquetSettings {
+    pub root: PathBuf,
+    pub depth: usize,
+    pub sample_interval_ms: Option<u64>,
+    pub sampling: SamplingConfig,
+    pub batch_size: usize,
+}
+
+impl Default for ParquetSettings {
+    fn default() -> Self {
+        Self {
+            root: PathBuf::from("data/parquet"),
+            depth: 20,
+            sample_interval_ms: None,
+            sampling: SamplingConfig::default(),
+            batch_size: 1024,
+        }
+    }
+}
+
+impl ParquetSettings {
+    pub fn to_config(&self) -> ParquetConfig {
+        ParquetConfig {
+            root: self.root.clone(),
+            depth: self.depth,
+            sample_interval: self.sample_interval_ms.map(|ms| Duration::milliseconds(ms as i64)),
+            sampling: self.sampling.clone(),
+            batch_size: self.batch_size,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RecorderSettings {
+    pub enabled: bool,
+    pub kind: RecorderKind,
+    pub tick: TickRecorderConfig,
+    pub parquet: ParquetSettings,
+}
+
+impl Default for RecorderSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            kind: RecorderKind::Tick,
+            tick: TickRecorderConfig::default(),
+            parquet: ParquetSettings::default(),
+        }
+    }
+}
+
+// sàn có order entry của từng market Binance, dùng để lấy symbol / feed tương ứng
+impl From<BinanceMarket> for Exchange {
+    fn from(market: BinanceMarket) -> Self {
+        match market {
+            BinanceMarket::Spot => Exchange::Binance,
+            BinanceMarket::UsdM => Exchange::BinanceFutures,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VenueKind {
+    // khớp lệnh giả lập theo market data
+    Paper,
+    // Binance thật, cần BINANCE_API_KEY / BINANCE_API_SECRET. Account `default` là spot,
+    // account khác chọn spot / usdm qua `market`
+    Live,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct VenueSettings {
+    pub kind: VenueKind,
+    // None = lấy theo `[fees]` của binance
+    pub maker_fee_bps: Option<Decimal>,
+    pub taker_fee_bps: Option<Decimal>,
+    // chỉ áp dụng cho paper / backtest
+    pub slippage_bps: Decimal,
+    // số dư ban đầu của paper, vd. { USDT = 1000 }
+    pub paper_balances: HashMap<String, Decimal>,
+    // prefix clientOrderId của OMS khi chạy live
+    pub oms_prefix: String,
+    // account thêm ngoài `default` (BINANCE_API_KEY / BINANCE_API_SECRET)
+    pub accounts: BTreeMap<String, AccountSettings>,
+    // strategy -> account, vd. { market_maker = "sub1" }. Không có route thì dùng `default`
+    pub routes: HashMap<String, String>,
+}
+
+impl Default for VenueSettings {
+    fn default() -> Self {
+        Self {
+            kind: VenueKind::Paper,
+            maker_fee_bps: None,
+            taker_fee_bps: None,
+            slippage_bps: PaperConfig::default().slippage_bps,
+            paper_balances: HashMap::new(),
+            oms_prefix: "bsa".to_string(),
+            accounts: BTreeMap::new(),
+            routes: HashMap::new(),
+        }
+    }
+}
+
+impl VenueSettings {
+    // account của `strategy` theo `routes`
+    pub fn account_for(&self, strategy: &str) -> &str {
+        self.routes.get(strategy).map_or(DEFAULT_ACCOUNT, String::as_str)
+    }
+}
+
+// `[venue.accounts.<name>]`: account / subaccount riêng (balance, position, rate limit riêng).
+// Key không ghi trong file, chỉ ghi tên biến môi trường chứa key
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AccountSettings {
+    pub api_key_env: String,
+    pub api_secret_env: String,
+    // số dư paper riêng, rỗng = dùng `venue.paper_balances`
+    pub paper_balances: HashMap<String, Decimal>,
+    // "spot" | "usdm": order đi lên spot hay USDⓈ-M futures (perp)
+    pub market: BinanceMarket,
+}
+
+impl AccountSettings {
+    pub fn credentials(&self) -> Option<BinanceCredentials> {
+        BinanceCredentials::from_env_vars(&self.api_key_env, &self.api_secret_env)
+    }
+}
+
+// Stream depth của Binance spot: partial `@depth{N}` hoặc diff + REST snapshot (depth = limit)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct BinanceStreamSettings {
+    pub mode: DepthMode,
+    pub speed: UpdateSpeed,
+    // mainnet | testnet: feed, REST, user stream, venue live của binance và binance_futures
+    pub network: BinanceNetwork,
+    // endpoint spot thay thế theo thứ tự failover (chỉ với mainnet), rỗng = chỉ dùng mainnet:
+    //   endpoints = ["mainnet", "data", { name = "vpn", rest = "https://...", ws = "wss://..." }]
+    pub endpoints: Vec<EndpointSpec>,
+    // đo lại latency tới từng endpoint, 0 = chỉ đo lúc khởi động
+    pub probe_secs: u64,
+}
+
+impl Default for BinanceStreamSettings {
+    fn default() -> Self {
+        Self {
+            mode: DepthMode::Partial,
+            speed: UpdateSpeed::Ms100,
+            network: BinanceNetwork::Mainnet,
+            endpoints: Vec::new(),
+            probe_secs: 300,
+        }
+    }
+}
+
+impl BinanceStreamSettings {
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.endpoints.is_empty() {
+            return errors;
+        }
+        if !matches!(self.network, BinanceNetwork::Mainnet | BinanceNetwork::Pool(_)) {
+            errors.push("`binance.endpoints` requires `binance.network = \"mainnet\"`".to_string());
+        }
+        errors.extend(self.endpoints.iter().filter_map(|spec| spec.resolve().err()));
+        errors
+    }
+
+    // `endpoints` -> `BinanceNetwork::Pool`, gọi sau khi validate
+    fn apply_endpoints(&mut self) {
+        let endpoints: Vec<_> = self.endpoints.iter().filter_map(|spec| spec.resolve().ok()).collect();
+        if !endpoints.is_empty() && self.network == BinanceNetwork::Mainnet {
+            self.network = BinanceNetwork::Pool(EndpointPool::new(endpoints));
+        }
+    }
+}
+
+// Đồng bộ giờ với Binance `/api/v3/time`: timestamp request ký + latency của feed theo giờ sàn
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ClockSettings {
+    pub enabled: bool,
+    pub sync_secs: u64,
+}
+
+impl Default for ClockSettings {
+    fn default() -> Self {
+        Self { enabled: true, sync_secs: 60 }
+    }
+}
+
+// Server cho process khác đọc data, None = tắt
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ServerSettings {
+    pub grpc_addr: Option<SocketAddr>,
+    // WebSocket JSON cho dashboard, endpoint /ws
+    pub ws_addr: Option<SocketAddr>,
+    // HTTP JSON: /health, /orderbook/:symbol, /best/:symbol, /positions
+    pub http_addr: Option<SocketAddr>,
+}
+
+impl ServerSettings {
+    pub fn any_enabled(&self) -> bool {
+        self.grpc_addr.is_some() || self.ws_addr.is_some() || self.http_addr.is_some()
+    }
+}
+
+// Đẩy data ra hệ thống ngoài (lệnh `serve`)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SinkSettings {
+    pub redis: RedisSinkConfig,
+    // cần build với `--features kafka`
+    pub kafka: KafkaSinkConfig,
+    pub clickhouse: ClickHouseConfig,
+}
+
+impl SinkSettings {
+    pub fn any_enabled(&self) -> bool {
+        self.redis.enabled || self.kafka.enabled || self.clickhouse.enabled
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    pub symbols: Vec<String>,
+    pub depth: usize,
+    pub exchanges: Vec<Exchange>,
+    pub binance: BinanceStreamSettings,
+    pub clock: ClockSettings,
+    // kiểm tra invariant của book sau mỗi update
+    pub book_checks: IntegrityConfig,
+    // consumer chậm: bỏ book update nào (fill không bao giờ bị bỏ)
+    pub backpressure: BackpressureConfig,
+    // lưu book lúc tắt, nạp lại lúc khởi động
+    pub book_state: BookStateConfig,
+    pub recorder: RecorderSettings,
+    pub venue: VenueSettings,
+    // pre-trade check cho mọi order qua venue (mọi account), kill switch qua SIGUSR1
+    pub risk: RiskLimits,
+    // phí maker/taker theo sàn + VIP tier, dùng cho arb, backtest, PnL
+    pub fees: FeeModel,
+    // giá neo của market-make: microprice + trade + mid sàn khác
+    pub fair_value: FairValueConfig,
+    // market-make: hedge inventory bằng order ngược chiều
+    pub hedge: HedgeConfig,
+    pub server: ServerSettings,
+    pub sink: SinkSettings,
+    pub db: DbSettings,
+    pub alerts: AlertSettings,
+    // lấy mẫu book trước khi vào SignalEngine (serve, paper-trade, tui)
+    pub signal_sampling: SamplingConfig,
+    // số snapshot gần nhất SignalEngine giữ mỗi symbol cho signal cần book trễ
+    pub signal_history: HistoryConfig,
+    // pool PancakeSwap / Uniswap, feed thêm vào `feeds()` với exchange "dex"
+    pub dex: DexSettings,
+    // runtime riêng cho feed, pin core / nice
+    pub threads: ThreadSettings,
+    // symbol im lặng quá lâu (socket vẫn sống) thì resubscribe
+    pub stale: StaleSettings,
+    // permessage-deflate cho WS tới sàn
+    pub ws: WsSettings,
+    // proxy, DNS cố định, root CA cho cả WS và REST
+    pub network: NetworkSettings,
+    // tạm dừng quote / ghi data theo giờ giao dịch, cửa sổ bảo trì
+    pub schedule: ScheduleSettings,
+    // chốt ngày giao dịch: PnL, cắt file recorder / log, reset counter risk, báo cáo
+    pub rollover: RolloverSettings,
+    // báo cáo PnL theo strategy / symbol / signal
+    pub attribution: AttributionSettings,
+    // không đọc từ file: BINANCE_API_KEY / BINANCE_API_SECRET
+    #[serde(skip)]
+    pub binance_credentials: Option<BinanceCredentials>,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            symbols: vec!["cakebnb".to_string()],
+            depth: 20,
+            exchanges: vec![Exchange::Binance],
+            binance: BinanceStreamSettings::default(),
+            clock: ClockSettings::default(),
+            book_checks: IntegrityConfig::default(),
+            backpressure: BackpressureConfig::default(),
+            book_state: BookStateConfig::default(),
+            recorder: RecorderSettings::default(),
+            venue: VenueSettings::default(),
+            risk: RiskLimits::default(),
+            fees: FeeModel::default(),
+            fair_value: FairValueConfig::default(),
+            hedge: HedgeConfig::default(),
+            server: ServerSettings::default(),
+            sink: SinkSettings::default(),
+            db: DbSettings::default(),
+            alerts: AlertSettings::default(),
+            signal_sampling: SamplingConfig::default(),
+            signal_history: HistoryConfig::default(),
+            dex: DexSettings::default(),
+            threads: ThreadSettings::default(),
+            stale: StaleSettings::default(),
+            ws: WsSettings::default(),
+            network: NetworkSettings::default(),
+            schedule: ScheduleSettings::default(),
+            rollover: RolloverSettings::default(),
+            attribution: AttributionSettings::default(),
+            binance_credentials: None,
+        }
+    }
+}
+
+impl AppConfig {
+    // Thứ tự ưu tiên: default < file TOML < biến môi trường BSA_*.
+    // File không tồn tại thì dùng default.
+    pub fn load(path: &str) -> Result<Self, ConfigError> {
+        Self::from_figment(Figment::new().merge(Toml::file(path)).merge(Env::prefixed(ENV_PREFIX).split("__")))
+    }
+
+    pub fn from_toml_str(toml: &str) -> Result<Self, ConfigError> {
+        Self::from_figment(Figment::new().merge(Toml::string(toml)))
+    }
+
+    fn from_figment(figment: Figment) -> Result<Self, ConfigError> {
+        let mut config: AppConfig = figment.extract()?;
+        config.binance_credentials = BinanceCredentials::from_env();
+        config.validate()?;
+        config.binance.apply_endpoints();
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut errors = Vec::new();
+        if self.symbols.is_empty() {
+            errors.push("`symbols` must not be empty".to_string());
+        }
+        for s in &self.symbols {
+            if s.trim().is_empty() || s.chars().any(char::is_whitespace) {
+                errors.push(format!("invalid symbol {:?}", s));
+            } else if let Err(e) = Instrument::parse(Exchange::Binance, s) {
+                errors.push(format!("invalid symbol: {}", e));
+            }
+        }
+        if self.exchanges.is_empty() {
+            errors.push("`exchanges` must not be empty".to_string());
+        }
+        for ex in &self.exchanges {
+            if *ex == Exchange::Dex {
+                errors.push("`exchanges` must not contain dex, configure pools under `[dex]`".to_string());
+                continue;
+            }
+            if !ex.is_enabled() {
+                errors.push(format!("exchange {} is not compiled in, rebuild with `--features {}`", ex.name(), ex.name()));
+            }
+            // binance spot: depth hợp lệ tuỳ `binance.mode`
+            if *ex == Exchange::Binance {
+                if let Err(e) = self.binance.mode.validate_depth(self.depth) {
+                    errors.push(e);
+                }
+            } else if let Some(allowed) = ex.allowed_depths()
+                && !allowed.contains(&self.depth)
+            {
+                errors.push(format!("depth {} not supported by {} (allowed: {:?})", self.depth, ex.name(), allowed));
+            }
+        }
+
+        let recorder = &self.recorder;
+        if recorder.enabled {
+            let depth = match recorder.kind {
+                RecorderKind::Tick => recorder.tick.depth,
+                RecorderKind::Parquet => recorder.parquet.depth,
+            };
+            if depth == 0 {
+                errors.push("recorder depth must be > 0".to_string());
+            }
+            if recorder.kind == RecorderKind::Parquet && recorder.parquet.batch_size == 0 {
+                errors.push("`recorder.parquet.batch_size` must be > 0".to_string());
+            }
+            let sampling = match recorder.kind {
+                RecorderKind::Tick => &recorder.tick.sampling,
+                RecorderKind::Parquet => &recorder.parquet.sampling,
+            };
+            errors.extend(sampling.validate().into_iter().map(|e| format!("recorder sampling {}", e)));
+        }
+
+        if self.clock.enabled && self.clock.sync_secs == 0 {
+            errors.push("`clock.sync_secs` must be > 0".to_string());
+        }
+        errors.extend(self.binance.validate());
+        errors.extend(self.alerts.validate());
+        errors.extend(self.threads.validate());
+        errors.extend(self.stale.validate());
+        errors.extend(self.network.validate());
+        errors.extend(self.schedule.validate());
+        errors.extend(self.rollover.validate());
+        errors.extend(self.risk.validate());
+        errors.extend(self.signal_sampling.validate().into_iter().map(|e| format!("signal_sampling {}", e)));
+
+        let venue = &self.venue;
+        if venue.kind == VenueKind::Live && self.binance_credentials.is_none() {
+            errors.push("`venue.kind = \"live\"` requires BINANCE_API_KEY / BINANCE_API_SECRET".to_string());
+        }
+        for (name, account) in &venue.accounts {
+            if name == DEFAULT_ACCOUNT {
+                errors.push(format!("`venue.accounts.{}` is reserved for BINANCE_API_KEY / BINANCE_API_SECRET", name));
+            } else if account.api_key_env.is_empty() || account.api_secret_env.is_empty() {
+                errors.push(format!("`venue.accounts.{}` requires api_key_env and api_secret_env", name));
+            } else if venue.kind == VenueKind::Live && account.credentials().is_none() {
+                errors.push(format!("account {} requires {} / {}", name, account.api_key_env, account.api_secret_env));
+            }
+        }
+        for (strategy, account) in &venue.routes {
+            if account != DEFAULT_ACCOUNT && !venue.accounts.contains_key(account) {
+                errors.push(format!("`venue.routes.{}` points to unknown account {:?}", strategy, account));
+            }
+        }
+        let negative = |v: Option<Decimal>| v.is_some_and(|v| v < Decimal::ZERO);
+        if negative(venue.maker_fee_bps) || negative(venue.taker_fee_bps) || venue.slippage_bps < Decimal::ZERO {
+            errors.push("venue fees and slippage must be >= 0".to_string());
+        }
+        errors.extend(self.fees.validate());
+        if self.fair_value.enabled {
+            errors.extend(self.fair_value.validate());
+        }
+        if self.hedge.enabled {
+            errors.extend(self.hedge.validate());
+            // feed của sàn hedge để paper khớp và mark vị thế hedge
+            let exchange = self.hedge_exchange();
+            if !self.exchanges.contains(&exchange) {
+                errors.push(format!("`hedge` places orders on {0}, `exchanges` must include {0}", exchange.name()));
+            }
+            if let Some(symbol) = &self.hedge.symbol
+                && !self.venue_symbols(exchange).iter().any(|s| s.eq_ignore_ascii_case(symbol))
+            {
+                errors.push(format!("`hedge.symbol` {:?} must be a {} symbol listed in `symbols`", symbol, exchange.name()));
+            }
+        }
+        if self.dex.enabled {
+            errors.extend(self.dex.validate());
+        }
+        let clickhouse = &self.sink.clickhouse;
+        if clickhouse.enabled && (clickhouse.batch_size == 0 || clickhouse.depth == 0 && clickhouse.levels) {
+            errors.push("`sink.clickhouse` batch_size and depth must be > 0".to_string());
+        }
+        if self.sink.kafka.enabled && !cfg!(feature = "kafka") {
+            errors.push("`sink.kafka.enabled` requires building with `--features kafka`".to_string());
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(errors))
+        }
+    }
+
+    // clock chỉ cần khi có feed Binance hoặc đặt lệnh live
+    pub fn needs_clock(&self) -> bool {
+        self.clock.enabled
+            && (self.venue.kind == VenueKind::Live
+                || self.exchanges.iter().any(|e| matches!(e, Exchange::Binance | Exchange::BinanceFutures)))
+    }
+
+    // mỗi (exchange, symbol) một instrument, symbol viết dạng chuẩn ("BTC/USDT")
+    // hoặc dạng của bất kỳ sàn nào ("btcusdt", "BTC-USDT"), bỏ qua symbol không parse được
+    pub fn instruments(&self) -> Vec<Instrument> {
+        self.exchanges
+            .iter()
+            .flat_map(|ex| self.symbols.iter().filter_map(move |s| Instrument::parse(*ex, s).ok()))
+            .collect()
+    }
+
+    // `symbols` viết theo dạng của `exchange` (kể cả khi exchange không nằm trong `exchanges`)
+    pub fn venue_symbols(&self, exchange: Exchange) -> Vec<String> {
+        self.symbols
+            .iter()
+            .map(|s| Instrument::parse(exchange, s).map_or_else(|_| s.clone(), |i| i.venue_symbol()))
+            .collect()
+    }
+
+    // sàn của order hedge theo `market` của account mà `[venue.routes] hedger` trỏ tới
+    pub fn hedge_exchange(&self) -> Exchange {
+        let account = self.venue.account_for(hedger::STRATEGY);
+        self.venue.accounts.get(account).map_or(BinanceMarket::Spot, |a| a.market).into()
+    }
+
+    // paper / backtest khớp trên binance: phí `[venue]` nếu có, không thì theo `[fees]`
+    pub fn paper_config(&self) -> PaperConfig {
+        let fees = self.fees.schedule(Exchange::Binance);
+        PaperConfig {
+            maker_fee_bps: self.venue.maker_fee_bps.unwrap_or(fees.maker_bps),
+            taker_fee_bps: self.venue.taker_fee_bps.unwrap_or(fees.taker_bps),
+            slippage_bps: self.venue.slippage_bps,
+        }
+    }
+
+    // mỗi (exchange, symbol) một feed, symbol đổi sang dạng của từng sàn, cộng pool `[dex]`
+    pub fn feeds(&self) -> Vec<Arc<dyn OrderbookFeed>> {
+        let mut feeds: Vec<Arc<dyn OrderbookFeed>> = self
+            .instruments()
+            .iter()
+            .map(|inst| inst.venue.feed(&inst.venue_symbol(), self.depth, &self.binance))
+            .collect();
+        if self.dex.enabled {
+            feeds.extend(self.dex.feeds());
+        }
+        feeds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // feed kraken cần feature `kraken`
+    #[cfg(feature = "kraken")]
+    #[test]
+    fn test_parse_toml() {
+        use crate::recorder::tick::TickFormat;
+        use crate::sink::kafka::KafkaFormat;
+
+        let config = AppConfig::from_toml_str(
+            r#"
+            symbols = ["btcusdt", "ethusdt"]
+            depth = 10
+            exchanges = ["binance", "kraken"]
+
+            [binance]
+            mode = "diff"
+            speed = "1000ms"
+            network = "testnet"
+
+            [recorder]
+            enabled = true
+            kind = "tick"
+
+            [recorder.tick]
+            root = "/tmp/ticks"
+            format = "csv"
+
+            [server]
+            grpc_addr = "127.0.0.1:50051"
+            http_addr = "127.0.0.1:8080"
+
+            [sink.redis]
+            enabled = true
+            stream_maxlen = 10000
+
+            [sink.kafka]
+            format = "avro"
+            topics = { trades = "raw.trades" }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.symbols, vec!["btcusdt", "ethusdt"]);
+        assert_eq!(config.exchanges, vec![Exchange::Binance, Exchange::Kraken]);
+        assert_eq!((config.binance.mode, config.binance.speed), (DepthMode::Full, UpdateSpeed::Ms1000));
+        assert_eq!(config.binance.network.rest_url(), "https://testnet.binance.vision");
+        assert_eq!(config.recorder.tick.format, TickFormat::Csv);
+        // field không khai báo lấy default
+        assert_eq!(config.recorder.tick.depth, 20);
+        let feeds = config.feeds();
+        assert_eq!(feeds.len(), 4);
+        assert_eq!((feeds[1].symbol(), feeds[3].symbol()), ("ethusdt", "ETH/USDT"));
+        assert_eq!(config.server.grpc_addr.unwrap().port(), 50051);
+        assert_eq!(config.server.http_addr.map(|a| a.port()), Some(8080));
+        assert_eq!((config.sink.redis.prefix.as_str(), config.sink.redis.stream_maxlen), ("md", Some(10000)));
+        assert_eq!((config.sink.kafka.format, config.sink.kafka.topics.trades.as_str()), (KafkaFormat::Avro, "raw.trades"));
+        assert_eq!(config.sink.kafka.topics.fills, "md.fills");
+
+        let defaults = AppConfig::from_toml_str("").unwrap();
+        assert_eq!((defaults.symbols[0].as_str(), defaults.depth), ("cakebnb", 20));
+        assert_eq!(defaults.binance.network, BinanceNetwork::Mainnet);
+
+        let pooled = AppConfig::from_toml_str(
+            r#"
+            [binance]
+            endpoints = ["data", { name = "vpn", rest = "https://10.0.0.1", ws = "wss://10.0.0.1:9443" }]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(pooled.binance.network.ws_url(), "wss://data-stream.binance.vision");
+        assert_eq!(pooled.binance.network.trading_rest_url(), "https://10.0.0.1");
+        let err = AppConfig::from_toml_str("[binance]\nnetwork = \"testnet\"\nendpoints = [\"eu\"]").unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid(errors) if errors.len() == 2));
+    }
+
+    #[test]
+    fn test_validation_reports_all_errors() {
+        let err = AppConfig::from_toml_str(
+            r#"
+            symbols = []
+            depth = 25
+            exchanges = ["binance", "kraken"]
+            "#,
+        )
+        .unwrap_err();
+        let ConfigError::Invalid(errors) = &err else {
+            panic!("expected validation error, got {}", err);
+        };
+        // symbols rỗng + depth 25 không hợp lệ với binance (kraken thì được),
+        // build không có feature `kraken` thì thêm lỗi sàn chưa compile
+        assert_eq!(errors.len(), if Exchange::Kraken.is_enabled() { 2 } else { 3 });
+        assert!(err.to_string().contains("depth 25 not supported by binance"));
+        assert_eq!(err.to_string().contains("kraken is not compiled in"), !Exchange::Kraken.is_enabled());
+
+        let err = AppConfig::from_toml_str(r#"exchanges = ["ftx"]"#).unwrap_err();
+        assert!(matches!(err, ConfigError::Load(_)));
+
+        // symbol hedge phải có trong `symbols` của sàn mà account hedge đặt order
+        let hedge = "symbols = [\"BTC/USDT\"]\n[hedge]\nenabled = true\nthreshold = 0.1\nsymbol = ";
+        assert!(AppConfig::from_toml_str(&format!("{}\"btcusdt\"", hedge)).is_ok());
+        let err = AppConfig::from_toml_str(&format!("{}\"BTCUSDT_PERP\"", hedge)).unwrap_err();
+        assert!(err.to_string().contains("must be a binance symbol listed in `symbols`"));
+        // account usdm: hedge trên perp, cần feed binance_futures
+        let perp = "[venue.accounts.perp]\nmarket = \"usdm\"\napi_key_env = \"PERP_KEY\"\napi_secret_env = \"PERP_SECRET\"\n[venue.routes]\nhedger = \"perp\"\n";
+        let config = AppConfig::from_toml_str(&format!("exchanges = [\"binance\", \"binance_futures\"]\n{}\"btcusdt\"\n{}", hedge, perp)).unwrap();
+        assert_eq!(config.hedge_exchange(), Exchange::BinanceFutures);
+        let err = AppConfig::from_toml_str(&format!("exchanges = [\"binance\"]\n{}\"btcusdt\"\n{}", hedge, perp)).unwrap_err();
+        assert!(err.to_string().contains("`exchanges` must include binance_futures"));
+    }
+
+    #[test]
+    fn test_venue_settings() {
+        let config = AppConfig::from_toml_str(
+            r#"
+            [venue]
+            kind = "paper"
+            taker_fee_bps = 7.5
+            paper_balances = { USDT = 1000 }
+            routes = { market_maker = "sub1" }
+
+            [venue.accounts.sub1]
+            api_key_env = "SUB1_API_KEY"
+            api_secret_env = "SUB1_API_SECRET"
+
+            [fees]
+            tiers = { binance = 3 }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.venue.kind, VenueKind::Paper);
+        assert_eq!((config.venue.account_for("market_maker"), config.venue.account_for("arb")), ("sub1", DEFAULT_ACCOUNT));
+        assert_eq!(config.paper_config().taker_fee_bps, Decimal::new(75, 1));
+        assert_eq!(config.paper_config().maker_fee_bps, Decimal::new(42, 1));
+        assert_eq!(config.venue.paper_balances["USDT"], Decimal::from(1000));
+
+        // live mà không có key thì báo lỗi ngay khi load
+        let mut config = AppConfig::default();
+        config.venue.kind = VenueKind::Live;
+        config.binance_credentials = None;
+        assert!(config.validate().unwrap_err().to_string().contains("venue.kind"));
+
+        let err = AppConfig::from_toml_str("[venue]\nroutes = { market_maker = \"sub2\" }").unwrap_err();
+        assert!(err.to_string().contains("unknown account \"sub2\""));
+    }
+}
@@ -0,0 +1,214 @@
+use rust_decimal::Decimal;
+
+use super::orderbook::{Level, OrderbookSnapshot, Side};
+
+const BPS: Decimal = Decimal::from_parts(10_000, 0, 0, false, 0);
+
+// Độ rộng bucket khi gom depth
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceBucket {
+    // bội số cố định của giá, vd. 10 x tick size
+    Step(Decimal),
+    // theo bps của mid, vd. 10 = dải 0.1%
+    Bps(Decimal),
+}
+
+// Ladder đã gom, price là mép bucket: bid làm tròn xuống, ask làm tròn lên
+#[derive(Debug, Clone, PartialEq)]
+pub struct BucketLadder {
+    pub step: Decimal,
+    // giá giảm dần
+    pub bids: Vec<Level>,
+    // giá tăng dần
+    pub asks: Vec<Level>,
+}
+
+// `levels` từ best ra ngoài, mỗi bucket cộng dồn qty, tối đa `max` bucket
+fn aggregate<'a>(
+    levels: impl Iterator<Item = (&'a Decimal, &'a Decimal)>,
+    max: usize,
+    bucket_of: impl Fn(Decimal) -> Decimal,
+) -> Vec<Level> {
+    let mut out: Vec<Level> = Vec::new();
+    for (price, qty) in levels {
+        let bucket = bucket_of(*price);
+        if let Some((p, q)) = out.last_mut()
+            && *p == bucket
+        {
+            *q += qty;
+        } else if out.len() == max {
+            break;
+        } else {
+            out.push((bucket, *qty));
+        }
+    }
+    out
+}
+
+// Các chỉ số depth cơ bản tính trực tiếp trên snapshot
+impl OrderbookSnapshot {
+    pub fn mid_price(&self) -> Option<Decimal> {
+        let ((bid, _), (ask, _)) = self.best_bid_ask()?;
+        Some((bid + ask) / Decimal::TWO)
+    }
+
+    pub fn spread(&self) -> Option<Decimal> {
+        let ((bid, _), (ask, _)) = self.best_bid_ask()?;
+        Some(ask - bid)
+    }
+
+    pub fn spread_bps(&self) -> Option<Decimal> {
+        let mid = self.mid_price()?;
+        if mid.is_zero() {
+            return None;
+        }
+        Some(self.spread()? / mid * BPS)
+    }
+
+    // mid có trọng số theo size đối diện: bid nhiều thì giá nghiêng về ask
+    pub fn microprice(&self) -> Option<Decimal> {
+        let ((bid, bid_qty), (ask, ask_qty)) = self.best_bid_ask()?;
+        let total = bid_qty + ask_qty;
+        if total.is_zero() {
+            return None;
+        }
+        Some((bid * ask_qty + ask * bid_qty) / total)
+    }
+
+    // Giá trung bình khi quét `size` trên một phía của book
+    // (Side::Ask = market buy ăn asks, Side::Bid = market sell ăn bids).
+    // None nếu book không đủ thanh khoản
+    pub fn vwap_for_size(&self, side: Side, size: Decimal) -> Option<Decimal> {
+        if size <= Decimal::ZERO {
+            return None;
+        }
+        let levels: Box<dyn Iterator<Item = (&Decimal, &Decimal)>> = match side {
+            Side::Bid => Box::new(self.bids.iter().rev()),
+            Side::Ask => Box::new(self.asks.iter()),
+        };
+
+        let mut remaining = size;
+        let mut notional = Decimal::ZERO;
+        for (price, qty) in levels {
+            let fill = remaining.min(*qty);
+            notional += fill * price;
+            remaining -= fill;
+            if remaining.is_zero() {
+                return Some(notional / size);
+            }
+        }
+        None
+    }
+
+    // (bid - ask) / (bid + ask) trên `levels` level đầu mỗi phía, trong [-1, 1]
+    pub fn imbalance(&self, levels: usize) -> Option<Decimal> {
+        let bid: Decimal = self.bids.values().rev().take(levels).sum();
+        let ask: Decimal = self.asks.values().take(levels).sum();
+        let total = bid + ask;
+        if total.is_zero() {
+            return None;
+        }
+        Some((bid - ask) / total)
+    }
+
+    // Tổng qty của một phía nằm trong khoảng `bps` basis point quanh mid
+    pub fn liquidity_within_bps(&self, side: Side, bps: Decimal) -> Decimal {
+        let Some(mid) = self.mid_price() else {
+            return Decimal::ZERO;
+        };
+        let band = mid * bps / BPS;
+        match side {
+            Side::Bid => self.bids.range(mid - band..).map(|(_, q)| *q).sum(),
+            Side::Ask => self.asks.range(..=mid + band).map(|(_, q)| *q).sum(),
+        }
+    }
+
+    // Gom book thành ladder gọn theo bucket giá để vẽ hoặc tính signal không nhạy với level lẻ.
+    // `levels` = số bucket mỗi phía (0 = tất cả). None nếu step <= 0 hoặc Bps mà book trống
+    pub fn bucketed(&self, bucket: PriceBucket, levels: usize) -> Option<BucketLadder> {
+        let step = match bucket {
+            PriceBucket::Step(step) => step,
+            PriceBucket::Bps(bps) => self.mid_price()? * bps / BPS,
+        };
+        if step <= Decimal::ZERO {
+            return None;
+        }
+        let max = if levels == 0 { usize::MAX } else { levels };
+        Some(BucketLadder {
+            step,
+            bids: aggregate(self.bids.iter().rev(), max, |p| (p / step).floor() * step),
+            asks: aggregate(self.asks.iter(), max, |p| (p / step).ceil() * step),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn book() -> OrderbookSnapshot {
+        let mut ob = OrderbookSnapshot::new();
+        ob.set_level(Side::Bid, dec!(99.9), dec!(3));
+        ob.set_level(Side::Bid, dec!(99.8), dec!(2));
+        ob.set_level(Side::Bid, dec!(99.0), dec!(10));
+        ob.set_level(Side::Ask, dec!(100.1), dec!(1));
+        ob.set_level(Side::Ask, dec!(100.2), dec!(4));
+        ob.set_level(Side::Ask, dec!(101.0), dec!(10));
+        ob
+    }
+
+    #[test]
+    fn test_mid_spread_and_microprice() {
+        let ob = book();
+        assert_eq!(ob.mid_price(), Some(dec!(100.0)));
+        assert_eq!(ob.spread(), Some(dec!(0.2)));
+        assert_eq!(ob.spread_bps(), Some(dec!(20)));
+        // (99.9 * 1 + 100.1 * 3) / 4
+        assert_eq!(ob.microprice(), Some(dec!(100.05)));
+        assert_eq!(OrderbookSnapshot::new().microprice(), None);
+    }
+
+    #[test]
+    fn test_vwap_for_size() {
+        let ob = book();
+        // 1 @ 100.1 + 2 @ 100.2
+        assert_eq!(ob.vwap_for_size(Side::Ask, dec!(3)), Some(dec!(300.5) / dec!(3)));
+        // 3 @ 99.9 + 1 @ 99.8
+        assert_eq!(ob.vwap_for_size(Side::Bid, dec!(4)), Some(dec!(99.875)));
+        assert_eq!(ob.vwap_for_size(Side::Ask, dec!(100)), None);
+        assert_eq!(ob.vwap_for_size(Side::Ask, Decimal::ZERO), None);
+    }
+
+    #[test]
+    fn test_imbalance_and_liquidity_within_bps() {
+        let ob = book();
+        // top 2: bid 5, ask 5
+        assert_eq!(ob.imbalance(2), Some(dec!(0)));
+        // top 1: bid 3, ask 1
+        assert_eq!(ob.imbalance(1), Some(dec!(0.5)));
+
+        // 25 bps quanh 100 -> [99.75, 100.25]
+        assert_eq!(ob.liquidity_within_bps(Side::Bid, dec!(25)), dec!(5));
+        assert_eq!(ob.liquidity_within_bps(Side::Ask, dec!(25)), dec!(5));
+        assert_eq!(ob.liquidity_within_bps(Side::Ask, dec!(100)), dec!(15));
+    }
+
+    #[test]
+    fn test_bucketed() {
+        let ob = book();
+        let ladder = ob.bucketed(PriceBucket::Step(dec!(0.5)), 0).unwrap();
+        // 99.9 + 99.8 -> 99.5, 100.1 + 100.2 -> 100.5
+        assert_eq!(ladder.bids, vec![(dec!(99.5), dec!(5)), (dec!(99.0), dec!(10))]);
+        assert_eq!(ladder.asks, vec![(dec!(100.5), dec!(5)), (dec!(101.0), dec!(10))]);
+
+        // 10 bps của mid 100 = step 0.1, giữ 2 bucket mỗi phía
+        let ladder = ob.bucketed(PriceBucket::Bps(dec!(10)), 2).unwrap();
+        assert_eq!(ladder.step, dec!(0.1));
+        assert_eq!(ladder.bids.len(), 2);
+        assert_eq!(ladder.asks[1], (dec!(100.2), dec!(4)));
+
+        assert_eq!(ob.bucketed(PriceBucket::Step(Decimal::ZERO), 0), None);
+        assert_eq!(OrderbookSnapshot::new().bucketed(PriceBucket::Bps(dec!(10)), 0), None);
+    }
+}
@@ -0,0 +1,236 @@
+use futures_util::Stream;
+use serde::{Deserialize, Serialize};
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+use tokio::sync::{broadcast, mpsc};
+use tracing::warn;
+
+// Consumer không theo kịp thì channel market data (book update, level event) có giới hạn
+// và bỏ message theo `DropPolicy`. Fill không bao giờ bị bỏ (`LosslessBus`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DropPolicy {
+    // ghi đè bản cũ nhất consumer chậm chưa đọc, consumer nhận `Lagged`
+    #[default]
+    DropOldest,
+    // giữ nguyên queue, bỏ bản mới tới khi consumer đọc bớt
+    DropNewest,
+}
+
+// `[backpressure]`
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct BackpressureConfig {
+    // book update + level event của mọi feed
+    pub snapshots: DropPolicy,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct DropStats {
+    pub sent: u64,
+    pub dropped: u64,
+}
+
+// broadcast có giới hạn, đếm message bị bỏ khi queue của consumer chậm nhất đã đầy
+#[derive(Debug)]
+pub struct BoundedBroadcast<T> {
+    tx: broadcast::Sender<T>,
+    capacity: usize,
+    drop_newest: AtomicBool,
+    sent: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl<T: Clone> BoundedBroadcast<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            tx: broadcast::channel(capacity).0,
+            capacity,
+            drop_newest: AtomicBool::new(false),
+            sent: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    pub fn set_policy(&self, policy: DropPolicy) {
+        self.drop_newest.store(policy == DropPolicy::DropNewest, Ordering::Relaxed);
+    }
+
+    pub fn policy(&self) -> DropPolicy {
+        if self.drop_newest.load(Ordering::Relaxed) { DropPolicy::DropNewest } else { DropPolicy::DropOldest }
+    }
+
+    // không có subscriber thì không gửi, cũng không tính là bỏ
+    pub fn send(&self, value: T) -> bool {
+        if self.tx.receiver_count() == 0 {
+            return false;
+        }
+        if self.tx.len() >= self.capacity {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            if self.drop_newest.load(Ordering::Relaxed) {
+                return false;
+            }
+        }
+        self.sent.fetch_add(1, Ordering::Relaxed);
+        self.tx.send(value).is_ok()
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<T> {
+        self.tx.subscribe()
+    }
+
+    pub fn receiver_count(&self) -> usize {
+        self.tx.receiver_count()
+    }
+
+    pub fn stats(&self) -> DropStats {
+        DropStats { sent: self.sent.load(Ordering::Relaxed), dropped: self.dropped.load(Ordering::Relaxed) }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct QueueStats {
+    pub sent: u64,
+    // số message chờ lớn nhất từng thấy ở một subscriber
+    pub max_depth: usize,
+}
+
+// Fan-out không mất message: mỗi subscriber một queue riêng, producer không bao giờ phải chờ
+// (fill đến từ cả code sync). Queue vượt `warn_depth` thì log để consumer kẹt không âm thầm
+// làm phình bộ nhớ
+#[derive(Debug)]
+pub struct LosslessBus<T> {
+    subscribers: Mutex<Vec<(mpsc::UnboundedSender<T>, Arc<AtomicUsize>)>>,
+    warn_depth: usize,
+    sent: AtomicU64,
+    max_depth: AtomicUsize,
+}
+
+impl<T: Clone> LosslessBus<T> {
+    pub fn new(warn_depth: usize) -> Self {
+        Self { subscribers: Mutex::new(Vec::new()), warn_depth, sent: AtomicU64::new(0), max_depth: AtomicUsize::new(0) }
+    }
+
+    // chỉ nhận message gửi sau khi subscribe
+    pub fn subscribe(&self) -> LosslessReceiver<T> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let depth = Arc::new(AtomicUsize::new(0));
+        self.subscribers.lock().unwrap_or_else(|e| e.into_inner()).push((tx, depth.clone()));
+        LosslessReceiver { rx, depth }
+    }
+
+    pub fn send(&self, value: T) {
+        let mut subscribers = self.subscribers.lock().unwrap_or_else(|e| e.into_inner());
+        // subscriber đã drop receiver thì bỏ khỏi danh sách
+        subscribers.retain(|(tx, depth)| {
+            // tăng trước khi gửi: receiver ở thread khác có thể đọc (và giảm) ngay sau `send`
+            let queued = depth.fetch_add(1, Ordering::Relaxed) + 1;
+            if tx.send(value.clone()).is_err() {
+                return false;
+            }
+            self.max_depth.fetch_max(queued, Ordering::Relaxed);
+            if queued == self.warn_depth {
+                warn!(queued, "lossless subscriber falling behind");
+            }
+            true
+        });
+        self.sent.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn receiver_count(&self) -> usize {
+        self.subscribers.lock().unwrap_or_else(|e| e.into_inner()).iter().filter(|(tx, _)| !tx.is_closed()).count()
+    }
+
+    pub fn stats(&self) -> QueueStats {
+        QueueStats { sent: self.sent.load(Ordering::Relaxed), max_depth: self.max_depth.load(Ordering::Relaxed) }
+    }
+}
+
+#[derive(Debug)]
+pub struct LosslessReceiver<T> {
+    rx: mpsc::UnboundedReceiver<T>,
+    depth: Arc<AtomicUsize>,
+}
+
+impl<T> LosslessReceiver<T> {
+    // None khi bus đã bị drop và queue đã đọc hết
+    pub async fn recv(&mut self) -> Option<T> {
+        let value = self.rx.recv().await?;
+        self.depth.fetch_sub(1, Ordering::Relaxed);
+        Some(value)
+    }
+
+    pub fn try_recv(&mut self) -> Result<T, mpsc::error::TryRecvError> {
+        let value = self.rx.try_recv()?;
+        self.depth.fetch_sub(1, Ordering::Relaxed);
+        Ok(value)
+    }
+
+    // số message đang chờ đọc
+    pub fn len(&self) -> usize {
+        self.depth.load(Ordering::Relaxed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn into_stream(self) -> impl Stream<Item = T> {
+        futures_util::stream::unfold(self, |mut rx| async move { rx.recv().await.map(|v| (v, rx)) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drop_policies() {
+        let bus = BoundedBroadcast::new(4);
+        // chưa có subscriber: không tính là bỏ
+        assert!(!bus.send(0));
+        let mut rx = bus.subscribe();
+        for i in 1..=6 {
+            bus.send(i);
+        }
+        // drop_oldest: 1, 2 bị ghi đè, consumer nhận Lagged rồi đọc tiếp từ 3
+        assert_eq!(bus.stats(), DropStats { sent: 6, dropped: 2 });
+        assert!(matches!(rx.try_recv(), Err(broadcast::error::TryRecvError::Lagged(2))));
+        assert_eq!(rx.try_recv().unwrap(), 3);
+
+        let bus = BoundedBroadcast::new(4);
+        bus.set_policy(DropPolicy::DropNewest);
+        let mut rx = bus.subscribe();
+        for i in 1..=6 {
+            bus.send(i);
+        }
+        // drop_newest: giữ 1..=4, bỏ 5, 6
+        assert_eq!(bus.stats(), DropStats { sent: 4, dropped: 2 });
+        assert_eq!((0..4).map(|_| rx.try_recv().unwrap()).collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert!(bus.send(7));
+        assert_eq!(rx.try_recv().unwrap(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_lossless_bus_keeps_every_message() {
+        let bus = LosslessBus::new(100);
+        let mut slow = bus.subscribe();
+        let mut fast = bus.subscribe();
+        for i in 0..1000 {
+            bus.send(i);
+            assert_eq!(fast.recv().await, Some(i));
+        }
+        // consumer chậm vẫn nhận đủ, đúng thứ tự
+        assert_eq!((slow.len(), bus.stats()), (1000, QueueStats { sent: 1000, max_depth: 1000 }));
+        for i in 0..1000 {
+            assert_eq!(slow.try_recv().unwrap(), i);
+        }
+        assert!(slow.is_empty());
+
+        drop(fast);
+        bus.send(1);
+        assert_eq!(bus.receiver_count(), 1);
+    }
+}
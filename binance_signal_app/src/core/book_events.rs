@@ -0,0 +1,151 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::cmp::Ordering;
+
+use super::orderbook::{BookSide, OrderbookSnapshot, Side};
+
+// Thay đổi của một level giữa hai snapshot liên tiếp
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookEvent {
+    LevelAdded { side: Side, price: Decimal, qty: Decimal },
+    LevelRemoved { side: Side, price: Decimal, old_qty: Decimal },
+    LevelChanged { side: Side, price: Decimal, old_qty: Decimal, new_qty: Decimal },
+}
+
+impl BookEvent {
+    pub fn side(&self) -> Side {
+        match self {
+            BookEvent::LevelAdded { side, .. }
+            | BookEvent::LevelRemoved { side, .. }
+            | BookEvent::LevelChanged { side, .. } => *side,
+        }
+    }
+
+    pub fn price(&self) -> Decimal {
+        match self {
+            BookEvent::LevelAdded { price, .. }
+            | BookEvent::LevelRemoved { price, .. }
+            | BookEvent::LevelChanged { price, .. } => *price,
+        }
+    }
+
+    // qty mới - qty cũ, âm khi level bị ăn / huỷ
+    pub fn qty_delta(&self) -> Decimal {
+        match self {
+            BookEvent::LevelAdded { qty, .. } => *qty,
+            BookEvent::LevelRemoved { old_qty, .. } => -*old_qty,
+            BookEvent::LevelChanged { old_qty, new_qty, .. } => new_qty - old_qty,
+        }
+    }
+}
+
+// Các event của một lần update book
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookDiff {
+    pub timestamp: DateTime<Utc>,
+    pub last_update_id: u64,
+    // bid trước, ask sau, mỗi bên theo giá tăng dần
+    pub events: Vec<BookEvent>,
+    // best bid / ask trước update, để nhận biết thay đổi ở top of book
+    pub prev_best_bid: Option<Decimal>,
+    pub prev_best_ask: Option<Decimal>,
+}
+
+impl BookDiff {
+    pub fn between(prev: &OrderbookSnapshot, next: &OrderbookSnapshot) -> Self {
+        Self {
+            timestamp: next.timestamp,
+            last_update_id: next.last_update_id,
+            events: diff(prev, next),
+            prev_best_bid: prev.best_bid().map(|(p, _)| p),
+            prev_best_ask: prev.best_ask().map(|(p, _)| p),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    // event tại best bid / ask trước update (vd. queue ở best bid bị ăn dần)
+    pub fn at_prev_top(&self, side: Side) -> Option<&BookEvent> {
+        let top = match side {
+            Side::Bid => self.prev_best_bid,
+            Side::Ask => self.prev_best_ask,
+        }?;
+        self.events.iter().find(|e| e.side() == side && e.price() == top)
+    }
+}
+
+// Merge hai book đã sort, O(n) theo số level
+pub fn diff(prev: &OrderbookSnapshot, next: &OrderbookSnapshot) -> Vec<BookEvent> {
+    let mut events = Vec::new();
+    diff_side(Side::Bid, &prev.bids, &next.bids, &mut events);
+    diff_side(Side::Ask, &prev.asks, &next.asks, &mut events);
+    events
+}
+
+fn diff_side(side: Side, prev: &BookSide, next: &BookSide, out: &mut Vec<BookEvent>) {
+    let mut a = prev.iter().peekable();
+    let mut b = next.iter().peekable();
+    loop {
+        let order = match (a.peek(), b.peek()) {
+            (None, None) => break,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some((pa, _)), Some((pb, _))) => pa.cmp(pb),
+        };
+        match order {
+            Ordering::Less => {
+                let (&price, &old_qty) = a.next().unwrap();
+                out.push(BookEvent::LevelRemoved { side, price, old_qty });
+            }
+            Ordering::Greater => {
+                let (&price, &qty) = b.next().unwrap();
+                out.push(BookEvent::LevelAdded { side, price, qty });
+            }
+            Ordering::Equal => {
+                let (&price, &old_qty) = a.next().unwrap();
+                let (_, &new_qty) = b.next().unwrap();
+                if old_qty != new_qty {
+                    out.push(BookEvent::LevelChanged { side, price, old_qty, new_qty });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_diff_snapshots() {
+        let mut prev = OrderbookSnapshot::new();
+        prev.set_level(Side::Bid, dec!(99), dec!(1));
+        prev.set_level(Side::Bid, dec!(100), dec!(2));
+        prev.set_level(Side::Ask, dec!(101), dec!(3));
+
+        let mut next = prev.clone();
+        next.set_level(Side::Bid, dec!(100), dec!(0.5));
+        next.set_level(Side::Bid, dec!(98), dec!(4));
+        next.set_level(Side::Ask, dec!(101), Decimal::ZERO);
+        next.set_level(Side::Ask, dec!(102), dec!(1));
+
+        let diff = BookDiff::between(&prev, &next);
+        assert_eq!(
+            diff.events,
+            vec![
+                BookEvent::LevelAdded { side: Side::Bid, price: dec!(98), qty: dec!(4) },
+                BookEvent::LevelChanged { side: Side::Bid, price: dec!(100), old_qty: dec!(2), new_qty: dec!(0.5) },
+                BookEvent::LevelRemoved { side: Side::Ask, price: dec!(101), old_qty: dec!(3) },
+                BookEvent::LevelAdded { side: Side::Ask, price: dec!(102), qty: dec!(1) },
+            ]
+        );
+        assert_eq!(diff.at_prev_top(Side::Bid).unwrap().qty_delta(), dec!(-1.5));
+        assert_eq!(diff.at_prev_top(Side::Ask).unwrap().qty_delta(), dec!(-3));
+
+        // book không đổi -> không có event
+        assert!(BookDiff::between(&next, &next).is_empty());
+    }
+}
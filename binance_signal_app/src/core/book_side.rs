@@ -0,0 +1,165 @@
+use rust_decimal::Decimal;
+use std::{
+    iter::Zip,
+    ops::{Bound, RangeBounds},
+    slice,
+};
+
+// Một phía của book dạng SoA: giá và qty ở hai Vec song song, sort tăng dần theo giá.
+// Nhiều symbol thì book nhỏ gọn hơn BTreeMap (không có node, clone = 2 memcpy) và
+// tìm level là binary search trên một mảng giá liền nhau. API bám theo phần BTreeMap
+// mà code đang dùng để đổi qua lại bằng feature `btree-book`.
+// Giữ Decimal thay vì u64 tick: feed của các sàn không có tick/lot size (chỉ venue
+// Binance tải exchangeInfo), và API trả `&Decimal` như BTreeMap. 32 byte mỗi level
+// so với 16 byte nếu dùng tick; snapshot 1000 level x2 phía vẫn chỉ ~64KB
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BookSide {
+    prices: Vec<Decimal>,
+    qtys: Vec<Decimal>,
+}
+
+pub type Iter<'a> = Zip<slice::Iter<'a, Decimal>, slice::Iter<'a, Decimal>>;
+
+impl BookSide {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { prices: Vec::with_capacity(capacity), qtys: Vec::with_capacity(capacity) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.prices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prices.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.prices.clear();
+        self.qtys.clear();
+    }
+
+    pub fn insert(&mut self, price: Decimal, qty: Decimal) -> Option<Decimal> {
+        match self.prices.binary_search(&price) {
+            Ok(i) => Some(std::mem::replace(&mut self.qtys[i], qty)),
+            Err(i) => {
+                self.prices.insert(i, price);
+                self.qtys.insert(i, qty);
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, price: &Decimal) -> Option<Decimal> {
+        let i = self.prices.binary_search(price).ok()?;
+        self.prices.remove(i);
+        Some(self.qtys.remove(i))
+    }
+
+    pub fn get(&self, price: &Decimal) -> Option<&Decimal> {
+        self.prices.binary_search(price).ok().map(|i| &self.qtys[i])
+    }
+
+    pub fn contains_key(&self, price: &Decimal) -> bool {
+        self.prices.binary_search(price).is_ok()
+    }
+
+    // giá tăng dần, `.rev()` cho bid từ best
+    pub fn iter(&self) -> Iter<'_> {
+        self.prices.iter().zip(self.qtys.iter())
+    }
+
+    pub fn keys(&self) -> slice::Iter<'_, Decimal> {
+        self.prices.iter()
+    }
+
+    pub fn values(&self) -> slice::Iter<'_, Decimal> {
+        self.qtys.iter()
+    }
+
+    pub fn first_key_value(&self) -> Option<(&Decimal, &Decimal)> {
+        self.iter().next()
+    }
+
+    pub fn last_key_value(&self) -> Option<(&Decimal, &Decimal)> {
+        self.iter().next_back()
+    }
+
+    pub fn range<R: RangeBounds<Decimal>>(&self, range: R) -> Iter<'_> {
+        let start = match range.start_bound() {
+            Bound::Included(p) => self.prices.partition_point(|x| x < p),
+            Bound::Excluded(p) => self.prices.partition_point(|x| x <= p),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(p) => self.prices.partition_point(|x| x <= p),
+            Bound::Excluded(p) => self.prices.partition_point(|x| x < p),
+            Bound::Unbounded => self.prices.len(),
+        };
+        let end = end.max(start);
+        self.prices[start..end].iter().zip(self.qtys[start..end].iter())
+    }
+}
+
+impl FromIterator<(Decimal, Decimal)> for BookSide {
+    // giá trùng thì giữ qty sau cùng như BTreeMap
+    fn from_iter<I: IntoIterator<Item = (Decimal, Decimal)>>(iter: I) -> Self {
+        let mut levels: Vec<(Decimal, Decimal)> = iter.into_iter().collect();
+        levels.sort_by_key(|(p, _)| *p);
+        let mut side = Self::with_capacity(levels.len());
+        for (p, q) in levels {
+            if side.prices.last() == Some(&p) {
+                if let Some(last) = side.qtys.last_mut() {
+                    *last = q;
+                }
+            } else {
+                side.prices.push(p);
+                side.qtys.push(q);
+            }
+        }
+        side
+    }
+}
+
+impl<'a> IntoIterator for &'a BookSide {
+    type Item = (&'a Decimal, &'a Decimal);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_matches_btreemap() {
+        let ops = [(dec!(100), dec!(1)), (dec!(99.5), dec!(2)), (dec!(101), dec!(3)), (dec!(100), dec!(4)), (dec!(99.5), dec!(0))];
+        let mut side = BookSide::new();
+        let mut tree = BTreeMap::new();
+        for (p, q) in ops {
+            if q.is_zero() {
+                assert_eq!(side.remove(&p), tree.remove(&p));
+            } else {
+                assert_eq!(side.insert(p, q), tree.insert(p, q));
+            }
+        }
+        assert!(side.iter().eq(tree.iter()));
+        assert!(side.iter().rev().eq(tree.iter().rev()));
+        assert!(side.range(dec!(100)..).eq(tree.range(dec!(100)..)));
+        assert!(side.range(..=dec!(100)).eq(tree.range(..=dec!(100))));
+        assert!(side.range(dec!(102)..dec!(105)).eq(tree.range(dec!(102)..dec!(105))));
+        assert_eq!((side.get(&dec!(100)), side.contains_key(&dec!(99.5))), (Some(&dec!(4)), false));
+        assert_eq!(side.last_key_value(), tree.last_key_value());
+
+        let collected: BookSide = [(dec!(2), dec!(1)), (dec!(1), dec!(1)), (dec!(2), dec!(5))].into_iter().collect();
+        assert_eq!(collected.iter().map(|(p, q)| (*p, *q)).collect::<Vec<_>>(), vec![(dec!(1), dec!(1)), (dec!(2), dec!(5))]);
+    }
+}
@@ -0,0 +1,205 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::VecDeque;
+
+use super::trade::Trade;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candle {
+    pub open_time: DateTime<Utc>,
+    pub close_time: DateTime<Utc>,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+    pub trades: u64,
+    pub closed: bool,
+}
+
+impl Candle {
+    pub fn from_trade(open_time: DateTime<Utc>, interval: Duration, trade: &Trade) -> Self {
+        Self {
+            open_time,
+            close_time: open_time + interval - Duration::milliseconds(1),
+            open: trade.price,
+            high: trade.price,
+            low: trade.price,
+            close: trade.price,
+            volume: trade.qty,
+            trades: 1,
+            closed: false,
+        }
+    }
+
+    pub fn update(&mut self, trade: &Trade) {
+        self.high = self.high.max(trade.price);
+        self.low = self.low.min(trade.price);
+        self.close = trade.price;
+        self.volume += trade.qty;
+        self.trades += 1;
+    }
+}
+
+// "1s", "1m", "15m", "1h", "4h", "1d", "1w" như Binance kline interval
+pub fn parse_interval(s: &str) -> Option<Duration> {
+    if s.len() < 2 {
+        return None;
+    }
+    let (num, unit) = s.split_at(s.len() - 1);
+    let n: i64 = num.parse().ok()?;
+    if n <= 0 {
+        return None;
+    }
+    match unit {
+        "s" => Some(Duration::seconds(n)),
+        "m" => Some(Duration::minutes(n)),
+        "h" => Some(Duration::hours(n)),
+        "d" => Some(Duration::days(n)),
+        "w" => Some(Duration::weeks(n)),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CandleStore {
+    pub interval: Duration,
+    pub max_len: usize,
+    candles: VecDeque<Candle>,
+}
+
+impl CandleStore {
+    pub fn new(interval: Duration, max_len: usize) -> Self {
+        Self {
+            interval,
+            max_len,
+            candles: VecDeque::new(),
+        }
+    }
+
+    fn bucket_start(&self, ts: DateTime<Utc>) -> DateTime<Utc> {
+        let step = self.interval.num_milliseconds().max(1);
+        let ms = ts.timestamp_millis();
+        DateTime::from_timestamp_millis(ms - ms.rem_euclid(step)).unwrap_or(ts)
+    }
+
+    // Kline stream gửi lại cùng một bar nhiều lần cho tới khi đóng (x = true).
+    // Trả về bar vừa đóng (một lần cho mỗi bar) để cập nhật indicator
+    pub fn upsert(&mut self, candle: Candle) -> Option<Candle> {
+        let closed = candle.closed.then(|| candle.clone());
+        match self.candles.back_mut() {
+            Some(last) if last.open_time == candle.open_time => {
+                let was_closed = last.closed;
+                *last = candle;
+                if was_closed {
+                    return None;
+                }
+            }
+            Some(last) if last.open_time > candle.open_time => return None,
+            _ => self.push(candle),
+        }
+        closed
+    }
+
+    // Tự build OHLCV từ trade tick, trả về bar trước đó khi trade mở bar mới
+    pub fn on_trade(&mut self, trade: &Trade) -> Option<Candle> {
+        let open_time = self.bucket_start(trade.timestamp);
+        match self.candles.back_mut() {
+            Some(last) if last.open_time == open_time => {
+                last.update(trade);
+                None
+            }
+            Some(last) if last.open_time > open_time => None,
+            _ => {
+                let closed = self.candles.back_mut().map(|last| {
+                    last.closed = true;
+                    last.clone()
+                });
+                let candle = Candle::from_trade(open_time, self.interval, trade);
+                self.push(candle);
+                closed
+            }
+        }
+    }
+
+    fn push(&mut self, candle: Candle) {
+        self.candles.push_back(candle);
+        while self.candles.len() > self.max_len {
+            self.candles.pop_front();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.candles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.candles.is_empty()
+    }
+
+    pub fn last(&self) -> Option<&Candle> {
+        self.candles.back()
+    }
+
+    // n bar gần nhất, cũ nhất ở đầu
+    pub fn latest(&self, n: usize) -> Vec<Candle> {
+        let skip = self.candles.len().saturating_sub(n);
+        self.candles.iter().skip(skip).cloned().collect()
+    }
+
+    // các bar có open_time trong [start, end)
+    pub fn range(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<Candle> {
+        self.candles
+            .iter()
+            .filter(|c| c.open_time >= start && c.open_time < end)
+            .cloned()
+            .collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::trade::TradeSide;
+
+    fn trade(price: f64, qty: f64, ts_ms: i64) -> Trade {
+        Trade {
+            symbol: "btcusdt".into(),
+            trade_id: 0,
+            price,
+            qty,
+            side: TradeSide::Buy,
+            timestamp: DateTime::from_timestamp_millis(ts_ms).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!(parse_interval("1m"), Some(Duration::minutes(1)));
+        assert_eq!(parse_interval("4h"), Some(Duration::hours(4)));
+        assert_eq!(parse_interval("m"), None);
+        assert_eq!(parse_interval("0m"), None);
+    }
+
+    #[test]
+    fn test_build_bars_from_trades() {
+        let mut store = CandleStore::new(Duration::minutes(1), 100);
+        assert_eq!(store.on_trade(&trade(100.0, 1.0, 60_000)), None);
+        store.on_trade(&trade(105.0, 2.0, 90_000));
+        store.on_trade(&trade(95.0, 1.0, 110_000));
+        let closed = store.on_trade(&trade(101.0, 0.5, 125_000)).unwrap();
+        assert_eq!((closed.open_time.timestamp_millis(), closed.close), (60_000, 95.0));
+
+        let bars = store.latest(10);
+        assert_eq!(bars.len(), 2);
+        let first = &bars[0];
+        assert_eq!((first.open, first.high, first.low, first.close), (100.0, 105.0, 95.0, 95.0));
+        assert_eq!(first.volume, 4.0);
+        assert!(first.closed);
+        assert_eq!(bars[1].open_time.timestamp_millis(), 120_000);
+
+        let from = DateTime::from_timestamp_millis(120_000).unwrap();
+        let to = DateTime::from_timestamp_millis(180_000).unwrap();
+        assert_eq!(store.range(from, to).len(), 1);
+    }
+}
@@ -0,0 +1,154 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::{
+    collections::VecDeque,
+    sync::{Arc, RwLock},
+};
+
+// đủ để ước lượng drift qua vài chu kỳ sync mà vẫn theo kịp khi đồng hồ bị chỉnh
+const MAX_SAMPLES: usize = 16;
+// mẫu có RTT quá lớn so với RTT nhỏ nhất bị bỏ khi ước lượng
+const RTT_TOLERANCE_MS: f64 = 5.0;
+
+// Một lần đo: offset = giờ sàn - giờ local tại điểm giữa request
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClockSample {
+    pub local: DateTime<Utc>,
+    pub offset_ms: f64,
+    pub rtt_ms: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ClockStatus {
+    pub offset_ms: f64,
+    // ms lệch thêm mỗi giây local, x 1e6
+    pub drift_ppm: f64,
+    pub last_rtt_ms: Option<f64>,
+    pub samples: usize,
+    pub last_sync: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default)]
+struct ClockState {
+    samples: VecDeque<ClockSample>,
+    // offset(t) = offset_ms + drift * (t - anchor)
+    anchor: Option<DateTime<Utc>>,
+    offset_ms: f64,
+    drift: f64,
+}
+
+impl ClockState {
+    // hồi quy tuyến tính offset theo thời gian local trên các mẫu RTT thấp
+    fn refit(&mut self) {
+        let min_rtt = self.samples.iter().map(|s| s.rtt_ms).fold(f64::INFINITY, f64::min);
+        let good: Vec<&ClockSample> =
+            self.samples.iter().filter(|s| s.rtt_ms <= min_rtt * 2.0 + RTT_TOLERANCE_MS).collect();
+        let Some(first) = good.first() else { return };
+        let xs: Vec<f64> = good.iter().map(|s| (s.local - first.local).num_microseconds().unwrap_or(0) as f64 / 1000.0).collect();
+        let n = good.len() as f64;
+        let mean_x = xs.iter().sum::<f64>() / n;
+        let mean_y = good.iter().map(|s| s.offset_ms).sum::<f64>() / n;
+        let var_x: f64 = xs.iter().map(|x| (x - mean_x).powi(2)).sum();
+        let cov: f64 = xs.iter().zip(&good).map(|(x, s)| (x - mean_x) * (s.offset_ms - mean_y)).sum();
+        self.drift = if var_x > 0.0 { cov / var_x } else { 0.0 };
+        self.offset_ms = mean_y;
+        self.anchor = Some(first.local + Duration::microseconds((mean_x * 1000.0) as i64));
+    }
+
+    fn offset_ms_at(&self, local: DateTime<Utc>) -> f64 {
+        match self.anchor {
+            Some(anchor) => self.offset_ms + self.drift * (local - anchor).num_microseconds().unwrap_or(0) as f64 / 1000.0,
+            None => 0.0,
+        }
+    }
+}
+
+// Ước lượng lệch đồng hồ local so với server sàn (kiểu NTP: giờ server coi như
+// ở giữa request). Clone rẻ, mọi bản clone dùng chung state. Chưa sync thì offset = 0
+#[derive(Debug, Clone, Default)]
+pub struct ClockSync {
+    state: Arc<RwLock<ClockState>>,
+}
+
+impl ClockSync {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // `sent` / `received`: giờ local khi gửi / nhận response, `server`: giờ sàn trả về
+    pub fn record(&self, sent: DateTime<Utc>, server: DateTime<Utc>, received: DateTime<Utc>) -> ClockSample {
+        let rtt = received - sent;
+        let local = sent + rtt / 2;
+        let sample = ClockSample {
+            local,
+            offset_ms: (server - local).num_microseconds().unwrap_or(0) as f64 / 1000.0,
+            rtt_ms: rtt.num_microseconds().unwrap_or(0) as f64 / 1000.0,
+        };
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        if state.samples.len() == MAX_SAMPLES {
+            state.samples.pop_front();
+        }
+        state.samples.push_back(sample);
+        state.refit();
+        sample
+    }
+
+    pub fn offset_at(&self, local: DateTime<Utc>) -> Duration {
+        let ms = self.state.read().unwrap_or_else(|e| e.into_inner()).offset_ms_at(local);
+        Duration::microseconds((ms * 1000.0) as i64)
+    }
+
+    // giờ local quy đổi sang giờ sàn
+    pub fn to_exchange_time(&self, local: DateTime<Utc>) -> DateTime<Utc> {
+        local + self.offset_at(local)
+    }
+
+    pub fn exchange_now(&self) -> DateTime<Utc> {
+        self.to_exchange_time(Utc::now())
+    }
+
+    pub fn is_synced(&self) -> bool {
+        self.state.read().unwrap_or_else(|e| e.into_inner()).anchor.is_some()
+    }
+
+    pub fn status(&self) -> ClockStatus {
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        let last = state.samples.back();
+        ClockStatus {
+            offset_ms: state.offset_ms_at(Utc::now()),
+            drift_ppm: state.drift * 1e6,
+            last_rtt_ms: last.map(|s| s.rtt_ms),
+            samples: state.samples.len(),
+            last_sync: last.map(|s| s.local),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offset_and_drift() {
+        let clock = ClockSync::new();
+        let t0 = DateTime::from_timestamp_millis(1_700_000_000_000).unwrap();
+        assert_eq!(clock.to_exchange_time(t0), t0);
+
+        // sàn nhanh hơn 100ms, lệch thêm 1ms mỗi 10s (100 ppm), RTT 20ms
+        for i in 0..5 {
+            let sent = t0 + Duration::seconds(10 * i);
+            let server = sent + Duration::milliseconds(10 + 100 + i);
+            clock.record(sent, server, sent + Duration::milliseconds(20));
+        }
+        // mẫu RTT lớn bị bỏ qua
+        let sent = t0 + Duration::seconds(50);
+        clock.record(sent, sent + Duration::milliseconds(900), sent + Duration::milliseconds(800));
+
+        let status = clock.status();
+        assert!((status.drift_ppm - 100.0).abs() < 1.0, "drift {}", status.drift_ppm);
+        let at = t0 + Duration::seconds(60) + Duration::milliseconds(10);
+        let offset = clock.offset_at(at).num_microseconds().unwrap() as f64 / 1000.0;
+        assert!((offset - 106.0).abs() < 0.1, "offset {}", offset);
+        assert!(clock.is_synced());
+    }
+}
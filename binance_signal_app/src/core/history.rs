@@ -0,0 +1,126 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use std::{collections::VecDeque, sync::Arc};
+
+use crate::core::orderbook::OrderbookSnapshot;
+
+// 60s book ở tốc độ 100ms
+pub const DEFAULT_HISTORY_CAPACITY: usize = 600;
+
+// `[signal_history]`: số snapshot gần nhất SignalEngine giữ cho mỗi symbol, 0 = tắt
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct HistoryConfig {
+    pub capacity: usize,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self { capacity: DEFAULT_HISTORY_CAPACITY }
+    }
+}
+
+// Ring buffer N snapshot gần nhất, sort theo timestamp. Snapshot là Arc đã publish
+// nên giữ lại không phải clone book; signal cần book trễ (OFI 1s, mid 1s trước)
+// đọc chung một bản thay vì mỗi signal tự giữ
+#[derive(Debug, Clone)]
+pub struct SnapshotHistory {
+    snaps: VecDeque<Arc<OrderbookSnapshot>>,
+    capacity: usize,
+}
+
+impl SnapshotHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self { snaps: VecDeque::with_capacity(capacity), capacity }
+    }
+
+    // snapshot cũ hơn bản mới nhất (clock lùi, replay lẫn thứ tự) bị bỏ để giữ thứ tự
+    pub fn push(&mut self, snap: Arc<OrderbookSnapshot>) -> bool {
+        if self.capacity == 0 || self.latest().is_some_and(|last| snap.timestamp < last.timestamp) {
+            return false;
+        }
+        if self.snaps.len() == self.capacity {
+            self.snaps.pop_front();
+        }
+        self.snaps.push_back(snap);
+        true
+    }
+
+    pub fn latest(&self) -> Option<&Arc<OrderbookSnapshot>> {
+        self.snaps.back()
+    }
+
+    pub fn oldest(&self) -> Option<&Arc<OrderbookSnapshot>> {
+        self.snaps.front()
+    }
+
+    // snapshot mới nhất có timestamp <= `ts` (book đang hiệu lực tại `ts`),
+    // None nếu history không lùi được tới `ts`
+    pub fn at(&self, ts: DateTime<Utc>) -> Option<&Arc<OrderbookSnapshot>> {
+        let idx = self.snaps.partition_point(|s| s.timestamp <= ts);
+        idx.checked_sub(1).and_then(|i| self.snaps.get(i))
+    }
+
+    // book tại `lag` trước snapshot mới nhất
+    pub fn lagged(&self, lag: Duration) -> Option<&Arc<OrderbookSnapshot>> {
+        self.at(self.latest()?.timestamp - lag)
+    }
+
+    // các snapshot có timestamp > `ts`, cũ tới mới
+    pub fn since(&self, ts: DateTime<Utc>) -> impl DoubleEndedIterator<Item = &Arc<OrderbookSnapshot>> {
+        let idx = self.snaps.partition_point(|s| s.timestamp <= ts);
+        self.snaps.range(idx..)
+    }
+
+    pub fn len(&self) -> usize {
+        self.snaps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snaps.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn clear(&mut self) {
+        self.snaps.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snap(ms: i64) -> Arc<OrderbookSnapshot> {
+        let mut ob = OrderbookSnapshot::new();
+        ob.timestamp = DateTime::from_timestamp_millis(ms).unwrap();
+        ob.last_update_id = ms as u64;
+        Arc::new(ob)
+    }
+
+    #[test]
+    fn test_ring_buffer_and_time_lookup() {
+        let mut history = SnapshotHistory::new(4);
+        for ms in [0, 100, 200, 300, 400, 500] {
+            assert!(history.push(snap(ms)));
+        }
+        // chỉ giữ 4 bản mới nhất
+        assert_eq!((history.len(), history.oldest().unwrap().last_update_id), (4, 200));
+        assert!(!history.push(snap(450)));
+
+        let id = |s: Option<&Arc<OrderbookSnapshot>>| s.map(|s| s.last_update_id);
+        assert_eq!(id(history.lagged(Duration::milliseconds(250))), Some(200));
+        assert_eq!(id(history.lagged(Duration::zero())), Some(500));
+        assert_eq!(id(history.at(DateTime::from_timestamp_millis(399).unwrap())), Some(300));
+        // đã bị đẩy ra khỏi buffer
+        assert_eq!(id(history.lagged(Duration::milliseconds(301))), None);
+        let since = history.since(DateTime::from_timestamp_millis(300).unwrap());
+        assert_eq!(since.map(|s| s.last_update_id).collect::<Vec<_>>(), vec![400, 500]);
+
+        let mut disabled = SnapshotHistory::new(0);
+        assert!(!disabled.push(snap(0)));
+        assert!(disabled.is_empty());
+    }
+}
@@ -0,0 +1,302 @@
+use std::collections::VecDeque;
+
+use super::candle::Candle;
+
+// Indicator cập nhật tăng dần O(1) theo từng bar đã đóng (close).
+// Trả về None cho tới khi đủ `period` bar (warm-up)
+pub trait Indicator {
+    type Output: Copy;
+
+    fn update_value(&mut self, value: f64) -> Option<Self::Output>;
+
+    fn value(&self) -> Option<Self::Output>;
+
+    fn reset(&mut self);
+
+    fn update(&mut self, candle: &Candle) -> Option<Self::Output> {
+        self.update_value(candle.close)
+    }
+}
+
+// EMA, seed bằng SMA của `period` giá đầu như cách tính phổ biến
+#[derive(Debug, Clone)]
+pub struct Ema {
+    period: usize,
+    alpha: f64,
+    count: usize,
+    seed_sum: f64,
+    value: Option<f64>,
+}
+
+impl Ema {
+    pub fn new(period: usize) -> Self {
+        let period = period.max(1);
+        Self { period, alpha: 2.0 / (period as f64 + 1.0), count: 0, seed_sum: 0.0, value: None }
+    }
+
+    pub fn period(&self) -> usize {
+        self.period
+    }
+}
+
+impl Indicator for Ema {
+    type Output = f64;
+
+    fn update_value(&mut self, value: f64) -> Option<f64> {
+        match self.value {
+            Some(prev) => self.value = Some(prev + self.alpha * (value - prev)),
+            None => {
+                self.count += 1;
+                self.seed_sum += value;
+                if self.count == self.period {
+                    self.value = Some(self.seed_sum / self.period as f64);
+                }
+            }
+        }
+        self.value
+    }
+
+    fn value(&self) -> Option<f64> {
+        self.value
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.period);
+    }
+}
+
+// RSI với Wilder smoothing (avg = (avg * (n - 1) + x) / n)
+#[derive(Debug, Clone)]
+pub struct Rsi {
+    period: usize,
+    prev: Option<f64>,
+    count: usize,
+    avg_gain: f64,
+    avg_loss: f64,
+    value: Option<f64>,
+}
+
+impl Rsi {
+    pub fn new(period: usize) -> Self {
+        Self { period: period.max(1), prev: None, count: 0, avg_gain: 0.0, avg_loss: 0.0, value: None }
+    }
+}
+
+impl Indicator for Rsi {
+    type Output = f64;
+
+    fn update_value(&mut self, value: f64) -> Option<f64> {
+        let prev = self.prev.replace(value)?;
+        let change = value - prev;
+        let (gain, loss) = (change.max(0.0), (-change).max(0.0));
+        let n = self.period as f64;
+        if self.count < self.period {
+            // warm-up: trung bình cộng của `period` thay đổi đầu tiên
+            self.count += 1;
+            self.avg_gain += gain / n;
+            self.avg_loss += loss / n;
+            if self.count < self.period {
+                return None;
+            }
+        } else {
+            self.avg_gain = (self.avg_gain * (n - 1.0) + gain) / n;
+            self.avg_loss = (self.avg_loss * (n - 1.0) + loss) / n;
+        }
+        let rsi = if self.avg_loss == 0.0 {
+            if self.avg_gain == 0.0 { 50.0 } else { 100.0 }
+        } else {
+            100.0 - 100.0 / (1.0 + self.avg_gain / self.avg_loss)
+        };
+        self.value = Some(rsi);
+        self.value
+    }
+
+    fn value(&self) -> Option<f64> {
+        self.value
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.period);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MacdValue {
+    pub macd: f64,
+    pub signal: f64,
+    pub histogram: f64,
+}
+
+// MACD = EMA(fast) - EMA(slow), signal = EMA(signal) của MACD
+#[derive(Debug, Clone)]
+pub struct Macd {
+    fast: Ema,
+    slow: Ema,
+    signal: Ema,
+    value: Option<MacdValue>,
+}
+
+impl Macd {
+    pub fn new(fast: usize, slow: usize, signal: usize) -> Self {
+        Self { fast: Ema::new(fast), slow: Ema::new(slow), signal: Ema::new(signal), value: None }
+    }
+}
+
+impl Default for Macd {
+    // 12 / 26 / 9
+    fn default() -> Self {
+        Self::new(12, 26, 9)
+    }
+}
+
+impl Indicator for Macd {
+    type Output = MacdValue;
+
+    fn update_value(&mut self, value: f64) -> Option<MacdValue> {
+        let fast = self.fast.update_value(value);
+        let slow = self.slow.update_value(value);
+        let (Some(fast), Some(slow)) = (fast, slow) else {
+            return None;
+        };
+        let macd = fast - slow;
+        let signal = self.signal.update_value(macd)?;
+        self.value = Some(MacdValue { macd, signal, histogram: macd - signal });
+        self.value
+    }
+
+    fn value(&self) -> Option<MacdValue> {
+        self.value
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.fast.period(), self.slow.period(), self.signal.period());
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BollingerBands {
+    pub middle: f64,
+    pub upper: f64,
+    pub lower: f64,
+}
+
+impl BollingerBands {
+    // (upper - lower) / middle
+    pub fn bandwidth(&self) -> f64 {
+        if self.middle == 0.0 { 0.0 } else { (self.upper - self.lower) / self.middle }
+    }
+}
+
+// SMA(period) ± k * độ lệch chuẩn (population) trên cùng cửa sổ
+#[derive(Debug, Clone)]
+pub struct Bollinger {
+    period: usize,
+    k: f64,
+    window: VecDeque<f64>,
+    sum: f64,
+    sum_sq: f64,
+    value: Option<BollingerBands>,
+}
+
+impl Bollinger {
+    pub fn new(period: usize, k: f64) -> Self {
+        let period = period.max(1);
+        Self { period, k, window: VecDeque::with_capacity(period + 1), sum: 0.0, sum_sq: 0.0, value: None }
+    }
+}
+
+impl Default for Bollinger {
+    // 20 bar, ±2σ
+    fn default() -> Self {
+        Self::new(20, 2.0)
+    }
+}
+
+impl Indicator for Bollinger {
+    type Output = BollingerBands;
+
+    fn update_value(&mut self, value: f64) -> Option<BollingerBands> {
+        self.window.push_back(value);
+        self.sum += value;
+        self.sum_sq += value * value;
+        if self.window.len() > self.period
+            && let Some(old) = self.window.pop_front()
+        {
+            self.sum -= old;
+            self.sum_sq -= old * old;
+        }
+        if self.window.len() < self.period {
+            return None;
+        }
+        let n = self.period as f64;
+        let mean = self.sum / n;
+        let std = (self.sum_sq / n - mean * mean).max(0.0).sqrt();
+        self.value = Some(BollingerBands { middle: mean, upper: mean + self.k * std, lower: mean - self.k * std });
+        self.value
+    }
+
+    fn value(&self) -> Option<BollingerBands> {
+        self.value
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.period, self.k);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close_enough(a: f64, b: f64, eps: f64) -> bool {
+        (a - b).abs() < eps
+    }
+
+    #[test]
+    fn test_ema_macd_bollinger() {
+        let mut ema = Ema::new(3);
+        let out: Vec<_> = (1..=6).map(|x| ema.update_value(x as f64)).collect();
+        // seed SMA(1, 2, 3) = 2, alpha = 0.5
+        assert_eq!(out, vec![None, None, Some(2.0), Some(3.0), Some(4.0), Some(5.0)]);
+        ema.reset();
+        assert_eq!(ema.value(), None);
+
+        // giá đứng yên -> MACD = 0, cần slow + signal - 1 bar để warm-up
+        let mut macd = Macd::new(3, 5, 2);
+        let out: Vec<_> = (0..6).map(|_| macd.update_value(10.0)).collect();
+        assert_eq!(out[4], None);
+        assert_eq!(out[5], Some(MacdValue { macd: 0.0, signal: 0.0, histogram: 0.0 }));
+
+        // mean 5, population std 2
+        let mut bb = Bollinger::new(8, 2.0);
+        let mut last = None;
+        for x in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+            last = bb.update_value(x);
+        }
+        assert_eq!(last, Some(BollingerBands { middle: 5.0, upper: 9.0, lower: 1.0 }));
+        // cửa sổ trượt: bỏ 2, thêm 2 -> giữ nguyên
+        assert_eq!(bb.update_value(2.0), last);
+        assert!(close_enough(last.unwrap().bandwidth(), 1.6, 1e-12));
+    }
+
+    #[test]
+    fn test_rsi_matches_wilder_reference() {
+        // ví dụ RSI(14) của StockCharts, bảng của họ làm tròn từng bước nên lệch ~0.07
+        let closes = [
+            44.34, 44.09, 44.15, 43.61, 44.33, 44.83, 45.10, 45.42, 45.84, 46.08, 45.89, 46.03, 45.61, 46.28, 46.28,
+            46.00, 46.03, 46.41, 46.22, 45.64,
+        ];
+        let expected = [70.53, 66.32, 66.55, 69.41, 66.36, 57.97];
+        let mut rsi = Rsi::new(14);
+        let out: Vec<f64> = closes.iter().filter_map(|c| rsi.update_value(*c)).collect();
+        assert_eq!(out.len(), expected.len());
+        for (got, want) in out.iter().zip(expected) {
+            assert!(close_enough(*got, want, 0.1), "{} vs {}", got, want);
+        }
+        assert!(close_enough(out[0], 70.464135, 1e-6));
+
+        let mut flat = Rsi::new(3);
+        let out: Vec<_> = (0..4).map(|_| flat.update_value(1.0)).collect();
+        assert_eq!(out[3], Some(50.0));
+    }
+}
@@ -0,0 +1,203 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+};
+
+use super::orderbook::{OrderbookSnapshot, Side};
+
+// Kiểm tra book sau mỗi update, bắt data hỏng mà sequence / checksum của sàn không báo
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct IntegrityConfig {
+    pub enabled: bool,
+    // vi phạm thì feed bỏ book hiện tại và sync lại (feed hỗ trợ: binance diff, okx)
+    pub resync: bool,
+}
+
+impl Default for IntegrityConfig {
+    fn default() -> Self {
+        Self { enabled: true, resync: true }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Violation {
+    // best bid >= best ask
+    Crossed { bid: Decimal, ask: Decimal },
+    // update id đi lùi so với bản đã publish
+    SequenceRegressed { prev: u64, next: u64 },
+    ZeroQty { side: Side, price: Decimal },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::Crossed { bid, ask } => write!(f, "crossed book: bid {} >= ask {}", bid, ask),
+            Violation::SequenceRegressed { prev, next } => write!(f, "update id went back: {} -> {}", prev, next),
+            Violation::ZeroQty { side, price } => write!(f, "zero qty {:?} level at {}", side, price),
+        }
+    }
+}
+
+// update id = 0 là feed không có sequence, bỏ qua kiểm tra thứ tự
+pub fn check(prev_update_id: u64, book: &OrderbookSnapshot) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    if let Some(((bid, _), (ask, _))) = book.best_bid_ask()
+        && bid >= ask
+    {
+        violations.push(Violation::Crossed { bid, ask });
+    }
+    if prev_update_id > 0 && book.last_update_id > 0 && book.last_update_id < prev_update_id {
+        violations.push(Violation::SequenceRegressed { prev: prev_update_id, next: book.last_update_id });
+    }
+    let zero = |side: Side| move |(p, q): (&Decimal, &Decimal)| (*q <= Decimal::ZERO).then_some(Violation::ZeroQty { side, price: *p });
+    violations.extend(book.bids.iter().filter_map(zero(Side::Bid)));
+    violations.extend(book.asks.iter().filter_map(zero(Side::Ask)));
+    violations
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct IntegrityStats {
+    pub crossed: u64,
+    pub sequence: u64,
+    pub zero_qty: u64,
+    pub resyncs: u64,
+}
+
+impl IntegrityStats {
+    pub fn violations(&self) -> u64 {
+        self.crossed + self.sequence + self.zero_qty
+    }
+}
+
+// Đếm vi phạm theo loại và giữ cờ resync cho feed, dùng chung giữa writer và reader nên toàn atomic
+#[derive(Debug)]
+pub struct IntegrityMonitor {
+    enabled: AtomicBool,
+    resync: AtomicBool,
+    pending_resync: AtomicBool,
+    // update trước có vi phạm, để chỉ log lần đầu của một chuỗi vi phạm
+    violating: AtomicBool,
+    crossed: AtomicU64,
+    sequence: AtomicU64,
+    zero_qty: AtomicU64,
+    resyncs: AtomicU64,
+}
+
+impl Default for IntegrityMonitor {
+    fn default() -> Self {
+        Self::new(IntegrityConfig::default())
+    }
+}
+
+impl IntegrityMonitor {
+    pub fn new(config: IntegrityConfig) -> Self {
+        Self {
+            enabled: AtomicBool::new(config.enabled),
+            resync: AtomicBool::new(config.resync),
+            pending_resync: AtomicBool::new(false),
+            violating: AtomicBool::new(false),
+            crossed: AtomicU64::new(0),
+            sequence: AtomicU64::new(0),
+            zero_qty: AtomicU64::new(0),
+            resyncs: AtomicU64::new(0),
+        }
+    }
+
+    pub fn configure(&self, config: IntegrityConfig) {
+        self.enabled.store(config.enabled, Ordering::Relaxed);
+        self.resync.store(config.resync, Ordering::Relaxed);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    // true nếu update trước đó còn sạch (vi phạm mới xuất hiện)
+    pub fn record(&self, violations: &[Violation]) -> bool {
+        let was_violating = self.violating.swap(!violations.is_empty(), Ordering::Relaxed);
+        if violations.is_empty() {
+            return false;
+        }
+        for v in violations {
+            let counter = match v {
+                Violation::Crossed { .. } => &self.crossed,
+                Violation::SequenceRegressed { .. } => &self.sequence,
+                Violation::ZeroQty { .. } => &self.zero_qty,
+            };
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+        if self.resync.load(Ordering::Relaxed) {
+            self.pending_resync.store(true, Ordering::Relaxed);
+        }
+        !was_violating
+    }
+
+    // feed gọi sau mỗi update, true thì phải sync lại book từ đầu
+    pub fn take_resync(&self) -> bool {
+        let pending = self.pending_resync.swap(false, Ordering::Relaxed);
+        if pending {
+            self.resyncs.fetch_add(1, Ordering::Relaxed);
+        }
+        pending
+    }
+
+    pub fn stats(&self) -> IntegrityStats {
+        IntegrityStats {
+            crossed: self.crossed.load(Ordering::Relaxed),
+            sequence: self.sequence.load(Ordering::Relaxed),
+            zero_qty: self.zero_qty.load(Ordering::Relaxed),
+            resyncs: self.resyncs.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_check_invariants() {
+        let mut ob = OrderbookSnapshot::new();
+        ob.set_level(Side::Bid, dec!(100), dec!(1));
+        ob.set_level(Side::Ask, dec!(101), dec!(1));
+        ob.last_update_id = 10;
+        assert!(check(9, &ob).is_empty());
+        assert!(check(0, &ob).is_empty());
+
+        // set_level không cho qty 0 nên chèn thẳng vào map
+        ob.bids.insert(dec!(99), Decimal::ZERO);
+        ob.set_level(Side::Ask, dec!(100), dec!(2));
+        assert_eq!(
+            check(11, &ob),
+            vec![
+                Violation::Crossed { bid: dec!(100), ask: dec!(100) },
+                Violation::SequenceRegressed { prev: 11, next: 10 },
+                Violation::ZeroQty { side: Side::Bid, price: dec!(99) },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_monitor_counts_and_resync() {
+        let monitor = IntegrityMonitor::default();
+        assert!(!monitor.record(&[]));
+        assert!(!monitor.take_resync());
+
+        let crossed = Violation::Crossed { bid: dec!(2), ask: dec!(1) };
+        assert!(monitor.record(&[crossed, Violation::ZeroQty { side: Side::Ask, price: dec!(3) }]));
+        // vẫn đang vi phạm thì không báo lại
+        assert!(!monitor.record(&[crossed]));
+        assert!(monitor.take_resync());
+        assert!(!monitor.take_resync());
+        assert_eq!(monitor.stats(), IntegrityStats { crossed: 2, sequence: 0, zero_qty: 1, resyncs: 1 });
+
+        monitor.configure(IntegrityConfig { enabled: true, resync: false });
+        monitor.record(&[Violation::SequenceRegressed { prev: 2, next: 1 }]);
+        assert!(!monitor.take_resync());
+        assert_eq!(monitor.stats().violations(), 4);
+    }
+}
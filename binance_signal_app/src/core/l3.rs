@@ -0,0 +1,158 @@
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use super::orderbook::{OrderbookSnapshot, Side};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct L3Order {
+    pub id: u64,
+    pub side: Side,
+    pub price: Decimal,
+    pub qty: Decimal,
+}
+
+// Book theo từng order (L3): mỗi level là hàng đợi order id theo thứ tự đến, để đo
+// vị trí thật trong hàng đợi thay vì ước lượng từ L2 như `QueuePosition`
+#[derive(Debug, Clone, Default)]
+pub struct OrderBookL3 {
+    orders: HashMap<u64, L3Order>,
+    bids: BTreeMap<Decimal, VecDeque<u64>>,
+    asks: BTreeMap<Decimal, VecDeque<u64>>,
+}
+
+impl OrderBookL3 {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn levels(&self, side: Side) -> &BTreeMap<Decimal, VecDeque<u64>> {
+        match side {
+            Side::Bid => &self.bids,
+            Side::Ask => &self.asks,
+        }
+    }
+
+    fn levels_mut(&mut self, side: Side) -> &mut BTreeMap<Decimal, VecDeque<u64>> {
+        match side {
+            Side::Bid => &mut self.bids,
+            Side::Ask => &mut self.asks,
+        }
+    }
+
+    // Thêm hoặc sửa order, trả về bản cũ. Giảm qty tại cùng giá thì giữ chỗ, tăng qty
+    // hoặc đổi giá / phía thì xếp cuối level (quy tắc của đa số sàn). qty <= 0 là xoá
+    pub fn upsert(&mut self, order: L3Order) -> Option<L3Order> {
+        if order.qty <= Decimal::ZERO || order.price <= Decimal::ZERO {
+            return self.remove(order.id);
+        }
+        if let Some(old) = self.orders.get_mut(&order.id)
+            && old.side == order.side
+            && old.price == order.price
+            && order.qty <= old.qty
+        {
+            return Some(std::mem::replace(old, order));
+        }
+        let old = self.remove(order.id);
+        self.levels_mut(order.side).entry(order.price).or_default().push_back(order.id);
+        self.orders.insert(order.id, order);
+        old
+    }
+
+    pub fn remove(&mut self, id: u64) -> Option<L3Order> {
+        let order = self.orders.remove(&id)?;
+        let levels = self.levels_mut(order.side);
+        if let Some(queue) = levels.get_mut(&order.price) {
+            queue.retain(|o| *o != id);
+            if queue.is_empty() {
+                levels.remove(&order.price);
+            }
+        }
+        Some(order)
+    }
+
+    pub fn get(&self, id: u64) -> Option<&L3Order> {
+        self.orders.get(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.orders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.orders.clear();
+        self.bids.clear();
+        self.asks.clear();
+    }
+
+    // order tại mức giá theo thứ tự ưu tiên
+    pub fn level_orders(&self, side: Side, price: Decimal) -> impl Iterator<Item = &L3Order> {
+        self.levels(side).get(&price).into_iter().flatten().filter_map(|id| self.orders.get(id))
+    }
+
+    pub fn level_qty(&self, side: Side, price: Decimal) -> Decimal {
+        self.level_orders(side, price).map(|o| o.qty).sum()
+    }
+
+    // (số order, tổng qty) đứng trước order `id` trong level của nó
+    pub fn queue_ahead(&self, id: u64) -> Option<(usize, Decimal)> {
+        let order = self.orders.get(&id)?;
+        let ahead = self.level_orders(order.side, order.price).take_while(|o| o.id != id);
+        Some(ahead.fold((0, Decimal::ZERO), |(n, qty), o| (n + 1, qty + o.qty)))
+    }
+
+    // gộp về L2, giữ timestamp / update id của `ob`
+    pub fn write_to(&self, ob: &mut OrderbookSnapshot) {
+        ob.clear();
+        for side in [Side::Bid, Side::Ask] {
+            for price in self.levels(side).keys() {
+                ob.set_level(side, *price, self.level_qty(side, *price));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn order(id: u64, side: Side, price: Decimal, qty: Decimal) -> L3Order {
+        L3Order { id, side, price, qty }
+    }
+
+    #[test]
+    fn test_queue_priority_and_aggregation() {
+        let mut book = OrderBookL3::new();
+        book.upsert(order(1, Side::Bid, dec!(100), dec!(1)));
+        book.upsert(order(2, Side::Bid, dec!(100), dec!(2)));
+        book.upsert(order(3, Side::Bid, dec!(100), dec!(3)));
+        book.upsert(order(4, Side::Ask, dec!(101), dec!(5)));
+        assert_eq!(book.queue_ahead(3), Some((2, dec!(3))));
+
+        // giảm qty giữ chỗ, tăng qty xếp cuối
+        book.upsert(order(1, Side::Bid, dec!(100), dec!(0.5)));
+        assert_eq!(book.queue_ahead(2), Some((1, dec!(0.5))));
+        book.upsert(order(2, Side::Bid, dec!(100), dec!(4)));
+        assert_eq!(book.level_orders(Side::Bid, dec!(100)).map(|o| o.id).collect::<Vec<_>>(), vec![1, 3, 2]);
+
+        assert_eq!(book.remove(1).map(|o| o.qty), Some(dec!(0.5)));
+        assert_eq!(book.queue_ahead(2), Some((1, dec!(3))));
+        // đổi giá: rời level cũ
+        book.upsert(order(3, Side::Bid, dec!(99), dec!(3)));
+        assert_eq!(book.queue_ahead(2), Some((0, Decimal::ZERO)));
+
+        let mut ob = OrderbookSnapshot::new();
+        book.write_to(&mut ob);
+        assert_eq!(ob.best_bid_ask(), Some(((dec!(100), dec!(4)), (dec!(101), dec!(5)))));
+        assert_eq!(ob.bids.len(), 2);
+
+        // qty 0 là xoá, level rỗng bị bỏ
+        book.upsert(order(4, Side::Ask, dec!(101), Decimal::ZERO));
+        assert!(book.level_orders(Side::Ask, dec!(101)).next().is_none());
+        assert_eq!((book.len(), book.asks.len()), (2, 0));
+    }
+}
@@ -0,0 +1,103 @@
+use rust_decimal::Decimal;
+use std::fmt;
+
+use super::orderbook::{to_f64, OrderbookSnapshot};
+
+pub const DEFAULT_BAR_WIDTH: usize = 30;
+
+// Ladder top-N dạng text cho log / CLI: ask trên (giá giảm dần tới best ask), bid dưới,
+// qty kèm thanh `#` theo tỉ lệ với level lớn nhất đang hiển thị, dòng spread ở giữa
+//
+//   30000.05  1.2 | ############
+//   30000.01  0.5 | #####
+//   --- spread 0.01 (0.00 bps) mid 30000.005 ---
+//   30000.00  3.0 | ##############################
+pub struct Ladder<'a> {
+    book: &'a OrderbookSnapshot,
+    depth: usize,
+    bar_width: usize,
+}
+
+impl<'a> Ladder<'a> {
+    pub fn new(book: &'a OrderbookSnapshot, depth: usize) -> Self {
+        Self { book, depth, bar_width: DEFAULT_BAR_WIDTH }
+    }
+
+    // 0 = không vẽ thanh
+    pub fn bar_width(mut self, width: usize) -> Self {
+        self.bar_width = width;
+        self
+    }
+
+    fn bar(&self, qty: Decimal, max: Decimal) -> String {
+        if max.is_zero() || self.bar_width == 0 {
+            return String::new();
+        }
+        // level nào cũng có ít nhất một `#` để nhìn thấy
+        let len = (to_f64(qty / max) * self.bar_width as f64).round() as usize;
+        "#".repeat(len.clamp(1, self.bar_width))
+    }
+}
+
+impl fmt::Display for Ladder<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let asks: Vec<_> = self.book.asks.iter().take(self.depth).collect();
+        let bids: Vec<_> = self.book.bids.iter().rev().take(self.depth).collect();
+        if asks.is_empty() && bids.is_empty() {
+            return write!(f, "(empty book)");
+        }
+
+        let levels = || asks.iter().chain(bids.iter());
+        let max = levels().map(|(_, q)| **q).max().unwrap_or_default();
+        let price_w = levels().map(|(p, _)| p.to_string().len()).max().unwrap_or(0);
+        let qty_w = levels().map(|(_, q)| q.to_string().len()).max().unwrap_or(0);
+        let row = |f: &mut fmt::Formatter<'_>, side: &str, p: &Decimal, q: &Decimal| {
+            writeln!(f, "{} {:>pw$}  {:>qw$} | {}", side, p, q, self.bar(*q, max), pw = price_w, qw = qty_w)
+        };
+
+        for (p, q) in asks.iter().rev() {
+            row(f, "ask", p, q)?;
+        }
+        match (self.book.spread(), self.book.spread_bps(), self.book.mid_price()) {
+            (Some(spread), Some(bps), Some(mid)) => {
+                writeln!(f, "--- spread {} ({:.2} bps) mid {} ---", spread, bps.round_dp(2), mid.normalize())?
+            }
+            // thiếu một phía
+            _ => writeln!(f, "--- no spread ---")?,
+        }
+        for (p, q) in &bids {
+            row(f, "bid", p, q)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::orderbook::Side;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_render_ladder() {
+        let mut ob = OrderbookSnapshot::new();
+        ob.set_level(Side::Bid, dec!(99.5), dec!(4));
+        ob.set_level(Side::Bid, dec!(100), dec!(2));
+        ob.set_level(Side::Ask, dec!(100.5), dec!(1));
+        ob.set_level(Side::Ask, dec!(101), dec!(0.5));
+        ob.set_level(Side::Ask, dec!(102), dec!(8));
+
+        let expected = "\
+ask   101  0.5 | #
+ask 100.5    1 | ##
+--- spread 0.5 (49.88 bps) mid 100.25 ---
+bid   100    2 | ####
+bid  99.5    4 | ########
+";
+        assert_eq!(Ladder::new(&ob, 2).bar_width(8).to_string(), expected);
+
+        ob.asks.clear();
+        assert!(Ladder::new(&ob, 1).bar_width(0).to_string().contains("--- no spread ---\nbid 100  2 | \n"));
+        assert_eq!(Ladder::new(&OrderbookSnapshot::new(), 5).to_string(), "(empty book)");
+    }
+}
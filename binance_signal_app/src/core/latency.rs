@@ -0,0 +1,108 @@
+use chrono::{DateTime, Utc};
+use std::collections::VecDeque;
+
+pub const DEFAULT_LATENCY_WINDOW: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LatencyStats {
+    pub count: usize,
+    pub p50_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+// Độ trễ từ event time của sàn (`E`) tới lúc nhận local trên N mẫu gần nhất.
+// Giá trị âm là do lệch đồng hồ giữa máy local và sàn, vẫn được giữ lại
+// để percentile phản ánh đúng độ lệch.
+#[derive(Debug, Clone)]
+pub struct LatencyTracker {
+    samples: VecDeque<f64>,
+    max_len: usize,
+}
+
+impl Default for LatencyTracker {
+    fn default() -> Self {
+        Self::new(DEFAULT_LATENCY_WINDOW)
+    }
+}
+
+impl LatencyTracker {
+    pub fn new(max_len: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(max_len.min(DEFAULT_LATENCY_WINDOW)),
+            max_len: max_len.max(1),
+        }
+    }
+
+    pub fn record(&mut self, event_time: DateTime<Utc>, received: DateTime<Utc>) {
+        let ms = (received - event_time).num_microseconds().unwrap_or(i64::MAX) as f64 / 1000.0;
+        self.record_ms(ms);
+    }
+
+    pub fn record_ms(&mut self, ms: f64) {
+        if self.samples.len() == self.max_len {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(ms);
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    // q trong [0, 1], nearest-rank
+    pub fn percentile(&self, q: f64) -> Option<f64> {
+        let mut sorted: Vec<f64> = self.samples.iter().copied().collect();
+        percentile_of(&mut sorted, q)
+    }
+
+    pub fn stats(&self) -> Option<LatencyStats> {
+        let mut sorted: Vec<f64> = self.samples.iter().copied().collect();
+        Some(LatencyStats {
+            count: sorted.len(),
+            p50_ms: percentile_of(&mut sorted, 0.5)?,
+            p99_ms: percentile_of(&mut sorted, 0.99)?,
+            max_ms: *sorted.last()?,
+        })
+    }
+}
+
+fn percentile_of(sorted: &mut [f64], q: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    sorted.sort_by(f64::total_cmp);
+    let rank = (q.clamp(0.0, 1.0) * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.saturating_sub(1).min(sorted.len() - 1)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_percentiles_over_rolling_window() {
+        let mut tracker = LatencyTracker::new(100);
+        assert_eq!(tracker.stats(), None);
+        for ms in 1..=100 {
+            tracker.record_ms(ms as f64);
+        }
+        let stats = tracker.stats().unwrap();
+        assert_eq!((stats.count, stats.p50_ms, stats.p99_ms, stats.max_ms), (100, 50.0, 99.0, 100.0));
+
+        // mẫu cũ bị đẩy ra khỏi cửa sổ
+        for _ in 0..100 {
+            tracker.record_ms(5.0);
+        }
+        assert_eq!(tracker.percentile(0.99), Some(5.0));
+
+        let now = Utc::now();
+        tracker.record(now - Duration::milliseconds(250), now);
+        assert_eq!(tracker.stats().unwrap().max_ms, 250.0);
+    }
+}
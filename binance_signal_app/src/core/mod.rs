@@ -1,3 +1,25 @@
+pub mod analytics;
+pub mod backpressure;
+pub mod book_events;
+// `--features btree-book` thì quay về BTreeMap
+#[cfg(not(feature = "btree-book"))]
+pub mod book_side;
 pub mod candle;
+pub mod clock;
+pub mod history;
+pub mod indicators;
+pub mod integrity;
+pub mod l3;
+pub mod ladder;
+pub mod latency;
+pub mod order;
 pub mod orderbook;
-pub mod signal;
\ No newline at end of file
+pub mod perp;
+pub mod pipeline;
+pub mod position;
+pub mod queue;
+pub mod sampling;
+pub mod signal;
+pub mod symbol;
+pub mod top_levels;
+pub mod trade;
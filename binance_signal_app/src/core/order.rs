@@ -0,0 +1,80 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+pub type OrderId = u64;
+
+// Tên variant theo quy ước Binance (BUY, LIMIT, PARTIALLY_FILLED, ...)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+impl OrderSide {
+    pub fn sign(&self) -> Decimal {
+        match self {
+            OrderSide::Buy => Decimal::ONE,
+            OrderSide::Sell => Decimal::NEGATIVE_ONE,
+        }
+    }
+
+    pub fn opposite(&self) -> Self {
+        match self {
+            OrderSide::Buy => OrderSide::Sell,
+            OrderSide::Sell => OrderSide::Buy,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum OrderType {
+    Limit,
+    Market,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum OrderStatus {
+    // đã gửi, chưa có ack từ sàn
+    PendingNew,
+    New,
+    PartiallyFilled,
+    Filled,
+    PendingCancel,
+    Canceled,
+    Rejected,
+    // STP của Binance trả về EXPIRED_IN_MATCH
+    #[serde(alias = "EXPIRED_IN_MATCH")]
+    Expired,
+}
+
+impl OrderStatus {
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            OrderStatus::Filled | OrderStatus::Canceled | OrderStatus::Rejected | OrderStatus::Expired
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fill {
+    pub order_id: OrderId,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub price: Decimal,
+    pub qty: Decimal,
+    // tính bằng quote asset
+    pub fee: Decimal,
+    pub is_maker: bool,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Fill {
+    pub fn notional(&self) -> Decimal {
+        self.price * self.qty
+    }
+}
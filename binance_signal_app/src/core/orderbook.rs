@@ -0,0 +1,508 @@
+use chrono::{DateTime, Duration, Utc};
+use std::{str::FromStr, sync::Arc, time::Instant};
+#[cfg(feature = "btree-book")]
+use std::collections::BTreeMap;
+use arc_swap::ArcSwap;
+use rust_decimal::{
+    prelude::{FromPrimitive, ToPrimitive},
+    Decimal,
+};
+use tokio::sync::{broadcast, watch};
+use tracing::{debug, warn};
+
+use super::{
+    backpressure::{BoundedBroadcast, DropPolicy, DropStats},
+    book_events::BookDiff,
+    clock::ClockSync,
+    integrity::{self, IntegrityMonitor, IntegrityStats},
+    ladder::Ladder,
+    latency::{LatencyStats, LatencyTracker},
+    pipeline::{self, Stage},
+    top_levels::{TopLevels, MAX_TOP_LEVELS},
+};
+
+const UPDATE_CHANNEL_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Bid,
+    Ask,
+}
+
+// (price, qty)
+pub type Level = (Decimal, Decimal);
+
+// Một phía của book, giá tăng dần: mặc định dạng SoA gọn cho chế độ nhiều symbol
+#[cfg(not(feature = "btree-book"))]
+pub use super::book_side::BookSide;
+#[cfg(feature = "btree-book")]
+pub type BookSide = BTreeMap<Decimal, Decimal>;
+
+// Parse chuỗi price/qty của sàn, chấp nhận cả dạng "1e-8"
+pub fn parse_decimal(s: &str) -> Option<Decimal> {
+    #[cfg(feature = "fast-float")]
+    if let Some(d) = parse_plain_decimal(s) {
+        return Some(d);
+    }
+    Decimal::from_str(s)
+        .or_else(|_| Decimal::from_scientific(s))
+        .ok()
+}
+
+// Dạng sàn gửi gần như mọi lúc ("-123.4500"): gom tối đa 19 chữ số vào u64 rồi dựng
+// Decimal giữ nguyên scale. Dạng khác (mũ, "_", dài hơn) trả None để qua `Decimal::from_str`
+#[cfg(feature = "fast-float")]
+fn parse_plain_decimal(s: &str) -> Option<Decimal> {
+    let bytes = s.as_bytes();
+    let (negative, digits) = match bytes.split_first()? {
+        (b'-', rest) => (true, rest),
+        _ => (false, bytes),
+    };
+    let (int, frac) = match digits.iter().position(|&b| b == b'.') {
+        Some(dot) => (&digits[..dot], &digits[dot + 1..]),
+        None => (digits, &[][..]),
+    };
+    if int.len() + frac.len() > 19 || int.len() + frac.len() == 0 || frac.len() > 28 {
+        return None;
+    }
+    let mut mantissa = 0u64;
+    for &b in int.iter().chain(frac) {
+        let digit = b.wrapping_sub(b'0');
+        if digit > 9 {
+            return None;
+        }
+        mantissa = mantissa * 10 + u64::from(digit);
+    }
+    Some(Decimal::from_parts(mantissa as u32, (mantissa >> 32) as u32, 0, negative, frac.len() as u32))
+}
+
+fn parse_level(price: &str, qty: &str) -> Option<Level> {
+    let (p, q) = (parse_decimal(price)?, parse_decimal(qty)?);
+    (p > Decimal::ZERO && q >= Decimal::ZERO).then_some((p, q))
+}
+
+pub fn to_f64(d: Decimal) -> f64 {
+    d.to_f64().unwrap_or(0.0)
+}
+
+// None với NaN/inf
+pub fn from_f64(v: f64) -> Option<Decimal> {
+    Decimal::from_f64(v)
+}
+
+pub fn level_to_f64((p, q): Level) -> (f64, f64) {
+    (to_f64(p), to_f64(q))
+}
+
+// Price/qty dùng Decimal để level so sánh chính xác (không NaN, không sai số làm tròn)
+#[derive(Debug, Clone)]
+pub struct OrderbookSnapshot {
+    pub timestamp: DateTime<Utc>,
+    pub last_update_id: u64,
+    pub bids: BookSide,
+    pub asks: BookSide,
+    // lúc nhận message (monotonic) tạo ra bản này, None với book từ REST / replay / đĩa
+    pub received: Option<Instant>,
+}
+
+impl Default for OrderbookSnapshot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OrderbookSnapshot {
+    pub fn new() -> Self {
+        Self {
+            timestamp: Utc::now(),
+            last_update_id: 0,
+            bids: BookSide::new(),
+            asks: BookSide::new(),
+            received: None,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.bids.clear();
+        self.asks.clear();
+    }
+
+    // qty = 0 nghĩa là xoá level (quy ước chung của các sàn)
+    pub fn set_level(&mut self, side: Side, price: Decimal, qty: Decimal) {
+        let book = match side {
+            Side::Bid => &mut self.bids,
+            Side::Ask => &mut self.asks,
+        };
+        if qty > Decimal::ZERO {
+            book.insert(price, qty);
+        } else {
+            book.remove(&price);
+        }
+    }
+
+    // chuỗi không parse được, giá <= 0 hoặc qty âm thì bỏ qua, tránh chèn level rác vào book
+    pub fn set_level_str(&mut self, side: Side, price: &str, qty: &str) {
+        if let Some((p, q)) = parse_level(price, qty) {
+            self.set_level(side, p, q);
+        }
+    }
+
+    // Thay cả một phía (snapshot), level lỗi bị bỏ như `set_level_str`.
+    // Insert từng level: `collect` (sort + bulk build) chậm hơn ~3x với 20 level
+    pub fn replace_levels_str(&mut self, side: Side, levels: &[[&str; 2]]) {
+        let book = match side {
+            Side::Bid => &mut self.bids,
+            Side::Ask => &mut self.asks,
+        };
+        book.clear();
+        for (price, qty) in levels.iter().filter_map(|[price, qty]| parse_level(price, qty)) {
+            if qty > Decimal::ZERO {
+                book.insert(price, qty);
+            }
+        }
+    }
+
+    pub fn best_bid(&self) -> Option<Level> {
+        self.bids.iter().next_back().map(|(p, q)| (*p, *q))
+    }
+
+    pub fn best_ask(&self) -> Option<Level> {
+        self.asks.iter().next().map(|(p, q)| (*p, *q))
+    }
+
+    pub fn best_bid_ask(&self) -> Option<(Level, Level)> {
+        match (self.best_bid(), self.best_ask()) {
+            (Some(bid), Some(ask)) => Some((bid, ask)),
+            _ => None,
+        }
+    }
+
+    // `timestamp` là lúc nhận update cuối cùng (giờ local)
+    pub fn age(&self) -> Duration {
+        Utc::now() - self.timestamp
+    }
+
+    pub fn is_stale(&self, max_age: Duration) -> bool {
+        self.age() > max_age
+    }
+}
+
+
+// Orderbook dùng chung giữa writer (WS task) và consumer.
+// Writer giữ một bản làm việc riêng, sau mỗi update publish một snapshot bất biến
+// (copy-on-write) qua ArcSwap nên reader không bao giờ phải chờ lock của writer.
+// Đồng thời publish qua:
+// - watch: chỉ giữ bản mới nhất, phù hợp cho consumer cần state hiện tại
+// - broadcast: mọi update, consumer chậm mất update theo `DropPolicy` (có đếm)
+// - events: thay đổi theo từng level, chỉ tính diff khi có subscriber
+// Mỗi bản publish được kiểm tra invariant (không crossed, update id không lùi, không level qty 0)
+#[derive(Debug)]
+pub struct SharedOrderbook {
+    writer: std::sync::Mutex<OrderbookSnapshot>,
+    current: ArcSwap<OrderbookSnapshot>,
+    latest_tx: watch::Sender<Arc<OrderbookSnapshot>>,
+    updates_tx: BoundedBroadcast<Arc<OrderbookSnapshot>>,
+    events_tx: BoundedBroadcast<Arc<BookDiff>>,
+    // top MAX_TOP_LEVELS level mỗi phía, cập nhật mỗi lần publish, chỉ báo khi level đổi
+    top_tx: watch::Sender<TopLevels<MAX_TOP_LEVELS>>,
+    latency: std::sync::Mutex<LatencyTracker>,
+    // có clock thì latency tính theo giờ sàn, không bị lệch đồng hồ local
+    clock: std::sync::OnceLock<ClockSync>,
+    integrity: IntegrityMonitor,
+}
+
+impl Default for SharedOrderbook {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SharedOrderbook {
+    pub fn new() -> Self {
+        let book = OrderbookSnapshot::new();
+        let snap = Arc::new(book.clone());
+        let (latest_tx, _) = watch::channel(snap.clone());
+        let updates_tx = BoundedBroadcast::new(UPDATE_CHANNEL_CAPACITY);
+        let events_tx = BoundedBroadcast::new(UPDATE_CHANNEL_CAPACITY);
+        let (top_tx, _) = watch::channel(TopLevels::default());
+        Self {
+            writer: std::sync::Mutex::new(book),
+            current: ArcSwap::new(snap),
+            latest_tx,
+            updates_tx,
+            events_tx,
+            top_tx,
+            latency: std::sync::Mutex::new(LatencyTracker::default()),
+            clock: std::sync::OnceLock::new(),
+            integrity: IntegrityMonitor::default(),
+        }
+    }
+
+    // `f` trả về true nếu book thay đổi và cần publish
+    pub fn update<F>(&self, f: F) -> bool
+    where
+        F: FnOnce(&mut OrderbookSnapshot) -> bool,
+    {
+        let mut book = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        if !f(&mut book) {
+            book.received = None;
+            return false;
+        }
+        // stamp chỉ thuộc về bản publish này, không dính sang update sau
+        let received = book.received.take();
+        let snap = Arc::new(OrderbookSnapshot { received, ..book.clone() });
+        let prev = self.current.load();
+        if self.integrity.is_enabled() {
+            let violations = integrity::check(prev.last_update_id, &snap);
+            if self.integrity.record(&violations) {
+                warn!(count = violations.len(), "orderbook invariant violated: {}", violations[0]);
+                debug!("book at violation\n{}", Ladder::new(&snap, 5));
+            }
+        }
+        // diff với bản đã publish trước đó, vẫn giữ lock writer để thứ tự event đúng
+        let diff = (self.events_tx.receiver_count() > 0).then(|| BookDiff::between(&prev, &snap));
+        drop(prev);
+        self.current.store(snap.clone());
+        let top = TopLevels::from_snapshot(&snap);
+        self.top_tx.send_if_modified(|cur| {
+            let changed = !cur.same_levels(&top);
+            *cur = top;
+            changed
+        });
+        drop(book);
+
+        if let Some(diff) = diff.filter(|d| !d.is_empty()) {
+            self.events_tx.send(Arc::new(diff));
+        }
+        self.latest_tx.send_replace(snap.clone());
+        self.updates_tx.send(snap);
+        pipeline::record(Stage::BookApply, received);
+        true
+    }
+
+    // message nhận lúc `received` vừa parse xong: ghi stage parse, update kế tiếp mang stamp này
+    pub fn mark_parsed(&self, received: Instant) {
+        pipeline::pipeline().record(Stage::Parse, received);
+        self.writer.lock().unwrap_or_else(|e| e.into_inner()).received = Some(received);
+    }
+
+    pub fn read<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&OrderbookSnapshot) -> R,
+    {
+        f(&self.current.load())
+    }
+
+    pub fn snapshot(&self) -> Arc<OrderbookSnapshot> {
+        self.current.load_full()
+    }
+
+    pub fn best_bid_ask(&self) -> Option<(Level, Level)> {
+        self.current.load().best_bid_ask()
+    }
+
+    pub fn watch(&self) -> watch::Receiver<Arc<OrderbookSnapshot>> {
+        self.latest_tx.subscribe()
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<OrderbookSnapshot>> {
+        self.updates_tx.subscribe()
+    }
+
+    // N level tốt nhất mỗi phía (N <= MAX_TOP_LEVELS), đọc bản đã tính sẵn lúc publish
+    pub fn top_n<const N: usize>(&self) -> TopLevels<N> {
+        self.top_tx.borrow().truncate::<N>()
+    }
+
+    // chỉ báo khi top MAX_TOP_LEVELS level đổi, strategy chỉ cần vài level đầu không bị đánh thức vô ích
+    pub fn subscribe_top(&self) -> watch::Receiver<TopLevels<MAX_TOP_LEVELS>> {
+        self.top_tx.subscribe()
+    }
+
+    // diff theo level của từng update (không gồm update trước khi subscribe)
+    pub fn subscribe_events(&self) -> broadcast::Receiver<Arc<BookDiff>> {
+        self.events_tx.subscribe()
+    }
+
+    pub fn is_stale(&self, max_age: Duration) -> bool {
+        self.current.load().is_stale(max_age)
+    }
+
+    // áp cho cả update và level event
+    pub fn set_drop_policy(&self, policy: DropPolicy) {
+        self.updates_tx.set_policy(policy);
+        self.events_tx.set_policy(policy);
+    }
+
+    // update + level event đã gửi / bị bỏ vì consumer chậm
+    pub fn drop_stats(&self) -> DropStats {
+        let (updates, events) = (self.updates_tx.stats(), self.events_tx.stats());
+        DropStats { sent: updates.sent + events.sent, dropped: updates.dropped + events.dropped }
+    }
+
+    // chỉ set được một lần, feed gắn lúc khởi động
+    pub fn set_clock(&self, clock: ClockSync) {
+        let _ = self.clock.set(clock);
+    }
+
+    // event_time_ms: field `E` (ms) của sàn, so với giờ nhận (quy về giờ sàn nếu có clock)
+    pub fn record_latency(&self, event_time_ms: i64) {
+        let Some(event_time) = DateTime::from_timestamp_millis(event_time_ms) else {
+            return;
+        };
+        let received = self.clock.get().map_or_else(Utc::now, ClockSync::exchange_now);
+        self.latency.lock().unwrap_or_else(|e| e.into_inner()).record(event_time, received);
+    }
+
+    pub fn integrity(&self) -> &IntegrityMonitor {
+        &self.integrity
+    }
+
+    pub fn integrity_stats(&self) -> IntegrityStats {
+        self.integrity.stats()
+    }
+
+    pub fn latency_stats(&self) -> Option<LatencyStats> {
+        self.latency.lock().unwrap_or_else(|e| e.into_inner()).stats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::book_events::BookEvent;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_set_level_insert_and_remove() {
+        let mut ob = OrderbookSnapshot::new();
+        ob.set_level_str(Side::Bid, "100.0", "1.5");
+        ob.set_level_str(Side::Bid, "99.5", "2.0");
+        ob.set_level_str(Side::Ask, "100.5", "1.0");
+        assert_eq!(ob.best_bid_ask(), Some(((dec!(100.0), dec!(1.5)), (dec!(100.5), dec!(1.0)))));
+
+        ob.set_level_str(Side::Bid, "100.0", "0.00000000");
+        assert_eq!(ob.best_bid(), Some((dec!(99.5), dec!(2.0))));
+        assert_eq!(ob.bids.len(), 1);
+
+        // cùng giá nhưng khác scale vẫn là một level
+        ob.set_level_str(Side::Ask, "100.50000000", "3.0");
+        assert_eq!(ob.asks.len(), 1);
+        assert_eq!(ob.best_ask(), Some((dec!(100.5), dec!(3.0))));
+
+        ob.set_level_str(Side::Ask, "abc", "1.0");
+        assert_eq!(ob.asks.len(), 1);
+    }
+
+    #[test]
+    fn test_decimal_conversions() {
+        assert_eq!(parse_decimal("0.00010000"), Some(dec!(0.0001)));
+        assert_eq!(parse_decimal("1e-8"), Some(dec!(0.00000001)));
+        assert_eq!(parse_decimal("nan"), None);
+        // fast path giữ đúng giá trị và scale như `Decimal::from_str`, dạng lạ rơi về parser chuẩn
+        for s in ["30000.01000000", "-0.5", "12", "7.", "18446744073709551615", "1_000.5", "0.1234567890123456789012", "-", "1.2.3"] {
+            let parsed = parse_decimal(s);
+            assert_eq!(parsed, Decimal::from_str(s).ok(), "{}", s);
+            assert_eq!(parsed.map(|d| d.scale()), Decimal::from_str(s).ok().map(|d| d.scale()), "{}", s);
+        }
+        assert_eq!(from_f64(f64::NAN), None);
+        assert_eq!(level_to_f64((dec!(25.3519), dec!(31.21))), (25.3519, 31.21));
+        // 0.1 + 0.2 cộng chính xác, khác f64
+        assert_eq!(dec!(0.1) + dec!(0.2), dec!(0.3));
+    }
+
+    #[tokio::test]
+    async fn test_shared_orderbook_publishes_updates() {
+        let shared = SharedOrderbook::new();
+        let mut latest = shared.watch();
+        let mut updates = shared.subscribe();
+
+        let changed = shared.update(|ob| {
+            ob.set_level(Side::Bid, dec!(10), dec!(1));
+            ob.set_level(Side::Ask, dec!(11), dec!(2));
+            true
+        });
+        assert!(changed);
+        assert!(!shared.update(|_| false));
+        assert_eq!(shared.best_bid_ask(), Some(((dec!(10), dec!(1)), (dec!(11), dec!(2)))));
+
+        latest.changed().await.unwrap();
+        assert_eq!(latest.borrow().best_bid(), Some((dec!(10), dec!(1))));
+
+        let snap = updates.recv().await.unwrap();
+        assert_eq!(snap.best_ask(), Some((dec!(11), dec!(2))));
+        // update không thay đổi gì thì không publish
+        assert!(updates.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_shared_orderbook_publishes_level_events() {
+        let shared = SharedOrderbook::new();
+        shared.update(|ob| {
+            ob.set_level(Side::Bid, dec!(10), dec!(1));
+            true
+        });
+        let mut events = shared.subscribe_events();
+        shared.update(|ob| {
+            ob.set_level(Side::Bid, dec!(10), dec!(0.4));
+            true
+        });
+        // publish lại nhưng không đổi level nào
+        shared.update(|_| true);
+
+        let diff = events.try_recv().unwrap();
+        assert_eq!(
+            diff.events,
+            vec![BookEvent::LevelChanged { side: Side::Bid, price: dec!(10), old_qty: dec!(1), new_qty: dec!(0.4) }]
+        );
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_reader_snapshot_is_immutable() {
+        let shared = SharedOrderbook::new();
+        shared.update(|ob| {
+            ob.set_level(Side::Bid, dec!(10), dec!(1));
+            true
+        });
+        let before = shared.snapshot();
+
+        shared.update(|ob| {
+            ob.set_level(Side::Bid, dec!(10), Decimal::ZERO);
+            true
+        });
+
+        // snapshot đã load không bị writer thay đổi
+        assert_eq!(before.best_bid(), Some((dec!(10), dec!(1))));
+        assert_eq!(shared.read(|ob| ob.bids.len()), 0);
+    }
+
+    #[test]
+    fn test_staleness_and_latency() {
+        let shared = SharedOrderbook::new();
+        shared.update(|ob| {
+            ob.timestamp = Utc::now() - Duration::seconds(10);
+            true
+        });
+        assert!(shared.is_stale(Duration::seconds(5)));
+        assert!(!shared.is_stale(Duration::seconds(30)));
+
+        assert_eq!(shared.latency_stats(), None);
+        shared.record_latency(Utc::now().timestamp_millis() - 100);
+        let stats = shared.latency_stats().unwrap();
+        assert_eq!(stats.count, 1);
+        assert!(stats.p99_ms >= 100.0);
+
+        // stamp nhận chỉ gắn vào bản publish ngay sau đó
+        let received = Instant::now();
+        shared.mark_parsed(received);
+        assert!(!shared.update(|_| false));
+        shared.update(|_| true);
+        assert_eq!(shared.snapshot().received, None);
+        shared.mark_parsed(received);
+        shared.update(|_| true);
+        assert_eq!(shared.snapshot().received, Some(received));
+        shared.update(|_| true);
+        assert_eq!(shared.snapshot().received, None);
+    }
+}
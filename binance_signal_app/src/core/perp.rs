@@ -0,0 +1,91 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::trade::TradeSide;
+
+// Binance USDⓈ-M trả funding mỗi 8h
+pub const FUNDING_PER_YEAR: f64 = 3.0 * 365.0;
+
+// Trạng thái perp của một symbol: mark/index (stream markPrice), funding, open interest (REST)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PerpStats {
+    pub symbol: String,
+    pub timestamp: DateTime<Utc>,
+    pub mark_price: f64,
+    pub index_price: f64,
+    // funding đã chốt gần nhất, None tới khi poll REST lần đầu
+    pub funding_rate: Option<f64>,
+    // funding dự kiến cho kỳ kế tiếp (field `r` của markPrice)
+    pub predicted_funding_rate: f64,
+    pub next_funding_time: DateTime<Utc>,
+    // số contract (base asset)
+    pub open_interest: Option<f64>,
+}
+
+impl PerpStats {
+    pub fn new(symbol: &str) -> Self {
+        Self {
+            symbol: symbol.to_uppercase(),
+            timestamp: Utc::now(),
+            mark_price: 0.0,
+            index_price: 0.0,
+            funding_rate: None,
+            predicted_funding_rate: 0.0,
+            next_funding_time: DateTime::default(),
+            open_interest: None,
+        }
+    }
+
+    // (mark - index) / index, bps
+    pub fn basis_bps(&self) -> Option<f64> {
+        (self.index_price > 0.0).then(|| (self.mark_price - self.index_price) / self.index_price * 10_000.0)
+    }
+
+    // lợi suất năm của việc short perp nếu funding giữ nguyên
+    pub fn annualized_funding(&self) -> f64 {
+        self.predicted_funding_rate * FUNDING_PER_YEAR
+    }
+
+    pub fn open_interest_notional(&self) -> Option<f64> {
+        self.open_interest.map(|oi| oi * self.mark_price)
+    }
+}
+
+// Lệnh thanh lý bắt buộc, `side` là phía của lệnh: Sell = vị thế long bị thanh lý
+#[derive(Debug, Clone, PartialEq)]
+pub struct Liquidation {
+    pub symbol: String,
+    pub side: TradeSide,
+    pub price: f64,
+    pub qty: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Liquidation {
+    pub fn is_long(&self) -> bool {
+        self.side == TradeSide::Sell
+    }
+
+    pub fn notional(&self) -> f64 {
+        self.price * self.qty
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_basis_and_carry() {
+        let mut stats = PerpStats::new("btcusdt");
+        assert_eq!((stats.symbol.as_str(), stats.basis_bps()), ("BTCUSDT", None));
+
+        stats.mark_price = 30_030.0;
+        stats.index_price = 30_000.0;
+        stats.predicted_funding_rate = 0.0001;
+        stats.open_interest = Some(2.0);
+        assert!((stats.basis_bps().unwrap() - 10.0).abs() < 1e-9);
+        assert!((stats.annualized_funding() - 0.1095).abs() < 1e-9);
+        assert_eq!(stats.open_interest_notional(), Some(60_060.0));
+    }
+}
@@ -0,0 +1,190 @@
+use serde::Serialize;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        LazyLock,
+    },
+    time::Instant,
+};
+
+// Biên trên (µs) của từng bucket, bucket cuối cùng là +inf
+pub const BUCKETS_US: [u64; 14] = [10, 25, 50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000, 25_000, 50_000, 100_000, 250_000];
+
+// Các mốc trên hot path, đo bằng thời gian từ lúc nhận message (monotonic) tới mốc đó.
+// Chi phí của một stage là hiệu percentile giữa stage đó và stage trước.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Parse,
+    BookApply,
+    Signal,
+    Decision,
+    OrderSend,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 5] = [Stage::Parse, Stage::BookApply, Stage::Signal, Stage::Decision, Stage::OrderSend];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stage::Parse => "parse",
+            Stage::BookApply => "book_apply",
+            Stage::Signal => "signal",
+            Stage::Decision => "decision",
+            Stage::OrderSend => "order_send",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Histogram {
+    counts: [AtomicU64; BUCKETS_US.len() + 1],
+    sum_us: AtomicU64,
+    max_us: AtomicU64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            counts: std::array::from_fn(|_| AtomicU64::new(0)),
+            sum_us: AtomicU64::new(0),
+            max_us: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Histogram {
+    pub fn record_us(&self, us: u64) {
+        let idx = BUCKETS_US.partition_point(|b| *b < us);
+        self.counts[idx].fetch_add(1, Ordering::Relaxed);
+        self.sum_us.fetch_add(us, Ordering::Relaxed);
+        self.max_us.fetch_max(us, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> HistogramSnapshot {
+        let buckets: Vec<u64> = self.counts.iter().map(|c| c.load(Ordering::Relaxed)).collect();
+        let count = buckets.iter().sum();
+        let max_us = self.max_us.load(Ordering::Relaxed);
+        HistogramSnapshot {
+            count,
+            sum_us: self.sum_us.load(Ordering::Relaxed),
+            max_us,
+            p50_us: quantile(&buckets, count, max_us, 0.5),
+            p99_us: quantile(&buckets, count, max_us, 0.99),
+            buckets,
+        }
+    }
+
+    pub fn reset(&self) {
+        for c in &self.counts {
+            c.store(0, Ordering::Relaxed);
+        }
+        self.sum_us.store(0, Ordering::Relaxed);
+        self.max_us.store(0, Ordering::Relaxed);
+    }
+}
+
+// biên trên của bucket chứa quantile q, bucket +inf thì lấy max
+fn quantile(buckets: &[u64], count: u64, max_us: u64, q: f64) -> Option<u64> {
+    if count == 0 {
+        return None;
+    }
+    let rank = ((q * count as f64).ceil() as u64).max(1);
+    let mut seen = 0;
+    for (i, n) in buckets.iter().enumerate() {
+        seen += n;
+        if seen >= rank {
+            return Some(BUCKETS_US.get(i).map_or(max_us, |b| (*b).min(max_us)));
+        }
+    }
+    Some(max_us)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HistogramSnapshot {
+    pub count: u64,
+    pub sum_us: u64,
+    pub max_us: u64,
+    pub p50_us: Option<u64>,
+    pub p99_us: Option<u64>,
+    // số mẫu theo BUCKETS_US, phần tử cuối là bucket +inf
+    pub buckets: Vec<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StageLatency {
+    pub stage: &'static str,
+    #[serde(flatten)]
+    pub histogram: HistogramSnapshot,
+}
+
+// Histogram theo stage, dùng chung cả process (feed, signal engine, strategy ghi vào cùng một chỗ)
+#[derive(Debug, Default)]
+pub struct PipelineLatency {
+    stages: [Histogram; Stage::ALL.len()],
+}
+
+impl PipelineLatency {
+    pub fn record(&self, stage: Stage, received: Instant) {
+        let us = received.elapsed().as_micros().min(u64::MAX as u128) as u64;
+        self.stages[stage as usize].record_us(us);
+    }
+
+    pub fn histogram(&self, stage: Stage) -> &Histogram {
+        &self.stages[stage as usize]
+    }
+
+    pub fn snapshot(&self) -> Vec<StageLatency> {
+        Stage::ALL
+            .iter()
+            .map(|s| StageLatency { stage: s.name(), histogram: self.histogram(*s).snapshot() })
+            .collect()
+    }
+
+    pub fn reset(&self) {
+        for h in &self.stages {
+            h.reset();
+        }
+    }
+}
+
+static PIPELINE: LazyLock<PipelineLatency> = LazyLock::new(PipelineLatency::default);
+
+pub fn pipeline() -> &'static PipelineLatency {
+    &PIPELINE
+}
+
+// ghi `stage` nếu message có stamp lúc nhận (book từ replay / REST thì không có)
+pub fn record(stage: Stage, received: Option<Instant>) {
+    if let Some(received) = received {
+        PIPELINE.record(stage, received);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_buckets_and_quantiles() {
+        let h = Histogram::default();
+        assert_eq!(h.snapshot().p50_us, None);
+        for us in [5, 8, 40, 90, 300] {
+            h.record_us(us);
+        }
+        h.record_us(1_000_000);
+        let snap = h.snapshot();
+        assert_eq!((snap.count, snap.max_us, snap.sum_us), (6, 1_000_000, 1_000_443));
+        assert_eq!(snap.buckets[0], 2);
+        assert_eq!(*snap.buckets.last().unwrap(), 1);
+        // rank 3 rơi vào bucket <= 50µs
+        assert_eq!(snap.p50_us, Some(50));
+        assert_eq!(snap.p99_us, Some(1_000_000));
+
+        let pipeline = PipelineLatency::default();
+        pipeline.record(Stage::Signal, Instant::now());
+        let stages = pipeline.snapshot();
+        assert_eq!(stages.iter().map(|s| s.stage).collect::<Vec<_>>(), ["parse", "book_apply", "signal", "decision", "order_send"]);
+        assert_eq!(stages[2].histogram.count, 1);
+        assert_eq!(stages[0].histogram.count, 0);
+    }
+}
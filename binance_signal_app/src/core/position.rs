@@ -0,0 +1,96 @@
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use super::order::Fill;
+
+// Vị thế một symbol: qty > 0 là long, < 0 là short
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Position {
+    pub qty: Decimal,
+    pub avg_price: Decimal,
+    pub realized_pnl: Decimal,
+    pub fees: Decimal,
+}
+
+impl Position {
+    pub fn apply_fill(&mut self, fill: &Fill) {
+        let signed = fill.qty * fill.side.sign();
+        self.fees += fill.fee;
+
+        if self.qty.is_zero() || self.qty.is_sign_positive() == signed.is_sign_positive() {
+            // mở thêm cùng chiều: cập nhật giá vốn
+            let total = self.qty.abs() + fill.qty;
+            self.avg_price = (self.avg_price * self.qty.abs() + fill.price * fill.qty) / total;
+            self.qty += signed;
+            return;
+        }
+
+        // đóng (một phần hoặc toàn bộ), phần dư mở vị thế ngược chiều tại giá fill
+        let closing = self.qty.abs().min(fill.qty);
+        let direction = if self.qty.is_sign_positive() { Decimal::ONE } else { Decimal::NEGATIVE_ONE };
+        self.realized_pnl += closing * (fill.price - self.avg_price) * direction;
+        self.qty += signed;
+
+        if self.qty.is_zero() {
+            self.avg_price = Decimal::ZERO;
+        } else if fill.qty > closing {
+            self.avg_price = fill.price;
+        }
+    }
+
+    pub fn unrealized_pnl(&self, mark: Decimal) -> Decimal {
+        self.qty * (mark - self.avg_price)
+    }
+
+    // realized - fees
+    pub fn net_realized_pnl(&self) -> Decimal {
+        self.realized_pnl - self.fees
+    }
+
+    pub fn is_flat(&self) -> bool {
+        self.qty.is_zero()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::order::OrderSide;
+    use chrono::Utc;
+    use rust_decimal_macros::dec;
+
+    fn fill(side: OrderSide, price: Decimal, qty: Decimal) -> Fill {
+        Fill {
+            order_id: 1,
+            symbol: "BTCUSDT".into(),
+            side,
+            price,
+            qty,
+            fee: dec!(0.1),
+            is_maker: true,
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_open_add_reduce_and_flip() {
+        let mut pos = Position::default();
+        pos.apply_fill(&fill(OrderSide::Buy, dec!(100), dec!(1)));
+        pos.apply_fill(&fill(OrderSide::Buy, dec!(110), dec!(1)));
+        assert_eq!((pos.qty, pos.avg_price), (dec!(2), dec!(105)));
+        assert_eq!(pos.unrealized_pnl(dec!(106)), dec!(2));
+
+        pos.apply_fill(&fill(OrderSide::Sell, dec!(120), dec!(1)));
+        assert_eq!((pos.qty, pos.avg_price, pos.realized_pnl), (dec!(1), dec!(105), dec!(15)));
+
+        // bán 3 khi đang long 1 -> short 2 tại 100
+        pos.apply_fill(&fill(OrderSide::Sell, dec!(100), dec!(3)));
+        assert_eq!((pos.qty, pos.avg_price, pos.realized_pnl), (dec!(-2), dec!(100), dec!(10)));
+        assert_eq!(pos.unrealized_pnl(dec!(90)), dec!(20));
+
+        pos.apply_fill(&fill(OrderSide::Buy, dec!(95), dec!(2)));
+        assert!(pos.is_flat());
+        assert_eq!(pos.realized_pnl, dec!(20));
+        assert_eq!(pos.net_realized_pnl(), dec!(19.5));
+    }
+}
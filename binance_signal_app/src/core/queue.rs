@@ -0,0 +1,112 @@
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+
+use super::{
+    order::OrderSide,
+    orderbook::{to_f64, OrderbookSnapshot},
+};
+
+// Vị trí trong hàng đợi của một limit order đang chờ tại một mức giá, ước lượng từ
+// thay đổi depth và trade tại mức giá đó. `level_qty` là khối lượng của người khác
+// (không tính order của mình)
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuePosition {
+    // khối lượng đứng trước order
+    pub ahead: Decimal,
+    level_qty: Decimal,
+    // volume trade tại mức giá kể từ lúc đặt, để ước lượng tốc độ khớp
+    traded: Decimal,
+    since: DateTime<Utc>,
+    last_update: DateTime<Utc>,
+}
+
+impl QueuePosition {
+    // order mới xếp sau toàn bộ level hiện có
+    pub fn new(level_qty: Decimal, ts: DateTime<Utc>) -> Self {
+        Self { ahead: level_qty, level_qty, traded: Decimal::ZERO, since: ts, last_update: ts }
+    }
+
+    // Level tăng = order mới xếp sau mình. Level giảm (ngoài phần trade đã trừ) = huỷ,
+    // chia cho phía trước / phía sau theo tỉ lệ khối lượng
+    pub fn on_level(&mut self, level_qty: Decimal, ts: DateTime<Utc>) {
+        if level_qty < self.level_qty {
+            let canceled = self.level_qty - level_qty;
+            self.ahead -= canceled * self.ahead / self.level_qty;
+        }
+        self.ahead = self.ahead.min(level_qty).max(Decimal::ZERO);
+        self.level_qty = level_qty;
+        self.touch(ts);
+    }
+
+    // Trade `qty` tại đúng mức giá: ăn phía trước trước, trả về phần tới lượt order mình
+    pub fn on_trade(&mut self, qty: Decimal, ts: DateTime<Utc>) -> Decimal {
+        let consumed = self.ahead.min(qty);
+        self.ahead -= consumed;
+        self.level_qty = (self.level_qty - qty).max(Decimal::ZERO);
+        self.traded += qty;
+        self.touch(ts);
+        qty - consumed
+    }
+
+    // trade xuyên qua mức giá: cả level đã bị ăn
+    pub fn clear(&mut self, ts: DateTime<Utc>) {
+        self.ahead = Decimal::ZERO;
+        self.level_qty = Decimal::ZERO;
+        self.touch(ts);
+    }
+
+    // volume khớp tại mức giá mỗi giây, None khi chưa có trade
+    pub fn fill_rate(&self) -> Option<f64> {
+        let secs = (self.last_update - self.since).num_milliseconds() as f64 / 1000.0;
+        (secs > 0.0 && self.traded > Decimal::ZERO).then(|| to_f64(self.traded) / secs)
+    }
+
+    // thời gian ước lượng tới khi khớp hết `remaining` nếu tốc độ khớp giữ nguyên
+    pub fn time_to_fill(&self, remaining: Decimal) -> Option<Duration> {
+        let rate = self.fill_rate()?;
+        let secs = to_f64(self.ahead + remaining) / rate;
+        Some(Duration::milliseconds((secs * 1000.0) as i64))
+    }
+
+    fn touch(&mut self, ts: DateTime<Utc>) {
+        self.last_update = self.last_update.max(ts);
+    }
+}
+
+// khối lượng trên book tại `price`, phía mà order `side` nằm chờ
+pub fn level_qty(book: &OrderbookSnapshot, side: OrderSide, price: Decimal) -> Decimal {
+    let levels = match side {
+        OrderSide::Buy => &book.bids,
+        OrderSide::Sell => &book.asks,
+    };
+    levels.get(&price).copied().unwrap_or(Decimal::ZERO)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_queue_depletion_and_eta() {
+        let t0 = Utc::now();
+        let mut q = QueuePosition::new(dec!(10), t0);
+        assert_eq!(q.time_to_fill(dec!(1)), None);
+
+        // thêm 10 phía sau, rồi huỷ 5 -> phía trước (10/20) mất 2.5
+        q.on_level(dec!(20), t0);
+        q.on_level(dec!(15), t0);
+        assert_eq!(q.ahead, dec!(7.5));
+
+        // trade 4 sau 2s -> còn 3.5 phía trước, 2 qty/s
+        assert_eq!(q.on_trade(dec!(4), t0 + Duration::seconds(2)), Decimal::ZERO);
+        assert_eq!(q.ahead, dec!(3.5));
+        assert_eq!(q.time_to_fill(dec!(0.5)), Some(Duration::seconds(2)));
+
+        // book sau trade khớp với phần đã trừ -> không tính là huỷ
+        q.on_level(dec!(11), t0 + Duration::seconds(2));
+        assert_eq!(q.ahead, dec!(3.5));
+        assert_eq!(q.on_trade(dec!(5), t0 + Duration::seconds(3)), dec!(1.5));
+        assert_eq!(q.ahead, Decimal::ZERO);
+    }
+}
@@ -0,0 +1,191 @@
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use super::orderbook::OrderbookSnapshot;
+
+// Cách lấy mẫu snapshot trước khi ghi / đưa vào SignalEngine.
+// TOML: { mode = "every" } | { mode = "interval", ms = 500 } | { mode = "best_change" } | { mode = "bps", bps = 1.0 }
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum SamplePolicy {
+    #[default]
+    Every,
+    // tối đa một snapshot mỗi `ms`
+    Interval { ms: u64 },
+    // chỉ khi best bid hoặc best ask đổi giá
+    BestChange,
+    // khi best bid / ask lệch quá `bps` so với lần lấy mẫu trước
+    Bps { bps: f64 },
+}
+
+impl SamplePolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            SamplePolicy::Interval { ms: 0 } => Err("sampling interval must be > 0 ms".to_string()),
+            SamplePolicy::Bps { bps } if !bps.is_finite() || *bps <= 0.0 => Err(format!("sampling bps must be > 0, got {}", bps)),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SamplingConfig {
+    pub default: SamplePolicy,
+    // override theo symbol, không phân biệt hoa thường
+    pub symbols: HashMap<String, SamplePolicy>,
+}
+
+impl SamplingConfig {
+    pub fn policy(&self, symbol: &str) -> SamplePolicy {
+        self.symbols
+            .iter()
+            .find(|(s, _)| s.eq_ignore_ascii_case(symbol))
+            .map(|(_, p)| *p)
+            .unwrap_or(self.default)
+    }
+
+    // `sample_interval_ms` cũ chỉ có hiệu lực khi default vẫn là every
+    pub fn with_interval(mut self, interval: Option<Duration>) -> Self {
+        if let (SamplePolicy::Every, Some(interval)) = (self.default, interval) {
+            self.default = SamplePolicy::Interval { ms: interval.num_milliseconds().max(1) as u64 };
+        }
+        self
+    }
+
+    pub fn validate(&self) -> Vec<String> {
+        std::iter::once(("default", &self.default))
+            .chain(self.symbols.iter().map(|(s, p)| (s.as_str(), p)))
+            .filter_map(|(name, p)| p.validate().err().map(|e| format!("{}: {}", name, e)))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    ts: DateTime<Utc>,
+    bid: Option<Decimal>,
+    ask: Option<Decimal>,
+}
+
+// lệch tương đối theo bps, phía nào trống thì coi là đổi
+fn moved_bps(prev: Option<Decimal>, next: Option<Decimal>) -> Option<Decimal> {
+    match (prev, next) {
+        (Some(a), Some(b)) if !a.is_zero() => Some(((b - a) / a).abs() * Decimal::from(10_000)),
+        (a, b) if a == b => Some(Decimal::ZERO),
+        _ => None,
+    }
+}
+
+// Quyết định có giữ snapshot hay không, trạng thái riêng cho từng symbol
+#[derive(Debug, Clone, Default)]
+pub struct Sampler {
+    config: SamplingConfig,
+    last: HashMap<String, Sample>,
+}
+
+impl Sampler {
+    // None = ghi tất cả
+    pub fn new(interval: Option<Duration>) -> Self {
+        Self::with_config(SamplingConfig::default().with_interval(interval))
+    }
+
+    pub fn with_config(config: SamplingConfig) -> Self {
+        Self { config, last: HashMap::new() }
+    }
+
+    pub fn is_passthrough(&self) -> bool {
+        self.config.default == SamplePolicy::Every && self.config.symbols.values().all(|p| *p == SamplePolicy::Every)
+    }
+
+    // chỉ xét thời gian: best_change / bps luôn giữ vì không có book
+    pub fn should_record(&mut self, symbol: &str, ts: DateTime<Utc>) -> bool {
+        self.check(symbol, Sample { ts, bid: None, ask: None }, false)
+    }
+
+    pub fn should_record_book(&mut self, symbol: &str, snap: &OrderbookSnapshot) -> bool {
+        let sample = Sample { ts: snap.timestamp, bid: snap.best_bid().map(|l| l.0), ask: snap.best_ask().map(|l| l.0) };
+        self.check(symbol, sample, true)
+    }
+
+    fn check(&mut self, symbol: &str, sample: Sample, has_book: bool) -> bool {
+        let policy = self.config.policy(symbol);
+        let keep = match (policy, self.last.get(symbol)) {
+            (SamplePolicy::Every, _) | (_, None) => true,
+            (SamplePolicy::Interval { ms }, Some(last)) => sample.ts - last.ts >= Duration::milliseconds(ms as i64),
+            (_, Some(_)) if !has_book => true,
+            (SamplePolicy::BestChange, Some(last)) => (last.bid, last.ask) != (sample.bid, sample.ask),
+            (SamplePolicy::Bps { bps }, Some(last)) => {
+                let threshold = Decimal::try_from(bps).unwrap_or(Decimal::ZERO);
+                [moved_bps(last.bid, sample.bid), moved_bps(last.ask, sample.ask)]
+                    .into_iter()
+                    .any(|m| m.is_none_or(|m| m >= threshold))
+            }
+        };
+        if keep && policy != SamplePolicy::Every {
+            self.last.insert(symbol.to_string(), sample);
+        }
+        keep
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::orderbook::Side;
+    use figment::{
+        providers::{Format, Toml},
+        Figment,
+    };
+    use rust_decimal_macros::dec;
+
+    fn book(ts: DateTime<Utc>, bid: Decimal, ask: Decimal) -> OrderbookSnapshot {
+        let mut snap = OrderbookSnapshot::new();
+        snap.set_level(Side::Bid, bid, dec!(1));
+        snap.set_level(Side::Ask, ask, dec!(1));
+        snap.timestamp = ts;
+        snap
+    }
+
+    #[test]
+    fn test_sampling_policies() {
+        let config: SamplingConfig = Figment::from(Toml::string(
+            r#"
+            default = { mode = "best_change" }
+            symbols = { ETHUSDT = { mode = "bps", bps = 5.0 }, bnbusdt = { mode = "interval", ms = 100 } }
+            "#,
+        ))
+        .extract()
+        .unwrap();
+        let mut sampler = Sampler::with_config(config);
+        let ts = Utc::now();
+
+        // best_change: đổi qty không tính
+        assert!(sampler.should_record_book("btcusdt", &book(ts, dec!(100), dec!(101))));
+        assert!(!sampler.should_record_book("btcusdt", &book(ts, dec!(100), dec!(101))));
+        assert!(sampler.should_record_book("btcusdt", &book(ts, dec!(100), dec!(100.5))));
+
+        // bps: 100 -> 100.04 là 4 bps, chưa đủ; 100.05 so với mẫu trước là 5 bps
+        assert!(sampler.should_record_book("ethusdt", &book(ts, dec!(100), dec!(101))));
+        assert!(!sampler.should_record_book("ethusdt", &book(ts, dec!(100.04), dec!(101))));
+        assert!(sampler.should_record_book("ethusdt", &book(ts, dec!(100.05), dec!(101))));
+
+        assert!(sampler.should_record_book("BNBUSDT", &book(ts, dec!(1), dec!(2))));
+        assert!(!sampler.should_record_book("BNBUSDT", &book(ts + Duration::milliseconds(50), dec!(3), dec!(4))));
+        assert!(sampler.should_record_book("BNBUSDT", &book(ts + Duration::milliseconds(100), dec!(3), dec!(4))));
+    }
+
+    #[test]
+    fn test_validate_and_legacy_interval() {
+        let config = SamplingConfig {
+            default: SamplePolicy::Bps { bps: 0.0 },
+            symbols: HashMap::from([("x".to_string(), SamplePolicy::Interval { ms: 0 })]),
+        };
+        assert_eq!(config.validate().len(), 2);
+        let legacy = SamplingConfig::default().with_interval(Some(Duration::seconds(1)));
+        assert_eq!(legacy.default, SamplePolicy::Interval { ms: 1000 });
+        assert!(Sampler::new(None).is_passthrough());
+    }
+}
@@ -0,0 +1,123 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::VecDeque;
+
+use crate::core::perp::PerpStats;
+use super::Signal;
+
+// Funding dự kiến kỳ tới, bps
+#[derive(Debug, Clone, Default)]
+pub struct FundingSignal {
+    rate: f64,
+}
+
+impl Signal for FundingSignal {
+    fn on_perp(&mut self, stats: &PerpStats) {
+        self.rate = stats.predicted_funding_rate;
+    }
+
+    fn value(&self) -> f64 {
+        self.rate * 10_000.0
+    }
+
+    fn reset(&mut self) {
+        self.rate = 0.0;
+    }
+}
+
+// Basis mark - index, bps
+#[derive(Debug, Clone, Default)]
+pub struct BasisSignal {
+    basis_bps: f64,
+}
+
+impl Signal for BasisSignal {
+    fn on_perp(&mut self, stats: &PerpStats) {
+        if let Some(b) = stats.basis_bps() {
+            self.basis_bps = b;
+        }
+    }
+
+    fn value(&self) -> f64 {
+        self.basis_bps
+    }
+
+    fn reset(&mut self) {
+        self.basis_bps = 0.0;
+    }
+}
+
+// Thay đổi tương đối của open interest trong `horizon` gần nhất (0.01 = +1%)
+#[derive(Debug, Clone)]
+pub struct OpenInterestSignal {
+    horizon: Duration,
+    samples: VecDeque<(DateTime<Utc>, f64)>,
+}
+
+impl OpenInterestSignal {
+    pub fn new(horizon: Duration) -> Self {
+        Self { horizon, samples: VecDeque::new() }
+    }
+}
+
+impl Signal for OpenInterestSignal {
+    fn on_perp(&mut self, stats: &PerpStats) {
+        let Some(oi) = stats.open_interest else { return };
+        // stream markPrice đẩy mỗi giây nhưng OI chỉ đổi khi poll
+        if self.samples.back().is_some_and(|(_, last)| *last == oi) {
+            return;
+        }
+        self.samples.push_back((stats.timestamp, oi));
+        // giữ lại một mẫu cũ hơn horizon làm mốc
+        while self.samples.len() > 2 && self.samples[1].0 <= stats.timestamp - self.horizon {
+            self.samples.pop_front();
+        }
+    }
+
+    fn value(&self) -> f64 {
+        match (self.samples.front(), self.samples.back()) {
+            (Some((_, first)), Some((_, last))) if *first > 0.0 => last / first - 1.0,
+            _ => 0.0,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.samples.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_carry_signals() {
+        let t0 = Utc::now();
+        let mut stats = PerpStats::new("BTCUSDT");
+        stats.mark_price = 100.5;
+        stats.index_price = 100.0;
+        stats.predicted_funding_rate = 0.0003;
+        stats.open_interest = Some(1000.0);
+        stats.timestamp = t0;
+
+        let mut funding = FundingSignal::default();
+        let mut basis = BasisSignal::default();
+        let mut oi = OpenInterestSignal::new(Duration::minutes(5));
+        for s in [&mut funding as &mut dyn Signal, &mut basis, &mut oi] {
+            s.on_perp(&stats);
+        }
+        assert!((funding.value() - 3.0).abs() < 1e-9);
+        assert!((basis.value() - 50.0).abs() < 1e-9);
+        assert_eq!(oi.value(), 0.0);
+
+        stats.timestamp = t0 + Duration::minutes(3);
+        stats.open_interest = Some(1100.0);
+        oi.on_perp(&stats);
+        assert!((oi.value() - 0.1).abs() < 1e-9);
+
+        // mẫu t0 ra khỏi horizon, mốc mới là 1100
+        stats.timestamp = t0 + Duration::minutes(9);
+        stats.open_interest = Some(1210.0);
+        oi.on_perp(&stats);
+        assert!((oi.value() - 0.1).abs() < 1e-9);
+    }
+}
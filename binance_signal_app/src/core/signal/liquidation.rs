@@ -0,0 +1,100 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::VecDeque;
+
+use crate::core::{orderbook::OrderbookSnapshot, perp::Liquidation, trade::Trade};
+use super::Signal;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LiqMeasure {
+    // notional long bị thanh lý (lệnh bán bắt buộc)
+    Long,
+    // notional short bị thanh lý (lệnh mua bắt buộc)
+    Short,
+    // short - long: dương = short bị ép, áp lực mua
+    Net,
+}
+
+// Notional thanh lý trong `horizon` gần nhất. Cửa sổ trượt theo timestamp của
+// mọi market data của symbol nên giá trị về 0 khi không còn thanh lý mới
+#[derive(Debug, Clone)]
+pub struct LiquidationSignal {
+    horizon: Duration,
+    measure: LiqMeasure,
+    // (timestamp, notional, is_long)
+    events: VecDeque<(DateTime<Utc>, f64, bool)>,
+    now: Option<DateTime<Utc>>,
+}
+
+impl LiquidationSignal {
+    pub fn new(horizon: Duration, measure: LiqMeasure) -> Self {
+        Self { horizon, measure, events: VecDeque::new(), now: None }
+    }
+
+    fn advance(&mut self, ts: DateTime<Utc>) {
+        if self.now.is_some_and(|now| ts <= now) {
+            return;
+        }
+        self.now = Some(ts);
+        let cutoff = ts - self.horizon;
+        while self.events.front().is_some_and(|(t, _, _)| *t < cutoff) {
+            self.events.pop_front();
+        }
+    }
+}
+
+impl Signal for LiquidationSignal {
+    fn on_orderbook(&mut self, snap: &OrderbookSnapshot) {
+        self.advance(snap.timestamp);
+    }
+
+    fn on_trade(&mut self, trade: &Trade) {
+        self.advance(trade.timestamp);
+    }
+
+    fn on_liquidation(&mut self, liq: &Liquidation) {
+        self.events.push_back((liq.timestamp, liq.notional(), liq.is_long()));
+        self.advance(liq.timestamp);
+    }
+
+    fn value(&self) -> f64 {
+        self.events
+            .iter()
+            .map(|(_, notional, is_long)| match (self.measure, is_long) {
+                (LiqMeasure::Long, true) | (LiqMeasure::Short, false) => *notional,
+                (LiqMeasure::Net, true) => -notional,
+                (LiqMeasure::Net, false) => *notional,
+                _ => 0.0,
+            })
+            .sum()
+    }
+
+    fn reset(&mut self) {
+        self.events.clear();
+        self.now = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::trade::TradeSide;
+
+    #[test]
+    fn test_liquidation_window() {
+        let t0 = Utc::now();
+        let liq = |side, qty, secs| Liquidation { symbol: "BTCUSDT".into(), side, price: 100.0, qty, timestamp: t0 + Duration::seconds(secs) };
+        let mut signals: Vec<LiquidationSignal> =
+            [LiqMeasure::Long, LiqMeasure::Short, LiqMeasure::Net].map(|m| LiquidationSignal::new(Duration::minutes(1), m)).into();
+        for s in &mut signals {
+            s.on_liquidation(&liq(TradeSide::Sell, 3.0, 0));
+            s.on_liquidation(&liq(TradeSide::Buy, 1.0, 30));
+        }
+        assert_eq!(signals.iter().map(|s| s.value()).collect::<Vec<_>>(), vec![300.0, 100.0, -200.0]);
+
+        // book 70s sau đẩy thanh lý đầu ra khỏi cửa sổ
+        let mut ob = OrderbookSnapshot::new();
+        ob.timestamp = t0 + Duration::seconds(70);
+        signals[2].on_orderbook(&ob);
+        assert_eq!(signals[2].value(), 100.0);
+    }
+}
@@ -0,0 +1,36 @@
+use chrono::Duration;
+
+use crate::core::{history::SnapshotHistory, orderbook::to_f64};
+use super::Signal;
+
+// Mid hiện tại so với mid `lag` trước (bps), đọc từ history chung của engine
+#[derive(Debug, Clone)]
+pub struct MidChangeSignal {
+    lag: Duration,
+    value: f64,
+}
+
+impl MidChangeSignal {
+    pub fn new(lag: Duration) -> Self {
+        Self { lag, value: 0.0 }
+    }
+}
+
+impl Signal for MidChangeSignal {
+    fn on_history(&mut self, history: &SnapshotHistory) {
+        let mids = history.latest().and_then(|s| s.mid_price()).zip(history.lagged(self.lag).and_then(|s| s.mid_price()));
+        // history chưa đủ dài thì 0
+        self.value = match mids {
+            Some((now, then)) if !then.is_zero() => to_f64((now - then) / then) * 10_000.0,
+            _ => 0.0,
+        };
+    }
+
+    fn value(&self) -> f64 {
+        self.value
+    }
+
+    fn reset(&mut self) {
+        self.value = 0.0;
+    }
+}
@@ -0,0 +1,472 @@
+pub mod carry;
+pub mod liquidation;
+pub mod mid_change;
+pub mod ofi;
+pub mod trade_flow;
+pub mod volatility;
+pub mod vpin;
+
+use chrono::{DateTime, Duration, Utc};
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::{broadcast, mpsc};
+
+use crate::core::{
+    history::{SnapshotHistory, DEFAULT_HISTORY_CAPACITY},
+    orderbook::OrderbookSnapshot,
+    perp::{Liquidation, PerpStats},
+    pipeline::{self, Stage},
+    sampling::Sampler,
+    trade::Trade,
+};
+use crate::ws::OrderbookFeed;
+use carry::{BasisSignal, FundingSignal, OpenInterestSignal};
+use liquidation::{LiqMeasure, LiquidationSignal};
+use mid_change::MidChangeSignal;
+use ofi::OfiSignal;
+use volatility::{VolSource, VolatilitySignal};
+use vpin::VpinSignal;
+
+const OUTPUT_CHANNEL_CAPACITY: usize = 1024;
+
+// Một signal nhận market data của đúng một symbol và trả về một giá trị số
+pub trait Signal: Send {
+    fn on_orderbook(&mut self, _snap: &OrderbookSnapshot) {}
+
+    // gọi sau `on_orderbook`, history đã có snapshot hiện tại ở cuối
+    fn on_history(&mut self, _history: &SnapshotHistory) {}
+
+    fn on_trade(&mut self, _trade: &Trade) {}
+
+    // mark/index, funding, open interest của perp
+    fn on_perp(&mut self, _stats: &PerpStats) {}
+
+    fn on_liquidation(&mut self, _liq: &Liquidation) {}
+
+    fn value(&self) -> f64;
+
+    // gọi khi feed resync, book mới không liên tục với book cũ
+    fn reset(&mut self) {}
+}
+
+#[derive(Debug, Clone)]
+pub enum MarketData {
+    Orderbook {
+        symbol: String,
+        snap: Arc<OrderbookSnapshot>,
+    },
+    Trade(Trade),
+    Perp(PerpStats),
+    Liquidation(Liquidation),
+}
+
+impl MarketData {
+    pub fn symbol(&self) -> &str {
+        match self {
+            MarketData::Orderbook { symbol, .. } => symbol,
+            MarketData::Trade(t) => &t.symbol,
+            MarketData::Perp(p) => &p.symbol,
+            MarketData::Liquidation(l) => &l.symbol,
+        }
+    }
+
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        match self {
+            MarketData::Orderbook { snap, .. } => snap.timestamp,
+            MarketData::Trade(t) => t.timestamp,
+            MarketData::Perp(p) => p.timestamp,
+            MarketData::Liquidation(l) => l.timestamp,
+        }
+    }
+}
+
+// Giá trị của tất cả signal của một symbol sau mỗi lần cập nhật
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignalOutput {
+    pub symbol: String,
+    pub timestamp: DateTime<Utc>,
+    pub values: Vec<(String, f64)>,
+}
+
+impl SignalOutput {
+    pub fn get(&self, name: &str) -> Option<f64> {
+        self.values.iter().find(|(n, _)| n == name).map(|(_, v)| *v)
+    }
+}
+
+// (tên đăng ký, signal) của một symbol
+type SignalSlots = Vec<(String, Box<dyn Signal>)>;
+
+// Registry signal theo symbol: fan-out market data tới các signal đã đăng ký
+// và publish output gộp qua broadcast
+pub struct SignalEngine {
+    signals: HashMap<String, SignalSlots>,
+    // snapshot gần nhất theo symbol, chỉ với symbol có signal
+    histories: HashMap<String, SnapshotHistory>,
+    history_capacity: usize,
+    output_tx: broadcast::Sender<SignalOutput>,
+}
+
+impl Default for SignalEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SignalEngine {
+    pub fn new() -> Self {
+        let (output_tx, _) = broadcast::channel(OUTPUT_CHANNEL_CAPACITY);
+        Self {
+            signals: HashMap::new(),
+            histories: HashMap::new(),
+            history_capacity: DEFAULT_HISTORY_CAPACITY,
+            output_tx,
+        }
+    }
+
+    // 0 = không giữ history, signal dùng `on_history` luôn thấy history rỗng
+    pub fn set_history_capacity(&mut self, capacity: usize) {
+        self.history_capacity = capacity;
+        self.histories.clear();
+    }
+
+    pub fn history(&self, symbol: &str) -> Option<&SnapshotHistory> {
+        self.histories.get(&symbol.to_uppercase())
+    }
+
+    // đăng ký lại cùng tên sẽ thay signal cũ
+    pub fn register(&mut self, symbol: &str, name: &str, signal: Box<dyn Signal>) {
+        let signals = self.signals.entry(symbol.to_uppercase()).or_default();
+        signals.retain(|(n, _)| n != name);
+        signals.push((name.to_string(), signal));
+    }
+
+    // ofi_100ms, ofi_1s, ofi_5s, ...
+    pub fn register_ofi(&mut self, symbol: &str, horizons: &[Duration]) {
+        for h in horizons {
+            self.register(symbol, &ofi_name(*h), Box::new(OfiSignal::new(*h)));
+        }
+    }
+
+    // vol_1m, vol_5m, vol_30m, ...
+    pub fn register_volatility(&mut self, symbol: &str, horizons: &[Duration], source: VolSource) {
+        for h in horizons {
+            self.register(symbol, &vol_name(*h), Box::new(VolatilitySignal::new(*h, source)));
+        }
+    }
+
+    // mid_chg_1s, mid_chg_5s, ... (bps, từ history)
+    pub fn register_mid_change(&mut self, symbol: &str, lags: &[Duration]) {
+        for lag in lags {
+            self.register(symbol, &format!("mid_chg_{}", horizon_suffix(*lag)), Box::new(MidChangeSignal::new(*lag)));
+        }
+    }
+
+    // funding_bps, basis_bps, oi_chg_5m, ... (chỉ có data với feed perp)
+    pub fn register_carry(&mut self, symbol: &str, oi_horizons: &[Duration]) {
+        self.register(symbol, "funding_bps", Box::new(FundingSignal::default()));
+        self.register(symbol, "basis_bps", Box::new(BasisSignal::default()));
+        for h in oi_horizons {
+            self.register(symbol, &format!("oi_chg_{}", horizon_suffix(*h)), Box::new(OpenInterestSignal::new(*h)));
+        }
+    }
+
+    // liq_long_1m, liq_short_1m, liq_net_1m, ... (notional quote)
+    pub fn register_liquidations(&mut self, symbol: &str, horizons: &[Duration]) {
+        for h in horizons {
+            let suffix = horizon_suffix(*h);
+            for (prefix, measure) in [("liq_long", LiqMeasure::Long), ("liq_short", LiqMeasure::Short), ("liq_net", LiqMeasure::Net)] {
+                self.register(symbol, &format!("{}_{}", prefix, suffix), Box::new(LiquidationSignal::new(*h, measure)));
+            }
+        }
+    }
+
+    // vpin: toxicity của trade flow, `bucket_volume` theo base asset
+    pub fn register_vpin(&mut self, symbol: &str, bucket_volume: f64, buckets: usize) {
+        self.register(symbol, "vpin", Box::new(VpinSignal::new(bucket_volume, buckets)));
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<SignalOutput> {
+        self.output_tx.subscribe()
+    }
+
+    // để subscribe sau khi engine đã move vào task `run`
+    pub fn output_sender(&self) -> broadcast::Sender<SignalOutput> {
+        self.output_tx.clone()
+    }
+
+    pub fn on_orderbook(&mut self, symbol: &str, snap: &Arc<OrderbookSnapshot>) -> Option<SignalOutput> {
+        let symbol = symbol.to_uppercase();
+        let signals = self.signals.get_mut(&symbol)?;
+        let capacity = self.history_capacity;
+        let history = self.histories.entry(symbol.clone()).or_insert_with(|| SnapshotHistory::new(capacity));
+        history.push(snap.clone());
+        for (_, s) in signals.iter_mut() {
+            s.on_orderbook(snap);
+            s.on_history(history);
+        }
+        let values = signals.iter().map(|(n, s)| (n.clone(), s.value())).collect();
+        pipeline::record(Stage::Signal, snap.received);
+        let output = SignalOutput { symbol, timestamp: snap.timestamp, values };
+        let _ = self.output_tx.send(output.clone());
+        Some(output)
+    }
+
+    pub fn on_trade(&mut self, trade: &Trade) -> Option<SignalOutput> {
+        let output = self.dispatch(&trade.symbol, trade.timestamp, |s| s.on_trade(trade))?;
+        let _ = self.output_tx.send(output.clone());
+        Some(output)
+    }
+
+    pub fn on_perp(&mut self, stats: &PerpStats) -> Option<SignalOutput> {
+        let output = self.dispatch(&stats.symbol, stats.timestamp, |s| s.on_perp(stats))?;
+        let _ = self.output_tx.send(output.clone());
+        Some(output)
+    }
+
+    pub fn on_liquidation(&mut self, liq: &Liquidation) -> Option<SignalOutput> {
+        let output = self.dispatch(&liq.symbol, liq.timestamp, |s| s.on_liquidation(liq))?;
+        let _ = self.output_tx.send(output.clone());
+        Some(output)
+    }
+
+    // dispatch theo loại data
+    pub fn on_market_data(&mut self, data: &MarketData) -> Option<SignalOutput> {
+        match data {
+            MarketData::Orderbook { symbol, snap } => self.on_orderbook(symbol, snap),
+            MarketData::Trade(trade) => self.on_trade(trade),
+            MarketData::Perp(stats) => self.on_perp(stats),
+            MarketData::Liquidation(liq) => self.on_liquidation(liq),
+        }
+    }
+
+    pub fn value(&self, symbol: &str, name: &str) -> Option<f64> {
+        self.signals
+            .get(&symbol.to_uppercase())?
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, s)| s.value())
+    }
+
+    pub fn values(&self, symbol: &str) -> Option<Vec<(String, f64)>> {
+        self.signals
+            .get(&symbol.to_uppercase())
+            .map(|signals| signals.iter().map(|(n, s)| (n.clone(), s.value())).collect())
+    }
+
+    pub fn reset(&mut self, symbol: &str) {
+        if let Some(history) = self.histories.get_mut(&symbol.to_uppercase()) {
+            history.clear();
+        }
+        if let Some(signals) = self.signals.get_mut(&symbol.to_uppercase()) {
+            for (_, s) in signals {
+                s.reset();
+            }
+        }
+    }
+
+    // Chạy engine cho tới khi mọi sender đóng
+    pub async fn run(mut self, mut rx: mpsc::Receiver<MarketData>) {
+        while let Some(data) = rx.recv().await {
+            self.on_market_data(&data);
+        }
+    }
+
+    fn dispatch<F>(&mut self, symbol: &str, timestamp: DateTime<Utc>, mut f: F) -> Option<SignalOutput>
+    where
+        F: FnMut(&mut dyn Signal),
+    {
+        let symbol = symbol.to_uppercase();
+        let signals = self.signals.get_mut(&symbol)?;
+        for (_, s) in signals.iter_mut() {
+            f(s.as_mut());
+        }
+        let values = signals.iter().map(|(n, s)| (n.clone(), s.value())).collect();
+        Some(SignalOutput { symbol, timestamp, values })
+    }
+}
+
+pub fn ofi_name(horizon: Duration) -> String {
+    let ms = horizon.num_milliseconds();
+    if ms % 1000 == 0 {
+        format!("ofi_{}s", ms / 1000)
+    } else {
+        format!("ofi_{}ms", ms)
+    }
+}
+
+pub fn vol_name(horizon: Duration) -> String {
+    format!("vol_{}", horizon_suffix(horizon))
+}
+
+// 1m, 90s, 500ms
+fn horizon_suffix(horizon: Duration) -> String {
+    let ms = horizon.num_milliseconds();
+    if ms % 60_000 == 0 {
+        format!("{}m", ms / 60_000)
+    } else if ms % 1000 == 0 {
+        format!("{}s", ms / 1000)
+    } else {
+        format!("{}ms", ms)
+    }
+}
+
+// Chuyển mọi update của feed vào channel của engine
+pub fn forward_orderbook(feed: Arc<dyn OrderbookFeed>, tx: mpsc::Sender<MarketData>) -> tokio::task::JoinHandle<()> {
+    forward_sampled(feed, tx, Sampler::default())
+}
+
+// Như `forward_orderbook` nhưng bỏ snapshot không qua được `sampler`
+pub fn forward_sampled(
+    feed: Arc<dyn OrderbookFeed>,
+    tx: mpsc::Sender<MarketData>,
+    mut sampler: Sampler,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut updates = feed.subscribe();
+        let symbol = feed.symbol().to_string();
+        loop {
+            match updates.recv().await {
+                Ok(snap) if !sampler.should_record_book(&symbol, &snap) => continue,
+                Ok(snap) => {
+                    let data = MarketData::Orderbook { symbol: symbol.clone(), snap };
+                    if tx.send(data).await.is_err() {
+                        break;
+                    }
+                }
+                // consumer chậm: bỏ qua phần bị mất, book kế tiếp vẫn đầy đủ
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    })
+}
+
+// Stats perp (funding, OI) của feed futures, feed khác thì không làm gì
+pub fn forward_perp(feed: Arc<dyn OrderbookFeed>, tx: mpsc::Sender<MarketData>) -> Option<tokio::task::JoinHandle<()>> {
+    Some(forward_broadcast(feed.subscribe_perp()?, tx, MarketData::Perp))
+}
+
+// Lệnh thanh lý của feed futures
+pub fn forward_liquidations(feed: Arc<dyn OrderbookFeed>, tx: mpsc::Sender<MarketData>) -> Option<tokio::task::JoinHandle<()>> {
+    Some(forward_broadcast(feed.subscribe_liquidations()?, tx, MarketData::Liquidation))
+}
+
+fn forward_broadcast<T, F>(mut updates: broadcast::Receiver<T>, tx: mpsc::Sender<MarketData>, wrap: F) -> tokio::task::JoinHandle<()>
+where
+    T: Clone + Send + 'static,
+    F: Fn(T) -> MarketData + Send + 'static,
+{
+    tokio::spawn(async move {
+        loop {
+            match updates.recv().await {
+                Ok(item) => {
+                    if tx.send(wrap(item)).await.is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        orderbook::{to_f64, Side},
+        trade::TradeSide,
+    };
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+    use trade_flow::TradeImbalanceSignal;
+
+    #[test]
+    fn test_engine_fans_out_and_publishes() {
+        let mut engine = SignalEngine::new();
+        engine.register_ofi("btcusdt", &[Duration::milliseconds(100), Duration::seconds(5)]);
+        engine.register("BTCUSDT", "trade_imb_1m", Box::new(TradeImbalanceSignal::new(Duration::minutes(1))));
+        let mut rx = engine.subscribe();
+
+        let mut ob = OrderbookSnapshot::new();
+        ob.set_level(Side::Bid, dec!(10), dec!(1));
+        ob.set_level(Side::Ask, dec!(11), dec!(1));
+        engine.on_orderbook("btcusdt", &Arc::new(ob.clone()));
+
+        ob.set_level(Side::Bid, dec!(10), dec!(4));
+        let ob = Arc::new(ob);
+        let out = engine.on_orderbook("BTCUSDT", &ob).unwrap();
+        assert_eq!(out.get("ofi_5s"), Some(3.0));
+        assert_eq!(out.values.len(), 3);
+
+        let trade = Trade {
+            symbol: "btcusdt".into(),
+            trade_id: 1,
+            price: 10.5,
+            qty: 2.0,
+            side: TradeSide::Buy,
+            timestamp: Utc::now(),
+        };
+        engine.on_trade(&trade);
+        assert_eq!(engine.value("btcusdt", "trade_imb_1m"), Some(1.0));
+
+        // symbol không có signal -> không publish
+        assert!(engine.on_orderbook("ETHUSDT", &ob).is_none());
+
+        assert_eq!(rx.try_recv().unwrap().symbol, "BTCUSDT");
+        assert_eq!(rx.try_recv().unwrap().get("ofi_5s"), Some(3.0));
+        assert_eq!(rx.try_recv().unwrap().get("trade_imb_1m"), Some(1.0));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_history_shared_by_lagged_signals() {
+        let mut engine = SignalEngine::new();
+        engine.set_history_capacity(3);
+        engine.register_mid_change("btcusdt", &[Duration::seconds(1), Duration::seconds(2)]);
+        let book = |ms: i64, bid: Decimal| {
+            let mut ob = OrderbookSnapshot::new();
+            ob.timestamp = DateTime::from_timestamp_millis(ms).unwrap();
+            ob.set_level(Side::Bid, bid, dec!(1));
+            ob.set_level(Side::Ask, bid + dec!(2), dec!(1));
+            Arc::new(ob)
+        };
+
+        engine.on_orderbook("btcusdt", &book(0, dec!(99)));
+        engine.on_orderbook("btcusdt", &book(1000, dec!(100)));
+        let out = engine.on_orderbook("btcusdt", &book(2000, dec!(101))).unwrap();
+        // mid 100 -> 101 -> 102
+        assert_eq!(out.get("mid_chg_1s"), Some(to_f64(dec!(1) / dec!(101)) * 10_000.0));
+        assert_eq!(out.get("mid_chg_2s"), Some(200.0));
+
+        // capacity 3: book lúc 0 bị đẩy ra, không còn đủ 2s
+        let out = engine.on_orderbook("btcusdt", &book(3000, dec!(101))).unwrap();
+        assert_eq!((out.get("mid_chg_1s"), out.get("mid_chg_2s")), (Some(0.0), Some(to_f64(dec!(1) / dec!(101)) * 10_000.0)));
+        engine.on_orderbook("btcusdt", &book(3500, dec!(101)));
+        assert_eq!(engine.history("BTCUSDT").unwrap().len(), 3);
+
+        // symbol không có signal thì không giữ history
+        engine.on_orderbook("ethusdt", &book(0, dec!(1)));
+        assert!(engine.history("ethusdt").is_none());
+        engine.reset("btcusdt");
+        assert!(engine.history("btcusdt").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_register_replaces_and_names() {
+        let mut engine = SignalEngine::new();
+        engine.register_ofi("btcusdt", &[Duration::seconds(1)]);
+        engine.register_ofi("btcusdt", &[Duration::seconds(1)]);
+        assert_eq!(engine.values("btcusdt").unwrap().len(), 1);
+        assert_eq!(ofi_name(Duration::milliseconds(100)), "ofi_100ms");
+        assert_eq!(ofi_name(Duration::seconds(5)), "ofi_5s");
+
+        engine.register_volatility("btcusdt", &[Duration::minutes(1), Duration::minutes(30)], VolSource::Mid);
+        assert_eq!(engine.value("btcusdt", "vol_30m"), Some(0.0));
+        assert_eq!(vol_name(Duration::seconds(90)), "vol_90s");
+
+        engine.register_carry("btcusdt", &[Duration::minutes(5)]);
+        assert_eq!(engine.value("btcusdt", "oi_chg_5m"), Some(0.0));
+    }
+}
@@ -0,0 +1,173 @@
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use std::collections::VecDeque;
+
+use crate::core::orderbook::{to_f64, Level, OrderbookSnapshot};
+use super::Signal;
+
+// Order Flow Imbalance (Cont, Kukanov, Stoikov 2014) tính từ thay đổi ở best bid/ask
+// giữa hai snapshot liên tiếp:
+//   e = 1{Pb >= Pb'}·qb - 1{Pb <= Pb'}·qb' - 1{Pa <= Pa'}·qa + 1{Pa >= Pa'}·qa'
+// (' là snapshot trước). OFI trên một horizon là tổng e trong khoảng thời gian đó.
+pub fn ofi_event(prev: (Level, Level), curr: (Level, Level)) -> Decimal {
+    let ((prev_bid, prev_bid_qty), (prev_ask, prev_ask_qty)) = prev;
+    let ((bid, bid_qty), (ask, ask_qty)) = curr;
+
+    let mut e = Decimal::ZERO;
+    if bid >= prev_bid {
+        e += bid_qty;
+    }
+    if bid <= prev_bid {
+        e -= prev_bid_qty;
+    }
+    if ask <= prev_ask {
+        e -= ask_qty;
+    }
+    if ask >= prev_ask {
+        e += prev_ask_qty;
+    }
+    e
+}
+
+#[derive(Debug, Clone)]
+pub struct OfiCalculator {
+    horizons: Vec<Duration>,
+    prev: Option<(Level, Level)>,
+    events: VecDeque<(DateTime<Utc>, Decimal)>,
+    last_ts: Option<DateTime<Utc>>,
+}
+
+impl OfiCalculator {
+    pub fn new(horizons: &[Duration]) -> Self {
+        Self {
+            horizons: horizons.to_vec(),
+            prev: None,
+            events: VecDeque::new(),
+            last_ts: None,
+        }
+    }
+
+    pub fn horizons(&self) -> &[Duration] {
+        &self.horizons
+    }
+
+    // Trả về e của update này; None nếu chưa có snapshot trước hoặc book rỗng một phía
+    pub fn on_orderbook(&mut self, snap: &OrderbookSnapshot) -> Option<Decimal> {
+        let curr = snap.best_bid_ask()?;
+        let prev = self.prev.replace(curr);
+        self.last_ts = Some(snap.timestamp);
+
+        // thời gian lấy từ snapshot để replay cho cùng kết quả
+        let max_horizon = self.horizons.iter().max().copied().unwrap_or(Duration::zero());
+        let cutoff = snap.timestamp - max_horizon;
+        while self.events.front().is_some_and(|(ts, _)| *ts <= cutoff) {
+            self.events.pop_front();
+        }
+
+        let e = ofi_event(prev?, curr);
+        self.events.push_back((snap.timestamp, e));
+        Some(e)
+    }
+
+    // Tổng OFI trong `horizon` tính tới snapshot gần nhất
+    pub fn value(&self, horizon: Duration) -> f64 {
+        let Some(last) = self.last_ts else {
+            return 0.0;
+        };
+        let cutoff = last - horizon;
+        let sum: Decimal = self
+            .events
+            .iter()
+            .rev()
+            .take_while(|(ts, _)| *ts > cutoff)
+            .map(|(_, e)| *e)
+            .sum();
+        to_f64(sum)
+    }
+
+    // OFI cho tất cả horizon đã cấu hình, cùng thứ tự
+    pub fn values(&self) -> Vec<(Duration, f64)> {
+        self.horizons.iter().map(|h| (*h, self.value(*h))).collect()
+    }
+
+    pub fn reset(&mut self) {
+        self.prev = None;
+        self.events.clear();
+        self.last_ts = None;
+    }
+}
+
+// OFI trên một horizon, dùng được như một Signal trong SignalEngine
+#[derive(Debug, Clone)]
+pub struct OfiSignal {
+    horizon: Duration,
+    calc: OfiCalculator,
+}
+
+impl OfiSignal {
+    pub fn new(horizon: Duration) -> Self {
+        Self {
+            horizon,
+            calc: OfiCalculator::new(&[horizon]),
+        }
+    }
+}
+
+impl Signal for OfiSignal {
+    fn on_orderbook(&mut self, snap: &OrderbookSnapshot) {
+        self.calc.on_orderbook(snap);
+    }
+
+    fn value(&self) -> f64 {
+        self.calc.value(self.horizon)
+    }
+
+    fn reset(&mut self) {
+        self.calc.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::orderbook::Side;
+    use rust_decimal_macros::dec;
+
+    fn snap(ms: i64, bid: (Decimal, Decimal), ask: (Decimal, Decimal)) -> OrderbookSnapshot {
+        let mut ob = OrderbookSnapshot::new();
+        ob.timestamp = DateTime::from_timestamp_millis(ms).unwrap();
+        ob.set_level(Side::Bid, bid.0, bid.1);
+        ob.set_level(Side::Ask, ask.0, ask.1);
+        ob
+    }
+
+    #[test]
+    fn test_ofi_event_cases() {
+        let prev = ((dec!(100), dec!(5)), (dec!(101), dec!(5)));
+        // cùng giá: chỉ thay đổi size
+        assert_eq!(ofi_event(prev, ((dec!(100), dec!(7)), (dec!(101), dec!(4)))), dec!(3));
+        // bid tăng giá: +qb mới, ask không đổi
+        assert_eq!(ofi_event(prev, ((dec!(100.5), dec!(2)), (dec!(101), dec!(5)))), dec!(2));
+        // ask giảm giá: -qa mới
+        assert_eq!(ofi_event(prev, ((dec!(100), dec!(5)), (dec!(100.5), dec!(3)))), dec!(-3));
+        // bid giảm giá: -qb cũ
+        assert_eq!(ofi_event(prev, ((dec!(99.5), dec!(9)), (dec!(101), dec!(5)))), dec!(-5));
+    }
+
+    #[test]
+    fn test_rolling_horizons() {
+        let mut ofi = OfiCalculator::new(&[Duration::milliseconds(100), Duration::seconds(1)]);
+        assert_eq!(ofi.on_orderbook(&snap(0, (dec!(100), dec!(5)), (dec!(101), dec!(5)))), None);
+        assert_eq!(ofi.on_orderbook(&snap(50, (dec!(100), dec!(7)), (dec!(101), dec!(5)))), Some(dec!(2)));
+        assert_eq!(ofi.on_orderbook(&snap(500, (dec!(100), dec!(10)), (dec!(101), dec!(5)))), Some(dec!(3)));
+        assert_eq!(ofi.on_orderbook(&snap(550, (dec!(100), dec!(12)), (dec!(101), dec!(5)))), Some(dec!(2)));
+
+        // 100ms: chỉ update lúc 550 và 500 (cutoff 450)
+        assert_eq!(ofi.value(Duration::milliseconds(100)), 5.0);
+        assert_eq!(ofi.value(Duration::seconds(1)), 7.0);
+
+        // sau 1s event lúc 50 bị loại, snapshot giống hệt nên e = 0
+        ofi.on_orderbook(&snap(1100, (dec!(100), dec!(12)), (dec!(101), dec!(5))));
+        assert_eq!(ofi.values(), vec![(Duration::milliseconds(100), 0.0), (Duration::seconds(1), 5.0)]);
+    }
+}
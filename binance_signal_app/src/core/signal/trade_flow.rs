@@ -0,0 +1,39 @@
+use chrono::Duration;
+
+use crate::core::trade::{Trade, TradeWindow};
+use super::Signal;
+
+// (buy - sell) / (buy + sell) của volume chủ động trong `horizon` gần nhất
+#[derive(Debug, Clone)]
+pub struct TradeImbalanceSignal {
+    horizon: Duration,
+    window: TradeWindow,
+}
+
+impl TradeImbalanceSignal {
+    pub fn new(horizon: Duration) -> Self {
+        Self {
+            horizon,
+            window: TradeWindow::new(horizon, 10_000),
+        }
+    }
+}
+
+impl Signal for TradeImbalanceSignal {
+    fn on_trade(&mut self, trade: &Trade) {
+        self.window.push(trade.clone());
+    }
+
+    fn value(&self) -> f64 {
+        let Some(last) = self.window.last() else {
+            return 0.0;
+        };
+        self.window
+            .imbalance_since(last.timestamp - self.horizon)
+            .unwrap_or(0.0)
+    }
+
+    fn reset(&mut self) {
+        self.window = TradeWindow::new(self.horizon, self.window.max_len);
+    }
+}
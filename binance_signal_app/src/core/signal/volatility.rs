@@ -0,0 +1,137 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::VecDeque;
+
+use crate::core::{
+    orderbook::{to_f64, OrderbookSnapshot},
+    trade::Trade,
+};
+use super::Signal;
+
+// giới hạn bộ nhớ khi book update dày (vài nghìn mid / giây)
+const MAX_SAMPLES: usize = 100_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VolSource {
+    // mid của best bid/ask, chỉ lấy mẫu khi mid đổi
+    Mid,
+    // giá khớp, nhạy với bid-ask bounce hơn
+    Trade,
+}
+
+// Realized volatility trên `horizon` gần nhất: sqrt(tổng r²) với r là log return
+// giữa hai giá liên tiếp. Giá trị là tỉ lệ trên cả horizon (0.001 = 10 bps), không annualize
+#[derive(Debug, Clone)]
+pub struct VolatilitySignal {
+    horizon: Duration,
+    source: VolSource,
+    last_price: Option<f64>,
+    returns: VecDeque<(DateTime<Utc>, f64)>,
+    sum_sq: f64,
+}
+
+impl VolatilitySignal {
+    pub fn new(horizon: Duration, source: VolSource) -> Self {
+        Self { horizon, source, last_price: None, returns: VecDeque::new(), sum_sq: 0.0 }
+    }
+
+    pub fn horizon(&self) -> Duration {
+        self.horizon
+    }
+
+    pub fn push(&mut self, timestamp: DateTime<Utc>, price: f64) {
+        if price <= 0.0 || !price.is_finite() {
+            return;
+        }
+        if let Some(last) = self.last_price.replace(price)
+            && last != price
+        {
+            let r = (price / last).ln();
+            self.returns.push_back((timestamp, r));
+            self.sum_sq += r * r;
+        }
+        let cutoff = timestamp - self.horizon;
+        while self
+            .returns
+            .front()
+            .is_some_and(|(t, _)| *t < cutoff || self.returns.len() > MAX_SAMPLES)
+        {
+            let (_, r) = self.returns.pop_front().unwrap();
+            self.sum_sq -= r * r;
+        }
+        // cộng trừ float lâu ngày có thể âm một chút
+        if self.returns.is_empty() {
+            self.sum_sq = 0.0;
+        }
+    }
+
+    pub fn realized_variance(&self) -> f64 {
+        self.sum_sq.max(0.0)
+    }
+
+    pub fn realized_vol(&self) -> f64 {
+        self.realized_variance().sqrt()
+    }
+
+    pub fn vol_bps(&self) -> f64 {
+        self.realized_vol() * 10_000.0
+    }
+
+    pub fn samples(&self) -> usize {
+        self.returns.len()
+    }
+}
+
+impl Signal for VolatilitySignal {
+    fn on_orderbook(&mut self, snap: &OrderbookSnapshot) {
+        if self.source == VolSource::Mid
+            && let Some(mid) = snap.mid_price()
+        {
+            self.push(snap.timestamp, to_f64(mid));
+        }
+    }
+
+    fn on_trade(&mut self, trade: &Trade) {
+        if self.source == VolSource::Trade {
+            self.push(trade.timestamp, trade.price);
+        }
+    }
+
+    fn value(&self) -> f64 {
+        self.realized_vol()
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.horizon, self.source);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_realized_vol_over_window() {
+        let t0 = Utc::now();
+        let mut vol = VolatilitySignal::new(Duration::minutes(1), VolSource::Trade);
+        vol.push(t0, 100.0);
+        assert_eq!(vol.value(), 0.0);
+
+        vol.push(t0 + Duration::seconds(10), 101.0);
+        vol.push(t0 + Duration::seconds(20), 101.0);
+        vol.push(t0 + Duration::seconds(30), 100.0);
+        let r1 = (101.0f64 / 100.0).ln();
+        let r2 = (100.0f64 / 101.0).ln();
+        assert_eq!(vol.samples(), 2);
+        assert!((vol.realized_variance() - (r1 * r1 + r2 * r2)).abs() < 1e-15);
+
+        // return đầu tiên ra khỏi cửa sổ 1 phút
+        vol.push(t0 + Duration::seconds(75), 100.0);
+        assert_eq!(vol.samples(), 1);
+        assert!((vol.vol_bps() - r2.abs() * 10_000.0).abs() < 1e-9);
+
+        vol.push(t0 + Duration::seconds(200), 102.0);
+        assert!((vol.realized_vol() - (102.0f64 / 100.0).ln()).abs() < 1e-12);
+        vol.reset();
+        assert_eq!((vol.samples(), vol.value()), (0, 0.0));
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::VecDeque;
+
+use crate::core::trade::{Trade, TradeSide};
+use super::Signal;
+
+// VPIN (Easley, López de Prado, O'Hara): chia trade thành các bucket cùng
+// volume `bucket_volume`, VPIN = Σ|buy - sell| / (n × bucket_volume) trên `buckets`
+// bucket gần nhất. Phân loại buy/sell theo aggressor của trade stream thay vì
+// bulk classification. Gần 1 = flow một chiều (toxic)
+#[derive(Debug, Clone)]
+pub struct VpinSignal {
+    bucket_volume: f64,
+    buckets: usize,
+    // |buy - sell| của các bucket đã đầy
+    imbalances: VecDeque<f64>,
+    buy: f64,
+    sell: f64,
+}
+
+impl VpinSignal {
+    pub fn new(bucket_volume: f64, buckets: usize) -> Self {
+        Self { bucket_volume, buckets: buckets.max(1), imbalances: VecDeque::new(), buy: 0.0, sell: 0.0 }
+    }
+
+    // None tới khi đầy bucket đầu tiên
+    pub fn vpin(&self) -> Option<f64> {
+        if self.imbalances.is_empty() {
+            return None;
+        }
+        Some(self.imbalances.iter().sum::<f64>() / (self.imbalances.len() as f64 * self.bucket_volume))
+    }
+}
+
+impl Signal for VpinSignal {
+    fn on_trade(&mut self, trade: &Trade) {
+        if self.bucket_volume <= 0.0 {
+            return;
+        }
+        // trade lớn có thể lấp nhiều bucket, phần dư sang bucket sau
+        let mut qty = trade.qty;
+        while qty > 0.0 {
+            let take = qty.min(self.bucket_volume - self.buy - self.sell);
+            match trade.side {
+                TradeSide::Buy => self.buy += take,
+                TradeSide::Sell => self.sell += take,
+            }
+            qty -= take;
+            if self.buy + self.sell >= self.bucket_volume * (1.0 - 1e-9) {
+                self.imbalances.push_back((self.buy - self.sell).abs());
+                if self.imbalances.len() > self.buckets {
+                    self.imbalances.pop_front();
+                }
+                self.buy = 0.0;
+                self.sell = 0.0;
+            }
+        }
+    }
+
+    fn value(&self) -> f64 {
+        self.vpin().unwrap_or(0.0)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.bucket_volume, self.buckets);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn trade(side: TradeSide, qty: f64) -> Trade {
+        Trade { symbol: "BTCUSDT".into(), trade_id: 1, price: 100.0, qty, side, timestamp: Utc::now() }
+    }
+
+    #[test]
+    fn test_vpin_buckets() {
+        let mut vpin = VpinSignal::new(10.0, 2);
+        vpin.on_trade(&trade(TradeSide::Buy, 6.0));
+        assert_eq!(vpin.vpin(), None);
+
+        // 4 buy lấp bucket 1 (toàn buy), 6 sang bucket 2
+        vpin.on_trade(&trade(TradeSide::Buy, 10.0));
+        assert_eq!(vpin.vpin(), Some(1.0));
+        // bucket 2: 6 buy + 4 sell -> |2|, trung bình (10 + 2) / 20
+        vpin.on_trade(&trade(TradeSide::Sell, 4.0));
+        assert!((vpin.value() - 0.6).abs() < 1e-9);
+
+        // flow cân bằng đẩy bucket toxic ra khỏi cửa sổ
+        for side in [TradeSide::Buy, TradeSide::Sell, TradeSide::Buy, TradeSide::Sell] {
+            vpin.on_trade(&trade(side, 5.0));
+        }
+        assert_eq!(vpin.value(), 0.0);
+    }
+}
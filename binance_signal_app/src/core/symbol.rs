@@ -0,0 +1,191 @@
+use rust_decimal::Decimal;
+use std::{collections::HashMap, fmt};
+
+use super::order::{OrderSide, OrderType};
+use crate::risk::OrderIntent;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SymbolError {
+    UnknownSymbol(String),
+    NotTrading(String),
+    QtyOutOfRange { qty: Decimal, min: Decimal, max: Decimal },
+    PriceOutOfRange { price: Decimal, min: Decimal, max: Decimal },
+    MinNotional { notional: Decimal, min: Decimal },
+}
+
+impl fmt::Display for SymbolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SymbolError::UnknownSymbol(s) => write!(f, "unknown symbol {}", s),
+            SymbolError::NotTrading(s) => write!(f, "symbol {} is not trading", s),
+            SymbolError::QtyOutOfRange { qty, min, max } => write!(f, "qty {} outside [{}, {}]", qty, min, max),
+            SymbolError::PriceOutOfRange { price, min, max } => write!(f, "price {} outside [{}, {}]", price, min, max),
+            SymbolError::MinNotional { notional, min } => write!(f, "notional {} below minimum {}", notional, min),
+        }
+    }
+}
+
+impl std::error::Error for SymbolError {}
+
+// Bộ lọc giao dịch của một symbol (PRICE_FILTER, LOT_SIZE, MIN_NOTIONAL / NOTIONAL).
+// Giá trị 0 = sàn không giới hạn
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolInfo {
+    pub symbol: String,
+    pub base_asset: String,
+    pub quote_asset: String,
+    pub trading: bool,
+    pub tick_size: Decimal,
+    pub min_price: Decimal,
+    pub max_price: Decimal,
+    pub step_size: Decimal,
+    pub min_qty: Decimal,
+    pub max_qty: Decimal,
+    pub min_notional: Decimal,
+}
+
+fn floor_to(value: Decimal, step: Decimal) -> Decimal {
+    if step.is_zero() {
+        return value;
+    }
+    ((value / step).floor() * step).normalize()
+}
+
+fn ceil_to(value: Decimal, step: Decimal) -> Decimal {
+    if step.is_zero() {
+        return value;
+    }
+    ((value / step).ceil() * step).normalize()
+}
+
+impl SymbolInfo {
+    // làm tròn về phía không cắt qua book: mua xuống, bán lên
+    pub fn round_price(&self, price: Decimal, side: OrderSide) -> Decimal {
+        match side {
+            OrderSide::Buy => floor_to(price, self.tick_size),
+            OrderSide::Sell => ceil_to(price, self.tick_size),
+        }
+    }
+
+    // luôn làm tròn xuống để không vượt số dư / max
+    pub fn round_qty(&self, qty: Decimal) -> Decimal {
+        floor_to(qty, self.step_size)
+    }
+
+    pub fn normalize(&self, order: &OrderIntent) -> OrderIntent {
+        OrderIntent {
+            symbol: self.symbol.clone(),
+            side: order.side,
+            order_type: order.order_type,
+            price: order.price.map(|p| self.round_price(p, order.side)),
+            qty: self.round_qty(order.qty),
+        }
+    }
+
+    // `ref_price` ước lượng notional cho market order, None thì bỏ qua min notional
+    pub fn validate(&self, order: &OrderIntent, ref_price: Option<Decimal>) -> Result<(), SymbolError> {
+        if !self.trading {
+            return Err(SymbolError::NotTrading(self.symbol.clone()));
+        }
+        let max_qty = if self.max_qty.is_zero() { Decimal::MAX } else { self.max_qty };
+        if order.qty <= Decimal::ZERO || order.qty < self.min_qty || order.qty > max_qty {
+            return Err(SymbolError::QtyOutOfRange { qty: order.qty, min: self.min_qty, max: self.max_qty });
+        }
+        if let (OrderType::Limit, Some(price)) = (order.order_type, order.price) {
+            let max_price = if self.max_price.is_zero() { Decimal::MAX } else { self.max_price };
+            if price < self.min_price || price <= Decimal::ZERO || price > max_price {
+                return Err(SymbolError::PriceOutOfRange { price, min: self.min_price, max: self.max_price });
+            }
+        }
+        if let Some(price) = order.price.or(ref_price) {
+            let notional = price * order.qty;
+            if notional < self.min_notional {
+                return Err(SymbolError::MinNotional { notional, min: self.min_notional });
+            }
+        }
+        Ok(())
+    }
+}
+
+// Tra cứu theo symbol chữ hoa
+#[derive(Debug, Clone, Default)]
+pub struct SymbolRegistry {
+    symbols: HashMap<String, SymbolInfo>,
+}
+
+impl SymbolRegistry {
+    pub fn new(symbols: impl IntoIterator<Item = SymbolInfo>) -> Self {
+        Self { symbols: symbols.into_iter().map(|s| (s.symbol.to_uppercase(), s)).collect() }
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<&SymbolInfo> {
+        self.symbols.get(&symbol.to_uppercase())
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    // làm tròn theo tick/lot rồi kiểm tra, trả về order đã chỉnh để gửi đi
+    pub fn prepare(&self, order: &OrderIntent, ref_price: Option<Decimal>) -> Result<OrderIntent, SymbolError> {
+        let info = self.get(&order.symbol).ok_or_else(|| SymbolError::UnknownSymbol(order.symbol.clone()))?;
+        let order = info.normalize(order);
+        info.validate(&order, ref_price)?;
+        Ok(order)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn btcusdt() -> SymbolInfo {
+        SymbolInfo {
+            symbol: "BTCUSDT".into(),
+            base_asset: "BTC".into(),
+            quote_asset: "USDT".into(),
+            trading: true,
+            tick_size: dec!(0.01),
+            min_price: dec!(0.01),
+            max_price: dec!(1000000),
+            step_size: dec!(0.00001),
+            min_qty: dec!(0.00001),
+            max_qty: dec!(9000),
+            min_notional: dec!(5),
+        }
+    }
+
+    #[test]
+    fn test_round_and_validate() {
+        let registry = SymbolRegistry::new([btcusdt()]);
+        let info = registry.get("btcusdt").unwrap();
+        assert_eq!(info.round_price(dec!(100.019), OrderSide::Buy), dec!(100.01));
+        assert_eq!(info.round_price(dec!(100.011), OrderSide::Sell), dec!(100.02));
+        assert_eq!(info.round_qty(dec!(0.123456789)), dec!(0.12345));
+
+        let order = registry
+            .prepare(&OrderIntent::limit("btcusdt", OrderSide::Buy, dec!(30000.005), dec!(0.0010009)), None)
+            .unwrap();
+        assert_eq!((order.symbol.as_str(), order.price, order.qty), ("BTCUSDT", Some(dec!(30000)), dec!(0.001)));
+
+        // 0.0001 * 30000 = 3 < 5
+        let err = registry.prepare(&OrderIntent::limit("BTCUSDT", OrderSide::Buy, dec!(30000), dec!(0.0001)), None);
+        assert_eq!(err, Err(SymbolError::MinNotional { notional: dec!(3), min: dec!(5) }));
+        // market: notional theo giá tham chiếu
+        let market = OrderIntent::market("BTCUSDT", OrderSide::Sell, dec!(0.0001));
+        assert!(registry.prepare(&market, None).is_ok());
+        assert!(registry.prepare(&market, Some(dec!(30000))).is_err());
+        // làm tròn về 0
+        assert!(matches!(
+            registry.prepare(&OrderIntent::market("BTCUSDT", OrderSide::Sell, dec!(0.000001)), None),
+            Err(SymbolError::QtyOutOfRange { .. })
+        ));
+        let unknown = OrderIntent::market("ETHUSDT", OrderSide::Buy, dec!(1));
+        assert_eq!(registry.prepare(&unknown, None), Err(SymbolError::UnknownSymbol("ETHUSDT".into())));
+    }
+}
@@ -0,0 +1,110 @@
+use chrono::{DateTime, Utc};
+
+use super::orderbook::{Level, OrderbookSnapshot};
+
+// Số level mỗi phía SharedOrderbook giữ sẵn sau mỗi update
+pub const MAX_TOP_LEVELS: usize = 10;
+
+// N level tốt nhất mỗi phía dạng mảng cố định, best ở index 0.
+// Phía có ít hơn N level thì phần cuối là None
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TopLevels<const N: usize> {
+    pub timestamp: DateTime<Utc>,
+    pub last_update_id: u64,
+    pub bids: [Option<Level>; N],
+    pub asks: [Option<Level>; N],
+}
+
+impl<const N: usize> Default for TopLevels<N> {
+    fn default() -> Self {
+        Self { timestamp: DateTime::default(), last_update_id: 0, bids: [None; N], asks: [None; N] }
+    }
+}
+
+impl<const N: usize> TopLevels<N> {
+    // chỉ đi qua N level đầu mỗi phía, không duyệt cả book
+    pub fn from_snapshot(snap: &OrderbookSnapshot) -> Self {
+        let mut top = Self { timestamp: snap.timestamp, last_update_id: snap.last_update_id, ..Self::default() };
+        for (slot, (p, q)) in top.bids.iter_mut().zip(snap.bids.iter().rev()) {
+            *slot = Some((*p, *q));
+        }
+        for (slot, (p, q)) in top.asks.iter_mut().zip(snap.asks.iter()) {
+            *slot = Some((*p, *q));
+        }
+        top
+    }
+
+    // M level đầu của bản đang giữ, M <= N kiểm tra lúc compile
+    pub fn truncate<const M: usize>(&self) -> TopLevels<M> {
+        const { assert!(M <= N, "cannot take more levels than are kept") };
+        TopLevels {
+            timestamp: self.timestamp,
+            last_update_id: self.last_update_id,
+            bids: std::array::from_fn(|i| self.bids[i]),
+            asks: std::array::from_fn(|i| self.asks[i]),
+        }
+    }
+
+    // cùng level (bỏ qua timestamp / update id), để chỉ báo consumer khi top thật sự đổi
+    pub fn same_levels(&self, other: &Self) -> bool {
+        self.bids == other.bids && self.asks == other.asks
+    }
+
+    pub fn best_bid(&self) -> Option<Level> {
+        self.bids[0]
+    }
+
+    pub fn best_ask(&self) -> Option<Level> {
+        self.asks[0]
+    }
+
+    pub fn bid_depth(&self) -> usize {
+        self.bids.iter().take_while(|l| l.is_some()).count()
+    }
+
+    pub fn ask_depth(&self) -> usize {
+        self.asks.iter().take_while(|l| l.is_some()).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::orderbook::{SharedOrderbook, Side};
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_top_levels_maintained_on_update() {
+        let shared = SharedOrderbook::new();
+        let mut top = shared.subscribe_top();
+        shared.update(|ob| {
+            for i in 0..20 {
+                ob.set_level(Side::Bid, dec!(100) - rust_decimal::Decimal::from(i), dec!(1));
+                ob.set_level(Side::Ask, dec!(101) + rust_decimal::Decimal::from(i), dec!(2));
+            }
+            true
+        });
+        assert!(top.has_changed().unwrap());
+        let top3 = shared.top_n::<3>();
+        assert_eq!(top3.bids, [Some((dec!(100), dec!(1))), Some((dec!(99), dec!(1))), Some((dec!(98), dec!(1)))]);
+        assert_eq!(top3.best_ask(), Some((dec!(101), dec!(2))));
+        assert_eq!(top.borrow_and_update().bid_depth(), MAX_TOP_LEVELS);
+
+        // đổi level ngoài top thì không báo subscriber
+        shared.update(|ob| {
+            ob.set_level(Side::Ask, dec!(150), dec!(9));
+            true
+        });
+        assert!(!top.has_changed().unwrap());
+        shared.update(|ob| {
+            ob.set_level(Side::Bid, dec!(100), dec!(0));
+            true
+        });
+        assert!(top.has_changed().unwrap());
+        let top2 = shared.top_n::<2>();
+        assert_eq!((top2.best_bid(), top2.ask_depth()), (Some((dec!(99), dec!(1))), 2));
+
+        let empty = TopLevels::<4>::from_snapshot(&OrderbookSnapshot::new());
+        assert_eq!((empty.bid_depth(), empty.best_ask()), (0, None));
+    }
+}
@@ -0,0 +1,139 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::VecDeque;
+
+// Phía chủ động (aggressor) của giao dịch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeSide {
+    Buy,
+    Sell,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trade {
+    pub symbol: String,
+    pub trade_id: u64,
+    pub price: f64,
+    pub qty: f64,
+    pub side: TradeSide,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TradeWindow {
+    pub max_age: Duration,
+    pub max_len: usize,
+    trades: VecDeque<Trade>,
+}
+
+impl TradeWindow {
+    pub fn new(max_age: Duration, max_len: usize) -> Self {
+        Self {
+            max_age,
+            max_len,
+            trades: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, trade: Trade) {
+        let cutoff = trade.timestamp - self.max_age;
+        self.trades.push_back(trade);
+
+        while self.trades.len() > self.max_len {
+            self.trades.pop_front();
+        }
+        while self.trades.front().is_some_and(|t| t.timestamp < cutoff) {
+            self.trades.pop_front();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.trades.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.trades.is_empty()
+    }
+
+    pub fn last(&self) -> Option<&Trade> {
+        self.trades.back()
+    }
+
+    // n trade gần nhất, mới nhất ở cuối
+    pub fn recent(&self, n: usize) -> Vec<Trade> {
+        let skip = self.trades.len().saturating_sub(n);
+        self.trades.iter().skip(skip).cloned().collect()
+    }
+
+    pub fn since(&self, since: DateTime<Utc>) -> impl Iterator<Item = &Trade> {
+        self.trades.iter().filter(move |t| t.timestamp >= since)
+    }
+
+    // (buy volume, sell volume) từ thời điểm `since`
+    pub fn volume_since(&self, since: DateTime<Utc>) -> (f64, f64) {
+        self.since(since).fold((0.0, 0.0), |(buy, sell), t| match t.side {
+            TradeSide::Buy => (buy + t.qty, sell),
+            TradeSide::Sell => (buy, sell + t.qty),
+        })
+    }
+
+    pub fn vwap_since(&self, since: DateTime<Utc>) -> Option<f64> {
+        let (notional, qty) = self.since(since).fold((0.0, 0.0), |(n, q), t| (n + t.price * t.qty, q + t.qty));
+        (qty > 0.0).then(|| notional / qty)
+    }
+
+    // (buy - sell) / (buy + sell) trong khoảng [-1, 1]
+    pub fn imbalance_since(&self, since: DateTime<Utc>) -> Option<f64> {
+        let (buy, sell) = self.volume_since(since);
+        let total = buy + sell;
+        if total > 0.0 {
+            Some((buy - sell) / total)
+        } else {
+            None
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(id: u64, qty: f64, side: TradeSide, ts: DateTime<Utc>) -> Trade {
+        Trade {
+            symbol: "btcusdt".into(),
+            trade_id: id,
+            price: 100.0,
+            qty,
+            side,
+            timestamp: ts,
+        }
+    }
+
+    #[test]
+    fn test_window_eviction_and_imbalance() {
+        let t0 = Utc::now();
+        let mut w = TradeWindow::new(Duration::seconds(10), 100);
+
+        w.push(trade(1, 5.0, TradeSide::Sell, t0));
+        w.push(trade(2, 3.0, TradeSide::Buy, t0 + Duration::seconds(5)));
+        w.push(trade(3, 1.0, TradeSide::Sell, t0 + Duration::seconds(12)));
+
+        // trade 1 đã quá 10s so với trade mới nhất
+        assert_eq!(w.len(), 2);
+        assert_eq!(w.volume_since(t0), (3.0, 1.0));
+        assert_eq!(w.imbalance_since(t0), Some(0.5));
+        assert_eq!(w.imbalance_since(t0 + Duration::seconds(20)), None);
+    }
+
+    #[test]
+    fn test_window_max_len() {
+        let t0 = Utc::now();
+        let mut w = TradeWindow::new(Duration::minutes(5), 3);
+        for i in 0..5 {
+            w.push(trade(i, 1.0, TradeSide::Buy, t0));
+        }
+        let ids: Vec<u64> = w.recent(10).iter().map(|t| t.trade_id).collect();
+        assert_eq!(ids, vec![2, 3, 4]);
+        assert_eq!(w.recent(1)[0].trade_id, 4);
+    }
+}
@@ -0,0 +1,359 @@
+pub mod pool;
+pub mod rpc;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use serde::Deserialize;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tracing::{debug, info, info_span, warn, Instrument as _};
+
+use crate::config::Exchange;
+use crate::core::orderbook::SharedOrderbook;
+use crate::symbols::Instrument;
+use crate::ws::{
+    reconnect::{BackoffConfig, ConnectionStatus, Reconnector},
+    OrderbookFeed,
+};
+use pool::{AmmCurve, CurveLadder, Reserves};
+use rpc::{word_to_f64, EthRpc, RpcError};
+
+// selector 4 byte của các hàm view cần đọc
+const GET_RESERVES: &str = "0x0902f1ac";
+const SLOT0: &str = "0x3850c7bd";
+const LIQUIDITY: &str = "0x1a686502";
+const FEE: &str = "0xddca3f43";
+
+// PancakeSwap là fork của Uniswap, cùng ABI cho phần cần đọc
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DexProtocol {
+    UniswapV2,
+    UniswapV3,
+    PancakeswapV2,
+    PancakeswapV3,
+}
+
+impl DexProtocol {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DexProtocol::UniswapV2 => "uniswap_v2",
+            DexProtocol::UniswapV3 => "uniswap_v3",
+            DexProtocol::PancakeswapV2 => "pancakeswap_v2",
+            DexProtocol::PancakeswapV3 => "pancakeswap_v3",
+        }
+    }
+
+    pub fn is_v3(&self) -> bool {
+        matches!(self, DexProtocol::UniswapV3 | DexProtocol::PancakeswapV3)
+    }
+
+    // phí swap cố định của v2, v3 đọc `fee()` của pool
+    fn v2_fee_bps(&self) -> Decimal {
+        match self {
+            DexProtocol::PancakeswapV2 => Decimal::new(25, 0),
+            _ => Decimal::new(30, 0),
+        }
+    }
+}
+
+// `[[dex.pools]]`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PoolConfig {
+    // dạng chuẩn "CAKE/BNB", WBNB / WETH ghi là BNB / ETH để khớp với sàn CEX
+    pub symbol: String,
+    pub protocol: DexProtocol,
+    pub address: String,
+    pub decimals0: u32,
+    pub decimals1: u32,
+    // token0 là token có địa chỉ nhỏ hơn, không phải lúc nào cũng là base
+    pub base_is_token0: bool,
+    // None = 25/30 bps với v2, đọc từ pool với v3
+    pub fee_bps: Option<Decimal>,
+    pub levels: usize,
+    // khoảng giá giữa hai level của book tổng hợp
+    pub step_bps: Decimal,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            symbol: String::new(),
+            protocol: DexProtocol::PancakeswapV2,
+            address: String::new(),
+            decimals0: 18,
+            decimals1: 18,
+            base_is_token0: true,
+            fee_bps: None,
+            levels: 10,
+            step_bps: Decimal::TEN,
+        }
+    }
+}
+
+// `[dex.gas]`: chi phí gas ước lượng của một swap, đổi sang quote của cặp khi tính arb
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct GasModel {
+    pub price_gwei: Decimal,
+    pub swap_units_v2: u64,
+    pub swap_units_v3: u64,
+    // token trả gas (BNB trên BSC, ETH trên mainnet)
+    pub native_asset: String,
+    // giá native theo quote, cần khi cặp không chứa native (vd. CAKE/USDT)
+    pub native_price: Option<Decimal>,
+}
+
+impl Default for GasModel {
+    fn default() -> Self {
+        Self {
+            // BSC, để dư so với giá gas tối thiểu
+            price_gwei: Decimal::ONE,
+            swap_units_v2: 130_000,
+            swap_units_v3: 180_000,
+            native_asset: "BNB".to_string(),
+            native_price: None,
+        }
+    }
+}
+
+impl GasModel {
+    // phí gas một swap theo native token
+    pub fn native_cost(&self, protocol: DexProtocol) -> Decimal {
+        let units = if protocol.is_v3() { self.swap_units_v3 } else { self.swap_units_v2 };
+        Decimal::from(units) * self.price_gwei / Decimal::from(1_000_000_000)
+    }
+
+    // phí gas theo quote của `pair`, `mid` là giá base theo quote. None khi không quy đổi được
+    pub fn quote_cost(&self, protocol: DexProtocol, pair: &Instrument, mid: Decimal) -> Option<Decimal> {
+        let native = self.native_cost(protocol);
+        let asset = self.native_asset.to_uppercase();
+        if pair.quote == asset {
+            Some(native)
+        } else if pair.base == asset {
+            Some(native * mid)
+        } else {
+            self.native_price.map(|p| native * p)
+        }
+    }
+}
+
+// `[dex]`: đọc state pool on-chain qua JSON-RPC, dựng book tổng hợp quanh giá implied
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DexSettings {
+    pub enabled: bool,
+    pub rpc_url: String,
+    pub poll_ms: u64,
+    pub pools: Vec<PoolConfig>,
+    pub gas: GasModel,
+}
+
+impl Default for DexSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            rpc_url: "https://bsc-dataseed.bnbchain.org".to_string(),
+            // gần bằng block time của BSC
+            poll_ms: 1000,
+            pools: Vec::new(),
+            gas: GasModel::default(),
+        }
+    }
+}
+
+impl DexSettings {
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.rpc_url.is_empty() || self.poll_ms == 0 {
+            errors.push("`dex.rpc_url` must be set and `dex.poll_ms` must be > 0".to_string());
+        }
+        if self.gas.price_gwei < Decimal::ZERO || self.gas.native_price.is_some_and(|p| p <= Decimal::ZERO) {
+            errors.push("`dex.gas.price_gwei` must be >= 0 and `dex.gas.native_price` > 0".to_string());
+        }
+        let mut seen = Vec::new();
+        for pool in &self.pools {
+            match Instrument::parse(Exchange::Dex, &pool.symbol) {
+                Ok(inst) if seen.contains(&inst.canonical()) => {
+                    errors.push(format!("duplicate dex pool for {}", inst.canonical()));
+                }
+                Ok(inst) => seen.push(inst.canonical()),
+                Err(e) => errors.push(format!("invalid dex pool symbol: {}", e)),
+            }
+            let hex = pool.address.strip_prefix("0x").unwrap_or("");
+            if hex.len() != 40 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                errors.push(format!("invalid dex pool address {:?}", pool.address));
+            }
+            if pool.levels == 0 || pool.step_bps <= Decimal::ZERO {
+                errors.push(format!("dex pool {} levels and step_bps must be > 0", pool.symbol));
+            }
+            if pool.fee_bps.is_some_and(|f| f < Decimal::ZERO || f >= Decimal::from(10_000)) {
+                errors.push(format!("dex pool {} fee_bps must be in [0, 10000)", pool.symbol));
+            }
+        }
+        errors
+    }
+
+    pub fn pool_feeds(&self) -> Vec<Arc<DexPoolFeed>> {
+        let poll = Duration::from_millis(self.poll_ms);
+        self.pools.iter().map(|pool| Arc::new(DexPoolFeed::new(pool.clone(), &self.rpc_url, poll))).collect()
+    }
+
+    pub fn feeds(&self) -> Vec<Arc<dyn OrderbookFeed>> {
+        self.pool_feeds().into_iter().map(|feed| feed as Arc<dyn OrderbookFeed>).collect()
+    }
+}
+
+// Feed poll state của một pool mỗi `poll`. RPC lỗi thì chờ theo backoff như WS mất kết nối
+#[derive(Debug)]
+pub struct DexPoolFeed {
+    pub symbol: String,
+    pub pool: PoolConfig,
+    pub poll: Duration,
+    pub orderbook: Arc<SharedOrderbook>,
+    pub backoff: BackoffConfig,
+    pub connection: ConnectionStatus,
+    rpc: EthRpc,
+    // state lần poll gần nhất, để tính chính xác trên đường cong thay vì book tổng hợp
+    curve: Mutex<Option<AmmCurve>>,
+}
+
+impl DexPoolFeed {
+    pub fn new(pool: PoolConfig, rpc_url: &str, poll: Duration) -> Self {
+        let symbol = Instrument::parse(Exchange::Dex, &pool.symbol).map_or_else(|_| pool.symbol.clone(), |i| i.venue_symbol());
+        Self {
+            symbol,
+            pool,
+            poll,
+            orderbook: Arc::new(SharedOrderbook::new()),
+            backoff: BackoffConfig::default(),
+            connection: ConnectionStatus::new(),
+            rpc: EthRpc::new(rpc_url),
+            curve: Mutex::new(None),
+        }
+    }
+
+    pub fn curve(&self) -> Option<AmmCurve> {
+        *self.curve.lock().unwrap()
+    }
+
+    pub async fn start(self: Arc<Self>) {
+        let span = info_span!("dex_pool", symbol = %self.symbol, protocol = self.pool.protocol.name());
+        self.run().instrument(span).await
+    }
+
+    async fn run(&self) {
+        let mut reconnect = Reconnector::new(self.backoff.clone(), self.connection.clone());
+        let mut fee = None;
+
+        loop {
+            reconnect.connecting();
+            let mut interval = tokio::time::interval(self.poll);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                let result = match fee {
+                    Some(fee) => self.poll_once(fee).await,
+                    None => match self.fetch_fee().await {
+                        Ok(f) => {
+                            info!(fee, address = %self.pool.address, "pool fee loaded");
+                            fee = Some(f);
+                            self.poll_once(f).await
+                        }
+                        Err(e) => Err(e),
+                    },
+                };
+                match result {
+                    Ok(()) if !self.connection.state().is_connected() => reconnect.connected(),
+                    Ok(()) => {}
+                    Err(e) => {
+                        warn!(error = %e, "pool poll failed");
+                        break;
+                    }
+                }
+            }
+            if !reconnect.wait().await {
+                return;
+            }
+        }
+    }
+
+    // phí swap dạng tỉ lệ (0.0025 = 25 bps)
+    async fn fetch_fee(&self) -> Result<f64, RpcError> {
+        let bps = match self.pool.fee_bps {
+            Some(bps) => bps,
+            None if self.pool.protocol.is_v3() => {
+                // uint24, đơn vị 1/100 bps (500 = 0.05%)
+                let words = self.rpc.call(&self.pool.address, FEE).await?;
+                Decimal::from_f64_retain(word_to_f64(&words[0])).unwrap_or_default() / Decimal::ONE_HUNDRED
+            }
+            None => self.pool.protocol.v2_fee_bps(),
+        };
+        Ok(bps.to_f64().unwrap_or_default() / 10_000.0)
+    }
+
+    async fn fetch_reserves(&self) -> Result<Option<Reserves>, RpcError> {
+        let address = &self.pool.address;
+        if self.pool.protocol.is_v3() {
+            let slot0 = self.rpc.call(address, SLOT0).await?;
+            let liquidity = self.rpc.call(address, LIQUIDITY).await?;
+            Ok(Reserves::from_v3(&slot0, &liquidity))
+        } else {
+            Ok(Reserves::from_v2(&self.rpc.call(address, GET_RESERVES).await?))
+        }
+    }
+
+    async fn poll_once(&self, fee: f64) -> Result<(), RpcError> {
+        let Some(reserves) = self.fetch_reserves().await? else {
+            // pool rỗng: giữ book cũ, không coi là lỗi kết nối
+            warn!("pool has no liquidity");
+            return Ok(());
+        };
+        let (base, quote) = reserves.oriented(self.pool.decimals0, self.pool.decimals1, self.pool.base_is_token0);
+        debug!(base, quote, "pool state");
+        self.apply(AmmCurve { base, quote, fee });
+        Ok(())
+    }
+
+    // lưu state pool và dựng lại book tổng hợp
+    pub fn apply(&self, curve: AmmCurve) {
+        let ladder = CurveLadder {
+            curve,
+            step: self.pool.step_bps.to_f64().unwrap_or_default() / 10_000.0,
+            levels: self.pool.levels,
+        };
+        *self.curve.lock().unwrap() = Some(curve);
+        self.orderbook.update(|ob| {
+            ladder.write_to(ob);
+            ob.timestamp = Utc::now();
+            true
+        });
+    }
+}
+
+#[async_trait]
+impl OrderbookFeed for DexPoolFeed {
+    fn exchange(&self) -> &'static str {
+        Exchange::Dex.name()
+    }
+
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    fn orderbook(&self) -> &SharedOrderbook {
+        &self.orderbook
+    }
+
+    fn connection(&self) -> Option<&ConnectionStatus> {
+        Some(&self.connection)
+    }
+
+    async fn start(self: Arc<Self>) {
+        DexPoolFeed::start(self).await
+    }
+}
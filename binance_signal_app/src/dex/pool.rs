@@ -0,0 +1,194 @@
+use rust_decimal::{prelude::FromPrimitive, Decimal};
+
+use crate::core::orderbook::{OrderbookSnapshot, Side};
+use super::rpc::word_to_f64;
+
+// 2^96, sqrtPriceX96 của v3 là fixed-point Q64.96
+const Q96: f64 = 79_228_162_514_264_337_593_543_950_336.0;
+// số chữ số có nghĩa giữ lại khi đổi f64 sang Decimal
+const SIGNIFICANT_DIGITS: u32 = 12;
+
+// Reserve theo đơn vị nhỏ nhất của token (wei). Pool v3 dùng reserve ảo của tick hiện
+// tại: x = L / sqrtP, y = L * sqrtP, chỉ đúng khi giá chưa ra khỏi tick range
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Reserves {
+    pub reserve0: f64,
+    pub reserve1: f64,
+}
+
+impl Reserves {
+    // `getReserves()`: (uint112 reserve0, uint112 reserve1, uint32 blockTimestampLast)
+    pub fn from_v2(words: &[String]) -> Option<Self> {
+        let [r0, r1, ..] = words else { return None };
+        Self::non_empty(word_to_f64(r0), word_to_f64(r1))
+    }
+
+    // `slot0()` word đầu là sqrtPriceX96, `liquidity()` là uint128
+    pub fn from_v3(slot0: &[String], liquidity: &[String]) -> Option<Self> {
+        let sqrt_price = word_to_f64(slot0.first()?) / Q96;
+        let liquidity = word_to_f64(liquidity.first()?);
+        if sqrt_price <= 0.0 {
+            return None;
+        }
+        Self::non_empty(liquidity / sqrt_price, liquidity * sqrt_price)
+    }
+
+    fn non_empty(reserve0: f64, reserve1: f64) -> Option<Self> {
+        (reserve0 > 0.0 && reserve1 > 0.0).then_some(Self { reserve0, reserve1 })
+    }
+
+    // (base, quote) theo đơn vị token
+    pub fn oriented(&self, decimals0: u32, decimals1: u32, base_is_token0: bool) -> (f64, f64) {
+        let amount0 = self.reserve0 / 10f64.powi(decimals0 as i32);
+        let amount1 = self.reserve1 / 10f64.powi(decimals1 as i32);
+        if base_is_token0 { (amount0, amount1) } else { (amount1, amount0) }
+    }
+}
+
+// Pool constant product x * y = k theo đơn vị token, phí lấy trên lượng đầu vào
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AmmCurve {
+    pub base: f64,
+    pub quote: f64,
+    // 0.0025 = 25 bps
+    pub fee: f64,
+}
+
+impl AmmCurve {
+    pub fn mid(&self) -> f64 {
+        self.quote / self.base
+    }
+
+    fn k(&self) -> f64 {
+        self.base * self.quote
+    }
+
+    // quote phải trả (gồm phí) để nhận `qty` base, None nếu vượt reserve
+    pub fn quote_in(&self, qty: f64) -> Option<f64> {
+        (qty < self.base).then(|| self.quote * qty / (self.base - qty) / (1.0 - self.fee))
+    }
+
+    // quote nhận về khi bán `qty` base
+    pub fn quote_out(&self, qty: f64) -> f64 {
+        let net = qty * (1.0 - self.fee);
+        self.quote * net / (self.base + net)
+    }
+
+    // lượng base mua được trước khi giá biên (gồm phí) lên tới `price`
+    pub fn buy_until(&self, price: f64) -> f64 {
+        (self.base - (self.k() / (price * (1.0 - self.fee))).sqrt()).max(0.0)
+    }
+
+    // lượng base bán được trước khi giá biên (sau phí) xuống tới `price`
+    pub fn sell_until(&self, price: f64) -> f64 {
+        (((self.k() * (1.0 - self.fee) / price).sqrt() - self.base) / (1.0 - self.fee)).max(0.0)
+    }
+}
+
+// Book tổng hợp từ đường cong: level i là lượng base đi qua khi giá dịch `step`
+// thêm một bậc, giá level là giá cuối bậc đã tính phí pool
+#[derive(Debug, Clone, Copy)]
+pub struct CurveLadder {
+    pub curve: AmmCurve,
+    pub step: f64,
+    pub levels: usize,
+}
+
+impl CurveLadder {
+    // (giá, qty), level gần mid trước
+    pub fn side(&self, side: Side) -> Vec<(f64, f64)> {
+        let AmmCurve { base, fee, .. } = self.curve;
+        let k = self.curve.k();
+        let mut prev = base;
+        let mut price = self.curve.mid();
+        let mut out = Vec::with_capacity(self.levels);
+        for _ in 0..self.levels {
+            price = match side {
+                Side::Ask => price * (1.0 + self.step),
+                Side::Bid => price / (1.0 + self.step),
+            };
+            let reserve = (k / price).sqrt();
+            let (quoted, qty) = match side {
+                Side::Ask => (price / (1.0 - fee), prev - reserve),
+                // bán base thì phí trừ trên base đưa vào
+                Side::Bid => (price * (1.0 - fee), (reserve - prev) / (1.0 - fee)),
+            };
+            out.push((quoted, qty));
+            prev = reserve;
+        }
+        out
+    }
+
+    pub fn write_to(&self, ob: &mut OrderbookSnapshot) {
+        ob.clear();
+        for side in [Side::Bid, Side::Ask] {
+            for (price, qty) in self.side(side) {
+                if let (Some(price), Some(qty)) = (to_decimal(price), to_decimal(qty)) {
+                    ob.set_level(side, price, qty);
+                }
+            }
+        }
+    }
+}
+
+fn to_decimal(v: f64) -> Option<Decimal> {
+    Decimal::from_f64(v)?.round_sf(SIGNIFICANT_DIGITS).map(|d| d.normalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::rpc::words;
+
+    fn word(v: u128) -> String {
+        format!("{:064x}", v)
+    }
+
+    #[test]
+    fn test_reserves_from_eth_call() {
+        // 1000 CAKE / 4 WBNB, token0 = CAKE
+        let raw = format!("0x{}{}{}", word(1000 * 10u128.pow(18)), word(4 * 10u128.pow(18)), word(1_700_000_000));
+        let reserves = Reserves::from_v2(&words(&raw).unwrap()).unwrap();
+        let (base, quote) = reserves.oriented(18, 18, true);
+        assert!((quote / base - 0.004).abs() < 1e-12);
+        assert!(words("0x").is_err() && words("0x12").is_err());
+
+        // v3: sqrtPriceX96 = 2^96 * sqrt(4), L = 10^18 -> giá 4, x = 0.5e18, y = 2e18
+        let slot0 = words(&format!("0x{}{}", word(2 << 96), word(0))).unwrap();
+        let reserves = Reserves::from_v3(&slot0, &[word(10u128.pow(18))]).unwrap();
+        let (base, quote) = reserves.oriented(18, 18, true);
+        assert!((base - 0.5).abs() < 1e-12 && (quote - 2.0).abs() < 1e-12);
+        // pool chưa có thanh khoản
+        assert_eq!(Reserves::from_v3(&slot0, &[word(0)]), None);
+    }
+
+    #[test]
+    fn test_curve_ladder_quotes_around_mid() {
+        let curve = AmmCurve { base: 1000.0, quote: 4.0, fee: 0.0025 };
+        let ladder = CurveLadder { curve, step: 0.001, levels: 5 };
+        let asks = ladder.side(Side::Ask);
+        let bids = ladder.side(Side::Bid);
+        assert!(bids[0].0 < curve.mid() && curve.mid() < asks[0].0);
+        assert!(asks.windows(2).all(|w| w[0].0 < w[1].0) && bids.windows(2).all(|w| w[0].0 > w[1].0));
+        // mua hết 5 level ask: x còn lại = sqrt(k / p5)
+        let bought: f64 = asks.iter().map(|(_, q)| q).sum();
+        let remaining = (4000.0 / (0.004 * 1.001f64.powi(5))).sqrt();
+        assert!((1000.0 - bought - remaining).abs() < 1e-9);
+
+        // mua tới giá biên của level ask cuối đúng bằng tổng qty của ladder
+        assert!((curve.buy_until(asks[4].0) - bought).abs() < 1e-9);
+        let sold: f64 = bids.iter().map(|(_, q)| q).sum();
+        assert!((curve.sell_until(bids[4].0) - sold).abs() < 1e-9);
+        // giá biên của quote_in tại buy_until(p) là p
+        let q = curve.buy_until(0.0041);
+        let marginal = (curve.quote_in(q + 1e-6).unwrap() - curve.quote_in(q).unwrap()) / 1e-6;
+        assert!((marginal - 0.0041).abs() < 1e-9);
+        assert!(curve.quote_out(10.0) < 10.0 * curve.mid() && curve.quote_in(1000.0).is_none());
+
+        let mut ob = OrderbookSnapshot::new();
+        ladder.write_to(&mut ob);
+        assert_eq!((ob.bids.len(), ob.asks.len()), (5, 5));
+        let ((bid, _), (ask, _)) = ob.best_bid_ask().unwrap();
+        assert!(bid < ask);
+    }
+}
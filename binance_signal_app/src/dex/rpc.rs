@@ -0,0 +1,93 @@
+use serde::Deserialize;
+use serde_json::json;
+use std::{
+    fmt,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::net;
+
+#[derive(Debug)]
+pub enum RpcError {
+    Http(reqwest::Error),
+    // lỗi JSON-RPC của node (revert, rate limit, ...)
+    Rpc { code: i64, message: String },
+    // kết quả eth_call không phải các word 32 byte hex
+    InvalidResult(String),
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RpcError::Http(e) => write!(f, "rpc http error: {}", e),
+            RpcError::Rpc { code, message } => write!(f, "rpc error {}: {}", code, message),
+            RpcError::InvalidResult(s) => write!(f, "invalid eth_call result {:?}", s),
+        }
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+impl From<reqwest::Error> for RpcError {
+    fn from(e: reqwest::Error) -> Self {
+        RpcError::Http(e)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse {
+    #[serde(default)]
+    result: Option<String>,
+    #[serde(default)]
+    error: Option<RpcErrorBody>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcErrorBody {
+    code: i64,
+    message: String,
+}
+
+// JSON-RPC HTTP tối thiểu, chỉ cần `eth_call` để đọc state của pool
+#[derive(Debug)]
+pub struct EthRpc {
+    url: String,
+    http: reqwest::Client,
+    next_id: AtomicU64,
+}
+
+impl EthRpc {
+    pub fn new(url: &str) -> Self {
+        Self { url: url.to_string(), http: net::http_client(), next_id: AtomicU64::new(1) }
+    }
+
+    // gọi hàm view không tham số, trả về các word 32 byte (hex, không có "0x")
+    pub async fn call(&self, to: &str, selector: &str) -> Result<Vec<String>, RpcError> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": self.next_id.fetch_add(1, Ordering::Relaxed),
+            "method": "eth_call",
+            "params": [{ "to": to, "data": selector }, "latest"],
+        });
+        let response: RpcResponse = self.http.post(&self.url).json(&body).send().await?.error_for_status()?.json().await?;
+        if let Some(e) = response.error {
+            return Err(RpcError::Rpc { code: e.code, message: e.message });
+        }
+        words(&response.result.unwrap_or_default())
+    }
+}
+
+pub fn words(result: &str) -> Result<Vec<String>, RpcError> {
+    let hex = result.strip_prefix("0x").unwrap_or(result);
+    // "0x" rỗng: địa chỉ không phải contract
+    if hex.is_empty() || !hex.len().is_multiple_of(64) || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(RpcError::InvalidResult(result.to_string()));
+    }
+    Ok(hex.as_bytes().chunks(64).map(|w| String::from_utf8_lossy(w).into_owned()).collect())
+}
+
+// uint256 -> f64, mất chính xác ngoài 53 bit nhưng đủ cho giá
+pub fn word_to_f64(word: &str) -> f64 {
+    let half = |s: &str| u128::from_str_radix(s, 16).unwrap_or(0) as f64;
+    half(&word[..32]) * 2f64.powi(128) + half(&word[32..])
+}
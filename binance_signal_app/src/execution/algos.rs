@@ -0,0 +1,452 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::time::{Instant, MissedTickBehavior};
+use tracing::{debug, info, warn};
+
+use crate::core::{
+    backpressure::LosslessReceiver,
+    order::{Fill, OrderSide},
+    orderbook::OrderbookSnapshot,
+    signal::MarketData,
+};
+use crate::risk::OrderIntent;
+use crate::supervisor::Shutdown;
+use crate::venue::{ExecutionVenue, VenueError};
+use crate::ws::OrderbookFeed;
+
+const BPS: Decimal = Decimal::from_parts(10_000, 0, 0, false, 0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum AlgoKind {
+    // chia đều theo thời gian
+    Twap,
+    // chia theo `profile` volume trong ngày, profile rỗng thì như TWAP
+    Vwap,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlgoConfig {
+    pub kind: AlgoKind,
+    pub duration: Duration,
+    pub slice_interval: Duration,
+    // child lấy tối đa phần này của depth đang hiển thị phía đối diện trong dải giá được phép.
+    // Tính trên book, không phải participation theo volume khớp của thị trường
+    pub max_book_take: Decimal,
+    // child là limit cắt qua best đối diện tối đa chừng này bps, không vượt `limit_price`
+    pub max_slippage_bps: Decimal,
+    pub limit_price: Option<Decimal>,
+    // chậm so với lịch thì child được lớn tới catch_up_mult × phần theo lịch, 1 = không đuổi
+    pub catch_up_mult: Decimal,
+    pub lot_size: Option<Decimal>,
+    // giá child làm tròn về tick, phía không cắt thêm qua book: mua xuống, bán lên
+    pub tick_size: Option<Decimal>,
+    // trọng số volume các khoảng đều nhau trong `duration` (VWAP)
+    pub profile: Vec<Decimal>,
+}
+
+impl Default for AlgoConfig {
+    fn default() -> Self {
+        Self {
+            kind: AlgoKind::Twap,
+            duration: Duration::from_secs(600),
+            slice_interval: Duration::from_secs(10),
+            max_book_take: Decimal::new(2, 1),
+            max_slippage_bps: Decimal::from(5),
+            limit_price: None,
+            catch_up_mult: Decimal::TWO,
+            lot_size: None,
+            tick_size: None,
+            profile: Vec::new(),
+        }
+    }
+}
+
+impl AlgoConfig {
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.duration.is_zero() || self.slice_interval.is_zero() || self.slice_interval > self.duration {
+            errors.push("algo slice interval must be > 0 and <= duration".to_string());
+        }
+        if self.max_book_take <= Decimal::ZERO || self.max_book_take > Decimal::ONE {
+            errors.push("algo max book take must be in (0, 1]".to_string());
+        }
+        if self.max_slippage_bps < Decimal::ZERO || self.catch_up_mult < Decimal::ONE {
+            errors.push("algo slippage must be >= 0 and catch-up multiple >= 1".to_string());
+        }
+        if self.profile.iter().any(|w| *w < Decimal::ZERO) || !self.profile.is_empty() && self.profile.iter().sum::<Decimal>().is_zero() {
+            errors.push("vwap profile weights must be >= 0 with a positive sum".to_string());
+        }
+        if self.tick_size.is_some_and(|tick| tick <= Decimal::ZERO) {
+            errors.push("algo tick size must be > 0".to_string());
+        }
+        errors
+    }
+
+    // phần của parent order phải khớp xong sau `elapsed`
+    pub fn target_fraction(&self, elapsed: Duration) -> Decimal {
+        let millis = |d: Duration| Decimal::from(d.as_millis() as u64);
+        let t = (millis(elapsed) / millis(self.duration).max(Decimal::ONE)).min(Decimal::ONE);
+        if self.kind == AlgoKind::Twap || self.profile.is_empty() {
+            return t;
+        }
+        // nội suy tuyến tính trong từng khoảng của profile
+        let pos = t * Decimal::from(self.profile.len());
+        let idx = usize::try_from(pos.floor()).unwrap_or(0).min(self.profile.len() - 1);
+        let done: Decimal = self.profile[..idx].iter().sum();
+        let partial = self.profile[idx] * (pos - Decimal::from(idx));
+        (done + partial) / self.profile.iter().sum::<Decimal>()
+    }
+
+    // giá child: cắt qua best đối diện tối đa `max_slippage_bps`, giới hạn bởi `limit_price`,
+    // làm tròn về `tick_size`
+    pub fn child_price(&self, side: OrderSide, book: &OrderbookSnapshot) -> Option<Decimal> {
+        let slip = Decimal::ONE + side.sign() * self.max_slippage_bps / BPS;
+        let price = match side {
+            OrderSide::Buy => book.best_ask()?.0 * slip,
+            OrderSide::Sell => book.best_bid()?.0 * slip,
+        };
+        let price = match (side, self.limit_price) {
+            (OrderSide::Buy, Some(limit)) => price.min(limit),
+            (OrderSide::Sell, Some(limit)) => price.max(limit),
+            (_, None) => price,
+        };
+        match (side, self.tick_size) {
+            (OrderSide::Buy, Some(tick)) if tick > Decimal::ZERO => Some((price / tick).floor() * tick),
+            (OrderSide::Sell, Some(tick)) if tick > Decimal::ZERO => Some((price / tick).ceil() * tick),
+            _ => Some(price),
+        }
+    }
+
+    // khối lượng phía đối diện khớp được ở giá `price` hoặc tốt hơn
+    pub fn available(side: OrderSide, book: &OrderbookSnapshot, price: Decimal) -> Decimal {
+        match side {
+            OrderSide::Buy => book.asks.iter().take_while(|(p, _)| **p <= price).map(|(_, q)| *q).sum(),
+            OrderSide::Sell => book.bids.iter().rev().take_while(|(p, _)| **p >= price).map(|(_, q)| *q).sum(),
+        }
+    }
+
+    // Qty child kế tiếp: đưa phần đã khớp về đúng lịch ở cuối slice tới, tối đa
+    // catch_up_mult × phần theo lịch của slice và max_book_take × `available`.
+    // `outstanding`: child đã gửi chưa khớp và chưa xác nhận huỷ, có thể còn khớp
+    pub fn child_qty(&self, parent_qty: Decimal, filled: Decimal, outstanding: Decimal, elapsed: Duration, available: Decimal) -> Decimal {
+        let committed = filled + outstanding;
+        let next = elapsed + self.slice_interval;
+        let scheduled = parent_qty * (self.target_fraction(next) - self.target_fraction(elapsed));
+        let behind = parent_qty * self.target_fraction(next) - committed;
+        let qty = behind
+            .min(scheduled * self.catch_up_mult)
+            .min(available * self.max_book_take)
+            .min(parent_qty - committed);
+        let qty = match self.lot_size {
+            Some(lot) if lot > Decimal::ZERO => (qty / lot).floor() * lot,
+            _ => qty,
+        };
+        qty.max(Decimal::ZERO)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AlgoReport {
+    pub kind: AlgoKind,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub qty: Decimal,
+    pub filled: Decimal,
+    // giá khớp trung bình, None nếu chưa khớp gì
+    pub avg_price: Option<Decimal>,
+    pub children: usize,
+    pub started: DateTime<Utc>,
+    pub finished: DateTime<Utc>,
+    pub completed: bool,
+}
+
+// child đã gửi, `closed_filled` = qty khớp cuối cùng khi venue xác nhận order đã đóng
+#[derive(Debug, Clone, Copy)]
+struct Child {
+    qty: Decimal,
+    filled: Decimal,
+    closed_filled: Option<Decimal>,
+}
+
+impl Child {
+    // phần còn có thể khớp (hoặc đã khớp nhưng fill chưa tới)
+    fn outstanding(&self) -> Decimal {
+        (self.closed_filled.unwrap_or(self.qty) - self.filled).max(Decimal::ZERO)
+    }
+}
+
+// Chạy một parent order qua ExecutionVenue (live đi qua OMS), mỗi slice huỷ child cũ
+// còn treo và gửi child mới theo lịch
+pub struct AlgoExecutor {
+    config: AlgoConfig,
+    parent: OrderIntent,
+    venue: Arc<dyn ExecutionVenue>,
+    fills: LosslessReceiver<Fill>,
+    // id child đã gửi (paper: order id, live: client order id)
+    children: HashMap<String, Child>,
+    filled: Decimal,
+    notional: Decimal,
+    started: DateTime<Utc>,
+}
+
+impl AlgoExecutor {
+    pub fn new(config: AlgoConfig, parent: OrderIntent, venue: Arc<dyn ExecutionVenue>) -> Self {
+        // subscribe trước khi gửi child đầu để không sót fill
+        let fills = venue.fills();
+        Self {
+            config,
+            parent,
+            venue,
+            fills,
+            children: HashMap::new(),
+            filled: Decimal::ZERO,
+            notional: Decimal::ZERO,
+            started: Utc::now(),
+        }
+    }
+
+    pub fn filled(&self) -> Decimal {
+        self.filled
+    }
+
+    fn done(&self) -> bool {
+        self.filled >= self.parent.qty
+    }
+
+    pub fn outstanding(&self) -> Decimal {
+        self.children.values().map(Child::outstanding).sum()
+    }
+
+    async fn on_fill(&mut self, fill: &Fill) {
+        // live: fill mang order id của sàn, OMS giữ client order id của child
+        let order_ref = match self.venue.oms_handle() {
+            Some(oms) => oms.lock().await.order_by_id(fill.order_id).map(|o| o.client_order_id.clone()),
+            None => None,
+        };
+        let order_ref = order_ref.unwrap_or_else(|| fill.order_id.to_string());
+        if let Some(child) = self.children.get_mut(&order_ref) {
+            child.filled += fill.qty;
+            self.filled += fill.qty;
+            self.notional += fill.notional();
+        }
+    }
+
+    async fn drain_fills(&mut self) {
+        while let Ok(fill) = self.fills.try_recv() {
+            self.on_fill(&fill).await;
+        }
+    }
+
+    // live `open_orders` ẩn order PendingCancel nên child đã gửi huỷ không bị huỷ lại
+    async fn cancel_children(&mut self) -> Result<(), VenueError> {
+        let symbol = self.parent.symbol.clone();
+        for order in self.venue.open_orders(&symbol).await? {
+            if self.children.contains_key(&order.id) {
+                self.venue.cancel(&symbol, &order.id).await?;
+            }
+        }
+        Ok(())
+    }
+
+    // Đánh dấu child đã đóng: live chờ trạng thái cuối trong OMS (PendingCancel vẫn có thể
+    // khớp), paper khớp / huỷ đồng bộ nên không còn trong `open_orders` là đã đóng
+    async fn refresh_children(&mut self) -> Result<(), VenueError> {
+        let open: HashSet<String> = self.venue.open_orders(&self.parent.symbol).await?.into_iter().map(|o| o.id).collect();
+        let oms = self.venue.oms_handle();
+        let oms = match &oms {
+            Some(oms) => Some(oms.lock().await),
+            None => None,
+        };
+        for (id, child) in self.children.iter_mut().filter(|(_, c)| c.closed_filled.is_none()) {
+            child.closed_filled = match &oms {
+                Some(oms) => match oms.order(id) {
+                    Some(order) => order.status.is_final().then_some(order.filled_qty),
+                    None => Some(child.filled),
+                },
+                None => (!open.contains(id)).then_some(child.filled),
+            };
+        }
+        Ok(())
+    }
+
+    // Một slice ở thời điểm `elapsed` kể từ lúc bắt đầu, trả về id child nếu có gửi
+    pub async fn slice(&mut self, book: &OrderbookSnapshot, elapsed: Duration) -> Result<Option<String>, VenueError> {
+        self.cancel_children().await?;
+        self.drain_fills().await;
+        self.refresh_children().await?;
+        let outstanding = self.outstanding();
+        if self.filled + outstanding >= self.parent.qty {
+            debug!(symbol = %self.parent.symbol, filled = %self.filled, %outstanding, "algo waiting on outstanding children");
+            return Ok(None);
+        }
+        let side = self.parent.side;
+        let Some(price) = self.config.child_price(side, book) else { return Ok(None) };
+        let available = AlgoConfig::available(side, book, price);
+        let qty = self.config.child_qty(self.parent.qty, self.filled, outstanding, elapsed, available);
+        if qty.is_zero() {
+            debug!(symbol = %self.parent.symbol, filled = %self.filled, %outstanding, %available, "algo slice skipped");
+            return Ok(None);
+        }
+        let id = self.venue.place_order(&OrderIntent::limit(&self.parent.symbol, side, price, qty)).await?;
+        self.children.insert(id.clone(), Child { qty, filled: Decimal::ZERO, closed_filled: None });
+        // paper khớp ngay trong `place_order`
+        self.drain_fills().await;
+        debug!(symbol = %self.parent.symbol, %price, %qty, filled = %self.filled, "algo child sent");
+        Ok(Some(id))
+    }
+
+    pub fn report(&self) -> AlgoReport {
+        AlgoReport {
+            kind: self.config.kind,
+            symbol: self.parent.symbol.clone(),
+            side: self.parent.side,
+            qty: self.parent.qty,
+            filled: self.filled,
+            avg_price: (!self.filled.is_zero()).then(|| self.notional / self.filled),
+            children: self.children.len(),
+            started: self.started,
+            finished: Utc::now(),
+            completed: self.done(),
+        }
+    }
+
+    // Slice mỗi `slice_interval` tới khi khớp đủ hoặc hết `duration`, child còn treo bị huỷ
+    pub async fn run(mut self, feed: Arc<dyn OrderbookFeed>, mut shutdown: Shutdown) -> AlgoReport {
+        let start = Instant::now();
+        self.started = Utc::now();
+        let mut tick = tokio::time::interval(self.config.slice_interval);
+        tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        while !self.done() && start.elapsed() < self.config.duration {
+            tokio::select! {
+                _ = tick.tick() => {
+                    let snap = feed.snapshot();
+                    self.venue.on_market_data(&MarketData::Orderbook { symbol: self.parent.symbol.clone(), snap: snap.clone() });
+                    self.drain_fills().await;
+                    if let Err(e) = self.slice(&snap, start.elapsed()).await {
+                        warn!(venue = self.venue.name(), symbol = %self.parent.symbol, error = %e, "algo slice failed");
+                    }
+                }
+                fill = self.fills.recv() => match fill {
+                    Some(fill) => self.on_fill(&fill).await,
+                    None => break,
+                },
+                _ = shutdown.wait() => break,
+            }
+        }
+        if let Err(e) = self.cancel_children().await {
+            warn!(venue = self.venue.name(), error = %e, "cancel algo children failed");
+        }
+        self.drain_fills().await;
+        let report = self.report();
+        info!(
+            symbol = %report.symbol,
+            side = ?report.side,
+            qty = %report.qty,
+            filled = %report.filled,
+            avg_price = ?report.avg_price,
+            children = report.children,
+            "algo finished"
+        );
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{order::OrderType, orderbook::Side};
+    use crate::rest::binance::{BinanceCredentials, BinanceRestClient};
+    use crate::venue::{binance::BinanceVenue, paper::PaperVenue};
+    use rust_decimal_macros::dec;
+
+    fn secs(s: u64) -> Duration {
+        Duration::from_secs(s)
+    }
+
+    #[test]
+    fn test_schedule_and_catch_up() {
+        let twap = AlgoConfig { duration: secs(100), slice_interval: secs(10), max_book_take: Decimal::ONE, ..Default::default() };
+        assert_eq!(twap.target_fraction(secs(25)), dec!(0.25));
+        // đúng lịch: mỗi slice 1/10
+        assert_eq!(twap.child_qty(dec!(10), dec!(3), Decimal::ZERO, secs(30), dec!(100)), dec!(1));
+        // chậm 3 slice: đuổi tối đa 2 × phần theo lịch
+        assert_eq!(twap.child_qty(dec!(10), Decimal::ZERO, Decimal::ZERO, secs(30), dec!(100)), dec!(2));
+        // book mỏng: giới hạn theo phần depth được lấy
+        let capped = AlgoConfig { max_book_take: dec!(0.5), ..twap.clone() };
+        assert_eq!(capped.child_qty(dec!(10), dec!(3), Decimal::ZERO, secs(30), dec!(1)), dec!(0.5));
+        // child trước còn treo 0.5: chỉ bù phần còn thiếu
+        assert_eq!(twap.child_qty(dec!(10), dec!(3), dec!(0.5), secs(30), dec!(100)), dec!(0.5));
+
+        // làm tròn về tick phía không cắt thêm qua book
+        let mut book = OrderbookSnapshot::new();
+        book.set_level(Side::Bid, dec!(99.97), dec!(1));
+        book.set_level(Side::Ask, dec!(100.03), dec!(1));
+        let ticked = AlgoConfig { tick_size: Some(dec!(0.01)), ..Default::default() };
+        assert_eq!(ticked.child_price(OrderSide::Buy, &book), Some(dec!(100.08)));
+        assert_eq!(ticked.child_price(OrderSide::Sell, &book), Some(dec!(99.93)));
+
+        // VWAP nửa đầu chiếm 3/4 volume
+        let vwap = AlgoConfig { kind: AlgoKind::Vwap, profile: vec![dec!(3), dec!(1)], ..twap };
+        assert_eq!(vwap.target_fraction(secs(50)), dec!(0.75));
+        assert_eq!(vwap.target_fraction(secs(75)), dec!(0.875));
+        assert_eq!(vwap.child_qty(dec!(8), dec!(6), Decimal::ZERO, secs(50), dec!(100)), dec!(0.4));
+        assert!(AlgoConfig { slice_interval: secs(0), ..Default::default() }.validate().len() == 1);
+    }
+
+    #[tokio::test]
+    async fn test_executor_slices_parent_order() {
+        let venue = Arc::new(PaperVenue::default());
+        let mut book = OrderbookSnapshot::new();
+        book.set_level(Side::Bid, dec!(99), dec!(10));
+        book.set_level(Side::Ask, dec!(100), dec!(10));
+        venue.on_market_data(&MarketData::Orderbook { symbol: "BTCUSDT".into(), snap: Arc::new(book.clone()) });
+
+        let config = AlgoConfig { duration: secs(40), slice_interval: secs(10), max_slippage_bps: Decimal::ZERO, ..Default::default() };
+        let mut algo = AlgoExecutor::new(config, OrderIntent::market("btcusdt", OrderSide::Buy, dec!(4)), venue.clone());
+        for i in 0..4 {
+            assert!(algo.slice(&book, secs(i * 10)).await.unwrap().is_some());
+            assert_eq!(algo.filled(), Decimal::from(i + 1));
+        }
+        // đã khớp đủ
+        assert_eq!(algo.slice(&book, secs(40)).await.unwrap(), None);
+        let report = algo.report();
+        assert_eq!((report.completed, report.children, report.avg_price), (true, 4, Some(dec!(100))));
+        assert_eq!(venue.position("BTCUSDT").await.unwrap().qty, dec!(4));
+    }
+
+    #[tokio::test]
+    async fn test_pending_cancel_child_stays_outstanding() {
+        // cổng đóng: REST lỗi ngay, order chỉ sống trong OMS
+        let client = BinanceRestClient::new(Some(BinanceCredentials::new("key", "secret"))).with_base_url("http://127.0.0.1:9");
+        let venue = Arc::new(BinanceVenue::new(client, "test"));
+        let oms = venue.oms_handle().unwrap();
+        let (pending, rejected) = {
+            let mut oms = oms.lock().await;
+            let pending = oms.create_order("btcusdt", OrderSide::Buy, OrderType::Limit, Some(dec!(100)), dec!(3));
+            oms.on_ack(&pending, 1).unwrap();
+            oms.request_cancel(&pending).unwrap();
+            (pending, oms.create_order("btcusdt", OrderSide::Buy, OrderType::Limit, Some(dec!(100)), dec!(1)))
+        };
+        let mut book = OrderbookSnapshot::new();
+        book.set_level(Side::Bid, dec!(99), dec!(10));
+        book.set_level(Side::Ask, dec!(100), dec!(10));
+
+        let mut algo = AlgoExecutor::new(AlgoConfig::default(), OrderIntent::market("btcusdt", OrderSide::Buy, dec!(3)), venue);
+        algo.children.insert(pending.clone(), Child { qty: dec!(3), filled: Decimal::ZERO, closed_filled: None });
+        // PendingCancel vẫn có thể khớp: không huỷ lại, không gửi child mới
+        assert_eq!(algo.slice(&book, secs(300)).await.unwrap(), None);
+        assert_eq!(algo.outstanding(), dec!(3));
+
+        algo.children.insert(rejected.clone(), Child { qty: dec!(1), filled: Decimal::ZERO, closed_filled: None });
+        oms.lock().await.on_reject(&rejected, "test").unwrap();
+        algo.refresh_children().await.unwrap();
+        assert_eq!(algo.outstanding(), dec!(3));
+    }
+}
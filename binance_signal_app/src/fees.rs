@@ -0,0 +1,127 @@
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::config::Exchange;
+
+const BPS: Decimal = Decimal::from_parts(10_000, 0, 0, false, 0);
+
+// Biểu phí public theo VIP tier (maker, taker), đơn vị 0.01 bps. Tier cao hơn
+// bảng thì dùng dòng cuối
+const BINANCE_SPOT: &[(i64, i64)] = &[(1000, 1000), (900, 1000), (800, 1000), (420, 600), (420, 540)];
+const BINANCE_FUTURES: &[(i64, i64)] = &[(200, 500), (160, 400), (140, 350), (120, 320), (100, 300)];
+const COINBASE: &[(i64, i64)] = &[(6000, 12000), (4000, 6000), (2500, 4000), (1500, 2500), (1000, 2000)];
+const OKX: &[(i64, i64)] = &[(800, 1000), (750, 900), (700, 850), (650, 800)];
+const BYBIT: &[(i64, i64)] = &[(1000, 1000), (675, 800), (650, 775), (625, 750)];
+const KRAKEN: &[(i64, i64)] = &[(2500, 4000), (2000, 3500), (1400, 2400), (1200, 2200), (1000, 2000)];
+const BITFINEX: &[(i64, i64)] = &[(1000, 2000), (800, 2000), (600, 2000), (400, 2000), (200, 2000)];
+const GATEIO: &[(i64, i64)] = &[(2000, 2000), (1850, 1850), (1750, 1750), (1650, 1650), (1550, 1550)];
+const MEXC: &[(i64, i64)] = &[(0, 500)];
+const HTX: &[(i64, i64)] = &[(2000, 2000), (1800, 1900), (1600, 1800), (1400, 1700)];
+// phí pool đã nằm trong giá book tổng hợp của `dex`, gas chưa tính
+const DEX: &[(i64, i64)] = &[(0, 0)];
+const KUCOIN: &[(i64, i64)] = &[(1000, 1000), (900, 1000), (750, 950), (650, 900), (500, 800)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct FeeSchedule {
+    pub maker_bps: Decimal,
+    pub taker_bps: Decimal,
+}
+
+impl Default for FeeSchedule {
+    // Binance spot VIP 0
+    fn default() -> Self {
+        Self { maker_bps: Decimal::TEN, taker_bps: Decimal::TEN }
+    }
+}
+
+impl FeeSchedule {
+    pub fn new(maker_bps: Decimal, taker_bps: Decimal) -> Self {
+        Self { maker_bps, taker_bps }
+    }
+
+    pub fn rate_bps(&self, is_maker: bool) -> Decimal {
+        if is_maker { self.maker_bps } else { self.taker_bps }
+    }
+
+    // phí theo quote của một lệnh khớp `notional`
+    pub fn fee(&self, notional: Decimal, is_maker: bool) -> Decimal {
+        notional.abs() * self.rate_bps(is_maker) / BPS
+    }
+
+    // biểu phí mặc định của sàn ở `tier`
+    pub fn for_tier(exchange: Exchange, tier: usize) -> Self {
+        let table = match exchange {
+            Exchange::Binance => BINANCE_SPOT,
+            Exchange::BinanceFutures => BINANCE_FUTURES,
+            Exchange::Coinbase => COINBASE,
+            Exchange::Okx => OKX,
+            Exchange::Bybit => BYBIT,
+            Exchange::Kraken => KRAKEN,
+            Exchange::Kucoin => KUCOIN,
+            Exchange::Gateio => GATEIO,
+            Exchange::Bitfinex => BITFINEX,
+            Exchange::Mexc => MEXC,
+            Exchange::Htx => HTX,
+            Exchange::Dex => DEX,
+        };
+        let (maker, taker) = table[tier.min(table.len() - 1)];
+        Self::new(Decimal::new(maker, 2), Decimal::new(taker, 2))
+    }
+}
+
+// `[fees]`: tier theo sàn, có thể ghi đè hẳn biểu phí (vd. phí đã giảm BNB)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct FeeModel {
+    pub tiers: HashMap<Exchange, usize>,
+    pub overrides: HashMap<Exchange, FeeSchedule>,
+}
+
+impl FeeModel {
+    pub fn schedule(&self, exchange: Exchange) -> FeeSchedule {
+        match self.overrides.get(&exchange) {
+            Some(schedule) => *schedule,
+            None => FeeSchedule::for_tier(exchange, self.tiers.get(&exchange).copied().unwrap_or(0)),
+        }
+    }
+
+    // theo `OrderbookFeed::exchange()`, sàn lạ (replay, ...) dùng mặc định
+    pub fn schedule_by_name(&self, exchange: &str) -> FeeSchedule {
+        Exchange::from_name(exchange).map(|e| self.schedule(e)).unwrap_or_default()
+    }
+
+    // maker âm = rebate, taker thì không
+    pub fn validate(&self) -> Vec<String> {
+        self.overrides
+            .iter()
+            .filter(|(_, s)| s.taker_bps < Decimal::ZERO)
+            .map(|(e, _)| format!("`fees.overrides.{}.taker_bps` must be >= 0", e.name()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_tiers_and_overrides() {
+        let mut model = FeeModel::default();
+        assert_eq!(model.schedule(Exchange::Binance), FeeSchedule::new(dec!(10), dec!(10)));
+
+        model.tiers.insert(Exchange::Binance, 3);
+        model.tiers.insert(Exchange::BinanceFutures, 99);
+        model.overrides.insert(Exchange::Okx, FeeSchedule::new(dec!(-0.5), dec!(5)));
+        assert_eq!(model.schedule(Exchange::Binance), FeeSchedule::new(dec!(4.2), dec!(6)));
+        assert_eq!(model.schedule_by_name("binance_futures"), FeeSchedule::new(dec!(1), dec!(3)));
+        assert_eq!(model.schedule_by_name("replay"), FeeSchedule::default());
+
+        let okx = model.schedule(Exchange::Okx);
+        assert_eq!((okx.fee(dec!(-1000), false), okx.fee(dec!(1000), true)), (dec!(0.5), dec!(-0.05)));
+        assert!(model.validate().is_empty());
+        model.overrides.insert(Exchange::Kraken, FeeSchedule::new(dec!(0), dec!(-1)));
+        assert_eq!(model.validate(), vec!["`fees.overrides.kraken.taker_bps` must be >= 0".to_string()]);
+    }
+}
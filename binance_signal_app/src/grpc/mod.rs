@@ -0,0 +1,258 @@
+// Result<_, tonic::Status> là kiểu bắt buộc của tonic, Status lớn cũng không đổi được
+#![allow(clippy::result_large_err)]
+
+use futures_util::{stream, Stream, StreamExt};
+use rust_decimal::Decimal;
+use std::{net::SocketAddr, pin::Pin};
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream, WatchStream};
+use tonic::{transport::Server, Request, Response, Status};
+use tracing::info;
+
+use crate::core::{
+    orderbook::OrderbookSnapshot,
+    signal::SignalOutput,
+    trade::{Trade, TradeSide},
+};
+use crate::hub::MarketHub;
+use crate::supervisor::Shutdown;
+
+pub mod proto {
+    tonic::include_proto!("market_data");
+}
+
+use proto::market_data_server::{MarketData, MarketDataServer};
+
+type GrpcStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+fn levels<'a>(iter: impl Iterator<Item = (&'a Decimal, &'a Decimal)>, depth: usize) -> Vec<proto::Level> {
+    iter.take(depth)
+        .map(|(p, q)| proto::Level { price: p.to_string(), qty: q.to_string() })
+        .collect()
+}
+
+// depth = 0 lấy toàn bộ book
+pub fn snapshot_to_proto(exchange: &str, symbol: &str, snap: &OrderbookSnapshot, depth: usize) -> proto::OrderbookSnapshot {
+    let depth = if depth == 0 { usize::MAX } else { depth };
+    proto::OrderbookSnapshot {
+        exchange: exchange.to_string(),
+        symbol: symbol.to_uppercase(),
+        timestamp_ms: snap.timestamp.timestamp_millis(),
+        last_update_id: snap.last_update_id,
+        bids: levels(snap.bids.iter().rev(), depth),
+        asks: levels(snap.asks.iter(), depth),
+    }
+}
+
+impl From<&Trade> for proto::Trade {
+    fn from(t: &Trade) -> Self {
+        let side = match t.side {
+            TradeSide::Buy => proto::Side::Buy,
+            TradeSide::Sell => proto::Side::Sell,
+        };
+        Self {
+            symbol: t.symbol.to_uppercase(),
+            trade_id: t.trade_id,
+            price: t.price,
+            qty: t.qty,
+            side: side as i32,
+            timestamp_ms: t.timestamp.timestamp_millis(),
+        }
+    }
+}
+
+impl From<&SignalOutput> for proto::SignalUpdate {
+    fn from(out: &SignalOutput) -> Self {
+        Self {
+            symbol: out.symbol.clone(),
+            timestamp_ms: out.timestamp.timestamp_millis(),
+            values: out
+                .values
+                .iter()
+                .map(|(name, value)| proto::SignalValue { name: name.clone(), value: *value })
+                .collect(),
+        }
+    }
+}
+
+fn exchange_filter(exchange: &str) -> Option<&str> {
+    (!exchange.is_empty()).then_some(exchange)
+}
+
+fn symbol_filter(symbols: &[String]) -> impl Fn(&str) -> bool + Send + 'static {
+    let symbols: Vec<String> = symbols.iter().map(|s| s.to_uppercase()).collect();
+    move |symbol| symbols.is_empty() || symbols.iter().any(|s| s.eq_ignore_ascii_case(symbol))
+}
+
+// Client chậm nhận `Lagged` thì bỏ qua phần bị mất, không đóng stream
+fn broadcast_stream<T, U>(
+    rx: tokio::sync::broadcast::Receiver<T>,
+    map: impl Fn(&T) -> Option<U> + Send + 'static,
+) -> GrpcStream<U>
+where
+    T: Clone + Send + 'static,
+    U: Send + 'static,
+{
+    Box::pin(BroadcastStream::new(rx).filter_map(move |item| {
+        let out = match item {
+            Ok(value) => map(&value).map(Ok),
+            Err(BroadcastStreamRecvError::Lagged(_)) => None,
+        };
+        std::future::ready(out)
+    }))
+}
+
+pub struct MarketDataService {
+    hub: MarketHub,
+}
+
+impl MarketDataService {
+    pub fn new(hub: MarketHub) -> Self {
+        Self { hub }
+    }
+}
+
+#[tonic::async_trait]
+impl MarketData for MarketDataService {
+    async fn get_snapshot(
+        &self,
+        request: Request<proto::SnapshotRequest>,
+    ) -> Result<Response<proto::OrderbookSnapshot>, Status> {
+        let req = request.into_inner();
+        let feed = self
+            .hub
+            .feed(&req.symbol, exchange_filter(&req.exchange))
+            .ok_or_else(|| Status::not_found(format!("no feed for {}", req.symbol)))?;
+        let snap = feed.snapshot();
+        Ok(Response::new(snapshot_to_proto(feed.exchange(), feed.symbol(), &snap, req.depth as usize)))
+    }
+
+    type SubscribeOrderbookStream = GrpcStream<proto::OrderbookSnapshot>;
+
+    async fn subscribe_orderbook(
+        &self,
+        request: Request<proto::SubscribeRequest>,
+    ) -> Result<Response<Self::SubscribeOrderbookStream>, Status> {
+        let req = request.into_inner();
+        let feeds = self.hub.select(&req.symbols, exchange_filter(&req.exchange));
+        if feeds.is_empty() {
+            return Err(Status::not_found("no feed matches the request"));
+        }
+        let depth = req.depth as usize;
+        // watch: gửi ngay bản hiện tại, sau đó chỉ bản mới nhất mỗi lần client kịp đọc
+        let streams = feeds.into_iter().map(|feed| {
+            WatchStream::new(feed.watch())
+                .map(move |snap| Ok(snapshot_to_proto(feed.exchange(), feed.symbol(), &snap, depth)))
+                .boxed()
+        });
+        Ok(Response::new(Box::pin(stream::select_all(streams))))
+    }
+
+    type SubscribeTradesStream = GrpcStream<proto::Trade>;
+
+    async fn subscribe_trades(
+        &self,
+        request: Request<proto::SubscribeRequest>,
+    ) -> Result<Response<Self::SubscribeTradesStream>, Status> {
+        let wanted = symbol_filter(&request.into_inner().symbols);
+        let stream = broadcast_stream(self.hub.subscribe_trades(), move |t: &Trade| wanted(&t.symbol).then(|| t.into()));
+        Ok(Response::new(stream))
+    }
+
+    type SubscribeSignalsStream = GrpcStream<proto::SignalUpdate>;
+
+    async fn subscribe_signals(
+        &self,
+        request: Request<proto::SubscribeRequest>,
+    ) -> Result<Response<Self::SubscribeSignalsStream>, Status> {
+        let wanted = symbol_filter(&request.into_inner().symbols);
+        let stream =
+            broadcast_stream(self.hub.subscribe_signals(), move |o: &SignalOutput| wanted(&o.symbol).then(|| o.into()));
+        Ok(Response::new(stream))
+    }
+}
+
+// Chạy gRPC server tới khi shutdown
+pub async fn serve(addr: SocketAddr, hub: MarketHub, mut shutdown: Shutdown) -> Result<(), tonic::transport::Error> {
+    info!(%addr, feeds = hub.feeds().len(), "grpc market data server listening");
+    Server::builder()
+        .add_service(MarketDataServer::new(MarketDataService::new(hub)))
+        .serve_with_shutdown(addr, async move { shutdown.wait().await })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::orderbook::{SharedOrderbook, Side};
+    use crate::replay::ReplayFeed;
+    use crate::ws::OrderbookFeed;
+    use chrono::Utc;
+    use proto::market_data_client::MarketDataClient;
+    use rust_decimal_macros::dec;
+    use std::sync::Arc;
+    use tokio::sync::broadcast;
+    use tokio_stream::wrappers::TcpListenerStream;
+
+    #[tokio::test]
+    async fn test_grpc_round_trip() {
+        let feed = Arc::new(ReplayFeed { symbol: "btcusdt".into(), orderbook: Arc::new(SharedOrderbook::new()) });
+        feed.orderbook().update(|ob| {
+            ob.set_level(Side::Bid, dec!(100), dec!(1));
+            ob.set_level(Side::Bid, dec!(99), dec!(2));
+            ob.set_level(Side::Ask, dec!(101), dec!(3));
+            true
+        });
+        let (signals_tx, _) = broadcast::channel(16);
+        let hub = MarketHub::new(vec![feed.clone() as Arc<dyn OrderbookFeed>], signals_tx);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = Server::builder()
+            .add_service(MarketDataServer::new(MarketDataService::new(hub.clone())))
+            .serve_with_incoming(TcpListenerStream::new(listener));
+        let server = tokio::spawn(server);
+
+        let mut client = MarketDataClient::connect(format!("http://{}", addr)).await.unwrap();
+        let snap = client
+            .get_snapshot(proto::SnapshotRequest { symbol: "BTCUSDT".into(), exchange: String::new(), depth: 1 })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!((snap.exchange.as_str(), snap.symbol.as_str()), ("replay", "BTCUSDT"));
+        assert_eq!(snap.bids, vec![proto::Level { price: "100".into(), qty: "1".into() }]);
+        assert_eq!(snap.asks.len(), 1);
+
+        let missing = client
+            .get_snapshot(proto::SnapshotRequest { symbol: "ETHUSDT".into(), ..Default::default() })
+            .await;
+        assert_eq!(missing.unwrap_err().code(), tonic::Code::NotFound);
+
+        let mut books = client
+            .subscribe_orderbook(proto::SubscribeRequest { symbols: vec!["btcusdt".into()], ..Default::default() })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(books.message().await.unwrap().unwrap().bids.len(), 2);
+
+        let mut trades = client
+            .subscribe_trades(proto::SubscribeRequest { symbols: vec!["BTCUSDT".into()], ..Default::default() })
+            .await
+            .unwrap()
+            .into_inner();
+        // stream được đăng ký khi server nhận request, publish sau đó
+        let trade = |symbol: &str, id| Trade {
+            symbol: symbol.into(),
+            trade_id: id,
+            price: 100.5,
+            qty: 0.1,
+            side: TradeSide::Sell,
+            timestamp: Utc::now(),
+        };
+        hub.publish_trade(trade("ethusdt", 1));
+        hub.publish_trade(trade("btcusdt", 2));
+        let got = trades.message().await.unwrap().unwrap();
+        assert_eq!((got.trade_id, got.side), (2, proto::Side::Sell as i32));
+
+        server.abort();
+    }
+}
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::bus::{EventBus, MarketEvent};
+use crate::core::{signal::SignalOutput, trade::Trade};
+use crate::ws::OrderbookFeed;
+
+const TRADE_CHANNEL_CAPACITY: usize = 1024;
+
+// Nguồn data dùng chung cho các server / sink (gRPC, ...): feed orderbook,
+// trade và output của SignalEngine. Clone rẻ, mọi bản clone dùng chung channel
+#[derive(Clone)]
+pub struct MarketHub {
+    feeds: Arc<Vec<Arc<dyn OrderbookFeed>>>,
+    trades_tx: broadcast::Sender<Trade>,
+    signals_tx: broadcast::Sender<SignalOutput>,
+    events: EventBus,
+}
+
+impl MarketHub {
+    // `signals_tx` lấy từ `SignalEngine::output_sender`
+    pub fn new(feeds: Vec<Arc<dyn OrderbookFeed>>, signals_tx: broadcast::Sender<SignalOutput>) -> Self {
+        let (trades_tx, _) = broadcast::channel(TRADE_CHANNEL_CAPACITY);
+        Self { feeds: Arc::new(feeds), trades_tx, signals_tx, events: EventBus::default() }
+    }
+
+    pub fn feeds(&self) -> &[Arc<dyn OrderbookFeed>] {
+        &self.feeds
+    }
+
+    // symbol dạng của sàn hoặc dạng chuẩn ("BTC/USDT"), không phân biệt hoa thường,
+    // exchange = None lấy feed đầu tiên khớp
+    pub fn feed(&self, symbol: &str, exchange: Option<&str>) -> Option<&Arc<dyn OrderbookFeed>> {
+        self.feeds
+            .iter()
+            .find(|f| feed_matches(f.as_ref(), symbol) && exchange.is_none_or(|e| f.exchange() == e))
+    }
+
+    // symbols rỗng = mọi feed
+    pub fn select(&self, symbols: &[String], exchange: Option<&str>) -> Vec<Arc<dyn OrderbookFeed>> {
+        self.feeds
+            .iter()
+            .filter(|f| symbols.is_empty() || symbols.iter().any(|s| feed_matches(f.as_ref(), s)))
+            .filter(|f| exchange.is_none_or(|e| f.exchange() == e))
+            .cloned()
+            .collect()
+    }
+
+    pub fn trades_sender(&self) -> broadcast::Sender<Trade> {
+        self.trades_tx.clone()
+    }
+
+    pub fn publish_trade(&self, trade: Trade) {
+        if self.events.has_subscribers() {
+            self.events.publish(MarketEvent::Trade(trade.clone()));
+        }
+        let _ = self.trades_tx.send(trade);
+    }
+
+    pub fn subscribe_trades(&self) -> broadcast::Receiver<Trade> {
+        self.trades_tx.subscribe()
+    }
+
+    pub fn subscribe_signals(&self) -> broadcast::Receiver<SignalOutput> {
+        self.signals_tx.subscribe()
+    }
+
+    // bus `MarketEvent` chung, depth / book ticker của feed chỉ có sau `attach_feeds`
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+
+    pub fn attach_feeds(&self) -> Vec<(String, tokio::task::JoinHandle<()>)> {
+        self.feeds
+            .iter()
+            .flat_map(|feed| {
+                let name = format!("event bus {}:{}", feed.exchange(), feed.symbol());
+                self.events.attach_feed(feed.clone()).into_iter().map(move |h| (name.clone(), h))
+            })
+            .collect()
+    }
+}
+
+fn feed_matches(feed: &dyn OrderbookFeed, symbol: &str) -> bool {
+    feed.symbol().eq_ignore_ascii_case(symbol) || feed.instrument().is_some_and(|i| i.matches(symbol))
+}
+
+// Chuyển trade từ một stream vào hub cho tới khi stream đóng
+pub fn forward_trades(mut rx: broadcast::Receiver<Trade>, hub: MarketHub) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            match rx.recv().await {
+                Ok(trade) => hub.publish_trade(trade),
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    })
+}
@@ -1,2 +1,37 @@
+pub mod alerts;
+pub mod bus;
+pub mod cli;
+pub mod config;
 pub mod ws;
-pub mod core;
\ No newline at end of file
+pub mod core;
+pub mod db;
+pub mod dex;
+pub mod execution;
+pub mod fees;
+pub mod grpc;
+pub mod hub;
+pub mod logfile;
+pub mod net;
+pub mod oms;
+pub mod portfolio;
+pub mod recorder;
+pub mod replay;
+pub mod backtest;
+pub mod risk;
+pub mod rest;
+pub mod rollover;
+pub mod runtime;
+pub mod schedule;
+pub mod server;
+pub mod sim;
+pub mod sink;
+pub mod strategy;
+pub mod supervisor;
+pub mod symbols;
+// Binance giả (REST + WS) cho test connector / OMS
+#[cfg(test)]
+pub mod testutil;
+pub mod threads;
+pub mod tui;
+pub mod venue;
+pub mod web;
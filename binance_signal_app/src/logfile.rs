@@ -0,0 +1,63 @@
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+};
+
+// Log ra file trong `--log-dir` thay cho stdout, rollover cuối ngày mở file mới.
+// Tên file theo lúc mở: bsa-20241005T000000.log
+struct LogDir {
+    dir: PathBuf,
+    current: Mutex<(PathBuf, File)>,
+}
+
+static LOG: OnceLock<LogDir> = OnceLock::new();
+
+fn open(dir: &Path) -> io::Result<(PathBuf, File)> {
+    let stem = format!("bsa-{}", chrono::Utc::now().format("%Y%m%dT%H%M%S"));
+    let mut path = dir.join(format!("{}.log", stem));
+    let mut n = 1;
+    while path.exists() {
+        path = dir.join(format!("{}-{}.log", stem, n));
+        n += 1;
+    }
+    let file = File::options().create(true).append(true).open(&path)?;
+    Ok((path, file))
+}
+
+// writer cho `tracing_subscriber::fmt().with_writer(..)`, gọi một lần lúc khởi động
+pub fn init(dir: &Path) -> io::Result<fn() -> LogWriter> {
+    fs::create_dir_all(dir)?;
+    let current = open(dir)?;
+    let _ = LOG.set(LogDir { dir: dir.to_path_buf(), current: Mutex::new(current) });
+    Ok(|| LogWriter)
+}
+
+// Mở file mới, trả về file vừa đóng. None nếu log không ra file
+pub fn rotate() -> io::Result<Option<PathBuf>> {
+    let Some(log) = LOG.get() else { return Ok(None) };
+    let next = open(&log.dir)?;
+    let mut current = log.current.lock().unwrap_or_else(|e| e.into_inner());
+    current.1.flush()?;
+    let (old, _) = std::mem::replace(&mut *current, next);
+    Ok(Some(old))
+}
+
+pub struct LogWriter;
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match LOG.get() {
+            Some(log) => log.current.lock().unwrap_or_else(|e| e.into_inner()).1.write(buf),
+            None => io::stdout().write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match LOG.get() {
+            Some(log) => log.current.lock().unwrap_or_else(|e| e.into_inner()).1.flush(),
+            None => io::stdout().flush(),
+        }
+    }
+}
@@ -0,0 +1,525 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::{collections::HashMap, fmt};
+
+use crate::core::{
+    order::{Fill, OrderId, OrderSide, OrderStatus, OrderType},
+    orderbook::{from_f64, OrderbookSnapshot},
+    position::Position,
+    queue::{level_qty, QueuePosition},
+    signal::MarketData,
+    trade::{Trade, TradeSide},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OmsError {
+    UnknownOrder(String),
+    InvalidTransition { from: OrderStatus, to: OrderStatus },
+}
+
+impl fmt::Display for OmsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OmsError::UnknownOrder(id) => write!(f, "unknown order {}", id),
+            OmsError::InvalidTransition { from, to } => write!(f, "invalid transition {:?} -> {:?}", from, to),
+        }
+    }
+}
+
+impl std::error::Error for OmsError {}
+
+// Chuyển trạng thái hợp lệ:
+// PENDING_NEW -> NEW -> PARTIALLY_FILLED -> FILLED / CANCELED / REJECTED / EXPIRED
+// PENDING_CANCEL có thể quay lại NEW/PARTIALLY_FILLED khi cancel bị từ chối
+pub fn can_transition(from: OrderStatus, to: OrderStatus) -> bool {
+    use OrderStatus::*;
+    match from {
+        PendingNew => to != PendingNew && to != PendingCancel,
+        New => matches!(to, PartiallyFilled | Filled | PendingCancel | Canceled | Expired),
+        PartiallyFilled => matches!(to, PartiallyFilled | Filled | PendingCancel | Canceled | Expired),
+        PendingCancel => matches!(to, New | PartiallyFilled | Filled | Canceled | Expired),
+        Filled | Canceled | Rejected | Expired => false,
+    }
+}
+
+// Event trạng thái order từ sàn (user data stream, REST query), không phụ thuộc venue
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderUpdate {
+    pub symbol: String,
+    pub order_id: OrderId,
+    pub client_order_id: String,
+    pub side: OrderSide,
+    pub order_type: OrderType,
+    pub price: Decimal,
+    pub qty: Decimal,
+    pub status: OrderStatus,
+    pub last_filled_qty: Decimal,
+    pub last_filled_price: Decimal,
+    pub cum_filled_qty: Decimal,
+    // quy về quote asset nếu biết, không thì để nguyên số phí sàn trả về
+    pub commission: Decimal,
+    pub is_maker: bool,
+    pub reject_reason: Option<String>,
+    pub event_time: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManagedOrder {
+    pub client_order_id: String,
+    pub order_id: Option<OrderId>,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub order_type: OrderType,
+    pub price: Option<Decimal>,
+    pub qty: Decimal,
+    pub filled_qty: Decimal,
+    pub avg_fill_price: Decimal,
+    pub status: OrderStatus,
+    // status trước khi gửi cancel, để khôi phục nếu cancel bị từ chối
+    pub(crate) prev_status: Option<OrderStatus>,
+    pub reject_reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    // ước lượng từ market data, None tới khi có book sau khi sàn nhận order
+    pub queue: Option<QueuePosition>,
+}
+
+impl ManagedOrder {
+    pub fn remaining(&self) -> Decimal {
+        self.qty - self.filled_qty
+    }
+
+    pub fn is_open(&self) -> bool {
+        !self.status.is_final()
+    }
+
+    // limit đã nằm trên book của sàn
+    pub fn is_resting(&self) -> bool {
+        self.order_type == OrderType::Limit && matches!(self.status, OrderStatus::New | OrderStatus::PartiallyFilled)
+    }
+
+    pub fn time_to_fill(&self) -> Option<chrono::Duration> {
+        self.queue.as_ref()?.time_to_fill(self.remaining())
+    }
+
+    fn set_status(&mut self, to: OrderStatus) -> Result<(), OmsError> {
+        if self.status == to {
+            return Ok(());
+        }
+        if !can_transition(self.status, to) {
+            return Err(OmsError::InvalidTransition { from: self.status, to });
+        }
+        self.status = to;
+        Ok(())
+    }
+
+    fn add_fill(&mut self, qty: Decimal, price: Decimal) {
+        let total = self.filled_qty + qty;
+        if !total.is_zero() {
+            self.avg_fill_price = (self.avg_fill_price * self.filled_qty + price * qty) / total;
+        }
+        self.filled_qty = total;
+    }
+}
+
+// Theo dõi order local theo clientOrderId và đối soát với event từ sàn
+#[derive(Debug)]
+pub struct Oms {
+    prefix: String,
+    next_seq: u64,
+    orders: HashMap<String, ManagedOrder>,
+    by_order_id: HashMap<OrderId, String>,
+    positions: HashMap<String, Position>,
+}
+
+impl Oms {
+    // prefix phân biệt order của process này với order đặt tay / bot khác
+    pub fn new(prefix: &str) -> Self {
+        Self {
+            prefix: prefix.to_string(),
+            next_seq: 1,
+            orders: HashMap::new(),
+            by_order_id: HashMap::new(),
+            positions: HashMap::new(),
+        }
+    }
+
+    // Đăng ký order trước khi gửi lên sàn, trả về clientOrderId để gửi kèm
+    pub fn create_order(
+        &mut self,
+        symbol: &str,
+        side: OrderSide,
+        order_type: OrderType,
+        price: Option<Decimal>,
+        qty: Decimal,
+    ) -> String {
+        let client_order_id = format!("{}-{}-{}", self.prefix, Utc::now().timestamp_millis(), self.next_seq);
+        self.next_seq += 1;
+        let now = Utc::now();
+        self.orders.insert(
+            client_order_id.clone(),
+            ManagedOrder {
+                client_order_id: client_order_id.clone(),
+                order_id: None,
+                symbol: symbol.to_uppercase(),
+                side,
+                order_type,
+                price,
+                qty,
+                filled_qty: Decimal::ZERO,
+                avg_fill_price: Decimal::ZERO,
+                status: OrderStatus::PendingNew,
+                prev_status: None,
+                reject_reason: None,
+                created_at: now,
+                updated_at: now,
+                queue: None,
+            },
+        );
+        client_order_id
+    }
+
+    // REST ack: gán orderId của sàn
+    pub fn on_ack(&mut self, client_order_id: &str, order_id: OrderId) -> Result<(), OmsError> {
+        let order = self.get_mut(client_order_id)?;
+        order.order_id = Some(order_id);
+        if order.status == OrderStatus::PendingNew {
+            order.set_status(OrderStatus::New)?;
+        }
+        order.updated_at = Utc::now();
+        self.by_order_id.insert(order_id, client_order_id.to_string());
+        Ok(())
+    }
+
+    // request bị từ chối (lỗi REST, risk check, ...)
+    pub fn on_reject(&mut self, client_order_id: &str, reason: &str) -> Result<(), OmsError> {
+        let order = self.get_mut(client_order_id)?;
+        order.set_status(OrderStatus::Rejected)?;
+        order.reject_reason = Some(reason.to_string());
+        order.updated_at = Utc::now();
+        Ok(())
+    }
+
+    pub fn request_cancel(&mut self, client_order_id: &str) -> Result<(), OmsError> {
+        let order = self.get_mut(client_order_id)?;
+        let prev = order.status;
+        order.set_status(OrderStatus::PendingCancel)?;
+        order.prev_status = Some(prev);
+        order.updated_at = Utc::now();
+        Ok(())
+    }
+
+    pub fn on_cancel_rejected(&mut self, client_order_id: &str) -> Result<(), OmsError> {
+        let order = self.get_mut(client_order_id)?;
+        if order.status == OrderStatus::PendingCancel {
+            let restore = order.prev_status.take().unwrap_or(OrderStatus::New);
+            order.set_status(restore)?;
+            order.updated_at = Utc::now();
+        }
+        Ok(())
+    }
+
+    // Áp dụng event từ sàn. Trả về fill mới (nếu có) sau khi đã cập nhật position.
+    // - order không có trong OMS (đặt từ nơi khác) được nhận vào
+    // - event trùng / đến muộn sau trạng thái cuối bị bỏ qua
+    pub fn apply_update(&mut self, update: &OrderUpdate) -> Result<Option<Fill>, OmsError> {
+        let client_order_id = self
+            .by_order_id
+            .get(&update.order_id)
+            .cloned()
+            .unwrap_or_else(|| update.client_order_id.clone());
+
+        if !self.orders.contains_key(&client_order_id) {
+            self.adopt(&client_order_id, update);
+        }
+        self.by_order_id.insert(update.order_id, client_order_id.clone());
+
+        let order = self.get_mut(&client_order_id)?;
+        order.order_id = Some(update.order_id);
+        if order.status.is_final() {
+            return Ok(None);
+        }
+        if update.status == OrderStatus::Rejected {
+            order.reject_reason = update.reject_reason.clone();
+        }
+        order.set_status(update.status)?;
+        order.prev_status = None;
+        order.updated_at = update.event_time;
+
+        // chỉ tính fill khi cum qty tăng, event lặp lại không bị cộng hai lần
+        let new_qty = update.cum_filled_qty - order.filled_qty;
+        if new_qty <= Decimal::ZERO || update.last_filled_qty.is_zero() {
+            return Ok(None);
+        }
+        let qty = new_qty.min(update.last_filled_qty);
+        order.add_fill(qty, update.last_filled_price);
+
+        let fill = Fill {
+            order_id: update.order_id,
+            symbol: order.symbol.clone(),
+            side: order.side,
+            price: update.last_filled_price,
+            qty,
+            fee: update.commission,
+            is_maker: update.is_maker,
+            timestamp: update.event_time,
+        };
+        self.positions.entry(fill.symbol.clone()).or_default().apply_fill(&fill);
+        Ok(Some(fill))
+    }
+
+    pub fn order(&self, client_order_id: &str) -> Option<&ManagedOrder> {
+        self.orders.get(client_order_id)
+    }
+
+    pub fn order_by_id(&self, order_id: OrderId) -> Option<&ManagedOrder> {
+        self.by_order_id.get(&order_id).and_then(|id| self.orders.get(id))
+    }
+
+    pub fn open_orders(&self, symbol: &str) -> Vec<&ManagedOrder> {
+        let symbol = symbol.to_uppercase();
+        let mut orders: Vec<_> = self
+            .orders
+            .values()
+            .filter(|o| o.symbol == symbol && o.is_open())
+            .collect();
+        orders.sort_by_key(|o| o.created_at);
+        orders
+    }
+
+    // symbol đang có order mở, sắp xếp để gọi cancel theo thứ tự ổn định
+    pub fn open_symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<String> = self
+            .orders
+            .values()
+            .filter(|o| o.is_open())
+            .map(|o| o.symbol.clone())
+            .collect();
+        symbols.sort();
+        symbols.dedup();
+        symbols
+    }
+
+    pub fn position(&self, symbol: &str) -> Position {
+        self.positions.get(&symbol.to_uppercase()).cloned().unwrap_or_default()
+    }
+
+    pub fn positions(&self) -> &HashMap<String, Position> {
+        &self.positions
+    }
+
+    pub fn orders(&self) -> impl Iterator<Item = &ManagedOrder> {
+        self.orders.values()
+    }
+
+    // Khôi phục sau restart: order đã lưu và position dựng lại từ toàn bộ fill
+    pub fn restore(&mut self, orders: Vec<ManagedOrder>, fills: &[Fill]) {
+        for order in orders {
+            if let Some(id) = order.order_id {
+                self.by_order_id.insert(id, order.client_order_id.clone());
+            }
+            self.orders.insert(order.client_order_id.clone(), order);
+        }
+        self.positions.clear();
+        for fill in fills {
+            self.positions.entry(fill.symbol.to_uppercase()).or_default().apply_fill(fill);
+        }
+    }
+
+    // Ước lượng queue của limit đang chờ. Level trên book đã gồm phần còn lại của
+    // order mình nên trừ ra trước khi so
+    pub fn on_market_data(&mut self, data: &MarketData) {
+        match data {
+            MarketData::Orderbook { symbol, snap } => self.on_orderbook(symbol, snap),
+            MarketData::Trade(trade) => self.on_trade(trade),
+            MarketData::Perp(_) | MarketData::Liquidation(_) => {}
+        }
+    }
+
+    pub fn on_orderbook(&mut self, symbol: &str, snap: &OrderbookSnapshot) {
+        let symbol = symbol.to_uppercase();
+        for order in self.orders.values_mut().filter(|o| o.symbol == symbol && o.is_resting()) {
+            let Some(price) = order.price else { continue };
+            let others = (level_qty(snap, order.side, price) - order.remaining()).max(Decimal::ZERO);
+            match &mut order.queue {
+                Some(queue) => queue.on_level(others, snap.timestamp),
+                None => order.queue = Some(QueuePosition::new(others, snap.timestamp)),
+            }
+        }
+    }
+
+    pub fn on_trade(&mut self, trade: &Trade) {
+        let (symbol, Some(trade_price), Some(qty)) = (trade.symbol.to_uppercase(), from_f64(trade.price), from_f64(trade.qty)) else {
+            return;
+        };
+        // sell aggressor ăn bid, buy aggressor ăn ask
+        let passive_side = match trade.side {
+            TradeSide::Sell => OrderSide::Buy,
+            TradeSide::Buy => OrderSide::Sell,
+        };
+        for order in self.orders.values_mut().filter(|o| o.symbol == symbol && o.side == passive_side && o.is_resting()) {
+            let (Some(price), Some(queue)) = (order.price, order.queue.as_mut()) else { continue };
+            let through = match order.side {
+                OrderSide::Buy => trade_price < price,
+                OrderSide::Sell => trade_price > price,
+            };
+            if trade_price == price {
+                queue.on_trade(qty, trade.timestamp);
+            } else if through {
+                queue.clear(trade.timestamp);
+            }
+        }
+    }
+
+    // bỏ order đã kết thúc khỏi bộ nhớ, giữ lại position
+    pub fn prune_closed(&mut self) {
+        self.orders.retain(|_, o| o.is_open());
+        let orders = &self.orders;
+        self.by_order_id.retain(|_, id| orders.contains_key(id));
+    }
+
+    fn adopt(&mut self, client_order_id: &str, update: &OrderUpdate) {
+        self.orders.insert(
+            client_order_id.to_string(),
+            ManagedOrder {
+                client_order_id: client_order_id.to_string(),
+                order_id: Some(update.order_id),
+                symbol: update.symbol.to_uppercase(),
+                side: update.side,
+                order_type: update.order_type,
+                price: (!update.price.is_zero()).then_some(update.price),
+                qty: update.qty,
+                filled_qty: Decimal::ZERO,
+                avg_fill_price: Decimal::ZERO,
+                status: OrderStatus::PendingNew,
+                prev_status: None,
+                reject_reason: None,
+                created_at: update.event_time,
+                updated_at: update.event_time,
+                queue: None,
+            },
+        );
+    }
+
+    fn get_mut(&mut self, client_order_id: &str) -> Result<&mut ManagedOrder, OmsError> {
+        self.orders
+            .get_mut(client_order_id)
+            .ok_or_else(|| OmsError::UnknownOrder(client_order_id.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn update(client_id: &str, status: OrderStatus, last: Decimal, cum: Decimal) -> OrderUpdate {
+        OrderUpdate {
+            symbol: "BTCUSDT".into(),
+            order_id: 42,
+            client_order_id: client_id.into(),
+            side: OrderSide::Buy,
+            order_type: OrderType::Limit,
+            price: dec!(100),
+            qty: dec!(3),
+            status,
+            last_filled_qty: last,
+            last_filled_price: dec!(100),
+            cum_filled_qty: cum,
+            commission: dec!(0.01),
+            is_maker: true,
+            reject_reason: None,
+            event_time: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_lifecycle_and_position() {
+        let mut oms = Oms::new("test");
+        let id = oms.create_order("btcusdt", OrderSide::Buy, OrderType::Limit, Some(dec!(100)), dec!(3));
+        assert_eq!(oms.order(&id).unwrap().status, OrderStatus::PendingNew);
+        oms.on_ack(&id, 42).unwrap();
+
+        let fill = oms.apply_update(&update(&id, OrderStatus::PartiallyFilled, dec!(1), dec!(1))).unwrap();
+        assert_eq!(fill.unwrap().qty, dec!(1));
+        // event lặp lại không cộng thêm fill
+        assert!(oms.apply_update(&update(&id, OrderStatus::PartiallyFilled, dec!(1), dec!(1))).unwrap().is_none());
+        assert_eq!(oms.open_orders("BTCUSDT").len(), 1);
+
+        oms.apply_update(&update(&id, OrderStatus::Filled, dec!(2), dec!(3))).unwrap();
+        let order = oms.order_by_id(42).unwrap();
+        assert_eq!((order.status, order.filled_qty, order.avg_fill_price), (OrderStatus::Filled, dec!(3), dec!(100)));
+        assert!(oms.open_orders("BTCUSDT").is_empty());
+        assert_eq!(oms.position("btcusdt").qty, dec!(3));
+
+        // event NEW đến muộn sau FILLED bị bỏ qua
+        assert!(oms.apply_update(&update(&id, OrderStatus::New, dec!(0), dec!(0))).unwrap().is_none());
+        assert_eq!(oms.order(&id).unwrap().status, OrderStatus::Filled);
+    }
+
+    #[test]
+    fn test_cancel_flow_and_invalid_transition() {
+        let mut oms = Oms::new("test");
+        let id = oms.create_order("btcusdt", OrderSide::Sell, OrderType::Limit, Some(dec!(105)), dec!(1));
+        assert_eq!(
+            oms.request_cancel(&id),
+            Err(OmsError::InvalidTransition { from: OrderStatus::PendingNew, to: OrderStatus::PendingCancel })
+        );
+
+        oms.on_ack(&id, 7).unwrap();
+        oms.request_cancel(&id).unwrap();
+        oms.on_cancel_rejected(&id).unwrap();
+        assert_eq!(oms.order(&id).unwrap().status, OrderStatus::New);
+
+        oms.request_cancel(&id).unwrap();
+        let mut canceled = update(&id, OrderStatus::Canceled, dec!(0), dec!(0));
+        canceled.order_id = 7;
+        oms.apply_update(&canceled).unwrap();
+        assert_eq!(oms.order(&id).unwrap().status, OrderStatus::Canceled);
+
+        oms.prune_closed();
+        assert!(oms.order(&id).is_none());
+        assert_eq!(oms.on_ack("missing", 1), Err(OmsError::UnknownOrder("missing".into())));
+    }
+
+    #[test]
+    fn test_adopts_external_order() {
+        let mut oms = Oms::new("test");
+        let fill = oms.apply_update(&update("web_abc", OrderStatus::PartiallyFilled, dec!(0.5), dec!(0.5))).unwrap();
+        assert!(fill.is_some());
+        assert_eq!(oms.open_orders("BTCUSDT")[0].client_order_id, "web_abc");
+        assert_eq!(oms.position("BTCUSDT").qty, dec!(0.5));
+    }
+
+    #[test]
+    fn test_queue_estimate_from_market_data() {
+        use crate::core::orderbook::Side;
+        let t0 = Utc::now();
+        let snap = |qty, secs| {
+            let mut ob = OrderbookSnapshot::new();
+            ob.set_level(Side::Bid, dec!(100), qty);
+            ob.timestamp = t0 + chrono::Duration::seconds(secs);
+            ob
+        };
+        let trade = |price, qty| Trade { symbol: "BTCUSDT".into(), trade_id: 1, price, qty, side: TradeSide::Sell, timestamp: t0 + chrono::Duration::seconds(1) };
+
+        let mut oms = Oms::new("test");
+        let id = oms.create_order("btcusdt", OrderSide::Buy, OrderType::Limit, Some(dec!(100)), dec!(2));
+        // chưa ack -> chưa ước lượng
+        oms.on_orderbook("btcusdt", &snap(dec!(5), 0));
+        assert_eq!(oms.order(&id).unwrap().queue, None);
+
+        // level 7 gồm cả 2 của mình
+        oms.on_ack(&id, 42).unwrap();
+        oms.on_orderbook("btcusdt", &snap(dec!(7), 0));
+        oms.on_trade(&trade(100.0, 3.0));
+        oms.on_orderbook("btcusdt", &snap(dec!(4), 1));
+        let order = oms.order(&id).unwrap();
+        assert_eq!(order.queue.as_ref().unwrap().ahead, dec!(2));
+        // 3 qty/s, còn 2 phía trước + 2 của mình
+        assert_eq!(order.time_to_fill(), Some(chrono::Duration::milliseconds(1333)));
+
+        oms.on_trade(&trade(99.5, 0.1));
+        assert_eq!(oms.order(&id).unwrap().queue.as_ref().unwrap().ahead, Decimal::ZERO);
+    }
+}
@@ -0,0 +1,566 @@
+use chrono::{DateTime, Utc};
+use futures_util::future::select_all;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+    sync::{Arc, RwLock},
+};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::core::{backpressure::LosslessReceiver, order::Fill, orderbook::OrderbookSnapshot, position::Position, signal::MarketData};
+use crate::fees::FeeSchedule;
+use crate::oms::Oms;
+use crate::supervisor::Shutdown;
+use crate::ws::OrderbookFeed;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PositionPnl {
+    pub symbol: String,
+    pub qty: Decimal,
+    pub avg_price: Decimal,
+    // mid gần nhất, None nếu chưa có book
+    pub mark: Option<Decimal>,
+    pub realized_pnl: Decimal,
+    pub fees: Decimal,
+    pub unrealized_pnl: Decimal,
+    // realized - fees + unrealized
+    pub total_pnl: Decimal,
+    pub notional: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PortfolioSnapshot {
+    pub timestamp: DateTime<Utc>,
+    pub positions: Vec<PositionPnl>,
+    pub realized_pnl: Decimal,
+    pub fees: Decimal,
+    pub unrealized_pnl: Decimal,
+    pub total_pnl: Decimal,
+    // tổng |notional| theo giá mark
+    pub gross_exposure: Decimal,
+}
+
+// Báo cáo PnL theo strategy / symbol / signal định kỳ:
+//
+//   [attribution]
+//   report_secs = 300
+//   report_dir = "data/reports"
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct AttributionSettings {
+    // 0 = chỉ báo cáo lúc dừng
+    pub report_secs: u64,
+    // mỗi lần một dòng trong attribution-YYYY-MM-DD.jsonl, None = chỉ log
+    pub report_dir: Option<PathBuf>,
+}
+
+impl Default for AttributionSettings {
+    fn default() -> Self {
+        Self { report_secs: 300, report_dir: None }
+    }
+}
+
+// giữ tag của chừng này order gần nhất, market maker requote liên tục
+const MAX_ORDER_TAGS: usize = 10_000;
+pub const UNTAGGED: &str = "untagged";
+
+// Strategy đặt order và signal khiến nó đặt
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct AttributionTag {
+    pub strategy: String,
+    pub trigger: String,
+}
+
+impl AttributionTag {
+    pub fn new(strategy: &str, trigger: &str) -> Self {
+        Self { strategy: strategy.to_string(), trigger: trigger.to_string() }
+    }
+
+    fn untagged() -> Self {
+        Self::new(UNTAGGED, UNTAGGED)
+    }
+}
+
+// position riêng cho mỗi (tag, symbol) để realized tính đúng theo lô của nó
+#[derive(Debug, Clone, Default)]
+struct Bucket {
+    position: Position,
+    fills: u64,
+    volume: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttributionRow {
+    pub strategy: String,
+    pub trigger: String,
+    pub symbol: String,
+    pub fills: u64,
+    // tổng notional đã khớp
+    pub volume: Decimal,
+    pub qty: Decimal,
+    pub realized_pnl: Decimal,
+    pub fees: Decimal,
+    pub unrealized_pnl: Decimal,
+    pub total_pnl: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttributionReport {
+    pub timestamp: DateTime<Utc>,
+    pub rows: Vec<AttributionRow>,
+    // total_pnl cộng theo từng chiều
+    pub by_strategy: BTreeMap<String, Decimal>,
+    pub by_trigger: BTreeMap<String, Decimal>,
+    pub by_symbol: BTreeMap<String, Decimal>,
+}
+
+// Position theo từng symbol từ fill, mark-to-market theo mid của book.
+// Symbol quy về chữ hoa để khớp giữa feed (btcusdt) và fill (BTCUSDT)
+#[derive(Debug, Clone, Default)]
+pub struct Portfolio {
+    positions: HashMap<String, Position>,
+    marks: HashMap<String, Decimal>,
+    // có thì phí tính lại theo biểu phí thay vì commission sàn trả về
+    // (Binance trừ phí bằng BNB / base asset, không phải quote)
+    fees: Option<FeeSchedule>,
+    // id order venue trả về -> tag, hàng đợi để bỏ tag cũ
+    tags: HashMap<String, AttributionTag>,
+    tag_queue: VecDeque<String>,
+    buckets: HashMap<(AttributionTag, String), Bucket>,
+}
+
+impl Portfolio {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_fees(fees: FeeSchedule) -> Self {
+        Self { fees: Some(fees), ..Self::default() }
+    }
+
+    // `order_ref` là id `place_order` trả về (paper: order id, live: client order id)
+    pub fn tag_order(&mut self, order_ref: &str, tag: AttributionTag) {
+        if self.tags.insert(order_ref.to_string(), tag).is_none() {
+            self.tag_queue.push_back(order_ref.to_string());
+        }
+        while self.tag_queue.len() > MAX_ORDER_TAGS {
+            if let Some(old) = self.tag_queue.pop_front() {
+                self.tags.remove(&old);
+            }
+        }
+    }
+
+    // fill không có tag (đặt tay, order trước khi restart) tính vào "untagged"
+    pub fn on_fill(&mut self, fill: &Fill) {
+        self.on_fill_ref(fill, &fill.order_id.to_string());
+    }
+
+    pub fn on_fill_ref(&mut self, fill: &Fill, order_ref: &str) {
+        let tag = self.tags.get(order_ref).cloned().unwrap_or_else(AttributionTag::untagged);
+        self.on_fill_tagged(fill, tag);
+    }
+
+    pub fn on_fill_tagged(&mut self, fill: &Fill, tag: AttributionTag) {
+        let fill = match self.fees {
+            Some(fees) => Fill { fee: fees.fee(fill.notional(), fill.is_maker), ..fill.clone() },
+            None => fill.clone(),
+        };
+        let symbol = fill.symbol.to_uppercase();
+        self.positions.entry(symbol.clone()).or_default().apply_fill(&fill);
+        let bucket = self.buckets.entry((tag, symbol)).or_default();
+        bucket.position.apply_fill(&fill);
+        bucket.fills += 1;
+        bucket.volume += fill.notional();
+    }
+
+    pub fn mark(&mut self, symbol: &str, price: Decimal) {
+        self.marks.insert(symbol.to_uppercase(), price);
+    }
+
+    pub fn on_orderbook(&mut self, symbol: &str, snap: &OrderbookSnapshot) {
+        if let Some(mid) = snap.mid_price() {
+            self.mark(symbol, mid);
+        }
+    }
+
+    pub fn on_market_data(&mut self, data: &MarketData) {
+        if let MarketData::Orderbook { symbol, snap } = data {
+            self.on_orderbook(symbol, snap);
+        }
+    }
+
+    pub fn position(&self, symbol: &str) -> Position {
+        self.positions.get(&symbol.to_uppercase()).cloned().unwrap_or_default()
+    }
+
+    pub fn mark_price(&self, symbol: &str) -> Option<Decimal> {
+        self.marks.get(&symbol.to_uppercase()).copied()
+    }
+
+    // chưa có giá mark thì coi như 0
+    pub fn unrealized_pnl(&self, symbol: &str) -> Decimal {
+        match (self.positions.get(&symbol.to_uppercase()), self.mark_price(symbol)) {
+            (Some(pos), Some(mark)) => pos.unrealized_pnl(mark),
+            _ => Decimal::ZERO,
+        }
+    }
+
+    pub fn total_pnl(&self) -> Decimal {
+        self.positions
+            .iter()
+            .map(|(symbol, pos)| pos.net_realized_pnl() + self.unrealized_pnl(symbol))
+            .sum()
+    }
+
+    // PnL theo (strategy, trigger, symbol), unrealized theo cùng giá mark với `snapshot`
+    pub fn attribution(&self) -> AttributionReport {
+        let mut rows: Vec<AttributionRow> = self
+            .buckets
+            .iter()
+            .map(|((tag, symbol), bucket)| {
+                let pos = &bucket.position;
+                let unrealized_pnl = self.mark_price(symbol).map(|m| pos.unrealized_pnl(m)).unwrap_or_default();
+                AttributionRow {
+                    strategy: tag.strategy.clone(),
+                    trigger: tag.trigger.clone(),
+                    symbol: symbol.clone(),
+                    fills: bucket.fills,
+                    volume: bucket.volume,
+                    qty: pos.qty,
+                    realized_pnl: pos.realized_pnl,
+                    fees: pos.fees,
+                    unrealized_pnl,
+                    total_pnl: pos.net_realized_pnl() + unrealized_pnl,
+                }
+            })
+            .collect();
+        rows.sort_by(|a, b| (&a.strategy, &a.trigger, &a.symbol).cmp(&(&b.strategy, &b.trigger, &b.symbol)));
+        let sum_by = |key: fn(&AttributionRow) -> &String| {
+            let mut totals = BTreeMap::new();
+            for row in &rows {
+                *totals.entry(key(row).clone()).or_insert(Decimal::ZERO) += row.total_pnl;
+            }
+            totals
+        };
+        AttributionReport {
+            timestamp: Utc::now(),
+            by_strategy: sum_by(|r| &r.strategy),
+            by_trigger: sum_by(|r| &r.trigger),
+            by_symbol: sum_by(|r| &r.symbol),
+            rows,
+        }
+    }
+
+    pub fn snapshot(&self) -> PortfolioSnapshot {
+        let mut positions: Vec<PositionPnl> = self
+            .positions
+            .iter()
+            .map(|(symbol, pos)| {
+                let mark = self.marks.get(symbol).copied();
+                let unrealized_pnl = self.unrealized_pnl(symbol);
+                PositionPnl {
+                    symbol: symbol.clone(),
+                    qty: pos.qty,
+                    avg_price: pos.avg_price,
+                    mark,
+                    realized_pnl: pos.realized_pnl,
+                    fees: pos.fees,
+                    unrealized_pnl,
+                    total_pnl: pos.net_realized_pnl() + unrealized_pnl,
+                    notional: pos.qty * mark.unwrap_or(pos.avg_price),
+                }
+            })
+            .collect();
+        positions.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+
+        PortfolioSnapshot {
+            timestamp: Utc::now(),
+            realized_pnl: positions.iter().map(|p| p.realized_pnl).sum(),
+            fees: positions.iter().map(|p| p.fees).sum(),
+            unrealized_pnl: positions.iter().map(|p| p.unrealized_pnl).sum(),
+            total_pnl: positions.iter().map(|p| p.total_pnl).sum(),
+            gross_exposure: positions.iter().map(|p| p.notional.abs()).sum(),
+            positions,
+        }
+    }
+}
+
+// Portfolio dùng chung giữa task cập nhật và strategy / API đọc
+#[derive(Debug, Clone, Default)]
+pub struct SharedPortfolio {
+    inner: Arc<RwLock<Portfolio>>,
+    // venue live: fill mang order id của sàn, tra OMS ra client order id đã tag
+    oms: Option<Arc<Mutex<Oms>>>,
+    // fill từ venue của account khác: order id có thể trùng account chính, tag theo "account:ref"
+    account: Option<String>,
+}
+
+impl SharedPortfolio {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_fees(fees: FeeSchedule) -> Self {
+        Self { inner: Arc::new(RwLock::new(Portfolio::with_fees(fees))), oms: None, account: None }
+    }
+
+    pub fn with_oms(mut self, oms: Option<Arc<Mutex<Oms>>>) -> Self {
+        self.oms = oms;
+        self
+    }
+
+    pub fn for_account(mut self, account: &str) -> Self {
+        self.account = Some(account.to_string());
+        self
+    }
+
+    pub fn tag_order(&self, order_ref: &str, tag: AttributionTag) {
+        let key = self.scoped(order_ref);
+        self.update(|p| p.tag_order(&key, tag));
+    }
+
+    fn scoped(&self, order_ref: &str) -> String {
+        match &self.account {
+            Some(account) => format!("{}:{}", account, order_ref),
+            None => order_ref.to_string(),
+        }
+    }
+
+    pub fn attribution(&self) -> AttributionReport {
+        self.read(Portfolio::attribution)
+    }
+
+    async fn order_ref(&self, fill: &Fill) -> String {
+        if let Some(oms) = &self.oms
+            && let Some(order) = oms.lock().await.order_by_id(fill.order_id)
+        {
+            return self.scoped(&order.client_order_id);
+        }
+        self.scoped(&fill.order_id.to_string())
+    }
+
+    pub fn read<R>(&self, f: impl FnOnce(&Portfolio) -> R) -> R {
+        f(&self.inner.read().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    pub fn update<R>(&self, f: impl FnOnce(&mut Portfolio) -> R) -> R {
+        f(&mut self.inner.write().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    pub fn position(&self, symbol: &str) -> Position {
+        self.read(|p| p.position(symbol))
+    }
+
+    pub fn snapshot(&self) -> PortfolioSnapshot {
+        self.read(Portfolio::snapshot)
+    }
+
+    // Log từng dòng (target "attribution") và ghi thêm vào file jsonl trong ngày
+    pub fn report_attribution(&self, settings: &AttributionSettings) -> AttributionReport {
+        let report = self.attribution();
+        for row in &report.rows {
+            info!(
+                target: "attribution",
+                strategy = %row.strategy,
+                trigger = %row.trigger,
+                symbol = %row.symbol,
+                fills = row.fills,
+                volume = %row.volume,
+                realized = %row.realized_pnl,
+                fees = %row.fees,
+                unrealized = %row.unrealized_pnl,
+                total_pnl = %row.total_pnl,
+                "pnl attribution"
+            );
+        }
+        if let Some(dir) = &settings.report_dir {
+            let path = dir.join(format!("attribution-{}.jsonl", report.timestamp.format("%Y-%m-%d")));
+            let written = fs::create_dir_all(dir)
+                .and_then(|_| serde_json::to_string(&report).map_err(io::Error::other))
+                .and_then(|line| {
+                    let mut file = fs::File::options().create(true).append(true).open(&path)?;
+                    writeln!(file, "{}", line)
+                });
+            if let Err(e) = written {
+                warn!(error = %e, path = %path.display(), "write attribution report failed");
+            }
+        }
+        report
+    }
+
+    // báo cáo mỗi `report_secs` và một lần cuối lúc shutdown
+    pub async fn run_attribution(self, settings: AttributionSettings, mut shutdown: Shutdown) {
+        let every = std::time::Duration::from_secs(settings.report_secs);
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(every), if settings.report_secs > 0 => {
+                    self.report_attribution(&settings);
+                }
+                _ = shutdown.wait() => break,
+            }
+        }
+        self.report_attribution(&settings);
+    }
+
+    // Cập nhật từ fill của venue và mark theo các feed cho tới khi fill stream đóng
+    pub async fn run(self, mut fills: LosslessReceiver<Fill>, feeds: Vec<Arc<dyn OrderbookFeed>>) {
+        let mut watches: Vec<_> = feeds.iter().map(|feed| feed.watch()).collect();
+        for feed in &feeds {
+            self.update(|p| p.on_orderbook(feed.symbol(), &feed.snapshot()));
+        }
+        loop {
+            let changed = async {
+                if watches.is_empty() {
+                    return std::future::pending().await;
+                }
+                let (res, idx, _) = select_all(watches.iter_mut().map(|rx| Box::pin(rx.changed()))).await;
+                res.ok().map(|_| idx)
+            };
+            tokio::select! {
+                fill = fills.recv() => match fill {
+                    Some(fill) => {
+                        let order_ref = self.order_ref(&fill).await;
+                        let (pos, total) = self.update(|p| {
+                            p.on_fill_ref(&fill, &order_ref);
+                            (p.position(&fill.symbol), p.total_pnl())
+                        });
+                        info!(
+                            symbol = %fill.symbol,
+                            side = ?fill.side,
+                            qty = %fill.qty,
+                            price = %fill.price,
+                            position = %pos.qty,
+                            total_pnl = %total,
+                            "fill"
+                        );
+                    }
+                    None => return,
+                },
+                idx = changed => match idx {
+                    Some(idx) => {
+                        let feed = &feeds[idx];
+                        self.update(|p| p.on_orderbook(feed.symbol(), &feed.snapshot()));
+                    }
+                    // feed đã dừng, chỉ còn cập nhật từ fill
+                    None => watches.clear(),
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{backpressure::LosslessBus, order::OrderSide, orderbook::Side};
+    use rust_decimal_macros::dec;
+
+    fn fill(side: OrderSide, price: Decimal, qty: Decimal) -> Fill {
+        Fill {
+            order_id: 1,
+            symbol: "BTCUSDT".into(),
+            side,
+            price,
+            qty,
+            fee: dec!(0.1),
+            is_maker: true,
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_mark_to_market() {
+        let mut portfolio = Portfolio::new();
+        portfolio.on_fill(&fill(OrderSide::Buy, dec!(100), dec!(2)));
+        // chưa có mark
+        assert_eq!(portfolio.unrealized_pnl("btcusdt"), Decimal::ZERO);
+
+        let mut ob = OrderbookSnapshot::new();
+        ob.set_level(Side::Bid, dec!(104), dec!(1));
+        ob.set_level(Side::Ask, dec!(106), dec!(1));
+        portfolio.on_orderbook("btcusdt", &ob);
+        assert_eq!(portfolio.unrealized_pnl("BTCUSDT"), dec!(10));
+
+        portfolio.on_fill(&fill(OrderSide::Sell, dec!(103), dec!(1)));
+        let snap = portfolio.snapshot();
+        let pos = &snap.positions[0];
+        assert_eq!((pos.qty, pos.avg_price, pos.mark), (dec!(1), dec!(100), Some(dec!(105))));
+        assert_eq!((pos.realized_pnl, pos.unrealized_pnl, pos.fees), (dec!(3), dec!(5), dec!(0.2)));
+        assert_eq!((snap.total_pnl, snap.gross_exposure), (dec!(7.8), dec!(105)));
+
+        // phí tính lại theo biểu phí, bỏ qua commission của fill
+        let mut portfolio = Portfolio::with_fees(FeeSchedule::new(dec!(2), dec!(5)));
+        portfolio.on_fill(&fill(OrderSide::Buy, dec!(100), dec!(2)));
+        portfolio.on_fill(&Fill { is_maker: false, ..fill(OrderSide::Sell, dec!(100), dec!(2)) });
+        assert_eq!(portfolio.position("BTCUSDT").fees, dec!(0.14));
+    }
+
+    #[test]
+    fn test_attribution_by_tag() {
+        let mut portfolio = Portfolio::new();
+        let (fv, micro) = (AttributionTag::new("market_maker", "fair_value"), AttributionTag::new("market_maker", "microprice"));
+        portfolio.tag_order("1", fv.clone());
+        portfolio.tag_order("2", micro);
+        portfolio.on_fill(&fill(OrderSide::Buy, dec!(100), dec!(1)));
+        portfolio.on_fill(&Fill { order_id: 2, ..fill(OrderSide::Sell, dec!(103), dec!(1)) });
+        // order không tag
+        portfolio.on_fill(&Fill { order_id: 9, symbol: "ethusdt".into(), ..fill(OrderSide::Buy, dec!(10), dec!(1)) });
+        portfolio.mark("BTCUSDT", dec!(102));
+        portfolio.mark("ETHUSDT", dec!(11));
+
+        let report = portfolio.attribution();
+        assert_eq!(report.rows.len(), 3);
+        let row = |trigger: &str| report.rows.iter().find(|r| r.trigger == trigger).unwrap();
+        // mỗi tag giữ position riêng: mua 100 mark 102, bán 103 mark 102
+        assert_eq!((row("fair_value").qty, row("fair_value").total_pnl), (dec!(1), dec!(1.9)));
+        assert_eq!((row("microprice").qty, row("microprice").total_pnl), (dec!(-1), dec!(0.9)));
+        assert_eq!((row(UNTAGGED).symbol.as_str(), row(UNTAGGED).volume), ("ETHUSDT", dec!(10)));
+        assert_eq!(report.by_strategy["market_maker"], dec!(2.8));
+        assert_eq!(report.by_symbol["ETHUSDT"], dec!(0.9));
+        // tổng theo tag khớp tổng portfolio
+        assert_eq!(report.by_trigger.values().copied().sum::<Decimal>(), portfolio.total_pnl());
+
+        portfolio.on_fill_tagged(&fill(OrderSide::Sell, dec!(102), dec!(1)), fv);
+        assert_eq!(portfolio.attribution().rows[0].qty, Decimal::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_shared_portfolio_follows_fills() {
+        let bus = LosslessBus::new(16);
+        let portfolio = SharedPortfolio::new();
+        let task = tokio::spawn(portfolio.clone().run(bus.subscribe(), Vec::new()));
+
+        bus.send(fill(OrderSide::Sell, dec!(100), dec!(0.5)));
+        drop(bus);
+        task.await.unwrap();
+        assert_eq!(portfolio.position("btcusdt").qty, dec!(-0.5));
+        assert_eq!(portfolio.snapshot().fees, dec!(0.1));
+    }
+
+    #[tokio::test]
+    async fn test_account_scope_keeps_tags_apart() {
+        let (main_bus, hedge_bus) = (LosslessBus::new(16), LosslessBus::new(16));
+        let portfolio = SharedPortfolio::new();
+        let hedge = portfolio.clone().for_account("sub1");
+        // paper venue mỗi account đếm id từ 1
+        portfolio.tag_order("1", AttributionTag::new("market_maker", "fair_value"));
+        hedge.tag_order("1", AttributionTag::new("hedger", "inventory"));
+        let tasks = [tokio::spawn(portfolio.clone().run(main_bus.subscribe(), Vec::new())), tokio::spawn(hedge.run(hedge_bus.subscribe(), Vec::new()))];
+
+        main_bus.send(fill(OrderSide::Buy, dec!(100), dec!(1)));
+        hedge_bus.send(fill(OrderSide::Sell, dec!(100), dec!(1)));
+        drop((main_bus, hedge_bus));
+        for task in tasks {
+            task.await.unwrap();
+        }
+        let report = portfolio.attribution();
+        let row = |strategy: &str| report.rows.iter().find(|r| r.strategy == strategy).unwrap().qty;
+        assert_eq!((row("market_maker"), row("hedger")), (dec!(1), dec!(-1)));
+        assert_eq!(portfolio.position("btcusdt").qty, Decimal::ZERO);
+    }
+}
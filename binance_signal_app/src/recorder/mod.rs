@@ -0,0 +1,115 @@
+pub mod parquet;
+pub mod schema;
+pub mod state;
+pub mod tick;
+
+use chrono::{DateTime, Utc};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+use tokio::sync::watch;
+
+pub use crate::core::sampling::{SamplePolicy, Sampler, SamplingConfig};
+
+#[derive(Debug)]
+pub enum RecorderError {
+    Io(std::io::Error),
+    Arrow(arrow::error::ArrowError),
+    Parquet(::parquet::errors::ParquetError),
+    Json(serde_json::Error),
+    // file do bản mới hơn ghi, hoặc version không hợp lệ
+    UnsupportedSchema { version: u32 },
+}
+
+impl fmt::Display for RecorderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecorderError::Io(e) => write!(f, "io error: {}", e),
+            RecorderError::Arrow(e) => write!(f, "arrow error: {}", e),
+            RecorderError::Parquet(e) => write!(f, "parquet error: {}", e),
+            RecorderError::Json(e) => write!(f, "json error: {}", e),
+            RecorderError::UnsupportedSchema { version } => write!(
+                f,
+                "unsupported schema version {} (supported {}..={})",
+                version,
+                schema::LEGACY_VERSION,
+                schema::SCHEMA_VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RecorderError {}
+
+impl From<std::io::Error> for RecorderError {
+    fn from(e: std::io::Error) -> Self {
+        RecorderError::Io(e)
+    }
+}
+
+impl From<arrow::error::ArrowError> for RecorderError {
+    fn from(e: arrow::error::ArrowError) -> Self {
+        RecorderError::Arrow(e)
+    }
+}
+
+impl From<::parquet::errors::ParquetError> for RecorderError {
+    fn from(e: ::parquet::errors::ParquetError) -> Self {
+        RecorderError::Parquet(e)
+    }
+}
+
+impl From<serde_json::Error> for RecorderError {
+    fn from(e: serde_json::Error) -> Self {
+        RecorderError::Json(e)
+    }
+}
+
+// rollover yêu cầu cắt file từ lần check trước
+fn cut_requested(cut: &mut Option<watch::Receiver<u64>>) -> bool {
+    let Some(cut) = cut else { return false };
+    if cut.has_changed().unwrap_or(false) {
+        cut.borrow_and_update();
+        return true;
+    }
+    false
+}
+
+// Đầu giờ chứa `ts`, dùng làm key partition
+pub fn hour_start(ts: DateTime<Utc>) -> DateTime<Utc> {
+    let secs = ts.timestamp();
+    DateTime::from_timestamp(secs - secs.rem_euclid(3600), 0).unwrap_or(ts)
+}
+
+// Layout kiểu Hive để đọc thẳng bằng pyarrow/polars/duckdb:
+// {root}/{kind}/symbol=BTCUSDT/date=2024-01-01/hour=13
+pub fn partition_dir(root: &Path, kind: &str, symbol: &str, hour: DateTime<Utc>) -> PathBuf {
+    root.join(kind)
+        .join(format!("symbol={}", symbol.to_uppercase()))
+        .join(format!("date={}", hour.format("%Y-%m-%d")))
+        .join(format!("hour={}", hour.format("%H")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_partition_dir_and_sampler() {
+        let ts = DateTime::parse_from_rfc3339("2024-03-05T13:45:10Z").unwrap().with_timezone(&Utc);
+        let hour = hour_start(ts);
+        assert_eq!(hour.to_rfc3339(), "2024-03-05T13:00:00+00:00");
+        assert_eq!(
+            partition_dir(Path::new("/data"), "orderbook", "btcusdt", hour),
+            PathBuf::from("/data/orderbook/symbol=BTCUSDT/date=2024-03-05/hour=13")
+        );
+
+        let mut sampler = Sampler::new(Some(Duration::seconds(1)));
+        assert!(sampler.should_record("BTCUSDT", ts));
+        assert!(!sampler.should_record("BTCUSDT", ts + Duration::milliseconds(500)));
+        assert!(sampler.should_record("ETHUSDT", ts + Duration::milliseconds(500)));
+        assert!(sampler.should_record("BTCUSDT", ts + Duration::seconds(1)));
+    }
+}
@@ -0,0 +1,420 @@
+use arrow::{
+    array::{ArrayRef, Float64Builder, ListBuilder, StringBuilder, TimestampMillisecondBuilder, UInt64Builder},
+    datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit},
+    record_batch::RecordBatch,
+};
+use ::parquet::{
+    arrow::ArrowWriter,
+    basic::{Compression, ZstdLevel},
+    file::properties::WriterProperties,
+};
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fs::{self, File},
+    path::PathBuf,
+    sync::Arc,
+};
+use tokio::sync::{mpsc, watch};
+use tracing::warn;
+
+use crate::core::{
+    orderbook::{level_to_f64, OrderbookSnapshot},
+    signal::MarketData,
+    trade::{Trade, TradeSide},
+};
+use super::{cut_requested, hour_start, partition_dir, schema::parquet_metadata, RecorderError, Sampler, SamplingConfig};
+
+#[derive(Debug, Clone)]
+pub struct ParquetConfig {
+    pub root: PathBuf,
+    // số level mỗi phía được ghi
+    pub depth: usize,
+    pub sample_interval: Option<Duration>,
+    pub sampling: SamplingConfig,
+    // số row buffer trước khi ghi một row group
+    pub batch_size: usize,
+}
+
+impl ParquetConfig {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            depth: 20,
+            sample_interval: None,
+            sampling: SamplingConfig::default(),
+            batch_size: 1024,
+        }
+    }
+}
+
+trait Row: Sized {
+    const KIND: &'static str;
+    fn schema() -> SchemaRef;
+    fn to_batch(rows: &[Self]) -> Result<RecordBatch, RecorderError>;
+}
+
+fn timestamp_type() -> DataType {
+    DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into()))
+}
+
+fn f64_list_type() -> DataType {
+    DataType::List(Arc::new(Field::new("item", DataType::Float64, true)))
+}
+
+#[derive(Debug, Clone)]
+struct BookRow {
+    timestamp: DateTime<Utc>,
+    symbol: String,
+    last_update_id: u64,
+    bids: Vec<(f64, f64)>,
+    asks: Vec<(f64, f64)>,
+}
+
+impl BookRow {
+    fn new(symbol: &str, snap: &OrderbookSnapshot, depth: usize) -> Self {
+        let to_f64 = |(p, q): (&Decimal, &Decimal)| level_to_f64((*p, *q));
+        Self {
+            timestamp: snap.timestamp,
+            symbol: symbol.to_uppercase(),
+            last_update_id: snap.last_update_id,
+            bids: snap.bids.iter().rev().take(depth).map(to_f64).collect(),
+            asks: snap.asks.iter().take(depth).map(to_f64).collect(),
+        }
+    }
+}
+
+impl Row for BookRow {
+    const KIND: &'static str = "orderbook";
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("timestamp", timestamp_type(), false),
+            Field::new("symbol", DataType::Utf8, false),
+            Field::new("last_update_id", DataType::UInt64, false),
+            // best trước: bid giảm dần, ask tăng dần
+            Field::new("bid_price", f64_list_type(), false),
+            Field::new("bid_qty", f64_list_type(), false),
+            Field::new("ask_price", f64_list_type(), false),
+            Field::new("ask_qty", f64_list_type(), false),
+        ])
+        .with_metadata(parquet_metadata(Self::KIND)))
+    }
+
+    fn to_batch(rows: &[Self]) -> Result<RecordBatch, RecorderError> {
+        let mut ts = TimestampMillisecondBuilder::new().with_timezone("UTC");
+        let mut symbol = StringBuilder::new();
+        let mut update_id = UInt64Builder::new();
+        let mut lists: [ListBuilder<Float64Builder>; 4] =
+            std::array::from_fn(|_| ListBuilder::new(Float64Builder::new()));
+
+        for row in rows {
+            ts.append_value(row.timestamp.timestamp_millis());
+            symbol.append_value(&row.symbol);
+            update_id.append_value(row.last_update_id);
+            for (i, levels) in [&row.bids, &row.asks].into_iter().enumerate() {
+                for (p, q) in levels {
+                    lists[i * 2].values().append_value(*p);
+                    lists[i * 2 + 1].values().append_value(*q);
+                }
+                lists[i * 2].append(true);
+                lists[i * 2 + 1].append(true);
+            }
+        }
+
+        let [mut bid_p, mut bid_q, mut ask_p, mut ask_q] = lists;
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(ts.finish()),
+            Arc::new(symbol.finish()),
+            Arc::new(update_id.finish()),
+            Arc::new(bid_p.finish()),
+            Arc::new(bid_q.finish()),
+            Arc::new(ask_p.finish()),
+            Arc::new(ask_q.finish()),
+        ];
+        Ok(RecordBatch::try_new(Self::schema(), columns)?)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TradeRow(Trade);
+
+impl Row for TradeRow {
+    const KIND: &'static str = "trades";
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("timestamp", timestamp_type(), false),
+            Field::new("symbol", DataType::Utf8, false),
+            Field::new("trade_id", DataType::UInt64, false),
+            Field::new("price", DataType::Float64, false),
+            Field::new("qty", DataType::Float64, false),
+            Field::new("side", DataType::Utf8, false),
+        ])
+        .with_metadata(parquet_metadata(Self::KIND)))
+    }
+
+    fn to_batch(rows: &[Self]) -> Result<RecordBatch, RecorderError> {
+        let mut ts = TimestampMillisecondBuilder::new().with_timezone("UTC");
+        let mut symbol = StringBuilder::new();
+        let mut trade_id = UInt64Builder::new();
+        let mut price = Float64Builder::new();
+        let mut qty = Float64Builder::new();
+        let mut side = StringBuilder::new();
+
+        for TradeRow(t) in rows {
+            ts.append_value(t.timestamp.timestamp_millis());
+            symbol.append_value(t.symbol.to_uppercase());
+            trade_id.append_value(t.trade_id);
+            price.append_value(t.price);
+            qty.append_value(t.qty);
+            side.append_value(match t.side {
+                TradeSide::Buy => "buy",
+                TradeSide::Sell => "sell",
+            });
+        }
+
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(ts.finish()),
+            Arc::new(symbol.finish()),
+            Arc::new(trade_id.finish()),
+            Arc::new(price.finish()),
+            Arc::new(qty.finish()),
+            Arc::new(side.finish()),
+        ];
+        Ok(RecordBatch::try_new(Self::schema(), columns)?)
+    }
+}
+
+// Một file parquet đang mở cho (symbol, giờ)
+struct Partition<R: Row> {
+    hour: DateTime<Utc>,
+    path: PathBuf,
+    writer: ArrowWriter<File>,
+    rows: Vec<R>,
+}
+
+impl<R: Row> Partition<R> {
+    fn open(config: &ParquetConfig, symbol: &str, hour: DateTime<Utc>) -> Result<Self, RecorderError> {
+        let dir = partition_dir(&config.root, R::KIND, symbol, hour);
+        fs::create_dir_all(&dir)?;
+        // mỗi lần mở là một file mới, restart giữa giờ không ghi đè file cũ
+        let path = dir.join(format!("part-{}.parquet", Utc::now().timestamp_millis()));
+        let props = WriterProperties::builder()
+            .set_compression(Compression::ZSTD(ZstdLevel::default()))
+            .build();
+        let writer = ArrowWriter::try_new(File::create(&path)?, R::schema(), Some(props))?;
+        Ok(Self {
+            hour,
+            path,
+            writer,
+            rows: Vec::new(),
+        })
+    }
+
+    fn flush(&mut self) -> Result<(), RecorderError> {
+        if self.rows.is_empty() {
+            return Ok(());
+        }
+        let batch = R::to_batch(&self.rows)?;
+        self.writer.write(&batch)?;
+        self.rows.clear();
+        Ok(())
+    }
+
+    // ghi footer; file chỉ đọc được sau khi close
+    fn close(mut self) -> Result<PathBuf, RecorderError> {
+        self.flush()?;
+        self.writer.close()?;
+        Ok(self.path)
+    }
+}
+
+fn push_row<R: Row>(
+    partitions: &mut HashMap<String, Partition<R>>,
+    closed: &mut Vec<PathBuf>,
+    config: &ParquetConfig,
+    symbol: &str,
+    ts: DateTime<Utc>,
+    row: R,
+) -> Result<(), RecorderError> {
+    let hour = hour_start(ts);
+    let symbol = symbol.to_uppercase();
+
+    if partitions.get(&symbol).is_some_and(|p| p.hour != hour)
+        && let Some(old) = partitions.remove(&symbol)
+    {
+        closed.push(old.close()?);
+    }
+    let partition = match partitions.entry(symbol) {
+        Entry::Occupied(e) => e.into_mut(),
+        Entry::Vacant(e) => {
+            let p = Partition::open(config, e.key(), hour)?;
+            e.insert(p)
+        }
+    };
+
+    partition.rows.push(row);
+    if partition.rows.len() >= config.batch_size {
+        partition.flush()?;
+    }
+    Ok(())
+}
+
+// Ghi orderbook snapshot và trade ra parquet, mỗi symbol mỗi giờ một file
+pub struct ParquetRecorder {
+    config: ParquetConfig,
+    sampler: Sampler,
+    books: HashMap<String, Partition<BookRow>>,
+    trades: HashMap<String, Partition<TradeRow>>,
+    // file đã đóng khi sang giờ mới
+    closed: Vec<PathBuf>,
+    cut: Option<watch::Receiver<u64>>,
+}
+
+impl ParquetRecorder {
+    pub fn new(config: ParquetConfig) -> Self {
+        Self {
+            sampler: Sampler::with_config(config.sampling.clone().with_interval(config.sample_interval)),
+            config,
+            books: HashMap::new(),
+            trades: HashMap::new(),
+            closed: Vec::new(),
+            cut: None,
+        }
+    }
+
+    // rollover cuối ngày: đổi giá trị là đóng mọi partition, dù chưa hết giờ
+    pub fn with_cut(mut self, cut: watch::Receiver<u64>) -> Self {
+        self.cut = Some(cut);
+        self
+    }
+
+    pub fn rotate(&mut self) -> Result<(), RecorderError> {
+        for (_, p) in self.books.drain() {
+            self.closed.push(p.close()?);
+        }
+        for (_, p) in self.trades.drain() {
+            self.closed.push(p.close()?);
+        }
+        Ok(())
+    }
+
+    pub fn record_orderbook(&mut self, symbol: &str, snap: &OrderbookSnapshot) -> Result<(), RecorderError> {
+        if !self.sampler.should_record_book(symbol, snap) {
+            return Ok(());
+        }
+        let row = BookRow::new(symbol, snap, self.config.depth);
+        push_row(&mut self.books, &mut self.closed, &self.config, symbol, snap.timestamp, row)
+    }
+
+    pub fn record_trade(&mut self, trade: &Trade) -> Result<(), RecorderError> {
+        push_row(&mut self.trades, &mut self.closed, &self.config, &trade.symbol, trade.timestamp, TradeRow(trade.clone()))
+    }
+
+    pub fn flush(&mut self) -> Result<(), RecorderError> {
+        for p in self.books.values_mut() {
+            p.flush()?;
+        }
+        for p in self.trades.values_mut() {
+            p.flush()?;
+        }
+        Ok(())
+    }
+
+    // Đóng mọi file đang mở, trả về danh sách file đã ghi
+    pub fn close(self) -> Result<Vec<PathBuf>, RecorderError> {
+        let mut paths = self.closed;
+        for (_, p) in self.books {
+            paths.push(p.close()?);
+        }
+        for (_, p) in self.trades {
+            paths.push(p.close()?);
+        }
+        Ok(paths)
+    }
+
+    // Ghi là blocking I/O, chạy trong spawn_blocking:
+    // tokio::task::spawn_blocking(move || recorder.run_blocking(rx))
+    pub fn run_blocking(mut self, mut rx: mpsc::Receiver<MarketData>) -> Result<Vec<PathBuf>, RecorderError> {
+        while let Some(data) = rx.blocking_recv() {
+            if cut_requested(&mut self.cut)
+                && let Err(e) = self.rotate()
+            {
+                warn!(recorder = "parquet", error = %e, "recorder rotate failed");
+            }
+            let result = match &data {
+                MarketData::Orderbook { symbol, snap } => self.record_orderbook(symbol, snap),
+                MarketData::Trade(trade) => self.record_trade(trade),
+                // chưa có schema cho funding / OI / thanh lý
+                MarketData::Perp(_) | MarketData::Liquidation(_) => Ok(()),
+            };
+            if let Err(e) = result {
+                warn!(recorder = "parquet", error = %e, "recorder write failed");
+            }
+        }
+        self.close()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::orderbook::Side;
+    use ::parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use rust_decimal_macros::dec;
+
+    fn read_rows(path: &PathBuf) -> usize {
+        let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+        reader.map(|b| b.unwrap().num_rows()).sum()
+    }
+
+    #[test]
+    fn test_records_partitioned_by_symbol_and_hour() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = ParquetConfig::new(dir.path());
+        config.batch_size = 2;
+        let mut recorder = ParquetRecorder::new(config);
+
+        let t0 = DateTime::parse_from_rfc3339("2024-03-05T13:59:59Z").unwrap().with_timezone(&Utc);
+        let mut ob = OrderbookSnapshot::new();
+        ob.set_level(Side::Bid, dec!(100), dec!(1));
+        ob.set_level(Side::Ask, dec!(101), dec!(2));
+        for i in 0..3 {
+            ob.timestamp = t0 + Duration::milliseconds(600 * i);
+            recorder.record_orderbook("btcusdt", &ob).unwrap();
+        }
+
+        recorder
+            .record_trade(&Trade {
+                symbol: "btcusdt".into(),
+                trade_id: 1,
+                price: 100.5,
+                qty: 0.1,
+                side: TradeSide::Buy,
+                timestamp: t0,
+            })
+            .unwrap();
+
+        let mut paths = recorder.close().unwrap();
+        paths.sort();
+        assert_eq!(paths.len(), 3);
+
+        let books = |hour: &str| -> Vec<&PathBuf> {
+            paths
+                .iter()
+                .filter(|p| p.to_string_lossy().contains(&format!("orderbook/symbol=BTCUSDT/date=2024-03-05/hour={}", hour)))
+                .collect()
+        };
+        // 13:59:59.0 và 13:59:59.6 ở giờ 13, 14:00:00.2 sang file giờ 14
+        assert_eq!(read_rows(books("13")[0]), 2);
+        assert_eq!(read_rows(books("14")[0]), 1);
+
+        let trades: Vec<_> = paths.iter().filter(|p| p.to_string_lossy().contains("trades/")).collect();
+        assert_eq!(read_rows(trades[0]), 1);
+    }
+}
@@ -0,0 +1,127 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::RecorderError;
+
+// Tăng khi dạng row trên đĩa đổi, kèm một bước `upgrade_*` cho version cũ.
+// v1: file không có header, dòng JSONL không có "type"
+// v2: header ở đầu file (dòng JSONL, dòng "#" của CSV, metadata của parquet, field của book state)
+pub const SCHEMA_VERSION: u32 = 2;
+pub const LEGACY_VERSION: u32 = 1;
+
+// key trong metadata của parquet
+pub const PARQUET_KIND_KEY: &str = "bsa.kind";
+pub const PARQUET_VERSION_KEY: &str = "bsa.schema_version";
+
+// Header đầu mỗi file recording
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchemaHeader {
+    // "orderbook" / "trades"
+    pub kind: String,
+    pub version: u32,
+    // số level mỗi phía, chỉ có với orderbook
+    #[serde(default)]
+    pub depth: Option<usize>,
+    pub created_at: i64,
+}
+
+impl SchemaHeader {
+    pub fn new(kind: &str, depth: Option<usize>) -> Self {
+        Self { kind: kind.to_string(), version: SCHEMA_VERSION, depth, created_at: Utc::now().timestamp_millis() }
+    }
+}
+
+pub fn legacy_version() -> u32 {
+    LEGACY_VERSION
+}
+
+// file do bản mới hơn ghi thì không đoán, báo lỗi
+pub fn check_version(version: u32) -> Result<u32, RecorderError> {
+    if (LEGACY_VERSION..=SCHEMA_VERSION).contains(&version) {
+        Ok(version)
+    } else {
+        Err(RecorderError::UnsupportedSchema { version })
+    }
+}
+
+pub fn parquet_metadata(kind: &str) -> HashMap<String, String> {
+    HashMap::from([
+        (PARQUET_KIND_KEY.to_string(), kind.to_string()),
+        (PARQUET_VERSION_KEY.to_string(), SCHEMA_VERSION.to_string()),
+    ])
+}
+
+// version của file parquet theo metadata, không có là v1
+pub fn parquet_version(metadata: &HashMap<String, String>) -> Result<u32, RecorderError> {
+    match metadata.get(PARQUET_VERSION_KEY) {
+        Some(v) => check_version(v.parse().map_err(|_| RecorderError::UnsupportedSchema { version: 0 })?),
+        None => Ok(LEGACY_VERSION),
+    }
+}
+
+// v1 -> v2 của một dòng JSONL: thêm tag "type", dòng orderbook là dòng có "bids"
+pub fn upgrade_tick_v1(row: &mut serde_json::Value) {
+    if let Some(obj) = row.as_object_mut()
+        && !obj.contains_key("type")
+    {
+        let kind = if obj.contains_key("bids") { "book" } else { "trade" };
+        obj.insert("type".to_string(), kind.into());
+    }
+}
+
+// đưa một dòng JSONL ở `version` lên dạng hiện tại
+pub fn upgrade_tick(version: u32, row: &mut serde_json::Value) {
+    if version < 2 {
+        upgrade_tick_v1(row);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{orderbook::OrderbookSnapshot, signal::MarketData, trade::TradeSide};
+    use crate::recorder::tick::{TickRecorder, TickRecorderConfig};
+    use crate::replay::reader::read_file;
+    use rust_decimal_macros::dec;
+    use std::fs;
+
+    #[test]
+    fn test_reads_v1_and_v2_rejects_newer() {
+        let dir = tempfile::tempdir().unwrap();
+
+        // capture v1: không header, không "type"
+        let v1 = dir.path().join("v1.jsonl");
+        fs::write(
+            &v1,
+            concat!(
+                r#"{"ts":1000,"symbol":"BTCUSDT","last_update_id":7,"bids":[["100.1","1.5"]],"asks":[["100.2","2"]]}"#,
+                "\n",
+                r#"{"ts":1001,"symbol":"BTCUSDT","trade_id":3,"price":100.2,"qty":0.5,"side":"sell"}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+        let events = read_file(&v1).unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(&events[0], MarketData::Orderbook { snap, .. } if snap.best_bid() == Some((dec!(100.1), dec!(1.5)))));
+        assert!(matches!(&events[1], MarketData::Trade(t) if t.side == TradeSide::Sell && t.trade_id == 3));
+
+        // v2 do recorder ghi: header + dòng có tag
+        let mut recorder = TickRecorder::new(TickRecorderConfig { root: dir.path().join("v2"), ..Default::default() });
+        let mut ob = OrderbookSnapshot::new();
+        ob.set_level(crate::core::orderbook::Side::Ask, dec!(101), dec!(1));
+        recorder.record_orderbook("btcusdt", &ob).unwrap();
+        let path = recorder.close().unwrap().remove(0);
+        let text = fs::read_to_string(&path).unwrap();
+        let header: serde_json::Value = serde_json::from_str(text.lines().next().unwrap()).unwrap();
+        assert_eq!((header["type"].as_str(), header["version"].as_u64()), (Some("header"), Some(SCHEMA_VERSION as u64)));
+        assert!(text.lines().nth(1).unwrap().contains(r#""type":"book""#));
+        assert_eq!(read_file(&path).unwrap().len(), 1);
+
+        // file của version mới hơn
+        let v3 = dir.path().join("v3.jsonl");
+        fs::write(&v3, r#"{"type":"header","kind":"trades","version":3,"created_at":0}"#).unwrap();
+        assert!(matches!(read_file(&v3), Err(RecorderError::UnsupportedSchema { version: 3 })));
+    }
+}
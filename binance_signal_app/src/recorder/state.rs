@@ -0,0 +1,173 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf, sync::Arc};
+use tracing::{info, warn};
+
+use super::{schema, RecorderError};
+use crate::core::orderbook::{Level, OrderbookSnapshot};
+use crate::ws::OrderbookFeed;
+
+// Lưu book lúc tắt, nạp lại lúc khởi động để strategy không phải chờ feed mới có book
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct BookStateConfig {
+    pub enabled: bool,
+    pub dir: PathBuf,
+    // file cũ hơn thì bỏ, book quá cũ không giúp được gì
+    pub max_age_secs: u64,
+}
+
+impl Default for BookStateConfig {
+    fn default() -> Self {
+        Self { enabled: false, dir: PathBuf::from("data/state"), max_age_secs: 3600 }
+    }
+}
+
+// Dạng trên đĩa: level theo list (price, qty) vì key JSON phải là chuỗi
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BookState {
+    // file lưu trước khi có field này là v1
+    #[serde(default = "schema::legacy_version")]
+    pub version: u32,
+    pub exchange: String,
+    pub symbol: String,
+    pub saved_at: DateTime<Utc>,
+    // timestamp của book, giữ nguyên để stale check vẫn đúng tới khi feed có data mới
+    pub timestamp: DateTime<Utc>,
+    pub last_update_id: u64,
+    pub bids: Vec<Level>,
+    pub asks: Vec<Level>,
+}
+
+impl BookState {
+    pub fn from_snapshot(exchange: &str, symbol: &str, snap: &OrderbookSnapshot) -> Self {
+        Self {
+            version: schema::SCHEMA_VERSION,
+            exchange: exchange.to_string(),
+            symbol: symbol.to_lowercase(),
+            saved_at: Utc::now(),
+            timestamp: snap.timestamp,
+            last_update_id: snap.last_update_id,
+            bids: snap.bids.iter().rev().map(|(p, q)| (*p, *q)).collect(),
+            asks: snap.asks.iter().map(|(p, q)| (*p, *q)).collect(),
+        }
+    }
+
+    pub fn to_snapshot(&self) -> OrderbookSnapshot {
+        OrderbookSnapshot {
+            timestamp: self.timestamp,
+            last_update_id: self.last_update_id,
+            bids: self.bids.iter().copied().collect(),
+            asks: self.asks.iter().copied().collect(),
+            received: None,
+        }
+    }
+}
+
+pub struct BookStateStore {
+    dir: PathBuf,
+    max_age: Duration,
+}
+
+impl BookStateStore {
+    pub fn new(config: &BookStateConfig) -> Self {
+        Self { dir: config.dir.clone(), max_age: Duration::seconds(config.max_age_secs as i64) }
+    }
+
+    pub fn path(&self, exchange: &str, symbol: &str) -> PathBuf {
+        self.dir.join(format!("{}_{}.json", exchange, symbol.to_lowercase()))
+    }
+
+    // ghi ra file tạm rồi rename để tắt giữa chừng không để lại file hỏng
+    pub fn save(&self, state: &BookState) -> Result<PathBuf, RecorderError> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(&state.exchange, &state.symbol);
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec(state)?)?;
+        fs::rename(&tmp, &path)?;
+        Ok(path)
+    }
+
+    // None nếu chưa có file hoặc file quá cũ
+    pub fn load(&self, exchange: &str, symbol: &str, now: DateTime<Utc>) -> Result<Option<BookState>, RecorderError> {
+        let bytes = match fs::read(self.path(exchange, symbol)) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let state: BookState = serde_json::from_slice(&bytes)?;
+        schema::check_version(state.version)?;
+        Ok((now - state.saved_at <= self.max_age).then_some(state))
+    }
+
+    // nạp vào book còn trống trước khi feed chạy, feed ghi đè khi có snapshot mới
+    pub fn restore(&self, feed: &dyn OrderbookFeed) -> bool {
+        let state = match self.load(feed.exchange(), feed.symbol(), Utc::now()) {
+            Ok(Some(state)) => state,
+            Ok(None) => return false,
+            Err(e) => {
+                warn!(exchange = feed.exchange(), symbol = feed.symbol(), error = %e, "cannot load book state");
+                return false;
+            }
+        };
+        let restored = feed.orderbook().update(|ob| {
+            if ob.best_bid().is_some() || ob.best_ask().is_some() {
+                return false;
+            }
+            *ob = state.to_snapshot();
+            true
+        });
+        if restored {
+            info!(exchange = feed.exchange(), symbol = feed.symbol(), last_update_id = state.last_update_id, "book state restored");
+        }
+        restored
+    }
+
+    pub fn save_all(&self, feeds: &[Arc<dyn OrderbookFeed>]) {
+        for feed in feeds {
+            let snap = feed.snapshot();
+            if snap.bids.is_empty() && snap.asks.is_empty() {
+                continue;
+            }
+            match self.save(&BookState::from_snapshot(feed.exchange(), feed.symbol(), &snap)) {
+                Ok(path) => info!(path = %path.display(), "book state saved"),
+                Err(e) => warn!(exchange = feed.exchange(), symbol = feed.symbol(), error = %e, "cannot save book state"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::orderbook::{SharedOrderbook, Side};
+    use crate::replay::ReplayFeed;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_save_and_restore_book() {
+        let dir = std::env::temp_dir().join(format!("bsa_state_{}", std::process::id()));
+        let store = BookStateStore::new(&BookStateConfig { enabled: true, dir: dir.clone(), max_age_secs: 60 });
+
+        let mut snap = OrderbookSnapshot::new();
+        snap.set_level(Side::Bid, dec!(100), dec!(1));
+        snap.set_level(Side::Bid, dec!(99.5), dec!(2));
+        snap.set_level(Side::Ask, dec!(100.5), dec!(3));
+        snap.last_update_id = 42;
+        store.save(&BookState::from_snapshot("replay", "BTCUSDT", &snap)).unwrap();
+
+        let feed = ReplayFeed { symbol: "btcusdt".into(), orderbook: Arc::new(SharedOrderbook::new()) };
+        assert!(store.restore(&feed));
+        let restored = feed.snapshot();
+        assert_eq!((restored.last_update_id, restored.timestamp), (42, snap.timestamp));
+        assert_eq!((restored.best_bid(), restored.asks.len()), (Some((dec!(100), dec!(1))), 1));
+        // book đã có data thì không ghi đè
+        assert!(!store.restore(&feed));
+
+        // file quá cũ bị bỏ
+        let later = Utc::now() + Duration::seconds(120);
+        assert_eq!(store.load("replay", "btcusdt", later).unwrap(), None);
+        assert_eq!(store.load("okx", "btcusdt", Utc::now()).unwrap(), None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
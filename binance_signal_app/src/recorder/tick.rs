@@ -0,0 +1,556 @@
+use chrono::{DateTime, Duration, Utc};
+use flate2::{write::GzEncoder, Compression};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+use tokio::sync::{mpsc, watch};
+use tracing::warn;
+
+use crate::core::{
+    orderbook::{OrderbookSnapshot, Side},
+    signal::MarketData,
+    trade::{Trade, TradeSide},
+};
+use super::{
+    cut_requested,
+    schema::SchemaHeader,
+    RecorderError, Sampler, SamplingConfig,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TickFormat {
+    Jsonl,
+    Csv,
+}
+
+impl TickFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            TickFormat::Jsonl => "jsonl",
+            TickFormat::Csv => "csv",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TickCompression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl TickCompression {
+    fn extension(&self) -> &'static str {
+        match self {
+            TickCompression::None => "",
+            TickCompression::Gzip => ".gz",
+            TickCompression::Zstd => ".zst",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TickRecorderConfig {
+    pub root: PathBuf,
+    pub format: TickFormat,
+    pub compression: TickCompression,
+    // số level mỗi phía được ghi
+    pub depth: usize,
+    // xoay file khi số byte (đã nén) trên đĩa vượt ngưỡng
+    pub max_file_bytes: Option<u64>,
+    // xoay file theo timestamp của tick
+    pub rotate_every_secs: Option<u64>,
+    pub sample_interval_ms: Option<u64>,
+    pub sampling: SamplingConfig,
+}
+
+impl Default for TickRecorderConfig {
+    fn default() -> Self {
+        Self {
+            root: PathBuf::from("data/ticks"),
+            format: TickFormat::Jsonl,
+            compression: TickCompression::None,
+            depth: 20,
+            max_file_bytes: Some(256 * 1024 * 1024),
+            rotate_every_secs: Some(3600),
+            sample_interval_ms: None,
+            sampling: SamplingConfig::default(),
+        }
+    }
+}
+
+// Một dòng orderbook trong file tick, price/qty giữ dạng chuỗi decimal
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BookRecord {
+    pub ts: i64,
+    pub symbol: String,
+    pub last_update_id: u64,
+    pub bids: Vec<(Decimal, Decimal)>,
+    pub asks: Vec<(Decimal, Decimal)>,
+}
+
+impl BookRecord {
+    pub fn new(symbol: &str, snap: &OrderbookSnapshot, depth: usize) -> Self {
+        Self {
+            ts: snap.timestamp.timestamp_millis(),
+            symbol: symbol.to_uppercase(),
+            last_update_id: snap.last_update_id,
+            bids: snap.bids.iter().rev().take(depth).map(|(p, q)| (*p, *q)).collect(),
+            asks: snap.asks.iter().take(depth).map(|(p, q)| (*p, *q)).collect(),
+        }
+    }
+
+    pub fn to_snapshot(&self) -> OrderbookSnapshot {
+        let mut ob = OrderbookSnapshot::new();
+        for (p, q) in &self.bids {
+            ob.set_level(Side::Bid, *p, *q);
+        }
+        for (p, q) in &self.asks {
+            ob.set_level(Side::Ask, *p, *q);
+        }
+        ob.last_update_id = self.last_update_id;
+        ob.timestamp = DateTime::from_timestamp_millis(self.ts).unwrap_or_default();
+        ob
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradeRecord {
+    pub ts: i64,
+    pub symbol: String,
+    pub trade_id: u64,
+    pub price: f64,
+    pub qty: f64,
+    pub side: String,
+}
+
+// Một dòng JSONL từ schema v2, dòng đầu file là header
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum TickRow {
+    Header(SchemaHeader),
+    Book(BookRecord),
+    Trade(TradeRecord),
+}
+
+impl From<&Trade> for TradeRecord {
+    fn from(t: &Trade) -> Self {
+        Self {
+            ts: t.timestamp.timestamp_millis(),
+            symbol: t.symbol.to_uppercase(),
+            trade_id: t.trade_id,
+            price: t.price,
+            qty: t.qty,
+            side: match t.side {
+                TradeSide::Buy => "buy".into(),
+                TradeSide::Sell => "sell".into(),
+            },
+        }
+    }
+}
+
+impl TradeRecord {
+    pub fn to_trade(&self) -> Trade {
+        Trade {
+            symbol: self.symbol.clone(),
+            trade_id: self.trade_id,
+            price: self.price,
+            qty: self.qty,
+            side: if self.side == "sell" { TradeSide::Sell } else { TradeSide::Buy },
+            timestamp: DateTime::from_timestamp_millis(self.ts).unwrap_or_default(),
+        }
+    }
+}
+
+// Đếm byte thực sự xuống đĩa (sau khi nén)
+struct CountingWriter {
+    inner: File,
+    written: u64,
+}
+
+impl Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+enum Encoder {
+    Plain(BufWriter<CountingWriter>),
+    Gzip(GzEncoder<BufWriter<CountingWriter>>),
+    Zstd(zstd::Encoder<'static, BufWriter<CountingWriter>>),
+}
+
+impl Encoder {
+    fn new(file: File, compression: TickCompression) -> io::Result<Self> {
+        let out = BufWriter::new(CountingWriter { inner: file, written: 0 });
+        Ok(match compression {
+            TickCompression::None => Encoder::Plain(out),
+            TickCompression::Gzip => Encoder::Gzip(GzEncoder::new(out, Compression::default())),
+            TickCompression::Zstd => Encoder::Zstd(zstd::Encoder::new(out, 0)?),
+        })
+    }
+
+    fn writer(&mut self) -> &mut dyn Write {
+        match self {
+            Encoder::Plain(w) => w,
+            Encoder::Gzip(w) => w,
+            Encoder::Zstd(w) => w,
+        }
+    }
+
+    fn bytes_on_disk(&self) -> u64 {
+        match self {
+            Encoder::Plain(w) => w.get_ref().written,
+            Encoder::Gzip(w) => w.get_ref().get_ref().written,
+            Encoder::Zstd(w) => w.get_ref().get_ref().written,
+        }
+    }
+
+    // ghi trailer của gzip/zstd và flush xuống file
+    fn finish(self) -> io::Result<()> {
+        let mut out = match self {
+            Encoder::Plain(w) => w,
+            Encoder::Gzip(w) => w.finish()?,
+            Encoder::Zstd(w) => w.finish()?,
+        };
+        out.flush()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Kind {
+    Orderbook,
+    Trades,
+}
+
+impl Kind {
+    fn name(&self) -> &'static str {
+        match self {
+            Kind::Orderbook => "orderbook",
+            Kind::Trades => "trades",
+        }
+    }
+}
+
+struct TickFile {
+    path: PathBuf,
+    started: DateTime<Utc>,
+    encoder: Encoder,
+}
+
+impl TickFile {
+    fn open(config: &TickRecorderConfig, kind: Kind, symbol: &str, started: DateTime<Utc>) -> Result<Self, RecorderError> {
+        let dir = config.root.join(kind.name()).join(symbol);
+        fs::create_dir_all(&dir)?;
+        let path = unique_path(
+            &dir,
+            &format!("{}-{}", kind.name(), started.format("%Y%m%dT%H%M%S%3f")),
+            &format!("{}{}", config.format.extension(), config.compression.extension()),
+        );
+
+        let mut encoder = Encoder::new(File::create(&path)?, config.compression)?;
+        let depth = (kind == Kind::Orderbook).then_some(config.depth);
+        let header = SchemaHeader::new(kind.name(), depth);
+        match config.format {
+            TickFormat::Jsonl => writeln!(encoder.writer(), "{}", serde_json::to_string(&TickRow::Header(header))?)?,
+            // CSV: dòng "#" chứa header, sau đó là tên cột
+            TickFormat::Csv => {
+                writeln!(encoder.writer(), "#{}", serde_json::to_string(&header)?)?;
+                writeln!(encoder.writer(), "{}", csv_header(kind, config.depth))?;
+            }
+        }
+        Ok(Self { path, started, encoder })
+    }
+
+    fn should_rotate(&self, config: &TickRecorderConfig, ts: DateTime<Utc>) -> bool {
+        let by_size = config
+            .max_file_bytes
+            .is_some_and(|max| self.encoder.bytes_on_disk() >= max);
+        let by_time = config
+            .rotate_every_secs
+            .is_some_and(|secs| ts - self.started >= Duration::seconds(secs as i64));
+        by_size || by_time
+    }
+}
+
+// file xoay trong cùng millisecond thì thêm hậu tố
+fn unique_path(dir: &Path, stem: &str, ext: &str) -> PathBuf {
+    let mut path = dir.join(format!("{}.{}", stem, ext));
+    let mut n = 1;
+    while path.exists() {
+        path = dir.join(format!("{}-{}.{}", stem, n, ext));
+        n += 1;
+    }
+    path
+}
+
+fn csv_header(kind: Kind, depth: usize) -> String {
+    match kind {
+        Kind::Orderbook => {
+            let mut cols = vec!["ts".to_string(), "symbol".into(), "last_update_id".into()];
+            for side in ["bid", "ask"] {
+                for i in 0..depth {
+                    cols.push(format!("{}_px_{}", side, i));
+                    cols.push(format!("{}_qty_{}", side, i));
+                }
+            }
+            cols.join(",")
+        }
+        Kind::Trades => "ts,symbol,trade_id,price,qty,side".into(),
+    }
+}
+
+fn book_csv_row(rec: &BookRecord, depth: usize) -> String {
+    let mut cols = vec![rec.ts.to_string(), rec.symbol.clone(), rec.last_update_id.to_string()];
+    for levels in [&rec.bids, &rec.asks] {
+        for i in 0..depth {
+            match levels.get(i) {
+                Some((p, q)) => {
+                    cols.push(p.to_string());
+                    cols.push(q.to_string());
+                }
+                None => {
+                    cols.push(String::new());
+                    cols.push(String::new());
+                }
+            }
+        }
+    }
+    cols.join(",")
+}
+
+fn trade_csv_row(rec: &TradeRecord) -> String {
+    format!("{},{},{},{},{},{}", rec.ts, rec.symbol, rec.trade_id, rec.price, rec.qty, rec.side)
+}
+
+// Ghi tick ra JSONL/CSV, mỗi (loại, symbol) một chuỗi file xoay vòng
+pub struct TickRecorder {
+    config: TickRecorderConfig,
+    sampler: Sampler,
+    files: HashMap<(Kind, String), TickFile>,
+    closed: Vec<PathBuf>,
+    // rollover cuối ngày: đổi giá trị là cắt file
+    cut: Option<watch::Receiver<u64>>,
+}
+
+impl TickRecorder {
+    pub fn new(config: TickRecorderConfig) -> Self {
+        Self {
+            sampler: Sampler::with_config(
+                config.sampling.clone().with_interval(config.sample_interval_ms.map(|ms| Duration::milliseconds(ms as i64))),
+            ),
+            config,
+            files: HashMap::new(),
+            closed: Vec::new(),
+            cut: None,
+        }
+    }
+
+    pub fn with_cut(mut self, cut: watch::Receiver<u64>) -> Self {
+        self.cut = Some(cut);
+        self
+    }
+
+    // đóng mọi file đang mở, data kế tiếp ghi sang file mới
+    pub fn rotate(&mut self) -> Result<(), RecorderError> {
+        for (_, f) in self.files.drain() {
+            f.encoder.finish()?;
+            self.closed.push(f.path);
+        }
+        Ok(())
+    }
+
+    pub fn record_orderbook(&mut self, symbol: &str, snap: &OrderbookSnapshot) -> Result<(), RecorderError> {
+        if !self.sampler.should_record_book(symbol, snap) {
+            return Ok(());
+        }
+        let rec = BookRecord::new(symbol, snap, self.config.depth);
+        let symbol = rec.symbol.clone();
+        let line = match self.config.format {
+            TickFormat::Jsonl => serde_json::to_string(&TickRow::Book(rec))?,
+            TickFormat::Csv => book_csv_row(&rec, self.config.depth),
+        };
+        self.write_line(Kind::Orderbook, &symbol, snap.timestamp, &line)
+    }
+
+    pub fn record_trade(&mut self, trade: &Trade) -> Result<(), RecorderError> {
+        let rec = TradeRecord::from(trade);
+        let symbol = rec.symbol.clone();
+        let line = match self.config.format {
+            TickFormat::Jsonl => serde_json::to_string(&TickRow::Trade(rec))?,
+            TickFormat::Csv => trade_csv_row(&rec),
+        };
+        self.write_line(Kind::Trades, &symbol, trade.timestamp, &line)
+    }
+
+    fn write_line(&mut self, kind: Kind, symbol: &str, ts: DateTime<Utc>, line: &str) -> Result<(), RecorderError> {
+        let key = (kind, symbol.to_string());
+        if self.files.get(&key).is_some_and(|f| f.should_rotate(&self.config, ts))
+            && let Some(old) = self.files.remove(&key)
+        {
+            old.encoder.finish()?;
+            self.closed.push(old.path);
+        }
+        if !self.files.contains_key(&key) {
+            let file = TickFile::open(&self.config, kind, symbol, ts)?;
+            self.files.insert(key.clone(), file);
+        }
+        if let Some(file) = self.files.get_mut(&key) {
+            writeln!(file.encoder.writer(), "{}", line)?;
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), RecorderError> {
+        for f in self.files.values_mut() {
+            f.encoder.writer().flush()?;
+        }
+        Ok(())
+    }
+
+    // Đóng mọi file, trả về tất cả file đã ghi kể cả file đã xoay
+    pub fn close(mut self) -> Result<Vec<PathBuf>, RecorderError> {
+        for (_, f) in self.files.drain() {
+            f.encoder.finish()?;
+            self.closed.push(f.path);
+        }
+        Ok(self.closed)
+    }
+
+    // tokio::task::spawn_blocking(move || recorder.run_blocking(rx))
+    pub fn run_blocking(mut self, mut rx: mpsc::Receiver<MarketData>) -> Result<Vec<PathBuf>, RecorderError> {
+        while let Some(data) = rx.blocking_recv() {
+            if cut_requested(&mut self.cut)
+                && let Err(e) = self.rotate()
+            {
+                warn!(recorder = "tick", error = %e, "recorder rotate failed");
+            }
+            let result = match &data {
+                MarketData::Orderbook { symbol, snap } => self.record_orderbook(symbol, snap),
+                MarketData::Trade(trade) => self.record_trade(trade),
+                // chưa có schema cho funding / OI / thanh lý
+                MarketData::Perp(_) | MarketData::Liquidation(_) => Ok(()),
+            };
+            if let Err(e) = result {
+                warn!(recorder = "tick", error = %e, "recorder write failed");
+            }
+        }
+        self.close()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use rust_decimal_macros::dec;
+    use std::io::{BufRead, BufReader, Read};
+
+    fn book(ms: i64) -> OrderbookSnapshot {
+        let mut ob = OrderbookSnapshot::new();
+        ob.set_level(Side::Bid, dec!(100.10), dec!(1.5));
+        ob.set_level(Side::Ask, dec!(100.20), dec!(2));
+        ob.last_update_id = ms as u64;
+        ob.timestamp = DateTime::from_timestamp_millis(ms).unwrap();
+        ob
+    }
+
+    fn read_lines(path: &Path, decode: impl FnOnce(File) -> Box<dyn Read>) -> Vec<String> {
+        BufReader::new(decode(File::open(path).unwrap()))
+            .lines()
+            .map(|l| l.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_jsonl_gzip_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut recorder = TickRecorder::new(TickRecorderConfig {
+            root: dir.path().into(),
+            compression: TickCompression::Gzip,
+            ..Default::default()
+        });
+        recorder.record_orderbook("btcusdt", &book(1_000)).unwrap();
+        recorder.record_orderbook("btcusdt", &book(2_000)).unwrap();
+        let paths = recorder.close().unwrap();
+        assert_eq!(paths.len(), 1);
+        assert!(paths[0].to_string_lossy().ends_with(".jsonl.gz"));
+
+        let lines = read_lines(&paths[0], |f| Box::new(GzDecoder::new(f)));
+        // header + 2 dòng book
+        assert_eq!(lines.len(), 3);
+        assert!(matches!(serde_json::from_str(&lines[0]).unwrap(), TickRow::Header(h) if h.depth == Some(20)));
+        let rec: BookRecord = serde_json::from_str(&lines[2]).unwrap();
+        assert_eq!(rec.symbol, "BTCUSDT");
+        assert_eq!(rec.to_snapshot().best_bid(), Some((dec!(100.10), dec!(1.5))));
+        assert_eq!(rec.to_snapshot().timestamp.timestamp_millis(), 2_000);
+    }
+
+    #[test]
+    fn test_csv_zstd_rotation_by_time() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut recorder = TickRecorder::new(TickRecorderConfig {
+            root: dir.path().into(),
+            format: TickFormat::Csv,
+            compression: TickCompression::Zstd,
+            depth: 2,
+            rotate_every_secs: Some(60),
+            ..Default::default()
+        });
+        recorder.record_orderbook("btcusdt", &book(0)).unwrap();
+        recorder.record_orderbook("btcusdt", &book(30_000)).unwrap();
+        recorder.record_orderbook("btcusdt", &book(61_000)).unwrap();
+        let paths = recorder.close().unwrap();
+        assert_eq!(paths.len(), 2);
+
+        let lines = read_lines(&paths[0], |f| Box::new(zstd::Decoder::new(f).unwrap()));
+        assert!(lines[0].starts_with(r#"#{"kind":"orderbook","version":2,"depth":2"#));
+        assert_eq!(lines[1], "ts,symbol,last_update_id,bid_px_0,bid_qty_0,bid_px_1,bid_qty_1,ask_px_0,ask_qty_0,ask_px_1,ask_qty_1");
+        assert_eq!(lines[2], "0,BTCUSDT,0,100.10,1.5,,,100.20,2,,");
+        assert_eq!(lines.len(), 4);
+    }
+
+    #[test]
+    fn test_rotation_by_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut recorder = TickRecorder::new(TickRecorderConfig {
+            root: dir.path().into(),
+            max_file_bytes: Some(1),
+            ..Default::default()
+        });
+        let trade = Trade {
+            symbol: "ethusdt".into(),
+            trade_id: 7,
+            price: 2000.5,
+            qty: 0.25,
+            side: TradeSide::Sell,
+            timestamp: Utc::now(),
+        };
+        for _ in 0..3 {
+            recorder.record_trade(&trade).unwrap();
+            // BufWriter chỉ xuống đĩa khi flush
+            recorder.flush().unwrap();
+        }
+        let paths = recorder.close().unwrap();
+        assert_eq!(paths.len(), 3);
+
+        let lines = read_lines(&paths[0], |f| Box::new(f));
+        let rec: TradeRecord = serde_json::from_str(&lines[1]).unwrap();
+        assert_eq!(rec.to_trade().side, TradeSide::Sell);
+        assert_eq!(rec.symbol, "ETHUSDT");
+    }
+}
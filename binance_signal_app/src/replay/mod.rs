@@ -0,0 +1,269 @@
+pub mod reader;
+pub mod store;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::{sync::mpsc, time::Instant};
+
+use crate::core::{
+    orderbook::SharedOrderbook,
+    signal::{MarketData, SignalEngine, SignalOutput},
+};
+use crate::recorder::RecorderError;
+use crate::ws::OrderbookFeed;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    // không sleep, chạy nhanh nhất có thể
+    Max,
+    RealTime,
+    // 10.0 = nhanh gấp 10 lần thời gian thực
+    Multiplier(f64),
+}
+
+impl ReplaySpeed {
+    // Thời gian chờ tính từ lúc bắt đầu replay cho event cách event đầu `elapsed`
+    fn delay(&self, elapsed: chrono::Duration) -> Option<std::time::Duration> {
+        let elapsed = elapsed.to_std().ok()?;
+        match self {
+            ReplaySpeed::Max => None,
+            ReplaySpeed::RealTime => Some(elapsed),
+            ReplaySpeed::Multiplier(x) if *x > 0.0 => Some(elapsed.div_f64(*x)),
+            ReplaySpeed::Multiplier(_) => None,
+        }
+    }
+}
+
+// Feed giả lập cho một symbol, book được Replayer cập nhật
+// nên strategy dùng Arc<dyn OrderbookFeed> chạy được trên data đã ghi
+#[derive(Debug)]
+pub struct ReplayFeed {
+    pub symbol: String,
+    pub orderbook: Arc<SharedOrderbook>,
+}
+
+#[async_trait]
+impl OrderbookFeed for ReplayFeed {
+    fn exchange(&self) -> &'static str {
+        "replay"
+    }
+
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    fn orderbook(&self) -> &SharedOrderbook {
+        &self.orderbook
+    }
+
+    // book do Replayer::run đẩy vào, không có kết nối nào để mở
+    async fn start(self: Arc<Self>) {}
+}
+
+pub struct Replayer {
+    events: Vec<MarketData>,
+    speed: ReplaySpeed,
+    feeds: HashMap<String, Arc<ReplayFeed>>,
+}
+
+impl Replayer {
+    pub fn new(mut events: Vec<MarketData>, speed: ReplaySpeed) -> Self {
+        // sort ổn định: event cùng timestamp giữ thứ tự trong file
+        events.sort_by_key(MarketData::timestamp);
+
+        let mut feeds = HashMap::new();
+        for ev in &events {
+            if let MarketData::Orderbook { symbol, .. } = ev {
+                feeds.entry(symbol.to_uppercase()).or_insert_with(|| {
+                    Arc::new(ReplayFeed {
+                        symbol: symbol.to_uppercase(),
+                        orderbook: Arc::new(SharedOrderbook::new()),
+                    })
+                });
+            }
+        }
+        Self { events, speed, feeds }
+    }
+
+    pub fn from_paths(paths: &[PathBuf], speed: ReplaySpeed) -> Result<Self, RecorderError> {
+        let mut events = Vec::new();
+        for path in paths {
+            events.extend(reader::read_file(path)?);
+        }
+        Ok(Self::new(events, speed))
+    }
+
+    pub fn from_dir(root: &Path, speed: ReplaySpeed) -> Result<Self, RecorderError> {
+        Self::from_paths(&reader::list_files(root)?, speed)
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn time_range(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        Some((self.events.first()?.timestamp(), self.events.last()?.timestamp()))
+    }
+
+    pub fn feed(&self, symbol: &str) -> Option<Arc<ReplayFeed>> {
+        self.feeds.get(&symbol.to_uppercase()).cloned()
+    }
+
+    pub fn feeds(&self) -> Vec<Arc<dyn OrderbookFeed>> {
+        self.feeds
+            .values()
+            .map(|f| f.clone() as Arc<dyn OrderbookFeed>)
+            .collect()
+    }
+
+    fn apply(&self, ev: &MarketData) {
+        if let MarketData::Orderbook { symbol, snap } = ev
+            && let Some(feed) = self.feeds.get(&symbol.to_uppercase())
+        {
+            feed.orderbook.update(|ob| {
+                *ob = (**snap).clone();
+                true
+            });
+        }
+    }
+
+    // Phát lại toàn bộ event theo `speed`: cập nhật ReplayFeed và gửi vào `tx`
+    // (vd. channel của SignalEngine::run). Trả về số event đã phát
+    pub async fn run(&self, tx: Option<mpsc::Sender<MarketData>>) -> usize {
+        let Some((t0, _)) = self.time_range() else {
+            return 0;
+        };
+        let started = Instant::now();
+
+        for (i, ev) in self.events.iter().enumerate() {
+            if let Some(delay) = self.speed.delay(ev.timestamp() - t0) {
+                tokio::time::sleep_until(started + delay).await;
+            }
+            self.apply(ev);
+            if let Some(tx) = &tx
+                && tx.send(ev.clone()).await.is_err()
+            {
+                return i;
+            }
+        }
+        self.events.len()
+    }
+
+    // Backtest đồng bộ: đẩy thẳng vào engine, bỏ qua speed
+    pub fn replay_into(&self, engine: &mut SignalEngine) -> Vec<SignalOutput> {
+        let mut outputs = Vec::new();
+        for ev in &self.events {
+            self.apply(ev);
+            let out = engine.on_market_data(ev);
+            outputs.extend(out);
+        }
+        outputs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        orderbook::{OrderbookSnapshot, Side},
+        trade::{Trade, TradeSide},
+    };
+    use crate::recorder::{
+        parquet::{ParquetConfig, ParquetRecorder},
+        tick::{TickCompression, TickFormat, TickRecorder, TickRecorderConfig},
+    };
+    use chrono::Duration;
+    use rust_decimal_macros::dec;
+
+    fn book(ms: i64, bid_qty: rust_decimal::Decimal) -> OrderbookSnapshot {
+        let mut ob = OrderbookSnapshot::new();
+        ob.set_level(Side::Bid, dec!(100.1), bid_qty);
+        ob.set_level(Side::Ask, dec!(100.2), dec!(1));
+        ob.timestamp = DateTime::from_timestamp_millis(ms).unwrap();
+        ob
+    }
+
+    fn trade(ms: i64) -> Trade {
+        Trade {
+            symbol: "BTCUSDT".into(),
+            trade_id: ms as u64,
+            price: 100.2,
+            qty: 0.5,
+            side: TradeSide::Buy,
+            timestamp: DateTime::from_timestamp_millis(ms).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replay_tick_files_through_feed_and_engine() {
+        let dir = tempfile::tempdir().unwrap();
+        for (format, compression) in [(TickFormat::Jsonl, TickCompression::Zstd), (TickFormat::Csv, TickCompression::Gzip)] {
+            let mut recorder = TickRecorder::new(TickRecorderConfig {
+                root: dir.path().join(format!("{:?}", format)),
+                format,
+                compression,
+                ..Default::default()
+            });
+            recorder.record_orderbook("btcusdt", &book(1_000, dec!(1))).unwrap();
+            recorder.record_trade(&trade(1_500)).unwrap();
+            recorder.record_orderbook("btcusdt", &book(2_000, dec!(4))).unwrap();
+            recorder.close().unwrap();
+        }
+
+        for sub in ["Jsonl", "Csv"] {
+            let replay = Replayer::from_dir(&dir.path().join(sub), ReplaySpeed::Max).unwrap();
+            assert_eq!(replay.len(), 3);
+
+            let mut engine = SignalEngine::new();
+            engine.register_ofi("btcusdt", &[Duration::seconds(5)]);
+            let outputs = replay.replay_into(&mut engine);
+            assert_eq!(outputs.last().unwrap().get("ofi_5s"), Some(3.0));
+
+            let feed: Arc<dyn OrderbookFeed> = replay.feed("BTCUSDT").unwrap();
+            assert_eq!(feed.best_bid_ask(), Some(((dec!(100.1), dec!(4)), (dec!(100.2), dec!(1)))));
+            assert_eq!(feed.snapshot().timestamp.timestamp_millis(), 2_000);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replay_parquet_over_channel() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut recorder = ParquetRecorder::new(ParquetConfig::new(dir.path()));
+        recorder.record_orderbook("btcusdt", &book(2_000, dec!(2))).unwrap();
+        recorder.record_orderbook("btcusdt", &book(1_000, dec!(1))).unwrap();
+        recorder.record_trade(&trade(1_500)).unwrap();
+        recorder.close().unwrap();
+
+        let replay = Replayer::from_dir(dir.path(), ReplaySpeed::Max).unwrap();
+        let feed = replay.feed("btcusdt").unwrap();
+        let mut updates = feed.subscribe();
+
+        let (tx, mut rx) = mpsc::channel(16);
+        assert_eq!(replay.run(Some(tx)).await, 3);
+
+        // phát theo thứ tự thời gian, không theo thứ tự ghi
+        let order: Vec<i64> = std::iter::from_fn(|| rx.try_recv().ok())
+            .map(|ev| ev.timestamp().timestamp_millis())
+            .collect();
+        assert_eq!(order, vec![1_000, 1_500, 2_000]);
+        assert_eq!(updates.recv().await.unwrap().best_bid(), Some((dec!(100.1), dec!(1))));
+        assert_eq!(feed.best_bid_ask().unwrap().0, (dec!(100.1), dec!(2)));
+    }
+
+    #[test]
+    fn test_speed_delay() {
+        let elapsed = Duration::seconds(10);
+        assert_eq!(ReplaySpeed::Max.delay(elapsed), None);
+        assert_eq!(ReplaySpeed::RealTime.delay(elapsed), Some(std::time::Duration::from_secs(10)));
+        assert_eq!(ReplaySpeed::Multiplier(10.0).delay(elapsed), Some(std::time::Duration::from_secs(1)));
+    }
+}
@@ -0,0 +1,240 @@
+use arrow::{
+    array::{Array, Float64Array, ListArray, StringArray, TimestampMillisecondArray, UInt64Array},
+    record_batch::RecordBatch,
+};
+use ::parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use chrono::DateTime;
+use flate2::read::GzDecoder;
+use std::{
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::core::{
+    orderbook::{from_f64, OrderbookSnapshot, Side},
+    signal::MarketData,
+    trade::{Trade, TradeSide},
+};
+use crate::recorder::{
+    schema::{self, SchemaHeader},
+    tick::TickRow,
+    RecorderError,
+};
+
+fn invalid(msg: impl Into<String>) -> RecorderError {
+    RecorderError::Io(io::Error::new(io::ErrorKind::InvalidData, msg.into()))
+}
+
+// Mọi file recording dưới `root`, sắp xếp theo đường dẫn
+pub fn list_files(root: &Path) -> Result<Vec<PathBuf>, RecorderError> {
+    let mut files = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if is_recording(&path) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn is_recording(path: &Path) -> bool {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    let name = name.trim_end_matches(".gz").trim_end_matches(".zst");
+    name.ends_with(".parquet") || name.ends_with(".jsonl") || name.ends_with(".csv")
+}
+
+// Đọc một file do recorder ghi (parquet / jsonl / csv, có thể nén gzip/zstd)
+pub fn read_file(path: &Path) -> Result<Vec<MarketData>, RecorderError> {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    if name.ends_with(".parquet") {
+        return read_parquet(path);
+    }
+
+    let file = File::open(path)?;
+    let reader: Box<dyn Read> = if name.ends_with(".gz") {
+        Box::new(GzDecoder::new(file))
+    } else if name.ends_with(".zst") {
+        Box::new(zstd::Decoder::new(file)?)
+    } else {
+        Box::new(file)
+    };
+    let lines = BufReader::new(reader).lines();
+
+    let base = name.trim_end_matches(".gz").trim_end_matches(".zst");
+    if base.ends_with(".csv") {
+        read_csv(lines)
+    } else if base.ends_with(".jsonl") {
+        read_jsonl(lines)
+    } else {
+        Err(invalid(format!("unknown recording format: {}", path.display())))
+    }
+}
+
+// file không có header là capture v1, từng dòng được nâng lên dạng hiện tại
+fn read_jsonl(lines: impl Iterator<Item = io::Result<String>>) -> Result<Vec<MarketData>, RecorderError> {
+    let mut out = Vec::new();
+    let mut version = schema::LEGACY_VERSION;
+    for line in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut value: serde_json::Value = serde_json::from_str(&line).map_err(io::Error::from)?;
+        schema::upgrade_tick(version, &mut value);
+        match serde_json::from_value(value).map_err(io::Error::from)? {
+            TickRow::Header(header) => version = schema::check_version(header.version)?,
+            TickRow::Book(rec) => out.push(MarketData::Orderbook {
+                symbol: rec.symbol.clone(),
+                snap: Arc::new(rec.to_snapshot()),
+            }),
+            TickRow::Trade(rec) => out.push(MarketData::Trade(rec.to_trade())),
+        }
+    }
+    Ok(out)
+}
+
+fn read_csv(mut lines: impl Iterator<Item = io::Result<String>>) -> Result<Vec<MarketData>, RecorderError> {
+    let Some(mut header) = lines.next().transpose()? else {
+        return Ok(Vec::new());
+    };
+    // v2 có dòng "#{header}" trước tên cột, v1 thì không
+    if let Some(json) = header.strip_prefix('#') {
+        let meta: SchemaHeader = serde_json::from_str(json).map_err(io::Error::from)?;
+        schema::check_version(meta.version)?;
+        let Some(columns) = lines.next().transpose()? else {
+            return Ok(Vec::new());
+        };
+        header = columns;
+    }
+    let is_book = header.contains("last_update_id");
+    // ts,symbol,last_update_id + (px, qty) * depth * 2
+    let depth = header.split(',').count().saturating_sub(3) / 4;
+
+    let mut out = Vec::new();
+    for line in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let cols: Vec<&str> = line.split(',').collect();
+        let ts = cols
+            .first()
+            .and_then(|c| c.parse::<i64>().ok())
+            .and_then(DateTime::from_timestamp_millis)
+            .ok_or_else(|| invalid(format!("bad csv row: {}", line)))?;
+        let symbol = cols.get(1).copied().unwrap_or("").to_string();
+
+        if is_book {
+            let mut ob = OrderbookSnapshot::new();
+            ob.timestamp = ts;
+            ob.last_update_id = cols.get(2).and_then(|c| c.parse().ok()).unwrap_or(0);
+            for (side_idx, side) in [Side::Bid, Side::Ask].into_iter().enumerate() {
+                for i in 0..depth {
+                    let base = 3 + (side_idx * depth + i) * 2;
+                    if let (Some(p), Some(q)) = (cols.get(base), cols.get(base + 1)) {
+                        ob.set_level_str(side, p, q);
+                    }
+                }
+            }
+            out.push(MarketData::Orderbook { symbol, snap: Arc::new(ob) });
+        } else {
+            // giá / qty hỏng là lỗi như timestamp, không đọc thành 0
+            let num = |i: usize| {
+                cols.get(i)
+                    .and_then(|c| c.parse::<f64>().ok())
+                    .filter(|v| v.is_finite())
+                    .ok_or_else(|| invalid(format!("bad csv row: {}", line)))
+            };
+            out.push(MarketData::Trade(Trade {
+                symbol,
+                trade_id: cols.get(2).and_then(|c| c.parse().ok()).unwrap_or(0),
+                price: num(3)?,
+                qty: num(4)?,
+                side: if cols.get(5) == Some(&"sell") { TradeSide::Sell } else { TradeSide::Buy },
+                timestamp: ts,
+            }));
+        }
+    }
+    Ok(out)
+}
+
+fn column<'a, T: 'static>(batch: &'a RecordBatch, name: &str) -> Result<&'a T, RecorderError> {
+    batch
+        .column_by_name(name)
+        .and_then(|c| c.as_any().downcast_ref::<T>())
+        .ok_or_else(|| invalid(format!("missing or mistyped column: {}", name)))
+}
+
+fn list_values(list: &ListArray, row: usize) -> Vec<f64> {
+    let values = list.value(row);
+    values
+        .as_any()
+        .downcast_ref::<Float64Array>()
+        .map(|a| a.iter().flatten().collect())
+        .unwrap_or_default()
+}
+
+fn read_parquet(path: &Path) -> Result<Vec<MarketData>, RecorderError> {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)?;
+    // file không có metadata version là v1, cột giống v2
+    schema::parquet_version(builder.schema().metadata())?;
+    let reader = builder.build()?;
+    let mut out = Vec::new();
+
+    for batch in reader {
+        let batch = batch?;
+        let ts = column::<TimestampMillisecondArray>(&batch, "timestamp")?;
+        let symbol = column::<StringArray>(&batch, "symbol")?;
+
+        if batch.column_by_name("bid_price").is_some() {
+            let update_id = column::<UInt64Array>(&batch, "last_update_id")?;
+            let bid_p = column::<ListArray>(&batch, "bid_price")?;
+            let bid_q = column::<ListArray>(&batch, "bid_qty")?;
+            let ask_p = column::<ListArray>(&batch, "ask_price")?;
+            let ask_q = column::<ListArray>(&batch, "ask_qty")?;
+
+            for row in 0..batch.num_rows() {
+                let mut ob = OrderbookSnapshot::new();
+                ob.timestamp = DateTime::from_timestamp_millis(ts.value(row)).unwrap_or_default();
+                ob.last_update_id = update_id.value(row);
+                for (side, prices, qtys) in [(Side::Bid, bid_p, bid_q), (Side::Ask, ask_p, ask_q)] {
+                    for (p, q) in list_values(prices, row).into_iter().zip(list_values(qtys, row)) {
+                        if let (Some(p), Some(q)) = (from_f64(p), from_f64(q)) {
+                            ob.set_level(side, p, q);
+                        }
+                    }
+                }
+                out.push(MarketData::Orderbook {
+                    symbol: symbol.value(row).to_string(),
+                    snap: Arc::new(ob),
+                });
+            }
+        } else {
+            let trade_id = column::<UInt64Array>(&batch, "trade_id")?;
+            let price = column::<Float64Array>(&batch, "price")?;
+            let qty = column::<Float64Array>(&batch, "qty")?;
+            let side = column::<StringArray>(&batch, "side")?;
+
+            for row in 0..batch.num_rows() {
+                out.push(MarketData::Trade(Trade {
+                    symbol: symbol.value(row).to_string(),
+                    trade_id: trade_id.value(row),
+                    price: price.value(row),
+                    qty: qty.value(row),
+                    side: if side.value(row) == "sell" { TradeSide::Sell } else { TradeSide::Buy },
+                    timestamp: DateTime::from_timestamp_millis(ts.value(row)).unwrap_or_default(),
+                }));
+            }
+        }
+    }
+    Ok(out)
+}
+
@@ -0,0 +1,318 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::UNIX_EPOCH,
+};
+use tracing::warn;
+
+use crate::core::{orderbook::OrderbookSnapshot, signal::MarketData, trade::Trade};
+use crate::recorder::RecorderError;
+use super::reader;
+
+// file index nằm ngay dưới root, list_files bỏ qua vì không phải đuôi recording
+pub const INDEX_FILE: &str = ".index.json";
+// số file đã decode giữ trong bộ nhớ
+const CACHE_FILES: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SpanKind {
+    Book,
+    Trade,
+}
+
+// Khoảng thời gian của một (symbol, loại) trong một file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexSpan {
+    pub symbol: String,
+    pub kind: SpanKind,
+    pub first_ms: i64,
+    pub last_ms: i64,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    // tương đối với root để chuyển thư mục data vẫn dùng lại được
+    pub path: PathBuf,
+    // file đổi size / mtime thì index lại
+    pub bytes: u64,
+    pub modified_ms: i64,
+    pub spans: Vec<IndexSpan>,
+}
+
+impl IndexEntry {
+    fn build(rel: PathBuf, bytes: u64, modified_ms: i64, events: &[MarketData]) -> Self {
+        let mut spans: Vec<IndexSpan> = Vec::new();
+        for ev in events {
+            let kind = match ev {
+                MarketData::Orderbook { .. } => SpanKind::Book,
+                MarketData::Trade(_) => SpanKind::Trade,
+                MarketData::Perp(_) | MarketData::Liquidation(_) => continue,
+            };
+            let symbol = ev.symbol().to_uppercase();
+            let ms = ev.timestamp().timestamp_millis();
+            match spans.iter_mut().find(|s| s.kind == kind && s.symbol == symbol) {
+                Some(span) => {
+                    span.first_ms = span.first_ms.min(ms);
+                    span.last_ms = span.last_ms.max(ms);
+                    span.count += 1;
+                }
+                None => spans.push(IndexSpan { symbol, kind, first_ms: ms, last_ms: ms, count: 1 }),
+            }
+        }
+        Self { path: rel, bytes, modified_ms, spans }
+    }
+
+    fn span(&self, symbol: &str, kind: SpanKind) -> Option<&IndexSpan> {
+        self.spans.iter().find(|s| s.kind == kind && s.symbol == symbol)
+    }
+}
+
+// file đã decode, event sort theo timestamp để binary search
+struct FileCache {
+    files: HashMap<PathBuf, Arc<Vec<MarketData>>>,
+    order: VecDeque<PathBuf>,
+}
+
+// Truy vấn ngẫu nhiên theo thời gian trên thư mục recording (tick / parquet):
+// index giữ khoảng thời gian của từng file nên mỗi query chỉ đọc vài file liên quan
+pub struct HistoricalStore {
+    root: PathBuf,
+    entries: Vec<IndexEntry>,
+    cache: Mutex<FileCache>,
+}
+
+impl HistoricalStore {
+    // nạp index có sẵn, index lại file mới / đã đổi rồi lưu lại
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self, RecorderError> {
+        let root = root.into();
+        let entries = match fs::read(root.join(INDEX_FILE)) {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+                warn!(root = %root.display(), error = %e, "index unreadable, rebuilding");
+                Vec::new()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let mut store = Self {
+            root,
+            entries,
+            cache: Mutex::new(FileCache { files: HashMap::new(), order: VecDeque::new() }),
+        };
+        store.refresh()?;
+        Ok(store)
+    }
+
+    pub fn entries(&self) -> &[IndexEntry] {
+        &self.entries
+    }
+
+    // quét lại root, dùng khi recorder vẫn đang ghi. Trả về số file được index lại
+    pub fn refresh(&mut self) -> Result<usize, RecorderError> {
+        let mut old: HashMap<PathBuf, IndexEntry> = self.entries.drain(..).map(|e| (e.path.clone(), e)).collect();
+        let mut reindexed = 0;
+        for path in reader::list_files(&self.root)? {
+            let rel = path.strip_prefix(&self.root).unwrap_or(&path).to_path_buf();
+            let meta = fs::metadata(&path)?;
+            let modified_ms = meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_millis() as i64);
+            if let Some(entry) = old.remove(&rel)
+                && entry.bytes == meta.len()
+                && entry.modified_ms == modified_ms
+            {
+                self.entries.push(entry);
+                continue;
+            }
+            // file đang ghi dở (vd. gzip chưa có trailer) thì bỏ qua, lần refresh sau đọc lại
+            match reader::read_file(&path) {
+                Ok(events) => {
+                    self.entries.push(IndexEntry::build(rel, meta.len(), modified_ms, &events));
+                    reindexed += 1;
+                }
+                Err(e) => warn!(path = %path.display(), error = %e, "skip unreadable recording"),
+            }
+        }
+        if reindexed > 0 || !old.is_empty() {
+            self.invalidate();
+            self.save_index()?;
+        }
+        Ok(reindexed)
+    }
+
+    fn save_index(&self) -> Result<(), RecorderError> {
+        let path = self.root.join(INDEX_FILE);
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec(&self.entries)?)?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    fn invalidate(&self) {
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.files.clear();
+            cache.order.clear();
+        }
+    }
+
+    fn load(&self, rel: &Path) -> Result<Arc<Vec<MarketData>>, RecorderError> {
+        if let Some(events) = self.cache.lock().ok().and_then(|c| c.files.get(rel).cloned()) {
+            return Ok(events);
+        }
+        let mut events = reader::read_file(&self.root.join(rel))?;
+        events.sort_by_key(MarketData::timestamp);
+        let events = Arc::new(events);
+        if let Ok(mut cache) = self.cache.lock() {
+            if cache.order.len() >= CACHE_FILES
+                && let Some(evicted) = cache.order.pop_front()
+            {
+                cache.files.remove(&evicted);
+            }
+            cache.order.push_back(rel.to_path_buf());
+            cache.files.insert(rel.to_path_buf(), events.clone());
+        }
+        Ok(events)
+    }
+
+    // Book mới nhất của `symbol` có timestamp <= `ts`
+    pub fn book_at(&self, symbol: &str, ts: DateTime<Utc>) -> Result<Option<OrderbookSnapshot>, RecorderError> {
+        let symbol = symbol.to_uppercase();
+        let ms = ts.timestamp_millis();
+        // file bắt đầu muộn nhất trước, file đầu tiên có book <= ts là đáp án
+        let mut candidates: Vec<(&IndexEntry, i64)> = self
+            .entries
+            .iter()
+            .filter_map(|e| e.span(&symbol, SpanKind::Book).filter(|s| s.first_ms <= ms).map(|s| (e, s.last_ms.min(ms))))
+            .collect();
+        candidates.sort_by_key(|(_, latest)| std::cmp::Reverse(*latest));
+
+        let mut best: Option<Arc<OrderbookSnapshot>> = None;
+        for (entry, latest) in candidates {
+            if best.as_ref().is_some_and(|b| b.timestamp.timestamp_millis() >= latest) {
+                break;
+            }
+            let events = self.load(&entry.path)?;
+            let end = events.partition_point(|ev| ev.timestamp() <= ts);
+            let found = events[..end].iter().rev().find_map(|ev| match ev {
+                MarketData::Orderbook { symbol: s, snap } if s.eq_ignore_ascii_case(&symbol) => Some(snap.clone()),
+                _ => None,
+            });
+            if let Some(snap) = found
+                && best.as_ref().is_none_or(|b| snap.timestamp > b.timestamp)
+            {
+                best = Some(snap);
+            }
+        }
+        Ok(best.map(|snap| (*snap).clone()))
+    }
+
+    // Trade của `symbol` trong [from, to], theo thời gian
+    pub fn trades_between(&self, symbol: &str, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<Trade>, RecorderError> {
+        Ok(self
+            .events_between(symbol, from, to)?
+            .into_iter()
+            .filter_map(|ev| match ev {
+                MarketData::Trade(t) => Some(t),
+                _ => None,
+            })
+            .collect())
+    }
+
+    // Mọi event (book + trade) của `symbol` trong [from, to], đưa thẳng vào Replayer::new
+    pub fn events_between(&self, symbol: &str, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<MarketData>, RecorderError> {
+        let symbol = symbol.to_uppercase();
+        let (from_ms, to_ms) = (from.timestamp_millis(), to.timestamp_millis());
+        let mut out = Vec::new();
+        for entry in &self.entries {
+            let overlaps = entry
+                .spans
+                .iter()
+                .any(|s| s.symbol == symbol && s.first_ms <= to_ms && s.last_ms >= from_ms);
+            if !overlaps {
+                continue;
+            }
+            let events = self.load(&entry.path)?;
+            let start = events.partition_point(|ev| ev.timestamp() < from);
+            let end = events.partition_point(|ev| ev.timestamp() <= to);
+            out.extend(events[start..end].iter().filter(|ev| ev.symbol().eq_ignore_ascii_case(&symbol)).cloned());
+        }
+        out.sort_by_key(MarketData::timestamp);
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{orderbook::Side, trade::TradeSide};
+    use crate::recorder::{
+        parquet::{ParquetConfig, ParquetRecorder},
+        tick::{TickRecorder, TickRecorderConfig},
+    };
+    use rust_decimal_macros::dec;
+
+    fn at(ms: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp_millis(ms).unwrap()
+    }
+
+    fn book(ms: i64, bid_qty: rust_decimal::Decimal) -> OrderbookSnapshot {
+        let mut ob = OrderbookSnapshot::new();
+        ob.set_level(Side::Bid, dec!(100), bid_qty);
+        ob.set_level(Side::Ask, dec!(101), dec!(1));
+        ob.timestamp = at(ms);
+        ob
+    }
+
+    fn trade(symbol: &str, ms: i64) -> Trade {
+        Trade { symbol: symbol.into(), trade_id: ms as u64, price: 100.5, qty: 1.0, side: TradeSide::Buy, timestamp: at(ms) }
+    }
+
+    #[test]
+    fn test_book_at_and_trades_between_across_files() {
+        let dir = tempfile::tempdir().unwrap();
+        // tick file xoay mỗi 60s -> 2 file book + 2 file trade, thêm parquet cho ethusdt
+        let mut recorder = TickRecorder::new(TickRecorderConfig {
+            root: dir.path().join("ticks"),
+            rotate_every_secs: Some(60),
+            ..Default::default()
+        });
+        for (ms, qty) in [(0, dec!(1)), (30_000, dec!(2)), (61_000, dec!(3))] {
+            recorder.record_orderbook("btcusdt", &book(ms, qty)).unwrap();
+        }
+        for ms in [10_000, 40_000, 70_000] {
+            recorder.record_trade(&trade("BTCUSDT", ms)).unwrap();
+        }
+        recorder.close().unwrap();
+        let mut parquet = ParquetRecorder::new(ParquetConfig::new(dir.path().join("parquet")));
+        parquet.record_trade(&trade("ETHUSDT", 20_000)).unwrap();
+        parquet.close().unwrap();
+
+        let mut store = HistoricalStore::open(dir.path()).unwrap();
+        assert_eq!(store.entries().len(), 5);
+        assert!(dir.path().join(INDEX_FILE).exists());
+
+        let qty_at = |ms| store.book_at("btcusdt", at(ms)).unwrap().map(|b| b.best_bid().unwrap().1);
+        assert_eq!(qty_at(-1), None);
+        assert_eq!(qty_at(45_000), Some(dec!(2)));
+        // khoảng trống giữa 2 file vẫn lấy book cuối của file trước
+        assert_eq!(qty_at(60_500), Some(dec!(2)));
+        assert_eq!(qty_at(99_000), Some(dec!(3)));
+
+        let ids: Vec<u64> = store.trades_between("BTCUSDT", at(10_000), at(70_000)).unwrap().iter().map(|t| t.trade_id).collect();
+        assert_eq!(ids, vec![10_000, 40_000, 70_000]);
+        assert_eq!(store.trades_between("ethusdt", at(0), at(30_000)).unwrap().len(), 1);
+        assert_eq!(store.events_between("btcusdt", at(25_000), at(65_000)).unwrap().len(), 3);
+
+        // mở lại dùng index đã lưu, không đọc lại file
+        assert_eq!(store.refresh().unwrap(), 0);
+        let reopened = HistoricalStore::open(dir.path()).unwrap();
+        assert_eq!(reopened.entries(), store.entries());
+    }
+}
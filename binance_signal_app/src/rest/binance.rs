@@ -0,0 +1,880 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::{Method, StatusCode};
+use rust_decimal::Decimal;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::Sha256;
+use std::{borrow::Cow, sync::Arc};
+use tracing::{debug, warn};
+
+use crate::core::{
+    clock::ClockSync,
+    order::{OrderSide, OrderStatus, OrderType},
+    symbol::SymbolInfo,
+};
+use crate::supervisor::Shutdown;
+use crate::net;
+use super::{
+    endpoints::EndpointPool,
+    binance_futures::{FUTURES_BASE_URL, FUTURES_TESTNET_BASE_URL},
+    rate_limit::{RateLimitStatus, RateLimiter, RequestCost},
+    RestError,
+};
+
+pub const BASE_URL: &str = "https://api.binance.com";
+pub const TESTNET_BASE_URL: &str = "https://testnet.binance.vision";
+const DEFAULT_RECV_WINDOW: u64 = 5_000;
+const USER_STREAM_COST: RequestCost = RequestCost::weight(2);
+
+// `[binance] network`: testnet dùng key riêng tạo trên trang testnet, số dư giả.
+// Chỉ có spot + USDⓈ-M futures. `custom` trỏ tới server giả lập / proxy
+// (vd. `testutil::MockBinance`), spot và futures dùng chung URL.
+// `Pool` dựng từ `[binance] endpoints` (không đọc từ config trực tiếp): spot đi theo
+// endpoint hiện tại của pool, futures vẫn như mainnet
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BinanceNetwork {
+    #[default]
+    Mainnet,
+    Testnet,
+    Custom { rest: String, ws: String },
+    #[serde(skip)]
+    Pool(Arc<EndpointPool>),
+}
+
+impl BinanceNetwork {
+    pub fn rest_url(&self) -> Cow<'_, str> {
+        match self {
+            BinanceNetwork::Mainnet => BASE_URL.into(),
+            BinanceNetwork::Testnet => TESTNET_BASE_URL.into(),
+            BinanceNetwork::Custom { rest, .. } => rest.into(),
+            BinanceNetwork::Pool(pool) => pool.current().rest.as_str().into(),
+        }
+    }
+
+    // gốc của WS spot, thêm "/ws/<stream>" hoặc "/stream?streams=..."
+    pub fn ws_url(&self) -> Cow<'_, str> {
+        match self {
+            BinanceNetwork::Mainnet => "wss://stream.binance.com:9443".into(),
+            BinanceNetwork::Testnet => "wss://stream.testnet.binance.vision".into(),
+            BinanceNetwork::Custom { ws, .. } => ws.into(),
+            BinanceNetwork::Pool(pool) => pool.current().ws.as_str().into(),
+        }
+    }
+
+    // request ký + user data stream: bỏ qua endpoint chỉ có market data
+    pub fn trading_rest_url(&self) -> Cow<'_, str> {
+        match self {
+            BinanceNetwork::Pool(pool) => pool.trading().rest.as_str().into(),
+            _ => self.rest_url(),
+        }
+    }
+
+    pub fn trading_ws_url(&self) -> Cow<'_, str> {
+        match self {
+            BinanceNetwork::Pool(pool) => pool.trading().ws.as_str().into(),
+            _ => self.ws_url(),
+        }
+    }
+
+    // connector không kết nối được tới `url` (dựng từ `ws_url()`): chuyển endpoint nếu
+    // pool vẫn đang trỏ vào đó
+    pub fn ws_failed(&self, url: &str) {
+        if let BinanceNetwork::Pool(pool) = self {
+            let current = pool.current();
+            if url.starts_with(current.ws.as_str()) {
+                pool.report_failure(&current.name);
+            }
+        }
+    }
+
+    pub fn pool(&self) -> Option<&Arc<EndpointPool>> {
+        match self {
+            BinanceNetwork::Pool(pool) => Some(pool),
+            _ => None,
+        }
+    }
+
+    pub fn futures_rest_url(&self) -> &str {
+        match self {
+            BinanceNetwork::Mainnet | BinanceNetwork::Pool(_) => FUTURES_BASE_URL,
+            BinanceNetwork::Testnet => FUTURES_TESTNET_BASE_URL,
+            BinanceNetwork::Custom { rest, .. } => rest,
+        }
+    }
+
+    pub fn futures_ws_url(&self) -> &str {
+        match self {
+            BinanceNetwork::Mainnet | BinanceNetwork::Pool(_) => "wss://fstream.binance.com",
+            BinanceNetwork::Testnet => "wss://fstream.binancefuture.com",
+            BinanceNetwork::Custom { ws, .. } => ws,
+        }
+    }
+}
+
+// `[venue.accounts.<name>] market`: spot hoặc USDⓈ-M futures (perp). Cùng key / cách ký,
+// khác host, path `/fapi` và vài field của response / user stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BinanceMarket {
+    #[default]
+    Spot,
+    UsdM,
+}
+
+impl BinanceMarket {
+    // gốc REST của request ký trên `network`
+    pub fn rest_url(self, network: &BinanceNetwork) -> Cow<'_, str> {
+        match self {
+            BinanceMarket::Spot => network.trading_rest_url(),
+            BinanceMarket::UsdM => network.futures_rest_url().into(),
+        }
+    }
+
+    // gốc WS của user data stream, thêm "/ws/<listenKey>"
+    pub fn ws_url(self, network: &BinanceNetwork) -> Cow<'_, str> {
+        match self {
+            BinanceMarket::Spot => network.trading_ws_url(),
+            BinanceMarket::UsdM => network.futures_ws_url().into(),
+        }
+    }
+
+    fn path(self, spot: &'static str, usdm: &'static str) -> &'static str {
+        match self {
+            BinanceMarket::Spot => spot,
+            BinanceMarket::UsdM => usdm,
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+pub struct BinanceCredentials {
+    pub api_key: String,
+    pub api_secret: String,
+}
+
+impl std::fmt::Debug for BinanceCredentials {
+    // không in secret ra log
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BinanceCredentials")
+            .field("api_key", &self.api_key)
+            .field("api_secret", &"***")
+            .finish()
+    }
+}
+
+impl BinanceCredentials {
+    pub fn new(api_key: &str, api_secret: &str) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+            api_secret: api_secret.to_string(),
+        }
+    }
+
+    // BINANCE_API_KEY / BINANCE_API_SECRET
+    pub fn from_env() -> Option<Self> {
+        Self::from_env_vars("BINANCE_API_KEY", "BINANCE_API_SECRET")
+    }
+
+    // key của account / subaccount khác, tên biến lấy từ `[venue.accounts.<name>]`
+    pub fn from_env_vars(key_var: &str, secret_var: &str) -> Option<Self> {
+        Some(Self {
+            api_key: std::env::var(key_var).ok()?,
+            api_secret: std::env::var(secret_var).ok()?,
+        })
+    }
+
+    // hex(HMAC-SHA256(secret, query))
+    pub fn sign(&self, query: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.api_secret.as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(query.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum TimeInForce {
+    #[serde(rename = "GTC")]
+    Gtc,
+    #[serde(rename = "IOC")]
+    Ioc,
+    #[serde(rename = "FOK")]
+    Fok,
+    // chỉ futures: post-only / hết hạn theo thời gian
+    #[serde(rename = "GTX")]
+    Gtx,
+    #[serde(rename = "GTD")]
+    Gtd,
+}
+
+impl TimeInForce {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TimeInForce::Gtc => "GTC",
+            TimeInForce::Ioc => "IOC",
+            TimeInForce::Fok => "FOK",
+            TimeInForce::Gtx => "GTX",
+            TimeInForce::Gtd => "GTD",
+        }
+    }
+}
+
+fn side_str(side: OrderSide) -> &'static str {
+    match side {
+        OrderSide::Buy => "BUY",
+        OrderSide::Sell => "SELL",
+    }
+}
+
+fn type_str(order_type: OrderType) -> &'static str {
+    match order_type {
+        OrderType::Limit => "LIMIT",
+        OrderType::Market => "MARKET",
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewOrderRequest {
+    pub symbol: String,
+    pub side: OrderSide,
+    pub order_type: OrderType,
+    pub time_in_force: Option<TimeInForce>,
+    pub quantity: Option<Decimal>,
+    // market order theo số quote (vd. mua 100 USDT BTC)
+    pub quote_order_qty: Option<Decimal>,
+    pub price: Option<Decimal>,
+    pub new_client_order_id: Option<String>,
+}
+
+impl NewOrderRequest {
+    pub fn limit(symbol: &str, side: OrderSide, price: Decimal, quantity: Decimal) -> Self {
+        Self {
+            symbol: symbol.to_uppercase(),
+            side,
+            order_type: OrderType::Limit,
+            time_in_force: Some(TimeInForce::Gtc),
+            quantity: Some(quantity),
+            quote_order_qty: None,
+            price: Some(price),
+            new_client_order_id: None,
+        }
+    }
+
+    pub fn market(symbol: &str, side: OrderSide, quantity: Decimal) -> Self {
+        Self {
+            symbol: symbol.to_uppercase(),
+            side,
+            order_type: OrderType::Market,
+            time_in_force: None,
+            quantity: Some(quantity),
+            quote_order_qty: None,
+            price: None,
+            new_client_order_id: None,
+        }
+    }
+
+    pub fn with_client_order_id(mut self, id: &str) -> Self {
+        self.new_client_order_id = Some(id.to_string());
+        self
+    }
+
+    pub fn with_time_in_force(mut self, tif: TimeInForce) -> Self {
+        self.time_in_force = Some(tif);
+        self
+    }
+
+    fn to_params(&self, market: BinanceMarket) -> Vec<(&'static str, String)> {
+        let mut params = vec![
+            ("symbol", self.symbol.clone()),
+            ("side", side_str(self.side).to_string()),
+            ("type", type_str(self.order_type).to_string()),
+        ];
+        if let Some(tif) = self.time_in_force {
+            params.push(("timeInForce", tif.as_str().to_string()));
+        }
+        if let Some(q) = self.quantity {
+            params.push(("quantity", q.normalize().to_string()));
+        }
+        if let Some(q) = self.quote_order_qty {
+            params.push(("quoteOrderQty", q.normalize().to_string()));
+        }
+        if let Some(p) = self.price {
+            params.push(("price", p.normalize().to_string()));
+        }
+        if let Some(id) = &self.new_client_order_id {
+            params.push(("newClientOrderId", id.clone()));
+        }
+        // FULL để response có luôn danh sách fill, futures không có FULL (fill chỉ qua user stream)
+        params.push(("newOrderRespType", market.path("FULL", "RESULT").to_string()));
+        params
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderFill {
+    pub price: Decimal,
+    pub qty: Decimal,
+    pub commission: Decimal,
+    pub commission_asset: String,
+    #[serde(default)]
+    pub trade_id: i64,
+}
+
+// Dùng chung cho response của place / cancel / openOrders
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderResponse {
+    pub symbol: String,
+    pub order_id: u64,
+    pub client_order_id: String,
+    pub price: Decimal,
+    pub orig_qty: Decimal,
+    pub executed_qty: Decimal,
+    // futures: `cumQuote`
+    #[serde(alias = "cumQuote")]
+    pub cummulative_quote_qty: Decimal,
+    pub status: OrderStatus,
+    #[serde(default)]
+    pub time_in_force: Option<TimeInForce>,
+    #[serde(rename = "type")]
+    pub order_type: OrderType,
+    pub side: OrderSide,
+    // transactTime với place/cancel, time với openOrders, updateTime với futures
+    #[serde(default, alias = "transactTime", alias = "updateTime")]
+    pub time: i64,
+    #[serde(default)]
+    pub fills: Vec<OrderFill>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Balance {
+    pub asset: String,
+    pub free: Decimal,
+    pub locked: Decimal,
+}
+
+impl Balance {
+    pub fn total(&self) -> Decimal {
+        self.free + self.locked
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountInfo {
+    pub can_trade: bool,
+    pub can_withdraw: bool,
+    pub can_deposit: bool,
+    pub update_time: i64,
+    pub balances: Vec<Balance>,
+}
+
+// `/fapi/v2/balance`: `balance` là số dư ví, đã gồm phần đang làm margin
+#[derive(Debug, Deserialize)]
+struct FuturesBalance {
+    asset: String,
+    balance: Decimal,
+}
+
+// Các filter cần để làm tròn / kiểm tra order, filter khác bỏ qua
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "filterType", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SymbolFilter {
+    #[serde(rename_all = "camelCase")]
+    PriceFilter { min_price: Decimal, max_price: Decimal, tick_size: Decimal },
+    #[serde(rename_all = "camelCase")]
+    LotSize { min_qty: Decimal, max_qty: Decimal, step_size: Decimal },
+    // filter cũ, sàn đang chuyển dần sang NOTIONAL. Futures vẫn dùng, field là `notional`
+    #[serde(rename_all = "camelCase")]
+    MinNotional {
+        #[serde(alias = "notional")]
+        min_notional: Decimal,
+    },
+    #[serde(rename_all = "camelCase")]
+    Notional { min_notional: Decimal },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangeSymbol {
+    pub symbol: String,
+    pub status: String,
+    pub base_asset: String,
+    pub quote_asset: String,
+    #[serde(default)]
+    pub filters: Vec<SymbolFilter>,
+}
+
+impl From<&ExchangeSymbol> for SymbolInfo {
+    fn from(s: &ExchangeSymbol) -> Self {
+        let mut info = SymbolInfo {
+            symbol: s.symbol.clone(),
+            base_asset: s.base_asset.clone(),
+            quote_asset: s.quote_asset.clone(),
+            trading: s.status == "TRADING",
+            tick_size: Decimal::ZERO,
+            min_price: Decimal::ZERO,
+            max_price: Decimal::ZERO,
+            step_size: Decimal::ZERO,
+            min_qty: Decimal::ZERO,
+            max_qty: Decimal::ZERO,
+            min_notional: Decimal::ZERO,
+        };
+        for filter in &s.filters {
+            match *filter {
+                SymbolFilter::PriceFilter { min_price, max_price, tick_size } => {
+                    (info.min_price, info.max_price, info.tick_size) = (min_price, max_price, tick_size);
+                }
+                SymbolFilter::LotSize { min_qty, max_qty, step_size } => {
+                    (info.min_qty, info.max_qty, info.step_size) = (min_qty, max_qty, step_size);
+                }
+                SymbolFilter::MinNotional { min_notional } | SymbolFilter::Notional { min_notional } => {
+                    info.min_notional = info.min_notional.max(min_notional);
+                }
+                SymbolFilter::Other => {}
+            }
+        }
+        info
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangeInfo {
+    pub server_time: i64,
+    pub symbols: Vec<ExchangeSymbol>,
+}
+
+impl ExchangeInfo {
+    pub fn symbol_infos(&self) -> Vec<SymbolInfo> {
+        self.symbols.iter().map(SymbolInfo::from).collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListenKey {
+    listen_key: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiErrorBody {
+    code: i64,
+    msg: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ServerTime {
+    #[serde(rename = "serverTime")]
+    server_time: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct BinanceRestClient {
+    http: reqwest::Client,
+    base_url: String,
+    credentials: Option<BinanceCredentials>,
+    recv_window: u64,
+    // clone client dùng chung limiter
+    limiter: Arc<RateLimiter>,
+    // timestamp của request ký lấy theo giờ sàn nếu có
+    clock: Option<ClockSync>,
+    market: BinanceMarket,
+}
+
+impl BinanceRestClient {
+    pub fn new(credentials: Option<BinanceCredentials>) -> Self {
+        Self {
+            http: net::http_client(),
+            base_url: BASE_URL.to_string(),
+            credentials,
+            recv_window: DEFAULT_RECV_WINDOW,
+            limiter: Arc::new(RateLimiter::default()),
+            clock: None,
+            market: BinanceMarket::Spot,
+        }
+    }
+
+    // path endpoint theo market, base URL đặt riêng (`BinanceMarket::rest_url`)
+    pub fn with_market(mut self, market: BinanceMarket) -> Self {
+        self.market = market;
+        self
+    }
+
+    pub fn market(&self) -> BinanceMarket {
+        self.market
+    }
+
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    // ms, Binance giới hạn tối đa 60000
+    pub fn with_recv_window(mut self, recv_window: u64) -> Self {
+        self.recv_window = recv_window.min(60_000);
+        self
+    }
+
+    // nhiều client cùng IP / account thì truyền cùng một limiter
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.limiter = limiter;
+        self
+    }
+
+    pub fn with_clock(mut self, clock: ClockSync) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn rate_limiter(&self) -> &Arc<RateLimiter> {
+        &self.limiter
+    }
+
+    pub fn rate_limit_status(&self) -> RateLimitStatus {
+        self.limiter.status()
+    }
+
+    pub async fn server_time(&self) -> Result<DateTime<Utc>, RestError> {
+        let resp: ServerTime = self.public(Method::GET, self.market.path("/api/v3/time", "/fapi/v1/time"), vec![], RequestCost::weight(1)).await?;
+        DateTime::from_timestamp_millis(resp.server_time)
+            .ok_or_else(|| RestError::Api { status: 200, code: 0, msg: format!("invalid serverTime {}", resp.server_time) })
+    }
+
+    // Đo một lần `/api/v3/time` và cập nhật `clock`
+    pub async fn sync_clock(&self, clock: &ClockSync) -> Result<(), RestError> {
+        let sent = Utc::now();
+        let server = self.server_time().await?;
+        let sample = clock.record(sent, server, Utc::now());
+        debug!(offset_ms = sample.offset_ms, rtt_ms = sample.rtt_ms, drift_ppm = clock.status().drift_ppm, "clock sample");
+        Ok(())
+    }
+
+    // Sync định kỳ tới khi shutdown, lỗi chỉ log lại và giữ ước lượng cũ
+    pub async fn run_clock_sync(self, clock: ClockSync, every: std::time::Duration, mut shutdown: Shutdown) {
+        let mut interval = tokio::time::interval(every);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.wait() => break,
+            }
+            if let Err(e) = self.sync_clock(&clock).await {
+                warn!(error = %e, "clock sync failed");
+            }
+        }
+    }
+
+    // symbols rỗng = mọi symbol (response vài MB). Futures không lọc được, luôn trả mọi symbol
+    pub async fn exchange_info(&self, symbols: &[String]) -> Result<ExchangeInfo, RestError> {
+        if self.market == BinanceMarket::UsdM {
+            return self.public(Method::GET, "/fapi/v1/exchangeInfo", vec![], RequestCost::weight(1)).await;
+        }
+        let params = match symbols {
+            [] => vec![],
+            _ => {
+                let list: Vec<String> = symbols.iter().map(|s| format!("\"{}\"", s.to_uppercase())).collect();
+                vec![("symbols", format!("[{}]", list.join(",")))]
+            }
+        };
+        self.public(Method::GET, "/api/v3/exchangeInfo", params, RequestCost::weight(20)).await
+    }
+
+    fn order_path(&self) -> &'static str {
+        self.market.path("/api/v3/order", "/fapi/v1/order")
+    }
+
+    pub async fn place_order(&self, req: &NewOrderRequest) -> Result<OrderResponse, RestError> {
+        self.signed(Method::POST, self.order_path(), req.to_params(self.market), RequestCost::order(1)).await
+    }
+
+    pub async fn cancel_order(&self, symbol: &str, order_id: u64) -> Result<OrderResponse, RestError> {
+        let params = vec![("symbol", symbol.to_uppercase()), ("orderId", order_id.to_string())];
+        self.signed(Method::DELETE, self.order_path(), params, RequestCost::weight(1)).await
+    }
+
+    pub async fn cancel_by_client_id(&self, symbol: &str, client_order_id: &str) -> Result<OrderResponse, RestError> {
+        let params = vec![
+            ("symbol", symbol.to_uppercase()),
+            ("origClientOrderId", client_order_id.to_string()),
+        ];
+        self.signed(Method::DELETE, self.order_path(), params, RequestCost::weight(1)).await
+    }
+
+    // huỷ mọi open order của symbol trong một request
+    pub async fn cancel_open_orders(&self, symbol: &str) -> Result<(), RestError> {
+        let params = vec![("symbol", symbol.to_uppercase())];
+        let path = self.market.path("/api/v3/openOrders", "/fapi/v1/allOpenOrders");
+        let _: serde_json::Value = self.signed(Method::DELETE, path, params, RequestCost::weight(1)).await?;
+        Ok(())
+    }
+
+    pub async fn query_order(&self, symbol: &str, order_id: u64) -> Result<OrderResponse, RestError> {
+        let params = vec![("symbol", symbol.to_uppercase()), ("orderId", order_id.to_string())];
+        self.signed(Method::GET, self.order_path(), params, RequestCost::weight(4)).await
+    }
+
+    // symbol = None lấy open order của mọi symbol (weight cao hơn nhiều)
+    pub async fn open_orders(&self, symbol: Option<&str>) -> Result<Vec<OrderResponse>, RestError> {
+        let weight = match self.market {
+            BinanceMarket::Spot => if symbol.is_some() { 6 } else { 80 },
+            BinanceMarket::UsdM => if symbol.is_some() { 1 } else { 40 },
+        };
+        let params = symbol.map(|s| vec![("symbol", s.to_uppercase())]).unwrap_or_default();
+        let path = self.market.path("/api/v3/openOrders", "/fapi/v1/openOrders");
+        self.signed(Method::GET, path, params, RequestCost::weight(weight)).await
+    }
+
+    // chỉ spot, số dư futures lấy qua `balances`
+    pub async fn account(&self) -> Result<AccountInfo, RestError> {
+        self.signed(Method::GET, "/api/v3/account", vec![], RequestCost::weight(20)).await
+    }
+
+    // chỉ các asset có số dư khác 0. Futures: số dư ví margin, không tách phần đang làm margin
+    pub async fn balances(&self) -> Result<Vec<Balance>, RestError> {
+        let balances = match self.market {
+            BinanceMarket::Spot => self.account().await?.balances,
+            BinanceMarket::UsdM => {
+                let raw: Vec<FuturesBalance> = self.signed(Method::GET, "/fapi/v2/balance", vec![], RequestCost::weight(5)).await?;
+                raw.into_iter().map(|b| Balance { asset: b.asset, free: b.balance, locked: Decimal::ZERO }).collect()
+            }
+        };
+        Ok(balances.into_iter().filter(|b| !b.total().is_zero()).collect())
+    }
+
+    fn listen_key_path(&self) -> &'static str {
+        self.market.path("/api/v3/userDataStream", "/fapi/v1/listenKey")
+    }
+
+    // futures chỉ có một listenKey mỗi account, keepalive / close không cần truyền key
+    fn listen_key_params(&self, listen_key: &str) -> Vec<(&'static str, String)> {
+        match self.market {
+            BinanceMarket::Spot => vec![("listenKey", listen_key.to_string())],
+            BinanceMarket::UsdM => vec![],
+        }
+    }
+
+    // listenKey cho user data stream, hết hạn sau 60 phút nếu không keepalive
+    pub async fn create_listen_key(&self) -> Result<String, RestError> {
+        let resp: ListenKey = self.api_key_only(Method::POST, self.listen_key_path(), vec![], USER_STREAM_COST).await?;
+        Ok(resp.listen_key)
+    }
+
+    pub async fn keepalive_listen_key(&self, listen_key: &str) -> Result<(), RestError> {
+        let params = self.listen_key_params(listen_key);
+        let _: serde_json::Value = self.api_key_only(Method::PUT, self.listen_key_path(), params, USER_STREAM_COST).await?;
+        Ok(())
+    }
+
+    pub async fn close_listen_key(&self, listen_key: &str) -> Result<(), RestError> {
+        let params = self.listen_key_params(listen_key);
+        let _: serde_json::Value = self.api_key_only(Method::DELETE, self.listen_key_path(), params, USER_STREAM_COST).await?;
+        Ok(())
+    }
+
+    // endpoint MARKET_DATA: không cần api key
+    pub(super) async fn public<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        params: Vec<(&'static str, String)>,
+        cost: RequestCost,
+    ) -> Result<T, RestError> {
+        let mut url = format!("{}{}", self.base_url, path);
+        if !params.is_empty() {
+            url.push('?');
+            url.push_str(&encode_query(&params));
+        }
+        self.execute(self.http.request(method, &url), cost).await
+    }
+
+    // endpoint USER_STREAM: chỉ cần header api key, không ký
+    async fn api_key_only<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        params: Vec<(&'static str, String)>,
+        cost: RequestCost,
+    ) -> Result<T, RestError> {
+        let credentials = self.credentials.as_ref().ok_or(RestError::MissingCredentials)?;
+        let mut url = format!("{}{}", self.base_url, path);
+        if !params.is_empty() {
+            url.push('?');
+            url.push_str(&encode_query(&params));
+        }
+        let req = self.http.request(method, &url).header("X-MBX-APIKEY", &credentials.api_key);
+        self.execute(req, cost).await
+    }
+
+    // Thêm timestamp + recvWindow, ký toàn bộ query string
+    fn signed_query(&self, params: Vec<(&'static str, String)>, timestamp: i64) -> Result<String, RestError> {
+        let credentials = self.credentials.as_ref().ok_or(RestError::MissingCredentials)?;
+        let mut query = encode_query(&params);
+        if !query.is_empty() {
+            query.push('&');
+        }
+        query.push_str(&format!("recvWindow={}&timestamp={}", self.recv_window, timestamp));
+        let signature = credentials.sign(&query);
+        Ok(format!("{}&signature={}", query, signature))
+    }
+
+    async fn signed<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        params: Vec<(&'static str, String)>,
+        cost: RequestCost,
+    ) -> Result<T, RestError> {
+        if self.credentials.is_none() {
+            return Err(RestError::MissingCredentials);
+        }
+        // chờ budget trước rồi mới lấy timestamp, tránh vượt recvWindow
+        self.limiter.acquire(cost).await;
+        let now = self.clock.as_ref().map_or_else(Utc::now, ClockSync::exchange_now);
+        let query = self.signed_query(params, now.timestamp_millis())?;
+        let api_key = self
+            .credentials
+            .as_ref()
+            .map(|c| c.api_key.clone())
+            .unwrap_or_default();
+
+        let url = format!("{}{}?{}", self.base_url, path, query);
+        let resp = self
+            .http
+            .request(method, &url)
+            .header("X-MBX-APIKEY", api_key)
+            .send()
+            .await?;
+        self.limiter.on_response(resp.status().as_u16(), resp.headers());
+        decode(resp.status(), &resp.text().await?)
+    }
+
+    // mọi request đi qua limiter, đồng bộ budget theo header của response
+    async fn execute<T: DeserializeOwned>(&self, req: reqwest::RequestBuilder, cost: RequestCost) -> Result<T, RestError> {
+        self.limiter.acquire(cost).await;
+        let resp = req.send().await?;
+        self.limiter.on_response(resp.status().as_u16(), resp.headers());
+        decode(resp.status(), &resp.text().await?)
+    }
+}
+
+fn encode_query(params: &[(&str, String)]) -> String {
+    params
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, urlencode(v)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+// giá trị param của Binance chủ yếu là ký tự an toàn; encode phần còn lại
+fn urlencode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => out.push(b as char),
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
+fn decode<T: DeserializeOwned>(status: StatusCode, body: &str) -> Result<T, RestError> {
+    if !status.is_success() {
+        return Err(match serde_json::from_str::<ApiErrorBody>(body) {
+            Ok(e) => RestError::Api { status: status.as_u16(), code: e.code, msg: e.msg },
+            Err(_) => RestError::Api { status: status.as_u16(), code: 0, msg: body.to_string() },
+        });
+    }
+    Ok(serde_json::from_str(body)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_signature_matches_binance_docs() {
+        // ví dụ trong tài liệu SIGNED endpoint của Binance
+        let creds = BinanceCredentials::new(
+            "vmPUZE6mv9SD5VNHk4HlWFsOr6aKE2zvsw0MuIgwCIPy6utIco14y7Ju91duEh8A",
+            "NhqPtmdSJYdKjVHjA7PZj4Mge3R5YNiP1e3UZjInClVN65XAbvqqM6A7H5fATj0j",
+        );
+        let query = "symbol=LTCBTC&side=BUY&type=LIMIT&timeInForce=GTC&quantity=1&price=0.1&recvWindow=5000&timestamp=1499827319559";
+        assert_eq!(creds.sign(query), "c8db56825ae71d6d79447849e617115f4a920fa2acdcab2b053c4b2838bd6b71");
+        assert!(!format!("{:?}", creds).contains("NhqPtmdS"));
+    }
+
+    #[test]
+    fn test_signed_query_layout() {
+        let client = BinanceRestClient::new(Some(BinanceCredentials::new("key", "secret"))).with_recv_window(10_000);
+        let req = NewOrderRequest::limit("ltcbtc", OrderSide::Buy, dec!(0.10), dec!(1.000)).with_client_order_id("my order");
+        let query = client.signed_query(req.to_params(BinanceMarket::Spot), 1499827319559).unwrap();
+        assert!(query.starts_with(
+            "symbol=LTCBTC&side=BUY&type=LIMIT&timeInForce=GTC&quantity=1&price=0.1&newClientOrderId=my%20order&newOrderRespType=FULL&recvWindow=10000&timestamp=1499827319559&signature="
+        ));
+
+        let unsigned = BinanceRestClient::new(None);
+        assert!(matches!(unsigned.signed_query(vec![], 0), Err(RestError::MissingCredentials)));
+    }
+
+    #[test]
+    fn test_decode_order_and_error() {
+        let body = r#"{
+            "symbol": "BTCUSDT", "orderId": 28, "orderListId": -1,
+            "clientOrderId": "6gCrw2kRUAF9CvJDGP16IP", "transactTime": 1507725176595,
+            "price": "0.00000000", "origQty": "10.00000000", "executedQty": "10.00000000",
+            "cummulativeQuoteQty": "10.00000000", "status": "FILLED", "timeInForce": "GTC",
+            "type": "MARKET", "side": "SELL",
+            "fills": [{"price": "4000.00000000", "qty": "1.00000000", "commission": "4.00000000", "commissionAsset": "USDT", "tradeId": 56}]
+        }"#;
+        let order: OrderResponse = decode(StatusCode::OK, body).unwrap();
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!(order.order_type, OrderType::Market);
+        assert_eq!(order.time, 1507725176595);
+        assert_eq!(order.fills[0].price, dec!(4000));
+
+        // USDⓈ-M trả cumQuote / updateTime, không có fills
+        let body = r#"{
+            "clientOrderId": "testOrder", "cumQty": "0", "cumQuote": "0", "executedQty": "0", "orderId": 22542179,
+            "avgPrice": "0.00000", "origQty": "10", "price": "0", "reduceOnly": false, "side": "BUY", "positionSide": "SHORT",
+            "status": "NEW", "stopPrice": "9300", "closePosition": false, "symbol": "BTCUSDT", "timeInForce": "GTD",
+            "type": "MARKET", "origType": "TRAILING_STOP_MARKET", "updateTime": 1566818724722, "workingType": "CONTRACT_PRICE"
+        }"#;
+        let order: OrderResponse = decode(StatusCode::OK, body).unwrap();
+        assert_eq!((order.order_id, order.time, order.fills.len()), (22542179, 1566818724722, 0));
+
+        let err = decode::<OrderResponse>(StatusCode::BAD_REQUEST, r#"{"code":-1121,"msg":"Invalid symbol."}"#);
+        assert!(matches!(err, Err(RestError::Api { status: 400, code: -1121, .. })));
+    }
+
+    #[test]
+    fn test_decode_exchange_info() {
+        let body = r#"{
+            "timezone": "UTC", "serverTime": 1565246363776, "rateLimits": [], "exchangeFilters": [],
+            "symbols": [{
+                "symbol": "ETHBTC", "status": "TRADING", "baseAsset": "ETH", "baseAssetPrecision": 8,
+                "quoteAsset": "BTC", "quotePrecision": 8, "orderTypes": ["LIMIT", "MARKET"],
+                "filters": [
+                    {"filterType": "PRICE_FILTER", "minPrice": "0.00000100", "maxPrice": "100000.00000000", "tickSize": "0.00000100"},
+                    {"filterType": "LOT_SIZE", "minQty": "0.00100000", "maxQty": "100000.00000000", "stepSize": "0.00100000"},
+                    {"filterType": "ICEBERG_PARTS", "limit": 10},
+                    {"filterType": "NOTIONAL", "minNotional": "0.00010000", "applyMinToMarket": true,
+                     "maxNotional": "9000000.00000000", "applyMaxToMarket": false, "avgPriceMins": 5}
+                ]
+            }]
+        }"#;
+        let info: ExchangeInfo = decode(StatusCode::OK, body).unwrap();
+        let eth = &info.symbol_infos()[0];
+        assert!(eth.trading);
+        assert_eq!((eth.tick_size, eth.step_size, eth.min_notional), (dec!(0.000001), dec!(0.001), dec!(0.0001)));
+        assert_eq!(info.symbols[0].filters[2], SymbolFilter::Other);
+    }
+}
@@ -0,0 +1,112 @@
+use reqwest::Method;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+use super::{binance::BinanceRestClient, rate_limit::RequestCost, RestError};
+
+pub const FUTURES_BASE_URL: &str = "https://fapi.binance.com";
+pub const FUTURES_TESTNET_BASE_URL: &str = "https://testnet.binancefuture.com";
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenInterest {
+    pub symbol: String,
+    pub open_interest: Decimal,
+    pub time: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FundingRate {
+    pub symbol: String,
+    pub funding_rate: Decimal,
+    pub funding_time: i64,
+}
+
+// `lastFundingRate` thực ra là funding dự kiến của kỳ đang chạy
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PremiumIndex {
+    pub symbol: String,
+    pub mark_price: Decimal,
+    pub index_price: Decimal,
+    pub last_funding_rate: Decimal,
+    pub next_funding_time: i64,
+    pub time: i64,
+}
+
+// Market data public của USDⓈ-M futures (`/fapi`), dùng chung phần gửi request + rate limit với client spot
+#[derive(Debug, Clone)]
+pub struct BinanceFuturesRestClient {
+    inner: BinanceRestClient,
+}
+
+impl Default for BinanceFuturesRestClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BinanceFuturesRestClient {
+    pub fn new() -> Self {
+        Self { inner: BinanceRestClient::new(None).with_base_url(FUTURES_BASE_URL) }
+    }
+
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.inner = self.inner.with_base_url(base_url);
+        self
+    }
+
+    pub async fn open_interest(&self, symbol: &str) -> Result<OpenInterest, RestError> {
+        let params = vec![("symbol", symbol.to_uppercase())];
+        self.inner.public(Method::GET, "/fapi/v1/openInterest", params, RequestCost::weight(1)).await
+    }
+
+    // funding đã chốt, cũ -> mới
+    pub async fn funding_rates(&self, symbol: &str, limit: u32) -> Result<Vec<FundingRate>, RestError> {
+        let params = vec![("symbol", symbol.to_uppercase()), ("limit", limit.clamp(1, 1000).to_string())];
+        self.inner.public(Method::GET, "/fapi/v1/fundingRate", params, RequestCost::weight(1)).await
+    }
+
+    pub async fn premium_index(&self, symbol: &str) -> Result<PremiumIndex, RestError> {
+        let params = vec![("symbol", symbol.to_uppercase())];
+        self.inner.public(Method::GET, "/fapi/v1/premiumIndex", params, RequestCost::weight(1)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::Query, routing::get, Json, Router};
+    use rust_decimal_macros::dec;
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_open_interest_and_funding() {
+        let app = Router::new()
+            .route(
+                "/fapi/v1/openInterest",
+                get(|Query(q): Query<HashMap<String, String>>| async move {
+                    Json(serde_json::json!({"openInterest": "10659.509", "symbol": q["symbol"], "time": 1589437530011i64}))
+                }),
+            )
+            .route(
+                "/fapi/v1/fundingRate",
+                get(|| async {
+                    Json(serde_json::json!([
+                        {"symbol": "BTCUSDT", "fundingRate": "-0.03750000", "fundingTime": 1570608000000i64, "markPrice": "34287.54619963"}
+                    ]))
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let client = BinanceFuturesRestClient::new().with_base_url(&format!("http://{}", addr));
+        let oi = client.open_interest("btcusdt").await.unwrap();
+        assert_eq!((oi.symbol.as_str(), oi.open_interest), ("BTCUSDT", dec!(10659.509)));
+        let rates = client.funding_rates("btcusdt", 1).await.unwrap();
+        assert_eq!((rates[0].funding_rate, rates[0].funding_time), (dec!(-0.0375), 1570608000000));
+        server.abort();
+    }
+}
@@ -0,0 +1,233 @@
+use futures_util::future::join_all;
+use serde::Deserialize;
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+
+use crate::net;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+// Một cặp REST + WS của Binance spot
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct BinanceEndpoint {
+    pub name: String,
+    pub rest: String,
+    pub ws: String,
+    // data-api / data-stream chỉ có market data, không ký request được
+    #[serde(default)]
+    pub data_only: bool,
+}
+
+impl BinanceEndpoint {
+    fn new(name: &str, rest: &str, ws: &str, data_only: bool) -> Self {
+        Self { name: name.to_string(), rest: rest.to_string(), ws: ws.to_string(), data_only }
+    }
+
+    // tên có sẵn dùng được trong `[binance] endpoints`
+    pub fn preset(name: &str) -> Option<Self> {
+        let endpoint = match name {
+            "mainnet" => Self::new(name, "https://api.binance.com", "wss://stream.binance.com:9443", false),
+            "mainnet-443" => Self::new(name, "https://api.binance.com", "wss://stream.binance.com:443", false),
+            "api1" | "api2" | "api3" | "api4" | "api-gcp" => {
+                Self::new(name, &format!("https://{}.binance.com", name), "wss://stream.binance.com:9443", false)
+            }
+            "data" => Self::new(name, "https://data-api.binance.vision", "wss://data-stream.binance.vision", true),
+            "us" => Self::new(name, "https://api.binance.us", "wss://stream.binance.us:9443", false),
+            _ => return None,
+        };
+        Some(endpoint)
+    }
+
+    // host:port của WS để đo thời gian kết nối
+    fn ws_host_port(&self) -> Option<(String, u16)> {
+        let url = reqwest::Url::parse(&self.ws).ok()?;
+        Some((url.host_str()?.to_string(), url.port_or_known_default()?))
+    }
+}
+
+// `endpoints = ["mainnet", "data", { name = "vpn", rest = "...", ws = "..." }]`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum EndpointSpec {
+    Preset(String),
+    Custom(BinanceEndpoint),
+}
+
+impl EndpointSpec {
+    pub fn resolve(&self) -> Result<BinanceEndpoint, String> {
+        match self {
+            EndpointSpec::Preset(name) => BinanceEndpoint::preset(name).ok_or_else(|| {
+                format!("unknown binance endpoint `{}` (mainnet, mainnet-443, api1..api4, api-gcp, data, us)", name)
+            }),
+            EndpointSpec::Custom(endpoint) => Ok(endpoint.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndpointStatus {
+    pub name: String,
+    // None = chưa đo hoặc không kết nối được
+    pub latency: Option<Duration>,
+    pub failures: u64,
+    pub current: bool,
+}
+
+#[derive(Debug)]
+struct PoolState {
+    // thứ tự thử, index vào `endpoints`
+    order: Vec<usize>,
+    current: usize,
+    latency: Vec<Option<Duration>>,
+    failures: Vec<u64>,
+}
+
+// Danh sách endpoint theo thứ tự ưu tiên. Kết nối lỗi thì chuyển sang endpoint sau,
+// `probe` đo thời gian kết nối TCP tới WS của từng endpoint và xếp lại nhanh nhất lên đầu.
+// Mọi feed Binance dùng chung một pool nên chuyển một lần là cả nhóm cùng chuyển
+pub struct EndpointPool {
+    endpoints: Vec<BinanceEndpoint>,
+    state: Mutex<PoolState>,
+}
+
+impl fmt::Debug for EndpointPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EndpointPool").field("current", &self.current().name).finish()
+    }
+}
+
+impl EndpointPool {
+    // `endpoints` không rỗng
+    pub fn new(endpoints: Vec<BinanceEndpoint>) -> Arc<Self> {
+        assert!(!endpoints.is_empty(), "endpoint pool needs at least one endpoint");
+        let n = endpoints.len();
+        let state = PoolState { order: (0..n).collect(), current: 0, latency: vec![None; n], failures: vec![0; n] };
+        Arc::new(Self { endpoints, state: Mutex::new(state) })
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, PoolState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn current(&self) -> &BinanceEndpoint {
+        let state = self.state();
+        &self.endpoints[state.order[state.current]]
+    }
+
+    // endpoint ký request được đầu tiên theo thứ tự hiện tại (order, account)
+    pub fn trading(&self) -> &BinanceEndpoint {
+        let state = self.state();
+        let idx = state.order.iter().cycle().skip(state.current).take(state.order.len()).find(|i| !self.endpoints[**i].data_only);
+        &self.endpoints[*idx.unwrap_or(&state.order[state.current])]
+    }
+
+    // Connector báo kết nối tới `name` lỗi. Chỉ chuyển khi `name` vẫn là endpoint hiện tại,
+    // nhiều feed cùng báo một lỗi thì không nhảy quá nhiều bậc
+    pub fn report_failure(&self, name: &str) {
+        let mut state = self.state();
+        let idx = state.order[state.current];
+        if self.endpoints[idx].name != name {
+            return;
+        }
+        state.failures[idx] += 1;
+        if state.order.len() > 1 {
+            state.current = (state.current + 1) % state.order.len();
+            warn!(from = name, to = %self.endpoints[state.order[state.current]].name, "binance endpoint failover");
+        }
+    }
+
+    pub fn status(&self) -> Vec<EndpointStatus> {
+        let state = self.state();
+        state
+            .order
+            .iter()
+            .enumerate()
+            .map(|(pos, i)| EndpointStatus {
+                name: self.endpoints[*i].name.clone(),
+                latency: state.latency[*i],
+                failures: state.failures[*i],
+                current: pos == state.current,
+            })
+            .collect()
+    }
+
+    // nhanh nhất lên đầu, không kết nối được xuống cuối (giữ thứ tự cấu hình nếu bằng nhau)
+    fn apply_latency(&self, latency: Vec<Option<Duration>>) {
+        let mut state = self.state();
+        let mut order: Vec<usize> = (0..self.endpoints.len()).collect();
+        order.sort_by_key(|i| (latency[*i].is_none(), latency[*i]));
+        state.order = order;
+        state.current = 0;
+        state.latency = latency;
+    }
+
+    // đo song song, mỗi endpoint tối đa PROBE_TIMEOUT
+    pub async fn probe(&self) {
+        let latency = join_all(self.endpoints.iter().map(|endpoint| async move {
+            let (host, port) = endpoint.ws_host_port()?;
+            let start = Instant::now();
+            match tokio::time::timeout(PROBE_TIMEOUT, net::network().connect_tcp(&host, port)).await {
+                Ok(Ok(_)) => Some(start.elapsed()),
+                Ok(Err(e)) => {
+                    warn!(endpoint = %endpoint.name, error = %e, "endpoint probe failed");
+                    None
+                }
+                Err(_) => {
+                    warn!(endpoint = %endpoint.name, "endpoint probe timed out");
+                    None
+                }
+            }
+        }))
+        .await;
+        self.apply_latency(latency);
+        info!(current = %self.current().name, status = ?self.status(), "binance endpoints probed");
+    }
+
+    // đo lại định kỳ (lần đầu đã đo lúc khởi động), kết nối lại lần sau sẽ dùng endpoint nhanh nhất
+    pub async fn run_probe(self: Arc<Self>, every: Duration) {
+        loop {
+            tokio::time::sleep(every).await;
+            self.probe().await;
+        }
+    }
+}
+
+impl PartialEq for EndpointPool {
+    fn eq(&self, other: &Self) -> bool {
+        self.endpoints == other.endpoints
+    }
+}
+
+impl Eq for EndpointPool {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failover_and_latency_order() {
+        let pool = EndpointPool::new(
+            ["data", "mainnet", "us"].iter().map(|n| EndpointSpec::Preset(n.to_string()).resolve().unwrap()).collect(),
+        );
+        assert_eq!(pool.current().ws, "wss://data-stream.binance.vision");
+        assert_eq!(pool.trading().name, "mainnet");
+
+        pool.report_failure("data");
+        // feed khác báo lỗi trễ cho endpoint cũ thì bỏ qua
+        pool.report_failure("data");
+        assert_eq!(pool.current().name, "mainnet");
+        pool.report_failure("mainnet");
+        pool.report_failure("us");
+        assert_eq!(pool.current().name, "data");
+
+        pool.apply_latency(vec![Some(Duration::from_millis(40)), None, Some(Duration::from_millis(12))]);
+        let status = pool.status();
+        assert_eq!(status.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(), ["us", "data", "mainnet"]);
+        assert_eq!((status[0].current, status[1].failures), (true, 1));
+        assert!(EndpointSpec::Preset("eu".into()).resolve().is_err());
+    }
+}
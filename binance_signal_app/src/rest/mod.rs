@@ -0,0 +1,40 @@
+pub mod binance;
+pub mod binance_futures;
+pub mod endpoints;
+pub mod rate_limit;
+
+use std::fmt;
+
+#[derive(Debug)]
+pub enum RestError {
+    Http(reqwest::Error),
+    // lỗi exchange trả về dạng {"code": -1121, "msg": "Invalid symbol."}
+    Api { status: u16, code: i64, msg: String },
+    Decode(serde_json::Error),
+    MissingCredentials,
+}
+
+impl fmt::Display for RestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RestError::Http(e) => write!(f, "http error: {}", e),
+            RestError::Api { status, code, msg } => write!(f, "api error {} ({}): {}", code, status, msg),
+            RestError::Decode(e) => write!(f, "decode error: {}", e),
+            RestError::MissingCredentials => write!(f, "signed endpoint requires api key/secret"),
+        }
+    }
+}
+
+impl std::error::Error for RestError {}
+
+impl From<reqwest::Error> for RestError {
+    fn from(e: reqwest::Error) -> Self {
+        RestError::Http(e)
+    }
+}
+
+impl From<serde_json::Error> for RestError {
+    fn from(e: serde_json::Error) -> Self {
+        RestError::Decode(e)
+    }
+}
@@ -0,0 +1,261 @@
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+use std::{sync::Mutex, time::Duration};
+use tokio::time::Instant;
+use tracing::{debug, warn};
+
+// Giới hạn mặc định của Binance spot (xem GET /api/v3/exchangeInfo -> rateLimits)
+pub const DEFAULT_WEIGHT_PER_MINUTE: u32 = 6000;
+pub const DEFAULT_ORDERS_PER_10S: u32 = 100;
+pub const DEFAULT_ORDERS_PER_DAY: u32 = 200_000;
+
+const DAY: Duration = Duration::from_secs(24 * 3600);
+
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    pub weight_per_minute: u32,
+    pub orders_per_10s: u32,
+    pub orders_per_day: u32,
+    // chừa lại một phần budget cho request ngoài app (đặt tay, bot khác cùng IP/account)
+    pub headroom: f64,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            weight_per_minute: DEFAULT_WEIGHT_PER_MINUTE,
+            orders_per_10s: DEFAULT_ORDERS_PER_10S,
+            orders_per_day: DEFAULT_ORDERS_PER_DAY,
+            headroom: 0.1,
+        }
+    }
+}
+
+// Weight của request và số order nó tạo ra
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestCost {
+    pub weight: u32,
+    pub orders: u32,
+}
+
+impl RequestCost {
+    pub const fn weight(weight: u32) -> Self {
+        Self { weight, orders: 0 }
+    }
+
+    pub const fn order(weight: u32) -> Self {
+        Self { weight, orders: 1 }
+    }
+}
+
+// Token bucket nạp đều theo cửa sổ. Sàn tính theo cửa sổ cố định nên local
+// luôn được đồng bộ xuống theo header "đã dùng" khi sàn báo nhiều hơn
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    fn new(limit: u32, window: Duration, headroom: f64) -> Self {
+        let capacity = (limit as f64 * (1.0 - headroom.clamp(0.0, 0.9))).max(1.0);
+        Self { capacity, tokens: capacity, refill_per_sec: capacity / window.as_secs_f64(), last: Instant::now() }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last = now;
+    }
+
+    fn wait_for(&self, n: f64) -> Duration {
+        // request lớn hơn cả bucket thì chờ tới khi đầy rồi cho qua
+        let n = n.min(self.capacity);
+        if self.tokens >= n {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64((n - self.tokens) / self.refill_per_sec)
+    }
+
+    fn take(&mut self, n: f64) {
+        self.tokens -= n;
+    }
+
+    fn sync_used(&mut self, used: f64) {
+        self.tokens = self.tokens.min(self.capacity - used);
+    }
+
+    fn remaining(&self) -> u32 {
+        self.tokens.max(0.0) as u32
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    weight: TokenBucket,
+    orders_10s: TokenBucket,
+    orders_day: TokenBucket,
+    // 429 / 418 kèm Retry-After: dừng mọi request tới lúc đó
+    banned_until: Option<Instant>,
+    throttled: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RateLimitStatus {
+    pub weight_remaining: u32,
+    pub weight_capacity: u32,
+    pub orders_10s_remaining: u32,
+    pub orders_day_remaining: u32,
+    pub banned_for_ms: Option<u64>,
+    // số lần request phải chờ budget
+    pub throttled: u64,
+}
+
+// Dùng chung cho mọi request của BinanceRestClient (clone client = chung limiter)
+#[derive(Debug)]
+pub struct RateLimiter {
+    state: Mutex<State>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(RateLimitConfig::default())
+    }
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        let state = State {
+            weight: TokenBucket::new(config.weight_per_minute, Duration::from_secs(60), config.headroom),
+            orders_10s: TokenBucket::new(config.orders_per_10s, Duration::from_secs(10), config.headroom),
+            orders_day: TokenBucket::new(config.orders_per_day, DAY, config.headroom),
+            banned_until: None,
+            throttled: 0,
+        };
+        Self { state: Mutex::new(state) }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // Chờ tới khi đủ budget rồi trừ luôn
+    pub async fn acquire(&self, cost: RequestCost) {
+        loop {
+            let wait = {
+                let mut state = self.lock();
+                let now = Instant::now();
+                state.weight.refill(now);
+                state.orders_10s.refill(now);
+                state.orders_day.refill(now);
+
+                let mut wait = state.banned_until.map(|t| t.saturating_duration_since(now)).unwrap_or_default();
+                wait = wait.max(state.weight.wait_for(cost.weight as f64));
+                if cost.orders > 0 {
+                    wait = wait
+                        .max(state.orders_10s.wait_for(cost.orders as f64))
+                        .max(state.orders_day.wait_for(cost.orders as f64));
+                }
+                if wait.is_zero() {
+                    state.weight.take(cost.weight as f64);
+                    state.orders_10s.take(cost.orders as f64);
+                    state.orders_day.take(cost.orders as f64);
+                    return;
+                }
+                state.throttled += 1;
+                wait
+            };
+            debug!(wait_ms = wait.as_millis() as u64, ?cost, "rate limited, waiting");
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    // Đồng bộ theo X-MBX-USED-WEIGHT-1M / X-MBX-ORDER-COUNT-* và xử lý 429 / 418
+    pub fn on_response(&self, status: u16, headers: &HeaderMap) {
+        let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).and_then(|v| v.parse::<f64>().ok());
+        let mut state = self.lock();
+        if let Some(used) = header("x-mbx-used-weight-1m") {
+            state.weight.sync_used(used);
+        }
+        if let Some(used) = header("x-mbx-order-count-10s") {
+            state.orders_10s.sync_used(used);
+        }
+        if let Some(used) = header("x-mbx-order-count-1d") {
+            state.orders_day.sync_used(used);
+        }
+        if status == 429 || status == 418 {
+            let retry_after = Duration::from_secs(header("retry-after").unwrap_or(60.0) as u64);
+            warn!(status, retry_after_secs = retry_after.as_secs(), "binance rate limit hit, backing off");
+            state.banned_until = Some(Instant::now() + retry_after);
+        }
+    }
+
+    pub fn status(&self) -> RateLimitStatus {
+        let mut state = self.lock();
+        let now = Instant::now();
+        state.weight.refill(now);
+        state.orders_10s.refill(now);
+        state.orders_day.refill(now);
+        RateLimitStatus {
+            weight_remaining: state.weight.remaining(),
+            weight_capacity: state.weight.capacity as u32,
+            orders_10s_remaining: state.orders_10s.remaining(),
+            orders_day_remaining: state.orders_day.remaining(),
+            banned_for_ms: state
+                .banned_until
+                .filter(|t| *t > now)
+                .map(|t| (t - now).as_millis() as u64),
+            throttled: state.throttled,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn limiter() -> RateLimiter {
+        RateLimiter::new(RateLimitConfig { weight_per_minute: 60, orders_per_10s: 2, orders_per_day: 1000, headroom: 0.0 })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_waits_for_budget() {
+        let limiter = limiter();
+        let start = Instant::now();
+        limiter.acquire(RequestCost::order(1)).await;
+        limiter.acquire(RequestCost::order(1)).await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        assert_eq!(limiter.status().orders_10s_remaining, 0);
+
+        // 2 order / 10s -> order thứ 3 chờ 5s
+        limiter.acquire(RequestCost::order(1)).await;
+        assert_eq!(start.elapsed().as_secs(), 5);
+        assert_eq!(limiter.status().throttled, 1);
+
+        // weight: 60 / phút = 1 / giây, order thứ 3 đã dùng 1 -> còn 4, request 10 chờ 6s
+        limiter.acquire(RequestCost::weight(55)).await;
+        limiter.acquire(RequestCost::weight(10)).await;
+        assert_eq!(start.elapsed().as_secs(), 11);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_syncs_with_headers_and_backs_off() {
+        let limiter = limiter();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-mbx-used-weight-1m", HeaderValue::from_static("50"));
+        limiter.on_response(200, &headers);
+        assert_eq!(limiter.status().weight_remaining, 10);
+
+        headers.insert("retry-after", HeaderValue::from_static("30"));
+        limiter.on_response(429, &headers);
+        assert_eq!(limiter.status().banned_for_ms, Some(30_000));
+
+        let start = Instant::now();
+        limiter.acquire(RequestCost::weight(1)).await;
+        assert_eq!(start.elapsed().as_secs(), 30);
+        assert_eq!(limiter.status().banned_for_ms, None);
+    }
+}
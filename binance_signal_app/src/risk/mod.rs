@@ -0,0 +1,480 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use tokio::sync::{watch, Mutex};
+use tracing::warn;
+
+use crate::core::{
+    order::{Fill, OrderSide, OrderType},
+    orderbook::OrderbookSnapshot,
+    position::Position,
+};
+use crate::oms::Oms;
+use crate::rest::binance::BinanceRestClient;
+
+const BPS: Decimal = Decimal::from_parts(10_000, 0, 0, false, 0);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RiskError {
+    KillSwitch(String),
+    InvalidQty,
+    MaxPosition { projected: Decimal, limit: Decimal },
+    MaxOrderNotional { notional: Decimal, limit: Decimal },
+    MaxOpenOrders { open: usize, limit: usize },
+    // notional đã khớp trong ngày + order mới
+    MaxDailyNotional { projected: Decimal, limit: Decimal },
+    PriceBand { price: Decimal, mid: Decimal, band_bps: Decimal },
+    // không có book để tính notional / price band
+    NoReferencePrice(String),
+}
+
+impl fmt::Display for RiskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskError::KillSwitch(reason) => write!(f, "trading halted by kill switch: {}", reason),
+            RiskError::InvalidQty => write!(f, "order qty must be positive"),
+            RiskError::MaxPosition { projected, limit } => {
+                write!(f, "projected position {} exceeds limit {}", projected, limit)
+            }
+            RiskError::MaxOrderNotional { notional, limit } => {
+                write!(f, "order notional {} exceeds limit {}", notional, limit)
+            }
+            RiskError::MaxOpenOrders { open, limit } => write!(f, "{} open orders, limit {}", open, limit),
+            RiskError::MaxDailyNotional { projected, limit } => {
+                write!(f, "daily traded notional {} would exceed limit {}", projected, limit)
+            }
+            RiskError::PriceBand { price, mid, band_bps } => {
+                write!(f, "price {} outside {} bps band around mid {}", price, band_bps, mid)
+            }
+            RiskError::NoReferencePrice(symbol) => write!(f, "no reference price for {}", symbol),
+        }
+    }
+}
+
+impl std::error::Error for RiskError {}
+
+// `[risk]`, None = không giới hạn
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct RiskLimits {
+    // |position| sau khi order (và các order cùng chiều đang mở) khớp hết
+    pub max_position: Option<Decimal>,
+    pub max_order_notional: Option<Decimal>,
+    // tính trên mỗi symbol
+    pub max_open_orders: Option<usize>,
+    // limit price không được lệch khỏi mid quá band này
+    pub price_band_bps: Option<Decimal>,
+    // tổng notional khớp trong ngày giao dịch, reset lúc rollover
+    pub max_daily_notional: Option<Decimal>,
+}
+
+impl RiskLimits {
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let limits = [
+            ("max_position", self.max_position),
+            ("max_order_notional", self.max_order_notional),
+            ("price_band_bps", self.price_band_bps),
+            ("max_daily_notional", self.max_daily_notional),
+        ];
+        for (name, limit) in limits {
+            if limit.is_some_and(|v| v <= Decimal::ZERO) {
+                errors.push(format!("`risk.{}` must be > 0", name));
+            }
+        }
+        if self.max_open_orders == Some(0) {
+            errors.push("`risk.max_open_orders` must be > 0".to_string());
+        }
+        errors
+    }
+}
+
+// Số liệu trong ngày giao dịch, tính từ fill
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DailyStats {
+    pub fills: u64,
+    pub notional: Decimal,
+    pub fees: Decimal,
+    pub started: Option<DateTime<Utc>>,
+}
+
+// Counter dùng chung giữa task nhận fill, pre-trade check và rollover cuối ngày
+#[derive(Debug, Default)]
+pub struct DailyCounters {
+    stats: std::sync::Mutex<DailyStats>,
+}
+
+impl DailyCounters {
+    fn lock(&self) -> std::sync::MutexGuard<'_, DailyStats> {
+        self.stats.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn on_fill(&self, fill: &Fill) {
+        let mut stats = self.lock();
+        stats.fills += 1;
+        stats.notional += fill.notional();
+        stats.fees += fill.fee;
+        stats.started.get_or_insert(fill.timestamp);
+    }
+
+    pub fn stats(&self) -> DailyStats {
+        self.lock().clone()
+    }
+
+    // sang ngày mới, trả về số liệu ngày vừa hết
+    pub fn reset(&self, now: DateTime<Utc>) -> DailyStats {
+        std::mem::replace(&mut *self.lock(), DailyStats { started: Some(now), ..Default::default() })
+    }
+}
+
+// Cờ dừng giao dịch dùng chung giữa các task. Khi đã bật, mọi order mới bị từ chối
+// cho tới khi `reset` thủ công.
+#[derive(Debug)]
+pub struct KillSwitch {
+    active: AtomicBool,
+    reason_tx: watch::Sender<Option<String>>,
+}
+
+impl Default for KillSwitch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KillSwitch {
+    pub fn new() -> Self {
+        let (reason_tx, _) = watch::channel(None);
+        Self {
+            active: AtomicBool::new(false),
+            reason_tx,
+        }
+    }
+
+    // trả về false nếu đã bật từ trước (giữ lý do đầu tiên)
+    pub fn trigger(&self, reason: &str) -> bool {
+        if self.active.swap(true, Ordering::SeqCst) {
+            return false;
+        }
+        warn!(reason, "kill switch triggered");
+        self.reason_tx.send_replace(Some(reason.to_string()));
+        true
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    pub fn reason(&self) -> Option<String> {
+        self.reason_tx.borrow().clone()
+    }
+
+    pub fn reset(&self) {
+        self.active.store(false, Ordering::SeqCst);
+        self.reason_tx.send_replace(None);
+    }
+
+    // Some(reason) khi kill switch bật
+    pub fn watch(&self) -> watch::Receiver<Option<String>> {
+        self.reason_tx.subscribe()
+    }
+}
+
+// Order sắp gửi, chưa có id
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderIntent {
+    pub symbol: String,
+    pub side: OrderSide,
+    pub order_type: OrderType,
+    pub price: Option<Decimal>,
+    pub qty: Decimal,
+}
+
+impl OrderIntent {
+    pub fn limit(symbol: &str, side: OrderSide, price: Decimal, qty: Decimal) -> Self {
+        Self {
+            symbol: symbol.to_uppercase(),
+            side,
+            order_type: OrderType::Limit,
+            price: Some(price),
+            qty,
+        }
+    }
+
+    pub fn market(symbol: &str, side: OrderSide, qty: Decimal) -> Self {
+        Self {
+            symbol: symbol.to_uppercase(),
+            side,
+            order_type: OrderType::Market,
+            price: None,
+            qty,
+        }
+    }
+}
+
+// Pre-trade check trước khi gửi order, cộng với kill switch toàn cục.
+// Clone dùng chung kill switch và counter ngày (mọi account một RiskManager)
+#[derive(Debug, Clone, Default)]
+pub struct RiskManager {
+    pub limits: RiskLimits,
+    kill_switch: Arc<KillSwitch>,
+    daily: Arc<DailyCounters>,
+}
+
+impl RiskManager {
+    pub fn new(limits: RiskLimits) -> Self {
+        Self {
+            limits,
+            kill_switch: Arc::new(KillSwitch::new()),
+            daily: Arc::new(DailyCounters::default()),
+        }
+    }
+
+    // counter ngày do rollover reset, fill cộng vào từ bên ngoài
+    pub fn with_daily(mut self, daily: Arc<DailyCounters>) -> Self {
+        self.daily = daily;
+        self
+    }
+
+    pub fn daily(&self) -> Arc<DailyCounters> {
+        self.daily.clone()
+    }
+
+    pub fn kill_switch(&self) -> Arc<KillSwitch> {
+        self.kill_switch.clone()
+    }
+
+    // `pending` = qty còn lại của các order cùng chiều đang mở
+    pub fn check(
+        &self,
+        order: &OrderIntent,
+        position: &Position,
+        pending: Decimal,
+        open_orders: usize,
+        book: Option<&OrderbookSnapshot>,
+    ) -> Result<(), RiskError> {
+        if let Some(reason) = self.kill_switch.reason() {
+            return Err(RiskError::KillSwitch(reason));
+        }
+        let OrderIntent { side, order_type, price, qty, .. } = *order;
+        if qty <= Decimal::ZERO {
+            return Err(RiskError::InvalidQty);
+        }
+
+        if let Some(limit) = self.limits.max_open_orders
+            && open_orders >= limit
+        {
+            return Err(RiskError::MaxOpenOrders { open: open_orders, limit });
+        }
+
+        if let Some(limit) = self.limits.max_position {
+            let projected = (position.qty + (pending + qty) * side.sign()).abs();
+            if projected > limit {
+                return Err(RiskError::MaxPosition { projected, limit });
+            }
+        }
+
+        let no_ref = || RiskError::NoReferencePrice(order.symbol.clone());
+        if let Some(band_bps) = self.limits.price_band_bps
+            && let (OrderType::Limit, Some(price)) = (order_type, price)
+        {
+            let mid = book.and_then(|b| b.mid_price()).ok_or_else(no_ref)?;
+            if (price - mid).abs() / mid * BPS > band_bps {
+                return Err(RiskError::PriceBand { price, mid, band_bps });
+            }
+        }
+
+        if let Some(limit) = self.limits.max_order_notional {
+            // market order ước lượng bằng giá phía đối diện
+            let ref_price = match (order_type, price) {
+                (OrderType::Limit, Some(p)) => p,
+                _ => book
+                    .and_then(|b| match side {
+                        OrderSide::Buy => b.best_ask(),
+                        OrderSide::Sell => b.best_bid(),
+                    })
+                    .map(|(p, _)| p)
+                    .ok_or_else(no_ref)?,
+            };
+            let notional = ref_price * qty;
+            if notional > limit {
+                return Err(RiskError::MaxOrderNotional { notional, limit });
+            }
+        }
+
+        if let Some(limit) = self.limits.max_daily_notional {
+            let price = price.or_else(|| book.and_then(|b| b.mid_price())).ok_or_else(no_ref)?;
+            let projected = self.daily.stats().notional + price * qty;
+            if projected > limit {
+                return Err(RiskError::MaxDailyNotional { projected, limit });
+            }
+        }
+        Ok(())
+    }
+
+    // Lấy position và open order từ OMS
+    pub fn check_order(&self, oms: &Oms, order: &OrderIntent, book: Option<&OrderbookSnapshot>) -> Result<(), RiskError> {
+        let open = oms.open_orders(&order.symbol);
+        let pending = open.iter().filter(|o| o.side == order.side).map(|o| o.remaining()).sum();
+        let position = oms.position(&order.symbol);
+        self.check(order, &position, pending, open.len(), book)
+    }
+
+    // Bật kill switch rồi huỷ mọi order đang mở trên sàn
+    pub async fn kill(&self, reason: &str, client: &BinanceRestClient, oms: &Mutex<Oms>) {
+        self.kill_switch.trigger(reason);
+        cancel_all(client, oms).await;
+    }
+}
+
+pub async fn cancel_all(client: &BinanceRestClient, oms: &Mutex<Oms>) {
+    let symbols = oms.lock().await.open_symbols();
+    for symbol in symbols {
+        if let Err(e) = client.cancel_open_orders(&symbol).await {
+            warn!(%symbol, error = %e, "cancel all failed");
+            continue;
+        }
+        // trạng thái cuối cùng đến qua user data stream
+        let mut oms = oms.lock().await;
+        let ids: Vec<String> = oms.open_orders(&symbol).iter().map(|o| o.client_order_id.clone()).collect();
+        for id in ids {
+            let _ = oms.request_cancel(&id);
+        }
+    }
+}
+
+// Khi kill switch bật (từ bất kỳ đâu) thì huỷ toàn bộ order, chạy cùng user stream của venue live
+pub async fn cancel_on_kill(kill_switch: Arc<KillSwitch>, client: BinanceRestClient, oms: Arc<Mutex<Oms>>) {
+    let mut rx = kill_switch.watch();
+    while rx.changed().await.is_ok() {
+        if rx.borrow_and_update().is_some() {
+            cancel_all(&client, &oms).await;
+        }
+    }
+}
+
+// `kill -USR1 <pid>` để dừng giao dịch từ bên ngoài
+#[cfg(unix)]
+pub fn spawn_signal_trigger(kill_switch: Arc<KillSwitch>) -> std::io::Result<tokio::task::JoinHandle<()>> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut usr1 = signal(SignalKind::user_defined1())?;
+    Ok(tokio::spawn(async move {
+        while usr1.recv().await.is_some() {
+            kill_switch.trigger("SIGUSR1");
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::orderbook::Side;
+    use rust_decimal_macros::dec;
+
+    fn book() -> OrderbookSnapshot {
+        let mut ob = OrderbookSnapshot::new();
+        ob.set_level(Side::Bid, dec!(99), dec!(5));
+        ob.set_level(Side::Ask, dec!(101), dec!(5));
+        ob
+    }
+
+    fn limits() -> RiskLimits {
+        RiskLimits {
+            max_position: Some(dec!(5)),
+            max_order_notional: Some(dec!(300)),
+            max_open_orders: Some(2),
+            price_band_bps: Some(dec!(200)),
+            max_daily_notional: None,
+        }
+    }
+
+    #[test]
+    fn test_pre_trade_checks() {
+        let risk = RiskManager::new(limits());
+        let ob = book();
+        let flat = Position::default();
+        let long = Position { qty: dec!(3), ..Default::default() };
+        let buy = |price, qty| OrderIntent::limit("btcusdt", OrderSide::Buy, price, qty);
+        let check = |order: &OrderIntent, position: &Position, pending, open| {
+            risk.check(order, position, pending, open, Some(&ob))
+        };
+
+        assert_eq!(check(&buy(dec!(100), dec!(2)), &flat, dec!(0), 0), Ok(()));
+        assert_eq!(check(&buy(dec!(100), dec!(0)), &flat, dec!(0), 0), Err(RiskError::InvalidQty));
+        assert_eq!(
+            check(&buy(dec!(100), dec!(1)), &flat, dec!(0), 2),
+            Err(RiskError::MaxOpenOrders { open: 2, limit: 2 })
+        );
+        // đã long 3, thêm 1 đang chờ + 2 mới = 6
+        assert_eq!(
+            check(&buy(dec!(100), dec!(2)), &long, dec!(1), 1),
+            Err(RiskError::MaxPosition { projected: dec!(6), limit: dec!(5) })
+        );
+        // bán để giảm vị thế thì được
+        let sell = OrderIntent::limit("btcusdt", OrderSide::Sell, dec!(100), dec!(2));
+        assert_eq!(check(&sell, &long, dec!(0), 0), Ok(()));
+
+        // mid 100, band 200 bps -> [98, 102]
+        let sell = OrderIntent::limit("btcusdt", OrderSide::Sell, dec!(103), dec!(1));
+        assert_eq!(
+            check(&sell, &flat, dec!(0), 0),
+            Err(RiskError::PriceBand { price: dec!(103), mid: dec!(100), band_bps: dec!(200) })
+        );
+        // market buy 3 @ ask 101 = 303
+        assert_eq!(
+            check(&OrderIntent::market("btcusdt", OrderSide::Buy, dec!(3)), &flat, dec!(0), 0),
+            Err(RiskError::MaxOrderNotional { notional: dec!(303), limit: dec!(300) })
+        );
+        assert_eq!(
+            risk.check(&OrderIntent::market("ethusdt", OrderSide::Buy, dec!(1)), &flat, dec!(0), 0, None),
+            Err(RiskError::NoReferencePrice("ETHUSDT".into()))
+        );
+    }
+
+    #[test]
+    fn test_daily_notional_resets_on_rollover() {
+        let risk = RiskManager::new(RiskLimits { max_daily_notional: Some(dec!(500)), ..Default::default() });
+        let daily = risk.daily();
+        let ts = Utc::now();
+        let fill = Fill { order_id: 1, symbol: "BTCUSDT".into(), side: OrderSide::Buy, price: dec!(100), qty: dec!(4), fee: dec!(0.4), is_maker: true, timestamp: ts };
+        daily.on_fill(&fill);
+        let buy = OrderIntent::limit("btcusdt", OrderSide::Buy, dec!(100), dec!(2));
+        assert_eq!(
+            risk.check(&buy, &Position::default(), dec!(0), 0, None),
+            Err(RiskError::MaxDailyNotional { projected: dec!(600), limit: dec!(500) })
+        );
+        let day = daily.reset(ts);
+        assert_eq!((day.fills, day.notional, day.fees), (1, dec!(400), dec!(0.4)));
+        assert_eq!(risk.check(&buy, &Position::default(), dec!(0), 0, None), Ok(()));
+    }
+
+    #[test]
+    fn test_kill_switch_blocks_orders() {
+        let risk = RiskManager::new(RiskLimits::default());
+        let mut oms = Oms::new("test");
+        let ob = book();
+        let id = oms.create_order("btcusdt", OrderSide::Buy, OrderType::Limit, Some(dec!(100)), dec!(1));
+        oms.on_ack(&id, 1).unwrap();
+        assert_eq!(oms.open_symbols(), vec!["BTCUSDT".to_string()]);
+        let order = OrderIntent::market("btcusdt", OrderSide::Buy, dec!(1));
+        assert!(risk.check_order(&oms, &order, Some(&ob)).is_ok());
+
+        let kill = risk.kill_switch();
+        let rx = kill.watch();
+        assert!(kill.trigger("drawdown"));
+        assert!(!kill.trigger("again"));
+        assert_eq!(rx.borrow().as_deref(), Some("drawdown"));
+        assert_eq!(risk.check_order(&oms, &order, Some(&ob)), Err(RiskError::KillSwitch("drawdown".into())));
+
+        kill.reset();
+        assert!(!kill.is_active());
+        assert!(risk.check_order(&oms, &order, Some(&ob)).is_ok());
+    }
+}
@@ -0,0 +1,282 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime, TimeZone, Timelike, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf, sync::Arc};
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+use crate::core::{backpressure::LosslessReceiver, order::Fill};
+use crate::db::Store;
+use crate::logfile;
+use crate::portfolio::{PortfolioSnapshot, SharedPortfolio};
+use crate::risk::{DailyCounters, DailyStats};
+use crate::supervisor::Shutdown;
+
+// Việc cuối ngày giao dịch: lưu PnL, cắt file recorder, xoay file log, reset counter
+// risk trong ngày và ghi báo cáo tổng kết:
+//
+//   [rollover]
+//   enabled = true
+//   # offset cố định, không theo DST
+//   timezone = "+07:00"
+//   at = "00:00"
+//   report_dir = "data/reports"
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct RolloverSettings {
+    pub enabled: bool,
+    pub timezone: String,
+    pub at: String,
+    // daily-YYYY-MM-DD.json, None = chỉ log
+    pub report_dir: Option<PathBuf>,
+}
+
+impl Default for RolloverSettings {
+    fn default() -> Self {
+        Self { enabled: false, timezone: "+00:00".to_string(), at: "00:00".to_string(), report_dir: None }
+    }
+}
+
+impl RolloverSettings {
+    pub fn validate(&self) -> Vec<String> {
+        match (self.enabled, DayBoundary::new(self)) {
+            (true, Err(e)) => vec![format!("`rollover`: {}", e)],
+            _ => Vec::new(),
+        }
+    }
+}
+
+// Ranh giới ngày giao dịch: ngày D chạy từ `at` ngày D tới `at` ngày D+1 theo `offset`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DayBoundary {
+    offset: FixedOffset,
+    at: NaiveTime,
+}
+
+impl DayBoundary {
+    pub fn new(settings: &RolloverSettings) -> Result<Self, String> {
+        let offset = settings
+            .timezone
+            .parse::<FixedOffset>()
+            .map_err(|_| format!("invalid timezone {:?}, expected an offset like \"+07:00\"", settings.timezone))?;
+        let at = NaiveTime::parse_from_str(&settings.at, "%H:%M")
+            .map_err(|_| format!("invalid time {:?}, expected HH:MM", settings.at))?;
+        Ok(Self { offset, at })
+    }
+
+    pub fn trading_day(&self, now: DateTime<Utc>) -> NaiveDate {
+        let shift = chrono::Duration::seconds(self.at.num_seconds_from_midnight() as i64);
+        (now.with_timezone(&self.offset) - shift).date_naive()
+    }
+
+    // lần rollover kế tiếp sau `now`
+    pub fn next(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let day = self.trading_day(now) + chrono::Duration::days(1);
+        self.offset
+            .from_local_datetime(&day.and_time(self.at))
+            .single()
+            .map(|t| t.with_timezone(&Utc))
+            .unwrap_or(now + chrono::Duration::days(1))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DailySummary {
+    pub day: NaiveDate,
+    pub closed_at: DateTime<Utc>,
+    // PnL cộng dồn lúc chốt, None nếu lệnh không có portfolio
+    pub portfolio: Option<PortfolioSnapshot>,
+    // total_pnl lúc chốt - lúc bắt đầu ngày
+    pub day_pnl: Option<Decimal>,
+    pub stats: DailyStats,
+    pub log_file: Option<PathBuf>,
+}
+
+pub struct Rollover {
+    boundary: DayBoundary,
+    report_dir: Option<PathBuf>,
+    portfolio: Option<SharedPortfolio>,
+    store: Option<Store>,
+    daily: Arc<DailyCounters>,
+    // recorder `with_cut`: tăng một mỗi lần rollover
+    cut: watch::Sender<u64>,
+    day_start_pnl: Decimal,
+}
+
+impl Rollover {
+    pub fn new(settings: &RolloverSettings) -> Result<Self, String> {
+        Ok(Self {
+            boundary: DayBoundary::new(settings)?,
+            report_dir: settings.report_dir.clone(),
+            portfolio: None,
+            store: None,
+            daily: Arc::new(DailyCounters::default()),
+            cut: watch::channel(0).0,
+            day_start_pnl: Decimal::ZERO,
+        })
+    }
+
+    pub fn with_portfolio(mut self, portfolio: SharedPortfolio) -> Self {
+        self.day_start_pnl = portfolio.snapshot().total_pnl;
+        self.portfolio = Some(portfolio);
+        self
+    }
+
+    // PnL cuối ngày ghi vào `daily_pnl` của ngày giao dịch (không phải ngày UTC)
+    pub fn with_store(mut self, store: Store) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    pub fn with_daily(mut self, daily: Arc<DailyCounters>) -> Self {
+        self.daily = daily;
+        self
+    }
+
+    pub fn daily(&self) -> Arc<DailyCounters> {
+        self.daily.clone()
+    }
+
+    pub fn cut_watch(&self) -> watch::Receiver<u64> {
+        self.cut.subscribe()
+    }
+
+    pub fn boundary(&self) -> DayBoundary {
+        self.boundary
+    }
+
+    // chốt ngày `day` lúc `now`
+    pub async fn roll(&mut self, day: NaiveDate, now: DateTime<Utc>) -> DailySummary {
+        let portfolio = self.portfolio.as_ref().map(|p| p.snapshot());
+        if let (Some(store), Some(snap)) = (&self.store, &portfolio)
+            && let Err(e) = store.save_pnl(day, snap).await
+        {
+            warn!(error = %e, %day, "persist end-of-day pnl failed");
+        }
+        let day_pnl = portfolio.as_ref().map(|snap| snap.total_pnl - self.day_start_pnl);
+        if let Some(snap) = &portfolio {
+            self.day_start_pnl = snap.total_pnl;
+        }
+        self.cut.send_modify(|n| *n += 1);
+        let log_file = logfile::rotate().unwrap_or_else(|e| {
+            warn!(error = %e, "rotate log file failed");
+            None
+        });
+        let stats = self.daily.reset(now);
+        let summary = DailySummary { day, closed_at: now, portfolio, day_pnl, stats, log_file };
+
+        info!(
+            target: "rollover",
+            %day,
+            day_pnl = ?summary.day_pnl,
+            fills = summary.stats.fills,
+            notional = %summary.stats.notional,
+            fees = %summary.stats.fees,
+            "trading day closed"
+        );
+        if let Some(dir) = &self.report_dir {
+            let path = dir.join(format!("daily-{}.json", day));
+            let written = fs::create_dir_all(dir)
+                .and_then(|_| serde_json::to_vec_pretty(&summary).map_err(std::io::Error::other))
+                .and_then(|json| fs::write(&path, json));
+            if let Err(e) = written {
+                warn!(error = %e, path = %path.display(), "write daily report failed");
+            }
+        }
+        summary
+    }
+
+    // Chờ tới ranh giới ngày rồi `roll`, fill (nếu có) cộng vào counter trong ngày
+    pub async fn run(mut self, mut fills: Option<LosslessReceiver<Fill>>, mut shutdown: Shutdown) {
+        loop {
+            let now = Utc::now();
+            let next = self.boundary.next(now);
+            let wait = (next - now).to_std().unwrap_or_default();
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {
+                    let day = self.boundary.trading_day(now);
+                    self.roll(day, Utc::now()).await;
+                }
+                fill = next_fill(&mut fills) => self.daily.on_fill(&fill),
+                _ = shutdown.wait() => break,
+            }
+        }
+    }
+}
+
+async fn next_fill(fills: &mut Option<LosslessReceiver<Fill>>) -> Fill {
+    let Some(rx) = fills else { return std::future::pending().await };
+    match rx.recv().await {
+        Some(fill) => fill,
+        None => {
+            *fills = None;
+            std::future::pending().await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{order::OrderSide, position::Position};
+    use crate::risk::{OrderIntent, RiskError, RiskLimits, RiskManager};
+    use rust_decimal_macros::dec;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_roll_snapshots_and_resets_day() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = RolloverSettings {
+            enabled: true,
+            timezone: "+07:00".into(),
+            at: "06:00".into(),
+            report_dir: Some(dir.path().into()),
+        };
+        let boundary = DayBoundary::new(&settings).unwrap();
+        // 22:30 UTC = 05:30 +07 hôm sau, vẫn thuộc ngày giao dịch trước
+        assert_eq!(boundary.trading_day(at("2024-03-04T22:30:00Z")), NaiveDate::from_ymd_opt(2024, 3, 4).unwrap());
+        assert_eq!(boundary.next(at("2024-03-04T22:30:00Z")), at("2024-03-04T23:00:00Z"));
+        assert_eq!(boundary.next(at("2024-03-04T23:00:00Z")), at("2024-03-05T23:00:00Z"));
+
+        let portfolio = SharedPortfolio::new();
+        // counter ngày dùng chung với pre-trade check
+        let risk = RiskManager::new(RiskLimits { max_daily_notional: Some(dec!(250)), ..Default::default() });
+        let mut rollover = Rollover::new(&settings).unwrap().with_portfolio(portfolio.clone()).with_daily(risk.daily());
+        let buy = OrderIntent::limit("btcusdt", OrderSide::Buy, dec!(100), dec!(1));
+        let fill = Fill {
+            order_id: 1,
+            symbol: "BTCUSDT".into(),
+            side: OrderSide::Buy,
+            price: dec!(100),
+            qty: dec!(2),
+            fee: dec!(0.2),
+            is_maker: true,
+            timestamp: at("2024-03-04T10:00:00Z"),
+        };
+        portfolio.update(|p| {
+            p.on_fill(&fill);
+            p.mark("BTCUSDT", dec!(101));
+        });
+        rollover.daily().on_fill(&fill);
+        assert!(matches!(risk.check(&buy, &Position::default(), Decimal::ZERO, 0, None), Err(RiskError::MaxDailyNotional { .. })));
+        let mut cut = rollover.cut_watch();
+
+        let day = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        let summary = rollover.roll(day, at("2024-03-04T23:00:00Z")).await;
+        assert_eq!((summary.stats.fills, summary.stats.notional), (1, dec!(200)));
+        assert_eq!(summary.day_pnl, Some(dec!(1.8)));
+        assert_eq!(risk.check(&buy, &Position::default(), Decimal::ZERO, 0, None), Ok(()));
+        assert!(cut.has_changed().unwrap());
+        cut.borrow_and_update();
+        assert!(dir.path().join("daily-2024-03-04.json").exists());
+
+        // ngày sau không có fill, PnL tính từ mốc mới
+        let summary = rollover.roll(day.succ_opt().unwrap(), at("2024-03-05T23:00:00Z")).await;
+        assert_eq!((summary.stats.fills, summary.day_pnl), (0, Some(Decimal::ZERO)));
+        assert!(cut.has_changed().unwrap());
+        assert!(RolloverSettings { enabled: true, timezone: "Asia/Saigon".into(), ..Default::default() }.validate().len() == 1);
+    }
+}
@@ -0,0 +1,254 @@
+use figment::{
+    providers::{Format, Toml},
+    Figment,
+};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::{path::PathBuf, sync::Arc, time::Duration};
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+use crate::config::ConfigError;
+use crate::strategy::market_maker::MarketMakerConfig;
+use crate::supervisor::Shutdown;
+
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+// Tham số strategy đổi được khi đang chạy (`--params strategy.toml`).
+// Key không có trong file = giữ giá trị từ CLI, xoá key là quay về giá trị CLI
+//
+//   [market_maker]
+//   half_spread_bps = 4
+//   order_qty = 0.002
+//
+//   [ofi]
+//   threshold = 12.5
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StrategyParams {
+    pub market_maker: QuotingParams,
+    pub ofi: OfiParams,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QuotingParams {
+    pub half_spread_bps: Option<Decimal>,
+    pub order_qty: Option<Decimal>,
+    pub max_inventory: Option<Decimal>,
+    pub skew_bps: Option<Decimal>,
+    pub requote_threshold_bps: Option<Decimal>,
+    pub vol_multiplier: Option<Decimal>,
+    pub toxic_vpin: Option<Decimal>,
+    pub toxic_spread_mult: Option<Decimal>,
+}
+
+impl QuotingParams {
+    // `base` là config lúc khởi động
+    pub fn apply(&self, base: &MarketMakerConfig) -> MarketMakerConfig {
+        let mut config = base.clone();
+        let fields = [
+            (&mut config.half_spread_bps, self.half_spread_bps),
+            (&mut config.order_qty, self.order_qty),
+            (&mut config.max_inventory, self.max_inventory),
+            (&mut config.skew_bps, self.skew_bps),
+            (&mut config.requote_threshold_bps, self.requote_threshold_bps),
+            (&mut config.vol_multiplier, self.vol_multiplier),
+            (&mut config.toxic_vpin, self.toxic_vpin),
+            (&mut config.toxic_spread_mult, self.toxic_spread_mult),
+        ];
+        for (field, value) in fields {
+            if let Some(value) = value {
+                *field = value;
+            }
+        }
+        config
+    }
+}
+
+// strategy OFI mẫu của `paper-trade`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OfiParams {
+    pub threshold: Option<f64>,
+    pub qty: Option<Decimal>,
+}
+
+impl StrategyParams {
+    pub fn load(path: &str) -> Result<Self, ConfigError> {
+        Self::from_figment(Figment::new().merge(Toml::file_exact(path)))
+    }
+
+    pub fn from_toml_str(toml: &str) -> Result<Self, ConfigError> {
+        Self::from_figment(Figment::new().merge(Toml::string(toml)))
+    }
+
+    fn from_figment(figment: Figment) -> Result<Self, ConfigError> {
+        let params: StrategyParams = figment.extract()?;
+        params.validate()?;
+        Ok(params)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut errors = Vec::new();
+        let mm = &self.market_maker;
+        let non_negative = [
+            ("half_spread_bps", mm.half_spread_bps),
+            ("max_inventory", mm.max_inventory),
+            ("skew_bps", mm.skew_bps),
+            ("requote_threshold_bps", mm.requote_threshold_bps),
+            ("vol_multiplier", mm.vol_multiplier),
+            ("toxic_vpin", mm.toxic_vpin),
+        ];
+        for (name, value) in non_negative {
+            if value.is_some_and(|v| v < Decimal::ZERO) {
+                errors.push(format!("`market_maker.{}` must be >= 0", name));
+            }
+        }
+        if mm.order_qty.is_some_and(|q| q <= Decimal::ZERO) {
+            errors.push("`market_maker.order_qty` must be > 0".to_string());
+        }
+        if mm.toxic_spread_mult.is_some_and(|m| m < Decimal::ONE) {
+            errors.push("`market_maker.toxic_spread_mult` must be >= 1".to_string());
+        }
+        if self.ofi.threshold.is_some_and(|t| !(t >= 0.0 && t.is_finite())) {
+            errors.push("`ofi.threshold` must be a finite number >= 0".to_string());
+        }
+        if self.ofi.qty.is_some_and(|q| q <= Decimal::ZERO) {
+            errors.push("`ofi.qty` must be > 0".to_string());
+        }
+        if errors.is_empty() { Ok(()) } else { Err(ConfigError::Invalid(errors)) }
+    }
+}
+
+// Đọc lại file params định kỳ, publish cả bộ mới qua watch khi nội dung đổi: strategy
+// luôn thấy trọn một phiên bản, không bao giờ nửa cũ nửa mới. File lỗi (sai cú pháp,
+// giá trị không hợp lệ, bị xoá) thì giữ bộ đang chạy và log một lần
+pub struct ParamsWatcher {
+    path: PathBuf,
+    interval: Duration,
+    tx: watch::Sender<Arc<StrategyParams>>,
+    last_error: Option<String>,
+}
+
+impl ParamsWatcher {
+    // lần đọc đầu lỗi thì không khởi động
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, ConfigError> {
+        let path = path.into();
+        let params = StrategyParams::load(&path.to_string_lossy())?;
+        Ok(Self { path, interval: DEFAULT_POLL_INTERVAL, tx: watch::channel(Arc::new(params)).0, last_error: None })
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<Arc<StrategyParams>> {
+        self.tx.subscribe()
+    }
+
+    pub fn current(&self) -> Arc<StrategyParams> {
+        self.tx.borrow().clone()
+    }
+
+    // true nếu vừa publish bộ params mới
+    pub fn poll(&mut self) -> bool {
+        match StrategyParams::load(&self.path.to_string_lossy()) {
+            Ok(params) => {
+                self.last_error = None;
+                if **self.tx.borrow() == params {
+                    return false;
+                }
+                info!(path = %self.path.display(), ?params, "strategy params reloaded");
+                self.tx.send_replace(Arc::new(params));
+                true
+            }
+            Err(e) => {
+                let e = e.to_string();
+                if self.last_error.as_ref() != Some(&e) {
+                    warn!(path = %self.path.display(), error = %e, "strategy params reload failed, keeping current");
+                    self.last_error = Some(e);
+                }
+                false
+            }
+        }
+    }
+
+    pub async fn run(mut self, mut shutdown: Shutdown) {
+        let mut tick = tokio::time::interval(self.interval);
+        tick.tick().await;
+        loop {
+            tokio::select! {
+                _ = tick.tick() => {
+                    self.poll();
+                }
+                _ = shutdown.wait() => break,
+            }
+        }
+    }
+}
+
+// params kế tiếp cho vòng select của strategy, không có watcher thì chờ mãi
+pub async fn next_params(params: &mut Option<watch::Receiver<Arc<StrategyParams>>>) -> Arc<StrategyParams> {
+    let Some(rx) = params else { return std::future::pending().await };
+    if rx.changed().await.is_err() {
+        // watcher đã dừng: giữ bộ cuối, không báo nữa
+        let last = rx.borrow().clone();
+        *params = None;
+        return last;
+    }
+    rx.borrow_and_update().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_parse_apply_and_validate() {
+        let params = StrategyParams::from_toml_str("[market_maker]\nhalf_spread_bps = 2.5\norder_qty = \"0.002\"\n[ofi]\nthreshold = 3").unwrap();
+        let base = MarketMakerConfig::default();
+        let config = params.market_maker.apply(&base);
+        assert_eq!((config.half_spread_bps, config.order_qty), (dec!(2.5), dec!(0.002)));
+        // key không có trong file giữ giá trị gốc
+        assert_eq!((config.skew_bps, config.symbol), (base.skew_bps, base.symbol));
+        assert_eq!(params.ofi.threshold, Some(3.0));
+
+        let Err(ConfigError::Invalid(errors)) = StrategyParams::from_toml_str("[market_maker]\norder_qty = 0\ntoxic_spread_mult = 0.5")
+        else {
+            panic!("expected invalid params")
+        };
+        assert_eq!(errors.len(), 2);
+        // gõ sai tên key thì báo lỗi thay vì lặng lẽ bỏ qua
+        assert!(StrategyParams::from_toml_str("[market_maker]\nhalf_spread = 1").is_err());
+        assert!(StrategyParams::load(concat!(env!("CARGO_MANIFEST_DIR"), "/strategy.example.toml")).is_ok());
+    }
+
+    #[test]
+    fn test_watcher_publishes_whole_valid_updates() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("params.toml");
+        std::fs::write(&path, "[market_maker]\nhalf_spread_bps = 5").unwrap();
+        let mut watcher = ParamsWatcher::load(&path).unwrap();
+        let mut rx = watcher.subscribe();
+        assert!(!watcher.poll());
+
+        std::fs::write(&path, "[market_maker]\nhalf_spread_bps = 7\norder_qty = 2").unwrap();
+        assert!(watcher.poll());
+        assert!(rx.has_changed().unwrap());
+        let params = rx.borrow_and_update().clone();
+        assert_eq!((params.market_maker.half_spread_bps, params.market_maker.order_qty), (Some(dec!(7)), Some(dec!(2))));
+
+        // file lỗi hoặc bị xoá: giữ bộ đang chạy
+        std::fs::write(&path, "[market_maker]\nhalf_spread_bps = -1").unwrap();
+        assert!(!watcher.poll());
+        std::fs::remove_file(&path).unwrap();
+        assert!(!watcher.poll());
+        assert!(!rx.has_changed().unwrap());
+        assert_eq!(watcher.current().market_maker.half_spread_bps, Some(dec!(7)));
+
+        assert!(ParamsWatcher::load(dir.path().join("missing.toml")).is_err());
+    }
+}
@@ -0,0 +1,354 @@
+use chrono::{DateTime, Datelike, NaiveTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::{sync::Arc, time::Duration};
+use tokio::{
+    sync::{broadcast, mpsc, watch},
+    time::{interval, MissedTickBehavior},
+};
+use tracing::info;
+
+use crate::core::signal::MarketData;
+use crate::supervisor::Shutdown;
+
+const EVENT_CAPACITY: usize = 64;
+
+// Tạm dừng quote / ghi data theo lịch, hết cửa sổ thì tự chạy lại. Giờ theo UTC:
+//
+//   [schedule]
+//   enabled = true
+//   # ngoài khung này không quote
+//   trading_hours = { from = "01:00", to = "23:00", days = ["mon", "tue", "wed", "thu", "fri"] }
+//
+//   [[schedule.windows]]
+//   name = "binance maintenance"
+//   start = "2026-10-20T02:00:00Z"
+//   end = "2026-10-20T04:00:00Z"
+//
+//   [[schedule.windows]]
+//   name = "funding blackout"
+//   from = "23:55"
+//   to = "00:05"
+//   pause = ["quoting"]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ScheduleSettings {
+    pub enabled: bool,
+    pub trading_hours: Option<DailyHours>,
+    pub windows: Vec<WindowSpec>,
+    pub check_ms: u64,
+}
+
+impl Default for ScheduleSettings {
+    fn default() -> Self {
+        Self { enabled: false, trading_hours: None, windows: Vec::new(), check_ms: 1000 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Activity {
+    Quoting,
+    Recording,
+}
+
+// "HH:MM" UTC, `to` <= `from` là khung qua nửa đêm. `days` rỗng = mọi ngày, tính theo ngày của `from`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DailyHours {
+    pub from: String,
+    pub to: String,
+    #[serde(default)]
+    pub days: Vec<String>,
+}
+
+impl DailyHours {
+    fn parse(&self) -> Result<(NaiveTime, NaiveTime, Vec<Weekday>), String> {
+        let time = |s: &str| NaiveTime::parse_from_str(s, "%H:%M").map_err(|_| format!("invalid time {:?}, expected HH:MM", s));
+        let days = self
+            .days
+            .iter()
+            .map(|d| d.parse::<Weekday>().map_err(|_| format!("invalid weekday {:?}", d)))
+            .collect::<Result<_, _>>()?;
+        Ok((time(&self.from)?, time(&self.to)?, days))
+    }
+
+    pub fn contains(&self, now: DateTime<Utc>) -> bool {
+        let Ok((from, to, days)) = self.parse() else {
+            return false;
+        };
+        let t = now.time();
+        let day_ok = |d: Weekday| days.is_empty() || days.contains(&d);
+        if from < to {
+            day_ok(now.weekday()) && from <= t && t < to
+        } else if t >= from {
+            day_ok(now.weekday())
+        } else {
+            // phần sau nửa đêm thuộc ngày hôm trước
+            t < to && day_ok(now.weekday().pred())
+        }
+    }
+}
+
+// Một cửa sổ tạm dừng: một lần (`start` / `end`, RFC3339) hoặc hằng ngày (`from` / `to`)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct WindowSpec {
+    pub name: String,
+    #[serde(default)]
+    pub start: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub end: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub from: Option<String>,
+    #[serde(default)]
+    pub to: Option<String>,
+    #[serde(default)]
+    pub days: Vec<String>,
+    // rỗng = dừng cả quote lẫn ghi
+    #[serde(default)]
+    pub pause: Vec<Activity>,
+}
+
+impl WindowSpec {
+    fn daily(&self) -> Option<DailyHours> {
+        let (from, to) = (self.from.clone()?, self.to.clone()?);
+        Some(DailyHours { from, to, days: self.days.clone() })
+    }
+
+    pub fn contains(&self, now: DateTime<Utc>) -> bool {
+        match (self.daily(), self.start, self.end) {
+            (Some(daily), _, _) => daily.contains(now),
+            (None, Some(start), Some(end)) => start <= now && now < end,
+            _ => false,
+        }
+    }
+
+    pub fn pauses(&self, activity: Activity) -> bool {
+        self.pause.is_empty() || self.pause.contains(&activity)
+    }
+}
+
+impl ScheduleSettings {
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if !self.enabled {
+            return errors;
+        }
+        if self.check_ms == 0 {
+            errors.push("`schedule.check_ms` must be > 0".to_string());
+        }
+        if let Some(hours) = &self.trading_hours
+            && let Err(e) = hours.parse()
+        {
+            errors.push(format!("`schedule.trading_hours`: {}", e));
+        }
+        for window in &self.windows {
+            match (window.daily(), window.start, window.end) {
+                (Some(daily), None, None) => {
+                    if let Err(e) = daily.parse() {
+                        errors.push(format!("schedule window {:?}: {}", window.name, e));
+                    }
+                }
+                (None, Some(start), Some(end)) if start < end => {}
+                _ => errors.push(format!(
+                    "schedule window {:?} needs either start < end or from / to",
+                    window.name
+                )),
+            }
+        }
+        errors
+    }
+
+    // tên cửa sổ đang dừng từng hoạt động, None = chạy
+    pub fn state_at(&self, now: DateTime<Utc>) -> SessionState {
+        let paused_by = |activity: Activity| {
+            self.windows
+                .iter()
+                .find(|w| w.pauses(activity) && w.contains(now))
+                .map(|w| w.name.clone())
+        };
+        let outside_hours = self.trading_hours.as_ref().filter(|h| !h.contains(now)).map(|_| "outside trading hours".to_string());
+        SessionState { quoting: paused_by(Activity::Quoting).or(outside_hours), recording: paused_by(Activity::Recording) }
+    }
+}
+
+// Some(lý do) = đang tạm dừng
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SessionState {
+    pub quoting: Option<String>,
+    pub recording: Option<String>,
+}
+
+impl SessionState {
+    pub fn paused(&self, activity: Activity) -> Option<&str> {
+        match activity {
+            Activity::Quoting => self.quoting.as_deref(),
+            Activity::Recording => self.recording.as_deref(),
+        }
+    }
+
+    pub fn is_active(&self, activity: Activity) -> bool {
+        self.paused(activity).is_none()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SessionEvent {
+    pub activity: Activity,
+    pub paused: bool,
+    // tên cửa sổ (lúc dừng) hoặc cửa sổ vừa hết (lúc chạy lại)
+    pub reason: String,
+    pub at: DateTime<Utc>,
+}
+
+// Tính lại trạng thái mỗi `check_ms`, đổi thì publish qua `watch` (consumer chỉ cần bản mới
+// nhất) và bắn `SessionEvent` cho ai cần từng lần chuyển (alert, log)
+pub struct Scheduler {
+    settings: ScheduleSettings,
+    state: watch::Sender<SessionState>,
+    events: broadcast::Sender<SessionEvent>,
+}
+
+impl Scheduler {
+    pub fn new(settings: ScheduleSettings) -> Arc<Self> {
+        let (state, _) = watch::channel(settings.state_at(Utc::now()));
+        let (events, _) = broadcast::channel(EVENT_CAPACITY);
+        Arc::new(Self { settings, state, events })
+    }
+
+    pub fn state(&self) -> SessionState {
+        self.state.borrow().clone()
+    }
+
+    pub fn watch(&self) -> watch::Receiver<SessionState> {
+        self.state.subscribe()
+    }
+
+    pub fn events(&self) -> broadcast::Receiver<SessionEvent> {
+        self.events.subscribe()
+    }
+
+    // cập nhật theo `now`, trả về các lần chuyển trạng thái
+    pub fn update(&self, now: DateTime<Utc>) -> Vec<SessionEvent> {
+        let next = self.settings.state_at(now);
+        let prev = self.state();
+        let mut changes = Vec::new();
+        for activity in [Activity::Quoting, Activity::Recording] {
+            let (before, after) = (prev.paused(activity), next.paused(activity));
+            if before.is_some() == after.is_some() {
+                continue;
+            }
+            let reason = after.or(before).unwrap_or_default().to_string();
+            let event = SessionEvent { activity, paused: after.is_some(), reason, at: now };
+            info!(target: "session", activity = ?event.activity, paused = event.paused, reason = %event.reason, "session state changed");
+            let _ = self.events.send(event.clone());
+            changes.push(event);
+        }
+        if next != prev {
+            self.state.send_replace(next);
+        }
+        changes
+    }
+
+    pub async fn run(self: Arc<Self>, mut shutdown: Shutdown) {
+        let mut tick = interval(Duration::from_millis(self.settings.check_ms));
+        tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = tick.tick() => {
+                    self.update(Utc::now());
+                }
+                _ = shutdown.wait() => break,
+            }
+        }
+    }
+}
+
+// Chờ trạng thái đổi, scheduler đã dừng (hoặc không có) thì chờ mãi
+pub async fn next_session(session: &mut Option<watch::Receiver<SessionState>>) {
+    let Some(rx) = session else { return std::future::pending().await };
+    if rx.changed().await.is_err() {
+        *session = None;
+        return std::future::pending().await;
+    }
+    rx.borrow_and_update();
+}
+
+// Chuyển tiếp data từ `rx`, bỏ đi trong lúc `activity` bị dừng (recorder, sink)
+pub fn gate(
+    mut rx: mpsc::Receiver<MarketData>,
+    session: watch::Receiver<SessionState>,
+    activity: Activity,
+) -> (mpsc::Receiver<MarketData>, tokio::task::JoinHandle<()>) {
+    let (tx, gated) = mpsc::channel(rx.max_capacity());
+    let handle = tokio::spawn(async move {
+        while let Some(data) = rx.recv().await {
+            if !session.borrow().is_active(activity) {
+                continue;
+            }
+            if tx.send(data).await.is_err() {
+                break;
+            }
+        }
+    });
+    (gated, handle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use figment::{
+        providers::{Format, Toml},
+        Figment,
+    };
+
+    fn at(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_windows_pause_and_resume() {
+        let settings: ScheduleSettings = Figment::from(Toml::string(
+            r#"
+            enabled = true
+            trading_hours = { from = "01:00", to = "23:00", days = ["mon", "tue", "wed", "thu", "fri"] }
+
+            [[windows]]
+            name = "maintenance"
+            start = "2026-10-20T02:00:00Z"
+            end = "2026-10-20T04:00:00Z"
+
+            [[windows]]
+            name = "blackout"
+            from = "23:55"
+            to = "00:05"
+            pause = ["recording"]
+            "#,
+        ))
+        .extract()
+        .unwrap();
+        assert!(settings.validate().is_empty(), "{:?}", settings.validate());
+
+        // thứ ba 2026-10-20
+        let scheduler = Scheduler::new(settings.clone());
+        scheduler.update(at("2026-10-20T01:30:00Z"));
+        assert_eq!(scheduler.state(), SessionState::default());
+        let events = scheduler.update(at("2026-10-20T02:00:00Z"));
+        assert_eq!(events.len(), 2);
+        assert_eq!(scheduler.state().quoting.as_deref(), Some("maintenance"));
+        let events = scheduler.update(at("2026-10-20T04:00:00Z"));
+        assert!(events.iter().all(|e| !e.paused && e.reason == "maintenance"));
+        assert_eq!(scheduler.state(), SessionState::default());
+
+        // blackout qua nửa đêm chỉ dừng ghi, 23:58 đã ngoài giờ quote
+        let state = settings.state_at(at("2026-10-20T23:58:00Z"));
+        assert_eq!((state.quoting.as_deref(), state.recording.as_deref()), (Some("outside trading hours"), Some("blackout")));
+        assert!(settings.state_at(at("2026-10-21T00:03:00Z")).recording.is_some());
+        // thứ bảy không quote
+        assert!(settings.state_at(at("2026-10-24T12:00:00Z")).quoting.is_some());
+
+        let mut bad = settings;
+        // vừa một lần vừa hằng ngày
+        (bad.windows[0].from, bad.windows[0].to) = (Some("02:00".into()), Some("03:00".into()));
+        bad.windows[1].to = Some("25:00".into());
+        assert_eq!(bad.validate().len(), 2);
+    }
+}
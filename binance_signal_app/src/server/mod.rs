@@ -0,0 +1,16 @@
+pub mod ws;
+
+use axum::Router;
+use std::{io, net::SocketAddr};
+use tracing::info;
+
+use crate::supervisor::Shutdown;
+
+// Chạy một axum Router tới khi shutdown
+pub async fn serve(name: &str, addr: SocketAddr, router: Router, mut shutdown: Shutdown) -> io::Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!(%addr, server = name, "listening");
+    axum::serve(listener, router)
+        .with_graceful_shutdown(async move { shutdown.wait().await })
+        .await
+}
@@ -0,0 +1,245 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::Response,
+    routing::get,
+    Router,
+};
+use chrono::{DateTime, Utc};
+use futures_util::{stream, SinkExt, StreamExt};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use tokio_stream::wrappers::{BroadcastStream, WatchStream};
+use tracing::debug;
+
+use crate::core::{orderbook::OrderbookSnapshot, signal::SignalOutput};
+use crate::hub::MarketHub;
+
+// số level mỗi phía gửi cho client (dashboard không cần full book)
+pub const DEFAULT_DEPTH: usize = 20;
+
+// Client gửi: {"op":"subscribe","symbols":["btcusdt"]}, "*" = mọi symbol
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum ClientMessage {
+    Subscribe { symbols: Vec<String> },
+    Unsubscribe { symbols: Vec<String> },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ServerMessage {
+    Book {
+        exchange: String,
+        symbol: String,
+        timestamp: DateTime<Utc>,
+        // [price, qty], bid giá giảm dần
+        bids: Vec<(Decimal, Decimal)>,
+        asks: Vec<(Decimal, Decimal)>,
+    },
+    Signal {
+        symbol: String,
+        timestamp: DateTime<Utc>,
+        values: BTreeMap<String, f64>,
+    },
+    // danh sách symbol đang theo dõi sau mỗi lệnh subscribe / unsubscribe
+    Subscribed { symbols: Vec<String> },
+    Error { message: String },
+}
+
+impl ServerMessage {
+    pub fn book(exchange: &str, symbol: &str, snap: &OrderbookSnapshot, depth: usize) -> Self {
+        ServerMessage::Book {
+            exchange: exchange.to_string(),
+            symbol: symbol.to_uppercase(),
+            timestamp: snap.timestamp,
+            bids: snap.bids.iter().rev().take(depth).map(|(p, q)| (*p, *q)).collect(),
+            asks: snap.asks.iter().take(depth).map(|(p, q)| (*p, *q)).collect(),
+        }
+    }
+
+    pub fn signal(out: &SignalOutput) -> Self {
+        ServerMessage::Signal {
+            symbol: out.symbol.clone(),
+            timestamp: out.timestamp,
+            values: out.values.iter().cloned().collect(),
+        }
+    }
+}
+
+// Symbol client đang theo dõi, chữ hoa
+#[derive(Debug, Clone, Default)]
+struct Subscriptions {
+    all: bool,
+    symbols: HashSet<String>,
+}
+
+impl Subscriptions {
+    fn apply(&mut self, msg: ClientMessage) {
+        match msg {
+            ClientMessage::Subscribe { symbols } => {
+                for s in symbols {
+                    if s == "*" {
+                        self.all = true;
+                    } else {
+                        self.symbols.insert(s.to_uppercase());
+                    }
+                }
+            }
+            ClientMessage::Unsubscribe { symbols } => {
+                for s in symbols {
+                    if s == "*" {
+                        self.all = false;
+                        self.symbols.clear();
+                    } else {
+                        self.symbols.remove(&s.to_uppercase());
+                    }
+                }
+            }
+        }
+    }
+
+    fn contains(&self, symbol: &str) -> bool {
+        self.all || self.symbols.contains(&symbol.to_uppercase())
+    }
+
+    fn list(&self) -> Vec<String> {
+        if self.all {
+            return vec!["*".to_string()];
+        }
+        let mut list: Vec<String> = self.symbols.iter().cloned().collect();
+        list.sort();
+        list
+    }
+}
+
+// GET /ws
+pub fn router(hub: MarketHub) -> Router {
+    Router::new().route("/ws", get(upgrade)).with_state(hub)
+}
+
+async fn upgrade(ws: WebSocketUpgrade, State(hub): State<MarketHub>) -> Response {
+    ws.on_upgrade(move |socket| client(socket, hub))
+}
+
+enum Event {
+    Book(usize),
+    Signal(SignalOutput),
+}
+
+// Mỗi client một task: book lấy theo watch (chỉ bản mới nhất, client chậm không làm nghẽn feed),
+// signal theo broadcast (bỏ qua khi lag)
+async fn client(socket: WebSocket, hub: MarketHub) {
+    let (mut sink, mut incoming) = socket.split();
+    let feeds = hub.feeds().to_vec();
+    let books = stream::select_all(
+        feeds
+            .iter()
+            .enumerate()
+            .map(|(idx, feed)| WatchStream::from_changes(feed.watch()).map(move |_| Event::Book(idx)).boxed()),
+    );
+    let signals = BroadcastStream::new(hub.subscribe_signals()).filter_map(|r| std::future::ready(r.ok().map(Event::Signal)));
+    let mut events = stream::select(books, signals);
+    let mut subs = Subscriptions::default();
+
+    loop {
+        let reply = tokio::select! {
+            msg = incoming.next() => match msg {
+                Some(Ok(Message::Text(text))) => match serde_json::from_str::<ClientMessage>(&text) {
+                    Ok(msg) => {
+                        subs.apply(msg);
+                        let mut replies = vec![ServerMessage::Subscribed { symbols: subs.list() }];
+                        // gửi ngay book hiện tại, không phải chờ update kế tiếp
+                        for feed in feeds.iter().filter(|f| subs.contains(f.symbol())) {
+                            replies.push(ServerMessage::book(feed.exchange(), feed.symbol(), &feed.snapshot(), DEFAULT_DEPTH));
+                        }
+                        replies
+                    }
+                    Err(e) => vec![ServerMessage::Error { message: format!("invalid message: {}", e) }],
+                },
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => continue,
+            },
+            Some(event) = events.next() => match event {
+                Event::Book(idx) => {
+                    let feed = &feeds[idx];
+                    if !subs.contains(feed.symbol()) {
+                        continue;
+                    }
+                    vec![ServerMessage::book(feed.exchange(), feed.symbol(), &feed.snapshot(), DEFAULT_DEPTH)]
+                }
+                Event::Signal(out) if subs.contains(&out.symbol) => vec![ServerMessage::signal(&out)],
+                Event::Signal(_) => continue,
+            },
+        };
+        for msg in reply {
+            let Ok(text) = serde_json::to_string(&msg) else { continue };
+            if sink.send(Message::Text(text)).await.is_err() {
+                debug!("ws client disconnected");
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::orderbook::{SharedOrderbook, Side};
+    use crate::replay::ReplayFeed;
+    use crate::ws::OrderbookFeed;
+    use rust_decimal_macros::dec;
+    use std::sync::Arc;
+    use tokio::sync::broadcast;
+    use tokio_tungstenite::{connect_async, tungstenite::Message as WsMessage};
+
+    #[tokio::test]
+    async fn test_rebroadcast_by_subscription() {
+        let feed = Arc::new(ReplayFeed { symbol: "btcusdt".into(), orderbook: Arc::new(SharedOrderbook::new()) });
+        let (signals_tx, _) = broadcast::channel(16);
+        let hub = MarketHub::new(vec![feed.clone() as Arc<dyn OrderbookFeed>], signals_tx.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move { axum::serve(listener, router(hub)).await });
+
+        let (mut ws, _) = connect_async(format!("ws://{}/ws", addr)).await.unwrap();
+        ws.send(WsMessage::Text(r#"{"op":"subscribe","symbols":["BTCUSDT"]}"#.into())).await.unwrap();
+        let msg = recv(&mut ws).await;
+        assert_eq!((msg["type"].as_str(), msg["symbols"][0].as_str()), (Some("subscribed"), Some("BTCUSDT")));
+        // book hiện tại (rỗng)
+        assert_eq!(recv(&mut ws).await["type"], "book");
+
+        feed.orderbook().update(|ob| {
+            ob.set_level(Side::Bid, dec!(100), dec!(1));
+            true
+        });
+        let msg = recv(&mut ws).await;
+        assert_eq!((msg["symbol"].as_str(), msg["bids"][0][0].as_str()), (Some("BTCUSDT"), Some("100")));
+
+        // signal của symbol khác không được gửi
+        let signal = |symbol: &str| SignalOutput { symbol: symbol.into(), timestamp: Utc::now(), values: vec![("ofi_1s".into(), 2.0)] };
+        signals_tx.send(signal("ETHUSDT")).unwrap();
+        signals_tx.send(signal("BTCUSDT")).unwrap();
+        let msg = recv(&mut ws).await;
+        assert_eq!((msg["type"].as_str(), msg["values"]["ofi_1s"].as_f64()), (Some("signal"), Some(2.0)));
+
+        ws.send(WsMessage::Text("nope".into())).await.unwrap();
+        assert_eq!(recv(&mut ws).await["type"], "error");
+        server.abort();
+    }
+
+    async fn recv<S>(ws: &mut S) -> serde_json::Value
+    where
+        S: futures_util::Stream<Item = Result<WsMessage, tokio_tungstenite::tungstenite::Error>> + Unpin,
+    {
+        loop {
+            if let Some(Ok(WsMessage::Text(text))) = ws.next().await {
+                return serde_json::from_str(&text).unwrap();
+            }
+        }
+    }
+}
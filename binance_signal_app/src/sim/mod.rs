@@ -0,0 +1,523 @@
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use crate::core::{
+    order::{Fill, OrderId, OrderSide, OrderStatus, OrderType},
+    orderbook::{from_f64, OrderbookSnapshot},
+    position::Position,
+    queue::{level_qty, QueuePosition},
+    signal::MarketData,
+    trade::{Trade, TradeSide},
+};
+
+const BPS: Decimal = Decimal::from_parts(10_000, 0, 0, false, 0);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SimError {
+    NoBook(String),
+    InvalidQty,
+    UnknownOrder(OrderId),
+}
+
+impl fmt::Display for SimError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimError::NoBook(symbol) => write!(f, "no orderbook for {}", symbol),
+            SimError::InvalidQty => write!(f, "order qty must be positive"),
+            SimError::UnknownOrder(id) => write!(f, "unknown order {}", id),
+        }
+    }
+}
+
+impl std::error::Error for SimError {}
+
+#[derive(Debug, Clone)]
+pub struct PaperConfig {
+    pub maker_fee_bps: Decimal,
+    pub taker_fee_bps: Decimal,
+    // trượt giá thêm cho lệnh taker (ngoài phần ăn qua nhiều level): latency, book đổi trước khi tới sàn
+    pub slippage_bps: Decimal,
+}
+
+impl Default for PaperConfig {
+    fn default() -> Self {
+        // mức phí spot mặc định của Binance: 0.1%
+        Self {
+            maker_fee_bps: Decimal::TEN,
+            taker_fee_bps: Decimal::TEN,
+            slippage_bps: Decimal::ZERO,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaperOrder {
+    pub id: OrderId,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub order_type: OrderType,
+    pub price: Option<Decimal>,
+    pub qty: Decimal,
+    pub filled_qty: Decimal,
+    pub status: OrderStatus,
+    // ước lượng vị trí trong hàng đợi tại mức giá của order (limit đang chờ)
+    pub queue: QueuePosition,
+    pub created_at: DateTime<Utc>,
+}
+
+impl PaperOrder {
+    pub fn remaining(&self) -> Decimal {
+        self.qty - self.filled_qty
+    }
+
+    pub fn is_open(&self) -> bool {
+        !self.status.is_final()
+    }
+
+    pub fn queue_ahead(&self) -> Decimal {
+        self.queue.ahead
+    }
+
+    // theo tốc độ khớp tại mức giá từ lúc đặt, None khi chưa có trade
+    pub fn time_to_fill(&self) -> Option<Duration> {
+        self.queue.time_to_fill(self.remaining())
+    }
+}
+
+// Sàn giả lập khớp lệnh trên orderbook local.
+// - Order taker (market, hoặc limit cắt qua spread) khớp ngay vào các level đối diện.
+// - Limit resting xếp hàng sau khối lượng đang có ở cùng mức giá; queue giảm khi
+//   có trade tại giá đó hoặc level bị huỷ bớt (xem `QueuePosition`), và chỉ khớp
+//   khi queue phía trước đã hết.
+// - Thanh khoản bị order taker ăn không được trừ khỏi snapshot: hai market order
+//   liên tiếp trên cùng snapshot sẽ khớp cùng giá.
+#[derive(Debug, Default)]
+pub struct PaperExchange {
+    config: PaperConfig,
+    next_id: OrderId,
+    orders: HashMap<OrderId, PaperOrder>,
+    books: HashMap<String, Arc<OrderbookSnapshot>>,
+    positions: HashMap<String, Position>,
+    fills: Vec<Fill>,
+}
+
+impl PaperExchange {
+    pub fn new(config: PaperConfig) -> Self {
+        Self {
+            config,
+            next_id: 1,
+            ..Default::default()
+        }
+    }
+
+    pub fn on_market_data(&mut self, data: &MarketData) -> Vec<Fill> {
+        match data {
+            MarketData::Orderbook { symbol, snap } => self.on_orderbook(symbol, snap.clone()),
+            MarketData::Trade(trade) => self.on_trade(trade),
+            MarketData::Perp(_) | MarketData::Liquidation(_) => Vec::new(),
+        }
+    }
+
+    pub fn on_orderbook(&mut self, symbol: &str, snap: Arc<OrderbookSnapshot>) -> Vec<Fill> {
+        let symbol = symbol.to_uppercase();
+        let mut fills = Vec::new();
+        let ids = self.resting_ids(&symbol);
+
+        for id in ids {
+            let Some(order) = self.orders.get_mut(&id) else { continue };
+            let Some(price) = order.price else { continue };
+
+            // phía đối diện đã đi qua giá của order -> chắc chắn đã bị khớp
+            let crossed = match order.side {
+                OrderSide::Buy => snap.best_ask().is_some_and(|(ask, _)| ask <= price),
+                OrderSide::Sell => snap.best_bid().is_some_and(|(bid, _)| bid >= price),
+            };
+            if crossed {
+                let qty = order.remaining();
+                fills.push(self.fill(id, price, qty, true, snap.timestamp));
+                continue;
+            }
+
+            order.queue.on_level(level_qty(&snap, order.side, price), snap.timestamp);
+        }
+
+        self.books.insert(symbol, snap);
+        fills
+    }
+
+    pub fn on_trade(&mut self, trade: &Trade) -> Vec<Fill> {
+        let symbol = trade.symbol.to_uppercase();
+        let (Some(trade_price), Some(mut available)) = (from_f64(trade.price), from_f64(trade.qty)) else {
+            return Vec::new();
+        };
+        // sell aggressor ăn bid, buy aggressor ăn ask
+        let passive_side = match trade.side {
+            TradeSide::Sell => OrderSide::Buy,
+            TradeSide::Buy => OrderSide::Sell,
+        };
+
+        let mut fills = Vec::new();
+        for id in self.resting_ids(&symbol) {
+            if available <= Decimal::ZERO {
+                break;
+            }
+            let Some(order) = self.orders.get_mut(&id) else { continue };
+            let Some(price) = order.price else { continue };
+            if order.side != passive_side {
+                continue;
+            }
+
+            let reached = match order.side {
+                OrderSide::Buy => trade_price <= price,
+                OrderSide::Sell => trade_price >= price,
+            };
+            if !reached {
+                continue;
+            }
+
+            if trade_price == price {
+                available = order.queue.on_trade(available, trade.timestamp);
+            } else {
+                order.queue.clear(trade.timestamp);
+            }
+
+            let qty = order.remaining().min(available);
+            if qty > Decimal::ZERO {
+                available -= qty;
+                fills.push(self.fill(id, price, qty, true, trade.timestamp));
+            }
+        }
+        fills
+    }
+
+    pub fn place_market(&mut self, symbol: &str, side: OrderSide, qty: Decimal) -> Result<(OrderId, Vec<Fill>), SimError> {
+        let symbol = symbol.to_uppercase();
+        let book = self.book(&symbol)?;
+        let id = self.insert_order(&symbol, side, OrderType::Market, None, qty, &book)?;
+
+        let fills = self.take_liquidity(id, &book, None);
+        // phần không khớp được (book mỏng) bị huỷ như IOC
+        if let Some(order) = self.orders.get_mut(&id)
+            && order.is_open()
+        {
+            order.status = OrderStatus::Expired;
+        }
+        Ok((id, fills))
+    }
+
+    pub fn place_limit(
+        &mut self,
+        symbol: &str,
+        side: OrderSide,
+        price: Decimal,
+        qty: Decimal,
+    ) -> Result<(OrderId, Vec<Fill>), SimError> {
+        let symbol = symbol.to_uppercase();
+        let book = self.book(&symbol)?;
+        let id = self.insert_order(&symbol, side, OrderType::Limit, Some(price), qty, &book)?;
+
+        let fills = self.take_liquidity(id, &book, Some(price));
+        if let Some(order) = self.orders.get_mut(&id) {
+            order.queue = QueuePosition::new(level_qty(&book, side, price), book.timestamp);
+        }
+        Ok((id, fills))
+    }
+
+    pub fn cancel(&mut self, id: OrderId) -> Result<(), SimError> {
+        let order = self.orders.get_mut(&id).ok_or(SimError::UnknownOrder(id))?;
+        if order.is_open() {
+            order.status = OrderStatus::Canceled;
+        }
+        Ok(())
+    }
+
+    pub fn order(&self, id: OrderId) -> Option<&PaperOrder> {
+        self.orders.get(&id)
+    }
+
+    pub fn open_orders(&self, symbol: &str) -> Vec<&PaperOrder> {
+        let symbol = symbol.to_uppercase();
+        let mut orders: Vec<_> = self
+            .orders
+            .values()
+            .filter(|o| o.symbol == symbol && o.is_open())
+            .collect();
+        orders.sort_by_key(|o| o.id);
+        orders
+    }
+
+    pub fn position(&self, symbol: &str) -> Position {
+        self.positions.get(&symbol.to_uppercase()).cloned().unwrap_or_default()
+    }
+
+    pub fn positions(&self) -> &HashMap<String, Position> {
+        &self.positions
+    }
+
+    pub fn fills(&self) -> &[Fill] {
+        &self.fills
+    }
+
+    // mọi order đã đặt (kể cả đã đóng), theo id
+    pub fn orders(&self) -> Vec<&PaperOrder> {
+        let mut orders: Vec<_> = self.orders.values().collect();
+        orders.sort_by_key(|o| o.id);
+        orders
+    }
+
+    // book gần nhất của symbol, tham chiếu cho pre-trade check
+    pub fn latest_book(&self, symbol: &str) -> Option<Arc<OrderbookSnapshot>> {
+        self.books.get(&symbol.to_uppercase()).cloned()
+    }
+
+    // mark theo mid của book gần nhất
+    pub fn unrealized_pnl(&self, symbol: &str) -> Decimal {
+        let symbol = symbol.to_uppercase();
+        match (self.positions.get(&symbol), self.books.get(&symbol).and_then(|b| b.mid_price())) {
+            (Some(pos), Some(mid)) => pos.unrealized_pnl(mid),
+            _ => Decimal::ZERO,
+        }
+    }
+
+    // realized - fees + unrealized trên mọi symbol
+    pub fn total_pnl(&self) -> Decimal {
+        self.positions
+            .iter()
+            .map(|(symbol, pos)| pos.net_realized_pnl() + self.unrealized_pnl(symbol))
+            .sum()
+    }
+
+    fn book(&self, symbol: &str) -> Result<Arc<OrderbookSnapshot>, SimError> {
+        self.books
+            .get(symbol)
+            .cloned()
+            .ok_or_else(|| SimError::NoBook(symbol.to_string()))
+    }
+
+    fn insert_order(
+        &mut self,
+        symbol: &str,
+        side: OrderSide,
+        order_type: OrderType,
+        price: Option<Decimal>,
+        qty: Decimal,
+        book: &OrderbookSnapshot,
+    ) -> Result<OrderId, SimError> {
+        if qty <= Decimal::ZERO {
+            return Err(SimError::InvalidQty);
+        }
+        let id = self.next_id.max(1);
+        self.next_id = id + 1;
+        self.orders.insert(
+            id,
+            PaperOrder {
+                id,
+                symbol: symbol.to_string(),
+                side,
+                order_type,
+                price,
+                qty,
+                filled_qty: Decimal::ZERO,
+                status: OrderStatus::New,
+                queue: QueuePosition::new(Decimal::ZERO, book.timestamp),
+                created_at: book.timestamp,
+            },
+        );
+        Ok(id)
+    }
+
+    // Khớp taker vào các level đối diện, dừng ở `limit` nếu có
+    fn take_liquidity(&mut self, id: OrderId, book: &OrderbookSnapshot, limit: Option<Decimal>) -> Vec<Fill> {
+        let Some(order) = self.orders.get(&id) else {
+            return Vec::new();
+        };
+        let side = order.side;
+        let mut remaining = order.remaining();
+
+        let levels: Vec<(Decimal, Decimal)> = match side {
+            OrderSide::Buy => book
+                .asks
+                .iter()
+                .take_while(|(p, _)| limit.is_none_or(|l| **p <= l))
+                .map(|(p, q)| (*p, *q))
+                .collect(),
+            OrderSide::Sell => book
+                .bids
+                .iter()
+                .rev()
+                .take_while(|(p, _)| limit.is_none_or(|l| **p >= l))
+                .map(|(p, q)| (*p, *q))
+                .collect(),
+        };
+
+        let slippage = match side {
+            OrderSide::Buy => Decimal::ONE + self.config.slippage_bps / BPS,
+            OrderSide::Sell => Decimal::ONE - self.config.slippage_bps / BPS,
+        };
+        let mut fills = Vec::new();
+        for (price, level_qty) in levels {
+            if remaining.is_zero() {
+                break;
+            }
+            let qty = remaining.min(level_qty);
+            remaining -= qty;
+            fills.push(self.fill(id, price * slippage, qty, false, book.timestamp));
+        }
+        fills
+    }
+
+    fn fill(&mut self, id: OrderId, price: Decimal, qty: Decimal, is_maker: bool, ts: DateTime<Utc>) -> Fill {
+        let fee_bps = if is_maker { self.config.maker_fee_bps } else { self.config.taker_fee_bps };
+        let order = self.orders.get_mut(&id).expect("fill for unknown order");
+        order.filled_qty += qty;
+        order.status = if order.remaining().is_zero() {
+            OrderStatus::Filled
+        } else {
+            OrderStatus::PartiallyFilled
+        };
+
+        let fill = Fill {
+            order_id: id,
+            symbol: order.symbol.clone(),
+            side: order.side,
+            price,
+            qty,
+            fee: price * qty * fee_bps / BPS,
+            is_maker,
+            timestamp: ts,
+        };
+        self.positions.entry(fill.symbol.clone()).or_default().apply_fill(&fill);
+        self.fills.push(fill.clone());
+        fill
+    }
+
+    // Limit đang chờ của symbol, theo ưu tiên giá rồi thời gian
+    fn resting_ids(&self, symbol: &str) -> Vec<OrderId> {
+        let mut orders: Vec<&PaperOrder> = self
+            .orders
+            .values()
+            .filter(|o| o.symbol == symbol && o.is_open() && o.order_type == OrderType::Limit)
+            .collect();
+        orders.sort_by(|a, b| {
+            let by_price = match a.side {
+                OrderSide::Buy => b.price.cmp(&a.price),
+                OrderSide::Sell => a.price.cmp(&b.price),
+            };
+            a.side.sign().cmp(&b.side.sign()).then(by_price).then(a.id.cmp(&b.id))
+        });
+        orders.iter().map(|o| o.id).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::orderbook::Side;
+    use rust_decimal_macros::dec;
+
+    fn book(bids: &[(Decimal, Decimal)], asks: &[(Decimal, Decimal)]) -> Arc<OrderbookSnapshot> {
+        let mut ob = OrderbookSnapshot::new();
+        for (p, q) in bids {
+            ob.set_level(Side::Bid, *p, *q);
+        }
+        for (p, q) in asks {
+            ob.set_level(Side::Ask, *p, *q);
+        }
+        Arc::new(ob)
+    }
+
+    fn trade(side: TradeSide, price: f64, qty: f64) -> Trade {
+        Trade {
+            symbol: "BTCUSDT".into(),
+            trade_id: 1,
+            price,
+            qty,
+            side,
+            timestamp: Utc::now(),
+        }
+    }
+
+    fn exchange() -> PaperExchange {
+        let mut ex = PaperExchange::new(PaperConfig {
+            maker_fee_bps: Decimal::ZERO,
+            taker_fee_bps: dec!(10),
+            slippage_bps: Decimal::ZERO,
+        });
+        ex.on_orderbook(
+            "btcusdt",
+            book(&[(dec!(100), dec!(5)), (dec!(99), dec!(5))], &[(dec!(101), dec!(1)), (dec!(102), dec!(2))]),
+        );
+        ex
+    }
+
+    #[test]
+    fn test_market_order_walks_book() {
+        let mut ex = exchange();
+        let (id, fills) = ex.place_market("BTCUSDT", OrderSide::Buy, dec!(2)).unwrap();
+        assert_eq!(fills.iter().map(|f| (f.price, f.qty)).collect::<Vec<_>>(), vec![(dec!(101), dec!(1)), (dec!(102), dec!(1))]);
+        assert_eq!(ex.order(id).unwrap().status, OrderStatus::Filled);
+        // phí taker 10 bps trên 203
+        assert_eq!(ex.position("btcusdt").fees, dec!(0.203));
+        assert_eq!(ex.position("btcusdt").qty, dec!(2));
+
+        // book không đủ -> phần còn lại expired
+        let (id, _) = ex.place_market("BTCUSDT", OrderSide::Sell, dec!(20)).unwrap();
+        assert_eq!(ex.order(id).unwrap().status, OrderStatus::Expired);
+        assert_eq!(ex.order(id).unwrap().filled_qty, dec!(10));
+
+        assert_eq!(ex.place_market("ETHUSDT", OrderSide::Buy, dec!(1)), Err(SimError::NoBook("ETHUSDT".into())));
+
+        // trượt giá 50bps trên lệnh taker
+        let mut ex = PaperExchange::new(PaperConfig { slippage_bps: dec!(50), ..PaperConfig::default() });
+        ex.on_orderbook("btcusdt", book(&[(dec!(100), dec!(1))], &[(dec!(101), dec!(1))]));
+        let (_, fills) = ex.place_market("BTCUSDT", OrderSide::Sell, dec!(1)).unwrap();
+        assert_eq!(fills[0].price, dec!(99.5));
+    }
+
+    #[test]
+    fn test_limit_queue_position() {
+        let mut ex = exchange();
+        let (id, fills) = ex.place_limit("BTCUSDT", OrderSide::Buy, dec!(100), dec!(3)).unwrap();
+        assert!(fills.is_empty());
+        assert_eq!(ex.order(id).unwrap().queue_ahead(), dec!(5));
+
+        // 4 đứng trước bị ăn, queue còn 1
+        assert!(ex.on_trade(&trade(TradeSide::Sell, 100.0, 4.0)).is_empty());
+        // level co về 0.5 -> phần còn lại phía trước đã huỷ bớt
+        ex.on_orderbook("BTCUSDT", book(&[(dec!(100), dec!(0.5))], &[(dec!(101), dec!(1))]));
+        assert_eq!(ex.order(id).unwrap().queue_ahead(), dec!(0.5));
+
+        // trade phía buy không chạm bid
+        assert!(ex.on_trade(&trade(TradeSide::Buy, 100.0, 10.0)).is_empty());
+
+        let fills = ex.on_trade(&trade(TradeSide::Sell, 100.0, 2.0));
+        assert_eq!(fills.len(), 1);
+        assert_eq!((fills[0].qty, fills[0].is_maker), (dec!(1.5), true));
+        assert_eq!(ex.order(id).unwrap().status, OrderStatus::PartiallyFilled);
+
+        // ask đi xuống qua giá order -> khớp hết phần còn lại
+        let fills = ex.on_orderbook("BTCUSDT", book(&[(dec!(99), dec!(1))], &[(dec!(100), dec!(1))]));
+        assert_eq!(fills[0].qty, dec!(1.5));
+        assert_eq!(ex.order(id).unwrap().status, OrderStatus::Filled);
+        assert!(ex.open_orders("BTCUSDT").is_empty());
+    }
+
+    #[test]
+    fn test_crossing_limit_and_pnl() {
+        let mut ex = exchange();
+        // limit 101.5 ăn được ask 101, phần còn lại nằm chờ
+        let (id, fills) = ex.place_limit("BTCUSDT", OrderSide::Buy, dec!(101.5), dec!(2)).unwrap();
+        assert_eq!(fills.len(), 1);
+        assert!(!fills[0].is_maker);
+        assert_eq!(ex.open_orders("btcusdt")[0].remaining(), dec!(1));
+
+        ex.cancel(id).unwrap();
+        assert_eq!(ex.order(id).unwrap().status, OrderStatus::Canceled);
+        assert_eq!(ex.cancel(42), Err(SimError::UnknownOrder(42)));
+
+        // long 1 @ 101, mid 100.5 -> unrealized -0.5
+        assert_eq!(ex.unrealized_pnl("BTCUSDT"), dec!(-0.5));
+        assert_eq!(ex.total_pnl(), dec!(-0.5) - dec!(0.101));
+    }
+}
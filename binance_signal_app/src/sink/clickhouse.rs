@@ -0,0 +1,400 @@
+use chrono::{DateTime, Utc};
+use futures_util::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio_stream::wrappers::{BroadcastStream, WatchStream};
+use tracing::{info, warn};
+
+use super::SinkError;
+use crate::core::{
+    orderbook::{to_f64, OrderbookSnapshot},
+    trade::{Trade, TradeSide},
+};
+use crate::hub::MarketHub;
+use crate::net;
+use crate::supervisor::Shutdown;
+
+// Ghi qua HTTP interface (cổng 8123), INSERT ... FORMAT JSONEachRow
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ClickHouseConfig {
+    pub enabled: bool,
+    pub url: String,
+    pub database: String,
+    pub user: Option<String>,
+    pub password: Option<String>,
+    // tạo bảng (IF NOT EXISTS) lúc start
+    pub create_tables: bool,
+    pub tops: bool,
+    pub levels: bool,
+    pub trades: bool,
+    // số level mỗi phía ghi vào `book_levels`
+    pub depth: usize,
+    // flush khi đủ batch_size dòng hoặc mỗi flush_interval_ms
+    pub batch_size: usize,
+    pub flush_interval_ms: u64,
+    pub max_retries: u32,
+    pub retry_backoff_ms: u64,
+}
+
+impl Default for ClickHouseConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: "http://127.0.0.1:8123".to_string(),
+            database: "default".to_string(),
+            user: None,
+            password: None,
+            create_tables: true,
+            tops: true,
+            levels: false,
+            trades: true,
+            depth: 10,
+            batch_size: 10_000,
+            flush_interval_ms: 1000,
+            max_retries: 5,
+            retry_backoff_ms: 200,
+        }
+    }
+}
+
+pub const TOPS_TABLE: &str = "book_tops";
+pub const LEVELS_TABLE: &str = "book_levels";
+pub const TRADES_TABLE: &str = "trades";
+
+// {db} được thay bằng `database`
+const CREATE_TABLES: [&str; 3] = [
+    "CREATE TABLE IF NOT EXISTS {db}.book_tops (
+        ts DateTime64(3, 'UTC'), exchange LowCardinality(String), symbol LowCardinality(String),
+        last_update_id UInt64, bid_price Float64, bid_qty Float64, ask_price Float64, ask_qty Float64
+    ) ENGINE = MergeTree PARTITION BY toDate(ts) ORDER BY (symbol, exchange, ts)",
+    "CREATE TABLE IF NOT EXISTS {db}.book_levels (
+        ts DateTime64(3, 'UTC'), exchange LowCardinality(String), symbol LowCardinality(String),
+        last_update_id UInt64, side Enum8('bid' = 1, 'ask' = 2), level UInt16, price Float64, qty Float64
+    ) ENGINE = MergeTree PARTITION BY toDate(ts) ORDER BY (symbol, exchange, ts, side, level)",
+    "CREATE TABLE IF NOT EXISTS {db}.trades (
+        ts DateTime64(3, 'UTC'), symbol LowCardinality(String), trade_id UInt64,
+        price Float64, qty Float64, side Enum8('buy' = 1, 'sell' = 2)
+    ) ENGINE = MergeTree PARTITION BY toDate(ts) ORDER BY (symbol, ts, trade_id)",
+];
+
+// DateTime64 đọc chuỗi "YYYY-MM-DD hh:mm:ss.sss" không phụ thuộc setting của server
+fn ch_time(ts: DateTime<Utc>) -> String {
+    ts.format("%Y-%m-%d %H:%M:%S%.3f").to_string()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TopRow {
+    pub ts: String,
+    pub exchange: String,
+    pub symbol: String,
+    pub last_update_id: u64,
+    pub bid_price: f64,
+    pub bid_qty: f64,
+    pub ask_price: f64,
+    pub ask_qty: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LevelRow {
+    pub ts: String,
+    pub exchange: String,
+    pub symbol: String,
+    pub last_update_id: u64,
+    pub side: &'static str,
+    // 0 = best
+    pub level: u16,
+    pub price: f64,
+    pub qty: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TradeRow {
+    pub ts: String,
+    pub symbol: String,
+    pub trade_id: u64,
+    pub price: f64,
+    pub qty: f64,
+    pub side: &'static str,
+}
+
+// Dòng chờ ghi, gom theo bảng
+#[derive(Debug, Clone, Default)]
+pub struct Batch {
+    pub tops: Vec<TopRow>,
+    pub levels: Vec<LevelRow>,
+    pub trades: Vec<TradeRow>,
+}
+
+impl Batch {
+    pub fn len(&self) -> usize {
+        self.tops.len() + self.levels.len() + self.trades.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn push_book(&mut self, config: &ClickHouseConfig, exchange: &str, symbol: &str, snap: &OrderbookSnapshot) {
+        let ts = ch_time(snap.timestamp);
+        let symbol = symbol.to_uppercase();
+        if config.tops
+            && let Some(((bid_p, bid_q), (ask_p, ask_q))) = snap.best_bid_ask()
+        {
+            self.tops.push(TopRow {
+                ts: ts.clone(),
+                exchange: exchange.to_string(),
+                symbol: symbol.clone(),
+                last_update_id: snap.last_update_id,
+                bid_price: to_f64(bid_p),
+                bid_qty: to_f64(bid_q),
+                ask_price: to_f64(ask_p),
+                ask_qty: to_f64(ask_q),
+            });
+        }
+        if config.levels {
+            let bids = snap.bids.iter().rev().take(config.depth).map(|l| ("bid", l));
+            let asks = snap.asks.iter().take(config.depth).map(|l| ("ask", l));
+            for (i, (side, (p, q))) in bids.enumerate().chain(asks.enumerate()) {
+                self.levels.push(LevelRow {
+                    ts: ts.clone(),
+                    exchange: exchange.to_string(),
+                    symbol: symbol.clone(),
+                    last_update_id: snap.last_update_id,
+                    side,
+                    level: i as u16,
+                    price: to_f64(*p),
+                    qty: to_f64(*q),
+                });
+            }
+        }
+    }
+
+    pub fn push_trade(&mut self, trade: &Trade) {
+        self.trades.push(TradeRow {
+            ts: ch_time(trade.timestamp),
+            symbol: trade.symbol.to_uppercase(),
+            trade_id: trade.trade_id,
+            price: trade.price,
+            qty: trade.qty,
+            side: match trade.side {
+                TradeSide::Buy => "buy",
+                TradeSide::Sell => "sell",
+            },
+        });
+    }
+}
+
+// Mỗi dòng một object JSON
+pub fn json_each_row<T: Serialize>(rows: &[T]) -> Result<String, SinkError> {
+    let mut body = String::new();
+    for row in rows {
+        body.push_str(&serde_json::to_string(row)?);
+        body.push('\n');
+    }
+    Ok(body)
+}
+
+// Lỗi mạng, 5xx và 429 thì thử lại; lỗi khác (sai schema, sai quyền) bỏ batch
+pub fn is_transient(err: &SinkError) -> bool {
+    match err {
+        SinkError::Http(e) => e.is_timeout() || e.is_connect() || e.is_request(),
+        SinkError::ClickHouse { status, .. } => *status >= 500 || *status == 429,
+        _ => false,
+    }
+}
+
+pub struct ClickHouseWriter {
+    config: ClickHouseConfig,
+    http: reqwest::Client,
+}
+
+impl ClickHouseWriter {
+    pub fn new(config: ClickHouseConfig) -> Self {
+        Self { config, http: net::http_client() }
+    }
+
+    async fn query_once(&self, query: &str, body: &str) -> Result<(), SinkError> {
+        let mut req = self.http.post(&self.config.url).query(&[("query", query)]).body(body.to_string());
+        if let Some(user) = &self.config.user {
+            req = req.header("X-ClickHouse-User", user);
+        }
+        if let Some(password) = &self.config.password {
+            req = req.header("X-ClickHouse-Key", password);
+        }
+        let resp = req.send().await?;
+        let status = resp.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        let message = resp.text().await.unwrap_or_default();
+        Err(SinkError::ClickHouse { status: status.as_u16(), message })
+    }
+
+    // Thử lại lỗi tạm thời với backoff gấp đôi mỗi lần
+    pub async fn query(&self, query: &str, body: &str) -> Result<(), SinkError> {
+        let mut attempt = 0;
+        loop {
+            match self.query_once(query, body).await {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt < self.config.max_retries && is_transient(&e) => {
+                    let delay = Duration::from_millis(self.config.retry_backoff_ms.saturating_mul(1 << attempt.min(10)));
+                    attempt += 1;
+                    warn!(error = %e, attempt, ?delay, "clickhouse request failed, retrying");
+                    tokio::time::sleep(delay).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    pub async fn create_tables(&self) -> Result<(), SinkError> {
+        for ddl in CREATE_TABLES {
+            self.query(&ddl.replace("{db}", &self.config.database), "").await?;
+        }
+        Ok(())
+    }
+
+    async fn insert<T: Serialize>(&self, table: &str, rows: &[T]) -> Result<(), SinkError> {
+        if rows.is_empty() {
+            return Ok(());
+        }
+        let query = format!("INSERT INTO {}.{} FORMAT JSONEachRow", self.config.database, table);
+        self.query(&query, &json_each_row(rows)?).await
+    }
+
+    // Batch lỗi (hết retry) bị bỏ để không giữ bộ nhớ vô hạn khi ClickHouse down lâu
+    pub async fn flush(&self, batch: &mut Batch) -> usize {
+        let batch = std::mem::take(batch);
+        let rows = batch.len();
+        let results = [
+            (TOPS_TABLE, self.insert(TOPS_TABLE, &batch.tops).await),
+            (LEVELS_TABLE, self.insert(LEVELS_TABLE, &batch.levels).await),
+            (TRADES_TABLE, self.insert(TRADES_TABLE, &batch.trades).await),
+        ];
+        for (table, result) in results {
+            if let Err(e) = result {
+                warn!(table, error = %e, "clickhouse insert failed, batch dropped");
+            }
+        }
+        rows
+    }
+
+    pub async fn run(self, hub: MarketHub, mut shutdown: Shutdown) {
+        if self.config.create_tables
+            && let Err(e) = self.create_tables().await
+        {
+            warn!(error = %e, "clickhouse create tables failed");
+        }
+        enum Event {
+            Book(usize),
+            Trade(Trade),
+        }
+        let feeds = hub.feeds().to_vec();
+        let books = stream::select_all(
+            feeds
+                .iter()
+                .enumerate()
+                .map(|(idx, feed)| WatchStream::from_changes(feed.watch()).map(move |_| Event::Book(idx)).boxed()),
+        );
+        let trades = BroadcastStream::new(hub.subscribe_trades()).filter_map(|r| std::future::ready(r.ok().map(Event::Trade)));
+        let mut events = stream::select(books, trades);
+        let mut ticker = tokio::time::interval(Duration::from_millis(self.config.flush_interval_ms.max(1)));
+        let mut batch = Batch::default();
+        let mut written = 0;
+        loop {
+            tokio::select! {
+                event = events.next() => match event {
+                    Some(Event::Book(idx)) => {
+                        let feed = &feeds[idx];
+                        batch.push_book(&self.config, feed.exchange(), feed.symbol(), &feed.snapshot());
+                    }
+                    Some(Event::Trade(trade)) if self.config.trades => batch.push_trade(&trade),
+                    Some(Event::Trade(_)) => {}
+                    None => break,
+                },
+                _ = ticker.tick() => {
+                    written += self.flush(&mut batch).await;
+                    continue;
+                }
+                _ = shutdown.wait() => break,
+            }
+            if batch.len() >= self.config.batch_size {
+                written += self.flush(&mut batch).await;
+            }
+        }
+        written += self.flush(&mut batch).await;
+        info!(rows = written, "clickhouse writer stopped");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::orderbook::Side;
+    use axum::{http::StatusCode, routing::post, Router};
+    use rust_decimal_macros::dec;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    #[test]
+    fn test_batch_rows() {
+        let config = ClickHouseConfig { levels: true, depth: 2, ..Default::default() };
+        let mut snap = OrderbookSnapshot::new();
+        snap.timestamp = DateTime::parse_from_rfc3339("2024-03-05T13:45:10.123Z").unwrap().with_timezone(&Utc);
+        for (p, q) in [(dec!(100), dec!(1)), (dec!(99), dec!(2)), (dec!(98), dec!(3))] {
+            snap.set_level(Side::Bid, p, q);
+        }
+        snap.set_level(Side::Ask, dec!(101), dec!(4));
+
+        let mut batch = Batch::default();
+        batch.push_book(&config, "binance", "btcusdt", &snap);
+        assert_eq!((batch.tops.len(), batch.levels.len()), (1, 3));
+        assert_eq!((batch.levels[1].side, batch.levels[1].level, batch.levels[1].price), ("bid", 1, 99.0));
+        assert_eq!((batch.levels[2].side, batch.levels[2].level), ("ask", 0));
+
+        let body = json_each_row(&batch.tops).unwrap();
+        assert_eq!(
+            body,
+            "{\"ts\":\"2024-03-05 13:45:10.123\",\"exchange\":\"binance\",\"symbol\":\"BTCUSDT\",\"last_update_id\":0,\
+             \"bid_price\":100.0,\"bid_qty\":1.0,\"ask_price\":101.0,\"ask_qty\":4.0}\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_insert_retries_transient_errors() {
+        // 503 hai lần rồi mới nhận
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let app = Router::new().route(
+            "/",
+            post(move || {
+                let counter = counter.clone();
+                async move {
+                    if counter.fetch_add(1, Ordering::SeqCst) < 2 { StatusCode::SERVICE_UNAVAILABLE } else { StatusCode::OK }
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let config = ClickHouseConfig { url: format!("http://{}/", addr), retry_backoff_ms: 1, ..Default::default() };
+        let writer = ClickHouseWriter::new(config.clone());
+        let trade = Trade { symbol: "btcusdt".into(), trade_id: 1, price: 1.0, qty: 1.0, side: TradeSide::Buy, timestamp: Utc::now() };
+        let mut batch = Batch::default();
+        batch.push_trade(&trade);
+        assert_eq!(writer.flush(&mut batch).await, 1);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert!(batch.is_empty());
+
+        // hết retry thì trả lỗi
+        let writer = ClickHouseWriter::new(ClickHouseConfig { max_retries: 0, ..config });
+        calls.store(0, Ordering::SeqCst);
+        let err = writer.query("SELECT 1", "").await.unwrap_err();
+        assert!(matches!(err, SinkError::ClickHouse { status: 503, .. }));
+        server.abort();
+    }
+}
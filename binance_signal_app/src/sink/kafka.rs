@@ -0,0 +1,456 @@
+use apache_avro::Schema;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::SinkError;
+use crate::core::{order::Fill, orderbook::OrderbookSnapshot, trade::Trade};
+use crate::recorder::tick::{BookRecord, TradeRecord};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KafkaFormat {
+    Json,
+    // Avro datum thô (không có header của schema registry), schema trong `schema_for`
+    Avro,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct KafkaTopics {
+    pub snapshots: String,
+    pub trades: String,
+    pub fills: String,
+}
+
+impl Default for KafkaTopics {
+    fn default() -> Self {
+        Self {
+            snapshots: "md.snapshots".to_string(),
+            trades: "md.trades".to_string(),
+            fills: "md.fills".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct KafkaSinkConfig {
+    pub enabled: bool,
+    pub brokers: String,
+    pub client_id: String,
+    pub format: KafkaFormat,
+    pub topics: KafkaTopics,
+    pub depth: usize,
+    // None = mọi update của book
+    pub sample_interval_ms: Option<u64>,
+    // batching do librdkafka làm: chờ tối đa linger_ms hoặc đủ batch_size message
+    pub linger_ms: u64,
+    pub batch_size: usize,
+    pub compression: String,
+}
+
+impl Default for KafkaSinkConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            brokers: "127.0.0.1:9092".to_string(),
+            client_id: "binance_signal_app".to_string(),
+            format: KafkaFormat::Json,
+            topics: KafkaTopics::default(),
+            depth: 20,
+            sample_interval_ms: None,
+            linger_ms: 5,
+            batch_size: 10_000,
+            compression: "lz4".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FillRecord {
+    pub ts: i64,
+    pub symbol: String,
+    pub order_id: u64,
+    pub side: String,
+    pub price: Decimal,
+    pub qty: Decimal,
+    pub fee: Decimal,
+    pub is_maker: bool,
+}
+
+impl From<&Fill> for FillRecord {
+    fn from(f: &Fill) -> Self {
+        Self {
+            ts: f.timestamp.timestamp_millis(),
+            symbol: f.symbol.to_uppercase(),
+            order_id: f.order_id,
+            side: format!("{:?}", f.side).to_lowercase(),
+            price: f.price,
+            qty: f.qty,
+            fee: f.fee,
+            is_maker: f.is_maker,
+        }
+    }
+}
+
+// Decimal serialize thành chuỗi nên price / qty là string trong Avro
+pub const SNAPSHOT_SCHEMA: &str = r#"{
+    "type": "record", "name": "BookRecord", "namespace": "market_data",
+    "fields": [
+        {"name": "ts", "type": "long"},
+        {"name": "symbol", "type": "string"},
+        {"name": "last_update_id", "type": "long"},
+        {"name": "bids", "type": {"type": "array", "items": {"type": "array", "items": "string"}}},
+        {"name": "asks", "type": {"type": "array", "items": {"type": "array", "items": "string"}}}
+    ]
+}"#;
+
+pub const TRADE_SCHEMA: &str = r#"{
+    "type": "record", "name": "TradeRecord", "namespace": "market_data",
+    "fields": [
+        {"name": "ts", "type": "long"},
+        {"name": "symbol", "type": "string"},
+        {"name": "trade_id", "type": "long"},
+        {"name": "price", "type": "double"},
+        {"name": "qty", "type": "double"},
+        {"name": "side", "type": "string"}
+    ]
+}"#;
+
+pub const FILL_SCHEMA: &str = r#"{
+    "type": "record", "name": "FillRecord", "namespace": "market_data",
+    "fields": [
+        {"name": "ts", "type": "long"},
+        {"name": "symbol", "type": "string"},
+        {"name": "order_id", "type": "long"},
+        {"name": "side", "type": "string"},
+        {"name": "price", "type": "string"},
+        {"name": "qty", "type": "string"},
+        {"name": "fee", "type": "string"},
+        {"name": "is_maker", "type": "boolean"}
+    ]
+}"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    Snapshot,
+    Trade,
+    Fill,
+}
+
+impl EventKind {
+    pub fn schema_name(&self) -> &'static str {
+        match self {
+            EventKind::Snapshot => "market_data.BookRecord",
+            EventKind::Trade => "market_data.TradeRecord",
+            EventKind::Fill => "market_data.FillRecord",
+        }
+    }
+}
+
+// Message đã encode, key = symbol để các event cùng symbol vào cùng partition (giữ thứ tự)
+#[derive(Debug, Clone, PartialEq)]
+pub struct KafkaMessage {
+    pub kind: EventKind,
+    pub topic: String,
+    pub key: String,
+    pub payload: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct KafkaEncoder {
+    format: KafkaFormat,
+    topics: KafkaTopics,
+    depth: usize,
+    schemas: [Schema; 3],
+}
+
+impl KafkaEncoder {
+    pub fn new(config: &KafkaSinkConfig) -> Result<Self, SinkError> {
+        Ok(Self {
+            format: config.format,
+            topics: config.topics.clone(),
+            depth: config.depth,
+            schemas: [
+                Schema::parse_str(SNAPSHOT_SCHEMA)?,
+                Schema::parse_str(TRADE_SCHEMA)?,
+                Schema::parse_str(FILL_SCHEMA)?,
+            ],
+        })
+    }
+
+    pub fn schema_for(&self, kind: EventKind) -> &Schema {
+        match kind {
+            EventKind::Snapshot => &self.schemas[0],
+            EventKind::Trade => &self.schemas[1],
+            EventKind::Fill => &self.schemas[2],
+        }
+    }
+
+    fn encode<T: Serialize>(&self, kind: EventKind, topic: &str, key: &str, record: &T) -> Result<KafkaMessage, SinkError> {
+        let payload = match self.format {
+            KafkaFormat::Json => serde_json::to_vec(record)?,
+            KafkaFormat::Avro => {
+                let value = apache_avro::to_value(record)?;
+                apache_avro::to_avro_datum(self.schema_for(kind), value)?
+            }
+        };
+        Ok(KafkaMessage { kind, topic: topic.to_string(), key: key.to_string(), payload })
+    }
+
+    pub fn snapshot(&self, symbol: &str, snap: &OrderbookSnapshot) -> Result<KafkaMessage, SinkError> {
+        let record = BookRecord::new(symbol, snap, self.depth);
+        self.encode(EventKind::Snapshot, &self.topics.snapshots, &record.symbol, &record)
+    }
+
+    pub fn trade(&self, trade: &Trade) -> Result<KafkaMessage, SinkError> {
+        let record = TradeRecord::from(trade);
+        self.encode(EventKind::Trade, &self.topics.trades, &record.symbol, &record)
+    }
+
+    pub fn fill(&self, fill: &Fill) -> Result<KafkaMessage, SinkError> {
+        let record = FillRecord::from(fill);
+        self.encode(EventKind::Fill, &self.topics.fills, &record.symbol, &record)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct KafkaMetricsSnapshot {
+    pub enqueued: u64,
+    pub delivered: u64,
+    // broker trả lỗi sau khi librdkafka hết retry
+    pub failed: u64,
+    // không vào được queue (đầy) hoặc encode lỗi
+    pub dropped: u64,
+}
+
+// Đếm từ delivery callback của librdkafka (thread riêng) nên dùng atomic
+#[derive(Debug, Default)]
+pub struct KafkaMetrics {
+    enqueued: AtomicU64,
+    delivered: AtomicU64,
+    failed: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl KafkaMetrics {
+    pub fn on_enqueued(&self) {
+        self.enqueued.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn on_delivery(&self, ok: bool) {
+        let counter = if ok { &self.delivered } else { &self.failed };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn on_dropped(&self) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> KafkaMetricsSnapshot {
+        KafkaMetricsSnapshot {
+            enqueued: self.enqueued.load(Ordering::Relaxed),
+            delivered: self.delivered.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(feature = "kafka")]
+pub use producer::KafkaSink;
+
+#[cfg(feature = "kafka")]
+mod producer {
+    use chrono::Duration;
+    use futures_util::{stream, StreamExt};
+    use rdkafka::{
+        config::ClientConfig,
+        message::{Header, OwnedHeaders},
+        producer::{BaseRecord, DeliveryResult, Producer, ProducerContext, ThreadedProducer},
+        ClientContext,
+    };
+    use std::{sync::Arc, time::Duration as StdDuration};
+    use tokio_stream::wrappers::{BroadcastStream, WatchStream};
+    use tracing::{info, warn};
+
+    use super::{KafkaEncoder, KafkaFormat, KafkaMessage, KafkaMetrics, KafkaSinkConfig};
+    use crate::core::{backpressure::LosslessReceiver, order::Fill, trade::Trade};
+    use crate::hub::MarketHub;
+    use crate::recorder::Sampler;
+    use crate::sink::SinkError;
+    use crate::supervisor::Shutdown;
+
+    const FLUSH_TIMEOUT: StdDuration = StdDuration::from_secs(5);
+
+    struct MetricsContext {
+        metrics: Arc<KafkaMetrics>,
+    }
+
+    impl ClientContext for MetricsContext {}
+
+    impl ProducerContext for MetricsContext {
+        type DeliveryOpaque = ();
+
+        fn delivery(&self, result: &DeliveryResult<'_>, _: ()) {
+            if let Err((e, _)) = result {
+                warn!(error = %e, "kafka delivery failed");
+            }
+            self.metrics.on_delivery(result.is_ok());
+        }
+    }
+
+    enum Event {
+        Book(usize),
+        Trade(Trade),
+        Fill(Fill),
+    }
+
+    pub struct KafkaSink {
+        config: KafkaSinkConfig,
+        encoder: KafkaEncoder,
+        producer: ThreadedProducer<MetricsContext>,
+        metrics: Arc<KafkaMetrics>,
+    }
+
+    impl KafkaSink {
+        pub fn new(config: KafkaSinkConfig) -> Result<Self, SinkError> {
+            let metrics = Arc::new(KafkaMetrics::default());
+            let producer = ClientConfig::new()
+                .set("bootstrap.servers", &config.brokers)
+                .set("client.id", &config.client_id)
+                .set("linger.ms", config.linger_ms.to_string())
+                .set("batch.num.messages", config.batch_size.to_string())
+                .set("compression.type", &config.compression)
+                .create_with_context(MetricsContext { metrics: metrics.clone() })?;
+            let encoder = KafkaEncoder::new(&config)?;
+            info!(brokers = %config.brokers, format = ?config.format, "kafka sink created");
+            Ok(Self { config, encoder, producer, metrics })
+        }
+
+        pub fn metrics(&self) -> Arc<KafkaMetrics> {
+            self.metrics.clone()
+        }
+
+        fn send(&self, msg: KafkaMessage) {
+            let content_type = match self.config.format {
+                KafkaFormat::Json => "application/json",
+                KafkaFormat::Avro => "avro/binary",
+            };
+            let headers = OwnedHeaders::new()
+                .insert(Header { key: "content-type", value: Some(content_type) })
+                .insert(Header { key: "schema", value: Some(msg.kind.schema_name()) });
+            let record = BaseRecord::to(&msg.topic).key(&msg.key).payload(&msg.payload).headers(headers);
+            match self.producer.send(record) {
+                Ok(()) => self.metrics.on_enqueued(),
+                Err((e, _)) => {
+                    self.metrics.on_dropped();
+                    warn!(topic = %msg.topic, error = %e, "kafka enqueue failed");
+                }
+            }
+        }
+
+        fn send_encoded(&self, msg: Result<KafkaMessage, SinkError>) {
+            match msg {
+                Ok(msg) => self.send(msg),
+                Err(e) => {
+                    self.metrics.on_dropped();
+                    warn!(error = %e, "kafka encode failed");
+                }
+            }
+        }
+
+        // Snapshot theo watch của feed (lấy mẫu theo `sample_interval_ms`), trade từ hub,
+        // fill nếu có venue. Flush queue trước khi dừng
+        pub async fn run(self, hub: MarketHub, fills: Option<LosslessReceiver<Fill>>, mut shutdown: Shutdown) {
+            let feeds = hub.feeds().to_vec();
+            let mut sampler = Sampler::new(self.config.sample_interval_ms.map(|ms| Duration::milliseconds(ms as i64)));
+            let books = stream::select_all(
+                feeds
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, feed)| WatchStream::from_changes(feed.watch()).map(move |_| Event::Book(idx)).boxed()),
+            );
+            let trades = BroadcastStream::new(hub.subscribe_trades())
+                .filter_map(|r| std::future::ready(r.ok().map(Event::Trade)))
+                .boxed();
+            let fills = match fills {
+                Some(rx) => rx.into_stream().map(Event::Fill).boxed(),
+                None => stream::empty().boxed(),
+            };
+            let mut events = stream::select(books, stream::select(trades, fills));
+            loop {
+                let event = tokio::select! {
+                    event = events.next() => match event {
+                        Some(event) => event,
+                        None => break,
+                    },
+                    _ = shutdown.wait() => break,
+                };
+                match event {
+                    Event::Book(idx) => {
+                        let feed = &feeds[idx];
+                        let snap = feed.snapshot();
+                        if sampler.should_record(feed.symbol(), snap.timestamp) {
+                            self.send_encoded(self.encoder.snapshot(feed.symbol(), &snap));
+                        }
+                    }
+                    Event::Trade(trade) => self.send_encoded(self.encoder.trade(&trade)),
+                    Event::Fill(fill) => self.send_encoded(self.encoder.fill(&fill)),
+                }
+            }
+            let producer = self.producer;
+            let flushed = tokio::task::spawn_blocking(move || producer.flush(FLUSH_TIMEOUT)).await;
+            if !matches!(flushed, Ok(Ok(()))) {
+                warn!("kafka flush timed out, pending messages lost");
+            }
+            let m = self.metrics.snapshot();
+            info!(enqueued = m.enqueued, delivered = m.delivered, failed = m.failed, dropped = m.dropped, "kafka sink stopped");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{order::OrderSide, orderbook::Side, trade::TradeSide};
+    use chrono::Utc;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_encode_json_and_avro() {
+        let mut snap = OrderbookSnapshot::new();
+        snap.set_level(Side::Bid, dec!(100), dec!(1));
+        snap.set_level(Side::Ask, dec!(101), dec!(2));
+        let trade = Trade { symbol: "btcusdt".into(), trade_id: 7, price: 100.5, qty: 0.1, side: TradeSide::Sell, timestamp: Utc::now() };
+        let fill = Fill {
+            order_id: 1,
+            symbol: "btcusdt".into(),
+            side: OrderSide::Buy,
+            price: dec!(100),
+            qty: dec!(0.5),
+            fee: dec!(0.05),
+            is_maker: true,
+            timestamp: Utc::now(),
+        };
+
+        let json = KafkaEncoder::new(&KafkaSinkConfig::default()).unwrap();
+        let msg = json.snapshot("btcusdt", &snap).unwrap();
+        assert_eq!((msg.topic.as_str(), msg.key.as_str()), ("md.snapshots", "BTCUSDT"));
+        let book: BookRecord = serde_json::from_slice(&msg.payload).unwrap();
+        assert_eq!(book.bids, vec![(dec!(100), dec!(1))]);
+
+        // Avro đọc lại được bằng đúng schema
+        let avro = KafkaEncoder::new(&KafkaSinkConfig { format: KafkaFormat::Avro, ..Default::default() }).unwrap();
+        for msg in [avro.snapshot("btcusdt", &snap).unwrap(), avro.trade(&trade).unwrap(), avro.fill(&fill).unwrap()] {
+            let schema = avro.schema_for(msg.kind);
+            let value = apache_avro::from_avro_datum(schema, &mut msg.payload.as_slice(), None).unwrap();
+            match msg.kind {
+                EventKind::Snapshot => assert_eq!(apache_avro::from_value::<BookRecord>(&value).unwrap(), book),
+                EventKind::Trade => assert_eq!(apache_avro::from_value::<TradeRecord>(&value).unwrap().trade_id, 7),
+                EventKind::Fill => assert_eq!(apache_avro::from_value::<FillRecord>(&value).unwrap(), FillRecord::from(&fill)),
+            }
+        }
+    }
+}
@@ -0,0 +1,65 @@
+pub mod clickhouse;
+pub mod kafka;
+pub mod redis;
+
+use std::fmt;
+
+#[derive(Debug)]
+pub enum SinkError {
+    Redis(::redis::RedisError),
+    Json(serde_json::Error),
+    // apache_avro::Error rất lớn (~200 byte), box để Result không phình
+    Avro(Box<apache_avro::Error>),
+    Http(reqwest::Error),
+    // HTTP status khác 2xx kèm body lỗi của ClickHouse
+    ClickHouse { status: u16, message: String },
+    #[cfg(feature = "kafka")]
+    Kafka(rdkafka::error::KafkaError),
+}
+
+impl fmt::Display for SinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SinkError::Redis(e) => write!(f, "redis error: {}", e),
+            SinkError::Json(e) => write!(f, "json error: {}", e),
+            SinkError::Avro(e) => write!(f, "avro error: {}", e),
+            SinkError::Http(e) => write!(f, "http error: {}", e),
+            SinkError::ClickHouse { status, message } => write!(f, "clickhouse error {}: {}", status, message.trim()),
+            #[cfg(feature = "kafka")]
+            SinkError::Kafka(e) => write!(f, "kafka error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SinkError {}
+
+impl From<::redis::RedisError> for SinkError {
+    fn from(e: ::redis::RedisError) -> Self {
+        SinkError::Redis(e)
+    }
+}
+
+impl From<serde_json::Error> for SinkError {
+    fn from(e: serde_json::Error) -> Self {
+        SinkError::Json(e)
+    }
+}
+
+impl From<apache_avro::Error> for SinkError {
+    fn from(e: apache_avro::Error) -> Self {
+        SinkError::Avro(Box::new(e))
+    }
+}
+
+impl From<reqwest::Error> for SinkError {
+    fn from(e: reqwest::Error) -> Self {
+        SinkError::Http(e)
+    }
+}
+
+#[cfg(feature = "kafka")]
+impl From<rdkafka::error::KafkaError> for SinkError {
+    fn from(e: rdkafka::error::KafkaError) -> Self {
+        SinkError::Kafka(e)
+    }
+}
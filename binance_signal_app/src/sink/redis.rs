@@ -0,0 +1,269 @@
+use chrono::{DateTime, Utc};
+use futures_util::{stream, StreamExt};
+use redis::aio::ConnectionManager;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use tokio_stream::wrappers::{BroadcastStream, WatchStream};
+use tracing::{info, warn};
+
+use super::SinkError;
+use crate::core::{orderbook::OrderbookSnapshot, signal::SignalOutput};
+use crate::hub::MarketHub;
+use crate::supervisor::Shutdown;
+use crate::ws::{BestBidAsk, OrderbookFeed};
+
+// Channel: {prefix}:best:BTCUSDT, {prefix}:book:BTCUSDT, {prefix}:signal:BTCUSDT.
+// Stream (XADD, field "data") dùng cùng tên key khi bật `stream_maxlen`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RedisSinkConfig {
+    pub enabled: bool,
+    pub url: String,
+    pub prefix: String,
+    // chỉ publish khi best bid/ask đổi
+    pub best: bool,
+    // snapshot `depth` level mỗi update, tốn băng thông nên mặc định tắt
+    pub snapshots: bool,
+    pub depth: usize,
+    pub signals: bool,
+    // None = chỉ pub/sub, Some(n) = thêm XADD với MAXLEN ~ n
+    pub stream_maxlen: Option<usize>,
+}
+
+impl Default for RedisSinkConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: "redis://127.0.0.1:6379".to_string(),
+            prefix: "md".to_string(),
+            best: true,
+            snapshots: false,
+            depth: 20,
+            signals: true,
+            stream_maxlen: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BestTick {
+    pub exchange: String,
+    pub symbol: String,
+    pub timestamp: DateTime<Utc>,
+    pub bid_price: Decimal,
+    pub bid_qty: Decimal,
+    pub ask_price: Decimal,
+    pub ask_qty: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BookMessage {
+    pub exchange: String,
+    pub symbol: String,
+    pub timestamp: DateTime<Utc>,
+    pub last_update_id: u64,
+    // [price, qty], bid giá giảm dần
+    pub bids: Vec<(Decimal, Decimal)>,
+    pub asks: Vec<(Decimal, Decimal)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SignalMessage {
+    pub symbol: String,
+    pub timestamp: DateTime<Utc>,
+    pub values: BTreeMap<String, f64>,
+}
+
+// (key, payload json) sẽ được PUBLISH (và XADD)
+pub type Outgoing = (String, String);
+
+// Chuyển event thành message theo config, tách khỏi kết nối để test được
+#[derive(Debug)]
+pub struct RedisEncoder {
+    config: RedisSinkConfig,
+    last_best: HashMap<(&'static str, String), BestBidAsk>,
+}
+
+impl RedisEncoder {
+    pub fn new(config: RedisSinkConfig) -> Self {
+        Self { config, last_best: HashMap::new() }
+    }
+
+    pub fn key(&self, kind: &str, symbol: &str) -> String {
+        format!("{}:{}:{}", self.config.prefix, kind, symbol.to_uppercase())
+    }
+
+    pub fn on_orderbook(
+        &mut self,
+        exchange: &'static str,
+        symbol: &str,
+        snap: &OrderbookSnapshot,
+    ) -> Result<Vec<Outgoing>, SinkError> {
+        let mut out = Vec::new();
+        let symbol = symbol.to_uppercase();
+        if self.config.best
+            && let Some(best) = snap.best_bid_ask()
+            && self.last_best.insert((exchange, symbol.clone()), best) != Some(best)
+        {
+            let ((bid_price, bid_qty), (ask_price, ask_qty)) = best;
+            let tick = BestTick {
+                exchange: exchange.to_string(),
+                symbol: symbol.clone(),
+                timestamp: snap.timestamp,
+                bid_price,
+                bid_qty,
+                ask_price,
+                ask_qty,
+            };
+            out.push((self.key("best", &symbol), serde_json::to_string(&tick)?));
+        }
+        if self.config.snapshots {
+            let depth = self.config.depth;
+            let book = BookMessage {
+                exchange: exchange.to_string(),
+                symbol: symbol.clone(),
+                timestamp: snap.timestamp,
+                last_update_id: snap.last_update_id,
+                bids: snap.bids.iter().rev().take(depth).map(|(p, q)| (*p, *q)).collect(),
+                asks: snap.asks.iter().take(depth).map(|(p, q)| (*p, *q)).collect(),
+            };
+            out.push((self.key("book", &symbol), serde_json::to_string(&book)?));
+        }
+        Ok(out)
+    }
+
+    pub fn on_signal(&self, out: &SignalOutput) -> Result<Option<Outgoing>, SinkError> {
+        if !self.config.signals {
+            return Ok(None);
+        }
+        let msg = SignalMessage {
+            symbol: out.symbol.to_uppercase(),
+            timestamp: out.timestamp,
+            values: out.values.iter().cloned().collect(),
+        };
+        Ok(Some((self.key("signal", &msg.symbol), serde_json::to_string(&msg)?)))
+    }
+}
+
+enum Event {
+    Book(usize),
+    Signal(SignalOutput),
+}
+
+pub struct RedisSink {
+    encoder: RedisEncoder,
+    conn: ConnectionManager,
+}
+
+impl RedisSink {
+    // ConnectionManager tự reconnect, lỗi lúc publish chỉ log và bỏ message
+    pub async fn connect(config: RedisSinkConfig) -> Result<Self, SinkError> {
+        let client = redis::Client::open(config.url.as_str())?;
+        let conn = client.get_connection_manager().await?;
+        info!(url = %config.url, prefix = %config.prefix, "redis sink connected");
+        Ok(Self { encoder: RedisEncoder::new(config), conn })
+    }
+
+    pub async fn publish(&mut self, messages: &[Outgoing]) -> Result<(), SinkError> {
+        if messages.is_empty() {
+            return Ok(());
+        }
+        let mut pipe = redis::pipe();
+        for (key, payload) in messages {
+            pipe.publish(key, payload).ignore();
+            if let Some(maxlen) = self.encoder.config.stream_maxlen {
+                pipe.cmd("XADD")
+                    .arg(key)
+                    .arg("MAXLEN")
+                    .arg("~")
+                    .arg(maxlen)
+                    .arg("*")
+                    .arg("data")
+                    .arg(payload)
+                    .ignore();
+            }
+        }
+        pipe.query_async::<_, ()>(&mut self.conn).await?;
+        Ok(())
+    }
+
+    // Book theo watch (chỉ bản mới nhất khi Redis chậm), signal theo broadcast
+    pub async fn run(mut self, hub: MarketHub, mut shutdown: Shutdown) {
+        let feeds = hub.feeds().to_vec();
+        let books = stream::select_all(
+            feeds
+                .iter()
+                .enumerate()
+                .map(|(idx, feed)| WatchStream::new(feed.watch()).map(move |_| Event::Book(idx)).boxed()),
+        );
+        let signals =
+            BroadcastStream::new(hub.subscribe_signals()).filter_map(|r| std::future::ready(r.ok().map(Event::Signal)));
+        let mut events = stream::select(books, signals);
+        let mut failures = 0u64;
+        loop {
+            let event = tokio::select! {
+                event = events.next() => match event {
+                    Some(event) => event,
+                    None => break,
+                },
+                _ = shutdown.wait() => break,
+            };
+            let messages = match event {
+                Event::Book(idx) => {
+                    let feed: &dyn OrderbookFeed = feeds[idx].as_ref();
+                    self.encoder.on_orderbook(feed.exchange(), feed.symbol(), &feed.snapshot())
+                }
+                Event::Signal(out) => self.encoder.on_signal(&out).map(|m| m.into_iter().collect()),
+            };
+            let result = match messages {
+                Ok(messages) => self.publish(&messages).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                failures += 1;
+                // tránh spam log khi Redis down lâu
+                if failures.is_power_of_two() {
+                    warn!(error = %e, failures, "redis publish failed");
+                }
+            }
+        }
+        info!(failures, "redis sink stopped");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::orderbook::Side;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_encoder_dedups_best_and_formats_keys() {
+        let config = RedisSinkConfig { snapshots: true, depth: 1, ..Default::default() };
+        let mut encoder = RedisEncoder::new(config);
+        let mut snap = OrderbookSnapshot::new();
+        snap.set_level(Side::Bid, dec!(100), dec!(1));
+        snap.set_level(Side::Bid, dec!(99), dec!(1));
+        snap.set_level(Side::Ask, dec!(101), dec!(2));
+
+        let out = encoder.on_orderbook("binance", "btcusdt", &snap).unwrap();
+        let keys: Vec<&str> = out.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, vec!["md:best:BTCUSDT", "md:book:BTCUSDT"]);
+        let best: serde_json::Value = serde_json::from_str(&out[0].1).unwrap();
+        assert_eq!((best["bid_price"].as_str(), best["ask_qty"].as_str()), (Some("100"), Some("2")));
+        let book: serde_json::Value = serde_json::from_str(&out[1].1).unwrap();
+        assert_eq!(book["bids"].as_array().unwrap().len(), 1);
+
+        // level sâu đổi, best giữ nguyên -> chỉ còn snapshot
+        snap.set_level(Side::Bid, dec!(99), dec!(5));
+        let out = encoder.on_orderbook("binance", "btcusdt", &snap).unwrap();
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].0, "md:book:BTCUSDT");
+
+        let signal = SignalOutput { symbol: "btcusdt".into(), timestamp: Utc::now(), values: vec![("ofi_1s".into(), 1.5)] };
+        let (key, payload) = encoder.on_signal(&signal).unwrap().unwrap();
+        assert_eq!(key, "md:signal:BTCUSDT");
+        assert!(payload.contains(r#""ofi_1s":1.5"#));
+    }
+}
@@ -0,0 +1,344 @@
+use chrono::{DateTime, Duration, Utc};
+use futures_util::future::select_all;
+use rust_decimal::Decimal;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::info;
+
+use crate::core::orderbook::OrderbookSnapshot;
+use crate::fees::FeeModel;
+use crate::ws::OrderbookFeed;
+
+const BPS: Decimal = Decimal::from_parts(10_000, 0, 0, false, 0);
+const OPPORTUNITY_CHANNEL_CAPACITY: usize = 1024;
+
+// Một sàn tham gia so sánh, cùng một cặp tiền (symbol có thể khác nhau giữa các sàn)
+#[derive(Clone)]
+pub struct ArbLeg {
+    pub feed: Arc<dyn OrderbookFeed>,
+    pub taker_fee_bps: Decimal,
+}
+
+impl ArbLeg {
+    pub fn new(feed: Arc<dyn OrderbookFeed>, taker_fee_bps: Decimal) -> Self {
+        Self { feed, taker_fee_bps }
+    }
+
+    // phí taker theo sàn của feed
+    pub fn with_fees(feed: Arc<dyn OrderbookFeed>, fees: &FeeModel) -> Self {
+        let taker_fee_bps = fees.schedule_by_name(feed.exchange()).taker_bps;
+        Self { feed, taker_fee_bps }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ArbConfig {
+    // chỉ emit khi lợi nhuận sau phí >= ngưỡng này
+    pub min_net_spread_bps: Decimal,
+    pub max_qty: Option<Decimal>,
+    // bỏ qua sàn có book cũ hơn, tránh báo cơ hội ảo
+    pub max_book_age: Duration,
+}
+
+impl Default for ArbConfig {
+    fn default() -> Self {
+        Self {
+            min_net_spread_bps: Decimal::ONE,
+            max_qty: None,
+            max_book_age: Duration::seconds(2),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArbOpportunity {
+    pub timestamp: DateTime<Utc>,
+    pub pair: String,
+    // mua (taker) ở sàn này ...
+    pub buy_exchange: String,
+    pub buy_symbol: String,
+    // ... bán (taker) ở sàn này
+    pub sell_exchange: String,
+    pub sell_symbol: String,
+    // khối lượng khớp được trong phạm vi depth hiện có và còn lãi
+    pub qty: Decimal,
+    // giá trung bình (VWAP) mỗi phía
+    pub buy_price: Decimal,
+    pub sell_price: Decimal,
+    // best bid bán - best ask mua, trước phí
+    pub gross_spread_bps: Decimal,
+    // trên VWAP, sau phí cả hai phía
+    pub net_spread_bps: Decimal,
+    // lợi nhuận theo quote currency, sau phí
+    pub profit: Decimal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Execution {
+    pub qty: Decimal,
+    pub buy_price: Decimal,
+    pub sell_price: Decimal,
+    pub profit: Decimal,
+}
+
+// Ăn dần ask của `buy` và bid của `sell` theo từng mức giá khi
+// bid * (1 - phí bán) vẫn lớn hơn ask * (1 + phí mua)
+pub fn executable(
+    buy: &OrderbookSnapshot,
+    buy_fee_bps: Decimal,
+    sell: &OrderbookSnapshot,
+    sell_fee_bps: Decimal,
+    max_qty: Option<Decimal>,
+) -> Option<Execution> {
+    let buy_mult = Decimal::ONE + buy_fee_bps / BPS;
+    let sell_mult = Decimal::ONE - sell_fee_bps / BPS;
+
+    let mut asks = buy.asks.iter().map(|(p, q)| (*p, *q));
+    let mut bids = sell.bids.iter().rev().map(|(p, q)| (*p, *q));
+    let (mut ask, mut bid) = (asks.next()?, bids.next()?);
+
+    let mut qty = Decimal::ZERO;
+    let mut cost = Decimal::ZERO;
+    let mut proceeds = Decimal::ZERO;
+    loop {
+        if bid.0 * sell_mult <= ask.0 * buy_mult {
+            break;
+        }
+        let mut take = ask.1.min(bid.1);
+        if let Some(max) = max_qty {
+            take = take.min(max - qty);
+        }
+        if take <= Decimal::ZERO {
+            break;
+        }
+        qty += take;
+        cost += take * ask.0;
+        proceeds += take * bid.0;
+        ask.1 -= take;
+        bid.1 -= take;
+
+        if ask.1.is_zero() {
+            match asks.next() {
+                Some(next) => ask = next,
+                None => break,
+            }
+        }
+        if bid.1.is_zero() {
+            match bids.next() {
+                Some(next) => bid = next,
+                None => break,
+            }
+        }
+    }
+
+    if qty.is_zero() {
+        return None;
+    }
+    Some(Execution {
+        qty,
+        buy_price: cost / qty,
+        sell_price: proceeds / qty,
+        profit: proceeds * sell_mult - cost * buy_mult,
+    })
+}
+
+// So best bid/ask của cùng một cặp trên nhiều sàn, mọi cặp (mua ở i, bán ở j)
+pub struct SpreadMonitor {
+    pub pair: String,
+    legs: Vec<ArbLeg>,
+    config: ArbConfig,
+    tx: broadcast::Sender<ArbOpportunity>,
+}
+
+impl SpreadMonitor {
+    pub fn new(pair: &str, legs: Vec<ArbLeg>, config: ArbConfig) -> Self {
+        let (tx, _) = broadcast::channel(OPPORTUNITY_CHANNEL_CAPACITY);
+        Self { pair: pair.to_string(), legs, config, tx }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ArbOpportunity> {
+        self.tx.subscribe()
+    }
+
+    pub fn check(&self) -> Vec<ArbOpportunity> {
+        let books: Vec<Option<Arc<OrderbookSnapshot>>> = self
+            .legs
+            .iter()
+            .map(|leg| {
+                let snap = leg.feed.snapshot();
+                (!snap.is_stale(self.config.max_book_age)).then_some(snap)
+            })
+            .collect();
+
+        let mut out = Vec::new();
+        for (i, buy) in self.legs.iter().enumerate() {
+            for (j, sell) in self.legs.iter().enumerate() {
+                if i == j {
+                    continue;
+                }
+                let (Some(buy_book), Some(sell_book)) = (&books[i], &books[j]) else {
+                    continue;
+                };
+                if let Some(opp) = self.evaluate(buy, buy_book, sell, sell_book) {
+                    out.push(opp);
+                }
+            }
+        }
+        out
+    }
+
+    fn evaluate(
+        &self,
+        buy: &ArbLeg,
+        buy_book: &OrderbookSnapshot,
+        sell: &ArbLeg,
+        sell_book: &OrderbookSnapshot,
+    ) -> Option<ArbOpportunity> {
+        let (best_ask, _) = buy_book.best_ask()?;
+        let (best_bid, _) = sell_book.best_bid()?;
+        let exec = executable(buy_book, buy.taker_fee_bps, sell_book, sell.taker_fee_bps, self.config.max_qty)?;
+
+        let cost = exec.qty * exec.buy_price;
+        let net_spread_bps = exec.profit / cost * BPS;
+        if net_spread_bps < self.config.min_net_spread_bps {
+            return None;
+        }
+        Some(ArbOpportunity {
+            timestamp: Utc::now(),
+            pair: self.pair.clone(),
+            buy_exchange: buy.feed.exchange().to_string(),
+            buy_symbol: buy.feed.symbol().to_string(),
+            sell_exchange: sell.feed.exchange().to_string(),
+            sell_symbol: sell.feed.symbol().to_string(),
+            qty: exec.qty,
+            buy_price: exec.buy_price,
+            sell_price: exec.sell_price,
+            gross_spread_bps: (best_bid - best_ask) / best_ask * BPS,
+            net_spread_bps,
+            profit: exec.profit,
+        })
+    }
+
+    // Kiểm tra lại mỗi khi một trong các book thay đổi
+    pub async fn run(self) {
+        if self.legs.len() < 2 {
+            return;
+        }
+        let mut watches: Vec<_> = self.legs.iter().map(|leg| leg.feed.watch()).collect();
+        loop {
+            for opp in self.check() {
+                info!(
+                    pair = %opp.pair,
+                    buy = %opp.buy_exchange,
+                    sell = %opp.sell_exchange,
+                    qty = %opp.qty,
+                    net_bps = %opp.net_spread_bps.round_dp(2),
+                    profit = %opp.profit,
+                    "arbitrage opportunity"
+                );
+                let _ = self.tx.send(opp);
+            }
+            let changed = watches.iter_mut().map(|rx| Box::pin(rx.changed()));
+            if select_all(changed).await.0.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::orderbook::{SharedOrderbook, Side};
+    use async_trait::async_trait;
+    use rust_decimal_macros::dec;
+
+    struct TestFeed {
+        exchange: &'static str,
+        orderbook: SharedOrderbook,
+    }
+
+    #[async_trait]
+    impl OrderbookFeed for TestFeed {
+        fn exchange(&self) -> &'static str {
+            self.exchange
+        }
+
+        fn symbol(&self) -> &str {
+            "btcusdt"
+        }
+
+        fn orderbook(&self) -> &SharedOrderbook {
+            &self.orderbook
+        }
+
+        async fn start(self: Arc<Self>) {}
+    }
+
+    fn book(bids: &[(Decimal, Decimal)], asks: &[(Decimal, Decimal)]) -> OrderbookSnapshot {
+        let mut ob = OrderbookSnapshot::new();
+        for (p, q) in bids {
+            ob.set_level(Side::Bid, *p, *q);
+        }
+        for (p, q) in asks {
+            ob.set_level(Side::Ask, *p, *q);
+        }
+        ob
+    }
+
+    #[test]
+    fn test_executable_bounded_by_depth_and_fees() {
+        let cheap = book(&[(dec!(99), dec!(5))], &[(dec!(100), dec!(1)), (dec!(100.5), dec!(2)), (dec!(102), dec!(10))]);
+        let rich = book(&[(dec!(101.5), dec!(2)), (dec!(101), dec!(5))], &[(dec!(102), dec!(5))]);
+
+        // không phí: ăn 1 @100 + 2 @100.5 rồi dừng vì ask 102 > bid 101
+        let exec = executable(&cheap, Decimal::ZERO, &rich, Decimal::ZERO, None).unwrap();
+        assert_eq!(exec.qty, dec!(3));
+        assert_eq!(exec.profit, dec!(3));
+
+        // phí 50bps mỗi phía -> chỉ còn mức 100 / 101.5 có lãi
+        let exec = executable(&cheap, dec!(50), &rich, dec!(50), None).unwrap();
+        assert_eq!(exec.qty, dec!(1));
+        assert_eq!((exec.buy_price, exec.sell_price), (dec!(100), dec!(101.5)));
+        assert_eq!(exec.profit, dec!(0.4925));
+
+        assert_eq!(executable(&cheap, Decimal::ZERO, &rich, Decimal::ZERO, Some(dec!(0.5))).unwrap().qty, dec!(0.5));
+        // chiều ngược lại không có cơ hội
+        assert_eq!(executable(&rich, Decimal::ZERO, &cheap, Decimal::ZERO, None), None);
+    }
+
+    #[tokio::test]
+    async fn test_monitor_emits_opportunities() {
+        let a = Arc::new(TestFeed { exchange: "binance", orderbook: SharedOrderbook::new() });
+        let b = Arc::new(TestFeed { exchange: "okx", orderbook: SharedOrderbook::new() });
+        let legs = vec![ArbLeg::new(a.clone(), dec!(10)), ArbLeg::with_fees(b.clone(), &FeeModel::default())];
+        assert_eq!(legs[1].taker_fee_bps, dec!(10));
+        let monitor = SpreadMonitor::new("BTC/USDT", legs.clone(), ArbConfig::default());
+        let checker = SpreadMonitor::new("BTC/USDT", legs, ArbConfig::default());
+        let mut rx = monitor.subscribe();
+        tokio::spawn(monitor.run());
+
+        b.orderbook.update(|ob| {
+            *ob = book(&[(dec!(101), dec!(1))], &[(dec!(101.1), dec!(1))]);
+            true
+        });
+        a.orderbook.update(|ob| {
+            *ob = book(&[(dec!(99.9), dec!(2))], &[(dec!(100), dec!(2))]);
+            true
+        });
+
+        let opp = rx.recv().await.unwrap();
+        assert_eq!((opp.buy_exchange.as_str(), opp.sell_exchange.as_str()), ("binance", "okx"));
+        assert_eq!(opp.qty, dec!(1));
+        assert_eq!(opp.gross_spread_bps, dec!(100));
+        assert_eq!(opp.net_spread_bps, dec!(79.9));
+        assert_eq!(checker.check().len(), 1);
+
+        // book cũ thì bỏ qua
+        a.orderbook.update(|ob| {
+            ob.timestamp = Utc::now() - Duration::seconds(10);
+            true
+        });
+        assert!(checker.check().is_empty());
+    }
+}
@@ -0,0 +1,327 @@
+use chrono::{DateTime, Duration, Utc};
+use futures_util::future::select_all;
+use rust_decimal::{
+    prelude::{FromPrimitive, ToPrimitive},
+    Decimal, RoundingStrategy,
+};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::info;
+
+use crate::core::orderbook::OrderbookSnapshot;
+use crate::dex::{pool::AmmCurve, DexPoolFeed, GasModel};
+use crate::ws::OrderbookFeed;
+use super::arb::ArbLeg;
+
+const BPS: Decimal = Decimal::from_parts(10_000, 0, 0, false, 0);
+const OPPORTUNITY_CHANNEL_CAPACITY: usize = 1024;
+// qty tính trên f64 được làm tròn xuống trước khi đổi sang Decimal
+const QTY_DP: u32 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CexDexDirection {
+    // swap quote -> base trên pool, bán base (taker) trên sàn
+    BuyDex,
+    // mua base (taker) trên sàn, swap base -> quote trên pool
+    SellDex,
+}
+
+#[derive(Debug, Clone)]
+pub struct CexDexConfig {
+    // lợi nhuận tối thiểu theo quote sau phí và gas
+    pub min_profit: Decimal,
+    pub min_net_spread_bps: Decimal,
+    pub max_qty: Option<Decimal>,
+    pub max_book_age: Duration,
+    pub gas: GasModel,
+}
+
+impl Default for CexDexConfig {
+    fn default() -> Self {
+        Self {
+            min_profit: Decimal::ZERO,
+            min_net_spread_bps: Decimal::ONE,
+            max_qty: None,
+            // pool chỉ đổi theo block, poll chậm hơn WS
+            max_book_age: Duration::seconds(5),
+            gas: GasModel::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CexDexOpportunity {
+    pub timestamp: DateTime<Utc>,
+    pub pair: String,
+    pub direction: CexDexDirection,
+    pub dex_pool: String,
+    pub cex_exchange: String,
+    pub cex_symbol: String,
+    pub qty: Decimal,
+    // giá trung bình mỗi phía, giá pool đã gồm phí swap
+    pub dex_price: Decimal,
+    pub cex_price: Decimal,
+    // giá pool (chưa phí) so với best bid/ask của sàn
+    pub gross_spread_bps: Decimal,
+    pub cex_fee: Decimal,
+    pub gas_cost: Decimal,
+    // theo quote, sau phí swap, phí taker và gas
+    pub profit: Decimal,
+    pub net_spread_bps: Decimal,
+}
+
+// Lượng base đi qua được khi giá biên của pool (gồm phí) còn tốt hơn giá sau phí
+// của từng level trên sàn. `levels` xếp từ giá tốt nhất, trả về 0 nếu không có chênh lệch
+pub fn size_against_curve(
+    curve: &AmmCurve,
+    direction: CexDexDirection,
+    levels: impl Iterator<Item = (Decimal, Decimal)>,
+    cex_fee_bps: Decimal,
+    max_qty: Option<Decimal>,
+) -> f64 {
+    let fee = (cex_fee_bps / BPS).to_f64().unwrap_or_default();
+    let max = max_qty.and_then(|q| q.to_f64()).unwrap_or(f64::INFINITY);
+    let mut qty = 0.0;
+    for (price, level_qty) in levels {
+        let (price, level_qty) = (price.to_f64().unwrap_or_default(), level_qty.to_f64().unwrap_or_default());
+        let target = match direction {
+            CexDexDirection::BuyDex => curve.buy_until(price * (1.0 - fee)),
+            CexDexDirection::SellDex => curve.sell_until(price * (1.0 + fee)),
+        };
+        let next = (qty + level_qty).min(target).min(max);
+        if next <= qty {
+            break;
+        }
+        let filled_level = next >= qty + level_qty;
+        qty = next;
+        if !filled_level {
+            break;
+        }
+    }
+    qty
+}
+
+// notional khi khớp `qty` lần lượt qua các level, None nếu book không đủ
+fn fill_notional(levels: impl Iterator<Item = (Decimal, Decimal)>, qty: Decimal) -> Option<Decimal> {
+    let mut left = qty;
+    let mut notional = Decimal::ZERO;
+    for (price, level_qty) in levels {
+        let take = left.min(level_qty);
+        notional += take * price;
+        left -= take;
+        if left.is_zero() {
+            return Some(notional);
+        }
+    }
+    None
+}
+
+// So pool DEX với từng sàn CEX cùng cặp, cả hai chiều, tính trên đường cong của pool
+// (không qua book tổng hợp) rồi trừ phí taker của sàn và gas của swap
+pub struct CexDexMonitor {
+    pub pair: String,
+    dex: Arc<DexPoolFeed>,
+    legs: Vec<ArbLeg>,
+    config: CexDexConfig,
+    tx: broadcast::Sender<CexDexOpportunity>,
+}
+
+impl CexDexMonitor {
+    pub fn new(dex: Arc<DexPoolFeed>, legs: Vec<ArbLeg>, config: CexDexConfig) -> Self {
+        let (tx, _) = broadcast::channel(OPPORTUNITY_CHANNEL_CAPACITY);
+        let pair = dex.instrument().map_or_else(|| dex.symbol().to_string(), |i| i.canonical());
+        Self { pair, dex, legs, config, tx }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<CexDexOpportunity> {
+        self.tx.subscribe()
+    }
+
+    pub fn check(&self) -> Vec<CexDexOpportunity> {
+        if self.dex.is_stale(self.config.max_book_age) {
+            return Vec::new();
+        }
+        let Some(curve) = self.dex.curve() else {
+            return Vec::new();
+        };
+        let mut out = Vec::new();
+        for leg in &self.legs {
+            let book = leg.feed.snapshot();
+            if book.is_stale(self.config.max_book_age) {
+                continue;
+            }
+            for direction in [CexDexDirection::BuyDex, CexDexDirection::SellDex] {
+                if let Some(opp) = self.evaluate(&curve, leg, &book, direction) {
+                    out.push(opp);
+                }
+            }
+        }
+        out
+    }
+
+    fn evaluate(
+        &self,
+        curve: &AmmCurve,
+        leg: &ArbLeg,
+        book: &OrderbookSnapshot,
+        direction: CexDexDirection,
+    ) -> Option<CexDexOpportunity> {
+        let bids = || book.bids.iter().rev().map(|(p, q)| (*p, *q));
+        let asks = || book.asks.iter().map(|(p, q)| (*p, *q));
+        let qty = match direction {
+            CexDexDirection::BuyDex => size_against_curve(curve, direction, bids(), leg.taker_fee_bps, self.config.max_qty),
+            CexDexDirection::SellDex => size_against_curve(curve, direction, asks(), leg.taker_fee_bps, self.config.max_qty),
+        };
+        let qty = Decimal::from_f64(qty)?.round_dp_with_strategy(QTY_DP, RoundingStrategy::ToZero);
+        if qty.is_zero() {
+            return None;
+        }
+
+        let mid = Decimal::from_f64(curve.mid())?;
+        let gas_cost = self.config.gas.quote_cost(self.dex.pool.protocol, &self.dex.instrument()?, mid)?;
+        let q = qty.to_f64()?;
+        let (dex_quote, cex_notional, gross_spread_bps) = match direction {
+            CexDexDirection::BuyDex => {
+                let (best_bid, _) = book.best_bid()?;
+                (Decimal::from_f64(curve.quote_in(q)?)?, fill_notional(bids(), qty)?, (best_bid - mid) / mid * BPS)
+            }
+            CexDexDirection::SellDex => {
+                let (best_ask, _) = book.best_ask()?;
+                (Decimal::from_f64(curve.quote_out(q))?, fill_notional(asks(), qty)?, (mid - best_ask) / best_ask * BPS)
+            }
+        };
+        let cex_fee = cex_notional * leg.taker_fee_bps / BPS;
+        let (cost, proceeds) = match direction {
+            CexDexDirection::BuyDex => (dex_quote, cex_notional - cex_fee),
+            CexDexDirection::SellDex => (cex_notional + cex_fee, dex_quote),
+        };
+        let profit = proceeds - cost - gas_cost;
+        let net_spread_bps = profit / cost * BPS;
+        if profit < self.config.min_profit || net_spread_bps < self.config.min_net_spread_bps {
+            return None;
+        }
+        Some(CexDexOpportunity {
+            timestamp: Utc::now(),
+            pair: self.pair.clone(),
+            direction,
+            dex_pool: self.dex.pool.address.clone(),
+            cex_exchange: leg.feed.exchange().to_string(),
+            cex_symbol: leg.feed.symbol().to_string(),
+            qty,
+            dex_price: dex_quote / qty,
+            cex_price: cex_notional / qty,
+            gross_spread_bps,
+            cex_fee,
+            gas_cost,
+            profit,
+            net_spread_bps,
+        })
+    }
+
+    // Kiểm tra lại mỗi khi pool hoặc một book trên sàn thay đổi
+    pub async fn run(self) {
+        if self.legs.is_empty() {
+            return;
+        }
+        let mut watches: Vec<_> = self.legs.iter().map(|leg| leg.feed.watch()).collect();
+        watches.push(self.dex.watch());
+        loop {
+            for opp in self.check() {
+                info!(
+                    pair = %opp.pair,
+                    direction = ?opp.direction,
+                    cex = %opp.cex_exchange,
+                    qty = %opp.qty,
+                    gas = %opp.gas_cost,
+                    net_bps = %opp.net_spread_bps.round_dp(2),
+                    profit = %opp.profit,
+                    "cex-dex opportunity"
+                );
+                let _ = self.tx.send(opp);
+            }
+            let changed = watches.iter_mut().map(|rx| Box::pin(rx.changed()));
+            if select_all(changed).await.0.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::orderbook::{SharedOrderbook, Side};
+    use crate::dex::PoolConfig;
+    use async_trait::async_trait;
+    use rust_decimal_macros::dec;
+
+    struct TestFeed {
+        orderbook: SharedOrderbook,
+    }
+
+    #[async_trait]
+    impl OrderbookFeed for TestFeed {
+        fn exchange(&self) -> &'static str {
+            "binance"
+        }
+
+        fn symbol(&self) -> &str {
+            "cakebnb"
+        }
+
+        fn orderbook(&self) -> &SharedOrderbook {
+            &self.orderbook
+        }
+
+        async fn start(self: Arc<Self>) {}
+    }
+
+    #[test]
+    fn test_monitor_sizes_against_curve_net_of_gas() {
+        let pool = PoolConfig { symbol: "CAKE/BNB".into(), address: format!("0x{}", "0e".repeat(20)), ..PoolConfig::default() };
+        let dex = Arc::new(DexPoolFeed::new(pool, "http://127.0.0.1:1", std::time::Duration::from_secs(1)));
+        // giá pool 0.004 BNB, phí 25 bps
+        dex.apply(AmmCurve { base: 100_000.0, quote: 400.0, fee: 0.0025 });
+
+        let cex = Arc::new(TestFeed { orderbook: SharedOrderbook::new() });
+        cex.orderbook.update(|ob| {
+            ob.set_level(Side::Bid, dec!(0.00412), dec!(200));
+            ob.set_level(Side::Bid, dec!(0.0041), dec!(5000));
+            ob.set_level(Side::Ask, dec!(0.00413), dec!(1000));
+            true
+        });
+        let legs = vec![ArbLeg::new(cex.clone(), dec!(10))];
+        let monitor = CexDexMonitor::new(dex.clone(), legs.clone(), CexDexConfig::default());
+        assert_eq!(monitor.pair, "CAKE/BNB");
+
+        let opps = monitor.check();
+        assert_eq!(opps.len(), 1);
+        let opp = &opps[0];
+        assert_eq!(opp.direction, CexDexDirection::BuyDex);
+        // ăn hết level 0.00412, dừng giữa level 0.0041 khi giá biên pool chạm 0.0041 * (1 - 10bps)
+        let curve = dex.curve().unwrap();
+        let expected = curve.buy_until(0.0041 * 0.999);
+        assert!(opp.qty > dec!(200) && (opp.qty.to_f64().unwrap() - expected).abs() < 1e-6);
+        // gas 130k * 1 gwei = 0.00013 BNB
+        assert_eq!(opp.gas_cost, dec!(0.00013));
+        let profit = opp.qty * opp.cex_price - opp.cex_fee - opp.qty * opp.dex_price - opp.gas_cost;
+        assert!((opp.profit - profit).abs() < dec!(0.000000001));
+        assert!(opp.profit > Decimal::ZERO && opp.dex_price > dec!(0.004));
+
+        // gas đắt hơn lợi nhuận thì không emit
+        let config = CexDexConfig { gas: GasModel { price_gwei: dec!(10000), ..GasModel::default() }, ..CexDexConfig::default() };
+        assert!(CexDexMonitor::new(dex.clone(), legs.clone(), config).check().is_empty());
+
+        // sàn rẻ hơn pool: mua trên sàn, bán vào pool
+        cex.orderbook.update(|ob| {
+            ob.clear();
+            ob.set_level(Side::Bid, dec!(0.0038), dec!(1000));
+            ob.set_level(Side::Ask, dec!(0.0039), dec!(300));
+            true
+        });
+        let opps = monitor.check();
+        assert_eq!(opps.len(), 1);
+        assert_eq!((opps[0].direction, opps[0].qty), (CexDexDirection::SellDex, dec!(300)));
+        assert_eq!(opps[0].cex_price, dec!(0.0039));
+    }
+}
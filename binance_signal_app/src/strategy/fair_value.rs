@@ -0,0 +1,248 @@
+use chrono::{DateTime, Duration, Utc};
+use futures_util::future::select_all;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::{broadcast, watch};
+
+use crate::core::{
+    orderbook::{to_f64, OrderbookSnapshot},
+    trade::{Trade, TradeWindow},
+};
+use crate::supervisor::Shutdown;
+use crate::ws::OrderbookFeed;
+use super::next_trade;
+
+// `[fair_value]`: thành phần chưa có data (chưa có trade, sàn khác book cũ) bị bỏ,
+// trọng số còn lại chia lại cho đủ 1
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct FairValueConfig {
+    pub enabled: bool,
+    pub microprice_weight: f64,
+    // VWAP các trade trong trade_window_ms gần nhất
+    pub trade_weight: f64,
+    pub trade_window_ms: u64,
+    // trung bình mid của các sàn khác cùng cặp
+    pub cross_venue_weight: f64,
+    pub max_venue_age_ms: u64,
+    // EMA theo thời gian, 0 = không làm mượt
+    pub half_life_ms: u64,
+}
+
+impl Default for FairValueConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            microprice_weight: 0.6,
+            trade_weight: 0.2,
+            trade_window_ms: 5_000,
+            cross_venue_weight: 0.2,
+            max_venue_age_ms: 2_000,
+            half_life_ms: 500,
+        }
+    }
+}
+
+impl FairValueConfig {
+    pub fn validate(&self) -> Vec<String> {
+        let weights = [self.microprice_weight, self.trade_weight, self.cross_venue_weight];
+        if weights.iter().any(|w| !w.is_finite() || *w < 0.0) || weights.iter().sum::<f64>() <= 0.0 {
+            vec!["`fair_value` weights must be >= 0 with a positive sum".to_string()]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct FairPrice {
+    pub timestamp: DateTime<Utc>,
+    // sau EMA
+    pub price: f64,
+    pub raw: f64,
+}
+
+// Giá hợp lý của một cặp: microprice của sàn chính + VWAP trade + mid sàn khác
+#[derive(Debug, Clone)]
+pub struct FairValue {
+    config: FairValueConfig,
+    microprice: Option<f64>,
+    trades: TradeWindow,
+    // (timestamp, mid) theo index sàn tham chiếu
+    venues: Vec<Option<(DateTime<Utc>, f64)>>,
+    current: Option<FairPrice>,
+}
+
+impl FairValue {
+    pub fn new(config: FairValueConfig, venues: usize) -> Self {
+        let trades = TradeWindow::new(Duration::milliseconds(config.trade_window_ms as i64), 10_000);
+        Self { config, microprice: None, trades, venues: vec![None; venues], current: None }
+    }
+
+    pub fn on_book(&mut self, snap: &OrderbookSnapshot) {
+        self.microprice = snap.microprice().map(to_f64);
+    }
+
+    pub fn on_trade(&mut self, trade: &Trade) {
+        self.trades.push(trade.clone());
+    }
+
+    pub fn on_venue_book(&mut self, venue: usize, snap: &OrderbookSnapshot) {
+        if let (Some(slot), Some(mid)) = (self.venues.get_mut(venue), snap.mid_price()) {
+            *slot = Some((snap.timestamp, to_f64(mid)));
+        }
+    }
+
+    // trung bình có trọng số, chưa làm mượt
+    pub fn raw(&self, now: DateTime<Utc>) -> Option<f64> {
+        let mut parts = Vec::with_capacity(3);
+        if let Some(micro) = self.microprice {
+            parts.push((self.config.microprice_weight, micro));
+        }
+        let since = now - Duration::milliseconds(self.config.trade_window_ms as i64);
+        if let Some(vwap) = self.trades.vwap_since(since) {
+            parts.push((self.config.trade_weight, vwap));
+        }
+        let max_age = Duration::milliseconds(self.config.max_venue_age_ms as i64);
+        let mids: Vec<f64> = self.venues.iter().flatten().filter(|(ts, _)| now - *ts <= max_age).map(|(_, mid)| *mid).collect();
+        if !mids.is_empty() {
+            parts.push((self.config.cross_venue_weight, mids.iter().sum::<f64>() / mids.len() as f64));
+        }
+        let total: f64 = parts.iter().map(|(w, _)| w).sum();
+        (total > 0.0).then(|| parts.iter().map(|(w, p)| w * p).sum::<f64>() / total)
+    }
+
+    // tính lại tại `now`, EMA với half life theo khoảng cách thời gian giữa hai lần
+    pub fn update(&mut self, now: DateTime<Utc>) -> Option<FairPrice> {
+        let raw = self.raw(now)?;
+        let price = match self.current {
+            Some(prev) if self.config.half_life_ms > 0 => {
+                let dt = (now - prev.timestamp).num_milliseconds().max(0) as f64;
+                let alpha = 1.0 - 0.5f64.powf(dt / self.config.half_life_ms as f64);
+                prev.price + alpha * (raw - prev.price)
+            }
+            _ => raw,
+        };
+        let fair = FairPrice { timestamp: now, price, raw };
+        self.current = Some(fair);
+        Some(fair)
+    }
+
+    pub fn current(&self) -> Option<FairPrice> {
+        self.current
+    }
+}
+
+// Tính fair value theo feed chính + các feed tham chiếu (+ trade) và publish qua watch
+pub struct FairValuePublisher {
+    fair: FairValue,
+    primary: Arc<dyn OrderbookFeed>,
+    venues: Vec<Arc<dyn OrderbookFeed>>,
+    trades: Option<broadcast::Receiver<Trade>>,
+    tx: watch::Sender<Option<FairPrice>>,
+}
+
+impl FairValuePublisher {
+    pub fn new(config: FairValueConfig, primary: Arc<dyn OrderbookFeed>, venues: Vec<Arc<dyn OrderbookFeed>>) -> Self {
+        let fair = FairValue::new(config, venues.len());
+        Self { fair, primary, venues, trades: None, tx: watch::channel(None).0 }
+    }
+
+    pub fn with_trades(mut self, trades: broadcast::Receiver<Trade>) -> Self {
+        self.trades = Some(trades);
+        self
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<Option<FairPrice>> {
+        self.tx.subscribe()
+    }
+
+    pub async fn run(mut self, mut shutdown: Shutdown) {
+        let mut primary = self.primary.watch();
+        let mut watches: Vec<_> = self.venues.iter().map(|f| f.watch()).collect();
+        let mut trades = self.trades.take();
+        loop {
+            let venue_changed = async {
+                if watches.is_empty() {
+                    return std::future::pending().await;
+                }
+                let (res, idx, _) = select_all(watches.iter_mut().map(|rx| Box::pin(rx.changed()))).await;
+                res.ok().map(|_| idx)
+            };
+            let now = tokio::select! {
+                changed = primary.changed() => {
+                    if changed.is_err() {
+                        return;
+                    }
+                    let snap = self.primary.snapshot();
+                    self.fair.on_book(&snap);
+                    snap.timestamp
+                }
+                idx = venue_changed => match idx {
+                    Some(idx) => {
+                        let snap = self.venues[idx].snapshot();
+                        self.fair.on_venue_book(idx, &snap);
+                        snap.timestamp
+                    }
+                    // feed tham chiếu đã dừng, mid cũ sẽ hết hạn theo max_venue_age_ms
+                    None => {
+                        watches.clear();
+                        continue;
+                    }
+                },
+                trade = next_trade(&mut trades) => {
+                    self.fair.on_trade(&trade);
+                    trade.timestamp
+                }
+                _ = shutdown.wait() => return,
+            };
+            if let Some(fair) = self.fair.update(now) {
+                self.tx.send_replace(Some(fair));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{orderbook::Side, trade::TradeSide};
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    fn book(bid: Decimal, ask: Decimal, ts: DateTime<Utc>) -> OrderbookSnapshot {
+        let mut ob = OrderbookSnapshot::new();
+        ob.set_level(Side::Bid, bid, dec!(1));
+        ob.set_level(Side::Ask, ask, dec!(1));
+        ob.timestamp = ts;
+        ob
+    }
+
+    #[test]
+    fn test_blend_and_smoothing() {
+        let t0 = Utc::now();
+        let config = FairValueConfig { enabled: true, half_life_ms: 1_000, ..FairValueConfig::default() };
+        let mut fair = FairValue::new(config, 1);
+        assert_eq!(fair.update(t0), None);
+
+        // chỉ có microprice -> lấy nguyên
+        fair.on_book(&book(dec!(100), dec!(102), t0));
+        assert_eq!(fair.update(t0).map(|f| f.price), Some(101.0));
+
+        // 0.6 × 101 + 0.2 × 104 + 0.2 × 98
+        let trade = Trade { symbol: "BTCUSDT".into(), trade_id: 1, price: 104.0, qty: 1.0, side: TradeSide::Buy, timestamp: t0 };
+        fair.on_trade(&trade);
+        fair.on_venue_book(0, &book(dec!(97), dec!(99), t0));
+        let t1 = t0 + Duration::seconds(1);
+        let f = fair.update(t1).unwrap();
+        assert!((f.raw - 101.0).abs() < 1e-9);
+
+        // mid sàn khác quá max_venue_age -> bỏ, chia lại trọng số (0.6 × 101 + 0.2 × 104) / 0.8
+        let t2 = t0 + Duration::seconds(3);
+        let f = fair.update(t2).unwrap();
+        assert!((f.raw - 101.75).abs() < 1e-9);
+        // EMA: 2 half life -> đi được 3/4 quãng
+        assert!((f.price - (101.0 + 0.75 * 0.75)).abs() < 1e-9);
+        assert!(FairValueConfig { trade_weight: -1.0, ..FairValueConfig::default() }.validate().len() == 1);
+    }
+}
@@ -0,0 +1,232 @@
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::{sync::Arc, time::Duration};
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, info, warn};
+
+use crate::core::{order::OrderSide, signal::MarketData};
+use crate::portfolio::{AttributionTag, SharedPortfolio};
+use crate::risk::OrderIntent;
+use crate::supervisor::Shutdown;
+use crate::venue::{ExecutionVenue, VenueError};
+use crate::ws::OrderbookFeed;
+
+// tên strategy trong `[venue.routes]` và báo cáo attribution
+pub const STRATEGY: &str = "hedger";
+
+// Hedge inventory của market maker bằng order ngược chiều trên symbol / account khác:
+//
+//   [hedge]
+//   enabled = true
+//   # None = cùng symbol đang quote, account chọn qua [venue.routes] hedger = "perp".
+//   # Account có `market = "usdm"` thì hedge trên perp (cần binance_futures trong `exchanges`),
+//   # symbol phải nằm trong `symbols`
+//   symbol = "BTCUSDT"
+//   ratio = 1
+//   threshold = 0.05
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct HedgeConfig {
+    pub enabled: bool,
+    pub symbol: Option<String>,
+    // qty hedge cho mỗi đơn vị inventory, 0.5 = hedge một nửa
+    pub ratio: Decimal,
+    // |inventory * ratio + vị thế hedge| vượt ngưỡng này mới hedge, hedge về 0
+    pub threshold: Decimal,
+    // làm tròn xuống theo lot size của symbol hedge
+    pub lot_size: Option<Decimal>,
+    pub check_ms: u64,
+}
+
+impl Default for HedgeConfig {
+    fn default() -> Self {
+        Self { enabled: false, symbol: None, ratio: Decimal::ONE, threshold: Decimal::ZERO, lot_size: None, check_ms: 1_000 }
+    }
+}
+
+impl HedgeConfig {
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.ratio <= Decimal::ZERO {
+            errors.push("`hedge.ratio` must be > 0".to_string());
+        }
+        if self.threshold <= Decimal::ZERO {
+            errors.push("`hedge.threshold` must be > 0".to_string());
+        }
+        if self.lot_size.is_some_and(|lot| lot <= Decimal::ZERO) {
+            errors.push("`hedge.lot_size` must be > 0".to_string());
+        }
+        if self.check_ms == 0 {
+            errors.push("`hedge.check_ms` must be > 0".to_string());
+        }
+        errors
+    }
+}
+
+// Order cần gửi để đưa exposure về 0, None khi còn trong ngưỡng hoặc nhỏ hơn một lot
+pub fn hedge_order(config: &HedgeConfig, inventory: Decimal, hedged: Decimal) -> Option<(OrderSide, Decimal)> {
+    let exposure = inventory * config.ratio + hedged;
+    if exposure.abs() <= config.threshold {
+        return None;
+    }
+    let qty = match config.lot_size {
+        Some(lot) => (exposure.abs() / lot).floor() * lot,
+        None => exposure.abs(),
+    };
+    let side = if exposure > Decimal::ZERO { OrderSide::Sell } else { OrderSide::Buy };
+    (qty > Decimal::ZERO).then_some((side, qty))
+}
+
+pub struct Hedger {
+    config: HedgeConfig,
+    // symbol market maker đang quote, inventory đọc từ portfolio
+    inventory_symbol: String,
+    hedge_symbol: String,
+    portfolio: SharedPortfolio,
+    venue: Arc<dyn ExecutionVenue>,
+    // venue paper khác account cần book của symbol hedge để khớp market order
+    feed: Option<Arc<dyn OrderbookFeed>>,
+}
+
+impl Hedger {
+    pub fn new(config: HedgeConfig, inventory_symbol: &str, portfolio: SharedPortfolio, venue: Arc<dyn ExecutionVenue>) -> Self {
+        let hedge_symbol = config.symbol.as_deref().unwrap_or(inventory_symbol).to_uppercase();
+        Self { config, inventory_symbol: inventory_symbol.to_uppercase(), hedge_symbol, portfolio, venue, feed: None }
+    }
+
+    pub fn with_feed(mut self, feed: Arc<dyn OrderbookFeed>) -> Self {
+        self.feed = Some(feed);
+        self
+    }
+
+    pub fn hedge_symbol(&self) -> &str {
+        &self.hedge_symbol
+    }
+
+    // Một lần kiểm tra, trả về id order hedge nếu có gửi
+    pub async fn check(&self) -> Result<Option<String>, VenueError> {
+        if let Some(feed) = &self.feed {
+            self.venue.on_market_data(&MarketData::Orderbook { symbol: self.hedge_symbol.clone(), snap: feed.snapshot() });
+        }
+        // order hedge trước chưa khớp xong (live), vị thế chưa phản ánh
+        if !self.venue.open_orders(&self.hedge_symbol).await?.is_empty() {
+            return Ok(None);
+        }
+        let hedged = self.venue.position(&self.hedge_symbol).await?.qty;
+        // portfolio gộp fill của account hedge, cùng symbol thì position đã gồm vị thế hedge
+        let mut inventory = self.portfolio.position(&self.inventory_symbol).qty;
+        if self.hedge_symbol == self.inventory_symbol {
+            inventory -= hedged;
+        }
+        let Some((side, qty)) = hedge_order(&self.config, inventory, hedged) else {
+            debug!(%inventory, %hedged, "inventory within hedge threshold");
+            return Ok(None);
+        };
+        let tag = AttributionTag::new(STRATEGY, "inventory");
+        // market order paper khớp ngay trong `place_order`: tag trước khi fill được publish
+        let on_id = |id: &str| self.portfolio.tag_order(id, tag.clone());
+        let id = self.venue.place_order_with(&OrderIntent::market(&self.hedge_symbol, side, qty), &on_id).await?;
+        info!(symbol = %self.hedge_symbol, ?side, %qty, %inventory, %hedged, "hedge order sent");
+        Ok(Some(id))
+    }
+
+    pub async fn run(self, mut shutdown: Shutdown) {
+        let mut tick = tokio::time::interval(Duration::from_millis(self.config.check_ms));
+        tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = tick.tick() => {
+                    if let Err(e) = self.check().await {
+                        warn!(venue = self.venue.name(), symbol = %self.hedge_symbol, error = %e, "hedge failed");
+                    }
+                }
+                _ = shutdown.wait() => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        order::Fill,
+        orderbook::{OrderbookSnapshot, Side},
+    };
+    use crate::risk::RiskManager;
+    use crate::venue::paper::PaperVenue;
+    use chrono::Utc;
+    use rust_decimal_macros::dec;
+
+    #[tokio::test]
+    async fn test_hedges_inventory_above_threshold() {
+        let config = HedgeConfig { enabled: true, symbol: Some("ethusdt".into()), ratio: dec!(0.5), threshold: dec!(0.1), lot_size: Some(dec!(0.01)), ..Default::default() };
+        assert_eq!(hedge_order(&config, dec!(0.15), Decimal::ZERO), None);
+        assert_eq!(hedge_order(&config, dec!(-1.005), Decimal::ZERO), Some((OrderSide::Buy, dec!(0.50))));
+        assert_eq!(hedge_order(&config, dec!(1), dec!(-0.45)), None);
+        // mặc định threshold 0, bật mà không đặt ngưỡng thì báo lỗi
+        assert_eq!(HedgeConfig { enabled: true, ..Default::default() }.validate().len(), 1);
+
+        let portfolio = SharedPortfolio::new();
+        portfolio.update(|p| {
+            p.on_fill(&Fill {
+                order_id: 1,
+                symbol: "BTCUSDT".into(),
+                side: OrderSide::Buy,
+                price: dec!(100),
+                qty: dec!(2),
+                fee: Decimal::ZERO,
+                is_maker: true,
+                timestamp: Utc::now(),
+            })
+        });
+        let venue = Arc::new(PaperVenue::default());
+        let mut book = OrderbookSnapshot::new();
+        book.set_level(Side::Bid, dec!(10), dec!(5));
+        book.set_level(Side::Ask, dec!(10.1), dec!(5));
+        venue.on_market_data(&MarketData::Orderbook { symbol: "ETHUSDT".into(), snap: Arc::new(book) });
+
+        let hedger = Hedger::new(config, "btcusdt", portfolio, venue.clone());
+        assert!(hedger.check().await.unwrap().is_some());
+        assert_eq!(venue.position("ETHUSDT").await.unwrap().qty, dec!(-1));
+        // đã hedge đủ, không gửi thêm
+        assert_eq!(hedger.check().await.unwrap(), None);
+    }
+
+    // multi thread: task portfolio chạy song song và nhận fill market order ngay khi
+    // `place_order` publish, tag phải có trước lúc đó
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_hedge_fills_merged_and_risk_checked() {
+        let fill = |side, qty| Fill { order_id: 1, symbol: "BTCUSDT".into(), side, price: dec!(100), qty, fee: Decimal::ZERO, is_maker: true, timestamp: Utc::now() };
+        let portfolio = SharedPortfolio::new();
+        portfolio.update(|p| p.on_fill(&fill(OrderSide::Buy, dec!(2))));
+        // account hedge riêng, cùng symbol, cùng risk gate
+        let risk = RiskManager::default();
+        let venue = Arc::new(PaperVenue::default().with_risk(risk.clone()));
+        let mut book = OrderbookSnapshot::new();
+        book.set_level(Side::Bid, dec!(100), dec!(5));
+        book.set_level(Side::Ask, dec!(100.1), dec!(5));
+        venue.on_market_data(&MarketData::Orderbook { symbol: "BTCUSDT".into(), snap: Arc::new(book) });
+        let scoped = portfolio.clone().for_account("sub1");
+        tokio::spawn(scoped.clone().run(venue.fills(), Vec::new()));
+
+        let config = HedgeConfig { enabled: true, threshold: dec!(0.1), ..Default::default() };
+        let hedger = Hedger::new(config, "btcusdt", scoped, venue.clone());
+        assert!(hedger.check().await.unwrap().is_some());
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !portfolio.position("BTCUSDT").qty.is_zero() {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .unwrap();
+        let report = portfolio.attribution();
+        let hedge = report.rows.iter().find(|r| r.strategy == STRATEGY).unwrap();
+        assert_eq!((hedge.trigger.as_str(), hedge.qty), ("inventory", dec!(-2)));
+        assert_eq!(hedger.check().await.unwrap(), None);
+
+        portfolio.update(|p| p.on_fill(&fill(OrderSide::Buy, dec!(1))));
+        risk.kill_switch().trigger("test");
+        assert!(matches!(hedger.check().await, Err(VenueError::Risk(_))));
+    }
+}
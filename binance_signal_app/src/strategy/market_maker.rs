@@ -0,0 +1,488 @@
+use rust_decimal::Decimal;
+use std::{sync::Arc, time::Duration};
+use tokio::sync::{broadcast, watch};
+use tokio::time::{Instant, MissedTickBehavior};
+use tracing::{debug, info, warn};
+
+use crate::core::{
+    order::OrderSide,
+    orderbook::{from_f64, OrderbookSnapshot},
+    pipeline::{self, Stage},
+    signal::{
+        volatility::{VolSource, VolatilitySignal},
+        vpin::VpinSignal,
+        MarketData, Signal,
+    },
+    trade::Trade,
+};
+use crate::portfolio::{AttributionTag, SharedPortfolio};
+use crate::risk::OrderIntent;
+use crate::runtime::{next_params, StrategyParams};
+use crate::schedule::{next_session, Activity, SessionState};
+use crate::supervisor::Shutdown;
+use crate::venue::{ExecutionVenue, VenueError, VenueOrder};
+use crate::ws::OrderbookFeed;
+use super::{fair_value::FairPrice, next_trade};
+
+// tên strategy trong báo cáo attribution
+pub const STRATEGY: &str = "market_maker";
+const BPS: Decimal = Decimal::from_parts(10_000, 0, 0, false, 0);
+
+#[derive(Debug, Clone)]
+pub struct MarketMakerConfig {
+    pub symbol: String,
+    // khoảng cách mỗi phía so với giá giữa (sau skew)
+    pub half_spread_bps: Decimal,
+    pub order_qty: Decimal,
+    // |position| không vượt quá mức này, phía làm tăng inventory bị thu nhỏ dần
+    pub max_inventory: Decimal,
+    // dịch giá giữa khi inventory chạm max: long thì hạ giá để dễ bán ra
+    pub skew_bps: Decimal,
+    // đặt lại quote định kỳ dù giá không đổi
+    pub refresh_interval: Duration,
+    // giữ order cũ nếu lệch so với quote mới không quá ngưỡng này
+    pub requote_threshold_bps: Decimal,
+    pub tick_size: Option<Decimal>,
+    // half spread tối thiểu = vol_multiplier × realized vol (bps) của mid trong vol_horizon, 0 = tắt
+    pub vol_multiplier: Decimal,
+    pub vol_horizon: Duration,
+    // VPIN >= toxic_vpin thì nhân half spread với toxic_spread_mult, 0 = tắt (cần trade stream)
+    pub toxic_vpin: Decimal,
+    pub toxic_spread_mult: Decimal,
+    pub vpin_bucket_qty: Decimal,
+    pub vpin_buckets: usize,
+}
+
+impl Default for MarketMakerConfig {
+    fn default() -> Self {
+        Self {
+            symbol: "BTCUSDT".to_string(),
+            half_spread_bps: Decimal::from(5),
+            order_qty: Decimal::new(1, 3),
+            max_inventory: Decimal::new(1, 2),
+            skew_bps: Decimal::from(5),
+            refresh_interval: Duration::from_secs(30),
+            requote_threshold_bps: Decimal::ONE,
+            tick_size: None,
+            vol_multiplier: Decimal::ZERO,
+            vol_horizon: Duration::from_secs(60),
+            toxic_vpin: Decimal::ZERO,
+            toxic_spread_mult: Decimal::TWO,
+            vpin_bucket_qty: Decimal::ONE,
+            vpin_buckets: 50,
+        }
+    }
+}
+
+impl MarketMakerConfig {
+    // thị trường biến động mạnh hoặc flow toxic thì nới spread
+    pub fn effective_half_spread_bps(&self, vol_bps: Decimal, vpin: Decimal) -> Decimal {
+        let half = self.half_spread_bps.max(self.vol_multiplier * vol_bps);
+        if self.toxic_vpin > Decimal::ZERO && vpin >= self.toxic_vpin {
+            half * self.toxic_spread_mult.max(Decimal::ONE)
+        } else {
+            half
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuoteLevel {
+    pub price: Decimal,
+    pub qty: Decimal,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Quotes {
+    pub bid: Option<QuoteLevel>,
+    pub ask: Option<QuoteLevel>,
+}
+
+fn round_down(price: Decimal, tick: Option<Decimal>) -> Decimal {
+    match tick {
+        Some(tick) if tick > Decimal::ZERO => (price / tick).floor() * tick,
+        _ => price,
+    }
+}
+
+fn round_up(price: Decimal, tick: Option<Decimal>) -> Decimal {
+    match tick {
+        Some(tick) if tick > Decimal::ZERO => (price / tick).ceil() * tick,
+        _ => price,
+    }
+}
+
+// Quote quanh `fair` (None = microprice của book), skew theo inventory và không bao giờ cắt qua book.
+// `vol_bps`: realized vol hiện tại, chỉ có tác dụng khi vol_multiplier > 0; `vpin` tương tự với toxic_vpin
+pub fn compute_quotes(
+    config: &MarketMakerConfig,
+    book: &OrderbookSnapshot,
+    fair: Option<Decimal>,
+    inventory: Decimal,
+    vol_bps: Decimal,
+    vpin: Decimal,
+) -> Quotes {
+    let (Some(fair), Some(((best_bid, _), (best_ask, _)))) = (fair.or_else(|| book.microprice()), book.best_bid_ask()) else {
+        return Quotes::default();
+    };
+    let ratio = if config.max_inventory > Decimal::ZERO {
+        (inventory / config.max_inventory).clamp(-Decimal::ONE, Decimal::ONE)
+    } else {
+        Decimal::ZERO
+    };
+    let center = fair * (Decimal::ONE - config.skew_bps * ratio / BPS);
+    let half = fair * config.effective_half_spread_bps(vol_bps, vpin) / BPS;
+
+    let mut bid = round_down(center - half, config.tick_size);
+    let mut ask = round_up(center + half, config.tick_size);
+    // skew mạnh có thể đẩy quote qua phía đối diện -> lùi về best cùng phía để vẫn là maker
+    if bid >= best_ask {
+        bid = best_bid;
+    }
+    if ask <= best_bid {
+        ask = best_ask;
+    }
+
+    let bid_qty = config.order_qty.min(config.max_inventory - inventory);
+    let ask_qty = config.order_qty.min(config.max_inventory + inventory);
+    Quotes {
+        bid: (bid_qty > Decimal::ZERO && bid > Decimal::ZERO).then_some(QuoteLevel { price: bid, qty: bid_qty }),
+        ask: (ask_qty > Decimal::ZERO).then_some(QuoteLevel { price: ask, qty: ask_qty }),
+    }
+}
+
+// Mỗi phía giữ tối đa một order, chạy được trên paper lẫn live qua ExecutionVenue
+pub struct MarketMaker {
+    config: MarketMakerConfig,
+    // config lúc khởi động, params hot-reload ghi đè lên bản này
+    base: MarketMakerConfig,
+    params: Option<watch::Receiver<Arc<StrategyParams>>>,
+    venue: Arc<dyn ExecutionVenue>,
+    last_refresh: Option<Instant>,
+    // stamp nhận của book đã đo latency, requote do refresh / trade dùng lại book cũ thì không đo
+    last_received: Option<std::time::Instant>,
+    volatility: VolatilitySignal,
+    vpin: VpinSignal,
+    trades: Option<broadcast::Receiver<Trade>>,
+    fair_value: Option<watch::Receiver<Option<FairPrice>>>,
+    session: Option<watch::Receiver<SessionState>>,
+    attribution: Option<SharedPortfolio>,
+}
+
+impl MarketMaker {
+    pub fn new(config: MarketMakerConfig, venue: Arc<dyn ExecutionVenue>) -> Self {
+        let horizon = chrono::Duration::from_std(config.vol_horizon).unwrap_or(chrono::Duration::minutes(1));
+        let vpin = VpinSignal::new(config.vpin_bucket_qty.try_into().unwrap_or(0.0), config.vpin_buckets);
+        Self {
+            base: config.clone(),
+            config,
+            params: None,
+            venue,
+            last_refresh: None,
+            last_received: None,
+            volatility: VolatilitySignal::new(horizon, VolSource::Mid),
+            vpin,
+            trades: None,
+            fair_value: None,
+            session: None,
+            attribution: None,
+        }
+    }
+
+    // spread / size / ngưỡng lấy từ `[market_maker]` của file params, đổi khi file đổi
+    pub fn with_params(mut self, params: watch::Receiver<Arc<StrategyParams>>) -> Self {
+        self.apply_params(&params.borrow());
+        self.params = Some(params);
+        self
+    }
+
+    // thay cả config một lần, quote kế tiếp dùng bộ mới
+    pub fn apply_params(&mut self, params: &StrategyParams) {
+        self.config = params.market_maker.apply(&self.base);
+    }
+
+    // trade của symbol đang quote, nuôi VPIN trong `run`
+    pub fn with_trades(mut self, trades: broadcast::Receiver<Trade>) -> Self {
+        self.trades = Some(trades);
+        self
+    }
+
+    pub fn config(&self) -> &MarketMakerConfig {
+        &self.config
+    }
+
+    pub fn vol_bps(&self) -> Decimal {
+        from_f64(self.volatility.vol_bps()).unwrap_or_default()
+    }
+
+    // quote quanh fair value thay vì microprice của feed
+    pub fn with_fair_value(mut self, fair_value: watch::Receiver<Option<FairPrice>>) -> Self {
+        self.fair_value = Some(fair_value);
+        self
+    }
+
+    // ngoài giờ giao dịch / trong cửa sổ bảo trì thì huỷ quote và chờ
+    pub fn with_session(mut self, session: watch::Receiver<SessionState>) -> Self {
+        self.session = Some(session);
+        self
+    }
+
+    // tag order vừa đặt để portfolio tách PnL theo nguồn giá neo quote
+    pub fn with_attribution(mut self, portfolio: SharedPortfolio) -> Self {
+        self.attribution = Some(portfolio);
+        self
+    }
+
+    fn paused(&self) -> Option<String> {
+        self.session.as_ref()?.borrow().paused(Activity::Quoting).map(str::to_string)
+    }
+
+    pub fn fair_price(&self) -> Option<Decimal> {
+        let fair = (*self.fair_value.as_ref()?.borrow())?;
+        from_f64(fair.price)
+    }
+
+    pub fn vpin(&self) -> Decimal {
+        from_f64(self.vpin.value()).unwrap_or_default()
+    }
+
+    pub fn on_trade(&mut self, trade: &Trade) {
+        self.vpin.on_trade(trade);
+    }
+
+    // order hiện tại vẫn đủ gần quote mới thì giữ nguyên để không mất chỗ trong queue
+    fn in_place(&self, current: &[&VenueOrder], target: Option<QuoteLevel>) -> bool {
+        match (current, target) {
+            ([], None) => true,
+            ([order], Some(level)) => order
+                .price
+                .is_some_and(|p| (p - level.price).abs() / level.price * BPS <= self.config.requote_threshold_bps),
+            _ => false,
+        }
+    }
+
+    pub async fn on_book(&mut self, book: &OrderbookSnapshot) -> Result<Quotes, VenueError> {
+        let symbol = self.config.symbol.clone();
+        let inventory = self.venue.position(&symbol).await?.qty;
+        self.volatility.on_orderbook(book);
+        let (vol_bps, vpin) = (self.vol_bps(), self.vpin());
+        let fair = self.fair_price();
+        let quotes = compute_quotes(&self.config, book, fair, inventory, vol_bps, vpin);
+        let received = book.received.filter(|r| self.last_received != Some(*r));
+        self.last_received = book.received.or(self.last_received);
+        pipeline::record(Stage::Decision, received);
+        let open = self.venue.open_orders(&symbol).await?;
+        let due = self
+            .last_refresh
+            .is_none_or(|t| t.elapsed() >= self.config.refresh_interval);
+
+        for (side, target) in [(OrderSide::Buy, quotes.bid), (OrderSide::Sell, quotes.ask)] {
+            let current: Vec<&VenueOrder> = open.iter().filter(|o| o.side == side).collect();
+            if !due && self.in_place(&current, target) {
+                continue;
+            }
+            for order in current {
+                self.venue.cancel(&symbol, &order.id).await?;
+            }
+            if let Some(level) = target {
+                let intent = OrderIntent::limit(&symbol, side, level.price, level.qty);
+                pipeline::record(Stage::OrderSend, received);
+                let trigger = if fair.is_some() { "fair_value" } else { "microprice" };
+                let tag = AttributionTag::new(STRATEGY, trigger);
+                // tag trước khi order tới sàn, fill khớp ngay vẫn được attribution đúng
+                let on_id = |id: &str| {
+                    if let Some(portfolio) = &self.attribution {
+                        portfolio.tag_order(id, tag.clone());
+                    }
+                };
+                self.venue.place_order_with(&intent, &on_id).await?;
+            }
+        }
+        if due {
+            self.last_refresh = Some(Instant::now());
+        }
+        debug!(symbol = %symbol, ?fair, %inventory, %vol_bps, %vpin, ?quotes, "requoted");
+        Ok(quotes)
+    }
+
+    // Requote mỗi khi book đổi hoặc đến kỳ refresh, huỷ hết quote khi shutdown
+    pub async fn run(mut self, feed: Arc<dyn OrderbookFeed>, mut shutdown: Shutdown) {
+        let mut watch = feed.watch();
+        let mut refresh = tokio::time::interval(self.config.refresh_interval);
+        refresh.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut trades = self.trades.take();
+        let mut params = self.params.take();
+        let mut session = self.session.clone();
+        loop {
+            tokio::select! {
+                changed = watch.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                }
+                trade = next_trade(&mut trades) => {
+                    self.on_trade(&trade);
+                    // paper khớp limit theo trade, live ước lượng queue
+                    self.venue.on_market_data(&MarketData::Trade(trade));
+                    continue;
+                }
+                params = next_params(&mut params) => {
+                    self.apply_params(&params);
+                    info!(symbol = %self.config.symbol, half_spread_bps = %self.config.half_spread_bps, order_qty = %self.config.order_qty, "quoting params updated");
+                }
+                _ = refresh.tick() => {}
+                _ = next_session(&mut session) => {
+                    if let Some(reason) = self.paused() {
+                        info!(symbol = %self.config.symbol, %reason, "quoting paused");
+                        if let Err(e) = self.venue.cancel_all(&self.config.symbol).await {
+                            warn!(venue = self.venue.name(), error = %e, "cancel quotes on pause failed");
+                        }
+                    } else {
+                        info!(symbol = %self.config.symbol, "quoting resumed");
+                        // quote lại ngay, không chờ refresh
+                        self.last_refresh = None;
+                    }
+                }
+                _ = shutdown.wait() => break,
+            }
+            let snap = feed.snapshot();
+            self.venue.on_market_data(&MarketData::Orderbook { symbol: self.config.symbol.clone(), snap: snap.clone() });
+            if self.paused().is_some() {
+                continue;
+            }
+            if let Err(e) = self.on_book(&snap).await {
+                warn!(venue = self.venue.name(), error = %e, "quote update failed");
+            }
+        }
+        if let Err(e) = self.venue.cancel_all(&self.config.symbol).await {
+            warn!(venue = self.venue.name(), error = %e, "cancel quotes on exit failed");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::orderbook::Side;
+    use crate::sim::PaperConfig;
+    use crate::venue::paper::PaperVenue;
+    use rust_decimal_macros::dec;
+
+    fn book(bid: Decimal, ask: Decimal) -> OrderbookSnapshot {
+        let mut ob = OrderbookSnapshot::new();
+        ob.set_level(Side::Bid, bid, dec!(1));
+        ob.set_level(Side::Ask, ask, dec!(1));
+        ob
+    }
+
+    fn config() -> MarketMakerConfig {
+        MarketMakerConfig {
+            symbol: "BTCUSDT".into(),
+            half_spread_bps: dec!(10),
+            order_qty: dec!(1),
+            max_inventory: dec!(2),
+            skew_bps: dec!(20),
+            refresh_interval: Duration::from_secs(3600),
+            requote_threshold_bps: dec!(5),
+            tick_size: Some(dec!(0.01)),
+            vol_multiplier: Decimal::ZERO,
+            vol_horizon: Duration::from_secs(60),
+            toxic_vpin: Decimal::ZERO,
+            toxic_spread_mult: Decimal::TWO,
+            vpin_bucket_qty: dec!(10),
+            vpin_buckets: 5,
+        }
+    }
+
+    fn level(price: Decimal, qty: Decimal) -> Option<QuoteLevel> {
+        Some(QuoteLevel { price, qty })
+    }
+
+    #[test]
+    fn test_quotes_skew_with_inventory() {
+        let cfg = config();
+        let ob = book(dec!(100), dec!(101));
+
+        // microprice 100.5, ±10bps
+        let q = compute_quotes(&cfg, &ob, None, Decimal::ZERO, Decimal::ZERO, Decimal::ZERO);
+        assert_eq!(q, Quotes { bid: level(dec!(100.39), dec!(1)), ask: level(dec!(100.61), dec!(1)) });
+
+        // long nửa max -> giá giữa hạ 10bps
+        let q = compute_quotes(&cfg, &ob, None, dec!(1), Decimal::ZERO, Decimal::ZERO);
+        assert_eq!(q, Quotes { bid: level(dec!(100.29), dec!(1)), ask: level(dec!(100.50), dec!(1)) });
+
+        // chạm max -> ngừng mua, phía bán vẫn giới hạn theo order_qty
+        let q = compute_quotes(&cfg, &ob, None, dec!(2), Decimal::ZERO, Decimal::ZERO);
+        assert_eq!((q.bid, q.ask.map(|l| l.qty)), (None, Some(dec!(1))));
+
+        // skew quá mạnh không được cắt qua best ask
+        let cfg = MarketMakerConfig { skew_bps: dec!(200), ..config() };
+        let q = compute_quotes(&cfg, &ob, None, dec!(-2), Decimal::ZERO, Decimal::ZERO);
+        assert_eq!(q.bid, level(dec!(100), dec!(1)));
+        assert_eq!(q.ask, None);
+
+        assert_eq!(compute_quotes(&cfg, &OrderbookSnapshot::new(), None, Decimal::ZERO, Decimal::ZERO, Decimal::ZERO), Quotes::default());
+
+        // vol 10bps × 2 = 20bps > half spread 10bps
+        let cfg = MarketMakerConfig { vol_multiplier: dec!(2), ..config() };
+        let q = compute_quotes(&cfg, &ob, None, Decimal::ZERO, dec!(10), Decimal::ZERO);
+        assert_eq!(q, Quotes { bid: level(dec!(100.29), dec!(1)), ask: level(dec!(100.71), dec!(1)) });
+
+        // flow toxic: VPIN vượt ngưỡng thì half spread x2
+        let cfg = MarketMakerConfig { toxic_vpin: dec!(0.5), ..config() };
+        assert_eq!(compute_quotes(&cfg, &ob, None, Decimal::ZERO, Decimal::ZERO, dec!(0.4)).bid, level(dec!(100.39), dec!(1)));
+        let q = compute_quotes(&cfg, &ob, None, Decimal::ZERO, Decimal::ZERO, dec!(0.8));
+        assert_eq!(q, Quotes { bid: level(dec!(100.29), dec!(1)), ask: level(dec!(100.71), dec!(1)) });
+
+        // neo theo fair value thay vì microprice
+        let q = compute_quotes(&config(), &ob, Some(dec!(100.2)), Decimal::ZERO, Decimal::ZERO, Decimal::ZERO);
+        assert_eq!(q, Quotes { bid: level(dec!(100.09), dec!(1)), ask: level(dec!(100.31), dec!(1)) });
+    }
+
+    #[tokio::test]
+    async fn test_market_maker_on_paper_venue() {
+        let venue = Arc::new(PaperVenue::new(PaperConfig::default()));
+        let mut mm = MarketMaker::new(config(), venue.clone());
+        let quotes_of = |orders: Vec<VenueOrder>| -> Vec<_> { orders.into_iter().map(|o| (o.side, o.price.unwrap(), o.qty)).collect() };
+
+        let ob = Arc::new(book(dec!(100), dec!(101)));
+        venue.on_market_data(&MarketData::Orderbook { symbol: "BTCUSDT".into(), snap: ob.clone() });
+        mm.on_book(&ob).await.unwrap();
+        assert_eq!(
+            quotes_of(venue.open_orders("BTCUSDT").await.unwrap()),
+            vec![(OrderSide::Buy, dec!(100.39), dec!(1)), (OrderSide::Sell, dec!(100.61), dec!(1))]
+        );
+
+        // ask đi xuống qua bid của mình -> bid khớp, quote lại lệch xuống
+        let ob = Arc::new(book(dec!(100), dec!(100.3)));
+        let fills = venue.on_market_data(&MarketData::Orderbook { symbol: "BTCUSDT".into(), snap: ob.clone() });
+        assert_eq!(fills.len(), 1);
+        mm.on_book(&ob).await.unwrap();
+        assert_eq!(venue.position("BTCUSDT").await.unwrap().qty, dec!(1));
+        let open = venue.open_orders("BTCUSDT").await.unwrap();
+        let ids: Vec<_> = open.iter().map(|o| o.id.clone()).collect();
+        assert_eq!(
+            quotes_of(open),
+            vec![(OrderSide::Buy, dec!(99.94), dec!(1)), (OrderSide::Sell, dec!(100.15), dec!(1))]
+        );
+
+        // book không đổi, chưa tới kỳ refresh -> giữ nguyên order
+        mm.on_book(&ob).await.unwrap();
+        let open = venue.open_orders("BTCUSDT").await.unwrap();
+        assert_eq!(open.iter().map(|o| o.id.clone()).collect::<Vec<_>>(), ids);
+
+        // params reload: spread / size mới áp dụng ngay ở lần quote kế tiếp
+        mm.apply_params(&StrategyParams::from_toml_str("[market_maker]\nhalf_spread_bps = 30\norder_qty = 0.5").unwrap());
+        mm.on_book(&ob).await.unwrap();
+        assert_eq!(
+            quotes_of(venue.open_orders("BTCUSDT").await.unwrap()),
+            vec![(OrderSide::Buy, dec!(99.74), dec!(0.5)), (OrderSide::Sell, dec!(100.36), dec!(0.5))]
+        );
+        // xoá key: quay về config lúc khởi động
+        mm.apply_params(&StrategyParams::default());
+        assert_eq!(mm.config().half_spread_bps, dec!(10));
+
+        venue.cancel_all("BTCUSDT").await.unwrap();
+        assert!(venue.open_orders("BTCUSDT").await.unwrap().is_empty());
+    }
+}
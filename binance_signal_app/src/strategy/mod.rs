@@ -0,0 +1,23 @@
+pub mod arb;
+pub mod cex_dex;
+pub mod fair_value;
+pub mod hedger;
+pub mod market_maker;
+pub mod triangular;
+
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::debug;
+
+use crate::core::trade::Trade;
+
+// trade kế tiếp cho vòng select của strategy, không có stream (hoặc đã đóng) thì chờ mãi
+async fn next_trade(trades: &mut Option<broadcast::Receiver<Trade>>) -> Trade {
+    loop {
+        let Some(rx) = trades else { return std::future::pending().await };
+        match rx.recv().await {
+            Ok(trade) => return trade,
+            Err(RecvError::Lagged(n)) => debug!(skipped = n, "strategy lagged behind trades"),
+            Err(RecvError::Closed) => *trades = None,
+        }
+    }
+}
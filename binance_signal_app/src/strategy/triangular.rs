@@ -0,0 +1,388 @@
+use chrono::{DateTime, Utc};
+use futures_util::future::select_all;
+use rust_decimal::Decimal;
+use std::{collections::HashMap, fmt, sync::Arc};
+use tokio::sync::broadcast;
+use tracing::info;
+
+use crate::config::Exchange;
+use crate::core::{order::OrderSide, orderbook::OrderbookSnapshot};
+use crate::symbols::Instrument;
+use crate::ws::{binance::BinanceOrderbookWS, OrderbookFeed};
+
+const BPS: Decimal = Decimal::from_parts(10_000, 0, 0, false, 0);
+const OPPORTUNITY_CHANNEL_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Market {
+    // dạng Binance, viết thường: ethbtc
+    pub symbol: String,
+    pub base: String,
+    pub quote: String,
+}
+
+impl Market {
+    // "ETH/BTC", bắt buộc có "/" để không nhầm với symbol viết liền
+    pub fn parse(s: &str) -> Option<Self> {
+        if !s.contains('/') {
+            return None;
+        }
+        let inst = Instrument::parse(Exchange::Binance, s).ok()?;
+        Some(Self { symbol: inst.venue_symbol(), base: inst.base, quote: inst.quote })
+    }
+
+    // asset còn lại khi đổi từ `asset` qua market này
+    fn other(&self, asset: &str) -> Option<&str> {
+        if self.base == asset {
+            Some(&self.quote)
+        } else if self.quote == asset {
+            Some(&self.base)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    // index trong `Triangle::markets`
+    pub market: usize,
+    // Buy: đổi quote -> base ở giá ask, Sell: base -> quote ở giá bid
+    pub side: OrderSide,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Triangle {
+    // asset bắt đầu và kết thúc vòng, vd. USDT
+    pub start: String,
+    pub markets: [Market; 3],
+}
+
+impl Triangle {
+    // "BTC/USDT,ETH/BTC,ETH/USDT", asset bắt đầu là quote của market đầu tiên
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let markets: Vec<Market> = s
+            .split(',')
+            .map(|m| Market::parse(m).ok_or_else(|| format!("invalid market {:?}", m)))
+            .collect::<Result<_, _>>()?;
+        let markets: [Market; 3] = markets
+            .try_into()
+            .map_err(|_| format!("triangle {:?} must have exactly 3 markets", s))?;
+        let triangle = Self { start: markets[0].quote.clone(), markets };
+        triangle.validate()?;
+        Ok(triangle)
+    }
+
+    pub fn name(&self) -> String {
+        let names: Vec<String> = self.markets.iter().map(|m| format!("{}/{}", m.base, m.quote)).collect();
+        names.join(",")
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        // mỗi asset phải xuất hiện đúng ở 2 market
+        let mut count: HashMap<&str, usize> = HashMap::new();
+        for m in &self.markets {
+            *count.entry(&m.base).or_default() += 1;
+            *count.entry(&m.quote).or_default() += 1;
+        }
+        if count.len() != 3 || count.values().any(|c| *c != 2) || !count.contains_key(self.start.as_str()) {
+            return Err(format!("{} is not a triangle", self.name()));
+        }
+        Ok(())
+    }
+
+    // Hai chiều đi vòng: start -> X -> Y -> start và ngược lại
+    pub fn cycles(&self) -> Vec<[Step; 3]> {
+        let first: Vec<usize> = (0..3).filter(|i| self.markets[*i].other(&self.start).is_some()).collect();
+        first.into_iter().filter_map(|i| self.cycle_from(i)).collect()
+    }
+
+    fn cycle_from(&self, first: usize) -> Option<[Step; 3]> {
+        let mut steps = Vec::with_capacity(3);
+        let mut asset = self.start.as_str();
+        let mut used = [false; 3];
+        let mut next = Some(first);
+        while let Some(i) = next {
+            let market = &self.markets[i];
+            used[i] = true;
+            let side = if market.quote == asset { OrderSide::Buy } else { OrderSide::Sell };
+            steps.push(Step { market: i, side });
+            asset = market.other(asset)?;
+            next = (0..3).find(|j| !used[*j] && self.markets[*j].other(asset).is_some());
+        }
+        if asset != self.start {
+            return None;
+        }
+        steps.try_into().ok()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriangleLeg {
+    pub symbol: String,
+    pub side: OrderSide,
+    pub price: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriangleOpportunity {
+    pub timestamp: DateTime<Utc>,
+    pub triangle: String,
+    pub start: String,
+    pub legs: Vec<TriangleLeg>,
+    // lợi nhuận một vòng sau phí taker cả 3 leg
+    pub profit_bps: Decimal,
+    // lượng `start` tối đa đi được qua top-of-book của cả 3 leg
+    pub max_start_qty: Decimal,
+    pub profit: Decimal,
+}
+
+// Đi một vòng trên best bid/ask, trả về None nếu thiếu book
+pub fn evaluate(
+    triangle: &Triangle,
+    steps: &[Step; 3],
+    books: &[&OrderbookSnapshot; 3],
+    taker_fee_bps: Decimal,
+) -> Option<TriangleOpportunity> {
+    let fee_mult = Decimal::ONE - taker_fee_bps / BPS;
+    // lượng asset hiện tại nhận được trên 1 đơn vị `start`
+    let mut rate = Decimal::ONE;
+    let mut max_start: Option<Decimal> = None;
+    let mut legs = Vec::with_capacity(3);
+
+    for step in steps {
+        let book = books[step.market];
+        // capacity: lượng asset đầu vào mà level tốt nhất nhận được
+        let (price, capacity, conversion) = match step.side {
+            OrderSide::Buy => {
+                let (price, qty) = book.best_ask()?;
+                (price, qty * price, Decimal::ONE / price)
+            }
+            OrderSide::Sell => {
+                let (price, qty) = book.best_bid()?;
+                (price, qty, price)
+            }
+        };
+        if price.is_zero() {
+            return None;
+        }
+        let cap_start = capacity / rate;
+        max_start = Some(max_start.map_or(cap_start, |m| m.min(cap_start)));
+        rate = rate * conversion * fee_mult;
+        legs.push(TriangleLeg { symbol: triangle.markets[step.market].symbol.clone(), side: step.side, price });
+    }
+
+    let max_start_qty = max_start?;
+    Some(TriangleOpportunity {
+        timestamp: Utc::now(),
+        triangle: triangle.name(),
+        start: triangle.start.clone(),
+        legs,
+        profit_bps: (rate - Decimal::ONE) * BPS,
+        max_start_qty,
+        profit: max_start_qty * (rate - Decimal::ONE),
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct TriangularConfig {
+    pub taker_fee_bps: Decimal,
+    pub min_profit_bps: Decimal,
+}
+
+impl Default for TriangularConfig {
+    fn default() -> Self {
+        Self { taker_fee_bps: Decimal::TEN, min_profit_bps: Decimal::ZERO }
+    }
+}
+
+#[derive(Debug)]
+pub enum TriangularError {
+    MissingFeed(String),
+}
+
+impl fmt::Display for TriangularError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TriangularError::MissingFeed(symbol) => write!(f, "no feed for {}", symbol),
+        }
+    }
+}
+
+impl std::error::Error for TriangularError {}
+
+pub struct TriangularScanner {
+    triangles: Vec<Triangle>,
+    // symbol (viết thường) -> feed, dùng chung giữa các tam giác
+    feeds: HashMap<String, Arc<dyn OrderbookFeed>>,
+    config: TriangularConfig,
+    tx: broadcast::Sender<TriangleOpportunity>,
+}
+
+impl TriangularScanner {
+    // Mỗi symbol một stream bookTicker (chỉ cần best bid/ask, cập nhật realtime)
+    pub fn new(triangles: Vec<Triangle>, config: TriangularConfig) -> Self {
+        let mut feeds: HashMap<String, Arc<dyn OrderbookFeed>> = HashMap::new();
+        for m in triangles.iter().flat_map(|t| t.markets.iter()) {
+            feeds
+                .entry(m.symbol.clone())
+                .or_insert_with(|| Arc::new(BinanceOrderbookWS::new_book_ticker(&m.symbol)));
+        }
+        Self::with_feeds(triangles, feeds, config).expect("every market has a feed")
+    }
+
+    pub fn with_feeds(
+        triangles: Vec<Triangle>,
+        feeds: HashMap<String, Arc<dyn OrderbookFeed>>,
+        config: TriangularConfig,
+    ) -> Result<Self, TriangularError> {
+        for m in triangles.iter().flat_map(|t| t.markets.iter()) {
+            if !feeds.contains_key(&m.symbol) {
+                return Err(TriangularError::MissingFeed(m.symbol.clone()));
+            }
+        }
+        let (tx, _) = broadcast::channel(OPPORTUNITY_CHANNEL_CAPACITY);
+        Ok(Self { triangles, feeds, config, tx })
+    }
+
+    pub fn feeds(&self) -> Vec<Arc<dyn OrderbookFeed>> {
+        self.feeds.values().cloned().collect()
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<TriangleOpportunity> {
+        self.tx.subscribe()
+    }
+
+    // Mọi vòng (cả 2 chiều) có lợi nhuận >= `min_profit_bps`
+    pub fn scan(&self) -> Vec<TriangleOpportunity> {
+        let mut out = Vec::new();
+        for triangle in &self.triangles {
+            let snaps = triangle.markets.clone().map(|m| self.feeds[&m.symbol].snapshot());
+            let books = [&*snaps[0], &*snaps[1], &*snaps[2]];
+            for steps in triangle.cycles() {
+                if let Some(opp) = evaluate(triangle, &steps, &books, self.config.taker_fee_bps)
+                    && opp.profit_bps >= self.config.min_profit_bps
+                {
+                    out.push(opp);
+                }
+            }
+        }
+        out
+    }
+
+    pub async fn run(self) {
+        let feeds = self.feeds();
+        if feeds.is_empty() {
+            return;
+        }
+        let mut watches: Vec<_> = feeds.iter().map(|f| f.watch()).collect();
+        loop {
+            for opp in self.scan() {
+                info!(
+                    triangle = %opp.triangle,
+                    start = %opp.start,
+                    profit_bps = %opp.profit_bps.round_dp(2),
+                    max_start_qty = %opp.max_start_qty,
+                    profit = %opp.profit,
+                    "triangular opportunity"
+                );
+                let _ = self.tx.send(opp);
+            }
+            let changed = watches.iter_mut().map(|rx| Box::pin(rx.changed()));
+            if select_all(changed).await.0.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::orderbook::{SharedOrderbook, Side};
+    use crate::replay::ReplayFeed;
+    use rust_decimal_macros::dec;
+
+    fn book(bid: Decimal, ask: Decimal, qty: Decimal) -> OrderbookSnapshot {
+        let mut ob = OrderbookSnapshot::new();
+        ob.set_level(Side::Bid, bid, qty);
+        ob.set_level(Side::Ask, ask, qty);
+        ob
+    }
+
+    #[test]
+    fn test_parse_triangle_and_cycles() {
+        let t = Triangle::parse("BTC/USDT, ETH/BTC, ETH/USDT").unwrap();
+        assert_eq!(t.start, "USDT");
+        assert_eq!(t.markets[1].symbol, "ethbtc");
+
+        let cycles = t.cycles();
+        assert_eq!(cycles.len(), 2);
+        // USDT -> BTC -> ETH -> USDT
+        let sides: Vec<_> = cycles[0].iter().map(|s| (s.market, s.side)).collect();
+        assert_eq!(sides, vec![(0, OrderSide::Buy), (1, OrderSide::Buy), (2, OrderSide::Sell)]);
+        // USDT -> ETH -> BTC -> USDT
+        let sides: Vec<_> = cycles[1].iter().map(|s| (s.market, s.side)).collect();
+        assert_eq!(sides, vec![(2, OrderSide::Buy), (1, OrderSide::Sell), (0, OrderSide::Sell)]);
+
+        assert!(Triangle::parse("BTC/USDT,ETH/BTC").is_err());
+        assert!(Triangle::parse("BTC/USDT,ETH/BTC,SOL/USDT").is_err());
+        assert!(Triangle::parse("BTC/USDT,ETH-BTC,ETH/USDT").is_err());
+    }
+
+    #[test]
+    fn test_evaluate_round_trip_with_fees() {
+        let t = Triangle::parse("BTC/USDT,ETH/BTC,ETH/USDT").unwrap();
+        // ETH/USDT bid 2100 > 40000 * 0.05 = 2000 -> vòng USDT -> BTC -> ETH -> USDT lãi 5% trước phí
+        let btc = book(dec!(39990), dec!(40000), dec!(1));
+        let eth_btc = book(dec!(0.0499), dec!(0.05), dec!(10));
+        let eth = book(dec!(2100), dec!(2101), dec!(2));
+        let books = [&btc, &eth_btc, &eth];
+        let cycles = t.cycles();
+
+        let opp = evaluate(&t, &cycles[0], &books, Decimal::ZERO).unwrap();
+        assert_eq!(opp.profit_bps, dec!(500));
+        // bị giới hạn bởi 2 ETH ở leg cuối: 2 ETH = 0.1 BTC = 4000 USDT
+        assert_eq!(opp.max_start_qty, dec!(4000));
+        assert_eq!(opp.profit, dec!(200));
+        assert_eq!(opp.legs[0], TriangleLeg { symbol: "btcusdt".into(), side: OrderSide::Buy, price: dec!(40000) });
+
+        // phí 10bps * 3 leg
+        let opp = evaluate(&t, &cycles[0], &books, dec!(10)).unwrap();
+        assert_eq!(opp.profit_bps.round_dp(4), dec!(468.5315));
+        // chiều ngược lại lỗ
+        assert!(evaluate(&t, &cycles[1], &books, Decimal::ZERO).unwrap().profit_bps < Decimal::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_scanner_filters_by_threshold() {
+        let t = Triangle::parse("BTC/USDT,ETH/BTC,ETH/USDT").unwrap();
+        let mut feeds: HashMap<String, Arc<dyn OrderbookFeed>> = HashMap::new();
+        for (symbol, snap) in [
+            ("btcusdt", book(dec!(39990), dec!(40000), dec!(1))),
+            ("ethbtc", book(dec!(0.0499), dec!(0.05), dec!(10))),
+            ("ethusdt", book(dec!(2010), dec!(2011), dec!(2))),
+        ] {
+            let orderbook = Arc::new(SharedOrderbook::new());
+            orderbook.update(|ob| {
+                *ob = snap;
+                true
+            });
+            feeds.insert(symbol.to_string(), Arc::new(ReplayFeed { symbol: symbol.to_string(), orderbook }));
+        }
+
+        let config = TriangularConfig { taker_fee_bps: dec!(10), min_profit_bps: dec!(10) };
+        let scanner = TriangularScanner::with_feeds(vec![t.clone()], feeds.clone(), config).unwrap();
+        let mut rx = scanner.subscribe();
+        // 0.5% trước phí, ~0.2% sau phí
+        let opps = scanner.scan();
+        assert_eq!(opps.len(), 1);
+        assert_eq!(opps[0].start, "USDT");
+
+        tokio::spawn(scanner.run());
+        assert_eq!(rx.recv().await.unwrap().triangle, "BTC/USDT,ETH/BTC,ETH/USDT");
+
+        feeds.remove("ethusdt");
+        assert!(TriangularScanner::with_feeds(vec![t], feeds, TriangularConfig::default()).is_err());
+    }
+}
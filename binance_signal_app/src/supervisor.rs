@@ -0,0 +1,274 @@
+use std::{fmt::Display, future::Future, pin::Pin, sync::Arc, time::Duration};
+use tokio::{
+    sync::watch,
+    task::JoinHandle,
+    time::{timeout_at, Instant},
+};
+use tracing::{info, warn};
+
+pub const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+type Hook = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+// Tín hiệu dừng dùng chung, clone cho từng task.
+// Task nào cũng có thể `trigger()` (vd. lỗi không phục hồi được).
+#[derive(Debug, Clone)]
+pub struct Shutdown {
+    tx: Arc<watch::Sender<bool>>,
+    rx: watch::Receiver<bool>,
+}
+
+impl Shutdown {
+    pub fn trigger(&self) {
+        self.tx.send_replace(true);
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        *self.rx.borrow()
+    }
+
+    pub async fn wait(&mut self) {
+        // sender không bao giờ drop trước receiver vì Shutdown giữ Arc của nó
+        let _ = self.rx.wait_for(|stop| *stop).await;
+    }
+}
+
+struct Task {
+    name: String,
+    handle: JoinHandle<Result<(), String>>,
+    // task không tự nghe shutdown (vd. JoinHandle có sẵn) thì abort ngay
+    abort_on_shutdown: bool,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct JoinReport {
+    pub finished: Vec<String>,
+    // (task, lỗi hoặc panic)
+    pub failed: Vec<(String, String)>,
+    // quá hạn, đã abort (task blocking thì chỉ bỏ không chờ nữa)
+    pub timed_out: Vec<String>,
+}
+
+impl JoinReport {
+    pub fn is_clean(&self) -> bool {
+        self.failed.is_empty() && self.timed_out.is_empty()
+    }
+}
+
+// Sở hữu mọi task của một lệnh chạy (feed, recorder, strategy, ...).
+// Khi dừng: phát tín hiệu shutdown, chạy các hook (vd. huỷ order), rồi join
+// từng task trong giới hạn thời gian thay vì để process bị kill giữa chừng.
+pub struct Supervisor {
+    shutdown: Shutdown,
+    tasks: Vec<Task>,
+    hooks: Vec<(String, Hook)>,
+}
+
+impl Default for Supervisor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Supervisor {
+    pub fn new() -> Self {
+        let (tx, rx) = watch::channel(false);
+        Self {
+            shutdown: Shutdown { tx: Arc::new(tx), rx },
+            tasks: Vec::new(),
+            hooks: Vec::new(),
+        }
+    }
+
+    pub fn shutdown_signal(&self) -> Shutdown {
+        self.shutdown.clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    // Future bị drop ngay khi có shutdown, dùng cho vòng lặp vô hạn không cần dọn dẹp
+    pub fn spawn<F>(&mut self, name: impl Into<String>, fut: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let mut shutdown = self.shutdown_signal();
+        let handle = tokio::spawn(async move {
+            tokio::select! {
+                _ = fut => {}
+                _ = shutdown.wait() => {}
+            }
+            Ok(())
+        });
+        self.push(name.into(), handle, false);
+    }
+
+    // Task tự xử lý shutdown (flush, in kết quả, ...) rồi mới trả về
+    pub fn spawn_graceful<F, Fut>(&mut self, name: impl Into<String>, f: F)
+    where
+        F: FnOnce(Shutdown) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let fut = f(self.shutdown_signal());
+        let handle = tokio::spawn(async move {
+            fut.await;
+            Ok(())
+        });
+        self.push(name.into(), handle, false);
+    }
+
+    // Task blocking (recorder) phải tự kết thúc khi input đóng, không abort được
+    pub fn spawn_blocking<F, E>(&mut self, name: impl Into<String>, f: F)
+    where
+        F: FnOnce() -> Result<(), E> + Send + 'static,
+        E: Display,
+    {
+        let handle = tokio::task::spawn_blocking(move || f().map_err(|e| e.to_string()));
+        self.push(name.into(), handle, false);
+    }
+
+    // Nhận một task đã spawn sẵn (vd. `forward_orderbook`), abort khi shutdown
+    pub fn adopt(&mut self, name: impl Into<String>, handle: JoinHandle<()>) {
+        let handle = tokio::spawn(async move {
+            match handle.await {
+                Ok(()) => Ok(()),
+                Err(e) if e.is_cancelled() => Ok(()),
+                Err(e) => Err(e.to_string()),
+            }
+        });
+        self.push(name.into(), handle, true);
+    }
+
+    // Chạy một lần lúc dừng, trước khi join task (vd. `risk::cancel_all`)
+    pub fn on_shutdown<F>(&mut self, name: impl Into<String>, hook: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.hooks.push((name.into(), Box::pin(hook)));
+    }
+
+    fn push(&mut self, name: String, handle: JoinHandle<Result<(), String>>, abort_on_shutdown: bool) {
+        self.tasks.push(Task { name, handle, abort_on_shutdown });
+    }
+
+    // Chờ SIGINT/SIGTERM hoặc task nào đó gọi `Shutdown::trigger`
+    pub async fn run_until_signal(self, timeout: Duration) -> std::io::Result<JoinReport> {
+        let mut shutdown = self.shutdown_signal();
+        tokio::select! {
+            signal = wait_for_signal() => info!(signal = signal?, "shutdown requested"),
+            _ = shutdown.wait() => info!("shutdown triggered"),
+        }
+        Ok(self.shutdown(timeout).await)
+    }
+
+    pub async fn shutdown(self, timeout: Duration) -> JoinReport {
+        let deadline = Instant::now() + timeout;
+        self.shutdown.trigger();
+
+        for (name, hook) in self.hooks {
+            if timeout_at(deadline, hook).await.is_err() {
+                warn!(hook = %name, "shutdown hook timed out");
+            }
+        }
+
+        for task in self.tasks.iter().filter(|t| t.abort_on_shutdown) {
+            task.handle.abort();
+        }
+
+        let mut report = JoinReport::default();
+        for mut task in self.tasks {
+            match timeout_at(deadline, &mut task.handle).await {
+                Ok(Ok(Ok(()))) => report.finished.push(task.name),
+                Ok(Err(e)) if e.is_cancelled() => report.finished.push(task.name),
+                Ok(Err(e)) => report.failed.push((task.name, e.to_string())),
+                Ok(Ok(Err(e))) => {
+                    warn!(task = %task.name, error = %e, "task failed");
+                    report.failed.push((task.name, e));
+                }
+                Err(_) => {
+                    warn!(task = %task.name, "task did not stop in time, aborting");
+                    task.handle.abort();
+                    report.timed_out.push(task.name);
+                }
+            }
+        }
+        info!(
+            finished = report.finished.len(),
+            failed = report.failed.len(),
+            timed_out = report.timed_out.len(),
+            "shutdown complete"
+        );
+        report
+    }
+}
+
+pub async fn wait_for_signal() -> std::io::Result<&'static str> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut term = signal(SignalKind::terminate())?;
+        tokio::select! {
+            r = tokio::signal::ctrl_c() => r.map(|_| "SIGINT"),
+            _ = term.recv() => Ok("SIGTERM"),
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        tokio::signal::ctrl_c().await.map(|_| "ctrl-c")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[tokio::test]
+    async fn test_shutdown_stops_and_flushes_tasks() {
+        let mut sup = Supervisor::new();
+        sup.spawn("forever", std::future::pending());
+        sup.adopt("adopted", tokio::spawn(std::future::pending()));
+
+        let flushed = Arc::new(AtomicBool::new(false));
+        let flag = flushed.clone();
+        sup.spawn_graceful("graceful", |mut shutdown| async move {
+            shutdown.wait().await;
+            flag.store(true, Ordering::SeqCst);
+        });
+
+        let hooked = Arc::new(AtomicBool::new(false));
+        let flag = hooked.clone();
+        sup.on_shutdown("cancel orders", async move {
+            flag.store(true, Ordering::SeqCst);
+        });
+        sup.spawn_blocking("recorder", || Err::<(), _>("disk full"));
+
+        let report = sup.shutdown(Duration::from_secs(1)).await;
+        assert_eq!(report.finished, vec!["forever", "adopted", "graceful"]);
+        assert_eq!(report.failed, vec![("recorder".to_string(), "disk full".to_string())]);
+        assert!(flushed.load(Ordering::SeqCst) && hooked.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_join_timeout_and_trigger_from_task() {
+        let mut sup = Supervisor::new();
+        // bỏ qua shutdown -> bị abort khi hết hạn
+        sup.spawn_graceful("stuck", |_| std::future::pending());
+        sup.spawn_graceful("fatal", |shutdown| async move { shutdown.trigger() });
+
+        let mut shutdown = sup.shutdown_signal();
+        tokio::time::timeout(Duration::from_secs(1), shutdown.wait()).await.unwrap();
+        assert!(shutdown.is_triggered());
+
+        let report = sup.shutdown(Duration::from_millis(50)).await;
+        assert_eq!(report.timed_out, vec!["stuck"]);
+        assert_eq!(report.finished, vec!["fatal"]);
+        assert!(!report.is_clean());
+    }
+}
@@ -0,0 +1,203 @@
+use std::fmt;
+
+use crate::config::Exchange;
+
+// Quote hay gặp, dùng để tách symbol viết liền (cakebnb, btcusdt). Dài trước để
+// "USDT" không bị khớp thành "USD" và "FDUSD" không thành "USD"
+const KNOWN_QUOTES: [&str; 16] = [
+    "FDUSD", "USDT", "USDC", "BUSD", "TUSD", "DAI", "USD", "EUR", "GBP", "TRY", "BRL", "JPY", "BTC", "ETH", "BNB", "SOL",
+];
+
+// Kraken dùng mã riêng cho vài asset
+const KRAKEN_ALIASES: [(&str, &str); 2] = [("XBT", "BTC"), ("XDG", "DOGE")];
+// Bitfinex: UST là USDT (không phải TerraUSD) nên chỉ đổi với symbol của Bitfinex
+const BITFINEX_ALIASES: [(&str, &str); 2] = [("UST", "USDT"), ("UDC", "USDC")];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SymbolParseError {
+    Empty,
+    // không tách được base/quote từ symbol viết liền
+    UnknownQuote(String),
+    SameAsset(String),
+}
+
+impl fmt::Display for SymbolParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SymbolParseError::Empty => write!(f, "empty symbol"),
+            SymbolParseError::UnknownQuote(s) => write!(f, "cannot split {:?} into base/quote, use \"BASE/QUOTE\"", s),
+            SymbolParseError::SameAsset(s) => write!(f, "symbol {:?} has the same base and quote", s),
+        }
+    }
+}
+
+impl std::error::Error for SymbolParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InstrumentKind {
+    Spot,
+    Perp,
+}
+
+// Cặp giao dịch dạng chuẩn, độc lập với cách viết của sàn.
+// base/quote viết hoa theo mã chung (BTC chứ không phải XBT)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Instrument {
+    pub base: String,
+    pub quote: String,
+    pub venue: Exchange,
+    pub kind: InstrumentKind,
+}
+
+impl Instrument {
+    pub fn new(base: &str, quote: &str, venue: Exchange, kind: InstrumentKind) -> Self {
+        Self { base: canonical_asset(base), quote: canonical_asset(quote), venue, kind }
+    }
+
+    // Nhận cả dạng chuẩn ("CAKE/BNB") lẫn dạng của sàn ("cakebnb", "CAKE-BNB", "XBT/USD", "BTC-USDT-SWAP")
+    pub fn parse(venue: Exchange, raw: &str) -> Result<Self, SymbolParseError> {
+        let (base, quote, perp) = match venue {
+            Exchange::Bitfinex => split_bitfinex(raw)?,
+            _ => split_pair(raw)?,
+        };
+        let kind = if perp || venue == Exchange::BinanceFutures { InstrumentKind::Perp } else { InstrumentKind::Spot };
+        Ok(Self::new(&base, &quote, venue, kind))
+    }
+
+    // "BTC/USDT", perp thêm hậu tố "-PERP"
+    pub fn canonical(&self) -> String {
+        match self.kind {
+            InstrumentKind::Spot => format!("{}/{}", self.base, self.quote),
+            InstrumentKind::Perp => format!("{}/{}-PERP", self.base, self.quote),
+        }
+    }
+
+    // Symbol truyền cho feed / REST của sàn
+    pub fn venue_symbol(&self) -> String {
+        let (base, quote) = (self.base.as_str(), self.quote.as_str());
+        match self.venue {
+            Exchange::Binance | Exchange::BinanceFutures | Exchange::Htx => format!("{}{}", base, quote).to_lowercase(),
+            Exchange::Bybit | Exchange::Mexc => format!("{}{}", base, quote),
+            Exchange::Coinbase | Exchange::Kucoin => format!("{}-{}", base, quote),
+            Exchange::Okx => match self.kind {
+                InstrumentKind::Spot => format!("{}-{}", base, quote),
+                InstrumentKind::Perp => format!("{}-{}-SWAP", base, quote),
+            },
+            Exchange::Kraken => format!("{}/{}", kraken_asset(base), kraken_asset(quote)),
+            Exchange::Dex => format!("{}/{}", base, quote),
+            Exchange::Gateio => format!("{}_{}", base, quote),
+            Exchange::Bitfinex => {
+                let (base, quote) = (bitfinex_asset(base), bitfinex_asset(quote));
+                // mã dài hơn 3 ký tự thì có dấu ":" (tTESTBTC:TESTUSD)
+                if base.len() > 3 || quote.len() > 3 { format!("t{}:{}", base, quote) } else { format!("t{}{}", base, quote) }
+            }
+        }
+    }
+
+    // cùng base/quote/kind, khác sàn cũng được
+    pub fn same_pair(&self, other: &Instrument) -> bool {
+        self.base == other.base && self.quote == other.quote && self.kind == other.kind
+    }
+
+    // `query` dạng chuẩn hoặc symbol của sàn, không phân biệt hoa thường
+    pub fn matches(&self, query: &str) -> bool {
+        if query.eq_ignore_ascii_case(&self.venue_symbol()) {
+            return true;
+        }
+        split_pair(query).is_ok_and(|(base, quote, _)| canonical_asset(&base) == self.base && canonical_asset(&quote) == self.quote)
+    }
+}
+
+impl fmt::Display for Instrument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.venue.name(), self.canonical())
+    }
+}
+
+fn canonical_asset(asset: &str) -> String {
+    let upper = asset.trim().to_uppercase();
+    KRAKEN_ALIASES.iter().find(|(k, _)| *k == upper).map_or(upper, |(_, c)| c.to_string())
+}
+
+fn kraken_asset(asset: &str) -> &str {
+    KRAKEN_ALIASES.iter().find(|(_, c)| *c == asset).map_or(asset, |(k, _)| k)
+}
+
+fn bitfinex_asset(asset: &str) -> &str {
+    BITFINEX_ALIASES.iter().find(|(_, c)| *c == asset).map_or(asset, |(b, _)| b)
+}
+
+// "tBTCUSD", "tBTCUST", "tDOGE:USD" hoặc dạng chuẩn
+fn split_bitfinex(raw: &str) -> Result<(String, String, bool), SymbolParseError> {
+    let s = raw.trim();
+    let s = s.strip_prefix('t').filter(|rest| rest.len() == 6 || rest.contains(':')).unwrap_or(s);
+    let (base, quote, perp) = match split_pair(s) {
+        Ok(pair) => pair,
+        // quote riêng của Bitfinex (UST, UDC) không có trong KNOWN_QUOTES
+        Err(_) if s.len() == 6 && s.is_ascii() => (s[..3].to_uppercase(), s[3..].to_uppercase(), false),
+        Err(e) => return Err(e),
+    };
+    let alias = |asset: String| BITFINEX_ALIASES.iter().find(|(b, _)| *b == asset).map_or(asset, |(_, c)| c.to_string());
+    Ok((alias(base), alias(quote), perp))
+}
+
+// (base, quote, là perp)
+fn split_pair(raw: &str) -> Result<(String, String, bool), SymbolParseError> {
+    let s = raw.trim().to_uppercase();
+    if s.is_empty() {
+        return Err(SymbolParseError::Empty);
+    }
+    let (s, perp) = match s.strip_suffix("-SWAP").or_else(|| s.strip_suffix("-PERP")) {
+        Some(rest) => (rest.to_string(), true),
+        None => (s, false),
+    };
+    let (base, quote) = match s.split_once(['/', '-', '_', ':']) {
+        Some((b, q)) => (b.to_string(), q.to_string()),
+        None => {
+            let quote = KNOWN_QUOTES
+                .iter()
+                .find(|q| s.len() > q.len() && s.ends_with(*q))
+                .ok_or_else(|| SymbolParseError::UnknownQuote(raw.to_string()))?;
+            (s[..s.len() - quote.len()].to_string(), quote.to_string())
+        }
+    };
+    if base.is_empty() || quote.is_empty() {
+        return Err(SymbolParseError::UnknownQuote(raw.to_string()));
+    }
+    if canonical_asset(&base) == canonical_asset(&quote) {
+        return Err(SymbolParseError::SameAsset(raw.to_string()));
+    }
+    Ok((base, quote, perp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_venue_symbols() {
+        for raw in ["cakebnb", "CAKE-BNB", "CAKE/BNB", "cake_bnb"] {
+            let inst = Instrument::parse(Exchange::Binance, raw).unwrap();
+            assert_eq!((inst.canonical(), inst.venue_symbol()), ("CAKE/BNB".to_string(), "cakebnb".to_string()), "{}", raw);
+        }
+        let btc = Instrument::parse(Exchange::Kraken, "xbt/usd").unwrap();
+        assert_eq!((btc.base.as_str(), btc.venue_symbol()), ("BTC", "XBT/USD".to_string()));
+        assert_eq!(Instrument::parse(Exchange::Coinbase, "btcusdt").unwrap().venue_symbol(), "BTC-USDT");
+        assert_eq!(Instrument::parse(Exchange::Kucoin, "BTC/USDT").unwrap().venue_symbol(), "BTC-USDT");
+        assert_eq!(Instrument::parse(Exchange::Gateio, "btc-usdt").unwrap().venue_symbol(), "BTC_USDT");
+        for (raw, canonical, venue) in [("tBTCUSD", "BTC/USD", "tBTCUSD"), ("tETHUST", "ETH/USDT", "tETHUST"), ("DOGE/USDT", "DOGE/USDT", "tDOGE:UST")] {
+            let inst = Instrument::parse(Exchange::Bitfinex, raw).unwrap();
+            assert_eq!((inst.canonical(), inst.venue_symbol()), (canonical.to_string(), venue.to_string()), "{}", raw);
+        }
+
+        let swap = Instrument::parse(Exchange::Okx, "BTC-USDT-SWAP").unwrap();
+        assert_eq!((swap.kind, swap.canonical(), swap.venue_symbol()), (InstrumentKind::Perp, "BTC/USDT-PERP".into(), "BTC-USDT-SWAP".into()));
+        let fut = Instrument::parse(Exchange::BinanceFutures, "BTC/USDT").unwrap();
+        assert_eq!((fut.kind, fut.to_string()), (InstrumentKind::Perp, "binance_futures:BTC/USDT-PERP".into()));
+        assert!(fut.matches("btcusdt") && fut.matches("XBT/USDT") && !fut.matches("ETH/USDT"));
+
+        assert_eq!(Instrument::parse(Exchange::Binance, "foobar"), Err(SymbolParseError::UnknownQuote("foobar".into())));
+        assert_eq!(Instrument::parse(Exchange::Binance, "usdt"), Err(SymbolParseError::UnknownQuote("usdt".into())));
+        assert!(matches!(Instrument::parse(Exchange::Kraken, "XBT/BTC"), Err(SymbolParseError::SameAsset(_))));
+    }
+}
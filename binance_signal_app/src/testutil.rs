@@ -0,0 +1,624 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, Query, State,
+    },
+    http::{StatusCode, Uri},
+    response::{IntoResponse, Response},
+    routing::{delete, get, post},
+    Json, Router,
+};
+use chrono::Utc;
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex, MutexGuard},
+};
+use tokio::{net::TcpListener, sync::broadcast, task::JoinHandle};
+
+use crate::rest::binance::BinanceNetwork;
+
+// listenKey duy nhất, user stream là stream `/ws/mock-listen-key`
+pub const LISTEN_KEY: &str = "mock-listen-key";
+
+type Params = Query<HashMap<String, String>>;
+
+// Order mock đã nhận, trạng thái theo chuỗi của Binance (NEW, FILLED, CANCELED, ...)
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockOrder {
+    pub order_id: u64,
+    pub client_order_id: String,
+    pub symbol: String,
+    pub side: String,
+    pub order_type: String,
+    pub price: Decimal,
+    pub qty: Decimal,
+    pub filled: Decimal,
+    pub status: String,
+    // đặt qua `/fapi`: user stream đẩy ORDER_TRADE_UPDATE thay cho executionReport
+    pub futures: bool,
+}
+
+impl MockOrder {
+    fn is_open(&self) -> bool {
+        self.status == "NEW" || self.status == "PARTIALLY_FILLED"
+    }
+
+    fn response(&self) -> Value {
+        json!({
+            "symbol": self.symbol,
+            "orderId": self.order_id,
+            "clientOrderId": self.client_order_id,
+            "price": self.price.to_string(),
+            "origQty": self.qty.to_string(),
+            "executedQty": self.filled.to_string(),
+            "cummulativeQuoteQty": (self.filled * self.price).to_string(),
+            "status": self.status,
+            "type": self.order_type,
+            "side": self.side,
+            "transactTime": Utc::now().timestamp_millis(),
+            "fills": [],
+        })
+    }
+
+    // `orig` = id gốc khi huỷ, "c" lúc đó là id của request huỷ (spot)
+    fn execution_report(&self, last_qty: Decimal, last_price: Decimal, orig: Option<&str>) -> String {
+        if self.futures {
+            return self.order_trade_update(last_qty, last_price);
+        }
+        let (c, orig) = match orig {
+            Some(orig) => (format!("cancel-{}", self.order_id), orig),
+            None => (self.client_order_id.clone(), ""),
+        };
+        json!({
+            "e": "executionReport",
+            "E": Utc::now().timestamp_millis(),
+            "s": self.symbol,
+            "c": c,
+            "C": orig,
+            "S": self.side,
+            "o": self.order_type,
+            "q": self.qty.to_string(),
+            "p": self.price.to_string(),
+            "X": self.status,
+            "r": "NONE",
+            "i": self.order_id,
+            "l": last_qty.to_string(),
+            "z": self.filled.to_string(),
+            "L": last_price.to_string(),
+            "n": "0",
+            "m": self.order_type != "MARKET",
+        })
+        .to_string()
+    }
+
+    // futures: "c" luôn là id gốc, kể cả khi huỷ
+    fn order_trade_update(&self, last_qty: Decimal, last_price: Decimal) -> String {
+        let now = Utc::now().timestamp_millis();
+        json!({
+            "e": "ORDER_TRADE_UPDATE",
+            "E": now,
+            "T": now,
+            "o": {
+                "s": self.symbol,
+                "c": self.client_order_id,
+                "S": self.side,
+                "o": self.order_type,
+                "q": self.qty.to_string(),
+                "p": self.price.to_string(),
+                "X": self.status,
+                "i": self.order_id,
+                "l": last_qty.to_string(),
+                "z": self.filled.to_string(),
+                "L": last_price.to_string(),
+                "m": self.order_type != "MARKET",
+                "ps": "BOTH",
+            },
+        })
+        .to_string()
+    }
+}
+
+struct MockState {
+    // depth snapshot theo symbol chữ hoa
+    snapshots: Mutex<HashMap<String, Value>>,
+    // message đã đẩy theo stream, phát lại cho kết nối mới để test không phụ thuộc thời điểm connect
+    history: Mutex<HashMap<String, Vec<String>>>,
+    live: broadcast::Sender<(String, String)>,
+    orders: Mutex<Vec<MockOrder>>,
+    balances: Mutex<Vec<Value>>,
+    // Some(msg) = từ chối mọi order mới
+    reject: Mutex<Option<String>>,
+}
+
+impl MockState {
+    fn push(&self, stream: &str, message: String) {
+        let mut history = lock(&self.history);
+        history.entry(stream.to_string()).or_default().push(message.clone());
+        let _ = self.live.send((stream.to_string(), message));
+    }
+}
+
+fn lock<T>(m: &Mutex<T>) -> MutexGuard<'_, T> {
+    m.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// Binance spot giả chạy trong process cho test connector / OMS: REST (depth, exchangeInfo,
+// order, account, listenKey, cùng các endpoint order / balance / listenKey của `/fapi`)
+// + WS `/ws/<stream>`. Message stream đẩy bằng `push`,
+// order được ack NEW ngay và chỉ khớp khi gọi `fill`. Server dừng khi drop
+pub struct MockBinance {
+    addr: SocketAddr,
+    state: Arc<MockState>,
+    server: JoinHandle<()>,
+}
+
+impl MockBinance {
+    pub async fn start() -> Self {
+        let state = Arc::new(MockState {
+            snapshots: Mutex::new(HashMap::new()),
+            history: Mutex::new(HashMap::new()),
+            live: broadcast::channel(1024).0,
+            orders: Mutex::new(Vec::new()),
+            balances: Mutex::new(Vec::new()),
+            reject: Mutex::new(None),
+        });
+        let app = Router::new()
+            .route("/api/v3/time", get(server_time))
+            .route("/api/v3/depth", get(depth))
+            .route("/api/v3/exchangeInfo", get(exchange_info))
+            .route("/api/v3/order", post(place_order).delete(cancel_order))
+            .route("/api/v3/openOrders", get(open_orders).delete(cancel_open_orders))
+            .route("/api/v3/account", get(account))
+            .route("/api/v3/userDataStream", post(listen_key).put(listen_key).delete(listen_key))
+            .route("/fapi/v1/exchangeInfo", get(exchange_info))
+            .route("/fapi/v1/order", post(place_order).delete(cancel_order))
+            .route("/fapi/v1/openOrders", get(open_orders))
+            .route("/fapi/v1/allOpenOrders", delete(cancel_open_orders))
+            .route("/fapi/v2/balance", get(futures_balance))
+            .route("/fapi/v1/listenKey", post(listen_key).put(listen_key).delete(listen_key))
+            .route("/ws/:stream", get(upgrade))
+            .with_state(state.clone());
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind mock binance");
+        let addr = listener.local_addr().expect("mock binance addr");
+        let server = tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+        Self { addr, state, server }
+    }
+
+    pub fn rest_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    // truyền cho `with_network` của feed / venue
+    pub fn network(&self) -> BinanceNetwork {
+        BinanceNetwork::Custom { rest: self.rest_url(), ws: format!("ws://{}", self.addr) }
+    }
+
+    pub fn set_snapshot(&self, symbol: &str, last_update_id: u64, bids: &[(&str, &str)], asks: &[(&str, &str)]) {
+        let snapshot = json!({ "lastUpdateId": last_update_id, "bids": levels(bids), "asks": levels(asks) });
+        lock(&self.state.snapshots).insert(symbol.to_uppercase(), snapshot);
+    }
+
+    // `stream` như trong URL, vd. "btcusdt@depth@100ms" hoặc `LISTEN_KEY`
+    pub fn push(&self, stream: &str, message: impl Into<String>) {
+        self.state.push(stream, message.into());
+    }
+
+    pub fn set_balance(&self, asset: &str, free: Decimal) {
+        let mut balances = lock(&self.state.balances);
+        balances.retain(|b| b["asset"] != asset);
+        balances.push(json!({ "asset": asset, "free": free.to_string(), "locked": "0" }));
+    }
+
+    pub fn reject_orders(&self, msg: Option<&str>) {
+        *lock(&self.state.reject) = msg.map(str::to_string);
+    }
+
+    pub fn orders(&self) -> Vec<MockOrder> {
+        lock(&self.state.orders).clone()
+    }
+
+    // khớp `qty` của order và đẩy executionReport lên user stream, false nếu order không mở
+    pub fn fill(&self, client_order_id: &str, qty: Decimal, price: Decimal) -> bool {
+        let report = {
+            let mut orders = lock(&self.state.orders);
+            let Some(order) = orders.iter_mut().find(|o| o.client_order_id == client_order_id && o.is_open()) else {
+                return false;
+            };
+            let qty = qty.min(order.qty - order.filled);
+            order.filled += qty;
+            order.status = if order.filled >= order.qty { "FILLED" } else { "PARTIALLY_FILLED" }.to_string();
+            order.execution_report(qty, price, None)
+        };
+        self.state.push(LISTEN_KEY, report);
+        true
+    }
+}
+
+impl Drop for MockBinance {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+// diff event của stream `<symbol>@depth@100ms`
+pub fn depth_diff(first_update_id: u64, final_update_id: u64, bids: &[(&str, &str)], asks: &[(&str, &str)]) -> String {
+    json!({
+        "e": "depthUpdate",
+        "E": Utc::now().timestamp_millis(),
+        "U": first_update_id,
+        "u": final_update_id,
+        "b": levels(bids),
+        "a": levels(asks),
+    })
+    .to_string()
+}
+
+fn levels<'a>(levels: &[(&'a str, &'a str)]) -> Vec<[&'a str; 2]> {
+    levels.iter().map(|(p, q)| [*p, *q]).collect()
+}
+
+fn api_error(code: i64, msg: &str) -> Response {
+    (StatusCode::BAD_REQUEST, Json(json!({ "code": code, "msg": msg }))).into_response()
+}
+
+// endpoint SIGNED thiếu chữ ký -> lỗi như sàn thật
+fn missing_signature(params: &HashMap<String, String>) -> Option<Response> {
+    (!params.contains_key("signature") || !params.contains_key("timestamp"))
+        .then(|| api_error(-1102, "Mandatory parameter 'signature' was not sent."))
+}
+
+fn param_decimal(params: &HashMap<String, String>, key: &str) -> Decimal {
+    params.get(key).and_then(|v| v.parse().ok()).unwrap_or_default()
+}
+
+async fn server_time() -> Json<Value> {
+    Json(json!({ "serverTime": Utc::now().timestamp_millis() }))
+}
+
+async fn depth(State(state): State<Arc<MockState>>, Query(params): Params) -> Response {
+    let symbol = params.get("symbol").cloned().unwrap_or_default().to_uppercase();
+    match lock(&state.snapshots).get(&symbol) {
+        Some(snapshot) => Json(snapshot.clone()).into_response(),
+        None => api_error(-1121, "Invalid symbol."),
+    }
+}
+
+// không có filter: venue gửi order nguyên như strategy đặt
+async fn exchange_info() -> Json<Value> {
+    Json(json!({ "serverTime": Utc::now().timestamp_millis(), "symbols": [] }))
+}
+
+async fn place_order(State(state): State<Arc<MockState>>, uri: Uri, Query(params): Params) -> Response {
+    if let Some(resp) = missing_signature(&params) {
+        return resp;
+    }
+    if let Some(msg) = lock(&state.reject).clone() {
+        return api_error(-2010, &msg);
+    }
+    let order = {
+        let mut orders = lock(&state.orders);
+        let order = MockOrder {
+            order_id: orders.len() as u64 + 1,
+            client_order_id: params.get("newClientOrderId").cloned().unwrap_or_default(),
+            symbol: params.get("symbol").cloned().unwrap_or_default().to_uppercase(),
+            side: params.get("side").cloned().unwrap_or_default(),
+            order_type: params.get("type").cloned().unwrap_or_default(),
+            price: param_decimal(&params, "price"),
+            qty: param_decimal(&params, "quantity"),
+            filled: Decimal::ZERO,
+            status: "NEW".to_string(),
+            futures: uri.path().starts_with("/fapi"),
+        };
+        orders.push(order.clone());
+        order
+    };
+    state.push(LISTEN_KEY, order.execution_report(Decimal::ZERO, Decimal::ZERO, None));
+    Json(order.response()).into_response()
+}
+
+fn cancel_where(state: &MockState, matches: impl Fn(&MockOrder) -> bool) -> Vec<Value> {
+    let canceled: Vec<MockOrder> = lock(&state.orders)
+        .iter_mut()
+        .filter(|o| o.is_open() && matches(o))
+        .map(|o| {
+            o.status = "CANCELED".to_string();
+            o.clone()
+        })
+        .collect();
+    canceled
+        .iter()
+        .map(|o| {
+            state.push(LISTEN_KEY, o.execution_report(Decimal::ZERO, Decimal::ZERO, Some(&o.client_order_id)));
+            o.response()
+        })
+        .collect()
+}
+
+async fn cancel_order(State(state): State<Arc<MockState>>, Query(params): Params) -> Response {
+    if let Some(resp) = missing_signature(&params) {
+        return resp;
+    }
+    let client_id = params.get("origClientOrderId").cloned();
+    let order_id = params.get("orderId").and_then(|v| v.parse::<u64>().ok());
+    let canceled = cancel_where(&state, |o| Some(&o.client_order_id) == client_id.as_ref() || Some(o.order_id) == order_id);
+    match canceled.into_iter().next() {
+        Some(resp) => Json(resp).into_response(),
+        None => api_error(-2011, "Unknown order sent."),
+    }
+}
+
+async fn cancel_open_orders(State(state): State<Arc<MockState>>, Query(params): Params) -> Response {
+    if let Some(resp) = missing_signature(&params) {
+        return resp;
+    }
+    let symbol = params.get("symbol").cloned().unwrap_or_default().to_uppercase();
+    Json(cancel_where(&state, |o| o.symbol == symbol)).into_response()
+}
+
+async fn open_orders(State(state): State<Arc<MockState>>, Query(params): Params) -> Response {
+    if let Some(resp) = missing_signature(&params) {
+        return resp;
+    }
+    let symbol = params.get("symbol").map(|s| s.to_uppercase());
+    let orders = lock(&state.orders);
+    let open: Vec<Value> = orders
+        .iter()
+        .filter(|o| o.is_open() && symbol.as_ref().is_none_or(|s| *s == o.symbol))
+        .map(MockOrder::response)
+        .collect();
+    Json(open).into_response()
+}
+
+async fn account(State(state): State<Arc<MockState>>, Query(params): Params) -> Response {
+    if let Some(resp) = missing_signature(&params) {
+        return resp;
+    }
+    let balances = lock(&state.balances).clone();
+    Json(json!({
+        "canTrade": true,
+        "canWithdraw": false,
+        "canDeposit": false,
+        "updateTime": Utc::now().timestamp_millis(),
+        "balances": balances,
+    }))
+    .into_response()
+}
+
+// số dư ví futures = free + locked của `set_balance`
+async fn futures_balance(State(state): State<Arc<MockState>>, Query(params): Params) -> Response {
+    if let Some(resp) = missing_signature(&params) {
+        return resp;
+    }
+    let balances: Vec<Value> = lock(&state.balances)
+        .iter()
+        .map(|b| {
+            let total = json_decimal(&b["free"]) + json_decimal(&b["locked"]);
+            json!({ "asset": b["asset"], "balance": total.to_string() })
+        })
+        .collect();
+    Json(balances).into_response()
+}
+
+fn json_decimal(value: &Value) -> Decimal {
+    value.as_str().and_then(|v| v.parse().ok()).unwrap_or_default()
+}
+
+async fn listen_key() -> Json<Value> {
+    Json(json!({ "listenKey": LISTEN_KEY }))
+}
+
+async fn upgrade(ws: WebSocketUpgrade, Path(stream): Path<String>, State(state): State<Arc<MockState>>) -> Response {
+    ws.on_upgrade(move |socket| serve_stream(socket, stream, state))
+}
+
+// phát lại history rồi chuyển tiếp message mới của stream tới khi client đóng
+async fn serve_stream(mut socket: WebSocket, stream: String, state: Arc<MockState>) {
+    let (backlog, mut live) = {
+        let history = lock(&state.history);
+        (history.get(&stream).cloned().unwrap_or_default(), state.live.subscribe())
+    };
+    for message in backlog {
+        if socket.send(Message::Text(message)).await.is_err() {
+            return;
+        }
+    }
+    loop {
+        tokio::select! {
+            msg = live.recv() => match msg {
+                Ok((s, message)) if s == stream => {
+                    if socket.send(Message::Text(message)).await.is_err() {
+                        return;
+                    }
+                }
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+            // axum tự trả lời ping, ở đây chỉ cần biết client đã đóng
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+// chuỗi price/qty xấu: rỗng, âm, NaN/inf, tràn Decimal, sai định dạng
+const BAD_NUMBERS: &[&str] = &[
+    "", "0", "-0", "-1", "-0.5", "NaN", "inf", "-inf", "1e400", "1e-400", "79228162514264337593543950336",
+    "0x1A", " 1", "1,5", "1.2.3", "abc", "1e", "--1", ".", "1e-8",
+];
+const JSON_NOISE: &[char] = &['{', '}', '[', ']', ',', ':', '"', '\\', '-', '.', 'e', '0', '9', ' ', 'n', 'x', 'é'];
+
+// Sinh input ngẫu nhiên với seed cố định cho property test (không cần proptest):
+// số hợp lệ lẫn chuỗi xấu, JSON bị cắt / đổi ký tự. Lỗi thì in seed để chạy lại đúng ca đó
+pub struct Fuzz {
+    state: u64,
+}
+
+impl Fuzz {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1 }
+    }
+
+    // xorshift64*
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    pub fn below(&mut self, n: u64) -> u64 {
+        if n == 0 { 0 } else { self.next_u64() % n }
+    }
+
+    pub fn chance(&mut self, percent: u64) -> bool {
+        self.below(100) < percent
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+
+    // 80% số dương quanh `center` (nhiều chữ số thập phân, có lúc bằng 0 = xoá level), còn lại là chuỗi xấu
+    pub fn number(&mut self, center: u64) -> String {
+        if self.chance(20) {
+            return self.pick(BAD_NUMBERS).to_string();
+        }
+        let scale = self.below(9) as u32;
+        let units = (center * 10u64.pow(scale)).saturating_add(self.below(10u64.pow(scale) * 4));
+        let mut value = Decimal::from_i128_with_scale(units as i128, scale);
+        if self.chance(10) {
+            value = Decimal::ZERO;
+        }
+        value.to_string()
+    }
+
+    pub fn levels(&mut self, center: u64, max: u64) -> Vec<[String; 2]> {
+        (0..self.below(max + 1)).map(|_| [self.number(center), self.number(1)]).collect()
+    }
+
+    // 1-4 lần xoá / chèn / thay ký tự hoặc cắt ngang trên JSON hợp lệ
+    pub fn mutate(&mut self, json: &str) -> String {
+        let mut chars: Vec<char> = json.chars().collect();
+        for _ in 0..=self.below(4) {
+            let at = self.below(chars.len() as u64 + 1) as usize;
+            match self.below(4) {
+                0 if at < chars.len() => {
+                    chars.remove(at);
+                }
+                1 => chars.insert(at, *self.pick(JSON_NOISE)),
+                2 if at < chars.len() => chars[at] = *self.pick(JSON_NOISE),
+                _ => chars.truncate(at),
+            }
+        }
+        chars.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::order::OrderSide;
+    use crate::rest::binance::{BinanceCredentials, BinanceMarket, BinanceRestClient};
+    use crate::risk::OrderIntent;
+    use crate::venue::{binance::BinanceVenue, ExecutionVenue, VenueError};
+    use crate::ws::{binance::BinanceOrderbookWS, OrderbookFeed};
+    use rust_decimal_macros::dec;
+    use std::time::Duration;
+
+    // chờ điều kiện tới tối đa 5s
+    async fn eventually(mut check: impl AsyncFnMut() -> bool) {
+        for _ in 0..100 {
+            if check().await {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("condition not met within 5s");
+    }
+
+    #[tokio::test]
+    async fn test_full_book_sync_against_mock() {
+        let mock = MockBinance::start().await;
+        mock.set_snapshot("btcusdt", 100, &[("100", "1")], &[("101", "1")]);
+        // event cũ hơn snapshot bị bỏ, event tiếp theo nối đúng lastUpdateId + 1
+        mock.push("btcusdt@depth@100ms", depth_diff(99, 100, &[("99", "5")], &[]));
+        mock.push("btcusdt@depth@100ms", depth_diff(101, 102, &[("100.5", "2")], &[("101", "0")]));
+
+        let ws = Arc::new(BinanceOrderbookWS::new_full("btcusdt", 100).with_network(mock.network()));
+        let task = tokio::spawn(ws.clone().start());
+        eventually(async || ws.snapshot().last_update_id == 102).await;
+        let snap = ws.snapshot();
+        assert_eq!(snap.best_bid(), Some((dec!(100.5), dec!(2))));
+        assert_eq!(snap.best_ask(), None);
+        assert!(!snap.bids.contains_key(&dec!(99)));
+
+        mock.push("btcusdt@depth@100ms", depth_diff(103, 103, &[], &[("102", "3")]));
+        eventually(async || ws.snapshot().best_ask() == Some((dec!(102), dec!(3)))).await;
+        task.abort();
+    }
+
+    #[tokio::test]
+    async fn test_venue_order_lifecycle_against_mock() {
+        let mock = MockBinance::start().await;
+        mock.set_balance("USDT", dec!(1000));
+        let client = BinanceRestClient::new(Some(BinanceCredentials::new("key", "secret"))).with_base_url(&mock.rest_url());
+        let venue = Arc::new(BinanceVenue::new(client, "mock").with_network(mock.network()));
+        let mut fills = venue.fills();
+        let task = tokio::spawn(venue.clone().start());
+
+        let id = venue.place_order(&OrderIntent::limit("btcusdt", OrderSide::Buy, dec!(100), dec!(2))).await.unwrap();
+        assert_eq!(mock.orders()[0].client_order_id, id);
+        assert!(mock.fill(&id, dec!(0.5), dec!(100)));
+        let fill = tokio::time::timeout(Duration::from_secs(5), fills.recv()).await.unwrap().unwrap();
+        assert_eq!((fill.qty, fill.price), (dec!(0.5), dec!(100)));
+        assert_eq!(venue.position("btcusdt").await.unwrap().qty, dec!(0.5));
+        assert_eq!(venue.balances().await.unwrap()[0].free, dec!(1000));
+
+        // huỷ cả symbol -> executionReport CANCELED đóng order trong OMS
+        venue.cancel_all("btcusdt").await.unwrap();
+        assert_eq!(mock.orders()[0].status, "CANCELED");
+        let oms = venue.oms().clone();
+        eventually(async || oms.lock().await.open_symbols().is_empty()).await;
+
+        mock.reject_orders(Some("Account has insufficient balance for requested action."));
+        let err = venue.place_order(&OrderIntent::market("btcusdt", OrderSide::Sell, dec!(1))).await.unwrap_err();
+        assert!(matches!(err, VenueError::Rest(_)));
+        task.abort();
+    }
+
+    // account usdm: order qua `/fapi`, fill về qua ORDER_TRADE_UPDATE
+    #[tokio::test]
+    async fn test_futures_venue_against_mock() {
+        let mock = MockBinance::start().await;
+        mock.set_balance("USDT", dec!(500));
+        let client = BinanceRestClient::new(Some(BinanceCredentials::new("key", "secret")))
+            .with_market(BinanceMarket::UsdM)
+            .with_base_url(&BinanceMarket::UsdM.rest_url(&mock.network()));
+        let venue = Arc::new(BinanceVenue::new(client, "perp").with_network(mock.network()));
+        assert_eq!(venue.name(), "binance_futures");
+        assert_eq!(venue.load_symbols(&[]).await.unwrap(), 0);
+        let mut fills = venue.fills();
+        let task = tokio::spawn(venue.clone().start());
+
+        let id = venue.place_order(&OrderIntent::market("btcusdt", OrderSide::Sell, dec!(0.2))).await.unwrap();
+        assert!(mock.orders()[0].futures);
+        assert!(mock.fill(&id, dec!(0.2), dec!(100)));
+        let fill = tokio::time::timeout(Duration::from_secs(5), fills.recv()).await.unwrap().unwrap();
+        assert_eq!((fill.side, fill.qty), (OrderSide::Sell, dec!(0.2)));
+        assert_eq!(venue.position("btcusdt").await.unwrap().qty, dec!(-0.2));
+        assert_eq!(venue.balances().await.unwrap()[0].total(), dec!(500));
+
+        venue.place_order(&OrderIntent::limit("btcusdt", OrderSide::Buy, dec!(90), dec!(0.2))).await.unwrap();
+        venue.cancel_all("btcusdt").await.unwrap();
+        let oms = venue.oms().clone();
+        eventually(async || oms.lock().await.open_symbols().is_empty()).await;
+        task.abort();
+    }
+}
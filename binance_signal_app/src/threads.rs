@@ -0,0 +1,203 @@
+use serde::Deserialize;
+use std::{
+    future::Future,
+    io,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+use tokio::{
+    runtime::{Builder, Handle, Runtime},
+    sync::oneshot,
+    task::JoinHandle,
+};
+use tracing::{info, warn};
+
+// Tách hot path ws/parse khỏi signal/strategy:
+//
+//   [threads]
+//   dedicated_feed_runtime = true
+//   feed_core = 2
+//   feed_nice = -10
+//   worker_threads = 4
+//   worker_cores = [4, 5, 6, 7]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ThreadSettings {
+    // mọi feed chạy trên một runtime current-thread riêng, không tranh worker với strategy
+    pub dedicated_feed_runtime: bool,
+    // pin thread feed vào core này (chỉ Linux)
+    pub feed_core: Option<usize>,
+    // nice của thread feed, -20..=19, giá trị âm cần CAP_SYS_NICE
+    pub feed_nice: Option<i32>,
+    // worker của runtime chính, None = số core
+    pub worker_threads: Option<usize>,
+    // worker runtime chính được pin lần lượt vào các core này
+    pub worker_cores: Vec<usize>,
+}
+
+impl ThreadSettings {
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let cores = thread::available_parallelism().map_or(usize::MAX, |n| n.get());
+        if self.feed_core.is_some_and(|c| c >= cores) || self.worker_cores.iter().any(|c| *c >= cores) {
+            errors.push(format!("`threads.feed_core` / `threads.worker_cores` must be < {} (cores on this host)", cores));
+        }
+        if (self.feed_core.is_some() || self.feed_nice.is_some()) && !self.dedicated_feed_runtime {
+            errors.push("`threads.feed_core` / `threads.feed_nice` require `threads.dedicated_feed_runtime = true`".to_string());
+        }
+        if self.feed_core.is_some_and(|c| self.worker_cores.contains(&c)) {
+            errors.push("`threads.feed_core` must not be one of `threads.worker_cores`".to_string());
+        }
+        if self.feed_nice.is_some_and(|n| !(-20..=19).contains(&n)) {
+            errors.push("`threads.feed_nice` must be in -20..=19".to_string());
+        }
+        if self.worker_threads == Some(0) {
+            errors.push("`threads.worker_threads` must be > 0".to_string());
+        }
+        errors
+    }
+
+    // runtime chính (signal, strategy, server), thay cho `#[tokio::main]`
+    pub fn build_main_runtime(&self) -> io::Result<Runtime> {
+        let mut builder = Builder::new_multi_thread();
+        builder.enable_all();
+        if let Some(n) = self.worker_threads {
+            builder.worker_threads(n);
+        }
+        if !self.worker_cores.is_empty() {
+            let cores = self.worker_cores.clone();
+            let next = AtomicUsize::new(0);
+            // on_thread_start chạy cả cho thread blocking, pin vòng tròn trên cùng tập core
+            builder.on_thread_start(move || {
+                let core = cores[next.fetch_add(1, Ordering::Relaxed) % cores.len()];
+                if let Err(e) = pin_current_thread(core) {
+                    warn!(core, error = %e, "cannot pin worker thread");
+                }
+            });
+        }
+        builder.build()
+    }
+}
+
+// Runtime current-thread trên một OS thread riêng cho ws/parse/book apply.
+// Drop (hoặc `stop`) thì runtime dừng và mọi task trên đó bị huỷ
+pub struct FeedRuntime {
+    handle: Handle,
+    stop: Option<oneshot::Sender<()>>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl FeedRuntime {
+    pub fn start(settings: &ThreadSettings) -> io::Result<Self> {
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+        let handle = runtime.handle().clone();
+        let (stop, stopped) = oneshot::channel();
+        let (core, nice) = (settings.feed_core, settings.feed_nice);
+        let thread = thread::Builder::new().name("feed-rt".into()).spawn(move || {
+            if let Some(core) = core
+                && let Err(e) = pin_current_thread(core)
+            {
+                warn!(core, error = %e, "cannot pin feed thread");
+            }
+            if let Some(nice) = nice
+                && let Err(e) = set_current_thread_nice(nice)
+            {
+                warn!(nice, error = %e, "cannot set feed thread priority");
+            }
+            info!(?core, ?nice, "feed runtime started");
+            let _ = runtime.block_on(stopped);
+        })?;
+        Ok(Self { handle, stop: Some(stop), thread: Some(thread) })
+    }
+
+    pub fn handle(&self) -> &Handle {
+        &self.handle
+    }
+
+    pub fn spawn<F>(&self, fut: F) -> JoinHandle<()>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.handle.spawn(fut)
+    }
+
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for FeedRuntime {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn pin_current_thread(core: usize) -> io::Result<()> {
+    // SAFETY: cpu_set_t là bitmask thuần, zeroed là tập rỗng; pid 0 = thread hiện tại
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_SET(core, &mut set);
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn pin_current_thread(_core: usize) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "core pinning is only supported on linux"))
+}
+
+// trên Linux nice theo từng thread (tid), không ảnh hưởng thread khác của process
+#[cfg(target_os = "linux")]
+pub fn set_current_thread_nice(nice: i32) -> io::Result<()> {
+    // SAFETY: gettid / setpriority chỉ đọc tham số, không giữ con trỏ
+    unsafe {
+        let tid = libc::gettid() as libc::id_t;
+        if libc::setpriority(libc::PRIO_PROCESS, tid, nice) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn set_current_thread_nice(_nice: i32) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "per-thread priority is only supported on linux"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feed_runtime_runs_on_own_thread() {
+        let settings = ThreadSettings { dedicated_feed_runtime: true, feed_core: Some(0), ..Default::default() };
+        assert!(settings.validate().is_empty());
+        assert_eq!(ThreadSettings { feed_nice: Some(-30), ..Default::default() }.validate().len(), 2);
+
+        let rt = FeedRuntime::start(&settings).unwrap();
+        let (tx, rx) = std::sync::mpsc::channel();
+        rt.spawn(async move {
+            tx.send(thread::current().name().map(str::to_string)).unwrap();
+        });
+        assert_eq!(rx.recv().unwrap().as_deref(), Some("feed-rt"));
+
+        // task chưa xong bị huỷ khi runtime dừng
+        let pending = rt.spawn(std::future::pending());
+        rt.stop();
+        let main = Builder::new_current_thread().build().unwrap();
+        assert!(main.block_on(pending).unwrap_err().is_cancelled());
+    }
+}
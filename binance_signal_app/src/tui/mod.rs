@@ -0,0 +1,359 @@
+use chrono::{DateTime, Utc};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    symbols,
+    text::{Line, Span},
+    widgets::{Axis, Block, Cell, Chart, Dataset, GraphType, List, ListItem, Paragraph, Row, Table},
+    DefaultTerminal, Frame,
+};
+use rust_decimal::Decimal;
+use std::{
+    collections::{HashMap, VecDeque},
+    error::Error,
+    time::Duration,
+};
+use tokio::sync::{broadcast, watch};
+use tracing::warn;
+
+use crate::cli::start_hub;
+use crate::config::AppConfig;
+use crate::net;
+use crate::core::{
+    orderbook::{to_f64, OrderbookSnapshot},
+    signal::SignalOutput,
+    trade::{Trade, TradeSide},
+};
+use crate::hub::MarketHub;
+use crate::portfolio::PortfolioSnapshot;
+use crate::supervisor::{Shutdown, Supervisor, DEFAULT_SHUTDOWN_TIMEOUT};
+
+const TICK: Duration = Duration::from_millis(100);
+const POSITIONS_POLL: Duration = Duration::from_secs(1);
+const MAX_TRADES: usize = 50;
+// ~20s lịch sử spread ở tick 100ms
+const MAX_SPREAD_POINTS: usize = 200;
+
+// Trạng thái hiển thị, tách khỏi terminal để test render bằng TestBackend
+pub struct TuiState {
+    // "exchange:symbol" theo thứ tự feed trong hub
+    labels: Vec<String>,
+    selected: usize,
+    depth: usize,
+    started_at: DateTime<Utc>,
+    trades: VecDeque<Trade>,
+    // (giây kể từ lúc mở, spread bps) theo từng feed
+    spreads: Vec<VecDeque<(f64, f64)>>,
+    // signal mới nhất theo symbol (lowercase)
+    signals: HashMap<String, SignalOutput>,
+    portfolio: Option<PortfolioSnapshot>,
+}
+
+impl TuiState {
+    pub fn new(hub: &MarketHub, depth: usize) -> Self {
+        let labels: Vec<String> = hub.feeds().iter().map(|f| format!("{}:{}", f.exchange(), f.symbol())).collect();
+        Self {
+            spreads: vec![VecDeque::new(); labels.len()],
+            labels,
+            selected: 0,
+            depth: depth.max(1),
+            started_at: Utc::now(),
+            trades: VecDeque::new(),
+            signals: HashMap::new(),
+            portfolio: None,
+        }
+    }
+
+    pub fn next_symbol(&mut self) {
+        if !self.labels.is_empty() {
+            self.selected = (self.selected + 1) % self.labels.len();
+        }
+    }
+
+    pub fn prev_symbol(&mut self) {
+        if !self.labels.is_empty() {
+            self.selected = (self.selected + self.labels.len() - 1) % self.labels.len();
+        }
+    }
+
+    pub fn on_trade(&mut self, trade: Trade) {
+        if self.trades.len() == MAX_TRADES {
+            self.trades.pop_back();
+        }
+        self.trades.push_front(trade);
+    }
+
+    pub fn on_signal(&mut self, signal: SignalOutput) {
+        self.signals.insert(signal.symbol.to_lowercase(), signal);
+    }
+
+    pub fn on_portfolio(&mut self, snapshot: Option<PortfolioSnapshot>) {
+        self.portfolio = snapshot;
+    }
+
+    // lấy mẫu spread mọi feed, gọi mỗi tick
+    pub fn sample(&mut self, hub: &MarketHub, now: DateTime<Utc>) {
+        let t = (now - self.started_at).num_milliseconds() as f64 / 1000.0;
+        for (feed, points) in hub.feeds().iter().zip(self.spreads.iter_mut()) {
+            let Some(((bid, _), (ask, _))) = feed.best_bid_ask() else { continue };
+            let mid = (bid + ask) / Decimal::TWO;
+            if mid.is_zero() {
+                continue;
+            }
+            if points.len() == MAX_SPREAD_POINTS {
+                points.pop_front();
+            }
+            points.push_back((t, to_f64((ask - bid) / mid * Decimal::from(10_000))));
+        }
+    }
+}
+
+pub fn draw(frame: &mut Frame, state: &TuiState, hub: &MarketHub) {
+    let [header, body] = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(frame.area());
+    let [left, right] = Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)]).areas(body);
+    let [chart, trades, bottom] =
+        Layout::vertical([Constraint::Percentage(40), Constraint::Percentage(30), Constraint::Percentage(30)]).areas(right);
+    let [signals, positions] = Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)]).areas(bottom);
+
+    let label = state.labels.get(state.selected).map(String::as_str).unwrap_or("-");
+    let title = format!(
+        " {} [{}/{}]  Tab/←→ đổi symbol, q thoát",
+        label,
+        state.selected + 1,
+        state.labels.len()
+    );
+    frame.render_widget(Paragraph::new(title).style(Style::default().add_modifier(Modifier::BOLD)), header);
+
+    let feed = hub.feeds().get(state.selected);
+    let symbol = feed.map(|f| f.symbol().to_lowercase()).unwrap_or_default();
+    match feed {
+        Some(feed) => draw_ladder(frame, left, &feed.snapshot(), state.depth),
+        None => frame.render_widget(Paragraph::new("no feed").block(Block::bordered().title("Depth")), left),
+    }
+    draw_spread(frame, chart, state.spreads.get(state.selected));
+    draw_trades(frame, trades, state, &symbol);
+    draw_signals(frame, signals, state.signals.get(&symbol));
+    draw_positions(frame, positions, state.portfolio.as_ref());
+}
+
+// ask trên (giá giảm dần tới best ask), bid dưới
+fn draw_ladder(frame: &mut Frame, area: Rect, book: &OrderbookSnapshot, depth: usize) {
+    let asks: Vec<_> = book.asks.iter().take(depth).collect();
+    let rows = asks
+        .into_iter()
+        .rev()
+        .map(|(p, q)| Row::new(vec![Cell::from(""), Cell::from(p.to_string()), Cell::from(q.to_string())]).style(Color::Red))
+        .chain(
+            book.bids
+                .iter()
+                .rev()
+                .take(depth)
+                .map(|(p, q)| Row::new(vec![Cell::from(q.to_string()), Cell::from(p.to_string()), Cell::from("")]).style(Color::Green)),
+        );
+    let table = Table::new(rows, [Constraint::Ratio(1, 3); 3])
+        .header(Row::new(["bid qty", "price", "ask qty"]).style(Style::default().add_modifier(Modifier::BOLD)))
+        .block(Block::bordered().title("Depth"));
+    frame.render_widget(table, area);
+}
+
+fn draw_spread(frame: &mut Frame, area: Rect, points: Option<&VecDeque<(f64, f64)>>) {
+    let data: Vec<(f64, f64)> = points.map(|p| p.iter().copied().collect()).unwrap_or_default();
+    let (x0, x1) = match (data.first(), data.last()) {
+        (Some(a), Some(b)) => (a.0, b.0.max(a.0 + 1.0)),
+        _ => (0.0, 1.0),
+    };
+    let y1 = data.iter().map(|p| p.1).fold(0.0_f64, f64::max).max(1.0) * 1.1;
+    let last = data.last().map(|p| format!(" {:.2} bps", p.1)).unwrap_or_default();
+    let dataset = Dataset::default()
+        .marker(symbols::Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(Color::Cyan))
+        .data(&data);
+    let chart = Chart::new(vec![dataset])
+        .block(Block::bordered().title(format!("Spread{}", last)))
+        .x_axis(Axis::default().bounds([x0, x1]))
+        .y_axis(Axis::default().bounds([0.0, y1]).labels(["0".to_string(), format!("{:.1}", y1)]));
+    frame.render_widget(chart, area);
+}
+
+fn draw_trades(frame: &mut Frame, area: Rect, state: &TuiState, symbol: &str) {
+    let items: Vec<ListItem> = state
+        .trades
+        .iter()
+        .filter(|t| t.symbol.eq_ignore_ascii_case(symbol))
+        .map(|t| {
+            let color = if t.side == TradeSide::Buy { Color::Green } else { Color::Red };
+            ListItem::new(Line::from(vec![
+                Span::raw(t.timestamp.format("%H:%M:%S%.3f ").to_string()),
+                Span::styled(format!("{:>4} ", if t.side == TradeSide::Buy { "BUY" } else { "SELL" }), color),
+                Span::raw(format!("{} @ {}", t.qty, t.price)),
+            ]))
+        })
+        .collect();
+    frame.render_widget(List::new(items).block(Block::bordered().title("Trades")), area);
+}
+
+fn draw_signals(frame: &mut Frame, area: Rect, signal: Option<&SignalOutput>) {
+    let lines: Vec<Line> = signal
+        .map(|s| s.values.iter().map(|(name, v)| Line::from(format!("{:<12} {:>10.4}", name, v))).collect())
+        .unwrap_or_default();
+    frame.render_widget(Paragraph::new(lines).block(Block::bordered().title("Signals")), area);
+}
+
+fn draw_positions(frame: &mut Frame, area: Rect, portfolio: Option<&PortfolioSnapshot>) {
+    let block = Block::bordered().title("Positions");
+    let Some(p) = portfolio else {
+        frame.render_widget(Paragraph::new("không có data (--api)").block(block), area);
+        return;
+    };
+    let rows = p.positions.iter().map(|pos| {
+        let style = if pos.total_pnl.is_sign_negative() { Style::default().fg(Color::Red) } else { Style::default().fg(Color::Green) };
+        Row::new(vec![
+            pos.symbol.clone(),
+            pos.qty.to_string(),
+            pos.avg_price.round_dp(4).to_string(),
+            pos.total_pnl.round_dp(4).to_string(),
+        ])
+        .style(style)
+    });
+    let table = Table::new(rows, [Constraint::Ratio(1, 4); 4])
+        .header(Row::new(["symbol", "qty", "avg", "pnl"]).style(Style::default().add_modifier(Modifier::BOLD)))
+        .block(block.title_bottom(format!(
+            " total {} | fees {} | gross {} ",
+            p.total_pnl.round_dp(4),
+            p.fees.round_dp(4),
+            p.gross_exposure.round_dp(2)
+        )));
+    frame.render_widget(table, area);
+}
+
+// Poll `/positions` của process `serve` / `market-make` đang chạy
+async fn poll_positions(url: String, tx: watch::Sender<Option<PortfolioSnapshot>>, mut shutdown: Shutdown) {
+    let client = net::http_client();
+    let mut interval = tokio::time::interval(POSITIONS_POLL);
+    loop {
+        tokio::select! {
+            _ = shutdown.wait() => break,
+            _ = interval.tick() => {}
+        }
+        let snapshot = match client.get(&url).send().await {
+            Ok(resp) if resp.status().is_success() => resp.json::<PortfolioSnapshot>().await.ok(),
+            Ok(_) => None,
+            Err(e) => {
+                warn!("poll {} lỗi: {}", url, e);
+                None
+            }
+        };
+        let _ = tx.send(snapshot);
+    }
+}
+
+// Trả false khi người dùng thoát
+fn handle_events(state: &mut TuiState) -> std::io::Result<bool> {
+    while event::poll(Duration::ZERO)? {
+        let Event::Key(key) = event::read()? else { continue };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+            KeyCode::Tab | KeyCode::Right | KeyCode::Down => state.next_symbol(),
+            KeyCode::BackTab | KeyCode::Left | KeyCode::Up => state.prev_symbol(),
+            _ => {}
+        }
+    }
+    Ok(true)
+}
+
+fn drain<T: Clone>(rx: &mut broadcast::Receiver<T>, mut f: impl FnMut(T)) {
+    loop {
+        match rx.try_recv() {
+            Ok(v) => f(v),
+            Err(broadcast::error::TryRecvError::Lagged(_)) => continue,
+            Err(_) => break,
+        }
+    }
+}
+
+async fn event_loop(
+    terminal: &mut DefaultTerminal,
+    hub: &MarketHub,
+    state: &mut TuiState,
+    positions: watch::Receiver<Option<PortfolioSnapshot>>,
+) -> Result<(), Box<dyn Error>> {
+    let mut trades = hub.subscribe_trades();
+    let mut signals = hub.subscribe_signals();
+    let mut interval = tokio::time::interval(TICK);
+    loop {
+        interval.tick().await;
+        drain(&mut trades, |t| state.on_trade(t));
+        drain(&mut signals, |s| state.on_signal(s));
+        state.on_portfolio(positions.borrow().clone());
+        state.sample(hub, Utc::now());
+        terminal.draw(|frame| draw(frame, state, hub))?;
+        if !handle_events(state)? {
+            return Ok(());
+        }
+    }
+}
+
+// `api` = base URL của HTTP API (vd. http://127.0.0.1:8080) để lấy positions
+pub async fn run(config: &AppConfig, api: Option<String>) -> Result<(), Box<dyn Error>> {
+    let mut sup = Supervisor::new();
+    let hub = start_hub(config, &mut sup);
+    let (positions_tx, positions_rx) = watch::channel(None);
+    if let Some(api) = api {
+        let url = format!("{}/positions", api.trim_end_matches('/'));
+        sup.spawn_graceful("tui positions", move |shutdown| poll_positions(url, positions_tx, shutdown));
+    }
+
+    let mut state = TuiState::new(&hub, config.depth);
+    let mut terminal = ratatui::try_init()?;
+    let result = event_loop(&mut terminal, &hub, &mut state, positions_rx).await;
+    ratatui::restore();
+    sup.shutdown(DEFAULT_SHUTDOWN_TIMEOUT).await;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::orderbook::{SharedOrderbook, Side};
+    use crate::replay::ReplayFeed;
+    use crate::ws::OrderbookFeed;
+    use ratatui::{backend::TestBackend, Terminal};
+    use rust_decimal_macros::dec;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_render_dashboard() {
+        let feed = Arc::new(ReplayFeed { symbol: "btcusdt".into(), orderbook: Arc::new(SharedOrderbook::new()) });
+        feed.orderbook().update(|ob| {
+            ob.set_level(Side::Bid, dec!(100), dec!(1));
+            ob.set_level(Side::Ask, dec!(100.1), dec!(3));
+            true
+        });
+        let hub = MarketHub::new(vec![feed as Arc<dyn OrderbookFeed>], broadcast::channel(16).0);
+        let mut state = TuiState::new(&hub, 5);
+        state.sample(&hub, Utc::now());
+        state.on_trade(Trade {
+            symbol: "BTCUSDT".into(),
+            trade_id: 1,
+            price: 100.1,
+            qty: 0.5,
+            side: TradeSide::Buy,
+            timestamp: Utc::now(),
+        });
+        state.on_signal(SignalOutput { symbol: "BTCUSDT".into(), timestamp: Utc::now(), values: vec![("ofi_1s".into(), 2.5)] });
+        state.next_symbol();
+        assert_eq!(state.selected, 0);
+
+        let mut terminal = Terminal::new(TestBackend::new(140, 30)).unwrap();
+        terminal.draw(|frame| draw(frame, &state, &hub)).unwrap();
+        let screen: String = terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        for text in ["100.1", "BUY", "ofi_1s", "2.5000", "10.00 bps", "--api"] {
+            assert!(screen.contains(text), "thiếu {:?}", text);
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+use super::{ExecutionVenue, VenueError};
+use crate::config::DEFAULT_ACCOUNT;
+use crate::core::position::Position;
+use crate::rest::{binance::Balance, rate_limit::RateLimitStatus};
+use crate::risk::RiskManager;
+
+// Trạng thái một account cho /accounts
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountSummary {
+    pub account: String,
+    pub venue: String,
+    pub positions: BTreeMap<String, Position>,
+    pub balances: Vec<Balance>,
+    // None với paper
+    pub rate_limit: Option<RateLimitStatus>,
+    // lỗi khi lấy số dư / vị thế (REST lỗi, ...)
+    pub error: Option<String>,
+}
+
+// Venue theo account: mỗi account có OMS, user stream, rate limiter riêng.
+// Strategy lấy venue theo tên của mình qua `[venue.routes]`
+#[derive(Clone, Default)]
+pub struct Accounts {
+    venues: BTreeMap<String, Arc<dyn ExecutionVenue>>,
+    routes: HashMap<String, String>,
+    // kill switch và counter ngày dùng chung cho mọi account
+    risk: RiskManager,
+}
+
+impl Accounts {
+    pub fn new(routes: HashMap<String, String>) -> Self {
+        Self { venues: BTreeMap::new(), routes, risk: RiskManager::default() }
+    }
+
+    pub fn with_risk(mut self, risk: RiskManager) -> Self {
+        self.risk = risk;
+        self
+    }
+
+    pub fn risk(&self) -> &RiskManager {
+        &self.risk
+    }
+
+    pub fn insert(&mut self, account: &str, venue: Arc<dyn ExecutionVenue>) {
+        self.venues.insert(account.to_string(), venue);
+    }
+
+    pub fn get(&self, account: &str) -> Option<&Arc<dyn ExecutionVenue>> {
+        self.venues.get(account)
+    }
+
+    pub fn account_for(&self, strategy: &str) -> &str {
+        self.routes.get(strategy).map_or(DEFAULT_ACCOUNT, String::as_str)
+    }
+
+    pub fn for_strategy(&self, strategy: &str) -> Result<Arc<dyn ExecutionVenue>, VenueError> {
+        let account = self.account_for(strategy);
+        self.get(account).cloned().ok_or_else(|| VenueError::UnknownAccount(account.to_string()))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Arc<dyn ExecutionVenue>)> {
+        self.venues.iter().map(|(name, venue)| (name.as_str(), venue))
+    }
+
+    pub fn len(&self) -> usize {
+        self.venues.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.venues.is_empty()
+    }
+
+    // account lỗi vẫn có trong kết quả, kèm `error`
+    pub async fn summaries(&self) -> Vec<AccountSummary> {
+        let mut out = Vec::with_capacity(self.venues.len());
+        for (account, venue) in &self.venues {
+            let mut summary = AccountSummary {
+                account: account.clone(),
+                venue: venue.name().to_string(),
+                positions: BTreeMap::new(),
+                balances: Vec::new(),
+                rate_limit: venue.rate_limit(),
+                error: None,
+            };
+            match venue.positions().await {
+                Ok(positions) => summary.positions = positions.into_iter().filter(|(_, p)| !p.qty.is_zero()).collect(),
+                Err(e) => summary.error = Some(e.to_string()),
+            }
+            match venue.balances().await {
+                Ok(balances) => summary.balances = balances,
+                Err(e) => summary.error = Some(e.to_string()),
+            }
+            out.push(summary);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        order::OrderSide,
+        orderbook::{OrderbookSnapshot, Side},
+        signal::MarketData,
+    };
+    use crate::risk::OrderIntent;
+    use crate::venue::paper::PaperVenue;
+    use rust_decimal_macros::dec;
+
+    #[tokio::test]
+    async fn test_routing_and_per_account_state() {
+        let main = Arc::new(PaperVenue::default().with_balances(&HashMap::from([("USDT".to_string(), dec!(1000))])));
+        let sub = Arc::new(PaperVenue::default().with_balances(&HashMap::from([("USDT".to_string(), dec!(500))])));
+        let mut accounts = Accounts::new(HashMap::from([("market_maker".to_string(), "sub1".to_string())]));
+        accounts.insert(DEFAULT_ACCOUNT, main.clone());
+        accounts.insert("sub1", sub.clone());
+
+        let mut book = OrderbookSnapshot::new();
+        book.set_level(Side::Bid, dec!(99), dec!(5));
+        book.set_level(Side::Ask, dec!(100), dec!(5));
+        let data = MarketData::Orderbook { symbol: "BTCUSDT".into(), snap: Arc::new(book) };
+        main.on_market_data(&data);
+        sub.on_market_data(&data);
+
+        // market maker đặt lệnh qua sub1, account mặc định không bị ảnh hưởng
+        let venue = accounts.for_strategy("market_maker").unwrap();
+        venue.place_order(&OrderIntent::market("BTCUSDT", OrderSide::Buy, dec!(1))).await.unwrap();
+
+        let summaries = accounts.summaries().await;
+        assert_eq!(summaries.iter().map(|s| s.account.as_str()).collect::<Vec<_>>(), vec![DEFAULT_ACCOUNT, "sub1"]);
+        assert!(summaries[0].positions.is_empty());
+        assert_eq!(summaries[1].positions["BTCUSDT"].qty, dec!(1));
+        assert!(summaries[1].balances.iter().any(|b| b.asset == "USDT" && b.free < dec!(500)));
+        assert!(summaries.iter().all(|s| s.rate_limit.is_none()));
+
+        assert!(Arc::ptr_eq(&accounts.for_strategy("arb").unwrap(), accounts.get(DEFAULT_ACCOUNT).unwrap()));
+        let missing = Accounts::new(HashMap::from([("arb".to_string(), "sub9".to_string())]));
+        assert!(matches!(missing.for_strategy("arb"), Err(VenueError::UnknownAccount(a)) if a == "sub9"));
+    }
+}
@@ -0,0 +1,350 @@
+use arc_swap::ArcSwap;
+use async_trait::async_trait;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex as StdMutex},
+};
+use tokio::sync::Mutex;
+
+use super::{ExecutionVenue, OnOrderId, VenueError, VenueOrder};
+use crate::core::{
+    backpressure::LosslessReceiver,
+    order::{Fill, OrderStatus, OrderType},
+    orderbook::OrderbookSnapshot,
+    position::Position,
+    signal::MarketData,
+    symbol::SymbolRegistry,
+};
+use crate::oms::{ManagedOrder, Oms};
+use crate::rest::{
+    binance::{Balance, BinanceMarket, BinanceNetwork, BinanceRestClient, NewOrderRequest},
+    rate_limit::RateLimitStatus,
+    RestError,
+};
+use crate::risk::{self, OrderIntent, RiskManager};
+use crate::ws::binance_user::BinanceUserStream;
+
+// Binance thật (spot hoặc USDⓈ-M theo market của `client`): đặt/huỷ qua REST, trạng thái
+// order và fill lấy từ OMS được user data stream cập nhật. Id của order là clientOrderId.
+// Order được làm tròn theo tick/lot size và kiểm tra min notional, rồi qua
+// pre-trade check (position / open order từ OMS, book gần nhất) trước khi gửi
+#[derive(Debug)]
+pub struct BinanceVenue {
+    client: BinanceRestClient,
+    oms: Arc<Mutex<Oms>>,
+    user: Arc<BinanceUserStream>,
+    symbols: ArcSwap<SymbolRegistry>,
+    risk: Option<RiskManager>,
+    // book gần nhất theo symbol (`on_market_data`), tham chiếu cho price band / notional
+    books: StdMutex<HashMap<String, Arc<OrderbookSnapshot>>>,
+}
+
+impl BinanceVenue {
+    pub fn new(client: BinanceRestClient, oms_prefix: &str) -> Self {
+        let oms = Arc::new(Mutex::new(Oms::new(oms_prefix)));
+        let user = Arc::new(BinanceUserStream::new(client.clone(), oms.clone()));
+        Self { client, oms, user, symbols: ArcSwap::from_pointee(SymbolRegistry::default()), risk: None, books: StdMutex::default() }
+    }
+
+    // pre-trade check trước mỗi order; kill switch bật thì chặn order mới và
+    // huỷ mọi order đang mở trên sàn (chạy trong `start`)
+    pub fn with_risk(mut self, risk: RiskManager) -> Self {
+        self.risk = Some(risk);
+        self
+    }
+
+    // user data stream theo mạng của `client` (testnet / mainnet)
+    pub fn with_network(mut self, network: BinanceNetwork) -> Self {
+        self.user = Arc::new(BinanceUserStream::new(self.client.clone(), self.oms.clone()).with_network(network));
+        self
+    }
+
+    // tải lại exchangeInfo (filter có thể đổi), trả về số symbol
+    pub async fn load_symbols(&self, symbols: &[String]) -> Result<usize, RestError> {
+        let info = self.client.exchange_info(symbols).await?;
+        self.set_symbols(SymbolRegistry::new(info.symbol_infos()));
+        Ok(self.symbols.load().len())
+    }
+
+    pub fn set_symbols(&self, registry: SymbolRegistry) {
+        self.symbols.store(Arc::new(registry));
+    }
+
+    pub fn symbols(&self) -> Arc<SymbolRegistry> {
+        self.symbols.load_full()
+    }
+
+    pub fn oms(&self) -> &Arc<Mutex<Oms>> {
+        &self.oms
+    }
+
+    pub fn user_stream(&self) -> &Arc<BinanceUserStream> {
+        &self.user
+    }
+}
+
+impl From<&ManagedOrder> for VenueOrder {
+    fn from(o: &ManagedOrder) -> Self {
+        Self {
+            id: o.client_order_id.clone(),
+            symbol: o.symbol.clone(),
+            side: o.side,
+            price: o.price,
+            qty: o.qty,
+            filled_qty: o.filled_qty,
+            status: o.status,
+            queue_ahead: o.queue.as_ref().map(|q| q.ahead),
+            time_to_fill: o.time_to_fill(),
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutionVenue for BinanceVenue {
+    fn name(&self) -> &str {
+        match self.client.market() {
+            BinanceMarket::Spot => "binance",
+            BinanceMarket::UsdM => "binance_futures",
+        }
+    }
+
+    async fn place_order_with(&self, intent: &OrderIntent, on_id: &OnOrderId<'_>) -> Result<String, VenueError> {
+        // chưa tải exchangeInfo thì gửi nguyên, để sàn kiểm tra
+        let registry = self.symbols.load();
+        let prepared;
+        let intent = if registry.is_empty() {
+            intent
+        } else {
+            prepared = registry.prepare(intent, None)?;
+            &prepared
+        };
+        let order_type = if intent.price.is_some() { OrderType::Limit } else { OrderType::Market };
+        let mut oms = self.oms.lock().await;
+        // check và tạo order cùng một lần lock để order song song không vượt limit
+        if let Some(risk) = &self.risk {
+            let book = self.books.lock().unwrap_or_else(|e| e.into_inner()).get(&intent.symbol).cloned();
+            risk.check_order(&oms, intent, book.as_deref())?;
+        }
+        let client_order_id = oms.create_order(&intent.symbol, intent.side, order_type, intent.price, intent.qty);
+        drop(oms);
+        // executionReport có thể về trước REST ack
+        on_id(&client_order_id);
+        let req = match intent.price {
+            Some(price) => NewOrderRequest::limit(&intent.symbol, intent.side, price, intent.qty),
+            None => NewOrderRequest::market(&intent.symbol, intent.side, intent.qty),
+        }
+        .with_client_order_id(&client_order_id);
+
+        match self.client.place_order(&req).await {
+            Ok(resp) => {
+                self.oms.lock().await.on_ack(&client_order_id, resp.order_id)?;
+                Ok(client_order_id)
+            }
+            Err(e) => {
+                self.oms.lock().await.on_reject(&client_order_id, &e.to_string())?;
+                Err(e.into())
+            }
+        }
+    }
+
+    async fn cancel(&self, symbol: &str, id: &str) -> Result<(), VenueError> {
+        self.oms.lock().await.request_cancel(id)?;
+        if let Err(e) = self.client.cancel_by_client_id(symbol, id).await {
+            self.oms.lock().await.on_cancel_rejected(id)?;
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    // order đang chờ huỷ coi như đã đóng với strategy, trạng thái cuối đến từ user stream
+    async fn open_orders(&self, symbol: &str) -> Result<Vec<VenueOrder>, VenueError> {
+        Ok(self
+            .oms
+            .lock()
+            .await
+            .open_orders(symbol)
+            .into_iter()
+            .filter(|o| o.status != OrderStatus::PendingCancel)
+            .map(VenueOrder::from)
+            .collect())
+    }
+
+    async fn position(&self, symbol: &str) -> Result<Position, VenueError> {
+        Ok(self.oms.lock().await.position(symbol))
+    }
+
+    async fn positions(&self) -> Result<HashMap<String, Position>, VenueError> {
+        Ok(self.oms.lock().await.positions().clone())
+    }
+
+    // số dư từ user stream, chưa có snapshot thì hỏi REST
+    async fn balances(&self) -> Result<Vec<Balance>, VenueError> {
+        let cached: Vec<Balance> = self
+            .user
+            .balances
+            .lock()
+            .await
+            .values()
+            .filter(|b| !b.total().is_zero())
+            .cloned()
+            .collect();
+        let mut balances = if cached.is_empty() { self.client.balances().await? } else { cached };
+        balances.sort_by(|a, b| a.asset.cmp(&b.asset));
+        Ok(balances)
+    }
+
+    fn fills(&self) -> LosslessReceiver<Fill> {
+        self.user.subscribe_fills()
+    }
+
+    async fn start(self: Arc<Self>) {
+        let Some(risk) = &self.risk else { return self.user.clone().start().await };
+        tokio::select! {
+            _ = self.user.clone().start() => {}
+            _ = risk::cancel_on_kill(risk.kill_switch(), self.client.clone(), self.oms.clone()) => {}
+        }
+    }
+
+    fn rate_limit(&self) -> Option<RateLimitStatus> {
+        Some(self.client.rate_limit_status())
+    }
+
+    fn oms_handle(&self) -> Option<Arc<Mutex<Oms>>> {
+        Some(self.oms.clone())
+    }
+
+    // OMS đang bận (đặt / huỷ lệnh) thì bỏ qua một update, queue tự khớp lại ở book sau
+    fn on_market_data(&self, data: &MarketData) -> Vec<Fill> {
+        if let MarketData::Orderbook { symbol, snap } = data {
+            self.books.lock().unwrap_or_else(|e| e.into_inner()).insert(symbol.to_uppercase(), snap.clone());
+        }
+        if let Ok(mut oms) = self.oms.try_lock() {
+            oms.on_market_data(data);
+        }
+        Vec::new()
+    }
+
+    async fn cancel_all(&self, symbol: &str) -> Result<(), VenueError> {
+        let ids: Vec<String> = self
+            .oms
+            .lock()
+            .await
+            .open_orders(symbol)
+            .iter()
+            .map(|o| o.client_order_id.clone())
+            .collect();
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let mut oms = self.oms.lock().await;
+        for id in &ids {
+            oms.request_cancel(id)?;
+        }
+        drop(oms);
+        // một request cho cả symbol thay vì huỷ từng order
+        if let Err(e) = self.client.cancel_open_orders(symbol).await {
+            let mut oms = self.oms.lock().await;
+            for id in &ids {
+                let _ = oms.on_cancel_rejected(id);
+            }
+            return Err(e.into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::order::OrderSide;
+    use crate::core::symbol::{SymbolError, SymbolInfo};
+    use crate::core::orderbook::Side;
+    use crate::rest::binance::BinanceCredentials;
+    use crate::risk::{RiskError, RiskLimits};
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    fn venue() -> BinanceVenue {
+        // cổng đóng: request lỗi ngay, không ra mạng
+        let client = BinanceRestClient::new(Some(BinanceCredentials::new("key", "secret"))).with_base_url("http://127.0.0.1:9");
+        BinanceVenue::new(client, "test")
+    }
+
+    #[tokio::test]
+    async fn test_rest_failure_rejects_order() {
+        let venue = venue();
+        let intent = OrderIntent::limit("btcusdt", OrderSide::Buy, dec!(100), dec!(1));
+        assert!(matches!(venue.place_order(&intent).await, Err(VenueError::Rest(_))));
+        assert!(venue.open_orders("btcusdt").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_symbol_filters_checked_before_submit() {
+        let venue = venue();
+        venue.set_symbols(SymbolRegistry::new([SymbolInfo {
+            symbol: "BTCUSDT".into(),
+            base_asset: "BTC".into(),
+            quote_asset: "USDT".into(),
+            trading: true,
+            tick_size: dec!(0.01),
+            min_price: dec!(0.01),
+            max_price: Decimal::ZERO,
+            step_size: dec!(0.001),
+            min_qty: dec!(0.001),
+            max_qty: Decimal::ZERO,
+            min_notional: dec!(5),
+        }]));
+
+        // bị chặn local, không tạo order trong OMS
+        let intent = OrderIntent::limit("btcusdt", OrderSide::Buy, dec!(100), dec!(0.01));
+        let err = venue.place_order(&intent).await.unwrap_err();
+        assert!(matches!(err, VenueError::Symbol(SymbolError::MinNotional { .. })));
+        assert!(venue.oms().lock().await.open_symbols().is_empty());
+
+        // qua filter (sau khi làm tròn) thì mới tới REST
+        let intent = OrderIntent::limit("btcusdt", OrderSide::Buy, dec!(100.009), dec!(0.0509));
+        assert!(matches!(venue.place_order(&intent).await, Err(VenueError::Rest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_open_orders_hide_pending_cancel() {
+        let venue = venue();
+        let mut oms = venue.oms().lock().await;
+        let a = oms.create_order("btcusdt", OrderSide::Buy, OrderType::Limit, Some(dec!(100)), dec!(1));
+        let b = oms.create_order("btcusdt", OrderSide::Sell, OrderType::Limit, Some(dec!(101)), dec!(1));
+        oms.on_ack(&a, 1).unwrap();
+        oms.on_ack(&b, 2).unwrap();
+        oms.request_cancel(&b).unwrap();
+        drop(oms);
+
+        let open = venue.open_orders("BTCUSDT").await.unwrap();
+        assert_eq!(open.iter().map(|o| o.id.as_str()).collect::<Vec<_>>(), vec![a.as_str()]);
+
+        // huỷ lỗi -> order quay lại trạng thái trước đó
+        assert!(venue.cancel("btcusdt", &a).await.is_err());
+        assert_eq!(venue.open_orders("btcusdt").await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_risk_checked_before_submit() {
+        let risk = RiskManager::new(RiskLimits { price_band_bps: Some(dec!(100)), ..Default::default() });
+        let venue = venue().with_risk(risk.clone());
+        let intent = OrderIntent::limit("btcusdt", OrderSide::Buy, dec!(100), dec!(1));
+        // chưa có book thì không tính được band
+        assert!(matches!(venue.place_order(&intent).await, Err(VenueError::Risk(RiskError::NoReferencePrice(_)))));
+
+        let mut ob = OrderbookSnapshot::new();
+        ob.set_level(Side::Bid, dec!(104), dec!(1));
+        ob.set_level(Side::Ask, dec!(106), dec!(1));
+        venue.on_market_data(&MarketData::Orderbook { symbol: "btcusdt".into(), snap: Arc::new(ob) });
+        assert!(matches!(venue.place_order(&intent).await, Err(VenueError::Risk(RiskError::PriceBand { .. }))));
+        // trong band thì tới REST
+        let intent = OrderIntent::limit("btcusdt", OrderSide::Buy, dec!(105), dec!(1));
+        assert!(matches!(venue.place_order(&intent).await, Err(VenueError::Rest(_))));
+
+        risk.kill_switch().trigger("test");
+        assert!(matches!(venue.place_order(&intent).await, Err(VenueError::Risk(RiskError::KillSwitch(_)))));
+        // order bị chặn không vào OMS (order lỗi REST ở trên đã bị reject)
+        assert!(venue.oms().lock().await.open_symbols().is_empty());
+    }
+}
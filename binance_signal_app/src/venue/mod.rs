@@ -0,0 +1,206 @@
+pub mod accounts;
+pub mod binance;
+pub mod paper;
+
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use std::{collections::HashMap, fmt, sync::Arc};
+use tokio::sync::Mutex;
+
+use crate::core::{
+    backpressure::LosslessReceiver,
+    clock::ClockSync,
+    order::{Fill, OrderSide, OrderStatus},
+    position::Position,
+    signal::MarketData,
+    symbol::{SymbolError, SymbolRegistry},
+};
+use crate::config::{AppConfig, VenueKind, DEFAULT_ACCOUNT};
+use crate::oms::{Oms, OmsError};
+use crate::rest::{
+    binance::{Balance, BinanceMarket, BinanceRestClient},
+    rate_limit::RateLimitStatus,
+    RestError,
+};
+use crate::risk::{OrderIntent, RiskError, RiskManager};
+use crate::sim::SimError;
+
+use self::{accounts::Accounts, binance::BinanceVenue, paper::PaperVenue};
+
+#[derive(Debug)]
+pub enum VenueError {
+    Sim(SimError),
+    Rest(RestError),
+    Oms(OmsError),
+    // order không qua được filter của sàn (tick/lot size, min notional)
+    Symbol(SymbolError),
+    // pre-trade check hoặc kill switch chặn, order không được gửi
+    Risk(RiskError),
+    UnknownOrder(String),
+    // `[venue.routes]` trỏ tới account không có
+    UnknownAccount(String),
+}
+
+impl fmt::Display for VenueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VenueError::Sim(e) => write!(f, "paper exchange: {}", e),
+            VenueError::Rest(e) => write!(f, "rest: {}", e),
+            VenueError::Oms(e) => write!(f, "oms: {}", e),
+            VenueError::Symbol(e) => write!(f, "symbol filter: {}", e),
+            VenueError::Risk(e) => write!(f, "risk: {}", e),
+            VenueError::UnknownOrder(id) => write!(f, "unknown order {}", id),
+            VenueError::UnknownAccount(name) => write!(f, "unknown account {}", name),
+        }
+    }
+}
+
+impl std::error::Error for VenueError {}
+
+impl From<SimError> for VenueError {
+    fn from(e: SimError) -> Self {
+        VenueError::Sim(e)
+    }
+}
+
+impl From<RestError> for VenueError {
+    fn from(e: RestError) -> Self {
+        VenueError::Rest(e)
+    }
+}
+
+impl From<OmsError> for VenueError {
+    fn from(e: OmsError) -> Self {
+        VenueError::Oms(e)
+    }
+}
+
+impl From<SymbolError> for VenueError {
+    fn from(e: SymbolError) -> Self {
+        VenueError::Symbol(e)
+    }
+}
+
+impl From<RiskError> for VenueError {
+    fn from(e: RiskError) -> Self {
+        VenueError::Risk(e)
+    }
+}
+
+// Order đang mở trên venue, id là chuỗi để dùng chung cho paper (số) và live (clientOrderId)
+#[derive(Debug, Clone, PartialEq)]
+pub struct VenueOrder {
+    pub id: String,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub price: Option<Decimal>,
+    pub qty: Decimal,
+    pub filled_qty: Decimal,
+    pub status: OrderStatus,
+    // ước lượng khối lượng đứng trước và thời gian tới khi khớp hết, None khi chưa đủ data
+    pub queue_ahead: Option<Decimal>,
+    pub time_to_fill: Option<chrono::Duration>,
+}
+
+impl VenueOrder {
+    pub fn remaining(&self) -> Decimal {
+        self.qty - self.filled_qty
+    }
+}
+
+// nhận id order từ `ExecutionVenue::place_order_with`
+pub type OnOrderId<'f> = dyn for<'a> Fn(&'a str) + Sync + 'f;
+
+// Nơi strategy gửi order, strategy không cần biết là sàn giả lập hay sàn thật
+#[async_trait]
+pub trait ExecutionVenue: Send + Sync {
+    fn name(&self) -> &str;
+
+    // trả về id của order, fill đến sau qua `on_market_data` (paper) hoặc user stream (live)
+    async fn place_order(&self, intent: &OrderIntent) -> Result<String, VenueError> {
+        self.place_order_with(intent, &|_| {}).await
+    }
+    // `on_id` chạy ngay khi order có id, trước khi gửi lên sàn (live) hay publish fill khớp
+    // ngay (paper): tag attribution đăng ký ở đây không bị fill về trước
+    async fn place_order_with(&self, intent: &OrderIntent, on_id: &OnOrderId<'_>) -> Result<String, VenueError>;
+    async fn cancel(&self, symbol: &str, id: &str) -> Result<(), VenueError>;
+    async fn open_orders(&self, symbol: &str) -> Result<Vec<VenueOrder>, VenueError>;
+    async fn position(&self, symbol: &str) -> Result<Position, VenueError>;
+    async fn positions(&self) -> Result<HashMap<String, Position>, VenueError>;
+    async fn balances(&self) -> Result<Vec<Balance>, VenueError>;
+    // mọi fill của venue, kể cả order không do strategy này đặt
+    fn fills(&self) -> LosslessReceiver<Fill>;
+
+    // task nền của venue (user data stream với live), paper không cần
+    async fn start(self: Arc<Self>) {}
+
+    // budget REST còn lại của account, None với paper
+    fn rate_limit(&self) -> Option<RateLimitStatus> {
+        None
+    }
+
+    // OMS của venue để lưu / khôi phục order, None với paper
+    fn oms_handle(&self) -> Option<Arc<Mutex<Oms>>> {
+        None
+    }
+
+    // Sàn giả lập khớp lệnh theo market data, sàn thật chỉ dùng để ước lượng queue
+    fn on_market_data(&self, _data: &MarketData) -> Vec<Fill> {
+        Vec::new()
+    }
+
+    async fn cancel_all(&self, symbol: &str) -> Result<(), VenueError> {
+        for order in self.open_orders(symbol).await? {
+            self.cancel(symbol, &order.id).await?;
+        }
+        Ok(())
+    }
+}
+
+// `[venue] kind = "paper" | "live"`: strategy giữ nguyên, chỉ đổi config.
+// Mỗi account (`default` + `[venue.accounts]`) một venue riêng. Live tải
+// exchangeInfo của các symbol trong config một lần cho mỗi market (spot / USDⓈ-M),
+// dùng chung cho mọi account cùng market.
+// `clock` = giờ sàn cho timestamp request ký (xem `ClockSync`).
+// Mọi account dùng chung một RiskManager từ `[risk]` (`Accounts::risk`)
+pub async fn from_config(config: &AppConfig, clock: Option<ClockSync>) -> Result<Accounts, VenueError> {
+    let settings = &config.venue;
+    let risk = RiskManager::new(config.risk.clone());
+    let mut accounts = Accounts::new(settings.routes.clone()).with_risk(risk.clone());
+    match settings.kind {
+        VenueKind::Paper => {
+            let paper = |balances| Arc::new(PaperVenue::new(config.paper_config()).with_balances(balances).with_risk(risk.clone()));
+            accounts.insert(DEFAULT_ACCOUNT, paper(&settings.paper_balances));
+            for (name, account) in &settings.accounts {
+                let balances = if account.paper_balances.is_empty() { &settings.paper_balances } else { &account.paper_balances };
+                accounts.insert(name, paper(balances));
+            }
+        }
+        VenueKind::Live => {
+            let default = config.binance_credentials.clone().ok_or(RestError::MissingCredentials)?;
+            let mut credentials = vec![(DEFAULT_ACCOUNT, BinanceMarket::Spot, default)];
+            for (name, account) in &settings.accounts {
+                credentials.push((name, account.market, account.credentials().ok_or(RestError::MissingCredentials)?));
+            }
+            let mut symbols: HashMap<BinanceMarket, Arc<SymbolRegistry>> = HashMap::new();
+            for (name, market, credentials) in credentials {
+                // client riêng = rate limiter riêng, giới hạn order của Binance tính theo account
+                let network = &config.binance.network;
+                let mut client = BinanceRestClient::new(Some(credentials)).with_market(market).with_base_url(&market.rest_url(network));
+                if let Some(clock) = &clock {
+                    client = client.with_clock(clock.clone());
+                }
+                let venue = BinanceVenue::new(client, &settings.oms_prefix).with_network(network.clone()).with_risk(risk.clone());
+                match symbols.get(&market) {
+                    Some(registry) => venue.set_symbols(registry.as_ref().clone()),
+                    None => {
+                        venue.load_symbols(&config.venue_symbols(market.into())).await?;
+                        symbols.insert(market, venue.symbols());
+                    }
+                }
+                accounts.insert(name, Arc::new(venue));
+            }
+        }
+    }
+    Ok(accounts)
+}
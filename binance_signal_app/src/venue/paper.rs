@@ -0,0 +1,279 @@
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use super::{ExecutionVenue, OnOrderId, VenueError, VenueOrder};
+use crate::core::{
+    backpressure::{LosslessBus, LosslessReceiver},
+    order::{Fill, OrderSide},
+    position::Position,
+    signal::MarketData,
+};
+use crate::rest::binance::Balance;
+use crate::risk::{OrderIntent, RiskManager};
+use crate::sim::{PaperConfig, PaperExchange, PaperOrder};
+
+const FILL_CHANNEL_CAPACITY: usize = 1024;
+// thứ tự quan trọng: USDT trước để BTCUSDT không bị tách thành BTCU + SDT...
+const QUOTE_ASSETS: &[&str] = &["USDT", "USDC", "FDUSD", "TUSD", "BUSD", "DAI", "EUR", "TRY", "BTC", "ETH", "BNB"];
+
+// BTCUSDT -> (BTC, USDT), None nếu không nhận ra quote
+pub fn split_symbol(symbol: &str) -> Option<(String, String)> {
+    let symbol = symbol.to_uppercase();
+    QUOTE_ASSETS.iter().find_map(|quote| {
+        let base = symbol.strip_suffix(quote)?;
+        (!base.is_empty()).then(|| (base.to_string(), quote.to_string()))
+    })
+}
+
+// PaperExchange sau một Mutex để dùng qua `&self` từ nhiều task.
+// Số dư giả lập = số dư ban đầu + dòng tiền của các fill (phí tính bằng quote)
+#[derive(Debug)]
+pub struct PaperVenue {
+    exchange: Mutex<PaperExchange>,
+    balances: Mutex<HashMap<String, Decimal>>,
+    fills_tx: LosslessBus<Fill>,
+    risk: Option<RiskManager>,
+}
+
+impl Default for PaperVenue {
+    fn default() -> Self {
+        Self::new(PaperConfig::default())
+    }
+}
+
+impl PaperVenue {
+    pub fn new(config: PaperConfig) -> Self {
+        let fills_tx = LosslessBus::new(FILL_CHANNEL_CAPACITY);
+        Self {
+            exchange: Mutex::new(PaperExchange::new(config)),
+            balances: Mutex::new(HashMap::new()),
+            fills_tx,
+            risk: None,
+        }
+    }
+
+    // pre-trade check trước mỗi order, kill switch huỷ order đang mở (`start`)
+    pub fn with_risk(mut self, risk: RiskManager) -> Self {
+        self.risk = Some(risk);
+        self
+    }
+
+    pub fn with_balances(self, balances: &HashMap<String, Decimal>) -> Self {
+        {
+            let mut current = self.balances.lock().unwrap_or_else(|e| e.into_inner());
+            for (asset, amount) in balances {
+                current.insert(asset.to_uppercase(), *amount);
+            }
+        }
+        self
+    }
+
+    pub fn with_exchange<R>(&self, f: impl FnOnce(&mut PaperExchange) -> R) -> R {
+        f(&mut self.exchange.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    fn publish(&self, fills: &[Fill]) {
+        if fills.is_empty() {
+            return;
+        }
+        let mut balances = self.balances.lock().unwrap_or_else(|e| e.into_inner());
+        for fill in fills {
+            if let Some((base, quote)) = split_symbol(&fill.symbol) {
+                let notional = fill.price * fill.qty;
+                let (base_delta, quote_delta) = match fill.side {
+                    OrderSide::Buy => (fill.qty, -notional - fill.fee),
+                    OrderSide::Sell => (-fill.qty, notional - fill.fee),
+                };
+                *balances.entry(base).or_default() += base_delta;
+                *balances.entry(quote).or_default() += quote_delta;
+            }
+            self.fills_tx.send(fill.clone());
+        }
+    }
+}
+
+fn parse_id(id: &str) -> Result<u64, VenueError> {
+    id.parse().map_err(|_| VenueError::UnknownOrder(id.to_string()))
+}
+
+impl From<&PaperOrder> for VenueOrder {
+    fn from(o: &PaperOrder) -> Self {
+        Self {
+            id: o.id.to_string(),
+            symbol: o.symbol.clone(),
+            side: o.side,
+            price: o.price,
+            qty: o.qty,
+            filled_qty: o.filled_qty,
+            status: o.status,
+            queue_ahead: o.price.map(|_| o.queue_ahead()),
+            time_to_fill: o.time_to_fill(),
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutionVenue for PaperVenue {
+    fn name(&self) -> &str {
+        "paper"
+    }
+
+    async fn place_order_with(&self, intent: &OrderIntent, on_id: &OnOrderId<'_>) -> Result<String, VenueError> {
+        let (id, fills) = self.with_exchange(|ex| -> Result<_, VenueError> {
+            if let Some(risk) = &self.risk {
+                let open = ex.open_orders(&intent.symbol);
+                let pending = open.iter().filter(|o| o.side == intent.side).map(|o| o.remaining()).sum();
+                let book = ex.latest_book(&intent.symbol);
+                risk.check(intent, &ex.position(&intent.symbol), pending, open.len(), book.as_deref())?;
+            }
+            Ok(match intent.price {
+                Some(price) => ex.place_limit(&intent.symbol, intent.side, price, intent.qty),
+                None => ex.place_market(&intent.symbol, intent.side, intent.qty),
+            }?)
+        })?;
+        let id = id.to_string();
+        on_id(&id);
+        self.publish(&fills);
+        Ok(id)
+    }
+
+    async fn cancel(&self, _symbol: &str, id: &str) -> Result<(), VenueError> {
+        let id = parse_id(id)?;
+        self.with_exchange(|ex| ex.cancel(id))?;
+        Ok(())
+    }
+
+    async fn open_orders(&self, symbol: &str) -> Result<Vec<VenueOrder>, VenueError> {
+        Ok(self.with_exchange(|ex| ex.open_orders(symbol).into_iter().map(VenueOrder::from).collect()))
+    }
+
+    async fn position(&self, symbol: &str) -> Result<Position, VenueError> {
+        Ok(self.with_exchange(|ex| ex.position(symbol)))
+    }
+
+    async fn positions(&self) -> Result<HashMap<String, Position>, VenueError> {
+        Ok(self.with_exchange(|ex| ex.positions().clone()))
+    }
+
+    async fn balances(&self) -> Result<Vec<Balance>, VenueError> {
+        let balances = self.balances.lock().unwrap_or_else(|e| e.into_inner());
+        let mut out: Vec<Balance> = balances
+            .iter()
+            .map(|(asset, free)| Balance { asset: asset.clone(), free: *free, locked: Decimal::ZERO })
+            .collect();
+        out.sort_by(|a, b| a.asset.cmp(&b.asset));
+        Ok(out)
+    }
+
+    fn fills(&self) -> LosslessReceiver<Fill> {
+        self.fills_tx.subscribe()
+    }
+
+    // kill switch bật thì huỷ mọi order đang mở, như venue live
+    async fn start(self: Arc<Self>) {
+        let Some(risk) = &self.risk else { return };
+        let mut rx = risk.kill_switch().watch();
+        while rx.changed().await.is_ok() {
+            if rx.borrow_and_update().is_none() {
+                continue;
+            }
+            self.with_exchange(|ex| {
+                let ids: Vec<_> = ex.orders().iter().filter(|o| o.is_open()).map(|o| o.id).collect();
+                for id in ids {
+                    let _ = ex.cancel(id);
+                }
+            });
+        }
+    }
+
+    fn on_market_data(&self, data: &MarketData) -> Vec<Fill> {
+        let fills = self.with_exchange(|ex| ex.on_market_data(data));
+        self.publish(&fills);
+        fills
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        order::{OrderSide, OrderStatus},
+        orderbook::{OrderbookSnapshot, Side},
+    };
+    use crate::risk::{RiskError, RiskLimits};
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_split_symbol() {
+        assert_eq!(split_symbol("btcusdt"), Some(("BTC".into(), "USDT".into())));
+        assert_eq!(split_symbol("ETHBTC"), Some(("ETH".into(), "BTC".into())));
+        assert_eq!(split_symbol("USDT"), None);
+    }
+
+    #[tokio::test]
+    async fn test_paper_venue_round_trip() {
+        let balances = HashMap::from([("usdt".to_string(), dec!(1000))]);
+        let venue = PaperVenue::new(PaperConfig::default()).with_balances(&balances);
+        let mut fills = venue.fills();
+        let mut ob = OrderbookSnapshot::new();
+        ob.set_level(Side::Bid, dec!(100), dec!(1));
+        ob.set_level(Side::Ask, dec!(101), dec!(1));
+        venue.on_market_data(&MarketData::Orderbook { symbol: "BTCUSDT".into(), snap: Arc::new(ob) });
+
+        let id = venue.place_order(&OrderIntent::limit("btcusdt", OrderSide::Buy, dec!(99), dec!(1))).await.unwrap();
+        let open = venue.open_orders("btcusdt").await.unwrap();
+        assert_eq!((open.len(), open[0].id.as_str(), open[0].status), (1, id.as_str(), OrderStatus::New));
+
+        venue.cancel_all("btcusdt").await.unwrap();
+        assert!(venue.open_orders("btcusdt").await.unwrap().is_empty());
+        assert!(venue.cancel("btcusdt", "abc").await.is_err());
+
+        venue.place_order(&OrderIntent::market("btcusdt", OrderSide::Buy, dec!(0.5))).await.unwrap();
+        assert_eq!(venue.position("btcusdt").await.unwrap().qty, dec!(0.5));
+        assert_eq!(venue.positions().await.unwrap()["BTCUSDT"].qty, dec!(0.5));
+        assert_eq!(fills.try_recv().unwrap().price, dec!(101));
+
+        // 0.5 @ 101 + phí 10bps
+        let balances = venue.balances().await.unwrap();
+        assert_eq!(
+            balances.iter().map(|b| (b.asset.as_str(), b.free)).collect::<Vec<_>>(),
+            vec![("BTC", dec!(0.5)), ("USDT", dec!(949.4495))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_kill_switch_blocks_place_order() {
+        let risk = RiskManager::new(RiskLimits { max_position: Some(dec!(1)), ..Default::default() });
+        let venue = Arc::new(PaperVenue::default().with_risk(risk.clone()));
+        let mut ob = OrderbookSnapshot::new();
+        ob.set_level(Side::Bid, dec!(100), dec!(5));
+        ob.set_level(Side::Ask, dec!(101), dec!(5));
+        venue.on_market_data(&MarketData::Orderbook { symbol: "BTCUSDT".into(), snap: Arc::new(ob) });
+
+        let over = OrderIntent::limit("btcusdt", OrderSide::Buy, dec!(99), dec!(2));
+        assert!(matches!(venue.place_order(&over).await, Err(VenueError::Risk(RiskError::MaxPosition { .. }))));
+        venue.place_order(&OrderIntent::limit("btcusdt", OrderSide::Buy, dec!(99), dec!(1))).await.unwrap();
+
+        let watcher = tokio::spawn(venue.clone().start());
+        tokio::task::yield_now().await;
+        risk.kill_switch().trigger("test");
+        let order = OrderIntent::market("btcusdt", OrderSide::Sell, dec!(0.5));
+        assert_eq!(
+            venue.place_order(&order).await.unwrap_err().to_string(),
+            "risk: trading halted by kill switch: test"
+        );
+        // order đang mở bị huỷ
+        for _ in 0..100 {
+            if venue.open_orders("btcusdt").await.unwrap().is_empty() {
+                break;
+            }
+            tokio::task::yield_now().await;
+        }
+        assert!(venue.open_orders("btcusdt").await.unwrap().is_empty());
+        watcher.abort();
+    }
+}
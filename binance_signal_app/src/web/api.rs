@@ -0,0 +1,333 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::core::{
+    analytics::PriceBucket,
+    backpressure::DropStats,
+    integrity::IntegrityStats,
+    orderbook::OrderbookSnapshot,
+    pipeline::{self, StageLatency, BUCKETS_US},
+};
+use crate::hub::MarketHub;
+use crate::portfolio::{AttributionReport, PortfolioSnapshot, SharedPortfolio};
+use crate::venue::accounts::{AccountSummary, Accounts};
+use crate::ws::{
+    deflate::{self, CompressionSnapshot},
+    OrderbookFeed,
+};
+
+// book không update quá lâu thì /health báo degraded
+const DEFAULT_STALE_AFTER_SECS: i64 = 30;
+const DEFAULT_DEPTH: usize = 20;
+
+#[derive(Clone)]
+pub struct ApiState {
+    hub: MarketHub,
+    portfolio: Option<SharedPortfolio>,
+    accounts: Option<Accounts>,
+    started_at: DateTime<Utc>,
+    stale_after: Duration,
+}
+
+impl ApiState {
+    pub fn new(hub: MarketHub) -> Self {
+        Self { hub, portfolio: None, accounts: None, started_at: Utc::now(), stale_after: Duration::seconds(DEFAULT_STALE_AFTER_SECS) }
+    }
+
+    // không có portfolio thì /positions trả 404
+    pub fn with_portfolio(mut self, portfolio: SharedPortfolio) -> Self {
+        self.portfolio = Some(portfolio);
+        self
+    }
+
+    // không có thì /accounts trả 404
+    pub fn with_accounts(mut self, accounts: Accounts) -> Self {
+        self.accounts = Some(accounts);
+        self
+    }
+
+    pub fn with_stale_after(mut self, stale_after: Duration) -> Self {
+        self.stale_after = stale_after;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiError {
+    #[serde(skip)]
+    status: StatusCode,
+    error: String,
+}
+
+impl ApiError {
+    fn not_found(message: impl Into<String>) -> Self {
+        Self { status: StatusCode::NOT_FOUND, error: message.into() }
+    }
+
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self { status: StatusCode::BAD_REQUEST, error: message.into() }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(self)).into_response()
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct BookQuery {
+    pub exchange: Option<String>,
+    // 0 = toàn bộ book
+    pub depth: Option<usize>,
+    // gom level theo bội số giá cố định, `depth` lúc đó là số bucket
+    pub bucket: Option<Decimal>,
+    // gom level theo bps của mid
+    pub bucket_bps: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BookResponse {
+    pub exchange: String,
+    pub symbol: String,
+    pub timestamp: DateTime<Utc>,
+    pub last_update_id: u64,
+    // [price, qty], bid giá giảm dần
+    pub bids: Vec<(Decimal, Decimal)>,
+    pub asks: Vec<(Decimal, Decimal)>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BestResponse {
+    pub exchange: String,
+    pub symbol: String,
+    pub timestamp: DateTime<Utc>,
+    pub bid: Option<(Decimal, Decimal)>,
+    pub ask: Option<(Decimal, Decimal)>,
+    pub mid: Option<Decimal>,
+    pub microprice: Option<Decimal>,
+    pub spread_bps: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FeedHealth {
+    pub exchange: String,
+    pub symbol: String,
+    // dạng chuẩn "BTC/USDT", None nếu không parse được
+    pub instrument: Option<String>,
+    // None nếu feed không có kết nối (replay)
+    pub connected: Option<bool>,
+    // số lần stale watchdog yêu cầu resubscribe
+    pub resubscribes: Option<u64>,
+    pub age_ms: i64,
+    pub stale: bool,
+    pub latency_p99_ms: Option<f64>,
+    // số lần book vi phạm invariant (crossed, update id lùi, qty 0) và số lần resync do đó
+    pub integrity: IntegrityStats,
+    // book update / level event bị bỏ vì consumer không theo kịp
+    pub updates: DropStats,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthResponse {
+    // "ok" hoặc "degraded"
+    pub status: &'static str,
+    pub uptime_secs: i64,
+    pub feeds: Vec<FeedHealth>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencyResponse {
+    // biên trên (µs) của bucket, `buckets` của mỗi stage dài hơn một phần tử (+inf)
+    pub bucket_bounds_us: &'static [u64],
+    pub stages: Vec<StageLatency>,
+}
+
+// GET /health, /latency, /compression, /orderbook/:symbol?depth&bucket|bucket_bps, /best/:symbol, /positions, /attribution, /accounts
+pub fn router(state: ApiState) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/latency", get(latency))
+        .route("/compression", get(compression))
+        .route("/orderbook/:symbol", get(orderbook))
+        .route("/best/:symbol", get(best))
+        .route("/positions", get(positions))
+        .route("/attribution", get(attribution))
+        .route("/accounts", get(accounts))
+        .with_state(state)
+}
+
+fn find_feed<'a>(state: &'a ApiState, symbol: &str, exchange: Option<&str>) -> Result<&'a Arc<dyn OrderbookFeed>, ApiError> {
+    state.hub.feed(symbol, exchange).ok_or_else(|| ApiError::not_found(format!("no feed for {}", symbol)))
+}
+
+fn book_response(feed: &dyn OrderbookFeed, snap: &OrderbookSnapshot, depth: usize) -> BookResponse {
+    let depth = if depth == 0 { usize::MAX } else { depth };
+    BookResponse {
+        exchange: feed.exchange().to_string(),
+        symbol: feed.symbol().to_uppercase(),
+        timestamp: snap.timestamp,
+        last_update_id: snap.last_update_id,
+        bids: snap.bids.iter().rev().take(depth).map(|(p, q)| (*p, *q)).collect(),
+        asks: snap.asks.iter().take(depth).map(|(p, q)| (*p, *q)).collect(),
+    }
+}
+
+async fn orderbook(
+    State(state): State<ApiState>,
+    Path(symbol): Path<String>,
+    Query(query): Query<BookQuery>,
+) -> Result<Json<BookResponse>, ApiError> {
+    let feed = find_feed(&state, &symbol, query.exchange.as_deref())?;
+    let depth = query.depth.unwrap_or(DEFAULT_DEPTH);
+    let snap = feed.snapshot();
+    let bucket = match (query.bucket, query.bucket_bps) {
+        (Some(step), None) => PriceBucket::Step(step),
+        (None, Some(bps)) => PriceBucket::Bps(bps),
+        (None, None) => return Ok(Json(book_response(feed.as_ref(), &snap, depth))),
+        (Some(_), Some(_)) => return Err(ApiError::bad_request("use either bucket or bucket_bps")),
+    };
+    let ladder = snap.bucketed(bucket, depth).ok_or_else(|| ApiError::bad_request("bucket must be > 0 on a non-empty book"))?;
+    let mut resp = book_response(feed.as_ref(), &snap, 0);
+    (resp.bids, resp.asks) = (ladder.bids, ladder.asks);
+    Ok(Json(resp))
+}
+
+async fn best(
+    State(state): State<ApiState>,
+    Path(symbol): Path<String>,
+    Query(query): Query<BookQuery>,
+) -> Result<Json<BestResponse>, ApiError> {
+    let feed = find_feed(&state, &symbol, query.exchange.as_deref())?;
+    let snap = feed.snapshot();
+    Ok(Json(BestResponse {
+        exchange: feed.exchange().to_string(),
+        symbol: feed.symbol().to_uppercase(),
+        timestamp: snap.timestamp,
+        bid: snap.best_bid(),
+        ask: snap.best_ask(),
+        mid: snap.mid_price().map(|m| m.normalize()),
+        microprice: snap.microprice().map(|m| m.normalize()),
+        spread_bps: snap.spread_bps().map(|s| s.round_dp(4)),
+    }))
+}
+
+async fn positions(State(state): State<ApiState>) -> Result<Json<PortfolioSnapshot>, ApiError> {
+    let portfolio = state.portfolio.as_ref().ok_or_else(|| ApiError::not_found("portfolio not enabled"))?;
+    Ok(Json(portfolio.snapshot()))
+}
+
+// PnL theo strategy / trigger / symbol
+async fn attribution(State(state): State<ApiState>) -> Result<Json<AttributionReport>, ApiError> {
+    let portfolio = state.portfolio.as_ref().ok_or_else(|| ApiError::not_found("portfolio not enabled"))?;
+    Ok(Json(portfolio.attribution()))
+}
+
+// số dư, vị thế, rate limit theo account
+async fn accounts(State(state): State<ApiState>) -> Result<Json<Vec<AccountSummary>>, ApiError> {
+    let accounts = state.accounts.as_ref().ok_or_else(|| ApiError::not_found("accounts not enabled"))?;
+    Ok(Json(accounts.summaries().await))
+}
+
+// 503 khi có feed stale hoặc mất kết nối để load balancer / monitor bắt được
+async fn health(State(state): State<ApiState>) -> (StatusCode, Json<HealthResponse>) {
+    let feeds: Vec<FeedHealth> = state
+        .hub
+        .feeds()
+        .iter()
+        .map(|feed| {
+            let snap = feed.snapshot();
+            FeedHealth {
+                exchange: feed.exchange().to_string(),
+                symbol: feed.symbol().to_uppercase(),
+                instrument: feed.instrument().map(|i| i.canonical()),
+                connected: feed.connection().map(|c| c.state().is_connected()),
+                resubscribes: feed.connection().map(|c| c.resubscribes()),
+                age_ms: snap.age().num_milliseconds(),
+                stale: snap.is_stale(state.stale_after),
+                latency_p99_ms: feed.latency().map(|l| l.p99_ms),
+                integrity: feed.orderbook().integrity_stats(),
+                updates: feed.orderbook().drop_stats(),
+            }
+        })
+        .collect();
+    let healthy = feeds.iter().all(|f| !f.stale && f.connected != Some(false));
+    let status = if healthy { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    let body = HealthResponse {
+        status: if healthy { "ok" } else { "degraded" },
+        uptime_secs: (Utc::now() - state.started_at).num_seconds(),
+        feeds,
+    };
+    (status, Json(body))
+}
+
+// histogram latency từ lúc nhận message tới từng stage của pipeline
+async fn latency() -> Json<LatencyResponse> {
+    Json(LatencyResponse { bucket_bounds_us: &BUCKETS_US, stages: pipeline::pipeline().snapshot() })
+}
+
+// byte WS theo sàn: nén trên dây / sau khi giải nén / không nén
+async fn compression() -> Json<Vec<CompressionSnapshot>> {
+    Json(deflate::snapshot())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::orderbook::{SharedOrderbook, Side};
+    use crate::replay::ReplayFeed;
+    use rust_decimal_macros::dec;
+    use tokio::sync::broadcast;
+
+    async fn get(addr: std::net::SocketAddr, path: &str) -> (u16, serde_json::Value) {
+        let resp = reqwest::get(format!("http://{}{}", addr, path)).await.unwrap();
+        (resp.status().as_u16(), resp.json().await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_http_api() {
+        let feed = Arc::new(ReplayFeed { symbol: "btcusdt".into(), orderbook: Arc::new(SharedOrderbook::new()) });
+        feed.orderbook().update(|ob| {
+            ob.set_level(Side::Bid, dec!(100), dec!(1));
+            ob.set_level(Side::Bid, dec!(99), dec!(2));
+            ob.set_level(Side::Ask, dec!(100.1), dec!(3));
+            true
+        });
+        let hub = MarketHub::new(vec![feed.clone() as Arc<dyn OrderbookFeed>], broadcast::channel(16).0);
+        let state = ApiState::new(hub).with_stale_after(Duration::hours(1));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move { axum::serve(listener, router(state)).await });
+
+        let (code, book) = get(addr, "/orderbook/BTCUSDT?depth=1").await;
+        assert_eq!(code, 200);
+        assert_eq!((book["bids"].as_array().unwrap().len(), book["bids"][0][0].as_str()), (1, Some("100")));
+        let (_, ladder) = get(addr, "/orderbook/btcusdt?bucket=5").await;
+        assert_eq!((ladder["bids"][1][0].as_str(), ladder["bids"][1][1].as_str()), (Some("95"), Some("2")));
+        assert_eq!(get(addr, "/orderbook/btcusdt?bucket=0").await.0, 400);
+
+        let (_, best) = get(addr, "/best/btcusdt").await;
+        assert_eq!((best["mid"].as_str(), best["spread_bps"].as_str()), (Some("100.05"), Some("9.9950")));
+
+        assert_eq!(get(addr, "/best/ethusdt").await.0, 404);
+        assert_eq!(get(addr, "/positions").await.0, 404);
+        assert_eq!(get(addr, "/attribution").await.0, 404);
+        let (code, health) = get(addr, "/health").await;
+        assert_eq!((code, health["status"].as_str(), health["feeds"][0]["connected"].is_null()), (200, Some("ok"), true));
+        assert_eq!(health["feeds"][0]["integrity"]["crossed"].as_u64(), Some(0));
+        let (_, latency) = get(addr, "/latency").await;
+        assert_eq!(latency["stages"][0]["stage"].as_str(), Some("parse"));
+        assert_eq!(latency["stages"][0]["buckets"].as_array().unwrap().len(), BUCKETS_US.len() + 1);
+        server.abort();
+    }
+}
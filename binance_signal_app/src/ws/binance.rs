@@ -1,105 +1,474 @@
-use tokio_tungstenite::{connect_async, tungstenite::Message};
-use futures_util::{StreamExt};
+use tokio_tungstenite::tungstenite::{self, Message};
+use futures_util::{Sink, Stream, StreamExt};
 use serde::Deserialize;
-use std::{collections::{BTreeMap}, time::Duration};
-use tokio::sync::Mutex;
-use chrono::{DateTime, Utc};
+use std::time::{Duration, Instant};
+use chrono::Utc;
 use std::sync::Arc;
+use async_trait::async_trait;
+use tracing::{debug, info, info_span, warn, Instrument};
 
+pub use crate::core::orderbook::OrderbookSnapshot;
+use crate::core::orderbook::{SharedOrderbook, Side};
+use crate::net;
+use crate::rest::binance::BinanceNetwork;
+use super::{
+    heartbeat::{Heartbeat, HeartbeatConfig},
+    reconnect::{BackoffConfig, ConnectionStatus, Reconnector},
+    check_levels, transport,
+    BestBidAsk, FeedError, MalformedCounter, OrderbookFeed, Validate,
+};
+
+// Price / qty mượn thẳng từ text của message (số của Binance không có ký tự escape),
+// không cấp phát String cho từng level
 #[derive(Debug, Clone, Deserialize)]
-struct DepthUpdate {
-    lastUpdateId: Option<u64>,
-    bids: Vec<[String; 2]>,
-    asks: Vec<[String; 2]>,
+pub struct DepthUpdate<'a> {
+    #[serde(rename = "lastUpdateId")]
+    last_update_id: Option<u64>,
+    #[serde(borrow)]
+    bids: Vec<[&'a str; 2]>,
+    #[serde(borrow)]
+    asks: Vec<[&'a str; 2]>,
 }
 
-#[derive(Debug, Clone)]
-pub struct OrderbookSnapshot {
-    pub timestamp: DateTime<Utc>,
-    pub bids: BTreeMap<f64, f64>,
-    pub asks: BTreeMap<f64, f64>,
+impl Validate for DepthUpdate<'_> {
+    fn validate(&self) -> Result<(), FeedError> {
+        check_levels(&self.bids)?;
+        check_levels(&self.asks)
+    }
+}
+
+impl DepthUpdate<'_> {
+    // thay toàn bộ book
+    pub fn apply_to(&self, ob: &mut OrderbookSnapshot) {
+        ob.replace_levels_str(Side::Bid, &self.bids);
+        ob.replace_levels_str(Side::Ask, &self.asks);
+        ob.last_update_id = self.last_update_id.unwrap_or(0);
+    }
+}
+
+// Diff event của stream `<symbol>@depth@100ms`
+#[derive(Debug, Clone, Deserialize)]
+struct DiffDepthEvent<'a> {
+    #[serde(rename = "E", default)]
+    event_time: Option<i64>,
+    #[serde(rename = "U")]
+    first_update_id: u64,
+    #[serde(rename = "u")]
+    final_update_id: u64,
+    #[serde(rename = "b", borrow)]
+    bids: Vec<[&'a str; 2]>,
+    #[serde(rename = "a", borrow)]
+    asks: Vec<[&'a str; 2]>,
+}
+
+impl Validate for DiffDepthEvent<'_> {
+    fn validate(&self) -> Result<(), FeedError> {
+        check_levels(&self.bids)?;
+        check_levels(&self.asks)
+    }
+}
+
+// Top-of-book event của stream `<symbol>@bookTicker`
+#[derive(Debug, Clone, Deserialize)]
+struct BookTickerEvent<'a> {
+    #[serde(rename = "u")]
+    update_id: u64,
+    #[serde(rename = "b")]
+    bid_price: &'a str,
+    #[serde(rename = "B")]
+    bid_qty: &'a str,
+    #[serde(rename = "a")]
+    ask_price: &'a str,
+    #[serde(rename = "A")]
+    ask_qty: &'a str,
+}
+
+impl Validate for BookTickerEvent<'_> {
+    fn validate(&self) -> Result<(), FeedError> {
+        check_levels(&[[self.bid_price, self.bid_qty], [self.ask_price, self.ask_qty]])
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DiffResult {
+    Applied,
+    Stale,
+    Gap,
+    // áp được nhưng book sau update vi phạm invariant (crossed, qty 0, ...)
+    Invalid,
+}
+
+impl DiffResult {
+    fn needs_resync(self) -> bool {
+        matches!(self, DiffResult::Gap | DiffResult::Invalid)
+    }
+}
+
+// depth cho phép của partial stream `@depth{N}`
+pub const PARTIAL_DEPTHS: [usize; 3] = [5, 10, 20];
+// `limit` tối đa của REST `/api/v3/depth`
+pub const MAX_SNAPSHOT_LIMIT: usize = 5000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DepthMode {
+    // `@depth{N}@100ms` partial snapshots
+    Partial,
+    // REST `/api/v3/depth` snapshot + `@depth@100ms` diff events
+    #[serde(alias = "diff")]
+    Full,
+    // `@bookTicker`: chỉ best bid/ask, realtime, nhẹ hơn depth
+    BookTicker,
+}
+
+impl DepthMode {
+    pub fn validate_depth(&self, depth: usize) -> Result<(), String> {
+        match self {
+            DepthMode::Partial if !PARTIAL_DEPTHS.contains(&depth) => {
+                Err(format!("depth {} not supported by binance (allowed: {:?})", depth, PARTIAL_DEPTHS))
+            }
+            DepthMode::Full if depth == 0 || depth > MAX_SNAPSHOT_LIMIT => {
+                Err(format!("binance diff snapshot limit must be in 1..={}, got {}", MAX_SNAPSHOT_LIMIT, depth))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+// Chu kỳ push của depth stream, 1000ms là mặc định của Binance (không có hậu tố)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum UpdateSpeed {
+    #[default]
+    #[serde(rename = "100ms")]
+    Ms100,
+    #[serde(rename = "1000ms")]
+    Ms1000,
+}
+
+impl UpdateSpeed {
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            UpdateSpeed::Ms100 => "@100ms",
+            UpdateSpeed::Ms1000 => "",
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct BinanceOrderbookWS {
     pub symbol: String,
     pub depth_level: usize,
-    pub orderbook: Arc<Mutex<OrderbookSnapshot>>,
+    pub mode: DepthMode,
+    pub speed: UpdateSpeed,
+    pub network: BinanceNetwork,
+    pub orderbook: Arc<SharedOrderbook>,
+    pub backoff: BackoffConfig,
+    pub heartbeat: HeartbeatConfig,
+    pub connection: ConnectionStatus,
+    pub malformed: MalformedCounter,
 }
 
 impl BinanceOrderbookWS {
     pub fn new(symbol: &str, depth_level: usize) -> Self {
+        Self::with_mode(symbol, depth_level, DepthMode::Partial)
+    }
+
+    // depth_level được dùng làm `limit` cho REST snapshot (tối đa 5000)
+    pub fn new_full(symbol: &str, depth_level: usize) -> Self {
+        Self::with_mode(symbol, depth_level, DepthMode::Full)
+    }
+
+    pub fn new_book_ticker(symbol: &str) -> Self {
+        Self::with_mode(symbol, 1, DepthMode::BookTicker)
+    }
+
+    pub fn with_mode(symbol: &str, depth_level: usize, mode: DepthMode) -> Self {
         Self {
             symbol: symbol.to_lowercase(),
             depth_level,
-            orderbook: Arc::new(Mutex::new(OrderbookSnapshot {
-                timestamp: Utc::now(),
-                bids: BTreeMap::new(),
-                asks: BTreeMap::new(),
-            })),
+            mode,
+            speed: UpdateSpeed::default(),
+            network: BinanceNetwork::default(),
+            orderbook: Arc::new(SharedOrderbook::new()),
+            backoff: BackoffConfig::default(),
+            heartbeat: HeartbeatConfig::default(),
+            connection: ConnectionStatus::new(),
+            malformed: MalformedCounter::default(),
+        }
+    }
+
+    pub fn with_speed(mut self, speed: UpdateSpeed) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    // stream + REST snapshot lấy từ testnet / mainnet
+    pub fn with_network(mut self, network: BinanceNetwork) -> Self {
+        self.network = network;
+        self
+    }
+
+    // Như `with_mode` nhưng kiểm tra depth theo giá trị Binance cho phép
+    pub fn try_new(symbol: &str, depth_level: usize, mode: DepthMode, speed: UpdateSpeed) -> Result<Self, String> {
+        mode.validate_depth(depth_level)?;
+        Ok(Self::with_mode(symbol, depth_level, mode).with_speed(speed))
+    }
+
+    fn stream_url(&self) -> String {
+        match self.mode {
+            DepthMode::Partial => format!(
+                "{}/ws/{}@depth{}{}",
+                self.network.ws_url(), self.symbol, self.depth_level, self.speed.suffix()
+            ),
+            DepthMode::Full => format!(
+                "{}/ws/{}@depth{}",
+                self.network.ws_url(), self.symbol, self.speed.suffix()
+            ),
+            DepthMode::BookTicker => format!(
+                "{}/ws/{}@bookTicker",
+                self.network.ws_url(), self.symbol
+            ),
         }
     }
 
     pub async fn start(self: Arc<Self>) {
-        let url = format!(
-            "wss://stream.binance.com:9443/ws/{}@depth{}@100ms",
-            self.symbol, self.depth_level
-        );
+        let span = info_span!("binance_ws", symbol = %self.symbol, mode = ?self.mode);
+        self.run().instrument(span).await
+    }
+
+    async fn run(&self) {
+        let mut reconnect = Reconnector::new(self.backoff.clone(), self.connection.clone());
+
+        // partial stream: lấy REST snapshot trước để có book ngay (thay book nạp từ đĩa nếu có)
+        if self.mode == DepthMode::Partial {
+            match self.fetch_depth_snapshot().await {
+                Ok(body) => match self.malformed.parse_valid::<DepthUpdate>(&self.symbol, &body) {
+                    Some(snapshot) if snapshot.last_update_id > Some(self.orderbook.snapshot().last_update_id) => {
+                        self.process_snapshot(snapshot).await
+                    }
+                    _ => {}
+                },
+                Err(e) => warn!(error = ?e, "REST bootstrap snapshot failed"),
+            }
+        }
 
         loop {
-            match connect_async(&url).await {
+            reconnect.connecting();
+            // dựng lại mỗi lần: endpoint pool có thể đã chuyển sang endpoint khác
+            let url = self.stream_url();
+            match transport::connect("binance", &url).await {
                 Ok((ws_stream, _)) => {
-                    println!("📡 Connected to Binance WS for {}", self.symbol);
-                    let (_, mut read) = ws_stream.split();
+                    reconnect.connected();
+                    info!(%url, "connected");
+                    let (mut write, mut read) = ws_stream.split();
+                    let mut heartbeat = Heartbeat::new(&self.heartbeat).on_resubscribe(&self.connection);
 
-                    while let Some(msg) = read.next().await {
-                        if let Ok(Message::Text(text)) = msg {
-                            if let Ok(data) = serde_json::from_str::<DepthUpdate>(&text) {
-                                self.process_snapshot(data).await;
+                    match self.mode {
+                        DepthMode::Partial => {
+                            while let Some(text) = heartbeat.next_text(&mut read, &mut write).await {
+                                self.handle_partial(&text, Instant::now()).await;
+                            }
+                        }
+                        DepthMode::Full => self.run_full_book(&mut heartbeat, &mut read, &mut write).await,
+                        DepthMode::BookTicker => {
+                            while let Some(text) = heartbeat.next_text(&mut read, &mut write).await {
+                                let received = Instant::now();
+                                if let Some(data) = self.malformed.parse_valid::<BookTickerEvent>(&self.symbol, &text) {
+                                    self.orderbook.mark_parsed(received);
+                                    self.process_book_ticker(data).await;
+                                }
                             }
                         }
                     }
+                    info!("stream closed");
                 }
                 Err(e) => {
-                    println!("⚠️ WS Error: {:?}, reconnecting...", e);
-                    tokio::time::sleep(Duration::from_secs(3)).await;
+                    warn!(error = ?e, "connect failed");
+                    self.network.ws_failed(&url);
                 }
             }
+            if !reconnect.wait().await {
+                return;
+            }
         }
     }
 
-    async fn process_snapshot(&self, data: DepthUpdate) {
-        let mut ob = self.orderbook.lock().await;
-        ob.bids.clear();
-        ob.asks.clear();
+    // Đồng bộ local orderbook theo hướng dẫn của Binance:
+    // buffer diff events trong lúc lấy REST snapshot, bỏ các event có u <= lastUpdateId,
+    // sau đó yêu cầu U <= lastUpdateId + 1 <= u cho mỗi event, nếu có gap thì resync.
+    async fn run_full_book<S, W>(&self, heartbeat: &mut Heartbeat, read: &mut S, write: &mut W)
+    where
+        S: Stream<Item = Result<Message, tungstenite::Error>> + Unpin,
+        W: Sink<Message, Error = tungstenite::Error> + Unpin,
+    {
+        loop {
+            // giữ text thô, event mượn từ text nên parse lúc áp
+            let mut buffer: Vec<String> = Vec::new();
 
-        for [price, qty] in data.bids {
-            let p: f64 = price.parse().unwrap_or(0.0);
-            let q: f64 = qty.parse().unwrap_or(0.0);
-            if q > 0.0 {
-                ob.bids.insert(p, q);
+            let snapshot = {
+                let fetch = self.fetch_depth_snapshot();
+                tokio::pin!(fetch);
+                loop {
+                    tokio::select! {
+                        res = &mut fetch => break res,
+                        text = heartbeat.next_text(read, write) => match text {
+                            Some(text) => buffer.push(text),
+                            None => return,
+                        },
+                    }
+                }
+            };
+
+            let body = match snapshot {
+                Ok(body) => body,
+                Err(e) => {
+                    warn!(error = ?e, "REST snapshot failed, retrying");
+                    tokio::time::sleep(Duration::from_secs(3)).await;
+                    continue;
+                }
+            };
+            let Some(snapshot) = self.malformed.parse_valid::<DepthUpdate>(&self.symbol, &body) else {
+                tokio::time::sleep(Duration::from_secs(3)).await;
+                continue;
+            };
+
+            self.process_snapshot(snapshot).await;
+
+            let mut failed = None;
+            for text in &buffer {
+                let Some(ev) = self.malformed.parse_valid::<DiffDepthEvent>(&self.symbol, text) else {
+                    continue;
+                };
+                let result = self.apply_diff(ev).await;
+                if result.needs_resync() {
+                    failed = Some(result);
+                    break;
+                }
             }
-        }
 
-        for [price, qty] in data.asks {
-            let p: f64 = price.parse().unwrap_or(0.0);
-            let q: f64 = qty.parse().unwrap_or(0.0);
-            if q > 0.0 {
-                ob.asks.insert(p, q);
+            if failed.is_none() {
+                while let Some(text) = heartbeat.next_text(read, write).await {
+                    let received = Instant::now();
+                    if let Some(ev) = self.malformed.parse_valid::<DiffDepthEvent>(&self.symbol, &text) {
+                        self.orderbook.mark_parsed(received);
+                        let result = self.apply_diff(ev).await;
+                        if result.needs_resync() {
+                            failed = Some(result);
+                            break;
+                        }
+                    }
+                }
             }
+
+            let Some(reason) = failed else {
+                // stream đóng -> để start() reconnect
+                return;
+            };
+            warn!(?reason, "local book out of sync, resyncing");
         }
+    }
 
-        ob.timestamp = Utc::now();
+    // body thô, parse (mượn) ở chỗ gọi
+    async fn fetch_depth_snapshot(&self) -> Result<String, reqwest::Error> {
+        let url = format!(
+            "{}/api/v3/depth?symbol={}&limit={}",
+            self.network.rest_url(),
+            self.symbol.to_uppercase(),
+            self.depth_level
+        );
+        net::http_client().get(&url).send().await?.error_for_status()?.text().await
+    }
+
+    // message của partial stream, message có level hỏng bị bỏ và đếm vào `malformed`
+    async fn handle_partial(&self, text: &str, received: Instant) {
+        if let Some(data) = self.malformed.parse_valid::<DepthUpdate>(&self.symbol, text) {
+            self.orderbook.mark_parsed(received);
+            self.process_snapshot(data).await;
+        }
     }
 
-    pub async fn get_best_price(&self) -> Option<((f64, f64), (f64, f64))> {
-        let ob = self.orderbook.lock().await;
-        let best_bid = ob.bids.iter().rev().next().map(|(p, q)| (*p, *q));
-        let best_ask = ob.asks.iter().next().map(|(p, q)| (*p, *q));
-        match (best_bid, best_ask) {
-            (Some(bid), Some(ask)) => Some((bid, ask)),
-            _ => None,
+    pub(crate) async fn process_snapshot(&self, data: DepthUpdate<'_>) {
+        debug!(bids = data.bids.len(), asks = data.asks.len(), last_update_id = ?data.last_update_id, "depth snapshot");
+        self.orderbook
+            .update(|ob| {
+                data.apply_to(ob);
+                ob.timestamp = Utc::now();
+                true
+            });
+    }
+
+    async fn process_book_ticker(&self, data: BookTickerEvent<'_>) {
+        debug!(update_id = data.update_id, "book ticker");
+        self.orderbook
+            .update(|ob| {
+                ob.clear();
+                ob.set_level_str(Side::Bid, data.bid_price, data.bid_qty);
+                ob.set_level_str(Side::Ask, data.ask_price, data.ask_qty);
+                ob.last_update_id = data.update_id;
+                ob.timestamp = Utc::now();
+                true
+            });
+    }
+
+    async fn apply_diff(&self, ev: DiffDepthEvent<'_>) -> DiffResult {
+        if let Some(event_time) = ev.event_time {
+            self.orderbook.record_latency(event_time);
+        }
+        let mut result = DiffResult::Applied;
+        self.orderbook
+            .update(|ob| {
+                if ev.final_update_id <= ob.last_update_id {
+                    result = DiffResult::Stale;
+                    return false;
+                }
+                if ev.first_update_id > ob.last_update_id + 1 {
+                    result = DiffResult::Gap;
+                    return false;
+                }
+
+                for [price, qty] in &ev.bids {
+                    ob.set_level_str(Side::Bid, price, qty);
+                }
+                for [price, qty] in &ev.asks {
+                    ob.set_level_str(Side::Ask, price, qty);
+                }
+
+                ob.last_update_id = ev.final_update_id;
+                ob.timestamp = Utc::now();
+                true
+            });
+        if result == DiffResult::Applied && self.orderbook.integrity().take_resync() {
+            result = DiffResult::Invalid;
         }
+        debug!(first = ev.first_update_id, last = ev.final_update_id, ?result, "depth diff");
+        result
+    }
+
+    pub async fn get_best_price(&self) -> Option<BestBidAsk> {
+        self.orderbook.best_bid_ask()
+    }
+}
+
+#[async_trait]
+impl OrderbookFeed for BinanceOrderbookWS {
+    fn exchange(&self) -> &'static str {
+        "binance"
+    }
+
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    fn orderbook(&self) -> &SharedOrderbook {
+        &self.orderbook
+    }
+
+    fn connection(&self) -> Option<&ConnectionStatus> {
+        Some(&self.connection)
+    }
+
+    async fn start(self: Arc<Self>) {
+        BinanceOrderbookWS::start(self).await
     }
 }
 
@@ -107,7 +476,11 @@ impl BinanceOrderbookWS {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
     use chrono::Utc;
+    use serde_json::json;
+    use crate::testutil::Fuzz;
 
     #[tokio::test]
     async fn test_parse_and_print_orderbook() {
@@ -122,44 +495,209 @@ mod tests {
         let parsed: DepthUpdate = serde_json::from_str(raw).unwrap();
         println!("Parsed bids: {:?}", parsed.bids);
         println!("Parsed asks: {:?}", parsed.asks);
-        assert!(parsed.bids.len() > 0);
-        assert!(parsed.asks.len() > 0);
+        assert!(!parsed.bids.is_empty());
+        assert!(!parsed.asks.is_empty());
     }
 
     #[tokio::test]
     async fn test_process_and_check_best_price() {
         let ob = BinanceOrderbookWS::new("btcusdt", 20);
         let raw = DepthUpdate {
-            lastUpdateId: Some(42),
-            bids: vec![["30100.1".into(), "1.5".into()], ["30099.9".into(), "0.5".into()]],
-            asks: vec![["30101.2".into(), "0.8".into()], ["30102.0".into(), "1.0".into()]],
+            last_update_id: Some(42),
+            bids: vec![["30100.1", "1.5"], ["30099.9", "0.5"]],
+            asks: vec![["30101.2", "0.8"], ["30102.0", "1.0"]],
         };
 
         ob.process_snapshot(raw).await;
 
         if let Some(((bid_price, _), (ask_price, _))) = ob.get_best_price().await {
             println!("🟢 Best Bid: {:.2}, Best Ask: {:.2}", bid_price, ask_price);
-            assert!(bid_price > 0.0);
+            assert!(bid_price > Decimal::ZERO);
             assert!(ask_price > bid_price); // kiểm tra không bị crossed
         } else {
             panic!("❌ Orderbook empty");
         }
     }
 
+    #[tokio::test]
+    async fn test_bad_level_drops_message() {
+        let ws = BinanceOrderbookWS::new("btcusdt", 5);
+        let good = r#"{"lastUpdateId":1,"bids":[["100.0","1.0"]],"asks":[["101.0","2.0"]]}"#;
+        ws.handle_partial(good, Instant::now()).await;
+
+        // một level hỏng: bỏ cả message, book giữ nguyên
+        for bad in [
+            r#"{"lastUpdateId":2,"bids":[["99.0","1.0"],["abc","1.0"]],"asks":[["101.0","2.0"]]}"#,
+            r#"{"lastUpdateId":3,"bids":[["100.0","1.0"]],"asks":[["0","2.0"]]}"#,
+            r#"{"lastUpdateId":4,"bids":[["100.0","-1"]],"asks":[["101.0","2.0"]]}"#,
+        ] {
+            ws.handle_partial(bad, Instant::now()).await;
+        }
+        assert_eq!(ws.malformed.count(), 3);
+        let snap = ws.snapshot();
+        assert_eq!(snap.last_update_id, 1);
+        assert_eq!(snap.bids.len(), 1);
+        assert_eq!(ws.get_best_price().await, Some(((dec!(100.0), dec!(1.0)), (dec!(101.0), dec!(2.0)))));
+
+        let tick = r#"{"u":5,"s":"BTCUSDT","b":"NaN","B":"1","a":"101","A":"1"}"#;
+        assert!(ws.malformed.parse_valid::<BookTickerEvent>("btcusdt", tick).is_none());
+        assert_eq!(ws.malformed.count(), 4);
+    }
+
+    #[test]
+    fn test_stream_url_speed_and_depth_validation() {
+        let ws = BinanceOrderbookWS::try_new("BTCUSDT", 10, DepthMode::Partial, UpdateSpeed::Ms1000).unwrap();
+        assert_eq!(ws.stream_url(), "wss://stream.binance.com:9443/ws/btcusdt@depth10");
+        let ws = BinanceOrderbookWS::try_new("btcusdt", 1000, DepthMode::Full, UpdateSpeed::Ms100).unwrap();
+        assert_eq!(ws.stream_url(), "wss://stream.binance.com:9443/ws/btcusdt@depth@100ms");
+        let ws = ws.with_network(BinanceNetwork::Testnet);
+        assert_eq!(ws.stream_url(), "wss://stream.testnet.binance.vision/ws/btcusdt@depth@100ms");
+        assert!(BinanceOrderbookWS::try_new("btcusdt", 50, DepthMode::Partial, UpdateSpeed::Ms100).is_err());
+        assert!(BinanceOrderbookWS::try_new("btcusdt", 6000, DepthMode::Full, UpdateSpeed::Ms100).is_err());
+    }
+
     #[tokio::test]
     async fn test_orderbook_timestamp_updated() {
         let ob = BinanceOrderbookWS::new("ethusdt", 10);
         let before = Utc::now();
         let raw = DepthUpdate {
-            lastUpdateId: None,
-            bids: vec![["2000.0".into(), "1.0".into()]],
-            asks: vec![["2001.0".into(), "2.0".into()]],
+            last_update_id: None,
+            bids: vec![["2000.0", "1.0"]],
+            asks: vec![["2001.0", "2.0"]],
         };
 
         ob.process_snapshot(raw).await;
 
-        let snap = ob.orderbook.lock().await;
+        let snap = ob.orderbook.snapshot();
         println!("📅 Timestamp: {}", snap.timestamp);
         assert!(snap.timestamp >= before);
     }
+
+    #[tokio::test]
+    async fn test_parse_diff_event() {
+        let raw = r#"
+        {
+            "e": "depthUpdate",
+            "E": 1672515782136,
+            "s": "BNBBTC",
+            "U": 157,
+            "u": 160,
+            "b": [["0.0024", "10"]],
+            "a": [["0.0026", "100"]]
+        }
+        "#;
+
+        let ev: DiffDepthEvent = serde_json::from_str(raw).unwrap();
+        assert_eq!(ev.first_update_id, 157);
+        assert_eq!(ev.final_update_id, 160);
+        assert_eq!(ev.bids.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_book_ticker_backs_best_price() {
+        let ob = BinanceOrderbookWS::new_book_ticker("BNBUSDT");
+        assert!(ob.stream_url().ends_with("/ws/bnbusdt@bookTicker"));
+
+        let raw = r#"{"u":400900217,"s":"BNBUSDT","b":"25.35190000","B":"31.21000000","a":"25.36520000","A":"40.66000000"}"#;
+        ob.process_book_ticker(serde_json::from_str(raw).unwrap()).await;
+
+        assert_eq!(ob.get_best_price().await, Some(((dec!(25.3519), dec!(31.21)), (dec!(25.3652), dec!(40.66)))));
+        assert_eq!(ob.orderbook.snapshot().last_update_id, 400900217);
+    }
+
+    #[tokio::test]
+    async fn test_apply_diff_sequence() {
+        let ob = BinanceOrderbookWS::new_full("btcusdt", 1000);
+        ob.process_snapshot(DepthUpdate {
+            last_update_id: Some(100),
+            bids: vec![["100.0", "1.0"], ["99.0", "2.0"]],
+            asks: vec![["101.0", "1.0"]],
+        })
+        .await;
+
+        // event cũ hơn snapshot -> bỏ qua
+        let stale = DiffDepthEvent {
+            event_time: None,
+            first_update_id: 90,
+            final_update_id: 100,
+            bids: vec![["100.0", "0"]],
+            asks: vec![],
+        };
+        assert_eq!(ob.apply_diff(stale).await, DiffResult::Stale);
+
+        // event đầu tiên chồng lên lastUpdateId + 1
+        let first = DiffDepthEvent {
+            event_time: Some(Utc::now().timestamp_millis()),
+            first_update_id: 95,
+            final_update_id: 105,
+            bids: vec![["100.0", "0"]],
+            asks: vec![["100.5", "3.0"]],
+        };
+        assert_eq!(ob.apply_diff(first).await, DiffResult::Applied);
+
+        let best = ob.get_best_price().await.unwrap();
+        assert_eq!(best, ((dec!(99.0), dec!(2.0)), (dec!(100.5), dec!(3.0))));
+
+        // thiếu update 106..109 -> gap
+        let gap = DiffDepthEvent {
+            event_time: None,
+            first_update_id: 110,
+            final_update_id: 112,
+            bids: vec![],
+            asks: vec![],
+        };
+        assert_eq!(ob.apply_diff(gap).await, DiffResult::Gap);
+        assert_eq!(ob.orderbook.snapshot().last_update_id, 105);
+        assert_eq!(ob.latency().map(|l| l.count), Some(1));
+
+        // bid đè lên best ask -> crossed, phải resync
+        let crossed = DiffDepthEvent {
+            event_time: None,
+            first_update_id: 106,
+            final_update_id: 107,
+            bids: vec![["100.5", "1.0"]],
+            asks: vec![],
+        };
+        assert_eq!(ob.apply_diff(crossed).await, DiffResult::Invalid);
+        assert_eq!(ob.orderbook.integrity_stats().crossed, 1);
+    }
+
+    // Snapshot + chuỗi diff ngẫu nhiên (số xấu, update id lộn xộn, JSON hỏng): không panic,
+    // book không có level giá / qty <= 0, update id không lùi, Applied thì book không crossed
+    #[tokio::test]
+    async fn test_fuzz_depth_messages_keep_book_invariants() {
+        for seed in 0..200 {
+            let mut fuzz = Fuzz::new(seed);
+            let ob = BinanceOrderbookWS::new_full("btcusdt", 1000);
+            let snapshot = json!({ "lastUpdateId": 100, "bids": fuzz.levels(100, 10), "asks": fuzz.levels(105, 10) }).to_string();
+            let snapshot = if fuzz.chance(20) { fuzz.mutate(&snapshot) } else { snapshot };
+            if let Ok(data) = serde_json::from_str::<DepthUpdate>(&snapshot) {
+                ob.process_snapshot(data).await;
+            }
+            for _ in 0..50 {
+                let before = ob.orderbook.snapshot().last_update_id;
+                let first = (before + 1 + fuzz.below(5)).saturating_sub(2);
+                let raw = json!({
+                    "e": "depthUpdate", "U": first, "u": first + fuzz.below(3),
+                    "b": fuzz.levels(100, 5), "a": fuzz.levels(105, 5),
+                })
+                .to_string();
+                let raw = if fuzz.chance(10) { fuzz.mutate(&raw) } else { raw };
+                let Ok(ev) = serde_json::from_str::<DiffDepthEvent>(&raw) else { continue };
+                let result = ob.apply_diff(ev).await;
+
+                let snap = ob.orderbook.snapshot();
+                assert!(snap.last_update_id >= before, "seed {}", seed);
+                let levels = snap.bids.iter().chain(snap.asks.iter());
+                assert!(levels.clone().all(|(p, q)| *p > Decimal::ZERO && *q > Decimal::ZERO), "seed {}: {:?}", seed, snap);
+                if result == DiffResult::Applied {
+                    assert!(snap.best_bid_ask().is_none_or(|((bid, _), (ask, _))| bid < ask), "seed {}", seed);
+                }
+                // feed thật sẽ resync từ đầu
+                if result.needs_resync() {
+                    break;
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,444 @@
+use futures_util::StreamExt;
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use rust_decimal::prelude::ToPrimitive;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::{broadcast, Mutex};
+use chrono::{DateTime, Utc};
+use async_trait::async_trait;
+use tracing::warn;
+
+use crate::core::{
+    orderbook::{SharedOrderbook, Side},
+    perp::{Liquidation, PerpStats},
+    trade::TradeSide,
+};
+use crate::rest::binance_futures::BinanceFuturesRestClient;
+use crate::rest::binance::BinanceNetwork;
+use super::{
+    heartbeat::{Heartbeat, HeartbeatConfig},
+    check_levels, parse_f64, parse_positive,
+    reconnect::{BackoffConfig, ConnectionStatus, Reconnector},
+    transport,
+    BestBidAsk, FeedError, MalformedCounter, OrderbookFeed,
+};
+
+// `data` giữ nguyên dạng text, parse theo `stream` mà không dựng serde_json::Value
+#[derive(Debug, Clone, Deserialize)]
+struct CombinedMessage<'a> {
+    stream: &'a str,
+    #[serde(borrow)]
+    data: &'a RawValue,
+}
+
+// Partial depth event của USDⓈ-M futures (khác spot: có U/u/pu và dùng key b/a)
+#[derive(Debug, Clone, Deserialize)]
+struct FuturesDepthEvent<'a> {
+    #[serde(rename = "E", default)]
+    event_time: Option<i64>,
+    #[serde(rename = "u")]
+    final_update_id: u64,
+    #[serde(rename = "b", borrow)]
+    bids: Vec<[&'a str; 2]>,
+    #[serde(rename = "a", borrow)]
+    asks: Vec<[&'a str; 2]>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct MarkPriceEvent {
+    #[serde(rename = "E")]
+    event_time: i64,
+    #[serde(rename = "p")]
+    mark_price: String,
+    #[serde(rename = "i")]
+    index_price: String,
+    #[serde(rename = "P")]
+    estimated_settle_price: String,
+    #[serde(rename = "r")]
+    funding_rate: String,
+    #[serde(rename = "T")]
+    next_funding_time: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ForceOrderEvent {
+    #[serde(rename = "o")]
+    order: ForceOrder,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ForceOrder {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "S")]
+    side: String,
+    // giá khớp trung bình
+    #[serde(rename = "ap")]
+    avg_price: String,
+    // tổng qty đã khớp
+    #[serde(rename = "z")]
+    filled_qty: String,
+    #[serde(rename = "T")]
+    trade_time: i64,
+}
+
+impl TryFrom<ForceOrder> for Liquidation {
+    type Error = FeedError;
+
+    fn try_from(o: ForceOrder) -> Result<Self, FeedError> {
+        Ok(Self {
+            symbol: o.symbol.to_uppercase(),
+            side: if o.side == "BUY" { TradeSide::Buy } else { TradeSide::Sell },
+            price: parse_positive("liquidation price", &o.avg_price)?,
+            qty: parse_positive("liquidation qty", &o.filled_qty)?,
+            timestamp: DateTime::from_timestamp_millis(o.trade_time).unwrap_or_default(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkPriceInfo {
+    pub mark_price: f64,
+    pub index_price: f64,
+    pub estimated_settle_price: f64,
+    pub funding_rate: f64,
+    pub next_funding_time: DateTime<Utc>,
+    pub event_time: DateTime<Utc>,
+}
+
+impl TryFrom<MarkPriceEvent> for MarkPriceInfo {
+    type Error = FeedError;
+
+    // funding rate có thể âm
+    fn try_from(ev: MarkPriceEvent) -> Result<Self, FeedError> {
+        Ok(Self {
+            mark_price: parse_positive("mark price", &ev.mark_price)?,
+            index_price: parse_positive("index price", &ev.index_price)?,
+            estimated_settle_price: parse_f64("estimated settle price", &ev.estimated_settle_price)?,
+            funding_rate: parse_f64("funding rate", &ev.funding_rate)?,
+            next_funding_time: DateTime::from_timestamp_millis(ev.next_funding_time).unwrap_or_default(),
+            event_time: DateTime::from_timestamp_millis(ev.event_time).unwrap_or_default(),
+        })
+    }
+}
+
+const PERP_CHANNEL_CAPACITY: usize = 256;
+// /fapi/v1/openInterest không có stream, poll theo chu kỳ
+const DEFAULT_REST_POLL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone)]
+pub struct BinanceFuturesWS {
+    pub symbol: String,
+    pub depth_level: usize,
+    pub network: BinanceNetwork,
+    pub orderbook: Arc<SharedOrderbook>,
+    pub backoff: BackoffConfig,
+    pub heartbeat: HeartbeatConfig,
+    pub connection: ConnectionStatus,
+    pub malformed: MalformedCounter,
+    pub mark_price: Arc<Mutex<Option<MarkPriceInfo>>>,
+    // gộp markPrice stream + funding / open interest từ REST
+    pub perp: Arc<Mutex<PerpStats>>,
+    perp_tx: broadcast::Sender<PerpStats>,
+    liquidations_tx: broadcast::Sender<Liquidation>,
+    rest: BinanceFuturesRestClient,
+    rest_poll: Duration,
+}
+
+impl BinanceFuturesWS {
+    // depth_level hợp lệ: 5, 10, 20
+    pub fn new(symbol: &str, depth_level: usize) -> Self {
+        Self {
+            symbol: symbol.to_lowercase(),
+            depth_level,
+            network: BinanceNetwork::default(),
+            orderbook: Arc::new(SharedOrderbook::new()),
+            backoff: BackoffConfig::default(),
+            heartbeat: HeartbeatConfig::default(),
+            connection: ConnectionStatus::new(),
+            malformed: MalformedCounter::default(),
+            mark_price: Arc::new(Mutex::new(None)),
+            perp: Arc::new(Mutex::new(PerpStats::new(symbol))),
+            perp_tx: broadcast::channel(PERP_CHANNEL_CAPACITY).0,
+            liquidations_tx: broadcast::channel(PERP_CHANNEL_CAPACITY).0,
+            rest: BinanceFuturesRestClient::new(),
+            rest_poll: DEFAULT_REST_POLL,
+        }
+    }
+
+    pub fn with_rest(mut self, rest: BinanceFuturesRestClient, poll: Duration) -> Self {
+        self.rest = rest;
+        self.rest_poll = poll;
+        self
+    }
+
+    // stream + REST funding / open interest
+    pub fn with_network(mut self, network: BinanceNetwork) -> Self {
+        self.rest = self.rest.with_base_url(network.futures_rest_url());
+        self.network = network;
+        self
+    }
+
+    fn stream_url(&self) -> String {
+        format!(
+            "{u}/stream?streams={s}@depth{d}@100ms/{s}@markPrice@1s/{s}@forceOrder",
+            u = self.network.futures_ws_url(),
+            s = self.symbol,
+            d = self.depth_level
+        )
+    }
+
+    pub async fn start(self: Arc<Self>) {
+        // poll_rest không tự dừng, feed dừng khi stream dừng
+        tokio::select! {
+            _ = self.run_stream() => {}
+            _ = self.poll_rest() => {}
+        }
+    }
+
+    async fn run_stream(&self) {
+        let url = self.stream_url();
+        let mut reconnect = Reconnector::new(self.backoff.clone(), self.connection.clone());
+
+        loop {
+            reconnect.connecting();
+            match transport::connect("binance_futures", &url).await {
+                Ok((ws_stream, _)) => {
+                    reconnect.connected();
+                    println!("📡 Connected to Binance Futures WS for {}", self.symbol);
+                    let (mut write, mut read) = ws_stream.split();
+                    let mut heartbeat = Heartbeat::new(&self.heartbeat).on_resubscribe(&self.connection);
+
+                    while let Some(text) = heartbeat.next_text(&mut read, &mut write).await {
+                        self.handle_message(&text).await;
+                    }
+                }
+                Err(e) => {
+                    println!("⚠️ WS Error: {:?}, reconnecting...", e);
+                }
+            }
+            if !reconnect.wait().await {
+                return;
+            }
+        }
+    }
+
+    async fn handle_message(&self, text: &str) {
+        let received = Instant::now();
+        let result = match serde_json::from_str::<CombinedMessage>(text) {
+            Ok(msg) => self.dispatch(msg, received).await,
+            Err(e) => Err(e.into()),
+        };
+        if let Err(e) = result {
+            self.malformed.record(&self.symbol, &e);
+        }
+    }
+
+    async fn dispatch(&self, msg: CombinedMessage<'_>, received: Instant) -> Result<(), FeedError> {
+        let data = msg.data.get();
+        if msg.stream.contains("@depth") {
+            let ev: FuturesDepthEvent = serde_json::from_str(data)?;
+            check_levels(&ev.bids)?;
+            check_levels(&ev.asks)?;
+            self.orderbook.mark_parsed(received);
+            self.process_depth(ev).await;
+        } else if msg.stream.contains("@markPrice") {
+            let info = MarkPriceInfo::try_from(serde_json::from_str::<MarkPriceEvent>(data)?)?;
+            self.update_perp(|p| {
+                p.mark_price = info.mark_price;
+                p.index_price = info.index_price;
+                p.predicted_funding_rate = info.funding_rate;
+                p.next_funding_time = info.next_funding_time;
+                p.timestamp = info.event_time;
+            })
+            .await;
+            *self.mark_price.lock().await = Some(info);
+        } else if msg.stream.contains("@forceOrder") {
+            let liq = Liquidation::try_from(serde_json::from_str::<ForceOrderEvent>(data)?.order)?;
+            let _ = self.liquidations_tx.send(liq);
+        }
+        Ok(())
+    }
+
+    async fn update_perp<F: FnOnce(&mut PerpStats)>(&self, f: F) {
+        let mut perp = self.perp.lock().await;
+        f(&mut perp);
+        let _ = self.perp_tx.send(perp.clone());
+    }
+
+    // open interest mỗi `rest_poll`, funding đã chốt khi sang kỳ funding mới
+    async fn poll_rest(&self) {
+        let mut interval = tokio::time::interval(self.rest_poll);
+        let mut funding_period = None;
+        loop {
+            interval.tick().await;
+            match self.rest.open_interest(&self.symbol).await {
+                Ok(oi) => self.update_perp(|p| p.open_interest = oi.open_interest.to_f64()).await,
+                Err(e) => warn!(symbol = %self.symbol, error = %e, "open interest poll failed"),
+            }
+
+            let next_funding = self.perp.lock().await.next_funding_time;
+            if funding_period == Some(next_funding) {
+                continue;
+            }
+            match self.rest.funding_rates(&self.symbol, 1).await {
+                Ok(rates) => {
+                    if let Some(last) = rates.last() {
+                        self.update_perp(|p| p.funding_rate = last.funding_rate.to_f64()).await;
+                    }
+                    funding_period = Some(next_funding);
+                }
+                Err(e) => warn!(symbol = %self.symbol, error = %e, "funding rate poll failed"),
+            }
+        }
+    }
+
+    async fn process_depth(&self, ev: FuturesDepthEvent<'_>) {
+        if let Some(event_time) = ev.event_time {
+            self.orderbook.record_latency(event_time);
+        }
+        self.orderbook
+            .update(|ob| {
+                ob.replace_levels_str(Side::Bid, &ev.bids);
+                ob.replace_levels_str(Side::Ask, &ev.asks);
+
+                ob.last_update_id = ev.final_update_id;
+                ob.timestamp = Utc::now();
+                true
+            });
+    }
+
+    pub async fn get_best_price(&self) -> Option<BestBidAsk> {
+        self.orderbook.best_bid_ask()
+    }
+
+    pub async fn get_mark_price(&self) -> Option<MarkPriceInfo> {
+        self.mark_price.lock().await.clone()
+    }
+
+    pub async fn get_funding_rate(&self) -> Option<f64> {
+        self.mark_price.lock().await.as_ref().map(|m| m.funding_rate)
+    }
+
+    pub async fn get_perp_stats(&self) -> PerpStats {
+        self.perp.lock().await.clone()
+    }
+}
+
+#[async_trait]
+impl OrderbookFeed for BinanceFuturesWS {
+    fn exchange(&self) -> &'static str {
+        "binance_futures"
+    }
+
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    fn orderbook(&self) -> &SharedOrderbook {
+        &self.orderbook
+    }
+
+    fn connection(&self) -> Option<&ConnectionStatus> {
+        Some(&self.connection)
+    }
+
+    fn subscribe_perp(&self) -> Option<broadcast::Receiver<PerpStats>> {
+        Some(self.perp_tx.subscribe())
+    }
+
+    fn subscribe_liquidations(&self) -> Option<broadcast::Receiver<Liquidation>> {
+        Some(self.liquidations_tx.subscribe())
+    }
+
+    async fn start(self: Arc<Self>) {
+        BinanceFuturesWS::start(self).await
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[tokio::test]
+    async fn test_bad_depth_level_rejected() {
+        let ws = BinanceFuturesWS::new("BTCUSDT", 5);
+        let depth = |id: u64, bid: &str| {
+            format!(r#"{{"stream":"btcusdt@depth5@100ms","data":{{"E":1,"u":{},"b":[["{}","1.0"]],"a":[["7405.96","3.340"]]}}}}"#, id, bid)
+        };
+        ws.handle_message(&depth(1, "7403.90")).await;
+        // giá hỏng không thành level 0 / âm trong book
+        ws.handle_message(&depth(2, "")).await;
+        ws.handle_message(&depth(3, "-7403.9")).await;
+        assert_eq!(ws.malformed.count(), 2);
+        assert_eq!(ws.get_best_price().await, Some(((dec!(7403.90), dec!(1.0)), (dec!(7405.96), dec!(3.340)))));
+        assert_eq!(ws.orderbook.snapshot().last_update_id, 1);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_depth_and_mark_price() {
+        let ws = BinanceFuturesWS::new("BTCUSDT", 5);
+
+        let depth = r#"
+        {
+            "stream": "btcusdt@depth5@100ms",
+            "data": {
+                "e": "depthUpdate", "E": 1571889248277, "T": 1571889248276, "s": "BTCUSDT",
+                "U": 390497796, "u": 390497878, "pu": 390497794,
+                "b": [["7403.89", "0.002"], ["7403.90", "3.906"]],
+                "a": [["7405.96", "3.340"], ["7406.63", "4.525"]]
+            }
+        }
+        "#;
+        ws.handle_message(depth).await;
+        assert_eq!(ws.get_best_price().await, Some(((dec!(7403.90), dec!(3.906)), (dec!(7405.96), dec!(3.340)))));
+
+        let mark = r#"
+        {
+            "stream": "btcusdt@markPrice@1s",
+            "data": {
+                "e": "markPriceUpdate", "E": 1562305380000, "s": "BTCUSDT",
+                "p": "11794.15000000", "i": "11784.62659091", "P": "11784.25641265",
+                "r": "0.00038167", "T": 1562306400000
+            }
+        }
+        "#;
+        let mut perp = ws.subscribe_perp().unwrap();
+        ws.handle_message(mark).await;
+        let stats = perp.try_recv().unwrap();
+        assert_eq!((stats.symbol.as_str(), stats.predicted_funding_rate, stats.funding_rate), ("BTCUSDT", 0.00038167, None));
+
+        let info = ws.get_mark_price().await.unwrap();
+        assert_eq!(info.mark_price, 11794.15);
+        assert_eq!(ws.get_funding_rate().await, Some(0.00038167));
+        assert_eq!(info.next_funding_time.timestamp_millis(), 1562306400000);
+
+        let mut liqs = ws.subscribe_liquidations().unwrap();
+        let force = r#"
+        {
+            "stream": "btcusdt@forceOrder",
+            "data": {
+                "e": "forceOrder", "E": 1568014460893,
+                "o": {
+                    "s": "BTCUSDT", "S": "SELL", "o": "LIMIT", "f": "IOC", "q": "0.014", "p": "9910",
+                    "ap": "9910", "X": "FILLED", "l": "0.014", "z": "0.014", "T": 1568014460893
+                }
+            }
+        }
+        "#;
+        ws.handle_message(force).await;
+        let liq = liqs.try_recv().unwrap();
+        assert!(liq.is_long());
+        assert_eq!((liq.price, liq.qty, liq.timestamp.timestamp_millis()), (9910.0, 0.014, 1568014460893));
+
+        // mark price hỏng: bỏ, giữ giá trước đó
+        ws.handle_message(&mark.replace("11794.15000000", "NaN")).await;
+        assert_eq!(ws.get_mark_price().await.unwrap().mark_price, 11794.15);
+        assert_eq!(ws.malformed.count(), 1);
+    }
+}
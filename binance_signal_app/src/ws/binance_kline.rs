@@ -0,0 +1,187 @@
+use futures_util::StreamExt;
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio::sync::{broadcast, Mutex};
+use chrono::DateTime;
+
+use crate::core::candle::{parse_interval, Candle, CandleStore};
+use crate::rest::binance::BinanceNetwork;
+use super::{
+    heartbeat::{Heartbeat, HeartbeatConfig},
+    parse_non_negative, parse_positive,
+    reconnect::{BackoffConfig, ConnectionStatus, Reconnector},
+    transport,
+    FeedError, MalformedCounter,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+struct KlineEvent {
+    #[serde(rename = "k")]
+    kline: KlineData,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct KlineData {
+    #[serde(rename = "t")]
+    open_time: i64,
+    #[serde(rename = "T")]
+    close_time: i64,
+    #[serde(rename = "o")]
+    open: String,
+    #[serde(rename = "h")]
+    high: String,
+    #[serde(rename = "l")]
+    low: String,
+    #[serde(rename = "c")]
+    close: String,
+    #[serde(rename = "v")]
+    volume: String,
+    #[serde(rename = "n")]
+    trades: u64,
+    #[serde(rename = "x")]
+    closed: bool,
+}
+
+impl TryFrom<KlineData> for Candle {
+    type Error = FeedError;
+
+    fn try_from(k: KlineData) -> Result<Self, FeedError> {
+        Ok(Candle {
+            open_time: DateTime::from_timestamp_millis(k.open_time).unwrap_or_default(),
+            close_time: DateTime::from_timestamp_millis(k.close_time).unwrap_or_default(),
+            open: parse_positive("kline open", &k.open)?,
+            high: parse_positive("kline high", &k.high)?,
+            low: parse_positive("kline low", &k.low)?,
+            close: parse_positive("kline close", &k.close)?,
+            volume: parse_non_negative("kline volume", &k.volume)?,
+            trades: k.trades,
+            closed: k.closed,
+        })
+    }
+}
+
+fn parse_kline(text: &str) -> Result<Candle, FeedError> {
+    serde_json::from_str::<KlineEvent>(text)?.kline.try_into()
+}
+
+#[derive(Debug, Clone)]
+pub struct BinanceKlineWS {
+    pub symbol: String,
+    pub interval: String,
+    pub network: BinanceNetwork,
+    pub candles: Arc<Mutex<CandleStore>>,
+    pub backoff: BackoffConfig,
+    pub heartbeat: HeartbeatConfig,
+    pub connection: ConnectionStatus,
+    pub malformed: MalformedCounter,
+    // bar đã đóng, cho indicator / strategy theo candle
+    closed_tx: broadcast::Sender<Candle>,
+}
+
+impl BinanceKlineWS {
+    // interval theo Binance: 1s, 1m, 3m, 5m, 15m, 30m, 1h, 2h, 4h, 6h, 8h, 12h, 1d, 3d, 1w
+    pub fn new(symbol: &str, interval: &str, max_len: usize) -> Option<Self> {
+        let step = parse_interval(interval)?;
+        Some(Self {
+            symbol: symbol.to_lowercase(),
+            interval: interval.to_string(),
+            network: BinanceNetwork::default(),
+            candles: Arc::new(Mutex::new(CandleStore::new(step, max_len))),
+            backoff: BackoffConfig::default(),
+            heartbeat: HeartbeatConfig::default(),
+            connection: ConnectionStatus::new(),
+            malformed: MalformedCounter::default(),
+            closed_tx: broadcast::channel(1024).0,
+        })
+    }
+
+    pub fn with_network(mut self, network: BinanceNetwork) -> Self {
+        self.network = network;
+        self
+    }
+
+    pub async fn start(self: Arc<Self>) {
+        let mut reconnect = Reconnector::new(self.backoff.clone(), self.connection.clone());
+
+        loop {
+            reconnect.connecting();
+            let url = format!(
+                "{}/ws/{}@kline_{}",
+                self.network.ws_url(), self.symbol, self.interval
+            );
+            match transport::connect("binance", &url).await {
+                Ok((ws_stream, _)) => {
+                    reconnect.connected();
+                    println!("📡 Connected to Binance kline_{} WS for {}", self.interval, self.symbol);
+                    let (mut write, mut read) = ws_stream.split();
+                    let mut heartbeat = Heartbeat::new(&self.heartbeat).on_resubscribe(&self.connection);
+
+                    while let Some(text) = heartbeat.next_text(&mut read, &mut write).await {
+                        self.handle_message(&text).await;
+                    }
+                }
+                Err(e) => {
+                    println!("⚠️ WS Error: {:?}, reconnecting...", e);
+                    self.network.ws_failed(&url);
+                }
+            }
+            if !reconnect.wait().await {
+                return;
+            }
+        }
+    }
+
+    async fn handle_message(&self, text: &str) {
+        match parse_kline(text) {
+            Ok(candle) => self.on_kline(candle).await,
+            Err(e) => self.malformed.record(&self.symbol, &e),
+        }
+    }
+
+    async fn on_kline(&self, candle: Candle) {
+        let closed = self.candles.lock().await.upsert(candle);
+        if let Some(bar) = closed {
+            let _ = self.closed_tx.send(bar);
+        }
+    }
+
+    pub fn subscribe_closed(&self) -> broadcast::Receiver<Candle> {
+        self.closed_tx.subscribe()
+    }
+
+    pub async fn latest(&self, n: usize) -> Vec<Candle> {
+        self.candles.lock().await.latest(n)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_kline_upsert_same_bar() {
+        let ws = BinanceKlineWS::new("BNBBTC", "1m", 10).unwrap();
+        let mut closed = ws.subscribe_closed();
+        for (close, closed) in [("0.0020", false), ("0.0022", true)] {
+            let raw = format!(
+                r#"{{"e":"kline","E":123456789,"s":"BNBBTC","k":{{"t":123400000,"T":123459999,"s":"BNBBTC","i":"1m","f":100,"L":200,"o":"0.0010","c":"{}","h":"0.0025","l":"0.0015","v":"1000","n":100,"x":{},"q":"1.0000","V":"500","Q":"0.500","B":"123456"}}}}"#,
+                close, closed
+            );
+            ws.handle_message(&raw).await;
+        }
+        // close hỏng: bỏ message, bar giữ nguyên
+        ws.handle_message(r#"{"k":{"t":123400000,"T":123459999,"o":"0.0010","c":"","h":"0.0025","l":"0.0015","v":"1","n":1,"x":false}}"#).await;
+        ws.handle_message(r#"{"k":{"t":123460000,"T":123519999,"o":"0.0010","c":"0.0011","h":"0.0025","l":"0.0015","v":"-1","n":1,"x":true}}"#).await;
+        assert_eq!(ws.malformed.count(), 2);
+        // chỉ bar đã đóng mới được publish
+        assert_eq!(closed.try_recv().unwrap().close, 0.0022);
+        assert!(closed.try_recv().is_err());
+
+        let bars = ws.latest(5).await;
+        assert_eq!(bars.len(), 1);
+        assert_eq!(bars[0].close, 0.0022);
+        assert!(bars[0].closed);
+        assert!(BinanceKlineWS::new("bnbbtc", "7x", 10).is_none());
+    }
+}
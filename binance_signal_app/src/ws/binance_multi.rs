@@ -0,0 +1,220 @@
+use tokio_tungstenite::tungstenite::Message;
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::json;
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::time::{interval, MissedTickBehavior};
+use tracing::warn;
+
+use crate::rest::binance::BinanceNetwork;
+use super::{
+    binance::{BinanceOrderbookWS, DepthUpdate, UpdateSpeed},
+    heartbeat::{Heartbeat, HeartbeatConfig},
+    reconnect::{BackoffConfig, ConnectionStatus, Reconnector},
+    transport,
+    stale::{StaleAction, StaleTracker},
+};
+
+// Payload của combined stream: {"stream":"<symbol>@depth20@100ms","data":{...}}
+#[derive(Debug, Clone, Deserialize)]
+struct CombinedMessage<'a> {
+    stream: &'a str,
+    #[serde(borrow)]
+    data: DepthUpdate<'a>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BinanceMultiStreamWS {
+    pub depth_level: usize,
+    pub speed: UpdateSpeed,
+    pub network: BinanceNetwork,
+    books: HashMap<String, Arc<BinanceOrderbookWS>>,
+    pub backoff: BackoffConfig,
+    pub heartbeat: HeartbeatConfig,
+    pub connection: ConnectionStatus,
+    // symbol im lặng quá lâu thì UNSUBSCRIBE + SUBSCRIBE riêng stream đó, vẫn im thì reconnect
+    pub stale_after: Option<Duration>,
+}
+
+impl BinanceMultiStreamWS {
+    pub fn new(symbols: &[&str], depth_level: usize) -> Self {
+        let books = symbols
+            .iter()
+            .map(|s| {
+                let ob = Arc::new(BinanceOrderbookWS::new(s, depth_level));
+                (ob.symbol.clone(), ob)
+            })
+            .collect();
+        Self {
+            depth_level,
+            speed: UpdateSpeed::default(),
+            network: BinanceNetwork::default(),
+            books,
+            backoff: BackoffConfig::default(),
+            heartbeat: HeartbeatConfig::default(),
+            connection: ConnectionStatus::new(),
+            stale_after: None,
+        }
+    }
+
+    pub fn with_stale_after(mut self, stale_after: Duration) -> Self {
+        self.stale_after = Some(stale_after);
+        self
+    }
+
+    pub fn with_speed(mut self, speed: UpdateSpeed) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    // handle tạo sẵn trong `new` nên chỉ đổi URL của stream gộp
+    pub fn with_network(mut self, network: BinanceNetwork) -> Self {
+        self.network = network;
+        self
+    }
+
+    pub fn symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<String> = self.books.keys().cloned().collect();
+        symbols.sort();
+        symbols
+    }
+
+    pub fn handle(&self, symbol: &str) -> Option<Arc<BinanceOrderbookWS>> {
+        self.books.get(&symbol.to_lowercase()).cloned()
+    }
+
+    pub fn handles(&self) -> &HashMap<String, Arc<BinanceOrderbookWS>> {
+        &self.books
+    }
+
+    fn stream_name(&self, symbol: &str) -> String {
+        format!("{}@depth{}{}", symbol, self.depth_level, self.speed.suffix())
+    }
+
+    fn stream_url(&self) -> String {
+        let streams: Vec<String> = self.symbols().iter().map(|s| self.stream_name(s)).collect();
+        format!("{}/stream?streams={}", self.network.ws_url(), streams.join("/"))
+    }
+
+    // {"method":"UNSUBSCRIBE"|"SUBSCRIBE","params":[stream],"id":n}
+    fn resubscribe_messages(&self, symbol: &str, id: u64) -> [Message; 2] {
+        let params = [self.stream_name(symbol)];
+        [
+            Message::Text(json!({"method": "UNSUBSCRIBE", "params": params, "id": id}).to_string()),
+            Message::Text(json!({"method": "SUBSCRIBE", "params": params, "id": id + 1}).to_string()),
+        ]
+    }
+
+    pub async fn start(self: Arc<Self>) {
+        let mut reconnect = Reconnector::new(self.backoff.clone(), self.connection.clone());
+        let mut tracker = self.stale_after.map(|silence| StaleTracker::new(silence, self.symbols()));
+        let mut request_id = 0;
+
+        loop {
+            reconnect.connecting();
+            let url = self.stream_url();
+            match transport::connect("binance", &url).await {
+                Ok((ws_stream, _)) => {
+                    reconnect.connected();
+                    println!("📡 Connected to Binance combined WS for {} symbols", self.books.len());
+                    let (mut write, mut read) = ws_stream.split();
+                    let mut heartbeat = Heartbeat::new(&self.heartbeat).on_resubscribe(&self.connection);
+                    let mut check = interval(self.stale_after.map_or(Duration::from_secs(1), |d| d / 4).max(Duration::from_millis(100)));
+                    check.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                    if let Some(tracker) = &mut tracker {
+                        tracker.reset();
+                    }
+
+                    'read: loop {
+                        tokio::select! {
+                            text = heartbeat.next_text(&mut read, &mut write) => {
+                                let Some(text) = text else { break };
+                                if let Ok(msg) = serde_json::from_str::<CombinedMessage>(&text) {
+                                    if let Some(tracker) = &mut tracker {
+                                        tracker.touch(msg.stream.split('@').next().unwrap_or_default());
+                                    }
+                                    self.dispatch(msg).await;
+                                }
+                            }
+                            _ = check.tick(), if tracker.is_some() => {
+                                let actions = tracker.as_mut().map(StaleTracker::check).unwrap_or_default();
+                                for (symbol, silent, action) in actions {
+                                    warn!(target: "feed_health", exchange = "binance", %symbol, silent_ms = silent.as_millis() as u64, ?action, "symbol silent on combined stream");
+                                    if action == StaleAction::Reconnect {
+                                        break 'read;
+                                    }
+                                    request_id += 2;
+                                    for msg in self.resubscribe_messages(&symbol, request_id) {
+                                        if write.send(msg).await.is_err() {
+                                            break 'read;
+                                        }
+                                    }
+                                    // handle không tự kết nối, chỉ để /health đếm số lần resubscribe
+                                    if let Some(ob) = self.books.get(&symbol) {
+                                        ob.connection.request_resubscribe();
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    println!("⚠️ WS Error: {:?}, reconnecting...", e);
+                    self.network.ws_failed(&url);
+                }
+            }
+            if !reconnect.wait().await {
+                return;
+            }
+        }
+    }
+
+    async fn dispatch(&self, msg: CombinedMessage<'_>) {
+        let symbol = msg.stream.split('@').next().unwrap_or_default();
+        if let Some(ob) = self.books.get(symbol) {
+            ob.process_snapshot(msg.data).await;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_combined_stream_url() {
+        let ws = BinanceMultiStreamWS::new(&["BTCUSDT", "cakebnb"], 10);
+        assert_eq!(
+            ws.stream_url(),
+            "wss://stream.binance.com:9443/stream?streams=btcusdt@depth10@100ms/cakebnb@depth10@100ms"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_routes_to_symbol() {
+        let ws = BinanceMultiStreamWS::new(&["btcusdt", "ethusdt"], 5);
+        let raw = r#"
+        {
+            "stream": "ethusdt@depth5@100ms",
+            "data": {
+                "lastUpdateId": 7,
+                "bids": [["2000.0", "1.0"]],
+                "asks": [["2001.0", "2.0"]]
+            }
+        }
+        "#;
+
+        let msg: CombinedMessage = serde_json::from_str(raw).unwrap();
+        ws.dispatch(msg).await;
+
+        let eth = ws.handle("ETHUSDT").unwrap();
+        assert_eq!(eth.get_best_price().await, Some(((dec!(2000.0), dec!(1.0)), (dec!(2001.0), dec!(2.0)))));
+        assert!(ws.handle("btcusdt").unwrap().get_best_price().await.is_none());
+
+        let [unsub, sub] = ws.resubscribe_messages("ethusdt", 4);
+        assert_eq!(unsub, Message::Text(r#"{"id":4,"method":"UNSUBSCRIBE","params":["ethusdt@depth5@100ms"]}"#.into()));
+        assert_eq!(sub, Message::Text(r#"{"id":5,"method":"SUBSCRIBE","params":["ethusdt@depth5@100ms"]}"#.into()));
+    }
+}
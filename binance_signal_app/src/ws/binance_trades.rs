@@ -0,0 +1,269 @@
+use futures_util::StreamExt;
+use serde::Deserialize;
+use std::{sync::Arc, time::Duration};
+use tokio::sync::{broadcast, Mutex};
+use chrono::{DateTime, Utc};
+
+use crate::core::trade::{Trade, TradeSide, TradeWindow};
+use crate::rest::binance::BinanceNetwork;
+use super::{
+    heartbeat::{Heartbeat, HeartbeatConfig},
+    parse_positive,
+    reconnect::{BackoffConfig, ConnectionStatus, Reconnector},
+    transport,
+    FeedError, MalformedCounter,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeStreamKind {
+    AggTrade,
+    Trade,
+}
+
+impl TradeStreamKind {
+    fn stream_name(&self) -> &'static str {
+        match self {
+            TradeStreamKind::AggTrade => "aggTrade",
+            TradeStreamKind::Trade => "trade",
+        }
+    }
+}
+
+// Dùng chung cho `@aggTrade` (id = "a") và `@trade` (id = "t").
+// Chuỗi mượn từ text của message, chỉ symbol được copy khi tạo Trade
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct TradeEvent<'a> {
+    #[serde(rename = "s")]
+    symbol: &'a str,
+    #[serde(rename = "a", alias = "t")]
+    id: u64,
+    #[serde(rename = "p")]
+    price: &'a str,
+    #[serde(rename = "q")]
+    qty: &'a str,
+    #[serde(rename = "T")]
+    trade_time: i64,
+    // buyer là maker -> bên bán chủ động
+    #[serde(rename = "m")]
+    buyer_is_maker: bool,
+}
+
+impl TryFrom<TradeEvent<'_>> for Trade {
+    type Error = FeedError;
+
+    fn try_from(ev: TradeEvent<'_>) -> Result<Self, FeedError> {
+        Ok(Trade {
+            symbol: ev.symbol.to_lowercase(),
+            trade_id: ev.id,
+            price: parse_positive("trade price", ev.price)?,
+            qty: parse_positive("trade qty", ev.qty)?,
+            side: if ev.buyer_is_maker { TradeSide::Sell } else { TradeSide::Buy },
+            timestamp: DateTime::from_timestamp_millis(ev.trade_time).unwrap_or_default(),
+        })
+    }
+}
+
+pub fn parse_trade(text: &str) -> Result<Trade, FeedError> {
+    serde_json::from_str::<TradeEvent>(text)?.try_into()
+}
+
+#[derive(Debug, Clone)]
+pub struct BinanceTradesWS {
+    pub symbol: String,
+    pub kind: TradeStreamKind,
+    pub network: BinanceNetwork,
+    pub trades: Arc<Mutex<TradeWindow>>,
+    pub backoff: BackoffConfig,
+    pub heartbeat: HeartbeatConfig,
+    pub connection: ConnectionStatus,
+    pub malformed: MalformedCounter,
+    trades_tx: broadcast::Sender<Trade>,
+}
+
+impl BinanceTradesWS {
+    pub fn new(symbol: &str, kind: TradeStreamKind) -> Self {
+        Self::with_window(symbol, kind, Duration::from_secs(300), 10_000)
+    }
+
+    pub fn with_window(symbol: &str, kind: TradeStreamKind, max_age: Duration, max_len: usize) -> Self {
+        let max_age = chrono::Duration::from_std(max_age).unwrap_or(chrono::Duration::minutes(5));
+        Self {
+            symbol: symbol.to_lowercase(),
+            kind,
+            network: BinanceNetwork::default(),
+            trades: Arc::new(Mutex::new(TradeWindow::new(max_age, max_len))),
+            backoff: BackoffConfig::default(),
+            heartbeat: HeartbeatConfig::default(),
+            connection: ConnectionStatus::new(),
+            malformed: MalformedCounter::default(),
+            trades_tx: broadcast::channel(1024).0,
+        }
+    }
+
+    pub fn with_network(mut self, network: BinanceNetwork) -> Self {
+        self.network = network;
+        self
+    }
+
+    pub async fn start(self: Arc<Self>) {
+        let mut reconnect = Reconnector::new(self.backoff.clone(), self.connection.clone());
+
+        loop {
+            reconnect.connecting();
+            let url = format!(
+                "{}/ws/{}@{}",
+                self.network.ws_url(),
+                self.symbol,
+                self.kind.stream_name()
+            );
+            match transport::connect("binance", &url).await {
+                Ok((ws_stream, _)) => {
+                    reconnect.connected();
+                    println!("📡 Connected to Binance {} WS for {}", self.kind.stream_name(), self.symbol);
+                    let (mut write, mut read) = ws_stream.split();
+                    let mut heartbeat = Heartbeat::new(&self.heartbeat).on_resubscribe(&self.connection);
+
+                    while let Some(text) = heartbeat.next_text(&mut read, &mut write).await {
+                        self.handle_message(&text).await;
+                    }
+                }
+                Err(e) => {
+                    println!("⚠️ WS Error: {:?}, reconnecting...", e);
+                    self.network.ws_failed(&url);
+                }
+            }
+            if !reconnect.wait().await {
+                return;
+            }
+        }
+    }
+
+    async fn handle_message(&self, text: &str) {
+        match parse_trade(text) {
+            Ok(trade) => self.process_trade(trade).await,
+            Err(e) => self.malformed.record(&self.symbol, &e),
+        }
+    }
+
+    async fn process_trade(&self, trade: Trade) {
+        self.trades.lock().await.push(trade.clone());
+        let _ = self.trades_tx.send(trade);
+    }
+
+    // mọi trade theo thứ tự nhận
+    pub fn subscribe(&self) -> broadcast::Receiver<Trade> {
+        self.trades_tx.subscribe()
+    }
+
+    pub async fn recent_trades(&self, n: usize) -> Vec<Trade> {
+        self.trades.lock().await.recent(n)
+    }
+
+    pub async fn last_trade(&self) -> Option<Trade> {
+        self.trades.lock().await.last().cloned()
+    }
+
+    // (buy volume, sell volume) trong `window` gần nhất
+    pub async fn buy_sell_volume(&self, window: Duration) -> (f64, f64) {
+        self.trades.lock().await.volume_since(window_start(window))
+    }
+
+    pub async fn buy_sell_imbalance(&self, window: Duration) -> Option<f64> {
+        self.trades.lock().await.imbalance_since(window_start(window))
+    }
+}
+
+fn window_start(window: Duration) -> DateTime<Utc> {
+    Utc::now() - chrono::Duration::from_std(window).unwrap_or(chrono::Duration::zero())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::Fuzz;
+
+    #[tokio::test]
+    async fn test_parse_agg_trade_and_trade() {
+        let agg = r#"{"e":"aggTrade","E":123456789,"s":"BNBBTC","a":12345,"p":"0.001","q":"100","f":100,"l":105,"T":123456785,"m":true,"M":true}"#;
+        let t = parse_trade(agg).unwrap();
+        assert_eq!(t.trade_id, 12345);
+        assert_eq!(t.side, TradeSide::Sell);
+        assert_eq!(t.symbol, "bnbbtc");
+
+        let raw = r#"{"e":"trade","E":123456789,"s":"BNBBTC","t":777,"p":"0.002","q":"5","T":123456785,"m":false,"M":true}"#;
+        let t = parse_trade(raw).unwrap();
+        assert_eq!(t.trade_id, 777);
+        assert_eq!(t.side, TradeSide::Buy);
+        assert_eq!((t.price, t.qty), (0.002, 5.0));
+
+        // số hỏng không còn thành giá 0
+        let bad = raw.replace(r#""p":"0.002""#, r#""p":"abc""#);
+        assert!(matches!(parse_trade(&bad), Err(FeedError::InvalidNumber { field: "trade price", .. })));
+        assert!(matches!(parse_trade(&raw.replace(r#""q":"5""#, r#""q":"NaN""#)), Err(FeedError::InvalidNumber { .. })));
+        assert!(matches!(parse_trade("{"), Err(FeedError::Json(_))));
+    }
+
+    #[tokio::test]
+    async fn test_malformed_trade_not_recorded() {
+        let ws = BinanceTradesWS::new("btcusdt", TradeStreamKind::Trade);
+        let mut rx = ws.subscribe();
+        for price in ["0", "-1", "", "1e999"] {
+            let raw = format!(r#"{{"e":"trade","s":"BTCUSDT","t":1,"p":"{}","q":"1","T":1,"m":false}}"#, price);
+            ws.handle_message(&raw).await;
+        }
+        assert_eq!(ws.malformed.count(), 4);
+        assert!(ws.last_trade().await.is_none());
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_imbalance_from_stream() {
+        let ws = BinanceTradesWS::new("btcusdt", TradeStreamKind::AggTrade);
+        let mut rx = ws.subscribe();
+        let now = Utc::now().timestamp_millis();
+        for (id, qty, maker) in [(1, "2.0", false), (2, "1.0", true), (3, "1.0", false)] {
+            let raw = format!(
+                r#"{{"e":"aggTrade","s":"BTCUSDT","a":{},"p":"30000","q":"{}","T":{},"m":{}}}"#,
+                id, qty, now, maker
+            );
+            ws.handle_message(&raw).await;
+        }
+        ws.handle_message(r#"{"e":"aggTrade","s":"BTCUSDT","a":4,"p":"0","q":"1","T":0,"m":false}"#).await;
+        assert_eq!(ws.malformed.count(), 1);
+
+        assert_eq!(ws.buy_sell_volume(Duration::from_secs(60)).await, (3.0, 1.0));
+        assert_eq!(ws.buy_sell_imbalance(Duration::from_secs(60)).await, Some(0.5));
+        assert_eq!(ws.last_trade().await.unwrap().trade_id, 3);
+        assert_eq!(rx.try_recv().unwrap().trade_id, 1);
+    }
+
+    // aggTrade với JSON hỏng / giá, qty xấu: không panic, trade nào đi qua cũng có giá / qty
+    // dương hữu hạn, còn lại được đếm vào malformed
+    #[tokio::test]
+    async fn test_fuzz_trade_messages_do_not_panic() {
+        let ws = BinanceTradesWS::new("btcusdt", TradeStreamKind::AggTrade);
+        let mut rx = ws.subscribe();
+        for seed in 0..300 {
+            let mut fuzz = Fuzz::new(seed);
+            let raw = format!(
+                r#"{{"e":"aggTrade","s":"BTCUSDT","a":{},"p":"{}","q":"{}","T":{},"m":{}}}"#,
+                fuzz.next_u64(),
+                fuzz.number(30_000),
+                fuzz.number(1),
+                fuzz.next_u64() as i64,
+                fuzz.chance(50)
+            );
+            let raw = if fuzz.chance(50) { fuzz.mutate(&raw) } else { raw };
+            ws.handle_message(&raw).await;
+        }
+        let mut accepted = 0;
+        while let Ok(trade) = rx.try_recv() {
+            assert!(trade.price.is_finite() && trade.price > 0.0, "{:?}", trade);
+            assert!(trade.qty.is_finite() && trade.qty > 0.0, "{:?}", trade);
+            accepted += 1;
+        }
+        assert!(accepted > 0 && ws.malformed.count() > 0);
+        assert_eq!(accepted + ws.malformed.count(), 300);
+    }
+}
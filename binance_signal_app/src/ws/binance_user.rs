@@ -0,0 +1,457 @@
+use futures_util::StreamExt;
+use serde::Deserialize;
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::sync::{broadcast, Mutex};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use tracing::warn;
+
+use crate::core::{
+    backpressure::{LosslessBus, LosslessReceiver},
+    order::{Fill, OrderSide, OrderStatus, OrderType},
+};
+use crate::oms::{Oms, OrderUpdate};
+use crate::rest::binance::{Balance, BinanceNetwork, BinanceRestClient};
+use super::{
+    heartbeat::{Heartbeat, HeartbeatConfig},
+    reconnect::{BackoffConfig, ConnectionStatus, Reconnector},
+    transport,
+};
+
+// listenKey hết hạn sau 60 phút, Binance khuyến nghị keepalive mỗi 30 phút
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(30 * 60);
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+// Spot `executionReport`, hoặc phần "o" của `ORDER_TRADE_UPDATE` futures (cùng tên field,
+// không có "C" / "r", "n" chỉ có khi có phí)
+#[derive(Debug, Clone, Deserialize)]
+struct ExecutionReport {
+    #[serde(rename = "E")]
+    event_time: i64,
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "c")]
+    client_order_id: String,
+    // với event cancel, "c" là id của request cancel còn "C" là id gốc
+    #[serde(rename = "C", default)]
+    orig_client_order_id: String,
+    #[serde(rename = "S")]
+    side: OrderSide,
+    #[serde(rename = "o")]
+    order_type: String,
+    #[serde(rename = "q")]
+    qty: Decimal,
+    #[serde(rename = "p")]
+    price: Decimal,
+    #[serde(rename = "X")]
+    status: OrderStatus,
+    #[serde(rename = "r", default)]
+    reject_reason: String,
+    #[serde(rename = "i")]
+    order_id: u64,
+    #[serde(rename = "l")]
+    last_filled_qty: Decimal,
+    #[serde(rename = "z")]
+    cum_filled_qty: Decimal,
+    #[serde(rename = "L")]
+    last_filled_price: Decimal,
+    #[serde(rename = "n", default)]
+    commission: Decimal,
+    #[serde(rename = "m")]
+    is_maker: bool,
+}
+
+impl From<ExecutionReport> for OrderUpdate {
+    fn from(ev: ExecutionReport) -> Self {
+        let client_order_id = if ev.orig_client_order_id.is_empty() {
+            ev.client_order_id
+        } else {
+            ev.orig_client_order_id
+        };
+        OrderUpdate {
+            symbol: ev.symbol,
+            order_id: ev.order_id,
+            client_order_id,
+            side: ev.side,
+            // LIMIT_MAKER, STOP_LOSS_LIMIT, ... đều có giá -> coi như limit
+            order_type: if ev.order_type == "MARKET" { OrderType::Market } else { OrderType::Limit },
+            price: ev.price,
+            qty: ev.qty,
+            status: ev.status,
+            last_filled_qty: ev.last_filled_qty,
+            last_filled_price: ev.last_filled_price,
+            cum_filled_qty: ev.cum_filled_qty,
+            commission: ev.commission,
+            is_maker: ev.is_maker,
+            reject_reason: (!matches!(ev.reject_reason.as_str(), "" | "NONE")).then_some(ev.reject_reason),
+            event_time: DateTime::from_timestamp_millis(ev.event_time).unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct AccountPositionEvent {
+    #[serde(rename = "E")]
+    event_time: i64,
+    #[serde(rename = "B")]
+    balances: Vec<RawBalance>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct RawBalance {
+    #[serde(rename = "a")]
+    asset: String,
+    #[serde(rename = "f")]
+    free: Decimal,
+    #[serde(rename = "l")]
+    locked: Decimal,
+}
+
+// `ACCOUNT_UPDATE` futures, chỉ lấy số dư ví của các asset vừa đổi
+#[derive(Debug, Clone, Deserialize)]
+struct FuturesAccountEvent {
+    #[serde(rename = "E")]
+    event_time: i64,
+    #[serde(rename = "a")]
+    update: FuturesAccountUpdate,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct FuturesAccountUpdate {
+    #[serde(rename = "B")]
+    balances: Vec<FuturesWalletBalance>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct FuturesWalletBalance {
+    #[serde(rename = "a")]
+    asset: String,
+    #[serde(rename = "wb")]
+    wallet_balance: Decimal,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct BalanceUpdateEvent {
+    #[serde(rename = "E")]
+    event_time: i64,
+    #[serde(rename = "a")]
+    asset: String,
+    #[serde(rename = "d")]
+    delta: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UserDataEvent {
+    Order(OrderUpdate),
+    // số dư mới của các asset vừa thay đổi
+    AccountPosition {
+        balances: Vec<Balance>,
+        event_time: DateTime<Utc>,
+    },
+    // nạp / rút / chuyển ví
+    BalanceDelta {
+        asset: String,
+        delta: Decimal,
+        event_time: DateTime<Utc>,
+    },
+    ListenKeyExpired,
+}
+
+pub(crate) fn parse_event(text: &str) -> Option<UserDataEvent> {
+    let value: serde_json::Value = serde_json::from_str(text).ok()?;
+    let ts = |ms: i64| DateTime::from_timestamp_millis(ms).unwrap_or_default();
+    match value.get("e")?.as_str()? {
+        "executionReport" => {
+            let ev: ExecutionReport = serde_json::from_value(value).ok()?;
+            Some(UserDataEvent::Order(ev.into()))
+        }
+        "outboundAccountPosition" => {
+            let ev: AccountPositionEvent = serde_json::from_value(value).ok()?;
+            let balances = ev
+                .balances
+                .into_iter()
+                .map(|b| Balance { asset: b.asset, free: b.free, locked: b.locked })
+                .collect();
+            Some(UserDataEvent::AccountPosition { balances, event_time: ts(ev.event_time) })
+        }
+        // futures: order nằm trong "o", event time ở ngoài
+        "ORDER_TRADE_UPDATE" => {
+            let event_time = value.get("E")?.clone();
+            let mut order = value.get("o")?.clone();
+            order.as_object_mut()?.insert("E".to_string(), event_time);
+            let ev: ExecutionReport = serde_json::from_value(order).ok()?;
+            Some(UserDataEvent::Order(ev.into()))
+        }
+        // số dư như `BinanceRestClient::balances` của futures: số dư ví, không tách margin
+        "ACCOUNT_UPDATE" => {
+            let ev: FuturesAccountEvent = serde_json::from_value(value).ok()?;
+            let balances = ev
+                .update
+                .balances
+                .into_iter()
+                .map(|b| Balance { asset: b.asset, free: b.wallet_balance, locked: Decimal::ZERO })
+                .collect();
+            Some(UserDataEvent::AccountPosition { balances, event_time: ts(ev.event_time) })
+        }
+        "balanceUpdate" => {
+            let ev: BalanceUpdateEvent = serde_json::from_value(value).ok()?;
+            Some(UserDataEvent::BalanceDelta { asset: ev.asset, delta: ev.delta, event_time: ts(ev.event_time) })
+        }
+        "listenKeyExpired" => Some(UserDataEvent::ListenKeyExpired),
+        _ => None,
+    }
+}
+
+// User data stream: fill và số dư cập nhật OMS theo thời gian thực.
+// Spot hay USDⓈ-M theo `BinanceRestClient::market` của `client`
+#[derive(Debug)]
+pub struct BinanceUserStream {
+    pub client: BinanceRestClient,
+    // mạng của stream, phải khớp với base URL của `client`
+    pub network: BinanceNetwork,
+    pub oms: Arc<Mutex<Oms>>,
+    pub balances: Arc<Mutex<HashMap<String, Balance>>>,
+    pub backoff: BackoffConfig,
+    // stream ít event, ping của client giữ cho idle timeout không bị kích hoạt sai
+    pub heartbeat: HeartbeatConfig,
+    pub connection: ConnectionStatus,
+    events_tx: broadcast::Sender<UserDataEvent>,
+    // portfolio / db / sink cần đủ mọi fill, không bỏ khi consumer chậm
+    fills_tx: Arc<LosslessBus<Fill>>,
+}
+
+impl BinanceUserStream {
+    pub fn new(client: BinanceRestClient, oms: Arc<Mutex<Oms>>) -> Self {
+        let (events_tx, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        let fills_tx = Arc::new(LosslessBus::new(EVENT_CHANNEL_CAPACITY));
+        Self {
+            client,
+            network: BinanceNetwork::default(),
+            oms,
+            balances: Arc::new(Mutex::new(HashMap::new())),
+            backoff: BackoffConfig::default(),
+            heartbeat: HeartbeatConfig::default(),
+            connection: ConnectionStatus::new(),
+            events_tx,
+            fills_tx,
+        }
+    }
+
+    pub fn with_network(mut self, network: BinanceNetwork) -> Self {
+        self.network = network;
+        self
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<UserDataEvent> {
+        self.events_tx.subscribe()
+    }
+
+    // fill mới sau khi OMS đã đối soát (event trùng không được phát lại)
+    pub fn subscribe_fills(&self) -> LosslessReceiver<Fill> {
+        self.fills_tx.subscribe()
+    }
+
+    pub async fn balance(&self, asset: &str) -> Option<Balance> {
+        self.balances.lock().await.get(&asset.to_uppercase()).cloned()
+    }
+
+    pub async fn start(self: Arc<Self>) {
+        let mut reconnect = Reconnector::new(self.backoff.clone(), self.connection.clone());
+        loop {
+            reconnect.connecting();
+            let listen_key = match self.client.create_listen_key().await {
+                Ok(key) => key,
+                Err(e) => {
+                    println!("⚠️ Binance listenKey error: {}, retrying...", e);
+                    if !reconnect.wait().await {
+                        return;
+                    }
+                    continue;
+                }
+            };
+
+            // số dư ban đầu, sau đó chỉ cập nhật bằng event
+            if let Ok(snapshot) = self.client.balances().await {
+                let mut balances = self.balances.lock().await;
+                for b in snapshot {
+                    balances.insert(b.asset.clone(), b);
+                }
+            }
+
+            let url = format!("{}/ws/{}", self.client.market().ws_url(&self.network), listen_key);
+            match transport::connect("binance", &url).await {
+                Ok((ws_stream, _)) => {
+                    reconnect.connected();
+                    println!("📡 Connected to Binance user data stream");
+                    let (mut write, mut read) = ws_stream.split();
+                    let mut heartbeat = Heartbeat::new(&self.heartbeat).on_resubscribe(&self.connection);
+                    let mut keepalive = tokio::time::interval(KEEPALIVE_INTERVAL);
+                    keepalive.tick().await;
+
+                    loop {
+                        tokio::select! {
+                            _ = keepalive.tick() => {
+                                if let Err(e) = self.client.keepalive_listen_key(&listen_key).await {
+                                    println!("⚠️ Binance listenKey keepalive error: {}", e);
+                                }
+                            }
+                            text = heartbeat.next_text(&mut read, &mut write) => {
+                                let Some(text) = text else { break };
+                                if !self.handle_message(&text).await {
+                                    println!("🔁 Binance listenKey expired, recreating...");
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    println!("⚠️ WS Error: {:?}, reconnecting...", e);
+                }
+            }
+            let _ = self.client.close_listen_key(&listen_key).await;
+            if !reconnect.wait().await {
+                return;
+            }
+        }
+    }
+
+    // false khi listenKey hết hạn và cần tạo lại
+    pub(crate) async fn handle_message(&self, text: &str) -> bool {
+        let Some(event) = parse_event(text) else {
+            return true;
+        };
+
+        match &event {
+            UserDataEvent::Order(update) => {
+                match self.oms.lock().await.apply_update(update) {
+                    Ok(Some(fill)) => self.fills_tx.send(fill),
+                    Ok(None) => {}
+                    Err(e) => warn!(client_order_id = %update.client_order_id, order_id = update.order_id, status = ?update.status, error = %e, "OMS rejected order update"),
+                }
+            }
+            UserDataEvent::AccountPosition { balances, .. } => {
+                let mut current = self.balances.lock().await;
+                for b in balances {
+                    current.insert(b.asset.clone(), b.clone());
+                }
+            }
+            // outboundAccountPosition theo sau mang số dư tuyệt đối, delta chỉ để thông báo
+            UserDataEvent::BalanceDelta { .. } => {}
+            UserDataEvent::ListenKeyExpired => {
+                let _ = self.events_tx.send(event);
+                return false;
+            }
+        }
+        let _ = self.events_tx.send(event);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::binance::BinanceCredentials;
+    use crate::testutil::Fuzz;
+    use rust_decimal_macros::dec;
+
+    const EXECUTION_REPORT: &str = r#"{
+        "e": "executionReport", "E": 1499405658658, "s": "ETHBTC", "c": "mUvoqJxFIILMdfAW5iGSOW",
+        "S": "BUY", "o": "LIMIT", "f": "GTC", "q": "1.00000000", "p": "0.10264410", "P": "0.00000000",
+        "F": "0.00000000", "g": -1, "C": "", "x": "TRADE", "X": "PARTIALLY_FILLED", "r": "NONE",
+        "i": 4293153, "l": "0.40000000", "z": "0.40000000", "L": "0.10264410", "n": "0.00000041",
+        "N": "ETH", "T": 1499405658657, "t": 42, "I": 8641984, "w": true, "m": true, "M": false,
+        "O": 1499405658657, "Z": "0.04105764", "Y": "0.04105764", "Q": "0.00000000"
+    }"#;
+
+    #[test]
+    fn test_parse_execution_report() {
+        let Some(UserDataEvent::Order(update)) = parse_event(EXECUTION_REPORT) else {
+            panic!("expected order update");
+        };
+        assert_eq!(update.client_order_id, "mUvoqJxFIILMdfAW5iGSOW");
+        assert_eq!(update.status, OrderStatus::PartiallyFilled);
+        assert_eq!((update.last_filled_qty, update.last_filled_price), (dec!(0.4), dec!(0.1026441)));
+        assert_eq!(update.reject_reason, None);
+
+        // cancel: id gốc nằm ở "C"
+        let cancel = EXECUTION_REPORT
+            .replace(r#""C": """#, r#""C": "orig-1""#)
+            .replace("PARTIALLY_FILLED", "CANCELED");
+        let Some(UserDataEvent::Order(update)) = parse_event(&cancel) else {
+            panic!("expected order update");
+        };
+        assert_eq!((update.client_order_id.as_str(), update.status), ("orig-1", OrderStatus::Canceled));
+    }
+
+    #[test]
+    fn test_parse_futures_events() {
+        let update = r#"{"e":"ORDER_TRADE_UPDATE","E":1568879465651,"T":1568879465650,"o":{
+            "s":"BTCUSDT","c":"bsa-7","S":"SELL","o":"MARKET","f":"GTC","q":"0.002","p":"0","ap":"7103.04",
+            "sp":"0","x":"TRADE","X":"FILLED","i":8886774,"l":"0.002","z":"0.002","L":"7103.04","N":"USDT",
+            "n":"0.0028","T":1568879465650,"t":42,"b":"0","a":"0","m":false,"R":false,"wt":"CONTRACT_PRICE",
+            "ot":"MARKET","ps":"BOTH","cp":false,"rp":"0"}}"#;
+        let Some(UserDataEvent::Order(update)) = parse_event(update) else {
+            panic!("expected order update");
+        };
+        assert_eq!((update.client_order_id.as_str(), update.status, update.order_type), ("bsa-7", OrderStatus::Filled, OrderType::Market));
+        assert_eq!((update.last_filled_qty, update.commission, update.reject_reason), (dec!(0.002), dec!(0.0028), None));
+        assert_eq!(update.event_time.timestamp_millis(), 1568879465651);
+
+        let account = r#"{"e":"ACCOUNT_UPDATE","E":1564745798939,"T":1564745798938,"a":{"m":"ORDER",
+            "B":[{"a":"USDT","wb":"122624.12345678","cw":"100.12345678","bc":"50.12345678"}],"P":[]}}"#;
+        let Some(UserDataEvent::AccountPosition { balances, .. }) = parse_event(account) else {
+            panic!("expected balances");
+        };
+        assert_eq!((balances[0].asset.as_str(), balances[0].total()), ("USDT", dec!(122624.12345678)));
+    }
+
+    #[tokio::test]
+    async fn test_events_update_oms_and_balances() {
+        let client = BinanceRestClient::new(Some(BinanceCredentials::new("key", "secret")));
+        let stream = BinanceUserStream::new(client, Arc::new(Mutex::new(Oms::new("test"))));
+        let mut events = stream.subscribe();
+        let mut fills = stream.subscribe_fills();
+
+        assert!(stream.handle_message(EXECUTION_REPORT).await);
+        assert!(stream.handle_message(EXECUTION_REPORT).await);
+        assert_eq!(fills.try_recv().unwrap().qty, dec!(0.4));
+        assert!(fills.try_recv().is_err());
+        let oms = stream.oms.lock().await;
+        let order = oms.order_by_id(4293153).unwrap();
+        assert_eq!(order.filled_qty, dec!(0.4));
+        assert_eq!(oms.position("ETHBTC").qty, dec!(0.4));
+        drop(oms);
+
+        let account = r#"{"e":"outboundAccountPosition","E":1564034571105,"u":1564034571073,
+            "B":[{"a":"ETH","f":"10000.000000","l":"0.000000"}]}"#;
+        assert!(stream.handle_message(account).await);
+        assert_eq!(stream.balance("eth").await.unwrap().free, dec!(10000));
+
+        assert!(!stream.handle_message(r#"{"e":"listenKeyExpired","E":1576653824250,"listenKey":"x"}"#).await);
+        assert!(matches!(events.recv().await.unwrap(), UserDataEvent::Order(_)));
+    }
+
+    // JSON hỏng / số xấu trong executionReport: không panic, OMS vẫn xử lý tiếp được
+    #[tokio::test]
+    async fn test_fuzz_user_events_do_not_panic() {
+        let client = BinanceRestClient::new(Some(BinanceCredentials::new("key", "secret")));
+        let stream = BinanceUserStream::new(client, Arc::new(Mutex::new(Oms::new("test"))));
+        for seed in 0..300 {
+            let mut fuzz = Fuzz::new(seed);
+            let mut raw = EXECUTION_REPORT.to_string();
+            for field in ["q", "p", "l", "z", "L", "n"] {
+                if fuzz.chance(30) {
+                    let value = fuzz.number(1);
+                    let start = raw.find(&format!(r#""{}": ""#, field)).unwrap() + field.len() + 5;
+                    let end = start + raw[start..].find('"').unwrap();
+                    raw.replace_range(start..end, &value);
+                }
+            }
+            let raw = if fuzz.chance(50) { fuzz.mutate(&raw) } else { raw };
+            parse_event(&raw);
+            stream.handle_message(&raw).await;
+        }
+        assert!(stream.handle_message(EXECUTION_REPORT).await);
+    }
+}
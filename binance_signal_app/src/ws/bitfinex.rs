@@ -0,0 +1,340 @@
+use tokio_tungstenite::tungstenite::Message;
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use std::sync::{Arc, Mutex, MutexGuard};
+use rust_decimal::Decimal;
+use chrono::Utc;
+use async_trait::async_trait;
+use tracing::{info, info_span, warn, Instrument};
+
+use crate::core::{
+    l3::{L3Order, OrderBookL3},
+    orderbook::{parse_decimal, SharedOrderbook, Side},
+};
+use super::{
+    heartbeat::{Heartbeat, HeartbeatConfig},
+    reconnect::{BackoffConfig, ConnectionStatus, Reconnector},
+    transport,
+    BestBidAsk, MalformedCounter, OrderbookFeed,
+};
+
+const WS_URL: &str = "wss://api-pub.bitfinex.com/ws/2";
+// `len` cho phép của channel book
+pub const BOOK_LENGTHS: [usize; 4] = [1, 25, 100, 250];
+// info code: server sắp restart, phải reconnect
+const INFO_RECONNECT: i64 = 20051;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitfinexBook {
+    // P0: level gộp [price, count, amount]
+    Aggregated,
+    // R0: từng order [order_id, price, amount], dựng thêm book L3
+    Raw,
+}
+
+impl BitfinexBook {
+    fn prec(&self) -> &'static str {
+        match self {
+            BitfinexBook::Aggregated => "P0",
+            BitfinexBook::Raw => "R0",
+        }
+    }
+}
+
+// {"event":"subscribed",...}, {"event":"error","msg":..,"code":..}, {"event":"info","code":..}
+#[derive(Debug, Deserialize)]
+struct Event<'a> {
+    event: &'a str,
+    #[serde(default)]
+    code: Option<i64>,
+    #[serde(default)]
+    msg: Option<&'a str>,
+}
+
+// Số của Bitfinex là JSON number, giữ text gốc để parse Decimal không qua f64
+type Entry<'a> = [&'a RawValue; 3];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ApplyResult {
+    Ok,
+    // book vi phạm invariant (crossed, qty 0, ...)
+    Invalid,
+    // server báo lỗi hoặc yêu cầu reconnect
+    Reconnect,
+}
+
+#[derive(Debug, Clone)]
+pub struct BitfinexOrderbookWS {
+    pub symbol: String,
+    pub book: BitfinexBook,
+    pub depth_level: usize,
+    pub orderbook: Arc<SharedOrderbook>,
+    // chỉ có với R0, L2 trong `orderbook` được gộp từ đây
+    pub l3: Option<Arc<Mutex<OrderBookL3>>>,
+    pub backoff: BackoffConfig,
+    pub heartbeat: HeartbeatConfig,
+    pub connection: ConnectionStatus,
+    pub malformed: MalformedCounter,
+}
+
+impl BitfinexOrderbookWS {
+    // symbol dạng "tBTCUSD", depth là `len` (1/25/100/250)
+    pub fn new(symbol: &str, depth_level: usize) -> Self {
+        Self::with_book(symbol, depth_level, BitfinexBook::Aggregated)
+    }
+
+    pub fn new_raw(symbol: &str, depth_level: usize) -> Self {
+        Self::with_book(symbol, depth_level, BitfinexBook::Raw)
+    }
+
+    pub fn with_book(symbol: &str, depth_level: usize, book: BitfinexBook) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            book,
+            depth_level,
+            orderbook: Arc::new(SharedOrderbook::new()),
+            l3: (book == BitfinexBook::Raw).then(|| Arc::new(Mutex::new(OrderBookL3::new()))),
+            backoff: BackoffConfig::default(),
+            // server gửi "hb" mỗi 15s, ping ở tầng ứng dụng là {"event":"ping"}
+            heartbeat: HeartbeatConfig::with_text_ping(r#"{"event":"ping"}"#),
+            connection: ConnectionStatus::new(),
+            malformed: MalformedCounter::default(),
+        }
+    }
+
+    fn subscribe_message(&self) -> String {
+        serde_json::json!({
+            "event": "subscribe",
+            "channel": "book",
+            "symbol": self.symbol,
+            "prec": self.book.prec(),
+            "freq": "F0",
+            "len": self.depth_level.to_string(),
+        })
+        .to_string()
+    }
+
+    // L3 hiện tại (R0), None với P0
+    pub fn l3(&self) -> Option<MutexGuard<'_, OrderBookL3>> {
+        self.l3.as_ref().map(|l3| l3.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    pub async fn start(self: Arc<Self>) {
+        let span = info_span!("bitfinex_ws", symbol = %self.symbol, prec = self.book.prec());
+        self.run().instrument(span).await
+    }
+
+    async fn run(&self) {
+        let mut reconnect = Reconnector::new(self.backoff.clone(), self.connection.clone());
+
+        loop {
+            reconnect.connecting();
+            match transport::connect("bitfinex", WS_URL).await {
+                Ok((ws_stream, _)) => {
+                    reconnect.connected();
+                    info!(url = WS_URL, "connected");
+                    let (mut write, mut read) = ws_stream.split();
+
+                    if let Err(e) = write.send(Message::Text(self.subscribe_message())).await {
+                        warn!(error = ?e, "subscribe failed");
+                    } else {
+                        // không có sequence: subscribe lại sẽ nhận snapshot mới
+                        let mut heartbeat = Heartbeat::new(&self.heartbeat).on_resubscribe(&self.connection);
+                        while let Some(text) = heartbeat.next_text(&mut read, &mut write).await {
+                            let result = self.handle_text(&text);
+                            if result != ApplyResult::Ok {
+                                warn!(?result, "resubscribing");
+                                break;
+                            }
+                        }
+                    }
+                    info!("stream closed");
+                }
+                Err(e) => warn!(error = ?e, "connect failed"),
+            }
+            if !reconnect.wait().await {
+                return;
+            }
+        }
+    }
+
+    // [chanId, [[..], ..]] snapshot, [chanId, [..]] update, [chanId, "hb"] heartbeat
+    fn handle_text(&self, text: &str) -> ApplyResult {
+        if text.starts_with('{') {
+            let Some(event) = self.malformed.parse::<Event>(&self.symbol, text) else {
+                return ApplyResult::Ok;
+            };
+            return match (event.event, event.code) {
+                ("error", code) => {
+                    warn!(?code, msg = event.msg, "bitfinex ws error");
+                    ApplyResult::Reconnect
+                }
+                ("info", Some(INFO_RECONNECT)) => ApplyResult::Reconnect,
+                _ => ApplyResult::Ok,
+            };
+        }
+
+        let Some(msg) = self.malformed.parse::<Vec<&RawValue>>(&self.symbol, text) else {
+            return ApplyResult::Ok;
+        };
+        let Some(payload) = msg.get(1).map(|p| p.get()) else {
+            return ApplyResult::Ok;
+        };
+        if payload.starts_with("[[") || payload == "[]" {
+            match self.malformed.parse::<Vec<Entry>>(&self.symbol, payload) {
+                Some(entries) => self.apply(&entries, true),
+                None => ApplyResult::Ok,
+            }
+        } else if payload.starts_with('[') {
+            match self.malformed.parse::<Entry>(&self.symbol, payload) {
+                Some(entry) => self.apply(&[entry], false),
+                None => ApplyResult::Ok,
+            }
+        } else {
+            // "hb", "cs"
+            ApplyResult::Ok
+        }
+    }
+
+    fn apply(&self, entries: &[Entry], is_snapshot: bool) -> ApplyResult {
+        match self.l3() {
+            Some(mut l3) => self.apply_raw(&mut l3, entries, is_snapshot),
+            None => self.apply_aggregated(entries, is_snapshot),
+        }
+        if self.orderbook.integrity().take_resync() {
+            return ApplyResult::Invalid;
+        }
+        ApplyResult::Ok
+    }
+
+    // count 0 = xoá level (amount 1 là bid, -1 là ask), amount > 0 là bid
+    fn apply_aggregated(&self, entries: &[Entry], is_snapshot: bool) {
+        self.orderbook.update(|ob| {
+            if is_snapshot {
+                ob.clear();
+            }
+            for [price, count, amount] in entries {
+                let (Some(price), Some(count), Some(amount)) =
+                    (parse_decimal(price.get()), parse_decimal(count.get()), parse_decimal(amount.get()))
+                else {
+                    continue;
+                };
+                let side = if amount > Decimal::ZERO { Side::Bid } else { Side::Ask };
+                let qty = if count.is_zero() { Decimal::ZERO } else { amount.abs() };
+                ob.set_level(side, price, qty);
+            }
+            ob.timestamp = Utc::now();
+            true
+        });
+    }
+
+    // price 0 = order bị xoá, chỉ cập nhật lại những level L2 bị chạm
+    fn apply_raw(&self, l3: &mut OrderBookL3, entries: &[Entry], is_snapshot: bool) {
+        if is_snapshot {
+            l3.clear();
+        }
+        let mut touched = Vec::with_capacity(entries.len() * 2);
+        for [id, price, amount] in entries {
+            let (Ok(id), Some(price), Some(amount)) =
+                (id.get().parse::<u64>(), parse_decimal(price.get()), parse_decimal(amount.get()))
+            else {
+                continue;
+            };
+            let old = if price.is_zero() {
+                l3.remove(id)
+            } else {
+                let side = if amount > Decimal::ZERO { Side::Bid } else { Side::Ask };
+                touched.push((side, price));
+                l3.upsert(L3Order { id, side, price, qty: amount.abs() })
+            };
+            if let Some(old) = old {
+                touched.push((old.side, old.price));
+            }
+        }
+        self.orderbook.update(|ob| {
+            if is_snapshot {
+                l3.write_to(ob);
+            } else {
+                for (side, price) in touched {
+                    ob.set_level(side, price, l3.level_qty(side, price));
+                }
+            }
+            ob.timestamp = Utc::now();
+            true
+        });
+    }
+
+    pub async fn get_best_price(&self) -> Option<BestBidAsk> {
+        self.orderbook.best_bid_ask()
+    }
+}
+
+#[async_trait]
+impl OrderbookFeed for BitfinexOrderbookWS {
+    fn exchange(&self) -> &'static str {
+        "bitfinex"
+    }
+
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    fn orderbook(&self) -> &SharedOrderbook {
+        &self.orderbook
+    }
+
+    fn connection(&self) -> Option<&ConnectionStatus> {
+        Some(&self.connection)
+    }
+
+    async fn start(self: Arc<Self>) {
+        BitfinexOrderbookWS::start(self).await
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[tokio::test]
+    async fn test_aggregated_book() {
+        let ws = BitfinexOrderbookWS::new("tBTCUSD", 25);
+        assert!(ws.l3().is_none());
+        assert_eq!(ws.handle_text(r#"{"event":"subscribed","channel":"book","chanId":17,"prec":"P0"}"#), ApplyResult::Ok);
+        let snapshot = r#"[17,[[30000.5,2,1.25],[29999,1,0.5],[30001,3,-2],[30002.1,1,-1e-8]]]"#;
+        assert_eq!(ws.handle_text(snapshot), ApplyResult::Ok);
+        assert_eq!(ws.get_best_price().await, Some(((dec!(30000.5), dec!(1.25)), (dec!(30001), dec!(2)))));
+        assert_eq!(ws.orderbook.snapshot().asks.get(&dec!(30002.1)), Some(&dec!(0.00000001)));
+
+        // count 0, amount 1 = xoá bid
+        assert_eq!(ws.handle_text("[17,[30000.5,0,1]]"), ApplyResult::Ok);
+        assert_eq!(ws.handle_text(r#"[17,"hb"]"#), ApplyResult::Ok);
+        assert_eq!(ws.get_best_price().await, Some(((dec!(29999), dec!(0.5)), (dec!(30001), dec!(2)))));
+
+        // bid đè lên ask
+        assert_eq!(ws.handle_text("[17,[30001.5,1,1]]"), ApplyResult::Invalid);
+        assert_eq!(ws.handle_text(r#"{"event":"info","code":20051,"msg":"Stop/Restart Websocket Server"}"#), ApplyResult::Reconnect);
+    }
+
+    #[tokio::test]
+    async fn test_raw_book_builds_l3_and_l2() {
+        let ws = BitfinexOrderbookWS::new_raw("tBTCUSD", 25);
+        let snapshot = "[5,[[101,100,0.5],[102,100,1.5],[103,99,2],[201,101,-1],[202,101,-3]]]";
+        assert_eq!(ws.handle_text(snapshot), ApplyResult::Ok);
+        assert_eq!(ws.get_best_price().await, Some(((dec!(100), dec!(2)), (dec!(101), dec!(4)))));
+        assert_eq!(ws.l3().unwrap().queue_ahead(102), Some((1, dec!(0.5))));
+
+        // order 101 bị xoá, 202 chuyển giá
+        assert_eq!(ws.handle_text("[5,[101,0,1]]"), ApplyResult::Ok);
+        assert_eq!(ws.handle_text("[5,[202,101.5,-3]]"), ApplyResult::Ok);
+        assert_eq!(ws.get_best_price().await, Some(((dec!(100), dec!(1.5)), (dec!(101), dec!(1)))));
+        let snap = ws.orderbook.snapshot();
+        assert_eq!(snap.asks.get(&dec!(101.5)), Some(&dec!(3)));
+
+        let l3 = ws.l3().unwrap();
+        assert_eq!((l3.len(), l3.queue_ahead(102)), (4, Some((0, Decimal::ZERO))));
+    }
+}
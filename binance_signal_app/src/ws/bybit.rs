@@ -0,0 +1,244 @@
+use tokio_tungstenite::tungstenite::Message;
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use std::sync::Arc;
+use chrono::Utc;
+use async_trait::async_trait;
+
+use crate::core::orderbook::{SharedOrderbook, Side};
+use super::{
+    heartbeat::{Heartbeat, HeartbeatConfig},
+    reconnect::{BackoffConfig, ConnectionStatus, Reconnector},
+    transport,
+    BestBidAsk, OrderbookFeed,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BybitCategory {
+    Spot,
+    // USDT perpetual
+    Linear,
+}
+
+impl BybitCategory {
+    fn ws_url(&self) -> &'static str {
+        match self {
+            BybitCategory::Spot => "wss://stream.bybit.com/v5/public/spot",
+            BybitCategory::Linear => "wss://stream.bybit.com/v5/public/linear",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct BookMessage {
+    topic: String,
+    #[serde(rename = "type")]
+    kind: String,
+    data: BookData,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct BookData {
+    #[serde(rename = "b")]
+    bids: Vec<[String; 2]>,
+    #[serde(rename = "a")]
+    asks: Vec<[String; 2]>,
+    #[serde(rename = "u")]
+    update_id: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct BybitOrderbookWS {
+    pub symbol: String,
+    pub category: BybitCategory,
+    pub depth_level: usize,
+    pub orderbook: Arc<SharedOrderbook>,
+    pub backoff: BackoffConfig,
+    pub heartbeat: HeartbeatConfig,
+    pub connection: ConnectionStatus,
+}
+
+impl BybitOrderbookWS {
+    pub fn new(symbol: &str, category: BybitCategory) -> Self {
+        Self::with_depth(symbol, category, 50)
+    }
+
+    // depth hợp lệ: spot 1/50/200, linear 1/50/200/500
+    pub fn with_depth(symbol: &str, category: BybitCategory, depth_level: usize) -> Self {
+        Self {
+            symbol: symbol.to_uppercase(),
+            category,
+            depth_level,
+            orderbook: Arc::new(SharedOrderbook::new()),
+            backoff: BackoffConfig::default(),
+            // Bybit khuyến nghị gửi {"op":"ping"} mỗi 20s
+            heartbeat: HeartbeatConfig::with_text_ping(r#"{"op":"ping"}"#),
+            connection: ConnectionStatus::new(),
+        }
+    }
+
+    fn topic(&self) -> String {
+        format!("orderbook.{}.{}", self.depth_level, self.symbol)
+    }
+
+    fn op_message(&self, op: &str) -> String {
+        serde_json::json!({ "op": op, "args": [self.topic()] }).to_string()
+    }
+
+    pub async fn start(self: Arc<Self>) {
+        let mut reconnect = Reconnector::new(self.backoff.clone(), self.connection.clone());
+
+        loop {
+            reconnect.connecting();
+            match transport::connect("bybit", self.category.ws_url()).await {
+                Ok((ws_stream, _)) => {
+                    reconnect.connected();
+                    println!("📡 Connected to Bybit WS for {} ({:?})", self.symbol, self.category);
+                    let (mut write, mut read) = ws_stream.split();
+
+                    if let Err(e) = write.send(Message::Text(self.op_message("subscribe"))).await {
+                        println!("⚠️ Bybit subscribe error: {:?}", e);
+                    } else {
+                        let mut heartbeat = Heartbeat::new(&self.heartbeat).on_resubscribe(&self.connection);
+                        while let Some(text) = heartbeat.next_text(&mut read, &mut write).await {
+                            let Ok(data) = serde_json::from_str::<BookMessage>(&text) else { continue };
+
+                            if !self.process_message(data).await {
+                                // sequence reset/gap -> unsubscribe + subscribe để nhận snapshot mới
+                                println!("🔁 Bybit sequence gap for {}, resubscribing...", self.symbol);
+                                let resub = [self.op_message("unsubscribe"), self.op_message("subscribe")];
+                                let mut ok = true;
+                                for m in resub {
+                                    if write.send(Message::Text(m)).await.is_err() {
+                                        ok = false;
+                                        break;
+                                    }
+                                }
+                                if !ok {
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    println!("⚠️ WS Error: {:?}, reconnecting...", e);
+                }
+            }
+            if !reconnect.wait().await {
+                return;
+            }
+        }
+    }
+
+    // false khi delta không nối tiếp update_id hiện tại
+    async fn process_message(&self, msg: BookMessage) -> bool {
+        if msg.topic != self.topic() {
+            return true;
+        }
+
+        // u = 1 nghĩa là service restart, Bybit gửi lại như snapshot
+        let is_snapshot = msg.kind == "snapshot" || msg.data.update_id == 1;
+        let mut in_sequence = true;
+
+        self.orderbook
+            .update(|ob| {
+                if is_snapshot {
+                    ob.clear();
+                } else if ob.last_update_id == 0 {
+                    // đang chờ snapshot sau khi resubscribe
+                    return false;
+                } else if msg.data.update_id != ob.last_update_id + 1 {
+                    ob.last_update_id = 0;
+                    in_sequence = false;
+                    return false;
+                }
+
+                for [price, qty] in &msg.data.bids {
+                    ob.set_level_str(Side::Bid, price, qty);
+                }
+                for [price, qty] in &msg.data.asks {
+                    ob.set_level_str(Side::Ask, price, qty);
+                }
+
+                ob.last_update_id = msg.data.update_id;
+                ob.timestamp = Utc::now();
+                true
+            });
+        in_sequence
+    }
+
+    pub async fn get_best_price(&self) -> Option<BestBidAsk> {
+        self.orderbook.best_bid_ask()
+    }
+}
+
+#[async_trait]
+impl OrderbookFeed for BybitOrderbookWS {
+    fn exchange(&self) -> &'static str {
+        "bybit"
+    }
+
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    fn orderbook(&self) -> &SharedOrderbook {
+        &self.orderbook
+    }
+
+    fn connection(&self) -> Option<&ConnectionStatus> {
+        Some(&self.connection)
+    }
+
+    async fn start(self: Arc<Self>) {
+        BybitOrderbookWS::start(self).await
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn message(kind: &str, u: u64, bids: &[(&str, &str)], asks: &[(&str, &str)]) -> BookMessage {
+        let to_levels = |levels: &[(&str, &str)]| -> Vec<[String; 2]> {
+            levels.iter().map(|(p, q)| [p.to_string(), q.to_string()]).collect()
+        };
+        BookMessage {
+            topic: "orderbook.50.BTCUSDT".into(),
+            kind: kind.into(),
+            data: BookData { bids: to_levels(bids), asks: to_levels(asks), update_id: u },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_and_delta() {
+        let ob = BybitOrderbookWS::new("btcusdt", BybitCategory::Linear);
+        assert!(ob.process_message(message("snapshot", 100, &[("30000.0", "1.0")], &[("30000.5", "2.0")])).await);
+        assert!(ob.process_message(message("delta", 101, &[("30000.2", "0.5")], &[("30000.5", "0")])).await);
+
+        let snap = ob.orderbook.snapshot();
+        assert_eq!(snap.best_bid(), Some((dec!(30000.2), dec!(0.5))));
+        assert!(snap.asks.is_empty());
+        assert_eq!(snap.last_update_id, 101);
+    }
+
+    #[tokio::test]
+    async fn test_delta_gap_requests_resubscribe() {
+        let ob = BybitOrderbookWS::new("BTCUSDT", BybitCategory::Spot);
+        // delta trước khi có snapshot bị bỏ qua
+        assert!(ob.process_message(message("delta", 5, &[("9.0", "1.0")], &[])).await);
+        assert!(ob.get_best_price().await.is_none());
+
+        assert!(ob.process_message(message("snapshot", 10, &[("1.0", "1.0")], &[("1.1", "1.0")])).await);
+        assert!(!ob.process_message(message("delta", 12, &[], &[])).await);
+        // các delta tiếp theo trong lúc chờ snapshot mới không trigger resubscribe lần nữa
+        assert!(ob.process_message(message("delta", 13, &[], &[])).await);
+
+        // service restart: u = 1 được coi như snapshot
+        assert!(ob.process_message(message("delta", 1, &[("2.0", "1.0")], &[("2.1", "1.0")])).await);
+        assert_eq!(ob.get_best_price().await, Some(((dec!(2.0), dec!(1.0)), (dec!(2.1), dec!(1.0)))));
+    }
+}
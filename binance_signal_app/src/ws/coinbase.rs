@@ -0,0 +1,234 @@
+use tokio_tungstenite::tungstenite::Message;
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use std::sync::Arc;
+use chrono::Utc;
+use async_trait::async_trait;
+use tracing::{info, warn};
+
+use crate::core::orderbook::{SharedOrderbook, Side};
+use super::{
+    heartbeat::{Heartbeat, HeartbeatConfig},
+    reconnect::{BackoffConfig, ConnectionStatus, Reconnector},
+    transport,
+    BestBidAsk, OrderbookFeed,
+};
+
+const WS_URL: &str = "wss://advanced-trade-ws.coinbase.com";
+
+#[derive(Debug, Clone, Deserialize)]
+struct MessageHeader {
+    channel: String,
+    #[serde(default)]
+    sequence_num: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct L2Message {
+    events: Vec<L2Event>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct L2Event {
+    #[serde(rename = "type")]
+    kind: String,
+    product_id: String,
+    updates: Vec<L2Update>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct L2Update {
+    side: String,
+    price_level: String,
+    new_quantity: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct CoinbaseOrderbookWS {
+    pub product_id: String,
+    pub orderbook: Arc<SharedOrderbook>,
+    pub backoff: BackoffConfig,
+    pub heartbeat: HeartbeatConfig,
+    pub connection: ConnectionStatus,
+}
+
+impl CoinbaseOrderbookWS {
+    // product_id dạng "BTC-USD"
+    pub fn new(product_id: &str) -> Self {
+        Self {
+            product_id: product_id.to_uppercase(),
+            orderbook: Arc::new(SharedOrderbook::new()),
+            backoff: BackoffConfig::default(),
+            heartbeat: HeartbeatConfig::default(),
+            connection: ConnectionStatus::new(),
+        }
+    }
+
+    fn subscribe_messages(&self) -> Vec<String> {
+        ["level2", "heartbeats"]
+            .iter()
+            .map(|channel| {
+                serde_json::json!({
+                    "type": "subscribe",
+                    "product_ids": [self.product_id],
+                    "channel": channel,
+                })
+                .to_string()
+            })
+            .collect()
+    }
+
+    pub async fn start(self: Arc<Self>) {
+        let mut reconnect = Reconnector::new(self.backoff.clone(), self.connection.clone());
+
+        loop {
+            reconnect.connecting();
+            match transport::connect("coinbase", WS_URL).await {
+                Ok((ws_stream, _)) => {
+                    reconnect.connected();
+                    info!(venue = "coinbase", symbol = %self.product_id, "connected");
+                    let (mut write, mut read) = ws_stream.split();
+
+                    let mut subscribed = true;
+                    for sub in self.subscribe_messages() {
+                        if let Err(e) = write.send(Message::Text(sub)).await {
+                            warn!(venue = "coinbase", symbol = %self.product_id, error = ?e, "subscribe failed");
+                            subscribed = false;
+                            break;
+                        }
+                    }
+
+                    if subscribed {
+                        let mut last_seq: Option<u64> = None;
+                        let mut heartbeat = Heartbeat::new(&self.heartbeat).on_resubscribe(&self.connection);
+                        while let Some(text) = heartbeat.next_text(&mut read, &mut write).await {
+                            let Ok(header) = serde_json::from_str::<MessageHeader>(&text) else {
+                                continue;
+                            };
+                            // sequence_num tăng liên tục trên mỗi connection, mất message -> resync
+                            if let Some(prev) = last_seq
+                                && header.sequence_num != prev + 1
+                            {
+                                warn!(venue = "coinbase", symbol = %self.product_id, prev, seq = header.sequence_num, "sequence gap, resyncing");
+                                break;
+                            }
+                            last_seq = Some(header.sequence_num);
+
+                            if header.channel == "l2_data"
+                                && let Ok(data) = serde_json::from_str::<L2Message>(&text)
+                            {
+                                self.process_l2(data).await;
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    warn!(venue = "coinbase", symbol = %self.product_id, error = ?e, "connect failed, reconnecting");
+                }
+            }
+            if !reconnect.wait().await {
+                return;
+            }
+        }
+    }
+
+    async fn process_l2(&self, data: L2Message) {
+        self.orderbook
+            .update(|ob| {
+                for event in &data.events {
+                    if event.product_id != self.product_id {
+                        continue;
+                    }
+                    if event.kind == "snapshot" {
+                        ob.clear();
+                    }
+                    for u in &event.updates {
+                        let side = match u.side.as_str() {
+                            "bid" => Side::Bid,
+                            "offer" | "ask" => Side::Ask,
+                            _ => continue,
+                        };
+                        ob.set_level_str(side, &u.price_level, &u.new_quantity);
+                    }
+                }
+
+                ob.timestamp = Utc::now();
+                true
+            });
+    }
+
+    pub async fn get_best_price(&self) -> Option<BestBidAsk> {
+        self.orderbook.best_bid_ask()
+    }
+}
+
+#[async_trait]
+impl OrderbookFeed for CoinbaseOrderbookWS {
+    fn exchange(&self) -> &'static str {
+        "coinbase"
+    }
+
+    fn symbol(&self) -> &str {
+        &self.product_id
+    }
+
+    fn orderbook(&self) -> &SharedOrderbook {
+        &self.orderbook
+    }
+
+    fn connection(&self) -> Option<&ConnectionStatus> {
+        Some(&self.connection)
+    }
+
+    async fn start(self: Arc<Self>) {
+        CoinbaseOrderbookWS::start(self).await
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[tokio::test]
+    async fn test_snapshot_then_update() {
+        let ob = CoinbaseOrderbookWS::new("btc-usd");
+        let snapshot = r#"
+        {
+            "channel": "l2_data",
+            "timestamp": "2023-02-09T20:32:50.714964855Z",
+            "sequence_num": 0,
+            "events": [{
+                "type": "snapshot",
+                "product_id": "BTC-USD",
+                "updates": [
+                    {"side": "bid", "event_time": "1970-01-01T00:00:00Z", "price_level": "21921.73", "new_quantity": "0.06317902"},
+                    {"side": "bid", "event_time": "1970-01-01T00:00:00Z", "price_level": "21921.30", "new_quantity": "0.5"},
+                    {"side": "offer", "event_time": "1970-01-01T00:00:00Z", "price_level": "21922.10", "new_quantity": "1.2"}
+                ]
+            }]
+        }
+        "#;
+        ob.process_l2(serde_json::from_str(snapshot).unwrap()).await;
+
+        let update = r#"
+        {
+            "channel": "l2_data",
+            "sequence_num": 1,
+            "events": [{
+                "type": "update",
+                "product_id": "BTC-USD",
+                "updates": [
+                    {"side": "bid", "event_time": "2023-02-09T20:32:50.714964855Z", "price_level": "21921.73", "new_quantity": "0"}
+                ]
+            }]
+        }
+        "#;
+        ob.process_l2(serde_json::from_str(update).unwrap()).await;
+
+        let ((bid_p, _), (ask_p, ask_q)) = ob.get_best_price().await.unwrap();
+        assert_eq!(bid_p, dec!(21921.30));
+        assert_eq!((ask_p, ask_q), (dec!(21922.10), dec!(1.2)));
+    }
+}
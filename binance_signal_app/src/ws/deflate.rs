@@ -0,0 +1,423 @@
+use flate2::{Decompress, FlushDecompress, Status};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    io,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, LazyLock, Mutex,
+    },
+    task::{Context, Poll},
+};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+// Header client gửi khi handshake. Client không nén frame gửi đi (chỉ subscribe / ping)
+// nên không cần client_max_window_bits
+pub const OFFER: &str = "permessage-deflate";
+
+// đuôi bị server cắt khỏi mỗi message nén (RFC 7692 7.2.2)
+const TAIL: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+
+// Frame được giải nén trước khi tới tungstenite nên giới hạn của nó không chặn kịp:
+// frame (trên dây lẫn sau giải nén) lớn hơn mặc định max_frame_size của tungstenite bị từ chối
+const MAX_FRAME_SIZE: usize = 16 << 20;
+// response handshake không thể dài hơn thế này
+const MAX_HANDSHAKE_SIZE: usize = 64 << 10;
+
+fn too_large(what: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("{} exceeds {} bytes", what, MAX_FRAME_SIZE))
+}
+
+// Byte theo sàn: nén trên dây vs sau khi giải nén, và frame không nén
+#[derive(Debug, Default)]
+pub struct CompressionStats {
+    negotiated: AtomicU64,
+    compressed_bytes: AtomicU64,
+    inflated_bytes: AtomicU64,
+    plain_bytes: AtomicU64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CompressionSnapshot {
+    pub venue: &'static str,
+    // số kết nối server chấp nhận permessage-deflate
+    pub negotiated: u64,
+    pub compressed_bytes: u64,
+    pub inflated_bytes: u64,
+    pub plain_bytes: u64,
+    // inflated / compressed, None nếu chưa có message nén
+    pub ratio: Option<f64>,
+}
+
+impl CompressionStats {
+    pub fn snapshot(&self, venue: &'static str) -> CompressionSnapshot {
+        let compressed_bytes = self.compressed_bytes.load(Ordering::Relaxed);
+        let inflated_bytes = self.inflated_bytes.load(Ordering::Relaxed);
+        CompressionSnapshot {
+            venue,
+            negotiated: self.negotiated.load(Ordering::Relaxed),
+            compressed_bytes,
+            inflated_bytes,
+            plain_bytes: self.plain_bytes.load(Ordering::Relaxed),
+            ratio: (compressed_bytes > 0).then(|| inflated_bytes as f64 / compressed_bytes as f64),
+        }
+    }
+}
+
+static STATS: LazyLock<Mutex<BTreeMap<&'static str, Arc<CompressionStats>>>> = LazyLock::new(Default::default);
+
+pub fn stats(venue: &'static str) -> Arc<CompressionStats> {
+    STATS.lock().unwrap_or_else(|e| e.into_inner()).entry(venue).or_default().clone()
+}
+
+pub fn snapshot() -> Vec<CompressionSnapshot> {
+    STATS.lock().unwrap_or_else(|e| e.into_inner()).iter().map(|(venue, s)| s.snapshot(venue)).collect()
+}
+
+// Tham số server trả về trong Sec-WebSocket-Extensions, None nếu server không nhận
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Negotiated {
+    // server reset dictionary sau mỗi message
+    pub server_no_context_takeover: bool,
+}
+
+impl Negotiated {
+    pub fn parse(header: &str) -> Option<Self> {
+        header.split(',').find_map(|ext| {
+            let mut params = ext.split(';').map(str::trim);
+            (params.next()? == OFFER).then(|| Negotiated {
+                server_no_context_takeover: params.any(|p| p == "server_no_context_takeover"),
+            })
+        })
+    }
+}
+
+// Giải nén từng frame: frame đầu của message nén có RSV1, các frame continuation sau đó
+// thuộc cùng message. Frame được viết lại không RSV1 với payload đã giải nén
+#[derive(Debug)]
+struct Inflater {
+    decompress: Decompress,
+    no_context_takeover: bool,
+    // đang ở giữa một message bị chia frame, Some(nén hay không)
+    fragmented: Option<bool>,
+}
+
+impl Inflater {
+    fn new(negotiated: Negotiated) -> Self {
+        Self { decompress: Decompress::new(false), no_context_takeover: negotiated.server_no_context_takeover, fragmented: None }
+    }
+
+    // Some(số byte đã dùng) khi `raw` có đủ một frame, None nếu cần đọc thêm
+    fn process(&mut self, raw: &[u8], out: &mut Vec<u8>, stats: &CompressionStats) -> io::Result<Option<usize>> {
+        let Some((header_len, payload_len)) = frame_len(raw)? else {
+            return Ok(None);
+        };
+        let total = header_len + payload_len;
+        if raw.len() < total {
+            return Ok(None);
+        }
+        let (b0, b1) = (raw[0], raw[1]);
+        let (fin, rsv1, opcode, masked) = (b0 & 0x80 != 0, b0 & 0x40 != 0, b0 & 0x0f, b1 & 0x80 != 0);
+        let compressed = match opcode {
+            0x1 | 0x2 => rsv1,
+            0x0 => self.fragmented.unwrap_or(false),
+            // control frame không bao giờ nén
+            _ => false,
+        };
+        if opcode < 0x8 {
+            self.fragmented = (!fin).then_some(compressed);
+        }
+        // frame từ server không được mask, frame lạ thì để tungstenite báo lỗi
+        if !compressed || masked {
+            out.extend_from_slice(&raw[..total]);
+            stats.plain_bytes.fetch_add(payload_len as u64, Ordering::Relaxed);
+            return Ok(Some(total));
+        }
+
+        let payload = &raw[header_len..total];
+        let mut inflated = Vec::with_capacity(payload.len() * 4);
+        self.inflate(payload, &mut inflated)?;
+        if fin {
+            self.inflate(&TAIL, &mut inflated)?;
+            if self.no_context_takeover {
+                self.decompress.reset(false);
+            }
+        }
+        stats.compressed_bytes.fetch_add(payload_len as u64, Ordering::Relaxed);
+        stats.inflated_bytes.fetch_add(inflated.len() as u64, Ordering::Relaxed);
+
+        write_header(out, b0 & !0x40, inflated.len());
+        out.extend_from_slice(&inflated);
+        Ok(Some(total))
+    }
+
+    fn inflate(&mut self, mut input: &[u8], out: &mut Vec<u8>) -> io::Result<()> {
+        loop {
+            if out.capacity() - out.len() < 1024 {
+                out.reserve(out.capacity().max(4096));
+            }
+            let before = self.decompress.total_in();
+            let status = self
+                .decompress
+                .decompress_vec(input, out, FlushDecompress::Sync)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            if out.len() > MAX_FRAME_SIZE {
+                return Err(too_large("inflated frame"));
+            }
+            input = &input[(self.decompress.total_in() - before) as usize..];
+            // hết input và decompress không dùng hết chỗ trống thì đã xả xong
+            if status == Status::StreamEnd || (input.is_empty() && out.len() < out.capacity()) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+// (độ dài header, độ dài payload) nếu đã đủ byte để đọc header, lỗi nếu payload quá
+// MAX_FRAME_SIZE (không chờ gom đủ một frame khổng lồ vào `raw`)
+fn frame_len(raw: &[u8]) -> io::Result<Option<(usize, usize)>> {
+    let Some(&b1) = raw.get(1) else { return Ok(None) };
+    let mask = if b1 & 0x80 != 0 { 4 } else { 0 };
+    let (ext, len) = match b1 & 0x7f {
+        126 => match raw.get(2..4) {
+            Some(b) => (2, u16::from_be_bytes([b[0], b[1]]) as u64),
+            None => return Ok(None),
+        },
+        127 => match raw.get(2..10).and_then(|b| <[u8; 8]>::try_from(b).ok()) {
+            Some(b) => (8, u64::from_be_bytes(b)),
+            None => return Ok(None),
+        },
+        n => (0, n as u64),
+    };
+    match usize::try_from(len) {
+        Ok(len) if len <= MAX_FRAME_SIZE => Ok(Some((2 + ext + mask, len))),
+        _ => Err(too_large("frame")),
+    }
+}
+
+fn write_header(out: &mut Vec<u8>, b0: u8, len: usize) {
+    out.push(b0);
+    match len {
+        0..=125 => out.push(len as u8),
+        126..=0xffff => {
+            out.push(126);
+            out.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        _ => {
+            out.push(127);
+            out.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Phase {
+    // đang đọc response HTTP của handshake, chưa biết server có nhận nén không
+    Handshake,
+    Frames(Option<Inflater>),
+}
+
+// Nằm giữa TCP/TLS và tungstenite (bản đang dùng không hỗ trợ RSV1): tự đọc response
+// handshake để biết có nén không, sau đó giải nén frame trước khi tungstenite thấy.
+// Chiều ghi đi thẳng xuống `inner`
+#[derive(Debug)]
+pub struct InflateStream<S> {
+    inner: S,
+    phase: Phase,
+    raw: Vec<u8>,
+    out: Vec<u8>,
+    out_pos: usize,
+    stats: Arc<CompressionStats>,
+}
+
+impl<S> InflateStream<S> {
+    pub fn new(inner: S, stats: Arc<CompressionStats>) -> Self {
+        Self { inner, phase: Phase::Handshake, raw: Vec::new(), out: Vec::new(), out_pos: 0, stats }
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    // đưa byte trong `raw` sang `out` được bao nhiêu thì đưa, false nếu cần đọc thêm
+    fn drain_raw(&mut self) -> io::Result<bool> {
+        let mut progressed = false;
+        loop {
+            match &mut self.phase {
+                Phase::Handshake => {
+                    let Some(end) = self.raw.windows(4).position(|w| w == b"\r\n\r\n").map(|i| i + 4) else {
+                        if self.raw.len() > MAX_HANDSHAKE_SIZE {
+                            return Err(io::Error::new(io::ErrorKind::InvalidData, "handshake response too large"));
+                        }
+                        return Ok(progressed);
+                    };
+                    let head = String::from_utf8_lossy(&self.raw[..end]);
+                    let negotiated = head
+                        .lines()
+                        .filter_map(|l| l.split_once(':'))
+                        .filter(|(name, _)| name.trim().eq_ignore_ascii_case("sec-websocket-extensions"))
+                        .find_map(|(_, value)| Negotiated::parse(value));
+                    if negotiated.is_some() {
+                        self.stats.negotiated.fetch_add(1, Ordering::Relaxed);
+                    }
+                    self.out.extend(self.raw.drain(..end));
+                    self.phase = Phase::Frames(negotiated.map(Inflater::new));
+                    progressed = true;
+                }
+                Phase::Frames(None) => {
+                    // không nén thì chỉ đếm và chuyển nguyên
+                    self.stats.plain_bytes.fetch_add(self.raw.len() as u64, Ordering::Relaxed);
+                    self.out.append(&mut self.raw);
+                    return Ok(progressed || !self.out.is_empty());
+                }
+                Phase::Frames(Some(inflater)) => {
+                    let Some(used) = inflater.process(&self.raw, &mut self.out, &self.stats)? else {
+                        return Ok(progressed);
+                    };
+                    self.raw.drain(..used);
+                    progressed = true;
+                }
+            }
+        }
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for InflateStream<S> {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            if this.out_pos < this.out.len() {
+                let n = buf.remaining().min(this.out.len() - this.out_pos);
+                buf.put_slice(&this.out[this.out_pos..this.out_pos + n]);
+                this.out_pos += n;
+                if this.out_pos == this.out.len() {
+                    this.out.clear();
+                    this.out_pos = 0;
+                }
+                return Poll::Ready(Ok(()));
+            }
+            if this.drain_raw()? && !this.out.is_empty() {
+                continue;
+            }
+            let mut chunk = [0u8; 16 * 1024];
+            let mut read = ReadBuf::new(&mut chunk);
+            match Pin::new(&mut this.inner).poll_read(cx, &mut read) {
+                Poll::Ready(Ok(())) if read.filled().is_empty() => {
+                    // EOF: frame dở dang thì trả nguyên để tungstenite báo lỗi
+                    this.out.append(&mut this.raw);
+                    if this.out.is_empty() {
+                        return Poll::Ready(Ok(()));
+                    }
+                }
+                Poll::Ready(Ok(())) => this.raw.extend_from_slice(read.filled()),
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for InflateStream<S> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{Compress, Compression, FlushCompress};
+    use tokio::io::AsyncReadExt;
+
+    // nén một message như server: raw deflate, sync flush, bỏ đuôi 00 00 ff ff
+    fn deflate(compress: &mut Compress, data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(data.len() + 64);
+        compress.compress_vec(data, &mut out, FlushCompress::Sync).unwrap();
+        assert!(out.ends_with(&TAIL));
+        out.truncate(out.len() - TAIL.len());
+        out
+    }
+
+    fn frame(b0: u8, payload: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_header(&mut out, b0, payload.len());
+        out.extend_from_slice(payload);
+        out
+    }
+
+    #[tokio::test]
+    async fn test_inflates_frames_after_handshake() {
+        let head = b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nSec-WebSocket-Extensions: permessage-deflate; server_max_window_bits=15\r\n\r\n";
+        let book = r#"{"bids":[["100.0","1.0"]],"asks":[["101.0","2.0"]]}"#.repeat(20);
+        let mut compress = Compress::new(Compression::default(), false);
+        let first = deflate(&mut compress, book.as_bytes());
+        // message thứ hai dùng lại dictionary (context takeover), chia làm hai frame
+        let second = deflate(&mut compress, book.as_bytes());
+        let (a, b) = second.split_at(second.len() / 2);
+
+        let mut wire = head.to_vec();
+        wire.extend(frame(0x80 | 0x40 | 0x1, &first));
+        wire.extend(frame(0x89, b"hb"));
+        wire.extend(frame(0x40 | 0x1, a));
+        wire.extend(frame(0x80, b));
+        wire.extend(frame(0x81, b"plain"));
+
+        let stats = Arc::new(CompressionStats::default());
+        let mut stream = InflateStream::new(&wire[..], stats.clone());
+        let mut got = Vec::new();
+        stream.read_to_end(&mut got).await.unwrap();
+
+        let mut expected = head.to_vec();
+        expected.extend(frame(0x81, book.as_bytes()));
+        expected.extend(frame(0x89, b"hb"));
+        let mut continuation = Vec::new();
+        // hai frame sau khi giải nén, ghép lại phải ra nguyên message
+        let rest = &got[expected.len()..];
+        let (h1, l1) = frame_len(rest).unwrap().unwrap();
+        continuation.extend_from_slice(&rest[h1..h1 + l1]);
+        let (h2, l2) = frame_len(&rest[h1 + l1..]).unwrap().unwrap();
+        continuation.extend_from_slice(&rest[h1 + l1 + h2..h1 + l1 + h2 + l2]);
+        assert_eq!(&got[..expected.len()], &expected[..]);
+        assert_eq!((rest[0], rest[h1 + l1]), (0x01, 0x80));
+        assert_eq!(continuation, book.as_bytes());
+        assert!(got.ends_with(&frame(0x81, b"plain")));
+
+        let snap = stats.snapshot("test");
+        assert_eq!((snap.negotiated, snap.inflated_bytes), (1, 2 * book.len() as u64));
+        assert_eq!(snap.compressed_bytes, (first.len() + second.len()) as u64);
+        assert!(snap.ratio.unwrap() > 5.0);
+        assert_eq!(Negotiated::parse("x-foo, permessage-deflate; server_no_context_takeover").map(|n| n.server_no_context_takeover), Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_oversized_frame_rejected() {
+        let head = b"HTTP/1.1 101 Switching Protocols\r\nSec-WebSocket-Extensions: permessage-deflate\r\n\r\n".to_vec();
+        let read = |wire: Vec<u8>| async move {
+            let mut stream = InflateStream::new(&wire[..], Arc::new(CompressionStats::default()));
+            stream.read_to_end(&mut Vec::new()).await
+        };
+
+        // header khai payload 2^40 byte: báo lỗi ngay, không chờ đọc hết
+        let mut wire = head.clone();
+        wire.extend([0x82, 127]);
+        wire.extend((1u64 << 40).to_be_bytes());
+        assert_eq!(read(wire).await.unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        // vài chục KB nén giải ra quá MAX_FRAME_SIZE
+        let mut compress = Compress::new(Compression::best(), false);
+        let bomb = deflate(&mut compress, &vec![0u8; MAX_FRAME_SIZE + 1]);
+        assert!(bomb.len() < 64 << 10);
+        let mut wire = head;
+        wire.extend(frame(0x80 | 0x40 | 0x2, &bomb));
+        assert_eq!(read(wire).await.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,350 @@
+use tokio_tungstenite::tungstenite::{self, Message};
+use futures_util::{Sink, SinkExt, Stream, StreamExt};
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use std::{sync::Arc, time::Duration};
+use chrono::Utc;
+use async_trait::async_trait;
+use tracing::{debug, info, info_span, warn, Instrument};
+
+use crate::core::orderbook::{SharedOrderbook, Side};
+use crate::net;
+use super::{
+    heartbeat::{Heartbeat, HeartbeatConfig},
+    reconnect::{BackoffConfig, ConnectionStatus, Reconnector},
+    transport,
+    BestBidAsk, MalformedCounter, OrderbookFeed,
+};
+
+const WS_URL: &str = "wss://api.gateio.ws/ws/v4/";
+const REST_URL: &str = "https://api.gateio.ws/api/v4";
+// `limit` tối đa của `/spot/order_book`
+pub const MAX_SNAPSHOT_LIMIT: usize = 100;
+
+// `/spot/order_book?with_id=true`
+#[derive(Debug, Clone, Deserialize)]
+struct OrderBookSnapshot<'a> {
+    id: u64,
+    #[serde(borrow)]
+    bids: Vec<[&'a str; 2]>,
+    #[serde(borrow)]
+    asks: Vec<[&'a str; 2]>,
+}
+
+// {"time":..,"channel":"spot.order_book_update","event":"update","result":{...}},
+// ack subscribe cũng có `result` nên phải xét `event`
+#[derive(Debug, Deserialize)]
+struct Envelope<'a> {
+    #[serde(default)]
+    channel: Option<&'a str>,
+    #[serde(default)]
+    event: Option<&'a str>,
+    #[serde(borrow, default)]
+    error: Option<&'a RawValue>,
+    #[serde(borrow, default)]
+    result: Option<&'a RawValue>,
+}
+
+// giống diff event của Binance: U..u là khoảng update id trong message
+#[derive(Debug, Clone, Deserialize)]
+struct BookUpdate<'a> {
+    // ms
+    #[serde(default)]
+    t: Option<i64>,
+    #[serde(rename = "U")]
+    first_update_id: u64,
+    #[serde(rename = "u")]
+    final_update_id: u64,
+    #[serde(rename = "b", borrow, default)]
+    bids: Vec<[&'a str; 2]>,
+    #[serde(rename = "a", borrow, default)]
+    asks: Vec<[&'a str; 2]>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UpdateResult {
+    Applied,
+    Stale,
+    Gap,
+    // book sau update vi phạm invariant (crossed, qty 0, ...)
+    Invalid,
+}
+
+impl UpdateResult {
+    fn needs_resync(self) -> bool {
+        matches!(self, UpdateResult::Gap | UpdateResult::Invalid)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GateioOrderbookWS {
+    pub symbol: String,
+    // `limit` cho REST snapshot
+    pub depth_level: usize,
+    pub orderbook: Arc<SharedOrderbook>,
+    pub backoff: BackoffConfig,
+    pub heartbeat: HeartbeatConfig,
+    pub connection: ConnectionStatus,
+    pub malformed: MalformedCounter,
+}
+
+impl GateioOrderbookWS {
+    // symbol dạng "BTC_USDT", depth bị chặn ở 100 (REST không trả nhiều hơn)
+    pub fn new(symbol: &str, depth_level: usize) -> Self {
+        Self {
+            symbol: symbol.to_uppercase(),
+            depth_level: depth_level.clamp(1, MAX_SNAPSHOT_LIMIT),
+            orderbook: Arc::new(SharedOrderbook::new()),
+            backoff: BackoffConfig::default(),
+            // Gate trả lời ping frame, không cần `spot.ping`
+            heartbeat: HeartbeatConfig::default(),
+            connection: ConnectionStatus::new(),
+            malformed: MalformedCounter::default(),
+        }
+    }
+
+    fn subscribe_message(&self) -> String {
+        serde_json::json!({
+            "time": Utc::now().timestamp(),
+            "channel": "spot.order_book_update",
+            "event": "subscribe",
+            "payload": [self.symbol, "100ms"],
+        })
+        .to_string()
+    }
+
+    pub async fn start(self: Arc<Self>) {
+        let span = info_span!("gateio_ws", symbol = %self.symbol);
+        self.run().instrument(span).await
+    }
+
+    async fn run(&self) {
+        let mut reconnect = Reconnector::new(self.backoff.clone(), self.connection.clone());
+
+        loop {
+            reconnect.connecting();
+            match transport::connect("gateio", WS_URL).await {
+                Ok((ws_stream, _)) => {
+                    reconnect.connected();
+                    info!(url = WS_URL, "connected");
+                    let (mut write, mut read) = ws_stream.split();
+                    let mut heartbeat = Heartbeat::new(&self.heartbeat).on_resubscribe(&self.connection);
+
+                    if let Err(e) = write.send(Message::Text(self.subscribe_message())).await {
+                        warn!(error = ?e, "subscribe failed");
+                    } else {
+                        self.run_book(&mut heartbeat, &mut read, &mut write).await;
+                    }
+                    info!("stream closed");
+                }
+                Err(e) => warn!(error = ?e, "connect failed"),
+            }
+            if !reconnect.wait().await {
+                return;
+            }
+        }
+    }
+
+    // Đồng bộ theo hướng dẫn của Gate: buffer update trong lúc lấy snapshot `with_id`,
+    // bỏ update có u <= id, sau đó U <= id hiện tại + 1 <= u, lệch thì lấy lại snapshot
+    async fn run_book<S, W>(&self, heartbeat: &mut Heartbeat, read: &mut S, write: &mut W)
+    where
+        S: Stream<Item = Result<Message, tungstenite::Error>> + Unpin,
+        W: Sink<Message, Error = tungstenite::Error> + Unpin,
+    {
+        loop {
+            let mut buffer: Vec<String> = Vec::new();
+
+            let snapshot = {
+                let fetch = self.fetch_snapshot();
+                tokio::pin!(fetch);
+                loop {
+                    tokio::select! {
+                        res = &mut fetch => break res,
+                        text = heartbeat.next_text(read, write) => match text {
+                            Some(text) => buffer.push(text),
+                            None => return,
+                        },
+                    }
+                }
+            };
+
+            let body = match snapshot {
+                Ok(body) => body,
+                Err(e) => {
+                    warn!(error = ?e, "REST snapshot failed, retrying");
+                    tokio::time::sleep(Duration::from_secs(3)).await;
+                    continue;
+                }
+            };
+            let Some(snapshot) = self.malformed.parse::<OrderBookSnapshot>(&self.symbol, &body) else {
+                tokio::time::sleep(Duration::from_secs(3)).await;
+                continue;
+            };
+            self.apply_snapshot(&snapshot);
+
+            let mut failed = None;
+            for text in &buffer {
+                match self.handle_text(text) {
+                    Ok(Some(result)) if result.needs_resync() => {
+                        failed = Some(result);
+                        break;
+                    }
+                    Ok(_) => {}
+                    Err(()) => return,
+                }
+            }
+
+            if failed.is_none() {
+                while let Some(text) = heartbeat.next_text(read, write).await {
+                    match self.handle_text(&text) {
+                        Ok(Some(result)) if result.needs_resync() => {
+                            failed = Some(result);
+                            break;
+                        }
+                        Ok(_) => {}
+                        Err(()) => return,
+                    }
+                }
+            }
+
+            let Some(reason) = failed else {
+                return;
+            };
+            warn!(?reason, "local book out of sync, resyncing");
+        }
+    }
+
+    // body thô, parse (mượn) ở chỗ gọi
+    async fn fetch_snapshot(&self) -> Result<String, reqwest::Error> {
+        let url = format!(
+            "{}/spot/order_book?currency_pair={}&limit={}&with_id=true",
+            REST_URL, self.symbol, self.depth_level
+        );
+        net::http_client().get(&url).send().await?.error_for_status()?.text().await
+    }
+
+    // Ok(None) với ack / pong, Err khi server báo lỗi (cần reconnect)
+    fn handle_text(&self, text: &str) -> Result<Option<UpdateResult>, ()> {
+        let Some(envelope) = self.malformed.parse::<Envelope>(&self.symbol, text) else {
+            return Ok(None);
+        };
+        if let Some(error) = envelope.error {
+            warn!(channel = envelope.channel, error = error.get(), "gateio ws error");
+            return Err(());
+        }
+        match (envelope.channel, envelope.event, envelope.result) {
+            (Some("spot.order_book_update"), Some("update"), Some(result)) => {
+                Ok(self.malformed.parse::<BookUpdate>(&self.symbol, result.get()).map(|update| self.apply_update(&update)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn apply_snapshot(&self, snapshot: &OrderBookSnapshot) -> bool {
+        debug!(bids = snapshot.bids.len(), asks = snapshot.asks.len(), id = snapshot.id, "order book snapshot");
+        self.orderbook.update(|ob| {
+            ob.replace_levels_str(Side::Bid, &snapshot.bids);
+            ob.replace_levels_str(Side::Ask, &snapshot.asks);
+            ob.last_update_id = snapshot.id;
+            ob.timestamp = Utc::now();
+            true
+        })
+    }
+
+    fn apply_update(&self, update: &BookUpdate) -> UpdateResult {
+        if let Some(t) = update.t {
+            self.orderbook.record_latency(t);
+        }
+        let mut result = UpdateResult::Applied;
+        self.orderbook.update(|ob| {
+            if update.final_update_id <= ob.last_update_id {
+                result = UpdateResult::Stale;
+                return false;
+            }
+            if update.first_update_id > ob.last_update_id + 1 {
+                result = UpdateResult::Gap;
+                return false;
+            }
+
+            for [price, qty] in &update.bids {
+                ob.set_level_str(Side::Bid, price, qty);
+            }
+            for [price, qty] in &update.asks {
+                ob.set_level_str(Side::Ask, price, qty);
+            }
+
+            ob.last_update_id = update.final_update_id;
+            ob.timestamp = Utc::now();
+            true
+        });
+        if result == UpdateResult::Applied && self.orderbook.integrity().take_resync() {
+            result = UpdateResult::Invalid;
+        }
+        debug!(first = update.first_update_id, last = update.final_update_id, ?result, "order book update");
+        result
+    }
+
+    pub async fn get_best_price(&self) -> Option<BestBidAsk> {
+        self.orderbook.best_bid_ask()
+    }
+}
+
+#[async_trait]
+impl OrderbookFeed for GateioOrderbookWS {
+    fn exchange(&self) -> &'static str {
+        "gateio"
+    }
+
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    fn orderbook(&self) -> &SharedOrderbook {
+        &self.orderbook
+    }
+
+    fn connection(&self) -> Option<&ConnectionStatus> {
+        Some(&self.connection)
+    }
+
+    async fn start(self: Arc<Self>) {
+        GateioOrderbookWS::start(self).await
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn update(first: u64, last: u64, bids: &str, asks: &str) -> String {
+        format!(
+            r#"{{"time":1606294781,"time_ms":1606294781236,"channel":"spot.order_book_update","event":"update","result":{{"t":1606294781123,"e":"depthUpdate","E":1606294781,"s":"BTC_USDT","U":{},"u":{},"b":[{}],"a":[{}]}}}}"#,
+            first, last, bids, asks
+        )
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_then_update_sequence() {
+        let ws = GateioOrderbookWS::new("btc_usdt", 500);
+        assert_eq!((ws.symbol.as_str(), ws.depth_level), ("BTC_USDT", MAX_SNAPSHOT_LIMIT));
+        let body = r#"{"id":100,"current":1623898993123,"update":1623898993121,"asks":[["101.0","2"]],"bids":[["100.0","1"],["99.5","3"]]}"#;
+        assert!(ws.apply_snapshot(&serde_json::from_str(body).unwrap()));
+
+        let ack = r#"{"time":1606294781,"channel":"spot.order_book_update","event":"subscribe","error":null,"result":{"status":"success"}}"#;
+        assert_eq!(ws.handle_text(ack), Ok(None));
+        assert_eq!(ws.handle_text(&update(90, 100, r#"["100.0","0"]"#, "")), Ok(Some(UpdateResult::Stale)));
+        // update đầu tiên chồng lên id + 1
+        assert_eq!(ws.handle_text(&update(98, 103, r#"["100.0","0"]"#, r#"["100.5","4"]"#)), Ok(Some(UpdateResult::Applied)));
+        assert_eq!(ws.get_best_price().await, Some(((dec!(99.5), dec!(3)), (dec!(100.5), dec!(4)))));
+
+        assert_eq!(ws.handle_text(&update(105, 106, "", "")), Ok(Some(UpdateResult::Gap)));
+        assert_eq!(ws.orderbook.snapshot().last_update_id, 103);
+        assert_eq!(ws.handle_text(&update(104, 104, r#"["100.5","1"]"#, "")), Ok(Some(UpdateResult::Invalid)));
+
+        let error = r#"{"time":1606294781,"channel":"spot.order_book_update","event":"subscribe","error":{"code":2,"message":"unknown currency pair"},"result":null}"#;
+        assert_eq!(ws.handle_text(error), Err(()));
+    }
+}
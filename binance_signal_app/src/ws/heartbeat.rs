@@ -0,0 +1,233 @@
+use futures_util::{Sink, SinkExt, Stream, StreamExt};
+use std::time::Duration;
+use tokio::{
+    sync::watch,
+    time::{interval_at, Instant, Interval, MissedTickBehavior},
+};
+use tokio_tungstenite::tungstenite::{self, Message};
+use tracing::{debug, warn};
+
+use super::reconnect::ConnectionStatus;
+
+#[derive(Debug, Clone)]
+pub struct HeartbeatConfig {
+    // None = không chủ động ping, chỉ trả lời ping của server
+    pub ping_interval: Option<Duration>,
+    // không nhận được frame nào (kể cả ping/pong) trong khoảng này thì coi như kết nối chết
+    pub idle_timeout: Option<Duration>,
+    // sàn cần ping ở tầng ứng dụng (OKX "ping", Bybit {"op":"ping"}) thay vì ping frame
+    pub text_ping: Option<String>,
+}
+
+impl Default for HeartbeatConfig {
+    fn default() -> Self {
+        Self {
+            ping_interval: Some(Duration::from_secs(15)),
+            idle_timeout: Some(Duration::from_secs(45)),
+            text_ping: None,
+        }
+    }
+}
+
+impl HeartbeatConfig {
+    pub fn with_text_ping(text: &str) -> Self {
+        Self { text_ping: Some(text.to_string()), ..Self::default() }
+    }
+}
+
+// Frame dữ liệu, sàn gửi nhị phân (protobuf MEXC, gzip HTX) đọc qua `next_frame`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Frame {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+// Đọc WS có xử lý heartbeat: trả lời Ping, gửi ping định kỳ và phát hiện
+// kết nối im lặng. `next_text` trả None khi cần đóng và reconnect.
+#[derive(Debug)]
+pub struct Heartbeat {
+    config: HeartbeatConfig,
+    ping: Option<Interval>,
+    last_message: Instant,
+    resubscribe: Option<watch::Receiver<u64>>,
+}
+
+impl Heartbeat {
+    pub fn new(config: &HeartbeatConfig) -> Self {
+        let ping = config.ping_interval.filter(|d| !d.is_zero()).map(|period| {
+            let mut ping = interval_at(Instant::now() + period, period);
+            ping.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            ping
+        });
+        Self { config: config.clone(), ping, last_message: Instant::now(), resubscribe: None }
+    }
+
+    // `status.request_resubscribe()` thì đóng kết nối này để connector subscribe lại
+    pub fn on_resubscribe(mut self, status: &ConnectionStatus) -> Self {
+        self.resubscribe = Some(status.watch_resubscribe());
+        self
+    }
+
+    fn ping_message(&self) -> Message {
+        match &self.config.text_ping {
+            Some(text) => Message::Text(text.clone()),
+            None => Message::Ping(Vec::new()),
+        }
+    }
+
+    // bỏ qua frame nhị phân
+    pub async fn next_text<S, W>(&mut self, read: &mut S, write: &mut W) -> Option<String>
+    where
+        S: Stream<Item = Result<Message, tungstenite::Error>> + Unpin,
+        W: Sink<Message, Error = tungstenite::Error> + Unpin,
+    {
+        loop {
+            if let Frame::Text(text) = self.next_frame(read, write).await? {
+                return Some(text);
+            }
+        }
+    }
+
+    pub async fn next_frame<S, W>(&mut self, read: &mut S, write: &mut W) -> Option<Frame>
+    where
+        S: Stream<Item = Result<Message, tungstenite::Error>> + Unpin,
+        W: Sink<Message, Error = tungstenite::Error> + Unpin,
+    {
+        loop {
+            let deadline = self.config.idle_timeout.map(|t| self.last_message + t);
+            tokio::select! {
+                msg = read.next() => {
+                    let msg = match msg {
+                        Some(Ok(msg)) => msg,
+                        Some(Err(e)) => {
+                            warn!(error = ?e, "ws read error");
+                            return None;
+                        }
+                        None => return None,
+                    };
+                    self.last_message = Instant::now();
+                    match msg {
+                        Message::Text(text) => return Some(Frame::Text(text)),
+                        Message::Binary(data) => return Some(Frame::Binary(data)),
+                        Message::Ping(payload) => {
+                            if write.send(Message::Pong(payload)).await.is_err() {
+                                return None;
+                            }
+                        }
+                        Message::Close(frame) => {
+                            debug!(?frame, "ws closed by server");
+                            return None;
+                        }
+                        Message::Pong(_) | Message::Frame(_) => {}
+                    }
+                }
+                _ = tick(&mut self.ping) => {
+                    let ping = self.ping_message();
+                    if write.send(ping).await.is_err() {
+                        return None;
+                    }
+                }
+                _ = sleep_until(deadline) => {
+                    warn!(idle_ms = self.last_message.elapsed().as_millis() as u64, "ws idle timeout");
+                    return None;
+                }
+                _ = changed(&mut self.resubscribe) => {
+                    warn!("resubscribe requested, closing connection");
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+async fn tick(ping: &mut Option<Interval>) {
+    match ping {
+        Some(ping) => {
+            ping.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+// sender drop thì không còn ai yêu cầu nữa, chờ mãi
+async fn changed(rx: &mut Option<watch::Receiver<u64>>) {
+    let Some(rx) = rx else {
+        return std::future::pending().await;
+    };
+    if rx.changed().await.is_err() {
+        std::future::pending::<()>().await;
+    }
+}
+
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::{sink::SinkMapErr, stream};
+    use std::convert::Infallible;
+
+    type Incoming = Result<Message, tungstenite::Error>;
+    type Sent = SinkMapErr<Vec<Message>, fn(Infallible) -> tungstenite::Error>;
+
+    fn sink() -> Sent {
+        Vec::new().sink_map_err(|e| match e {})
+    }
+
+    #[tokio::test]
+    async fn test_replies_to_ping_and_returns_text() {
+        let frames: Vec<Incoming> = vec![
+            Ok(Message::Ping(vec![1, 2])),
+            Ok(Message::Binary(vec![3])),
+            Ok(Message::Text("hello".into())),
+            Ok(Message::Close(None)),
+            Ok(Message::Text("after close".into())),
+        ];
+        let mut read = stream::iter(frames);
+        let mut write = sink();
+        let mut hb = Heartbeat::new(&HeartbeatConfig::default());
+
+        assert_eq!(hb.next_text(&mut read, &mut write).await.as_deref(), Some("hello"));
+        assert_eq!(write.get_ref(), &vec![Message::Pong(vec![1, 2])]);
+        assert_eq!(hb.next_text(&mut read, &mut write).await, None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_pings_and_idle_timeout() {
+        let mut read = stream::pending::<Incoming>();
+        let mut write = sink();
+        let config = HeartbeatConfig {
+            ping_interval: Some(Duration::from_secs(10)),
+            idle_timeout: Some(Duration::from_secs(25)),
+            text_ping: Some("ping".into()),
+        };
+        let mut hb = Heartbeat::new(&config);
+
+        let start = Instant::now();
+        assert_eq!(hb.next_text(&mut read, &mut write).await, None);
+        assert_eq!(start.elapsed(), Duration::from_secs(25));
+        // ping lúc 10s và 20s
+        assert_eq!(write.get_ref(), &vec![Message::Text("ping".into()), Message::Text("ping".into())]);
+    }
+
+    #[tokio::test]
+    async fn test_resubscribe_request_closes_connection() {
+        let status = ConnectionStatus::new();
+        // yêu cầu từ trước khi kết nối không tính
+        status.request_resubscribe();
+        let mut read = stream::pending::<Incoming>();
+        let mut write = sink();
+        let config = HeartbeatConfig { ping_interval: None, idle_timeout: None, text_ping: None };
+        let mut hb = Heartbeat::new(&config).on_resubscribe(&status);
+
+        let requester = status.clone();
+        tokio::spawn(async move { requester.request_resubscribe() });
+        assert_eq!(hb.next_text(&mut read, &mut write).await, None);
+        assert_eq!(status.resubscribes(), 2);
+    }
+}
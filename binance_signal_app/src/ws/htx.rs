@@ -0,0 +1,247 @@
+use tokio_tungstenite::tungstenite::Message;
+use futures_util::{SinkExt, StreamExt};
+use flate2::read::GzDecoder;
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use std::{io::Read, sync::Arc};
+use chrono::Utc;
+use async_trait::async_trait;
+use tracing::{debug, info, info_span, warn, Instrument};
+
+use crate::core::orderbook::{SharedOrderbook, Side};
+use super::{
+    heartbeat::{Frame, Heartbeat, HeartbeatConfig},
+    reconnect::{BackoffConfig, ConnectionStatus, Reconnector},
+    transport,
+    BestBidAsk, MalformedCounter, OrderbookFeed,
+};
+
+const WS_URL: &str = "wss://api.huobi.pro/ws";
+
+// Mọi message của HTX là JSON nén gzip: {"ping":..}, {"status":"ok","subbed":..},
+// {"status":"error","err-msg":..} hoặc {"ch":..,"ts":..,"tick":{...}}
+#[derive(Debug, Deserialize)]
+struct Envelope<'a> {
+    #[serde(default)]
+    ping: Option<i64>,
+    #[serde(default)]
+    status: Option<&'a str>,
+    #[serde(rename = "err-msg", default)]
+    err_msg: Option<&'a str>,
+    #[serde(default)]
+    ts: Option<i64>,
+    #[serde(borrow, default)]
+    tick: Option<&'a RawValue>,
+}
+
+// Số là JSON number, giữ text gốc để parse Decimal không qua f64
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RefreshTick<'a> {
+    seq_num: u64,
+    #[serde(borrow)]
+    bids: Vec<[&'a RawValue; 2]>,
+    #[serde(borrow)]
+    asks: Vec<[&'a RawValue; 2]>,
+}
+
+fn levels<'a>(levels: &[[&'a RawValue; 2]]) -> Vec<[&'a str; 2]> {
+    levels.iter().map(|[p, q]| [p.get(), q.get()]).collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Action {
+    Continue,
+    // trả lời ping của server: {"pong": <ts>}
+    Pong(i64),
+    Resubscribe,
+}
+
+#[derive(Debug, Clone)]
+pub struct HtxOrderbookWS {
+    pub symbol: String,
+    pub depth_level: usize,
+    pub orderbook: Arc<SharedOrderbook>,
+    pub backoff: BackoffConfig,
+    pub heartbeat: HeartbeatConfig,
+    pub connection: ConnectionStatus,
+    pub malformed: MalformedCounter,
+}
+
+impl HtxOrderbookWS {
+    // symbol dạng "btcusdt", depth là số level của `mbp.refresh` (5/10/20)
+    pub fn new(symbol: &str, depth_level: usize) -> Self {
+        Self {
+            symbol: symbol.to_lowercase(),
+            depth_level,
+            orderbook: Arc::new(SharedOrderbook::new()),
+            backoff: BackoffConfig::default(),
+            // server ping mỗi 5s, client chỉ cần trả pong
+            heartbeat: HeartbeatConfig { ping_interval: None, ..HeartbeatConfig::default() },
+            connection: ConnectionStatus::new(),
+            malformed: MalformedCounter::default(),
+        }
+    }
+
+    fn topic(&self) -> String {
+        format!("market.{}.mbp.refresh.{}", self.symbol, self.depth_level)
+    }
+
+    fn subscribe_message(&self) -> String {
+        serde_json::json!({ "sub": self.topic(), "id": self.symbol }).to_string()
+    }
+
+    pub async fn start(self: Arc<Self>) {
+        let span = info_span!("htx_ws", symbol = %self.symbol);
+        self.run().instrument(span).await
+    }
+
+    async fn run(&self) {
+        let mut reconnect = Reconnector::new(self.backoff.clone(), self.connection.clone());
+
+        loop {
+            reconnect.connecting();
+            match transport::connect("htx", WS_URL).await {
+                Ok((ws_stream, _)) => {
+                    reconnect.connected();
+                    info!(url = WS_URL, "connected");
+                    let (mut write, mut read) = ws_stream.split();
+
+                    if let Err(e) = write.send(Message::Text(self.subscribe_message())).await {
+                        warn!(error = ?e, "subscribe failed");
+                    } else {
+                        let mut heartbeat = Heartbeat::new(&self.heartbeat).on_resubscribe(&self.connection);
+                        while let Some(frame) = heartbeat.next_frame(&mut read, &mut write).await {
+                            let Frame::Binary(data) = frame else { continue };
+                            match self.process_frame(&data) {
+                                Action::Continue => {}
+                                Action::Pong(ts) => {
+                                    let pong = serde_json::json!({ "pong": ts }).to_string();
+                                    if write.send(Message::Text(pong)).await.is_err() {
+                                        break;
+                                    }
+                                }
+                                Action::Resubscribe => {
+                                    warn!("resubscribing");
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                    info!("stream closed");
+                }
+                Err(e) => warn!(error = ?e, "connect failed"),
+            }
+            if !reconnect.wait().await {
+                return;
+            }
+        }
+    }
+
+    fn process_frame(&self, data: &[u8]) -> Action {
+        let mut text = String::new();
+        if let Err(e) = GzDecoder::new(data).read_to_string(&mut text) {
+            warn!(error = %e, "gzip decode failed");
+            return Action::Continue;
+        }
+        self.process_text(&text)
+    }
+
+    fn process_text(&self, text: &str) -> Action {
+        let Some(envelope) = self.malformed.parse::<Envelope>(&self.symbol, text) else {
+            return Action::Continue;
+        };
+        if let Some(ts) = envelope.ping {
+            return Action::Pong(ts);
+        }
+        if envelope.status == Some("error") {
+            warn!(err = envelope.err_msg, "htx ws error");
+            return Action::Resubscribe;
+        }
+        let Some(tick) = envelope.tick else {
+            return Action::Continue;
+        };
+        let Some(tick) = self.malformed.parse::<RefreshTick>(&self.symbol, tick.get()) else {
+            return Action::Continue;
+        };
+        if let Some(ts) = envelope.ts {
+            self.orderbook.record_latency(ts);
+        }
+        self.apply_refresh(&tick);
+        if self.orderbook.integrity().take_resync() {
+            return Action::Resubscribe;
+        }
+        Action::Continue
+    }
+
+    // mỗi message refresh là snapshot top N
+    fn apply_refresh(&self, tick: &RefreshTick) {
+        debug!(bids = tick.bids.len(), asks = tick.asks.len(), seq = tick.seq_num, "mbp refresh");
+        self.orderbook.update(|ob| {
+            ob.replace_levels_str(Side::Bid, &levels(&tick.bids));
+            ob.replace_levels_str(Side::Ask, &levels(&tick.asks));
+            ob.last_update_id = tick.seq_num;
+            ob.timestamp = Utc::now();
+            true
+        });
+    }
+
+    pub async fn get_best_price(&self) -> Option<BestBidAsk> {
+        self.orderbook.best_bid_ask()
+    }
+}
+
+#[async_trait]
+impl OrderbookFeed for HtxOrderbookWS {
+    fn exchange(&self) -> &'static str {
+        "htx"
+    }
+
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    fn orderbook(&self) -> &SharedOrderbook {
+        &self.orderbook
+    }
+
+    fn connection(&self) -> Option<&ConnectionStatus> {
+        Some(&self.connection)
+    }
+
+    async fn start(self: Arc<Self>) {
+        HtxOrderbookWS::start(self).await
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::GzEncoder, Compression};
+    use rust_decimal_macros::dec;
+    use std::io::Write;
+
+    fn gzip(text: &str) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(text.as_bytes()).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_gzip_frames_ping_and_refresh() {
+        let ws = HtxOrderbookWS::new("BTCUSDT", 5);
+        assert_eq!(ws.topic(), "market.btcusdt.mbp.refresh.5");
+
+        assert_eq!(ws.process_frame(&gzip(r#"{"ping":1492420473027}"#)), Action::Pong(1492420473027));
+        assert_eq!(ws.process_frame(&gzip(r#"{"id":"btcusdt","status":"ok","subbed":"market.btcusdt.mbp.refresh.5","ts":1}"#)), Action::Continue);
+
+        let refresh = r#"{"ch":"market.btcusdt.mbp.refresh.5","ts":1573199608679,"tick":{"seqNum":100020142010,"bids":[[30000.5,0.25],[29999,1]],"asks":[[30001,2.5e-3]]}}"#;
+        assert_eq!(ws.process_frame(&gzip(refresh)), Action::Continue);
+        assert_eq!(ws.get_best_price().await, Some(((dec!(30000.5), dec!(0.25)), (dec!(30001), dec!(0.0025)))));
+        assert_eq!(ws.orderbook.snapshot().last_update_id, 100020142010);
+
+        let error = r#"{"status":"error","err-code":"bad-request","err-msg":"invalid topic","ts":1}"#;
+        assert_eq!(ws.process_frame(&gzip(error)), Action::Resubscribe);
+    }
+}
@@ -0,0 +1,305 @@
+use tokio_tungstenite::tungstenite::Message;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use std::{collections::BTreeMap, sync::Arc};
+use rust_decimal::Decimal;
+use chrono::Utc;
+use async_trait::async_trait;
+
+use crate::core::orderbook::{parse_decimal, OrderbookSnapshot, SharedOrderbook, Side};
+use super::{
+    heartbeat::{Heartbeat, HeartbeatConfig},
+    reconnect::{BackoffConfig, ConnectionStatus, Reconnector},
+    transport,
+    BestBidAsk, OrderbookFeed,
+};
+
+const WS_URL: &str = "wss://ws.kraken.com";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ApplyResult {
+    Ok,
+    Ignored,
+    ChecksumMismatch,
+}
+
+// Kraken tính checksum trên chuỗi price/volume gốc nên giữ lại dạng string
+#[derive(Debug, Default)]
+struct RawBook {
+    bids: BTreeMap<Decimal, (String, String)>,
+    asks: BTreeMap<Decimal, (String, String)>,
+}
+
+impl RawBook {
+    fn apply_levels(&mut self, side: Side, levels: &[Value], depth: usize) {
+        let book = match side {
+            Side::Bid => &mut self.bids,
+            Side::Ask => &mut self.asks,
+        };
+        for level in levels {
+            let (Some(price), Some(volume)) = (
+                level.get(0).and_then(Value::as_str),
+                level.get(1).and_then(Value::as_str),
+            ) else {
+                continue;
+            };
+            let (Some(p), Some(q)) = (parse_decimal(price), parse_decimal(volume)) else {
+                continue;
+            };
+            if q > Decimal::ZERO {
+                book.insert(p, (price.to_string(), volume.to_string()));
+            } else {
+                book.remove(&p);
+            }
+        }
+
+        // Kraken không gửi delete cho level bị đẩy ra ngoài depth đã subscribe
+        while book.len() > depth {
+            let worst = match side {
+                Side::Bid => book.keys().next().copied(),
+                Side::Ask => book.keys().next_back().copied(),
+            };
+            match worst {
+                Some(p) => {
+                    book.remove(&p);
+                }
+                None => break,
+            }
+        }
+    }
+
+    // top 10 asks (tăng dần) rồi top 10 bids (giảm dần), bỏ dấu '.' và số 0 ở đầu
+    fn checksum(&self) -> u32 {
+        fn push(buf: &mut String, s: &str) {
+            let digits: String = s.chars().filter(|c| *c != '.').collect();
+            buf.push_str(digits.trim_start_matches('0'));
+        }
+
+        let mut buf = String::new();
+        for (p, q) in self.asks.values().take(10) {
+            push(&mut buf, p);
+            push(&mut buf, q);
+        }
+        for (p, q) in self.bids.values().rev().take(10) {
+            push(&mut buf, p);
+            push(&mut buf, q);
+        }
+        crc32fast::hash(buf.as_bytes())
+    }
+
+    fn write_to(&self, ob: &mut OrderbookSnapshot) {
+        ob.clear();
+        for (p, q) in self.bids.values() {
+            ob.set_level_str(Side::Bid, p, q);
+        }
+        for (p, q) in self.asks.values() {
+            ob.set_level_str(Side::Ask, p, q);
+        }
+        ob.timestamp = Utc::now();
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct KrakenOrderbookWS {
+    pub pair: String,
+    pub depth_level: usize,
+    pub orderbook: Arc<SharedOrderbook>,
+    pub backoff: BackoffConfig,
+    pub heartbeat: HeartbeatConfig,
+    pub connection: ConnectionStatus,
+}
+
+impl KrakenOrderbookWS {
+    // pair dạng "XBT/USD", depth hợp lệ: 10, 25, 100, 500, 1000
+    pub fn new(pair: &str, depth_level: usize) -> Self {
+        Self {
+            pair: pair.to_uppercase(),
+            depth_level,
+            orderbook: Arc::new(SharedOrderbook::new()),
+            backoff: BackoffConfig::default(),
+            heartbeat: HeartbeatConfig::default(),
+            connection: ConnectionStatus::new(),
+        }
+    }
+
+    fn event_message(&self, event: &str) -> String {
+        serde_json::json!({
+            "event": event,
+            "pair": [self.pair],
+            "subscription": { "name": "book", "depth": self.depth_level },
+        })
+        .to_string()
+    }
+
+    pub async fn start(self: Arc<Self>) {
+        let mut reconnect = Reconnector::new(self.backoff.clone(), self.connection.clone());
+
+        loop {
+            reconnect.connecting();
+            match transport::connect("kraken", WS_URL).await {
+                Ok((ws_stream, _)) => {
+                    reconnect.connected();
+                    println!("📡 Connected to Kraken WS for {}", self.pair);
+                    let (mut write, mut read) = ws_stream.split();
+
+                    if let Err(e) = write.send(Message::Text(self.event_message("subscribe"))).await {
+                        println!("⚠️ Kraken subscribe error: {:?}", e);
+                    } else {
+                        let mut raw = RawBook::default();
+                        let mut heartbeat = Heartbeat::new(&self.heartbeat).on_resubscribe(&self.connection);
+                        while let Some(text) = heartbeat.next_text(&mut read, &mut write).await {
+                            let Ok(value) = serde_json::from_str::<Value>(&text) else { continue };
+
+                            if self.process_message(&mut raw, &value).await == ApplyResult::ChecksumMismatch {
+                                println!("🔁 Kraken checksum mismatch for {}, resubscribing...", self.pair);
+                                raw = RawBook::default();
+                                let resub = [self.event_message("unsubscribe"), self.event_message("subscribe")];
+                                let mut ok = true;
+                                for m in resub {
+                                    if write.send(Message::Text(m)).await.is_err() {
+                                        ok = false;
+                                        break;
+                                    }
+                                }
+                                if !ok {
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    println!("⚠️ WS Error: {:?}, reconnecting...", e);
+                }
+            }
+            if !reconnect.wait().await {
+                return;
+            }
+        }
+    }
+
+    // Book message: [channelID, {..}, ({..},) "book-10", "XBT/USD"]
+    // snapshot có "as"/"bs", update có "a"/"b" và "c" (checksum) ở object cuối
+    async fn process_message(&self, raw: &mut RawBook, value: &Value) -> ApplyResult {
+        let Some(arr) = value.as_array() else {
+            return ApplyResult::Ignored;
+        };
+        if arr.len() < 4 || arr[arr.len() - 1].as_str() != Some(self.pair.as_str()) {
+            return ApplyResult::Ignored;
+        }
+
+        let mut checksum: Option<u32> = None;
+        for obj in arr[1..arr.len() - 2].iter().filter_map(Value::as_object) {
+            if let Some(levels) = obj.get("as").and_then(Value::as_array) {
+                raw.asks.clear();
+                raw.apply_levels(Side::Ask, levels, self.depth_level);
+            }
+            if let Some(levels) = obj.get("bs").and_then(Value::as_array) {
+                raw.bids.clear();
+                raw.apply_levels(Side::Bid, levels, self.depth_level);
+            }
+            if let Some(levels) = obj.get("a").and_then(Value::as_array) {
+                raw.apply_levels(Side::Ask, levels, self.depth_level);
+            }
+            if let Some(levels) = obj.get("b").and_then(Value::as_array) {
+                raw.apply_levels(Side::Bid, levels, self.depth_level);
+            }
+            if let Some(c) = obj.get("c").and_then(Value::as_str) {
+                checksum = c.parse().ok();
+            }
+        }
+
+        if let Some(expected) = checksum
+            && expected != raw.checksum()
+        {
+            return ApplyResult::ChecksumMismatch;
+        }
+
+        self.orderbook
+            .update(|ob| {
+                raw.write_to(ob);
+                true
+            });
+        ApplyResult::Ok
+    }
+
+    pub async fn get_best_price(&self) -> Option<BestBidAsk> {
+        self.orderbook.best_bid_ask()
+    }
+}
+
+#[async_trait]
+impl OrderbookFeed for KrakenOrderbookWS {
+    fn exchange(&self) -> &'static str {
+        "kraken"
+    }
+
+    fn symbol(&self) -> &str {
+        &self.pair
+    }
+
+    fn orderbook(&self) -> &SharedOrderbook {
+        &self.orderbook
+    }
+
+    fn connection(&self) -> Option<&ConnectionStatus> {
+        Some(&self.connection)
+    }
+
+    async fn start(self: Arc<Self>) {
+        KrakenOrderbookWS::start(self).await
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[tokio::test]
+    async fn test_snapshot_update_and_checksum() {
+        let ob = KrakenOrderbookWS::new("xbt/usd", 10);
+        let mut raw = RawBook::default();
+
+        let snapshot: Value = serde_json::from_str(r#"
+            [0, {
+                "as": [["5541.30000", "2.50700000", "1534614248.123678"], ["5541.80000", "0.33000000", "1534614098.345543"]],
+                "bs": [["5541.20000", "1.52900000", "1534614248.765567"], ["5539.90000", "0.30000000", "1534614241.769870"]]
+            }, "book-10", "XBT/USD"]
+        "#).unwrap();
+        assert_eq!(ob.process_message(&mut raw, &snapshot).await, ApplyResult::Ok);
+
+        // "5541.20000" -> "554120000", "1.52900000" -> "152900000"
+        let expected = crc32fast::hash(
+            b"5541300002507000005541800003300000055412000015290000055399000030000000",
+        );
+        let update = serde_json::json!([
+            0,
+            { "b": [["5541.20000", "1.52900000", "1534614248.765567"]], "c": expected.to_string() },
+            "book-10",
+            "XBT/USD"
+        ]);
+        assert_eq!(ob.process_message(&mut raw, &update).await, ApplyResult::Ok);
+        assert_eq!(ob.get_best_price().await, Some(((dec!(5541.2), dec!(1.529)), (dec!(5541.3), dec!(2.507)))));
+
+        let corrupted = serde_json::json!([
+            0,
+            { "a": [["5541.30000", "0.00000000", "1534614248.123678"]], "c": "1" },
+            "book-10",
+            "XBT/USD"
+        ]);
+        assert_eq!(ob.process_message(&mut raw, &corrupted).await, ApplyResult::ChecksumMismatch);
+    }
+
+    #[test]
+    fn test_truncate_to_depth() {
+        let mut raw = RawBook::default();
+        let levels: Vec<Value> = (0..5)
+            .map(|i| serde_json::json!([format!("{}.0", 100 + i), "1.0", "0"]))
+            .collect();
+        raw.apply_levels(Side::Bid, &levels, 3);
+        let prices: Vec<Decimal> = raw.bids.keys().copied().collect();
+        assert_eq!(prices, vec![dec!(102), dec!(103), dec!(104)]);
+    }
+}
@@ -0,0 +1,484 @@
+use tokio_tungstenite::tungstenite::{self, Message};
+use futures_util::{Sink, SinkExt, Stream, StreamExt};
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use std::{fmt, sync::Arc, time::Duration};
+use chrono::Utc;
+use async_trait::async_trait;
+use tracing::{debug, info, info_span, warn, Instrument};
+
+use crate::core::orderbook::{SharedOrderbook, Side};
+use crate::net;
+use super::{
+    heartbeat::{Heartbeat, HeartbeatConfig},
+    reconnect::{BackoffConfig, ConnectionStatus, Reconnector},
+    transport,
+    BestBidAsk, FeedError, MalformedCounter, OrderbookFeed,
+};
+
+const REST_URL: &str = "https://api.kucoin.com";
+// snapshot public lớn nhất không cần API key, full depth (`/api/v3/...`) phải ký
+const SNAPSHOT_PATH: &str = "/api/v1/market/orderbook/level2_100";
+
+// Lỗi khi lấy token WS / snapshot qua REST
+#[derive(Debug)]
+pub enum KucoinError {
+    Http(reqwest::Error),
+    // code khác "200000"
+    Api { code: String, msg: String },
+    NoServer,
+    InvalidSequence(String),
+}
+
+impl fmt::Display for KucoinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KucoinError::Http(e) => write!(f, "kucoin http error: {}", e),
+            KucoinError::Api { code, msg } => write!(f, "kucoin api error {}: {}", code, msg),
+            KucoinError::NoServer => write!(f, "kucoin bullet-public returned no instance server"),
+            KucoinError::InvalidSequence(s) => write!(f, "kucoin snapshot has invalid sequence {:?}", s),
+        }
+    }
+}
+
+impl std::error::Error for KucoinError {}
+
+impl From<reqwest::Error> for KucoinError {
+    fn from(e: reqwest::Error) -> Self {
+        KucoinError::Http(e)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RestResponse<T> {
+    code: String,
+    #[serde(default)]
+    msg: Option<String>,
+    data: Option<T>,
+}
+
+impl<T> RestResponse<T> {
+    fn into_data(self) -> Result<T, KucoinError> {
+        match self.data {
+            Some(data) if self.code == "200000" => Ok(data),
+            _ => Err(KucoinError::Api { code: self.code, msg: self.msg.unwrap_or_default() }),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BulletToken {
+    token: String,
+    instance_servers: Vec<InstanceServer>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InstanceServer {
+    endpoint: String,
+    // ms
+    ping_interval: u64,
+    ping_timeout: u64,
+}
+
+// WS endpoint lấy từ `/api/v1/bullet-public`, token chỉ dùng cho một lần connect
+#[derive(Debug, Clone)]
+struct WsEndpoint {
+    server: InstanceServer,
+    token: String,
+}
+
+impl WsEndpoint {
+    fn url(&self, connect_id: i64) -> String {
+        format!("{}?token={}&connectId={}", self.server.endpoint, self.token, connect_id)
+    }
+
+    // server quy định chu kỳ ping, quá ping_interval + ping_timeout không có gì thì server đóng
+    fn heartbeat(&self, base: &HeartbeatConfig) -> HeartbeatConfig {
+        let interval = Duration::from_millis(self.server.ping_interval);
+        HeartbeatConfig {
+            ping_interval: Some(interval),
+            idle_timeout: Some(interval + Duration::from_millis(self.server.ping_timeout)),
+            ..base.clone()
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Level2Snapshot {
+    // chuỗi số
+    sequence: String,
+    bids: Vec<[String; 2]>,
+    asks: Vec<[String; 2]>,
+}
+
+impl Level2Snapshot {
+    fn levels(&self, side: Side) -> Vec<[&str; 2]> {
+        let levels = match side {
+            Side::Bid => &self.bids,
+            Side::Ask => &self.asks,
+        };
+        levels.iter().map(|[p, q]| [p.as_str(), q.as_str()]).collect()
+    }
+}
+
+// {"type":"message","topic":"/market/level2:BTC-USDT","subject":"trade.l2update","data":{...}},
+// ngoài ra có welcome / ack / pong / error (data là chuỗi)
+#[derive(Debug, Deserialize)]
+struct Envelope<'a> {
+    #[serde(rename = "type")]
+    kind: &'a str,
+    #[serde(default)]
+    subject: Option<&'a str>,
+    #[serde(borrow, default)]
+    data: Option<&'a RawValue>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct L2Update<'a> {
+    sequence_start: u64,
+    sequence_end: u64,
+    #[serde(default)]
+    time: Option<i64>,
+    #[serde(borrow)]
+    changes: L2Changes<'a>,
+}
+
+// [price, size, sequence]: size 0 = xoá level, price 0 chỉ để tăng sequence
+#[derive(Debug, Clone, Deserialize)]
+struct L2Changes<'a> {
+    #[serde(borrow)]
+    asks: Vec<[&'a str; 3]>,
+    #[serde(borrow)]
+    bids: Vec<[&'a str; 3]>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UpdateResult {
+    Applied,
+    Stale,
+    Gap,
+    // book sau update vi phạm invariant (crossed, qty 0, ...)
+    Invalid,
+}
+
+impl UpdateResult {
+    fn needs_resync(self) -> bool {
+        matches!(self, UpdateResult::Gap | UpdateResult::Invalid)
+    }
+}
+
+// Level2 của KuCoin: lấy token qua REST rồi mới connect, snapshot REST (100 level)
+// + incremental update có sequence. Level ngoài 100 level của snapshot chỉ có từ
+// update nên có thể thiếu
+#[derive(Debug, Clone)]
+pub struct KucoinOrderbookWS {
+    pub symbol: String,
+    pub orderbook: Arc<SharedOrderbook>,
+    pub backoff: BackoffConfig,
+    // chu kỳ ping lấy theo server trả về từ bullet-public
+    pub heartbeat: HeartbeatConfig,
+    pub connection: ConnectionStatus,
+    pub malformed: MalformedCounter,
+}
+
+impl KucoinOrderbookWS {
+    // symbol dạng "BTC-USDT"
+    pub fn new(symbol: &str) -> Self {
+        Self {
+            symbol: symbol.to_uppercase(),
+            orderbook: Arc::new(SharedOrderbook::new()),
+            backoff: BackoffConfig::default(),
+            // KuCoin không nhận ping frame, cần {"type":"ping"} ở tầng ứng dụng
+            heartbeat: HeartbeatConfig::with_text_ping(r#"{"id":"ping","type":"ping"}"#),
+            connection: ConnectionStatus::new(),
+            malformed: MalformedCounter::default(),
+        }
+    }
+
+    fn subscribe_message(&self) -> String {
+        serde_json::json!({
+            "id": Utc::now().timestamp_millis().to_string(),
+            "type": "subscribe",
+            "topic": format!("/market/level2:{}", self.symbol),
+            "privateChannel": false,
+            "response": true,
+        })
+        .to_string()
+    }
+
+    pub async fn start(self: Arc<Self>) {
+        let span = info_span!("kucoin_ws", symbol = %self.symbol);
+        self.run().instrument(span).await
+    }
+
+    async fn run(&self) {
+        let mut reconnect = Reconnector::new(self.backoff.clone(), self.connection.clone());
+
+        loop {
+            reconnect.connecting();
+            match fetch_ws_endpoint().await {
+                Ok(endpoint) => match transport::connect("kucoin", endpoint.url(Utc::now().timestamp_millis())).await {
+                    Ok((ws_stream, _)) => {
+                        reconnect.connected();
+                        info!(endpoint = %endpoint.server.endpoint, "connected");
+                        let (mut write, mut read) = ws_stream.split();
+                        let mut heartbeat = Heartbeat::new(&endpoint.heartbeat(&self.heartbeat)).on_resubscribe(&self.connection);
+
+                        if let Err(e) = write.send(Message::Text(self.subscribe_message())).await {
+                            warn!(error = ?e, "subscribe failed");
+                        } else {
+                            self.run_book(&mut heartbeat, &mut read, &mut write).await;
+                        }
+                        info!("stream closed");
+                    }
+                    Err(e) => warn!(error = ?e, "connect failed"),
+                },
+                Err(e) => warn!(error = %e, "bullet-public token request failed"),
+            }
+            if !reconnect.wait().await {
+                return;
+            }
+        }
+    }
+
+    // Theo hướng dẫn của KuCoin: buffer update trong lúc lấy snapshot, bỏ update có
+    // sequenceEnd <= sequence của snapshot, sequenceStart > sequence hiện tại + 1 là gap
+    async fn run_book<S, W>(&self, heartbeat: &mut Heartbeat, read: &mut S, write: &mut W)
+    where
+        S: Stream<Item = Result<Message, tungstenite::Error>> + Unpin,
+        W: Sink<Message, Error = tungstenite::Error> + Unpin,
+    {
+        loop {
+            let mut buffer: Vec<String> = Vec::new();
+
+            let snapshot = {
+                let fetch = fetch_snapshot(&self.symbol);
+                tokio::pin!(fetch);
+                loop {
+                    tokio::select! {
+                        res = &mut fetch => break res,
+                        text = heartbeat.next_text(read, write) => match text {
+                            Some(text) => buffer.push(text),
+                            None => return,
+                        },
+                    }
+                }
+            };
+
+            let snapshot = match snapshot {
+                Ok(snapshot) => snapshot,
+                Err(e) => {
+                    warn!(error = %e, "REST snapshot failed, retrying");
+                    tokio::time::sleep(Duration::from_secs(3)).await;
+                    continue;
+                }
+            };
+            self.apply_snapshot(&snapshot);
+
+            let mut failed = None;
+            for text in &buffer {
+                match self.handle_text(text) {
+                    Ok(Some(result)) if result.needs_resync() => {
+                        failed = Some(result);
+                        break;
+                    }
+                    Ok(_) => {}
+                    Err(()) => return,
+                }
+            }
+
+            if failed.is_none() {
+                while let Some(text) = heartbeat.next_text(read, write).await {
+                    match self.handle_text(&text) {
+                        Ok(Some(result)) if result.needs_resync() => {
+                            failed = Some(result);
+                            break;
+                        }
+                        Ok(_) => {}
+                        Err(()) => return,
+                    }
+                }
+            }
+
+            let Some(reason) = failed else {
+                return;
+            };
+            warn!(?reason, "local book out of sync, resyncing");
+        }
+    }
+
+    // Ok(None) với welcome / ack / pong, Err khi server báo lỗi (cần reconnect)
+    fn handle_text(&self, text: &str) -> Result<Option<UpdateResult>, ()> {
+        let Some(envelope) = self.malformed.parse::<Envelope>(&self.symbol, text) else {
+            return Ok(None);
+        };
+        match (envelope.kind, envelope.subject, envelope.data) {
+            ("message", Some("trade.l2update"), Some(data)) => {
+                Ok(self.malformed.parse::<L2Update>(&self.symbol, data.get()).map(|update| self.apply_update(&update)))
+            }
+            ("error", _, data) => {
+                warn!(data = data.map(|d| d.get()), "kucoin ws error");
+                Err(())
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn apply_snapshot(&self, snapshot: &Level2Snapshot) -> bool {
+        let sequence = match snapshot.sequence.parse::<u64>() {
+            Ok(sequence) => sequence,
+            Err(_) => {
+                let err = FeedError::InvalidNumber { field: "sequence", value: snapshot.sequence.clone() };
+                self.malformed.record(&self.symbol, &err);
+                return false;
+            }
+        };
+        debug!(bids = snapshot.bids.len(), asks = snapshot.asks.len(), sequence, "level2 snapshot");
+        self.orderbook.update(|ob| {
+            ob.replace_levels_str(Side::Bid, &snapshot.levels(Side::Bid));
+            ob.replace_levels_str(Side::Ask, &snapshot.levels(Side::Ask));
+            ob.last_update_id = sequence;
+            ob.timestamp = Utc::now();
+            true
+        })
+    }
+
+    fn apply_update(&self, update: &L2Update) -> UpdateResult {
+        if let Some(time) = update.time {
+            self.orderbook.record_latency(time);
+        }
+        let mut result = UpdateResult::Applied;
+        self.orderbook.update(|ob| {
+            if update.sequence_end <= ob.last_update_id {
+                result = UpdateResult::Stale;
+                return false;
+            }
+            if update.sequence_start > ob.last_update_id + 1 {
+                result = UpdateResult::Gap;
+                return false;
+            }
+
+            // update chồng lên snapshot: chỉ áp change có sequence mới hơn book
+            let last = ob.last_update_id;
+            let sides = [(Side::Bid, &update.changes.bids), (Side::Ask, &update.changes.asks)];
+            for (side, changes) in sides {
+                for [price, size, sequence] in changes {
+                    if sequence.parse::<u64>().is_ok_and(|seq| seq > last) {
+                        ob.set_level_str(side, price, size);
+                    }
+                }
+            }
+
+            ob.last_update_id = update.sequence_end;
+            ob.timestamp = Utc::now();
+            true
+        });
+        if result == UpdateResult::Applied && self.orderbook.integrity().take_resync() {
+            result = UpdateResult::Invalid;
+        }
+        debug!(start = update.sequence_start, end = update.sequence_end, ?result, "level2 update");
+        result
+    }
+
+    pub async fn get_best_price(&self) -> Option<BestBidAsk> {
+        self.orderbook.best_bid_ask()
+    }
+}
+
+async fn fetch_ws_endpoint() -> Result<WsEndpoint, KucoinError> {
+    let url = format!("{}/api/v1/bullet-public", REST_URL);
+    let response: RestResponse<BulletToken> = net::http_client().post(&url).send().await?.error_for_status()?.json().await?;
+    let bullet = response.into_data()?;
+    let server = bullet.instance_servers.into_iter().next().ok_or(KucoinError::NoServer)?;
+    Ok(WsEndpoint { server, token: bullet.token })
+}
+
+async fn fetch_snapshot(symbol: &str) -> Result<Level2Snapshot, KucoinError> {
+    let url = format!("{}{}?symbol={}", REST_URL, SNAPSHOT_PATH, symbol);
+    let response: RestResponse<Level2Snapshot> = net::http_client().get(&url).send().await?.error_for_status()?.json().await?;
+    let snapshot = response.into_data()?;
+    if snapshot.sequence.parse::<u64>().is_err() {
+        return Err(KucoinError::InvalidSequence(snapshot.sequence));
+    }
+    Ok(snapshot)
+}
+
+#[async_trait]
+impl OrderbookFeed for KucoinOrderbookWS {
+    fn exchange(&self) -> &'static str {
+        "kucoin"
+    }
+
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    fn orderbook(&self) -> &SharedOrderbook {
+        &self.orderbook
+    }
+
+    fn connection(&self) -> Option<&ConnectionStatus> {
+        Some(&self.connection)
+    }
+
+    async fn start(self: Arc<Self>) {
+        KucoinOrderbookWS::start(self).await
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn update(start: u64, end: u64, bids: &str, asks: &str) -> String {
+        format!(
+            r#"{{"type":"message","topic":"/market/level2:BTC-USDT","subject":"trade.l2update","data":{{"sequenceStart":{},"sequenceEnd":{},"symbol":"BTC-USDT","time":1663747970273,"changes":{{"asks":[{}],"bids":[{}]}}}}}}"#,
+            start, end, asks, bids
+        )
+    }
+
+    #[test]
+    fn test_bullet_response_and_endpoint() {
+        let raw = r#"{"code":"200000","data":{"token":"abc","instanceServers":[{"endpoint":"wss://ws-api-spot.kucoin.com/","encrypt":true,"protocol":"websocket","pingInterval":18000,"pingTimeout":10000}]}}"#;
+        let bullet = serde_json::from_str::<RestResponse<BulletToken>>(raw).unwrap().into_data().unwrap();
+        let endpoint = WsEndpoint { server: bullet.instance_servers[0].clone(), token: bullet.token };
+        assert_eq!(endpoint.url(7), "wss://ws-api-spot.kucoin.com/?token=abc&connectId=7");
+        let heartbeat = endpoint.heartbeat(&KucoinOrderbookWS::new("btc-usdt").heartbeat);
+        assert_eq!((heartbeat.ping_interval, heartbeat.idle_timeout), (Some(Duration::from_secs(18)), Some(Duration::from_secs(28))));
+        assert!(heartbeat.text_ping.unwrap().contains("ping"));
+
+        let err = serde_json::from_str::<RestResponse<BulletToken>>(r#"{"code":"400100","msg":"bad"}"#).unwrap();
+        assert!(matches!(err.into_data(), Err(KucoinError::Api { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_then_sequenced_updates() {
+        let ws = KucoinOrderbookWS::new("btc-usdt");
+        let snapshot: Level2Snapshot = serde_json::from_str(
+            r#"{"sequence":"100","time":1,"bids":[["100.0","1"],["99.5","3"]],"asks":[["101.0","2"],["101.5","1"]]}"#,
+        )
+        .unwrap();
+        assert!(ws.apply_snapshot(&snapshot));
+
+        // buffer trước snapshot, đã nằm trong snapshot
+        assert_eq!(ws.handle_text(&update(95, 100, r#"["100.0","0","99"]"#, "")), Ok(Some(UpdateResult::Stale)));
+        // chồng lên snapshot: change 100 bị bỏ, 101 / 102 được áp, price 0 chỉ tăng sequence
+        let overlap = update(99, 103, r#"["100.0","0","100"],["99.5","0","101"]"#, r#"["101.0","5","102"],["0","0","103"]"#);
+        assert_eq!(ws.handle_text(&overlap), Ok(Some(UpdateResult::Applied)));
+        assert_eq!(ws.get_best_price().await, Some(((dec!(100.0), dec!(1)), (dec!(101.0), dec!(5)))));
+        assert_eq!(ws.orderbook.snapshot().last_update_id, 103);
+
+        // thiếu 104
+        assert_eq!(ws.handle_text(&update(105, 106, "", "")), Ok(Some(UpdateResult::Gap)));
+        // bid đè lên best ask
+        assert_eq!(ws.handle_text(&update(104, 104, r#"["101.0","1","104"]"#, "")), Ok(Some(UpdateResult::Invalid)));
+
+        assert_eq!(ws.handle_text(r#"{"id":"1","type":"welcome"}"#), Ok(None));
+        assert_eq!(ws.handle_text(r#"{"id":"1","type":"error","code":404,"data":"topic not found"}"#), Err(()));
+    }
+}
@@ -0,0 +1,226 @@
+use tokio_tungstenite::tungstenite::Message;
+use futures_util::{SinkExt, StreamExt};
+use prost::Message as _;
+use serde::Deserialize;
+use std::sync::Arc;
+use chrono::Utc;
+use async_trait::async_trait;
+use tracing::{debug, info, info_span, warn, Instrument};
+
+use crate::core::orderbook::{SharedOrderbook, Side};
+use super::{
+    heartbeat::{Frame, Heartbeat, HeartbeatConfig},
+    reconnect::{BackoffConfig, ConnectionStatus, Reconnector},
+    transport,
+    BestBidAsk, FeedError, MalformedCounter, OrderbookFeed,
+};
+
+mod pb {
+    tonic::include_proto!("mexc");
+}
+
+use pb::{push_data_v3_api_wrapper::Body, PublicLimitDepthsV3Api, PushDataV3ApiWrapper};
+
+// endpoint protobuf, channel JSON cũ đã bị MEXC bỏ
+const WS_URL: &str = "wss://wbs-api.mexc.com/ws";
+
+// {"id":0,"code":0,"msg":"spot@public.limit.depth.v3.api.pb@BTCUSDT@20"} hoặc {"msg":"PONG"}
+#[derive(Debug, Deserialize)]
+struct Response<'a> {
+    #[serde(default)]
+    code: i64,
+    #[serde(default)]
+    msg: Option<&'a str>,
+}
+
+fn levels(items: &[pb::PublicLimitDepthV3ApiItem]) -> Vec<[&str; 2]> {
+    items.iter().map(|i| [i.price.as_str(), i.quantity.as_str()]).collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct MexcOrderbookWS {
+    pub symbol: String,
+    pub depth_level: usize,
+    pub orderbook: Arc<SharedOrderbook>,
+    pub backoff: BackoffConfig,
+    pub heartbeat: HeartbeatConfig,
+    pub connection: ConnectionStatus,
+    pub malformed: MalformedCounter,
+}
+
+impl MexcOrderbookWS {
+    // symbol dạng "BTCUSDT", depth 5/10/20
+    pub fn new(symbol: &str, depth_level: usize) -> Self {
+        Self {
+            symbol: symbol.to_uppercase(),
+            depth_level,
+            orderbook: Arc::new(SharedOrderbook::new()),
+            backoff: BackoffConfig::default(),
+            // 60s không có ping thì server đóng kết nối
+            heartbeat: HeartbeatConfig::with_text_ping(r#"{"method":"PING"}"#),
+            connection: ConnectionStatus::new(),
+            malformed: MalformedCounter::default(),
+        }
+    }
+
+    fn channel(&self) -> String {
+        format!("spot@public.limit.depth.v3.api.pb@{}@{}", self.symbol, self.depth_level)
+    }
+
+    fn subscribe_message(&self) -> String {
+        serde_json::json!({ "method": "SUBSCRIPTION", "params": [self.channel()] }).to_string()
+    }
+
+    pub async fn start(self: Arc<Self>) {
+        let span = info_span!("mexc_ws", symbol = %self.symbol);
+        self.run().instrument(span).await
+    }
+
+    async fn run(&self) {
+        let mut reconnect = Reconnector::new(self.backoff.clone(), self.connection.clone());
+
+        loop {
+            reconnect.connecting();
+            match transport::connect("mexc", WS_URL).await {
+                Ok((ws_stream, _)) => {
+                    reconnect.connected();
+                    info!(url = WS_URL, "connected");
+                    let (mut write, mut read) = ws_stream.split();
+
+                    if let Err(e) = write.send(Message::Text(self.subscribe_message())).await {
+                        warn!(error = ?e, "subscribe failed");
+                    } else {
+                        let mut heartbeat = Heartbeat::new(&self.heartbeat).on_resubscribe(&self.connection);
+                        while let Some(frame) = heartbeat.next_frame(&mut read, &mut write).await {
+                            let ok = match frame {
+                                Frame::Binary(data) => self.process_push(&data),
+                                Frame::Text(text) => self.process_response(&text),
+                            };
+                            if !ok {
+                                warn!("resubscribing");
+                                break;
+                            }
+                        }
+                    }
+                    info!("stream closed");
+                }
+                Err(e) => warn!(error = ?e, "connect failed"),
+            }
+            if !reconnect.wait().await {
+                return;
+            }
+        }
+    }
+
+    // false khi server từ chối subscribe (code vẫn là 0, chỉ có msg báo lỗi)
+    fn process_response(&self, text: &str) -> bool {
+        let Some(response) = self.malformed.parse::<Response>(&self.symbol, text) else {
+            return true;
+        };
+        if response.code != 0 || response.msg.is_some_and(|m| m.starts_with("Not Subscribed")) {
+            warn!(code = response.code, msg = response.msg, "mexc ws error");
+            return false;
+        }
+        true
+    }
+
+    // false khi book sau update vi phạm invariant
+    fn process_push(&self, data: &[u8]) -> bool {
+        let push = match PushDataV3ApiWrapper::decode(data) {
+            Ok(push) => push,
+            Err(e) => {
+                self.malformed.record(&self.symbol, &FeedError::Protobuf(e));
+                return true;
+            }
+        };
+        let Some(Body::PublicLimitDepths(depth)) = &push.body else {
+            return true;
+        };
+        if let Some(send_time) = push.send_time {
+            self.orderbook.record_latency(send_time);
+        }
+        self.apply_depth(depth);
+        !self.orderbook.integrity().take_resync()
+    }
+
+    fn apply_depth(&self, depth: &PublicLimitDepthsV3Api) {
+        debug!(bids = depth.bids.len(), asks = depth.asks.len(), version = %depth.version, "limit depth");
+        self.orderbook.update(|ob| {
+            ob.replace_levels_str(Side::Bid, &levels(&depth.bids));
+            ob.replace_levels_str(Side::Ask, &levels(&depth.asks));
+            // version là chuỗi số tăng dần
+            if let Ok(version) = depth.version.parse::<u64>() {
+                ob.last_update_id = version;
+            }
+            ob.timestamp = Utc::now();
+            true
+        });
+    }
+
+    pub async fn get_best_price(&self) -> Option<BestBidAsk> {
+        self.orderbook.best_bid_ask()
+    }
+}
+
+#[async_trait]
+impl OrderbookFeed for MexcOrderbookWS {
+    fn exchange(&self) -> &'static str {
+        "mexc"
+    }
+
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    fn orderbook(&self) -> &SharedOrderbook {
+        &self.orderbook
+    }
+
+    fn connection(&self) -> Option<&ConnectionStatus> {
+        Some(&self.connection)
+    }
+
+    async fn start(self: Arc<Self>) {
+        MexcOrderbookWS::start(self).await
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn item(price: &str, quantity: &str) -> pb::PublicLimitDepthV3ApiItem {
+        pb::PublicLimitDepthV3ApiItem { price: price.into(), quantity: quantity.into() }
+    }
+
+    #[tokio::test]
+    async fn test_decode_limit_depth_push() {
+        let ws = MexcOrderbookWS::new("btcusdt", 5);
+        assert_eq!(ws.channel(), "spot@public.limit.depth.v3.api.pb@BTCUSDT@5");
+
+        let push = PushDataV3ApiWrapper {
+            channel: ws.channel(),
+            body: Some(Body::PublicLimitDepths(PublicLimitDepthsV3Api {
+                asks: vec![item("30001.5", "0.4"), item("30002", "1")],
+                bids: vec![item("30000", "2.5")],
+                event_type: "spot@public.limit.depth.v3.api.pb".into(),
+                version: "36913293511".into(),
+            })),
+            symbol: Some("BTCUSDT".into()),
+            symbol_id: None,
+            create_time: None,
+            send_time: Some(Utc::now().timestamp_millis()),
+        };
+        assert!(ws.process_push(&push.encode_to_vec()));
+        assert_eq!(ws.get_best_price().await, Some(((dec!(30000), dec!(2.5)), (dec!(30001.5), dec!(0.4)))));
+        assert_eq!(ws.orderbook.snapshot().last_update_id, 36913293511);
+
+        // rác nhị phân bị đếm, không đóng kết nối
+        assert!(ws.process_push(&[0xff, 0xff, 0xff]));
+        assert_eq!(ws.malformed.count(), 1);
+        assert!(ws.process_response(r#"{"id":0,"code":0,"msg":"PONG"}"#));
+        assert!(!ws.process_response(r#"{"id":0,"code":0,"msg":"Not Subscribed successfully! [spot@public.limit.depth.v3.api.pb@BTCUSDT@5].  Reason： Blocked! "}"#));
+    }
+}
@@ -1 +1,212 @@
-pub mod binance;
\ No newline at end of file
+pub mod binance;
+pub mod binance_futures;
+pub mod binance_kline;
+pub mod binance_multi;
+pub mod binance_trades;
+pub mod binance_user;
+#[cfg(feature = "bitfinex")]
+pub mod bitfinex;
+#[cfg(feature = "bybit")]
+pub mod bybit;
+#[cfg(feature = "coinbase")]
+pub mod coinbase;
+pub mod deflate;
+#[cfg(feature = "gateio")]
+pub mod gateio;
+pub mod heartbeat;
+#[cfg(feature = "htx")]
+pub mod htx;
+#[cfg(feature = "kraken")]
+pub mod kraken;
+#[cfg(feature = "kucoin")]
+pub mod kucoin;
+#[cfg(feature = "mexc")]
+pub mod mexc;
+#[cfg(feature = "okx")]
+pub mod okx;
+pub mod reconnect;
+pub mod stale;
+pub mod transport;
+
+use async_trait::async_trait;
+use chrono::Duration;
+use serde::Deserialize;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+use tokio::sync::{broadcast, watch};
+use tracing::warn;
+
+use crate::core::{
+    book_events::BookDiff,
+    latency::LatencyStats,
+    perp::{Liquidation, PerpStats},
+    orderbook::{Level, OrderbookSnapshot, SharedOrderbook},
+    top_levels::{TopLevels, MAX_TOP_LEVELS},
+};
+use crate::config::Exchange;
+use crate::symbols::Instrument;
+use reconnect::ConnectionStatus;
+
+// ((bid_price, bid_qty), (ask_price, ask_qty))
+pub type BestBidAsk = (Level, Level);
+
+#[async_trait]
+pub trait OrderbookFeed: Send + Sync {
+    fn exchange(&self) -> &'static str;
+    fn symbol(&self) -> &str;
+    fn orderbook(&self) -> &SharedOrderbook;
+    async fn start(self: Arc<Self>);
+
+    // base/quote chuẩn của feed, None nếu không parse được (vd. replay)
+    fn instrument(&self) -> Option<Instrument> {
+        Instrument::parse(Exchange::from_name(self.exchange())?, self.symbol()).ok()
+    }
+
+    // đọc lock-free từ snapshot mới nhất
+    fn best_bid_ask(&self) -> Option<BestBidAsk> {
+        self.orderbook().best_bid_ask()
+    }
+
+    fn snapshot(&self) -> Arc<OrderbookSnapshot> {
+        self.orderbook().snapshot()
+    }
+
+    // snapshot mới nhất, `changed().await` để chờ update tiếp theo
+    fn watch(&self) -> watch::Receiver<Arc<OrderbookSnapshot>> {
+        self.orderbook().watch()
+    }
+
+    // mọi update, theo thứ tự
+    fn subscribe(&self) -> broadcast::Receiver<Arc<OrderbookSnapshot>> {
+        self.orderbook().subscribe()
+    }
+
+    // top level mỗi phía, chỉ báo khi vài level đầu đổi (`orderbook().top_n::<N>()` để đọc)
+    fn subscribe_top(&self) -> watch::Receiver<TopLevels<MAX_TOP_LEVELS>> {
+        self.orderbook().subscribe_top()
+    }
+
+    // thay đổi theo từng level (LevelAdded / LevelRemoved / LevelChanged)
+    fn subscribe_events(&self) -> broadcast::Receiver<Arc<BookDiff>> {
+        self.orderbook().subscribe_events()
+    }
+
+    // strategy nên bỏ qua tín hiệu khi book không được update quá `max_age`
+    fn is_stale(&self, max_age: Duration) -> bool {
+        self.orderbook().is_stale(max_age)
+    }
+
+    // None nếu feed không có event time (hoặc chưa nhận event nào)
+    fn latency(&self) -> Option<LatencyStats> {
+        self.orderbook().latency_stats()
+    }
+
+    // mark/index, funding, open interest, None nếu không phải feed perp
+    fn subscribe_perp(&self) -> Option<broadcast::Receiver<PerpStats>> {
+        None
+    }
+
+    // lệnh thanh lý bắt buộc, None nếu không phải feed perp
+    fn subscribe_liquidations(&self) -> Option<broadcast::Receiver<Liquidation>> {
+        None
+    }
+
+    // None nếu feed không có kết nối (vd. replay)
+    fn connection(&self) -> Option<&ConnectionStatus> {
+        None
+    }
+}
+
+// Message không parse được hoặc có số không hợp lệ. Feed bỏ message, đếm và log
+// thay vì đưa giá / qty 0 vào book, trade window hay candle
+#[derive(Debug, thiserror::Error)]
+pub enum FeedError {
+    #[error("invalid json: {0}")]
+    Json(#[from] serde_json::Error),
+    // feed nhị phân (MEXC)
+    #[error("invalid protobuf: {0}")]
+    Protobuf(prost::DecodeError),
+    #[error("invalid number in {field}: {value:?}")]
+    InvalidNumber { field: &'static str, value: String },
+}
+
+// Message đã parse nhưng còn phải kiểm tra số trước khi áp vào book
+pub(crate) trait Validate {
+    fn validate(&self) -> Result<(), FeedError>;
+}
+
+// số hữu hạn (NaN / inf / tràn f64 bị từ chối)
+pub fn parse_f64(field: &'static str, value: &str) -> Result<f64, FeedError> {
+    #[cfg(feature = "fast-float")]
+    let parsed = lexical_core::parse::<f64>(value.as_bytes()).ok();
+    #[cfg(not(feature = "fast-float"))]
+    let parsed = value.parse::<f64>().ok();
+    match parsed {
+        Some(v) if v.is_finite() => Ok(v),
+        _ => Err(FeedError::InvalidNumber { field, value: value.to_string() }),
+    }
+}
+
+// giá, qty trade: > 0
+pub(crate) fn parse_positive(field: &'static str, value: &str) -> Result<f64, FeedError> {
+    match parse_f64(field, value)? {
+        v if v > 0.0 => Ok(v),
+        _ => Err(FeedError::InvalidNumber { field, value: value.to_string() }),
+    }
+}
+
+// volume: >= 0
+pub(crate) fn parse_non_negative(field: &'static str, value: &str) -> Result<f64, FeedError> {
+    match parse_f64(field, value)? {
+        v if v >= 0.0 => Ok(v),
+        _ => Err(FeedError::InvalidNumber { field, value: value.to_string() }),
+    }
+}
+
+// level [price, qty] của book: giá > 0, qty >= 0 (0 = xoá level).
+// Một level hỏng thì bỏ cả message, không áp nửa chừng
+pub(crate) fn check_levels(levels: &[[&str; 2]]) -> Result<(), FeedError> {
+    for [price, qty] in levels {
+        parse_positive("level price", price)?;
+        parse_non_negative("level qty", qty)?;
+    }
+    Ok(())
+}
+
+// Số message bị bỏ của một feed. Log lần 1, 2, 4, 8, ... để không spam khi sàn gửi rác liên tục
+#[derive(Debug, Clone, Default)]
+pub struct MalformedCounter(Arc<AtomicU64>);
+
+impl MalformedCounter {
+    pub fn record(&self, feed: &str, err: &FeedError) {
+        let count = self.0.fetch_add(1, Ordering::Relaxed) + 1;
+        if count.is_power_of_two() {
+            warn!(feed, count, error = %err, "malformed message dropped");
+        }
+    }
+
+    pub fn count(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    // parse JSON (có thể mượn từ `text`), lỗi thì đếm và trả None
+    pub(crate) fn parse<'a, T: Deserialize<'a>>(&self, feed: &str, text: &'a str) -> Option<T> {
+        serde_json::from_str(text).map_err(|e| self.record(feed, &FeedError::Json(e))).ok()
+    }
+
+    // như `parse`, thêm kiểm tra số (level giá / qty)
+    pub(crate) fn parse_valid<'a, T: Deserialize<'a> + Validate>(&self, feed: &str, text: &'a str) -> Option<T> {
+        let value: T = self.parse(feed, text)?;
+        match value.validate() {
+            Ok(()) => Some(value),
+            Err(e) => {
+                self.record(feed, &e);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,335 @@
+use tokio_tungstenite::tungstenite::Message;
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use std::{collections::BTreeMap, sync::Arc};
+use rust_decimal::Decimal;
+use chrono::Utc;
+use async_trait::async_trait;
+
+use crate::core::orderbook::{parse_decimal, OrderbookSnapshot, SharedOrderbook, Side};
+use super::{
+    heartbeat::{Heartbeat, HeartbeatConfig},
+    reconnect::{BackoffConfig, ConnectionStatus, Reconnector},
+    transport,
+    BestBidAsk, OrderbookFeed,
+};
+
+const WS_URL: &str = "wss://ws.okx.com:8443/ws/v5/public";
+const CHECKSUM_DEPTH: usize = 25;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OkxChannel {
+    // 400 levels, snapshot + incremental update, có checksum
+    Books,
+    // 5 levels, mỗi message là full snapshot
+    Books5,
+}
+
+impl OkxChannel {
+    fn name(&self) -> &'static str {
+        match self {
+            OkxChannel::Books => "books",
+            OkxChannel::Books5 => "books5",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct BookMessage {
+    #[serde(default)]
+    action: Option<String>,
+    data: Vec<BookData>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BookData {
+    // [price, size, deprecated, order count]
+    asks: Vec<Vec<String>>,
+    bids: Vec<Vec<String>>,
+    #[serde(default)]
+    checksum: Option<i64>,
+    #[serde(default)]
+    prev_seq_id: Option<i64>,
+    #[serde(default)]
+    seq_id: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ApplyResult {
+    Ok,
+    SequenceGap,
+    ChecksumMismatch,
+    // book vi phạm invariant (crossed, qty 0, ...)
+    Invalid,
+}
+
+// Giữ nguyên chuỗi price/size gốc vì checksum OKX tính trên chuỗi exchange gửi
+#[derive(Debug, Default)]
+struct RawBook {
+    bids: BTreeMap<Decimal, (String, String)>,
+    asks: BTreeMap<Decimal, (String, String)>,
+    seq_id: Option<i64>,
+}
+
+impl RawBook {
+    fn apply(&mut self, data: &BookData, is_snapshot: bool) -> ApplyResult {
+        if is_snapshot {
+            self.bids.clear();
+            self.asks.clear();
+        } else if let (Some(prev), Some(last)) = (data.prev_seq_id, self.seq_id)
+            && prev != last
+        {
+            return ApplyResult::SequenceGap;
+        }
+
+        apply_levels(&mut self.bids, &data.bids);
+        apply_levels(&mut self.asks, &data.asks);
+        self.seq_id = data.seq_id;
+
+        match data.checksum {
+            Some(expected) if expected as i32 != self.checksum() => ApplyResult::ChecksumMismatch,
+            _ => ApplyResult::Ok,
+        }
+    }
+
+    // crc32 của "bid1px:bid1sz:ask1px:ask1sz:..." trên 25 level đầu, ép kiểu về i32
+    fn checksum(&self) -> i32 {
+        let mut parts: Vec<&str> = Vec::with_capacity(CHECKSUM_DEPTH * 4);
+        let mut bids = self.bids.values().rev();
+        let mut asks = self.asks.values();
+        for _ in 0..CHECKSUM_DEPTH {
+            if let Some((p, q)) = bids.next() {
+                parts.push(p);
+                parts.push(q);
+            }
+            if let Some((p, q)) = asks.next() {
+                parts.push(p);
+                parts.push(q);
+            }
+        }
+        crc32fast::hash(parts.join(":").as_bytes()) as i32
+    }
+
+    fn write_to(&self, ob: &mut OrderbookSnapshot) {
+        ob.clear();
+        for (p, q) in self.bids.values() {
+            ob.set_level_str(Side::Bid, p, q);
+        }
+        for (p, q) in self.asks.values() {
+            ob.set_level_str(Side::Ask, p, q);
+        }
+        ob.last_update_id = self.seq_id.unwrap_or(0).max(0) as u64;
+        ob.timestamp = Utc::now();
+    }
+}
+
+fn apply_levels(book: &mut BTreeMap<Decimal, (String, String)>, levels: &[Vec<String>]) {
+    for level in levels {
+        let (Some(price), Some(size)) = (level.first(), level.get(1)) else {
+            continue;
+        };
+        let (Some(p), Some(q)) = (parse_decimal(price), parse_decimal(size)) else {
+            continue;
+        };
+        if q > Decimal::ZERO {
+            book.insert(p, (price.clone(), size.clone()));
+        } else {
+            book.remove(&p);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OkxOrderbookWS {
+    pub inst_id: String,
+    pub channel: OkxChannel,
+    pub orderbook: Arc<SharedOrderbook>,
+    pub backoff: BackoffConfig,
+    pub heartbeat: HeartbeatConfig,
+    pub connection: ConnectionStatus,
+}
+
+impl OkxOrderbookWS {
+    // inst_id dạng "BTC-USDT"
+    pub fn new(inst_id: &str, channel: OkxChannel) -> Self {
+        Self {
+            inst_id: inst_id.to_uppercase(),
+            channel,
+            orderbook: Arc::new(SharedOrderbook::new()),
+            backoff: BackoffConfig::default(),
+            // OKX đóng kết nối nếu 30s không có data, cần gửi "ping" dạng text
+            heartbeat: HeartbeatConfig::with_text_ping("ping"),
+            connection: ConnectionStatus::new(),
+        }
+    }
+
+    fn subscribe_message(&self) -> String {
+        serde_json::json!({
+            "op": "subscribe",
+            "args": [{ "channel": self.channel.name(), "instId": self.inst_id }],
+        })
+        .to_string()
+    }
+
+    pub async fn start(self: Arc<Self>) {
+        let mut reconnect = Reconnector::new(self.backoff.clone(), self.connection.clone());
+
+        loop {
+            reconnect.connecting();
+            match transport::connect("okx", WS_URL).await {
+                Ok((ws_stream, _)) => {
+                    reconnect.connected();
+                    println!("📡 Connected to OKX WS for {} ({})", self.inst_id, self.channel.name());
+                    let (mut write, mut read) = ws_stream.split();
+
+                    if let Err(e) = write.send(Message::Text(self.subscribe_message())).await {
+                        println!("⚠️ OKX subscribe error: {:?}", e);
+                    } else {
+                        let mut raw = RawBook::default();
+                        let mut heartbeat = Heartbeat::new(&self.heartbeat).on_resubscribe(&self.connection);
+                        while let Some(text) = heartbeat.next_text(&mut read, &mut write).await {
+                            if let Ok(data) = serde_json::from_str::<BookMessage>(&text) {
+                                let result = self.process_message(&mut raw, data).await;
+                                if result != ApplyResult::Ok {
+                                    println!("🔁 OKX {:?} for {}, resubscribing...", result, self.inst_id);
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    println!("⚠️ WS Error: {:?}, reconnecting...", e);
+                }
+            }
+            if !reconnect.wait().await {
+                return;
+            }
+        }
+    }
+
+    async fn process_message(&self, raw: &mut RawBook, msg: BookMessage) -> ApplyResult {
+        // books5 không có action: mỗi message là snapshot đầy đủ
+        let is_snapshot = match self.channel {
+            OkxChannel::Books5 => true,
+            OkxChannel::Books => msg.action.as_deref() == Some("snapshot"),
+        };
+
+        for data in &msg.data {
+            let result = raw.apply(data, is_snapshot);
+            if result != ApplyResult::Ok {
+                return result;
+            }
+        }
+
+        self.orderbook
+            .update(|ob| {
+                raw.write_to(ob);
+                true
+            });
+        if self.orderbook.integrity().take_resync() {
+            return ApplyResult::Invalid;
+        }
+        ApplyResult::Ok
+    }
+
+    pub async fn get_best_price(&self) -> Option<BestBidAsk> {
+        self.orderbook.best_bid_ask()
+    }
+}
+
+#[async_trait]
+impl OrderbookFeed for OkxOrderbookWS {
+    fn exchange(&self) -> &'static str {
+        "okx"
+    }
+
+    fn symbol(&self) -> &str {
+        &self.inst_id
+    }
+
+    fn orderbook(&self) -> &SharedOrderbook {
+        &self.orderbook
+    }
+
+    fn connection(&self) -> Option<&ConnectionStatus> {
+        Some(&self.connection)
+    }
+
+    async fn start(self: Arc<Self>) {
+        OkxOrderbookWS::start(self).await
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn level(p: &str, q: &str) -> Vec<String> {
+        vec![p.into(), q.into(), "0".into(), "1".into()]
+    }
+
+    #[test]
+    fn test_checksum_string_layout() {
+        let mut raw = RawBook::default();
+        let data = BookData {
+            asks: vec![level("3366.8", "9"), level("3368", "8")],
+            bids: vec![level("3366.1", "7")],
+            checksum: None,
+            prev_seq_id: None,
+            seq_id: Some(10),
+        };
+        assert_eq!(raw.apply(&data, true), ApplyResult::Ok);
+
+        // bid1:ask1:ask2 xen kẽ theo thứ tự OKX
+        let expected = crc32fast::hash(b"3366.1:7:3366.8:9:3368:8") as i32;
+        assert_eq!(raw.checksum(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_update_with_checksum_and_sequence() {
+        let ob = OkxOrderbookWS::new("btc-usdt", OkxChannel::Books);
+        let mut raw = RawBook::default();
+
+        let snapshot = BookMessage {
+            action: Some("snapshot".into()),
+            data: vec![BookData {
+                asks: vec![level("101.0", "2")],
+                bids: vec![level("100.0", "1"), level("99.5", "3")],
+                checksum: Some(crc32fast::hash(b"100.0:1:101.0:2:99.5:3") as i32 as i64),
+                prev_seq_id: Some(-1),
+                seq_id: Some(1),
+            }],
+        };
+        assert_eq!(ob.process_message(&mut raw, snapshot).await, ApplyResult::Ok);
+
+        let bad_checksum = BookMessage {
+            action: Some("update".into()),
+            data: vec![BookData {
+                asks: vec![],
+                bids: vec![level("100.0", "0")],
+                checksum: Some(12345),
+                prev_seq_id: Some(1),
+                seq_id: Some(2),
+            }],
+        };
+        assert_eq!(ob.process_message(&mut raw, bad_checksum).await, ApplyResult::ChecksumMismatch);
+
+        let gap = BookMessage {
+            action: Some("update".into()),
+            data: vec![BookData {
+                asks: vec![],
+                bids: vec![],
+                checksum: None,
+                prev_seq_id: Some(7),
+                seq_id: Some(8),
+            }],
+        };
+        assert_eq!(ob.process_message(&mut raw, gap).await, ApplyResult::SequenceGap);
+
+        assert_eq!(ob.get_best_price().await, Some(((dec!(100.0), dec!(1)), (dec!(101.0), dec!(2)))));
+    }
+}
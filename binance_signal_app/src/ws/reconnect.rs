@@ -0,0 +1,229 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::watch;
+use tracing::{error, warn};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConnectionState {
+    // chưa start
+    Idle,
+    Connecting { attempt: u32 },
+    Connected,
+    // đang chờ `delay` trước lần thử thứ `attempt`
+    Reconnecting { attempt: u32, delay: Duration },
+    // vượt `max_retries`, feed đã dừng
+    Failed { attempts: u32 },
+}
+
+impl ConnectionState {
+    pub fn is_connected(&self) -> bool {
+        matches!(self, ConnectionState::Connected)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BackoffConfig {
+    pub initial: Duration,
+    pub max: Duration,
+    pub multiplier: f64,
+    // 0.0 = không jitter, 0.5 = delay ngẫu nhiên trong [50%, 100%]
+    pub jitter: f64,
+    // None = thử lại mãi
+    pub max_retries: Option<u32>,
+    // log error khi số lần thử liên tiếp đạt ngưỡng này
+    pub alert_after: u32,
+    // kết nối giữ được lâu hơn thì lần mất kết nối sau bắt đầu lại từ `initial`
+    pub reset_after: Duration,
+}
+
+impl Default for BackoffConfig {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_millis(500),
+            max: Duration::from_secs(60),
+            multiplier: 2.0,
+            jitter: 0.5,
+            max_retries: None,
+            alert_after: 10,
+            reset_after: Duration::from_secs(30),
+        }
+    }
+}
+
+impl BackoffConfig {
+    // `r` trong [0, 1) là phần ngẫu nhiên, tách ra để test được
+    pub fn delay_for(&self, attempt: u32, r: f64) -> Duration {
+        let exp = self.multiplier.powi(attempt.saturating_sub(1).min(64) as i32);
+        let base = self.initial.as_secs_f64() * exp;
+        let capped = base.min(self.max.as_secs_f64());
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        Duration::from_secs_f64(capped * (1.0 - jitter * r))
+    }
+}
+
+// Trạng thái kết nối của một feed, consumer `watch()` để theo dõi.
+// `request_resubscribe` để bên ngoài (stale watchdog) bắt connector bỏ kết nối hiện tại
+// và kết nối + subscribe lại, kể cả khi socket vẫn trả ping/pong
+#[derive(Debug, Clone)]
+pub struct ConnectionStatus {
+    tx: Arc<watch::Sender<ConnectionState>>,
+    resubscribe: Arc<watch::Sender<u64>>,
+}
+
+impl Default for ConnectionStatus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConnectionStatus {
+    pub fn new() -> Self {
+        Self { tx: Arc::new(watch::channel(ConnectionState::Idle).0), resubscribe: Arc::new(watch::channel(0).0) }
+    }
+
+    pub fn state(&self) -> ConnectionState {
+        *self.tx.borrow()
+    }
+
+    pub fn watch(&self) -> watch::Receiver<ConnectionState> {
+        self.tx.subscribe()
+    }
+
+    fn set(&self, state: ConnectionState) {
+        self.tx.send_replace(state);
+    }
+
+    pub fn request_resubscribe(&self) {
+        self.resubscribe.send_modify(|n| *n += 1);
+    }
+
+    // số lần đã yêu cầu resubscribe
+    pub fn resubscribes(&self) -> u64 {
+        *self.resubscribe.borrow()
+    }
+
+    // chỉ báo các yêu cầu sau lúc gọi, yêu cầu cũ (lúc chưa kết nối) bị bỏ
+    pub fn watch_resubscribe(&self) -> watch::Receiver<u64> {
+        self.resubscribe.subscribe()
+    }
+}
+
+// Dùng trong vòng lặp reconnect của mỗi WS:
+//     reconnect.connecting(); connect... reconnect.connected(); đọc...
+//     if !reconnect.wait().await { return; }
+// Mọi subscription được gửi lại sau mỗi lần connect nên không cần nhớ riêng.
+#[derive(Debug)]
+pub struct Reconnector {
+    config: BackoffConfig,
+    status: ConnectionStatus,
+    attempt: u32,
+    connected_at: Option<Instant>,
+}
+
+impl Reconnector {
+    pub fn new(config: BackoffConfig, status: ConnectionStatus) -> Self {
+        Self { config, status, attempt: 0, connected_at: None }
+    }
+
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    pub fn connecting(&mut self) {
+        self.status.set(ConnectionState::Connecting { attempt: self.attempt });
+    }
+
+    pub fn connected(&mut self) {
+        self.connected_at = Some(Instant::now());
+        self.status.set(ConnectionState::Connected);
+    }
+
+    // Gọi sau khi connect lỗi hoặc stream đóng. Trả về false nếu đã hết lượt thử.
+    pub async fn wait(&mut self) -> bool {
+        let Some(delay) = self.next_delay(random_unit()) else {
+            return false;
+        };
+        tokio::time::sleep(delay).await;
+        true
+    }
+
+    fn next_delay(&mut self, r: f64) -> Option<Duration> {
+        if let Some(at) = self.connected_at.take()
+            && at.elapsed() >= self.config.reset_after
+        {
+            self.attempt = 0;
+        }
+        self.attempt += 1;
+
+        if let Some(max) = self.config.max_retries
+            && self.attempt > max
+        {
+            error!(attempts = max, "giving up reconnecting");
+            self.status.set(ConnectionState::Failed { attempts: max });
+            return None;
+        }
+        if self.attempt >= self.config.alert_after {
+            error!(attempt = self.attempt, "reconnect attempts above alert threshold");
+        }
+
+        let delay = self.config.delay_for(self.attempt, r);
+        warn!(attempt = self.attempt, delay_ms = delay.as_millis() as u64, "reconnecting");
+        self.status.set(ConnectionState::Reconnecting { attempt: self.attempt, delay });
+        Some(delay)
+    }
+}
+
+// [0, 1), đủ cho jitter, không cần thêm crate rand
+fn random_unit() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exponential_delay_with_cap_and_jitter() {
+        let config = BackoffConfig { initial: Duration::from_millis(100), max: Duration::from_secs(1), ..Default::default() };
+        assert_eq!(config.delay_for(1, 0.0), Duration::from_millis(100));
+        assert_eq!(config.delay_for(3, 0.0), Duration::from_millis(400));
+        assert_eq!(config.delay_for(10, 0.0), Duration::from_secs(1));
+        // jitter 0.5: r gần 1 thì còn một nửa
+        assert_eq!(config.delay_for(2, 1.0), Duration::from_millis(100));
+        assert_eq!(config.delay_for(u32::MAX, 0.0), Duration::from_secs(1));
+
+        for _ in 0..100 {
+            assert!((0.0..1.0).contains(&random_unit()));
+        }
+    }
+
+    #[test]
+    fn test_reconnector_states_and_max_retries() {
+        let status = ConnectionStatus::new();
+        let config = BackoffConfig { max_retries: Some(2), reset_after: Duration::ZERO, ..Default::default() };
+        let mut reconnect = Reconnector::new(config, status.clone());
+        let states = status.watch();
+        assert_eq!(status.state(), ConnectionState::Idle);
+
+        reconnect.connecting();
+        assert_eq!(status.state(), ConnectionState::Connecting { attempt: 0 });
+        assert!(reconnect.next_delay(0.0).is_some());
+        assert!(matches!(status.state(), ConnectionState::Reconnecting { attempt: 1, .. }));
+        assert!(states.has_changed().unwrap());
+
+        // kết nối lại được đủ lâu -> đếm lại từ đầu
+        reconnect.connected();
+        assert!(status.state().is_connected());
+        assert!(reconnect.next_delay(0.0).is_some());
+        assert_eq!(reconnect.attempt(), 1);
+
+        assert!(reconnect.next_delay(0.0).is_some());
+        assert!(reconnect.next_delay(0.0).is_none());
+        assert_eq!(status.state(), ConnectionState::Failed { attempts: 2 });
+    }
+}
@@ -0,0 +1,185 @@
+use serde::Deserialize;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::time::{interval, Instant, MissedTickBehavior};
+use tracing::warn;
+
+use super::OrderbookFeed;
+
+// Phát hiện symbol im lặng dù socket vẫn sống (vẫn trả ping/pong):
+//
+//   [stale]
+//   enabled = true
+//   silence_secs = 30
+//   check_secs = 5
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct StaleSettings {
+    pub enabled: bool,
+    // không có book update trong khoảng này thì resubscribe
+    pub silence_secs: u64,
+    pub check_secs: u64,
+}
+
+impl Default for StaleSettings {
+    fn default() -> Self {
+        Self { enabled: false, silence_secs: 30, check_secs: 5 }
+    }
+}
+
+impl StaleSettings {
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.enabled && (self.silence_secs == 0 || self.check_secs == 0) {
+            errors.push("`stale.silence_secs` and `stale.check_secs` must be > 0".to_string());
+        }
+        if self.enabled && self.check_secs > self.silence_secs {
+            errors.push("`stale.check_secs` must be <= `stale.silence_secs`".to_string());
+        }
+        errors
+    }
+
+    pub fn silence(&self) -> Duration {
+        Duration::from_secs(self.silence_secs)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StaleAction {
+    // lần đầu im lặng quá `silence`: subscribe lại riêng symbol đó
+    Resubscribe,
+    // đã resubscribe mà vẫn im lặng thêm một `silence` nữa: bỏ cả kết nối
+    Reconnect,
+}
+
+// Lần update cuối của từng symbol trên một kết nối. Sau mỗi action symbol được
+// tính lại từ đầu để không bắn liên tục trong lúc chờ kết quả
+#[derive(Debug)]
+pub struct StaleTracker {
+    silence: Duration,
+    last: HashMap<String, Instant>,
+    resubscribed: HashSet<String>,
+}
+
+impl StaleTracker {
+    pub fn new<I: IntoIterator<Item = String>>(silence: Duration, symbols: I) -> Self {
+        let now = Instant::now();
+        Self { silence, last: symbols.into_iter().map(|s| (s, now)).collect(), resubscribed: HashSet::new() }
+    }
+
+    pub fn touch(&mut self, symbol: &str) {
+        if let Some(last) = self.last.get_mut(symbol) {
+            *last = Instant::now();
+            self.resubscribed.remove(symbol);
+        }
+    }
+
+    // kết nối mới: mọi symbol bắt đầu đếm lại
+    pub fn reset(&mut self) {
+        let now = Instant::now();
+        self.last.values_mut().for_each(|t| *t = now);
+        self.resubscribed.clear();
+    }
+
+    // (symbol, thời gian im lặng, action), sort theo symbol
+    pub fn check(&mut self) -> Vec<(String, Duration, StaleAction)> {
+        let now = Instant::now();
+        let mut actions = Vec::new();
+        for (symbol, last) in &mut self.last {
+            let silent = now - *last;
+            if silent < self.silence {
+                continue;
+            }
+            let action = if self.resubscribed.insert(symbol.clone()) {
+                StaleAction::Resubscribe
+            } else {
+                self.resubscribed.remove(symbol);
+                StaleAction::Reconnect
+            };
+            *last = now;
+            actions.push((symbol.clone(), silent, action));
+        }
+        actions.sort_by(|a, b| a.0.cmp(&b.0));
+        actions
+    }
+}
+
+// Watchdog cho các feed một symbol / một kết nối: im lặng quá lâu thì yêu cầu connector
+// kết nối lại (mọi subscription được gửi lại sau mỗi lần connect). Feed không có
+// `connection()` (replay, dex poll) thì bỏ qua
+pub async fn watch_feeds(feeds: Vec<Arc<dyn OrderbookFeed>>, settings: StaleSettings) {
+    let feeds: Vec<_> = feeds.into_iter().filter(|f| f.connection().is_some()).collect();
+    let keys: Vec<String> = feeds.iter().map(|f| format!("{}:{}", f.exchange(), f.symbol())).collect();
+    let mut tracker = StaleTracker::new(settings.silence(), keys.iter().cloned());
+    // (update id, timestamp) lần check trước để biết book có đổi không
+    let mut seen: Vec<_> = feeds
+        .iter()
+        .map(|f| {
+            let snap = f.snapshot();
+            (snap.last_update_id, snap.timestamp)
+        })
+        .collect();
+
+    let mut tick = interval(Duration::from_secs(settings.check_secs));
+    tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        tick.tick().await;
+        for ((feed, key), seen) in feeds.iter().zip(&keys).zip(&mut seen) {
+            let snap = feed.snapshot();
+            let current = (snap.last_update_id, snap.timestamp);
+            // chưa kết nối thì đã có reconnect lo, không tính là im lặng
+            if current != *seen || !feed.connection().is_some_and(|c| c.state().is_connected()) {
+                *seen = current;
+                tracker.touch(key);
+            }
+        }
+        for (key, silent, action) in tracker.check() {
+            let Some(feed) = keys.iter().position(|k| *k == key).map(|i| &feeds[i]) else {
+                continue;
+            };
+            warn!(
+                target: "feed_health",
+                exchange = feed.exchange(),
+                symbol = feed.symbol(),
+                silent_ms = silent.as_millis() as u64,
+                ?action,
+                "symbol silent, resubscribing"
+            );
+            if let Some(connection) = feed.connection() {
+                connection.request_resubscribe();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_resubscribe_then_reconnect() {
+        let mut tracker = StaleTracker::new(Duration::from_secs(10), ["btcusdt".to_string(), "ethusdt".to_string()]);
+        tokio::time::advance(Duration::from_secs(6)).await;
+        tracker.touch("btcusdt");
+        assert!(tracker.check().is_empty());
+
+        tokio::time::advance(Duration::from_secs(5)).await;
+        assert_eq!(tracker.check(), vec![("ethusdt".to_string(), Duration::from_secs(11), StaleAction::Resubscribe)]);
+        // eth vừa được tính lại nên chưa tới lượt, btc thì đủ 10s
+        tokio::time::advance(Duration::from_secs(5)).await;
+        assert_eq!(tracker.check().iter().map(|a| (a.0.as_str(), a.2)).collect::<Vec<_>>(), vec![("btcusdt", StaleAction::Resubscribe)]);
+
+        // eth vẫn im lặng sau khi resubscribe -> reconnect, btc có update lại
+        tracker.touch("btcusdt");
+        tokio::time::advance(Duration::from_secs(6)).await;
+        assert_eq!(tracker.check(), vec![("ethusdt".to_string(), Duration::from_secs(11), StaleAction::Reconnect)]);
+
+        tracker.reset();
+        tokio::time::advance(Duration::from_secs(9)).await;
+        assert!(tracker.check().is_empty());
+        assert!(StaleSettings { enabled: true, check_secs: 60, ..Default::default() }.validate().len() == 1);
+    }
+}
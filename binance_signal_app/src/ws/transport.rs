@@ -0,0 +1,155 @@
+use serde::Deserialize;
+use std::{
+    io,
+    pin::Pin,
+    sync::OnceLock,
+    task::{Context, Poll},
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::TcpStream,
+};
+use tokio_rustls::{client::TlsStream, rustls::ServerName};
+use tokio_tungstenite::{
+    client_async,
+    tungstenite::{self, client::IntoClientRequest, handshake::client::Response, http::HeaderValue},
+    WebSocketStream,
+};
+
+use super::deflate::{self, InflateStream};
+use crate::net;
+
+// Cấu hình chung cho mọi kết nối WS tới sàn:
+//
+//   [ws]
+//   compression = true
+//   # CPU là nút cổ chai thì tắt riêng sàn đó
+//   compression_exclude = ["binance"]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct WsSettings {
+    // đề nghị permessage-deflate khi handshake, server không nhận thì như cũ
+    pub compression: bool,
+    // tên sàn như `exchange()` của feed
+    pub compression_exclude: Vec<String>,
+}
+
+impl Default for WsSettings {
+    fn default() -> Self {
+        Self { compression: true, compression_exclude: Vec::new() }
+    }
+}
+
+impl WsSettings {
+    pub fn compression_for(&self, venue: &str) -> bool {
+        self.compression && !self.compression_exclude.iter().any(|v| v.eq_ignore_ascii_case(venue))
+    }
+}
+
+static SETTINGS: OnceLock<WsSettings> = OnceLock::new();
+
+// gọi một lần lúc khởi động trước khi start feed, không gọi thì dùng default
+pub fn configure(settings: &WsSettings) {
+    let _ = SETTINGS.set(settings.clone());
+}
+
+fn settings() -> &'static WsSettings {
+    SETTINGS.get_or_init(WsSettings::default)
+}
+
+pub enum Transport {
+    Plain(TcpStream),
+    Tls(Box<TlsStream<TcpStream>>),
+}
+
+pub type WsStream = WebSocketStream<InflateStream<Transport>>;
+
+// Thay cho `connect_async`: TCP (proxy / resolve theo `[network]`), TLS (wss), đề nghị permessage-deflate theo `[ws]`
+// rồi handshake. `venue` để tắt nén theo sàn và gom byte vào `deflate::snapshot()`
+pub async fn connect<R: IntoClientRequest>(venue: &'static str, request: R) -> Result<(WsStream, Response), tungstenite::Error> {
+    let mut request = request.into_client_request()?;
+    if settings().compression_for(venue) {
+        request.headers_mut().insert("Sec-WebSocket-Extensions", HeaderValue::from_static(deflate::OFFER));
+    }
+    let uri = request.uri();
+    let tls = uri.scheme_str() == Some("wss");
+    let host = uri.host().ok_or(tungstenite::Error::Url(tungstenite::error::UrlError::NoHostName))?.to_string();
+    let port = uri.port_u16().unwrap_or(if tls { 443 } else { 80 });
+
+    let network = net::network();
+    let tcp = network.connect_tcp(&host, port).await?;
+    tcp.set_nodelay(true)?;
+    let transport = if tls {
+        let name = ServerName::try_from(host.as_str()).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        Transport::Tls(Box::new(network.tls().connect(name, tcp).await?))
+    } else {
+        Transport::Plain(tcp)
+    };
+    client_async(request, InflateStream::new(transport, deflate::stats(venue))).await
+}
+
+impl AsyncRead for Transport {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Transport::Plain(s) => Pin::new(s).poll_read(cx, buf),
+            Transport::Tls(s) => Pin::new(s.as_mut()).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for Transport {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Transport::Plain(s) => Pin::new(s).poll_write(cx, buf),
+            Transport::Tls(s) => Pin::new(s.as_mut()).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Transport::Plain(s) => Pin::new(s).poll_flush(cx),
+            Transport::Tls(s) => Pin::new(s.as_mut()).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Transport::Plain(s) => Pin::new(s).poll_shutdown(cx),
+            Transport::Tls(s) => Pin::new(s.as_mut()).poll_shutdown(cx),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::{SinkExt, StreamExt};
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::tungstenite::Message;
+
+    #[tokio::test]
+    async fn test_connect_plain_without_negotiated_compression() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            // server không nhận extension -> frame đi nguyên
+            let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            ws.send(Message::Text("hello".into())).await.unwrap();
+            ws.next().await
+        });
+
+        let (mut ws, response) = connect("transport_test", format!("ws://{}/ws", addr)).await.unwrap();
+        assert!(response.headers().get("Sec-WebSocket-Extensions").is_none());
+        assert_eq!(ws.next().await.unwrap().unwrap(), Message::Text("hello".into()));
+        ws.send(Message::Text("sub".into())).await.unwrap();
+        assert_eq!(server.await.unwrap().unwrap().unwrap(), Message::Text("sub".into()));
+
+        let stats = deflate::snapshot().into_iter().find(|s| s.venue == "transport_test").unwrap();
+        assert_eq!((stats.negotiated, stats.compressed_bytes), (0, 0));
+        assert!(stats.plain_bytes > 0);
+
+        let settings = WsSettings { compression_exclude: vec!["Binance".into()], ..Default::default() };
+        assert_eq!((settings.compression_for("binance"), settings.compression_for("okx")), (false, true));
+    }
+}